@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+
+/// Defines when an enemy ability is used
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "type")]
+pub enum EnemyAbilityTrigger {
+    EveryNRounds { rounds: i16 }, // Used on every round that is a multiple of rounds
+    OnRound { round: i16 },       // Used once on the given round
+    HpBelow { hp_fraction: f64 }, // Used once the first time the enemy's hp drops below the given fraction of its max hp
+}
+
+/// Defines what an enemy ability does when used
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "type")]
+pub enum EnemyAbilityEffect {
+    ArmorShred {
+        defense_reduction: f64,
+    }, // Reduces the defense of every living hero by the given fraction for the rest of the fight
+    Slam {
+        damage_multiplier: f64,
+        max_targets: u8,
+    }, // Attacks up to max_targets random living heroes for a multiple of the enemy's normal attack damage
+    SelfHeal {
+        hp_fraction: f64,
+    }, // Restores the given fraction of the enemy's max hp
+}
+
+/// A special attack or effect used by an enemy on a schedule or trigger, in addition to its regular attack
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EnemyAbility {
+    name: String,
+    trigger: EnemyAbilityTrigger,
+    effect: EnemyAbilityEffect,
+    #[serde(default, skip_serializing)]
+    has_triggered: bool, // Whether a one-time trigger has already been used this fight
+}
+
+impl EnemyAbility {
+    pub fn get_name(&self) -> String {
+        return self.name.to_string();
+    }
+
+    pub fn get_trigger(&self) -> EnemyAbilityTrigger {
+        return self.trigger;
+    }
+
+    pub fn get_effect(&self) -> EnemyAbilityEffect {
+        return self.effect;
+    }
+
+    /// Check whether the ability should be used this round, marking one-time triggers as used
+    pub fn check_trigger(&mut self, round: i16, hp: f64, hp_max: f64) -> bool {
+        match self.trigger {
+            EnemyAbilityTrigger::EveryNRounds { rounds } => {
+                return round % rounds == 0;
+            }
+            EnemyAbilityTrigger::OnRound {
+                round: trigger_round,
+            } => {
+                return round == trigger_round;
+            }
+            EnemyAbilityTrigger::HpBelow { hp_fraction } => {
+                if !self.has_triggered && hp > 0.0 && hp < hp_fraction * hp_max {
+                    self.has_triggered = true;
+                    return true;
+                }
+                return false;
+            }
+        }
+    }
+}
+
+/// Create an enemy ability performing type validation
+pub fn create_enemy_ability(
+    name: String,
+    trigger: EnemyAbilityTrigger,
+    effect: EnemyAbilityEffect,
+) -> Result<EnemyAbility, &'static str> {
+    match trigger {
+        EnemyAbilityTrigger::EveryNRounds { rounds } if rounds < 1 => {
+            return Err("EveryNRounds trigger rounds must be > 0");
+        }
+        EnemyAbilityTrigger::OnRound { round } if round < 1 => {
+            return Err("OnRound trigger round must be > 0");
+        }
+        EnemyAbilityTrigger::HpBelow { hp_fraction } if !(0.0..=1.0).contains(&hp_fraction) => {
+            return Err("HpBelow trigger hp_fraction must be within range 0.0-1.0 inclusive");
+        }
+        _ => (),
+    }
+
+    match effect {
+        EnemyAbilityEffect::ArmorShred { defense_reduction }
+            if !(0.0..=1.0).contains(&defense_reduction) =>
+        {
+            return Err("ArmorShred defense_reduction must be within range 0.0-1.0 inclusive");
+        }
+        EnemyAbilityEffect::Slam {
+            damage_multiplier,
+            max_targets,
+        } if damage_multiplier < 0.0 || max_targets < 1 => {
+            return Err("Slam damage_multiplier must be >= 0 and max_targets must be > 0");
+        }
+        EnemyAbilityEffect::SelfHeal { hp_fraction } if hp_fraction < 0.0 => {
+            return Err("SelfHeal hp_fraction must be >= 0");
+        }
+        _ => (),
+    }
+
+    return Ok(EnemyAbility {
+        name,
+        trigger,
+        effect,
+        has_triggered: false,
+    });
+}
+
+/// Defines what happens to a boss once it enrages
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "type")]
+pub enum EnrageEffect {
+    DamageMultiplier { multiplier: f64 }, // The boss's damage is multiplied for the rest of the fight
+    Wipe,                                 // The boss instantly defeats the entire party
+}
+
+/// An enrage timer that punishes fights lasting longer than a number of rounds
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Enrage {
+    rounds: i16, // The boss enrages at the start of the round after this many rounds have elapsed
+    effect: EnrageEffect,
+}
+
+impl Enrage {
+    pub fn get_rounds(&self) -> i16 {
+        return self.rounds;
+    }
+
+    pub fn get_effect(&self) -> EnrageEffect {
+        return self.effect;
+    }
+
+    /// Returns the enrage effect if the boss enrages at the start of this round
+    pub fn check_enrage(&self, round: i16) -> Option<EnrageEffect> {
+        if round == self.rounds + 1 {
+            return Some(self.effect);
+        }
+        return None;
+    }
+}
+
+/// Create an enrage timer performing type validation
+pub fn create_enrage(rounds: i16, effect: EnrageEffect) -> Result<Enrage, &'static str> {
+    if rounds < 1 {
+        return Err("enrage rounds must be > 0");
+    }
+    if let EnrageEffect::DamageMultiplier { multiplier } = effect {
+        if multiplier < 1.0 {
+            return Err("enrage damage multiplier must be >= 1.0");
+        }
+    }
+    return Ok(Enrage { rounds, effect });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_self_heal(trigger: EnemyAbilityTrigger) -> EnemyAbility {
+        return create_enemy_ability(
+            String::from("Test Heal"),
+            trigger,
+            EnemyAbilityEffect::SelfHeal { hp_fraction: 0.1 },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn scheduled_abilities_trigger_on_their_rounds() {
+        let mut every_third = create_self_heal(EnemyAbilityTrigger::EveryNRounds { rounds: 3 });
+        let mut on_second = create_self_heal(EnemyAbilityTrigger::OnRound { round: 2 });
+
+        let every_third_rounds: Vec<i16> = (1..=9)
+            .filter(|round| every_third.check_trigger(*round, 100.0, 100.0))
+            .collect();
+        let on_second_rounds: Vec<i16> = (1..=9)
+            .filter(|round| on_second.check_trigger(*round, 100.0, 100.0))
+            .collect();
+
+        assert_eq!(every_third_rounds, vec![3, 6, 9]);
+        assert_eq!(on_second_rounds, vec![2]);
+    }
+
+    #[test]
+    fn hp_below_abilities_trigger_once() {
+        let mut ability = create_self_heal(EnemyAbilityTrigger::HpBelow { hp_fraction: 0.5 });
+
+        assert!(!ability.check_trigger(1, 60.0, 100.0));
+        assert!(ability.check_trigger(2, 40.0, 100.0));
+        assert!(!ability.check_trigger(3, 30.0, 100.0));
+    }
+
+    #[test]
+    fn invalid_abilities_and_enrages_are_rejected() {
+        assert!(create_enemy_ability(
+            String::from("Test Slam"),
+            EnemyAbilityTrigger::EveryNRounds { rounds: 0 },
+            EnemyAbilityEffect::Slam {
+                damage_multiplier: 2.0,
+                max_targets: 1,
+            },
+        )
+        .is_err());
+        assert!(create_enemy_ability(
+            String::from("Test Shred"),
+            EnemyAbilityTrigger::OnRound { round: 1 },
+            EnemyAbilityEffect::ArmorShred {
+                defense_reduction: 1.5,
+            },
+        )
+        .is_err());
+        assert!(create_enrage(5, EnrageEffect::DamageMultiplier { multiplier: 0.5 }).is_err());
+
+        let enrage = create_enrage(5, EnrageEffect::Wipe).unwrap();
+        assert_eq!(enrage.check_enrage(5), None);
+        assert_eq!(enrage.check_enrage(6), Some(EnrageEffect::Wipe));
+    }
+}
@@ -0,0 +1,92 @@
+use std::io::{Read, Write};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::inputs::HeroInput;
+
+/// The version byte prefixed to every build code, bump when the encoded HeroInput layout changes
+/// Version 2 encodes fields by name, so fields skipped when writing (such as skill tiers) don't shift the fields after them
+const BUILD_CODE_VERSION: u8 = 2;
+
+/// Encode a hero build as a compact, url-safe string that can be shared and imported with decode_build_code
+/// The code is base64 of a version byte followed by the deflate compressed MessagePack map of the hero's builder input
+pub fn encode_build_code(hero: &HeroInput) -> String {
+    let payload = rmp_serde::to_vec_named(hero).unwrap();
+    let mut encoder = DeflateEncoder::new(vec![BUILD_CODE_VERSION], Compression::best());
+    encoder.write_all(&payload).unwrap();
+    return URL_SAFE_NO_PAD.encode(encoder.finish().unwrap());
+}
+
+/// Decode a build code created by encode_build_code back into a hero's builder input
+pub fn decode_build_code(code: &str) -> Result<HeroInput, &'static str> {
+    let bytes = match URL_SAFE_NO_PAD.decode(code.trim()) {
+        Ok(bytes) => bytes,
+        Err(_) => return Err("build code is not valid base64"),
+    };
+    let (version, compressed) = match bytes.split_first() {
+        Some(split) => split,
+        None => return Err("build code is empty"),
+    };
+    if *version != BUILD_CODE_VERSION {
+        return Err("build code version is not supported by this version of the simulator");
+    }
+
+    let mut payload = vec![];
+    if DeflateDecoder::new(compressed)
+        .read_to_end(&mut payload)
+        .is_err()
+    {
+        return Err("build code is corrupted");
+    }
+    return match rmp_serde::from_slice(&payload) {
+        Ok(hero) => Ok(hero),
+        Err(_) => Err("build code does not contain a valid hero"),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inputs::create_hero_input;
+
+    fn create_test_hero_input() -> HeroInput {
+        return create_hero_input(
+            String::from("Test_Lord"),
+            String::from("Lord"),
+            37,
+            3,
+            String::from("Light"),
+            40,
+            40,
+            40,
+            [
+                String::from("God of War"),
+                String::from("Divine Form"),
+                String::from("Empty"),
+                String::from("Empty"),
+            ],
+            std::array::from_fn(|slot| f!("Item {}", slot + 1)),
+            std::array::from_fn(|_| String::from("Epic")),
+            std::array::from_fn(|_| String::from("Light 3")),
+            std::array::from_fn(|_| String::from("Mammoth T9")),
+        );
+    }
+
+    #[test]
+    fn build_code_round_trips() {
+        let hero = create_test_hero_input();
+        assert_eq!(decode_build_code(&encode_build_code(&hero)), Ok(hero));
+    }
+
+    #[test]
+    fn build_code_of_another_version_is_rejected() {
+        let mut bytes = URL_SAFE_NO_PAD
+            .decode(encode_build_code(&create_test_hero_input()))
+            .unwrap();
+        bytes[0] = BUILD_CODE_VERSION - 1;
+        assert!(decode_build_code(&URL_SAFE_NO_PAD.encode(bytes)).is_err());
+    }
+}
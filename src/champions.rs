@@ -0,0 +1,429 @@
+use std::{collections::HashMap, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    equipment::ElementType,
+    formatting::{create_stats_view, StatsView},
+    hero_builder::{_create_hero_class, HeroClass},
+    heroes::HeroArchetype,
+};
+
+/// Defines the champions that can lead a team, each with a unique ability scaled by their innate tier
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    strum::Display,
+    strum::EnumString,
+    strum::EnumIter,
+)]
+pub enum Champion {
+    Argon,   // Increases the attack and defense of every hero
+    Ashley,  // Increases the attack and defense of every hero, doubled against bosses
+    Donovan, // Increases attack per spellcaster, hp per fighter, and crit chance and evasion per rogue
+    Hemma,   // Increases hp, and drains hp from healthy allies each round to grow her attack
+    Lilu,    // Increases hp, and heals every living hero at the end of each round
+    Polonia, // Increases defense and evasion, and steals loot from the enemy each round
+    Rudo,    // Increases the break chance of every hero for the first few rounds
+    Sia,     // Increases the attack of every hero
+    Yami,    // Increases the crit chance and evasion of every hero
+    Aang,    // Not yet modeled
+    Sokka,   // Not yet modeled
+    #[strum(serialize = "King Reinholdt")]
+    #[serde(rename = "King Reinholdt")]
+    KingReinholdt, // Not yet modeled
+}
+
+/// The base stats and ability parameters of a champion, loaded from champions.yaml
+/// Ability parameters are listed per innate tier from 1 to 4, and any that are omitted have no effect
+/// Champions with base stats, an element type, and the equipment allowed in each slot can also be built in hero_builder.csv like a class
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ChampionData {
+    innate_tier_ranks: [u8; 3], // The minimum rank for each innate tier after the first, in tier order
+    base_hp: Vec<f64>,          // Base stats for each level, starting from level 1
+    base_atk: Vec<f64>,
+    base_def: Vec<f64>,
+    base_eva: f64,
+    base_crit_chance: f64,
+    base_crit_mult: f64,
+    base_threat_rating: u16,
+    element_type: String,                // Empty if the champion can't be built
+    equipment_allowed: [Vec<String>; 6], // The item types allowed in each slot, all empty if the champion can't be built
+    attack_bonus: [f64; 4],              // % added to every hero's attack
+    defense_bonus: [f64; 4],             // % added to every hero's defense
+    boss_bonus_multiplier: f64,          // Multiplies the attack and defense bonuses against bosses
+    hp_bonus: [f64; 4],                  // % added to every hero's hp
+    crit_chance_bonus: [f64; 4],
+    evasion_bonus: [f64; 4],
+    attack_bonus_per_spellcaster: [f64; 4],
+    hp_bonus_per_fighter: [f64; 4],
+    evasion_bonus_per_rogue: [f64; 4],
+    mercenary_multiplier: f64, // Mercenaries multiply every stat the champion increases by this
+    crit_chance_aura_per_rogue: [f64; 4], // Added to every hero's crit rolls rather than to their stats
+    round_heal: [f64; 4], // Hp restored to every living hero at the end of each round
+    round_heal_hp_max_fraction: [f64; 4], // The share of the champion's max hp added to the round heal
+    break_chance_bonus: [f64; 4],
+    break_chance_rounds: [i16; 4], // The last round the break chance bonus applies in
+    loot_chance: [f64; 4],         // The chance to steal loot on each non-critical hit
+    loot_chance_per_trickster: f64,
+    loot_cap: u8, // The most loot that can be stolen in a fight, the champion steals no loot if 0
+    loot_cap_per_trickster: u8,
+    drain_hp_fraction: [f64; 4], // The share of max hp drained each round from every ally with more hp left than that
+    drain_attack_fraction: [f64; 4], // The share of the champion's attack gained for each ally drained
+    drain_self_heal: [f64; 4],       // Hp the champion restores each round after draining
+}
+
+/// The data of a champion missing from champions.yaml, whose ability has no effect
+pub(crate) static NO_CHAMPION_DATA: ChampionData = ChampionData {
+    innate_tier_ranks: [4, 7, 11],
+    base_hp: Vec::new(),
+    base_atk: Vec::new(),
+    base_def: Vec::new(),
+    base_eva: 0.0,
+    base_crit_chance: 0.05,
+    base_crit_mult: 2.0,
+    base_threat_rating: 10,
+    element_type: String::new(),
+    equipment_allowed: [
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+    ],
+    attack_bonus: [0.0; 4],
+    defense_bonus: [0.0; 4],
+    boss_bonus_multiplier: 1.0,
+    hp_bonus: [0.0; 4],
+    crit_chance_bonus: [0.0; 4],
+    evasion_bonus: [0.0; 4],
+    attack_bonus_per_spellcaster: [0.0; 4],
+    hp_bonus_per_fighter: [0.0; 4],
+    evasion_bonus_per_rogue: [0.0; 4],
+    mercenary_multiplier: 1.25,
+    crit_chance_aura_per_rogue: [0.0; 4],
+    round_heal: [0.0; 4],
+    round_heal_hp_max_fraction: [0.0; 4],
+    break_chance_bonus: [0.0; 4],
+    break_chance_rounds: [0; 4],
+    loot_chance: [0.0; 4],
+    loot_chance_per_trickster: 0.0,
+    loot_cap: 0,
+    loot_cap_per_trickster: 0,
+    drain_hp_fraction: [0.0; 4],
+    drain_attack_fraction: [0.0; 4],
+    drain_self_heal: [0.0; 4],
+};
+
+impl Default for ChampionData {
+    fn default() -> Self {
+        return NO_CHAMPION_DATA.clone();
+    }
+}
+
+/// Returns the index of an innate tier from 1 to 4 into the per tier ability parameters
+fn tier_index(innate_tier: u8) -> usize {
+    return (innate_tier.clamp(1, 4) - 1) as usize;
+}
+
+impl ChampionData {
+    /// Returns every problem with the data, such as ranks out of order or negative bonuses
+    pub fn get_validation_errors(&self) -> Vec<String> {
+        let mut errors: Vec<String> = vec![];
+        if !self
+            .innate_tier_ranks
+            .windows(2)
+            .all(|ranks| ranks[0] < ranks[1])
+        {
+            errors.push(String::from("innate_tier_ranks must be increasing"));
+        }
+        if self.base_hp.len() != self.base_atk.len() || self.base_hp.len() != self.base_def.len() {
+            errors.push(String::from(
+                "base_hp, base_atk, and base_def must list the same number of levels",
+            ));
+        }
+        let per_tier = [
+            ("attack_bonus", self.attack_bonus),
+            ("defense_bonus", self.defense_bonus),
+            ("hp_bonus", self.hp_bonus),
+            ("crit_chance_bonus", self.crit_chance_bonus),
+            ("evasion_bonus", self.evasion_bonus),
+            (
+                "attack_bonus_per_spellcaster",
+                self.attack_bonus_per_spellcaster,
+            ),
+            ("hp_bonus_per_fighter", self.hp_bonus_per_fighter),
+            ("evasion_bonus_per_rogue", self.evasion_bonus_per_rogue),
+            (
+                "crit_chance_aura_per_rogue",
+                self.crit_chance_aura_per_rogue,
+            ),
+            ("round_heal", self.round_heal),
+            ("break_chance_bonus", self.break_chance_bonus),
+            ("drain_attack_fraction", self.drain_attack_fraction),
+            ("drain_self_heal", self.drain_self_heal),
+        ];
+        for (name, values) in per_tier {
+            if values.iter().any(|value| *value < 0.0) {
+                errors.push(f!("{} must be >= 0", name));
+            }
+        }
+        let fractions = [
+            (
+                "round_heal_hp_max_fraction",
+                self.round_heal_hp_max_fraction,
+            ),
+            ("loot_chance", self.loot_chance),
+            ("drain_hp_fraction", self.drain_hp_fraction),
+        ];
+        for (name, values) in fractions {
+            if values.iter().any(|value| !(0.0..=1.0).contains(value)) {
+                errors.push(f!("{} must be within range 0-1 inclusive", name));
+            }
+        }
+        if !self.element_type.is_empty() && ElementType::from_str(&self.element_type).is_err() {
+            errors.push(f!("unknown element_type {}", self.element_type));
+        }
+        if self
+            .equipment_allowed
+            .iter()
+            .any(|allowed| allowed.is_empty())
+            && self
+                .equipment_allowed
+                .iter()
+                .any(|allowed| !allowed.is_empty())
+        {
+            errors.push(String::from(
+                "equipment_allowed must list item types for every slot or for none",
+            ));
+        }
+        if self.boss_bonus_multiplier < 0.0 || self.mercenary_multiplier < 0.0 {
+            errors.push(String::from(
+                "boss_bonus_multiplier and mercenary_multiplier must be >= 0",
+            ));
+        }
+        return errors;
+    }
+
+    /// Returns the (hp, attack, defense) base stats at the given level, if champions.yaml lists them
+    pub fn get_base_stats(&self, level: u8) -> Option<(f64, f64, f64)> {
+        let i = (level as usize).checked_sub(1)?;
+        return Some((
+            *self.base_hp.get(i)?,
+            *self.base_atk.get(i)?,
+            *self.base_def.get(i)?,
+        ));
+    }
+
+    /// Returns the class heroes of the champion are built as, or None if champions.yaml lacks its base stats, element type, or equipment allowed
+    /// Champions reach innate tiers by rank and have no skill slots, so the class lists no innate skills
+    pub fn create_hero_class(&self, champion: Champion) -> Option<HeroClass> {
+        if self.base_hp.is_empty()
+            || self.element_type.is_empty()
+            || self
+                .equipment_allowed
+                .iter()
+                .any(|allowed| allowed.is_empty())
+        {
+            return None;
+        }
+        return Some(_create_hero_class(
+            champion.to_string(),
+            String::from("Champion"),
+            0,
+            0,
+            self.base_hp.clone(),
+            self.base_atk.clone(),
+            self.base_def.clone(),
+            self.base_eva,
+            self.base_crit_chance,
+            self.base_crit_mult,
+            self.base_threat_rating,
+            self.element_type.to_string(),
+            self.equipment_allowed.clone(),
+            vec![],
+            Default::default(),
+            false,
+            Some(HeroArchetype::Champion),
+        ));
+    }
+
+    /// Returns the (attack %, defense %) bonuses given to every hero, with attack growing per spellcaster in the team
+    pub fn get_attack_and_defense_bonus(
+        &self,
+        innate_tier: u8,
+        num_spellcasters: u8,
+        is_boss: bool,
+    ) -> (f64, f64) {
+        let i = tier_index(innate_tier);
+        let multiplier = if is_boss {
+            self.boss_bonus_multiplier
+        } else {
+            1.0
+        };
+        return (
+            (self.attack_bonus[i]
+                + self.attack_bonus_per_spellcaster[i] * f64::from(num_spellcasters))
+                * multiplier,
+            self.defense_bonus[i] * multiplier,
+        );
+    }
+
+    /// Returns the (hp %, crit chance, evasion) bonuses given to every hero, with hp growing per fighter and evasion per rogue in the team
+    pub fn get_hero_bonuses(
+        &self,
+        innate_tier: u8,
+        num_fighters: u8,
+        num_rogues: u8,
+    ) -> (f64, f64, f64) {
+        let i = tier_index(innate_tier);
+        return (
+            self.hp_bonus[i] + self.hp_bonus_per_fighter[i] * f64::from(num_fighters),
+            self.crit_chance_bonus[i],
+            self.evasion_bonus[i] + self.evasion_bonus_per_rogue[i] * f64::from(num_rogues),
+        );
+    }
+
+    /// Returns whether the champion increases each of (hp, crit chance, evasion) at the given innate tier, whatever the team makeup
+    pub fn get_boosted_stats(&self, innate_tier: u8) -> (bool, bool, bool) {
+        let i = tier_index(innate_tier);
+        return (
+            self.hp_bonus[i] != 0.0 || self.hp_bonus_per_fighter[i] != 0.0,
+            self.crit_chance_bonus[i] != 0.0,
+            self.evasion_bonus[i] != 0.0 || self.evasion_bonus_per_rogue[i] != 0.0,
+        );
+    }
+
+    pub fn get_mercenary_multiplier(&self) -> f64 {
+        return self.mercenary_multiplier;
+    }
+
+    /// Returns the (hp fraction drained, attack fraction gained, self heal) of each ally drained per round
+    pub fn get_drain(&self, innate_tier: u8) -> (f64, f64, f64) {
+        let i = tier_index(innate_tier);
+        return (
+            self.drain_hp_fraction[i],
+            self.drain_attack_fraction[i],
+            self.drain_self_heal[i],
+        );
+    }
+
+    /// Returns the innate tier, from 1 to 4, of the champion at the given rank
+    pub fn calculate_innate_tier(&self, rank: u8) -> u8 {
+        return 1 + self
+            .innate_tier_ranks
+            .iter()
+            .filter(|tier_rank| rank >= **tier_rank)
+            .count() as u8;
+    }
+
+    /// Returns the hp restored to every living hero at the end of each round, given the champion's own max hp
+    /// Lilu heals a flat amount plus a share of her max hp, both growing with her innate tier
+    pub fn get_round_heal(&self, innate_tier: u8, champion_hp_max: f64) -> f64 {
+        let i = tier_index(innate_tier);
+        return self.round_heal[i] + self.round_heal_hp_max_fraction[i] * champion_hp_max;
+    }
+
+    /// Returns the bonus to every hero's break chance during the given round, starting from round 1
+    pub fn get_break_chance_bonus(&self, innate_tier: u8, round: i16) -> f64 {
+        let i = tier_index(innate_tier);
+        if round > self.break_chance_rounds[i] {
+            return 0.0;
+        }
+        return self.break_chance_bonus[i];
+    }
+
+    /// Returns the bonus to every hero's crit chance, added to each of their crit rolls rather than to their stats
+    /// Donovan's aura grows with his innate tier and the number of rogues in the team
+    pub fn get_crit_chance_aura(&self, innate_tier: u8, num_rogues: u8) -> f64 {
+        return self.crit_chance_aura_per_rogue[tier_index(innate_tier)] * f64::from(num_rogues);
+    }
+
+    /// Returns the chance to steal loot each round and the most loot that can be stolen in a fight, if the champion steals loot
+    pub fn get_loot_stealing(&self, innate_tier: u8, num_tricksters: u8) -> Option<(f64, u8)> {
+        if self.loot_cap == 0 {
+            return None;
+        }
+        return Some((
+            self.loot_chance[tier_index(innate_tier)]
+                + f64::from(num_tricksters) * self.loot_chance_per_trickster,
+            self.loot_cap + num_tricksters * self.loot_cap_per_trickster,
+        ));
+    }
+}
+
+/// Returns a champion's data, or data with no effect if the champion is missing from champions.yaml
+pub fn find_champion_data(
+    champion_data: &HashMap<Champion, ChampionData>,
+    champion: Champion,
+) -> &ChampionData {
+    return champion_data.get(&champion).unwrap_or(&NO_CHAMPION_DATA);
+}
+
+impl Champion {
+    /// Returns whether the champion's ability is simulated, champions that are not only count towards the team's size
+    pub fn is_modeled(&self) -> bool {
+        return !matches!(
+            self,
+            Champion::Aang | Champion::Sokka | Champion::KingReinholdt
+        );
+    }
+
+    /// Format a card of the champion's base stats at the given level and ability at the innate tier reached by the given rank
+    pub fn fmt_card(&self, data: &ChampionData, level: u8, rank: u8) -> StatsView {
+        let innate_tier = data.calculate_innate_tier(rank);
+        let i = tier_index(innate_tier);
+        let mut view = create_stats_view(f!("{} (level {}, rank {})", self, level, rank))
+            .text("Innate Tier", innate_tier);
+        if let Some((hp, attack, defense)) = data.get_base_stats(level) {
+            view = view
+                .float("Base HP", hp)
+                .float("Base Attack", attack)
+                .float("Base Defense", defense);
+        }
+        if !self.is_modeled() {
+            return view.text("Ability", "not simulated");
+        }
+        let parameters = [
+            ("Attack Bonus", data.attack_bonus[i]),
+            ("Defense Bonus", data.defense_bonus[i]),
+            ("HP Bonus", data.hp_bonus[i]),
+            ("Crit Chance Bonus", data.crit_chance_bonus[i]),
+            ("Evasion Bonus", data.evasion_bonus[i]),
+            ("Attack / Spellcaster", data.attack_bonus_per_spellcaster[i]),
+            ("HP / Fighter", data.hp_bonus_per_fighter[i]),
+            ("Evasion / Rogue", data.evasion_bonus_per_rogue[i]),
+            ("Crit Aura / Rogue", data.crit_chance_aura_per_rogue[i]),
+            ("Round Heal", data.round_heal[i]),
+            ("Round Heal / Max HP", data.round_heal_hp_max_fraction[i]),
+            ("Break Chance Bonus", data.break_chance_bonus[i]),
+            ("Loot Chance", data.loot_chance[i]),
+            ("Drain / Max HP", data.drain_hp_fraction[i]),
+            ("Drain Attack", data.drain_attack_fraction[i]),
+            ("Drain Self Heal", data.drain_self_heal[i]),
+        ];
+        for (label, value) in parameters {
+            if value != 0.0 {
+                view = view.float(label, value);
+            }
+        }
+        if data.break_chance_bonus[i] != 0.0 {
+            view = view.text("Break Chance Rounds", data.break_chance_rounds[i]);
+        }
+        if data.loot_cap > 0 {
+            view = view.text("Loot Cap", data.loot_cap);
+        }
+        if data.boss_bonus_multiplier != 1.0 {
+            view = view.float("Boss Multiplier", data.boss_bonus_multiplier);
+        }
+        return view;
+    }
+}
@@ -0,0 +1,643 @@
+use std::time::Duration;
+
+use clap::{Args, CommandFactory, Parser, Subcommand};
+
+use crate::errors::{exit_with_error, install_error_reporting, ErrorFormat, ErrorKind};
+use st_sim::champions::Champion;
+use st_sim::heroes::HeroArchetype;
+use st_sim::rng::RngAlgorithm;
+use st_sim::simulations::TargetingModel;
+use st_sim::studies::hero_optimization_study::{parse_budget, OptimizationAxis};
+use st_sim::studies::quality_sweep_study::QualitySweepMode;
+use st_sim::trials::ResultFormat;
+
+/// The command line of the st_sim binary, each command dispatched to its handler in the commands module
+#[derive(Parser, Debug, Clone, PartialEq)]
+#[command(
+    name = "st_sim",
+    about = "Simulate Shop Titans heroes against dungeons and run studies of their builds",
+    after_help = "Without a command, the default Daimyo skill study is run"
+)]
+pub struct Cli {
+    #[command(flatten)]
+    pub options: GlobalOptions,
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+/// Options accepted by every command, before or after it
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct GlobalOptions {
+    /// Report failures as a json object on stderr instead of the standard panic message
+    #[arg(long, global = true, value_name = "human|json")]
+    pub error_format: Option<ErrorFormat>,
+    /// The generator simulations draw from (default chacha)
+    #[arg(long, global = true, value_name = "chacha|xoshiro|pcg")]
+    pub rng: Option<RngAlgorithm>,
+    /// Seed the generator so runs are reproducible
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+    /// Add bootstrapped confidence intervals from this many resamples to trial results
+    #[arg(long, global = true, value_name = "RESAMPLES", default_value_t = 0)]
+    pub bootstrap: usize,
+    /// The confidence level of bootstrapped intervals
+    #[arg(long, global = true, value_name = "LEVEL", default_value_t = 0.95, value_parser = parse_confidence_level)]
+    pub confidence: f64,
+    /// Keep simulating each trial until its success rate's 95% confidence interval is narrower than this
+    #[arg(long, global = true, value_name = "WIDTH", value_parser = parse_interval_width)]
+    pub target_ci_width: Option<f64>,
+    /// The most simulations a trial runs to reach --target-ci-width
+    #[arg(long, global = true, value_name = "QTY", default_value_t = 100000, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub max_sims: usize,
+    /// Load game data from a url mirroring the repository layout
+    #[arg(long, global = true, value_name = "URL")]
+    pub data_url: Option<String>,
+    /// Load game data from another checkout
+    #[arg(long, global = true, value_name = "DIR")]
+    pub data_dir: Option<String>,
+    /// Run a study's trials on this many threads, or 0 for one per core (default 1)
+    #[arg(long, global = true, value_name = "QTY")]
+    pub threads: Option<usize>,
+    /// Keep only this many of the best trials in a study's rankings, so huge sweeps run in constant memory
+    #[arg(long, global = true, value_name = "QTY")]
+    pub ranking_limit: Option<usize>,
+    /// Also save every individual simulation of a study to a compressed csv
+    #[arg(long, global = true)]
+    pub raw_results: bool,
+    /// Also save trial results, raw results, and combat traces as MessagePack
+    #[arg(long, global = true)]
+    pub msgpack: bool,
+    /// Also append every trial result of a study to a cumulative dataset, skipping results it already contains
+    #[arg(long, global = true, value_name = "PATH")]
+    pub dataset: Option<String>,
+    /// How a study saves its trial results, Json writing one object per trial with per-hero detail (default Wide)
+    #[arg(long, global = true, value_name = "Wide|Long|Json")]
+    pub result_format: Option<ResultFormat>,
+    /// Set by "queue run" for the study it runs
+    #[arg(long, global = true, hide = true)]
+    pub queue_id: Option<usize>,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum CliCommand {
+    /// Simulate a party of heroes against a dungeon and print the trial result
+    Simulate(SimulateArgs),
+    /// Run or resume a study
+    Study {
+        #[command(subcommand)]
+        command: StudyCommand,
+    },
+    /// Build heroes through the full gear, skill, element, and spirit pipeline and print each one's stat sheet and SimHero values without simulating,
+    /// to compare line by line with the in-game stat screen
+    Inspect {
+        /// The identifiers of the heroes to inspect, every loaded hero (or every hero of --file) if none are given
+        heroes: Vec<String>,
+        /// A csv with hero_builder.csv's columns to build the heroes from (default input/hero_builder.csv)
+        #[arg(long, value_name = "CSV")]
+        file: Option<String>,
+    },
+    /// Check every input file and data sheet before a long batch, exiting nonzero with a report of all problems found
+    Validate,
+    /// Print a class's per level base stats as yaml for hero_classes.yaml, growing each stat from its level 1 value
+    ClassCurve {
+        /// The level 1 hp and its growth rate
+        #[arg(long, value_name = "BASE,GROWTH", value_parser = parse_growth)]
+        hp: (f64, f64),
+        /// The level 1 attack and its growth rate
+        #[arg(long, value_name = "BASE,GROWTH", value_parser = parse_growth)]
+        atk: (f64, f64),
+        /// The level 1 defense and its growth rate
+        #[arg(long, value_name = "BASE,GROWTH", value_parser = parse_growth)]
+        def: (f64, f64),
+        /// The levels where growth steps up (default 10,21,31)
+        #[arg(long, value_delimiter = ',', value_name = "LEVELS")]
+        breakpoints: Option<Vec<u8>>,
+        /// The last level to print (default 40)
+        #[arg(long, value_name = "LEVEL")]
+        max_level: Option<u8>,
+    },
+    /// Add the hero classes of a csv to hero_classes.yaml, replacing classes of the same name, with base stats given per level or as growth formulas, skipping invalid rows
+    ImportClasses { file: String },
+    /// Print an estimated hero_classes.yaml entry for a new class from the classes of the same archetype and tier
+    ScaffoldClass {
+        class: String,
+        #[arg(long, value_name = "fighter|rogue|spellcaster")]
+        archetype: HeroArchetype,
+        #[arg(long)]
+        tier: u8,
+        /// Estimate a promoted class
+        #[arg(long)]
+        promoted: bool,
+        /// The class's element type
+        #[arg(long)]
+        element: Option<String>,
+        /// Known base stats to use instead of estimates
+        #[arg(long, value_delimiter = ',', value_name = "LEVEL:HP/ATK/DEF", value_parser = parse_known_stats)]
+        stats: Vec<(u8, [f64; 3])>,
+    },
+    /// Queue studies to run one at a time, with higher priorities first
+    Queue {
+        #[command(subcommand)]
+        command: QueueCommand,
+    },
+    /// Check heroes before adding them to hero_builder.csv
+    Hero {
+        #[command(subcommand)]
+        command: HeroCommand,
+    },
+    /// Save, load, and list named rosters of hero builds kept in input/rosters
+    Roster {
+        #[command(subcommand)]
+        command: RosterCommand,
+    },
+    /// Manage the game data the simulator loads
+    Data {
+        #[command(subcommand)]
+        command: DataCommand,
+    },
+    /// Run a small trial twice from the same seed (default 0), serially and across --threads threads (default 4),
+    /// exiting nonzero with the first simulation that differs
+    VerifyDeterminism(DeterminismArgs),
+    /// Quickly simulate a hero in hero_builder.csv against a dungeon
+    Evaluate(EvaluateArgs),
+    /// Compare heroes head to head, a hero against its gear tiers higher, the rankings of two runs, or each build's success rate across difficulty tiers
+    Compare {
+        #[command(subcommand)]
+        command: CompareCommand,
+    },
+    /// Print a card of a hero's final stats broken down by source, its gear, and build warnings
+    ShowHero {
+        /// The hero's identifier in hero_builder.csv
+        hero: String,
+    },
+    /// Print the chance of crafting each quality and the crafts expected to reach it
+    CraftOdds(CraftOddsArgs),
+    /// Print a card of a champion's base stats and ability from champions.yaml
+    ShowChampion(ShowChampionArgs),
+    /// Print a shareable build code for a hero in hero_builder.csv
+    BuildCode {
+        /// The hero's identifier in hero_builder.csv
+        hero: String,
+    },
+    /// Validate a shared build code against the current blueprints and classes and add it to hero_builder.csv
+    ImportBuild { code: String },
+    /// Add every valid hero of a csv with hero_builder.csv's columns to hero_builder.csv, reporting each row that can't be imported
+    ImportHeroes { file: String },
+    /// Search for a better build of a hero in hero_builder.csv, setting each skill, gear, or pet slot to its best candidate in turn
+    Optimize(OptimizeArgs),
+    /// Run a party through every part of a quest line in quest_lines.yaml, reporting where runs fail
+    QuestLine(QuestLineArgs),
+    /// Find how many floors of ever stronger enemies each hero can clear
+    Endless(EndlessArgs),
+    /// Rank every loadout of the candidate items given for at least one slot, keeping the current item in slots without candidates
+    Gear(GearArgs),
+    /// Find the success rate gained by each quality upgrade of each item a hero wears
+    Quality(QualityArgs),
+    /// Rank every team that can be formed from a roster against a quest in quests.yaml
+    Team(TeamArgs),
+    /// Rank every combination of skills for a hero in hero_builder.csv fighting alone
+    Single(SingleArgs),
+    /// Compare quest assignment strategies over a simulated day
+    Schedule,
+    /// Evaluate the Lord/Daimyo duo against each dungeon's barrier elements
+    Coverage,
+    /// Rank Lord builds by their impact on a Daimyo carry
+    Support,
+}
+
+/// The dungeon a command simulates against
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct DungeonArgs {
+    /// The dungeon zone, as named in dungeons.yaml
+    #[arg(long)]
+    pub dungeon: String,
+    /// The difficulty, 1-8
+    #[arg(long, default_value_t = 4, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=8))]
+    pub difficulty: usize,
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct SimulateArgs {
+    /// The identifiers of the party's heroes, in order
+    #[arg(long, value_delimiter = ',', required = true)]
+    pub party: Vec<String>,
+    #[command(flatten)]
+    pub dungeon: DungeonArgs,
+    /// The number of simulations to run
+    #[arg(long, default_value_t = 500)]
+    pub sims: usize,
+    /// How the mob chooses the hero hit by single target attacks (default ThreatWeighted)
+    #[arg(long, value_name = "ThreatWeighted|LowestHp|RoundRobin")]
+    pub targeting: Option<TargetingModel>,
+    /// Save every attack of each simulation (round, attacker, target, damage, crit and evade, hp after) to this csv
+    #[arg(long, value_name = "CSV")]
+    pub trace: Option<String>,
+    /// Only save the traces of lost simulations
+    #[arg(long, requires = "trace")]
+    pub trace_losses: bool,
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct DeterminismArgs {
+    /// The dungeon zone, as named in dungeons.yaml (default the first alphabetically)
+    #[arg(long)]
+    pub dungeon: Option<String>,
+    /// The difficulty, 1-8
+    #[arg(long, default_value_t = 4, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=8))]
+    pub difficulty: usize,
+    /// The identifiers of the party's heroes (default the first heroes alphabetically that fit the dungeon)
+    #[arg(long, value_delimiter = ',')]
+    pub party: Vec<String>,
+    /// The number of simulations to run each way
+    #[arg(long, default_value_t = 200)]
+    pub sims: usize,
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct EvaluateArgs {
+    /// The hero's identifier in hero_builder.csv
+    pub hero: String,
+    #[command(flatten)]
+    pub dungeon: DungeonArgs,
+    /// The identifiers of fixed party members
+    #[arg(long, value_delimiter = ',')]
+    pub party: Vec<String>,
+    /// The number of simulations to run
+    #[arg(long, default_value_t = 200)]
+    pub sims: usize,
+    /// Keep running and re-evaluate the hero whenever its build changes in hero_builder.csv
+    #[arg(long)]
+    pub watch: bool,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum CompareCommand {
+    /// Simulate two heroes in the same party slot against the same dungeon and print their results side by side
+    Hero {
+        hero_a: String,
+        hero_b: String,
+        #[command(flatten)]
+        dungeon: DungeonArgs,
+        /// The identifiers of fixed party members
+        #[arg(long, value_delimiter = ',')]
+        party: Vec<String>,
+        /// The number of simulations to run for each hero
+        #[arg(long, default_value_t = 500)]
+        sims: usize,
+    },
+    /// Simulate a hero in hero_builder.csv as built and with each item swapped for each item of the same type up to --tiers tiers higher
+    Tiers {
+        hero: String,
+        #[command(flatten)]
+        dungeon: DungeonArgs,
+        /// The identifiers of fixed party members
+        #[arg(long, value_delimiter = ',')]
+        party: Vec<String>,
+        /// Only swap the item of this slot, 1-6 (default every slot)
+        #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=6))]
+        slot: Option<usize>,
+        /// How many tiers higher to swap items up to
+        #[arg(long, default_value_t = 1)]
+        tiers: u8,
+        /// The number of simulations to run for each build
+        #[arg(long, default_value_t = 500)]
+        sims: usize,
+    },
+    /// Rank two trial results csvs or study directories by success rate and list how each trial moved
+    Results { run_a: String, run_b: String },
+    /// Fit success rate against difficulty tier for each build of a results csv or study directory, ranking builds by the tier they reach --target at
+    Curves {
+        run: String,
+        /// The success rate builds are ranked by reaching
+        #[arg(long, default_value_t = 0.9, value_parser = parse_target_success_rate)]
+        target: f64,
+    },
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct CraftOddsArgs {
+    /// The fraction worker and furniture bonuses add to quality chances
+    #[arg(long, default_value_t = 0.0)]
+    pub quality_bonus: f64,
+    /// The number of crafts to simulate
+    #[arg(long, default_value_t = 100000)]
+    pub crafts: usize,
+    /// Also report the crafts expected for each item of a hero in hero_builder.csv
+    #[arg(long)]
+    pub hero: Option<String>,
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct ShowChampionArgs {
+    pub champion: Champion,
+    #[arg(long, default_value_t = 1)]
+    pub level: u8,
+    #[arg(long, default_value_t = 1)]
+    pub rank: u8,
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct OptimizeArgs {
+    /// The hero's identifier in hero_builder.csv
+    #[arg(long)]
+    pub hero: String,
+    #[command(flatten)]
+    pub dungeon: DungeonArgs,
+    /// The parts of the build to vary
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "skills,gear",
+        value_name = "skills,gear,pet"
+    )]
+    pub axes: Vec<OptimizationAxis>,
+    /// How long to search for, such as 90s, 30m, or 2h
+    #[arg(long, default_value = "10m", value_parser = parse_budget)]
+    pub budget: Duration,
+    /// The identifiers of fixed party members
+    #[arg(long, value_delimiter = ',')]
+    pub party: Vec<String>,
+    /// The number of simulations to run for each build
+    #[arg(long, default_value_t = 50)]
+    pub sims: i32,
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct QuestLineArgs {
+    /// The quest line's identifier in quest_lines.yaml
+    pub quest_line: String,
+    /// The identifiers of the party's heroes
+    #[arg(long, value_delimiter = ',')]
+    pub party: Vec<String>,
+    /// The number of runs of the line
+    #[arg(long, default_value_t = 500)]
+    pub sims: i32,
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct EndlessArgs {
+    /// The identifiers of the heroes to climb with, each alongside the fixed party members
+    #[arg(value_delimiter = ',', required = true)]
+    pub heroes: Vec<String>,
+    #[command(flatten)]
+    pub dungeon: DungeonArgs,
+    /// The fraction enemy stats grow by each floor
+    #[arg(long, default_value_t = 0.1)]
+    pub scaling: f64,
+    /// The most floors to climb
+    #[arg(long, default_value_t = 50)]
+    pub max_floors: usize,
+    /// The identifiers of fixed party members
+    #[arg(long, value_delimiter = ',')]
+    pub party: Vec<String>,
+    /// The number of simulations to run on each floor
+    #[arg(long, default_value_t = 100)]
+    pub sims: i32,
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct GearArgs {
+    /// The hero's identifier in hero_builder.csv
+    #[arg(long)]
+    pub hero: String,
+    #[command(flatten)]
+    pub dungeon: DungeonArgs,
+    /// The candidate items of each slot
+    #[arg(long, value_delimiter = ',', value_name = "ITEMS")]
+    pub slot_1: Vec<String>,
+    #[arg(long, value_delimiter = ',', value_name = "ITEMS")]
+    pub slot_2: Vec<String>,
+    #[arg(long, value_delimiter = ',', value_name = "ITEMS")]
+    pub slot_3: Vec<String>,
+    #[arg(long, value_delimiter = ',', value_name = "ITEMS")]
+    pub slot_4: Vec<String>,
+    #[arg(long, value_delimiter = ',', value_name = "ITEMS")]
+    pub slot_5: Vec<String>,
+    #[arg(long, value_delimiter = ',', value_name = "ITEMS")]
+    pub slot_6: Vec<String>,
+    /// The identifiers of fixed party members
+    #[arg(long, value_delimiter = ',')]
+    pub party: Vec<String>,
+    /// The number of simulations to run for each loadout
+    #[arg(long, default_value_t = 100)]
+    pub sims: i32,
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct QualityArgs {
+    /// The hero's identifier in hero_builder.csv
+    #[arg(long)]
+    pub hero: String,
+    #[command(flatten)]
+    pub dungeon: DungeonArgs,
+    /// Upgrade each slot on its own, or set every slot to each quality at once
+    #[arg(long, default_value = "per_slot", value_name = "per_slot|uniform")]
+    pub mode: QualitySweepMode,
+    /// The identifiers of fixed party members
+    #[arg(long, value_delimiter = ',')]
+    pub party: Vec<String>,
+    /// The number of simulations to run for each build
+    #[arg(long, default_value_t = 100)]
+    pub sims: i32,
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct TeamArgs {
+    /// The identifiers of the heroes teams are formed from
+    #[arg(long, value_delimiter = ',', required = true)]
+    pub roster: Vec<String>,
+    /// The quest's identifier in quests.yaml
+    #[arg(long)]
+    pub quest: String,
+    /// The heroes per team (default the max heroes of the quest's dungeon)
+    #[arg(long)]
+    pub size: Option<usize>,
+    /// Classes that at most one hero of each team may have
+    #[arg(long, value_delimiter = ',')]
+    pub unique_classes: Vec<String>,
+    /// The number of simulations to run for each team
+    #[arg(long, default_value_t = 100)]
+    pub sims: i32,
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct SingleArgs {
+    /// The hero's identifier in hero_builder.csv
+    pub hero: String,
+    #[command(flatten)]
+    pub dungeon: DungeonArgs,
+    /// The skills to draw from (default every T4 skill)
+    #[arg(long, value_delimiter = ',')]
+    pub skills: Vec<String>,
+    /// Only draw skills of these rarities, such as Common,Rare
+    #[arg(long, value_delimiter = ',')]
+    pub rarities: Vec<String>,
+    /// The number of simulations to run for each combination
+    #[arg(long, default_value_t = 100)]
+    pub sims: i32,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum StudyCommand {
+    /// Run the static party skill, support hero skill, single hero skill, gear optimization, quality sweep, or targeting sensitivity study described by a yaml or toml study config
+    Run { config: String },
+    /// Continue an interrupted skill, gear, team, or quality study from the checkpoint.msgpack it saved in its target/simulations folder
+    Resume { checkpoint: String },
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum HeroCommand {
+    /// Check every hero of a csv with hero_builder.csv's columns, reporting each row that can't be imported
+    Validate { file: String },
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum QueueCommand {
+    /// Queue a study, run as if its arguments were passed directly, such as "queue add --priority 2 gear input/gear.yaml"
+    Add {
+        /// Studies of higher priority run first
+        #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+        priority: i32,
+        /// The study's arguments, everything after the priority
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Run queued studies one at a time until none are waiting
+    Run,
+    /// Show the queue
+    List,
+    /// Pause a study, stopping it after its current batch of trials if it's running
+    Pause { id: usize },
+    /// Let a paused study run again from where it stopped
+    Resume { id: usize },
+    /// Reprioritize a study
+    Priority {
+        id: usize,
+        #[arg(allow_negative_numbers = true)]
+        priority: i32,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum RosterCommand {
+    /// Add heroes from hero_builder.csv to a roster, creating it if needed and replacing builds of the same identifier
+    Save {
+        name: String,
+        #[arg(required = true)]
+        heroes: Vec<String>,
+    },
+    /// Remove heroes from a roster
+    Remove {
+        name: String,
+        #[arg(required = true)]
+        heroes: Vec<String>,
+    },
+    /// Add every hero of a roster to hero_builder.csv, skipping identifiers already there
+    Load { name: String },
+    /// List the heroes of a roster
+    Show { name: String },
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum DataCommand {
+    /// Validate the data sheets and input yaml files of another checkout, then copy them over the local ones
+    Import { dir: String },
+}
+
+/// Parse a "<base>,<growth rate>" argument
+fn parse_growth(value: &str) -> Result<(f64, f64), String> {
+    let values = value
+        .split(',')
+        .map(|value| value.trim().parse::<f64>())
+        .collect::<Result<Vec<f64>, _>>()
+        .map_err(|_| String::from("values must be numbers"))?;
+    let [base, growth_rate] = values[..] else {
+        return Err(String::from("must be given as <base>,<growth rate>"));
+    };
+    return Ok((base, growth_rate));
+}
+
+/// Parse a "<level>:<hp>/<atk>/<def>" argument
+fn parse_known_stats(value: &str) -> Result<(u8, [f64; 3]), String> {
+    let (level, stats) = value
+        .split_once(':')
+        .ok_or("must be given as <level>:<hp>/<atk>/<def>")?;
+    let stats: Vec<f64> = stats
+        .split('/')
+        .map(|stat| stat.trim().parse())
+        .collect::<Result<Vec<f64>, _>>()
+        .map_err(|_| String::from("stats must be numbers"))?;
+    return Ok((
+        level
+            .trim()
+            .parse()
+            .map_err(|_| String::from("level must be a number"))?,
+        stats
+            .try_into()
+            .map_err(|_| String::from("must give hp, atk, and def for each level"))?,
+    ));
+}
+
+/// Parse a number, such as a fraction or level
+fn parse_number(value: &str) -> Result<f64, String> {
+    return value
+        .trim()
+        .parse()
+        .map_err(|_| String::from("must be a number"));
+}
+
+/// Parse a confidence level, a fraction such as 0.95
+fn parse_confidence_level(value: &str) -> Result<f64, String> {
+    let level = parse_number(value)?;
+    if !(0.0..1.0).contains(&level) {
+        return Err(String::from("must be within range 0-1, such as 0.95"));
+    }
+    return Ok(level);
+}
+
+/// Parse a confidence interval width, a fraction such as 0.05
+fn parse_interval_width(value: &str) -> Result<f64, String> {
+    let width = parse_number(value)?;
+    if width <= 0.0 || width > 1.0 {
+        return Err(String::from("must be within range 0-1, such as 0.05"));
+    }
+    return Ok(width);
+}
+
+/// Parse a target success rate, a fraction such as 0.9
+fn parse_target_success_rate(value: &str) -> Result<f64, String> {
+    let target = parse_number(value)?;
+    if target <= 0.0 || target >= 1.0 {
+        return Err(String::from(
+            "must be within range 0-1 exclusive, such as 0.9",
+        ));
+    }
+    return Ok(target);
+}
+
+/// Parse the command line with clap and install error reporting in its --error-format
+/// Invalid arguments exit as an input error, reported in the --error-format if it could still be read
+pub fn parse_cli() -> Cli {
+    let result = Cli::try_parse();
+    let error_format = match &result {
+        Ok(cli) => cli.options.error_format,
+        Err(_) => Cli::command()
+            .ignore_errors(true)
+            .try_get_matches()
+            .ok()
+            .and_then(|matches| matches.get_one::<ErrorFormat>("error_format").copied()),
+    };
+    install_error_reporting(error_format.unwrap_or(ErrorFormat::Human));
+    return match result {
+        Ok(cli) => cli,
+        Err(e) if !e.use_stderr() => e.exit(), // Help
+        Err(e) => exit_with_error(
+            ErrorKind::Input,
+            e.render()
+                .to_string()
+                .trim_start_matches("error: ")
+                .trim_end()
+                .to_string(),
+        ),
+    };
+}
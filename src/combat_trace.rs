@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+
+use crate::formatting::serialize_rounded;
+
+/// The actor name used for the mob in trace events
+pub const MOB_ACTOR: &str = "Mob";
+
+/// Defines the kinds of action recorded by a combat trace
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, strum::Display)]
+pub enum CombatAction {
+    HeroAttack,  // A hero attacks the mob
+    PetAttack,   // A hero's pet procs against the mob
+    MobAttack,   // The mob attacks a single hero
+    MobAoe,      // The mob hits every living hero at once
+    MobSlam,     // The mob's slam ability hits a hero
+    LordProtect, // The Lord takes a hit in place of a hero who would have died
+    Revive,      // A hero's skill brings a fallen hero back, with the hp they return with
+}
+
+/// Whether an attack was evaded, landed normally, or landed as a critical hit
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum HitKind {
+    Evaded,
+    Normal,
+    Critical,
+}
+
+/// A single attack or revive within a simulation
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CombatEvent {
+    round: i16,
+    action: CombatAction,
+    actor: String,
+    target: String,
+    damage: f64, // 0 if evaded
+    crit: bool,
+    evaded: bool,
+    target_hp_after: f64, // After any fatal blow survival or Lord save
+}
+
+impl CombatEvent {
+    pub fn get_round(&self) -> i16 {
+        return self.round;
+    }
+
+    pub fn get_action(&self) -> CombatAction {
+        return self.action;
+    }
+
+    pub fn get_actor(&self) -> String {
+        return self.actor.to_string();
+    }
+
+    pub fn get_target(&self) -> String {
+        return self.target.to_string();
+    }
+
+    pub fn get_damage(&self) -> f64 {
+        return self.damage;
+    }
+
+    pub fn is_crit(&self) -> bool {
+        return self.crit;
+    }
+
+    pub fn is_evaded(&self) -> bool {
+        return self.evaded;
+    }
+
+    pub fn get_target_hp_after(&self) -> f64 {
+        return self.target_hp_after;
+    }
+}
+
+/// The attacks of a simulation in the order they happened, recorded only when the trace is enabled so untraced simulations allocate nothing
+#[derive(Debug, Clone, PartialEq)]
+pub struct CombatTrace {
+    enabled: bool,
+    round: i16,
+    events: Vec<CombatEvent>,
+}
+
+pub fn create_combat_trace(enabled: bool) -> CombatTrace {
+    return CombatTrace {
+        enabled,
+        round: 0,
+        events: vec![],
+    };
+}
+
+impl CombatTrace {
+    /// Set the round recorded with following events
+    pub fn set_round(&mut self, round: i16) {
+        self.round = round;
+    }
+
+    pub fn record(
+        &mut self,
+        action: CombatAction,
+        actor: &str,
+        target: &str,
+        hit: HitKind,
+        damage: f64,
+        target_hp_after: f64,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        self.events.push(CombatEvent {
+            round: self.round,
+            action,
+            actor: actor.to_string(),
+            target: target.to_string(),
+            damage: if hit == HitKind::Evaded { 0.0 } else { damage },
+            crit: hit == HitKind::Critical,
+            evaded: hit == HitKind::Evaded,
+            target_hp_after,
+        });
+    }
+
+    pub fn into_events(self) -> Vec<CombatEvent> {
+        return self.events;
+    }
+}
+
+/// A row of a trace csv, one per event of each traced simulation
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TraceCSVRecord {
+    simulation: usize, // The index of the simulation within the trial
+    outcome: String,
+    round: i16,
+    action: String,
+    actor: String,
+    target: String,
+    #[serde(serialize_with = "serialize_rounded")]
+    damage: f64,
+    crit: bool,
+    evaded: bool,
+    #[serde(serialize_with = "serialize_rounded")]
+    target_hp_after: f64,
+}
+
+pub fn create_trace_csv_record(
+    simulation: usize,
+    outcome: String,
+    event: &CombatEvent,
+) -> TraceCSVRecord {
+    return TraceCSVRecord {
+        simulation,
+        outcome,
+        round: event.round,
+        action: event.action.to_string(),
+        actor: event.actor.to_string(),
+        target: event.target.to_string(),
+        damage: event.damage,
+        crit: event.crit,
+        evaded: event.evaded,
+        target_hp_after: event.target_hp_after,
+    };
+}
@@ -0,0 +1,156 @@
+pub mod class_commands;
+pub mod data_commands;
+pub mod hero_commands;
+pub mod queue_commands;
+pub mod simulate_commands;
+pub mod study_commands;
+
+use std::collections::HashMap;
+
+use log::info;
+
+use crate::{
+    cli::{DungeonArgs, GlobalOptions},
+    errors::{exit_with_error, set_error_kind, ErrorKind},
+};
+use st_sim::{
+    data_sources::{create_file_data_source, create_remote_data_source, DataSource},
+    dungeons::{create_trial_dungeon, Dungeon, TrialDungeon},
+    hero_builder::Hero,
+    heroes::SimHero,
+    inputs::{load_heroes_as_sim_heroes_from_csv, load_heroes_from_csv, load_sim_heroes_from_csv},
+    rng::RngConfig,
+    simulator::GameData,
+    statistics::StatisticsConfig,
+    studies::HeroBuilderInformation,
+};
+
+/// The game data and heroes the commands of the st_sim binary run on, loaded once the data only commands have been ruled out
+pub struct CommandContext {
+    pub data_source: Box<dyn DataSource>,
+    pub hero_builder_information: HeroBuilderInformation,
+    pub heroes: HashMap<String, SimHero>, // Every loaded hero, from heroes.csv and hero_builder.csv
+    pub heroes_from_builder: HashMap<String, Hero>, // The heroes of hero_builder.csv, with their skill references resolved
+    pub dungeons: HashMap<String, Dungeon>,
+    pub rng: RngConfig, // The generator and seed every trial and study of the command draws from
+    pub statistics: StatisticsConfig, // How every trial and study of the command computes its intervals
+}
+
+/// Returns the heroes of a "--party <hero identifiers>" argument, exiting with an input error if one isn't loaded
+pub fn get_party(identifiers: &[String], heroes: &HashMap<String, SimHero>) -> Vec<SimHero> {
+    return identifiers
+        .iter()
+        .map(|identifier| {
+            heroes.get(identifier).cloned().unwrap_or_else(|| {
+                exit_with_error(ErrorKind::Input, f!("hero {} not found", identifier))
+            })
+        })
+        .collect();
+}
+
+/// Returns the dungeon of a "--dungeon <zone>" argument at its "--difficulty", exiting with an input error if it isn't in dungeons.yaml
+pub fn get_trial_dungeon(args: &DungeonArgs, dungeons: &HashMap<String, Dungeon>) -> TrialDungeon {
+    let dungeon = dungeons.get(&args.dungeon).unwrap_or_else(|| {
+        exit_with_error(
+            ErrorKind::Input,
+            f!("dungeon {} not found in input/dungeons.yaml", args.dungeon),
+        )
+    });
+    return create_trial_dungeon(dungeon.clone(), args.difficulty, Some(false), None, None);
+}
+
+/// Returns the generator and statistics of every trial from the global options
+pub fn configure_simulations(options: &GlobalOptions) -> (RngConfig, StatisticsConfig) {
+    let rng_config = RngConfig {
+        algorithm: options.rng.unwrap_or_default(),
+        seed: options.seed,
+    };
+    let statistics_config = StatisticsConfig {
+        bootstrap_resamples: options.bootstrap,
+        confidence_level: options.confidence,
+        target_interval_width: options.target_ci_width,
+        max_simulation_qty: options.max_sims,
+    };
+    return (rng_config, statistics_config);
+}
+
+/// Returns where game data is loaded from, the local checkout unless "--data-url <url>" or "--data-dir <dir>" is passed
+/// Heroes are always loaded from the local input folder
+pub fn create_data_source(options: &GlobalOptions) -> Box<dyn DataSource> {
+    let data_source: Box<dyn DataSource> = match &options.data_url {
+        Some(url) => Box::new(create_remote_data_source(url)),
+        None => Box::new(create_file_data_source(
+            options.data_dir.as_deref().unwrap_or("."),
+        )),
+    };
+    set_error_kind(ErrorKind::Data);
+    return data_source;
+}
+
+/// Log to a new target/logs/trial_<n>.log each run
+pub fn init_logging() {
+    let mut i = 0;
+    while std::path::Path::new(&f!("target/logs/trial_{}.log", i)).exists() {
+        i += 1;
+    }
+    fast_log::init(fast_log::Config::new().file(&f!("target/logs/trial_{}.log", i))).unwrap();
+    info!("Start of Log File");
+}
+
+/// Load every hero of heroes.csv and hero_builder.csv as SimHeroes, builder heroes replacing heroes of the same identifier
+pub fn load_sim_heroes(
+    hero_builder_information: &HeroBuilderInformation,
+) -> HashMap<String, SimHero> {
+    let heroes_from_builder = load_heroes_as_sim_heroes_from_csv(
+        String::from("input/hero_builder.csv"),
+        hero_builder_information,
+    )
+    .unwrap_or_else(|e| exit_with_error(ErrorKind::Data, e));
+
+    let mut loaded_heroes =
+        load_sim_heroes_from_csv(String::from("input/heroes.csv"), hero_builder_information)
+            .iter()
+            .map(|hero| (hero.get_identifier(), hero.clone()))
+            .collect::<HashMap<String, SimHero>>();
+    loaded_heroes.extend(heroes_from_builder);
+    return loaded_heroes;
+}
+
+/// Load every hero and the dungeons for the commands that simulate or inspect heroes, on the game data loaded by load_game_data
+pub fn load_command_context(
+    data_source: Box<dyn DataSource>,
+    game_data: GameData,
+    rng: RngConfig,
+    statistics: StatisticsConfig,
+) -> CommandContext {
+    let hero_builder_information = game_data.hero_builder_information;
+    let heroes = load_sim_heroes(&hero_builder_information);
+    let dungeons = data_source
+        .get_dungeons()
+        .unwrap_or_else(|e| exit_with_error(ErrorKind::Data, e));
+
+    let mut heroes_from_builder = load_heroes_from_csv(
+        String::from("input/hero_builder.csv"),
+        hero_builder_information.bp_map.clone(),
+        hero_builder_information.hero_classes.clone(),
+    )
+    .unwrap_or_else(|e| exit_with_error(ErrorKind::Data, e));
+    for (identifier, hero) in heroes_from_builder.iter_mut() {
+        hero.resolve_skill_references(
+            &hero_builder_information.hero_skill_tier_1_name_map,
+            &hero_builder_information.hero_skill_map,
+        )
+        .unwrap_or_else(|e| exit_with_error(ErrorKind::Data, f!("{} for hero {}", e, identifier)));
+    }
+
+    set_error_kind(ErrorKind::Input);
+    return CommandContext {
+        data_source,
+        hero_builder_information,
+        heroes,
+        heroes_from_builder,
+        dungeons,
+        rng,
+        statistics,
+    };
+}
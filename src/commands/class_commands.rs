@@ -0,0 +1,156 @@
+use std::collections::{BTreeMap, HashMap};
+
+use log::info;
+
+use crate::errors::{exit_with_error, set_error_kind, ErrorKind};
+use st_sim::{
+    hero_builder::{
+        _create_hero_class, create_class_stat_curves, scaffold_hero_class, HeroClass,
+        CLASS_GROWTH_BREAKPOINTS, MAX_HERO_LEVEL,
+    },
+    heroes::HeroArchetype,
+    inputs::{_save_hero_classes_to_yaml, import_hero_classes_from_csv},
+};
+
+/// "class-curve": print a class's per level base stats as yaml for hero_classes.yaml,
+/// growth stepping up at the breakpoints (default 10,21,31) up to the max level (default 40)
+pub fn print_class_curve(
+    hp: (f64, f64),
+    atk: (f64, f64),
+    def: (f64, f64),
+    breakpoints: Option<&[u8]>,
+    max_level: Option<u8>,
+) {
+    set_error_kind(ErrorKind::Input);
+    let curves = create_class_stat_curves(
+        hp,
+        atk,
+        def,
+        breakpoints.unwrap_or(&CLASS_GROWTH_BREAKPOINTS),
+        max_level.unwrap_or(MAX_HERO_LEVEL),
+    )
+    .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+    print!("{}", serde_yaml::to_string(&curves).unwrap());
+}
+
+/// "import-classes <csv path>": add the hero classes of a csv to hero_classes.yaml, replacing classes of the same name,
+/// with base stats given per level or as growth formulas, see import_hero_classes_from_csv for the columns. Invalid rows are reported and skipped
+pub fn import_classes(path: &str) {
+    set_error_kind(ErrorKind::Input);
+    let file = std::fs::File::open(path)
+        .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, f!("{}: {}", path, e)));
+    let (imported, errors) = import_hero_classes_from_csv(file);
+    for error in &errors {
+        eprintln!("Skipped {}", error);
+    }
+    set_error_kind(ErrorKind::Runtime);
+    let imported_qty = imported.len();
+    if imported_qty > 0 {
+        _save_hero_classes_to_yaml(
+            String::from("input/hero_classes.yaml"),
+            imported
+                .into_iter()
+                .map(|hero_class| (hero_class.get_class(), hero_class))
+                .collect(),
+        )
+        .unwrap();
+    }
+    println!("Imported {} classes into hero_classes.yaml", imported_qty);
+}
+
+/// Save the Jarl to hero_classes.yaml before the default study
+pub fn save_jarl_hero_class() {
+    let hc_hm = HashMap::from([(
+        String::from("Jarl"),
+        _create_hero_class(
+            String::from("Jarl"),
+            String::from("Titan Soul (Berserker)"),
+            0,
+            0,
+            vec![
+                100.0, 105.0, 110.0, 115.0, 120.0, 125.0, 130.0, 135.0, 140.0, 150.0, 160.0, 170.0,
+                180.0, 190.0, 200.0, 210.0, 220.0, 230.0, 240.0, 250.0, 265.0, 280.0, 295.0, 310.0,
+                325.0, 340.0, 355.0, 370.0, 385.0, 400.0, 420.0, 440.0, 460.0, 480.0, 500.0, 520.0,
+                540.0, 560.0, 580.0, 600.0,
+            ],
+            vec![
+                75.0, 81.0, 87.0, 94.0, 100.0, 106.0, 112.0, 119.0, 125.0, 137.0, 150.0, 162.0,
+                175.0, 187.0, 200.0, 212.0, 225.0, 237.0, 250.0, 262.0, 281.0, 300.0, 319.0, 337.0,
+                356.0, 375.0, 394.0, 412.0, 431.0, 450.0, 475.0, 500.0, 525.0, 550.0, 575.0, 600.0,
+                625.0, 650.0, 675.0, 700.0,
+            ],
+            vec![
+                90.0, 95.0, 100.0, 105.0, 110.0, 115.0, 121.0, 126.0, 131.0, 141.0, 151.0, 161.0,
+                172.0, 182.0, 192.0, 202.0, 212.0, 223.0, 233.0, 243.0, 258.0, 274.0, 289.0, 304.0,
+                319.0, 335.0, 350.0, 365.0, 381.0, 396.0, 416.0, 437.0, 457.0, 478.0, 498.0, 518.0,
+                539.0, 559.0, 580.0, 600.0,
+            ],
+            0.0,
+            0.05,
+            2.0,
+            90,
+            String::from("Fire"),
+            [
+                vec![
+                    String::from("Mace"),
+                    String::from("Axe"),
+                    String::from("Gun"),
+                ],
+                vec![String::from("Heavy Armor")],
+                vec![String::from("Gauntlets"), String::from("Helmet")],
+                vec![String::from("Heavy Footwear")],
+                vec![String::from("Shield"), String::from("Cloak")],
+                vec![String::from("Herbal Medicine"), String::from("Potion")],
+            ],
+            vec![],
+            [
+                String::from("Berserk Rage"),
+                String::from("Anger Point"),
+                String::from("The Beast Within"),
+                String::from("The Beast Unleashed"),
+            ],
+            false,
+            None,
+        ),
+    )]);
+
+    _save_hero_classes_to_yaml(String::from("input/hero_classes.yaml"), hc_hm).unwrap();
+}
+
+/// "scaffold-class <class>": print an estimated hero_classes.yaml entry for a new class from the classes of its archetype closest in tier,
+/// fit to any known base stats given as (level, [hp, atk, def])
+pub fn print_class_scaffold(
+    class: &str,
+    archetype: HeroArchetype,
+    tier: u8,
+    promoted: bool,
+    element_type: Option<String>,
+    known_stats: &[(u8, [f64; 3])],
+    hero_classes: &HashMap<String, HeroClass>,
+) {
+    let (scaffold, peers) = scaffold_hero_class(
+        class.to_string(),
+        archetype,
+        tier,
+        promoted,
+        element_type,
+        known_stats,
+        hero_classes,
+    )
+    .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+    println!("# Estimated from {}", peers.join(", "));
+    print!(
+        "{}",
+        serde_yaml::to_string(&BTreeMap::from([(class, scaffold)])).unwrap()
+    );
+}
+
+/// Warn about each class whose stats were estimated rather than taken from the game
+pub fn warn_estimated_classes(hero_classes: &HashMap<String, HeroClass>) {
+    for (class, hero_class) in hero_classes.iter().collect::<BTreeMap<_, _>>() {
+        if hero_class.is_estimated() {
+            info!("Class {} has estimated stats", class);
+            eprintln!("warning: class {} has estimated stats", class);
+        }
+    }
+}
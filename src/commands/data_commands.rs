@@ -0,0 +1,58 @@
+use crate::errors::{exit_with_error, set_error_kind, ErrorKind};
+use st_sim::{
+    data_sources::{import_game_data, BLUEPRINTS_SHEET, HERO_SKILLS_SHEET, INNATE_SKILLS_SHEET},
+    validation::{fmt_validation_report, validate_inputs, DataSheetPaths},
+};
+
+/// "validate": check every input file and data sheet before a long batch, exiting nonzero with a report of all problems found
+pub fn validate_local_inputs() {
+    let reports = validate_inputs(
+        "input",
+        &DataSheetPaths {
+            hero_skills: String::from(HERO_SKILLS_SHEET),
+            innate_skills: String::from(INNATE_SKILLS_SHEET),
+            blueprints: String::from(BLUEPRINTS_SHEET),
+        },
+    );
+    print!("{}", fmt_validation_report(&reports));
+    let invalid_qty = reports.iter().filter(|report| !report.is_valid()).count();
+    if invalid_qty > 0 {
+        exit_with_error(
+            ErrorKind::Data,
+            f!(
+                "{} of {} input files are invalid",
+                invalid_qty,
+                reports.len()
+            ),
+        );
+    }
+}
+
+/// "data import <dir>": validate the data sheets and input yaml files of another checkout, then copy them over the local ones
+/// Nothing is copied unless every file is valid, and hero csvs are left alone
+pub fn import_data(dir: &str) {
+    let reports = validate_inputs(
+        &f!("{}/input", dir),
+        &DataSheetPaths {
+            hero_skills: f!("{}/{}", dir, HERO_SKILLS_SHEET),
+            innate_skills: f!("{}/{}", dir, INNATE_SKILLS_SHEET),
+            blueprints: f!("{}/{}", dir, BLUEPRINTS_SHEET),
+        },
+    );
+    print!("{}", fmt_validation_report(&reports));
+    let invalid_qty = reports.iter().filter(|report| !report.is_valid()).count();
+    if invalid_qty > 0 {
+        exit_with_error(
+            ErrorKind::Data,
+            f!(
+                "{} of {} files in {} are invalid, nothing was imported",
+                invalid_qty,
+                reports.len(),
+                dir
+            ),
+        );
+    }
+    set_error_kind(ErrorKind::Runtime);
+    let names = import_game_data(dir, ".").unwrap();
+    println!("Imported {} files from {}", names.len(), dir);
+}
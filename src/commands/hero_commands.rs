@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::{
+    cli::{CraftOddsArgs, RosterCommand, ShowChampionArgs},
+    commands::CommandContext,
+    errors::{exit_with_error, set_error_kind, ErrorKind},
+};
+use st_sim::{
+    build_codes::{decode_build_code, encode_build_code},
+    champions::find_champion_data,
+    crafting::{create_craft_quality_odds, ItemQuality, BASE_QUALITY_CHANCES},
+    hero_builder::{Hero, StatBreakdown},
+    inputs::{import_heroes_from_csv, save_heroes_to_csv, HeroInput},
+    rng::create_sim_rng,
+    roster::{get_roster_path, load_or_create_roster, load_roster},
+};
+
+/// "hero validate <csv path>": check every hero of a csv with hero_builder.csv's columns, reporting each row that can't be imported
+pub fn validate_hero_csv(file: &str, context: &CommandContext) {
+    let hero_builder_information = &context.hero_builder_information;
+    let csv_file = std::fs::File::open(file)
+        .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, f!("{}: {}", file, e)));
+    let (mut valid_heroes, errors) = import_heroes_from_csv(
+        csv_file,
+        &hero_builder_information.bp_map,
+        &hero_builder_information.hero_classes,
+    );
+    let mut errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    valid_heroes.retain_mut(|hero| {
+        let result = hero.resolve_skill_references(
+            &hero_builder_information.hero_skill_tier_1_name_map,
+            &hero_builder_information.hero_skill_map,
+        );
+        if let Err(e) = &result {
+            errors.push(f!("{}: {}", hero.get_identifier(), e));
+        }
+        return result.is_ok();
+    });
+    for error in &errors {
+        println!("Invalid {}", error);
+    }
+    println!(
+        "{} of {} heroes in {} are valid",
+        valid_heroes.len(),
+        valid_heroes.len() + errors.len(),
+        file
+    );
+    if !errors.is_empty() {
+        exit_with_error(
+            ErrorKind::Data,
+            f!("{} heroes in {} are invalid", errors.len(), file),
+        );
+    }
+}
+
+/// "inspect [hero identifiers...]": print the full stat sheet and computed SimHero values of heroes without running simulations,
+/// every loaded hero if none are given, or the heroes of another csv with hero_builder.csv's columns
+pub fn inspect_heroes(hero_identifiers: &[String], file: Option<&str>, context: &CommandContext) {
+    let hero_builder_information = &context.hero_builder_information;
+    let csv_heroes: HashMap<String, Hero> = match file {
+        Some(file) => {
+            let csv_file = std::fs::File::open(file)
+                .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, f!("{}: {}", file, e)));
+            let (valid_heroes, errors) = import_heroes_from_csv(
+                csv_file,
+                &hero_builder_information.bp_map,
+                &hero_builder_information.hero_classes,
+            );
+            for error in &errors {
+                println!("Invalid {}", error);
+            }
+            valid_heroes
+                .into_iter()
+                .map(|hero| (hero.get_identifier(), hero))
+                .collect()
+        }
+        None => context.heroes_from_builder.clone(),
+    };
+    let csv_name = file.unwrap_or("input/hero_builder.csv");
+    let mut identifiers = hero_identifiers.to_vec();
+    if identifiers.is_empty() {
+        identifiers = match file {
+            Some(_) => csv_heroes.keys().cloned().collect(),
+            None => context.heroes.keys().cloned().collect(),
+        };
+        identifiers.sort();
+    }
+    set_error_kind(ErrorKind::Runtime);
+    for identifier in identifiers {
+        let Some(mut hero) = csv_heroes.get(&identifier).cloned() else {
+            // Heroes of input/heroes.csv are loaded with their final stats, so only their SimHero values are printed
+            match context.heroes.get(&identifier).filter(|_| file.is_none()) {
+                Some(sim_hero) => println!("{}", sim_hero.fmt_inspect()),
+                None => exit_with_error(
+                    ErrorKind::Input,
+                    f!(
+                        "hero {} not found among the valid heroes of {}",
+                        identifier,
+                        csv_name
+                    ),
+                ),
+            }
+            continue;
+        };
+        let sim_hero = hero_builder_information
+            .build_sim_hero(hero.clone())
+            .unwrap_or_else(|e| {
+                exit_with_error(ErrorKind::Data, f!("{} for hero {}", e, identifier))
+            });
+        if let Err(e) = hero
+            .resolve_skill_references(
+                &hero_builder_information.hero_skill_tier_1_name_map,
+                &hero_builder_information.hero_skill_map,
+            )
+            .map_err(|e| e.to_string())
+            .and_then(|_| {
+                hero.calculate_innate_tier(
+                    &hero_builder_information.class_innate_skill_names_map,
+                    &hero_builder_information.innate_skill_map,
+                    &hero_builder_information.champion_data,
+                )
+                .map_err(|e| e.to_string())
+            })
+        {
+            exit_with_error(ErrorKind::Data, f!("{} for hero {}", e, identifier));
+        }
+        let breakdown = calculate_stat_breakdown(&mut hero, context);
+        println!(
+            "{}",
+            hero.fmt_card(&breakdown, &hero_builder_information.bp_map)
+        );
+        println!("{}", sim_hero.fmt_inspect());
+    }
+}
+
+/// Run the gear and skill stat pipeline on a hero whose innate tier is calculated, returning its stat breakdown
+fn calculate_stat_breakdown(hero: &mut Hero, context: &CommandContext) -> StatBreakdown {
+    let hero_builder_information = &context.hero_builder_information;
+    let identifier = hero.get_identifier();
+    return hero
+        .calculate_stat_improvements_from_gear_and_skills(
+            &hero_builder_information.bp_map,
+            &hero_builder_information.hero_skill_tier_1_name_map,
+            &hero_builder_information.hero_skill_map,
+            &hero_builder_information.class_innate_skill_names_map,
+            &hero_builder_information.innate_skill_map,
+            &hero_builder_information.pet_skill_map,
+            &hero_builder_information.spirit_map,
+            &hero_builder_information.elements,
+            &hero_builder_information.seed_values,
+        )
+        .unwrap_or_else(|e| exit_with_error(ErrorKind::Data, f!("{} for hero {}", e, identifier)));
+}
+
+/// "roster save|remove|load|show <name>": keep named rosters of hero builds in input/rosters and load them into hero_builder.csv
+pub fn run_roster_command(command: &RosterCommand, context: &CommandContext) {
+    let hero_builder_information = &context.hero_builder_information;
+    match command {
+        RosterCommand::Save { name, heroes } | RosterCommand::Remove { name, heroes } => {
+            let mut roster =
+                load_or_create_roster(name).unwrap_or_else(|e| exit_with_error(ErrorKind::Data, e));
+            for identifier in heroes {
+                if matches!(command, RosterCommand::Save { .. }) {
+                    let hero = context
+                        .heroes_from_builder
+                        .get(identifier)
+                        .unwrap_or_else(|| {
+                            exit_with_error(
+                                ErrorKind::Input,
+                                f!("hero {} not found in input/hero_builder.csv", identifier),
+                            )
+                        });
+                    roster.add_hero(hero.clone());
+                } else if let Err(e) = roster.remove_hero(identifier) {
+                    exit_with_error(ErrorKind::Input, f!("{}: {}", identifier, e));
+                }
+            }
+            set_error_kind(ErrorKind::Runtime);
+            roster.save(&get_roster_path(name)).unwrap();
+            println!(
+                "Saved roster {} with {} heroes to {}",
+                name,
+                roster.get_identifiers().len(),
+                get_roster_path(name)
+            );
+        }
+        RosterCommand::Load { name } => {
+            let roster = load_roster(&get_roster_path(name))
+                .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e));
+            let roster_heroes = roster
+                .load_heroes(
+                    &hero_builder_information.bp_map,
+                    &hero_builder_information.hero_classes,
+                )
+                .unwrap_or_else(|e| exit_with_error(ErrorKind::Data, e));
+            let mut loaded: HashMap<String, Hero> = HashMap::new();
+            for (identifier, hero) in roster_heroes {
+                if context.heroes_from_builder.contains_key(&identifier) {
+                    eprintln!(
+                        "Skipped {}: a hero with that identifier already exists in hero_builder.csv",
+                        identifier
+                    );
+                    continue;
+                }
+                loaded.insert(identifier, hero);
+            }
+            set_error_kind(ErrorKind::Runtime);
+            let loaded_qty = loaded.len();
+            if loaded_qty > 0 {
+                save_heroes_to_csv(String::from("input/hero_builder.csv"), loaded).unwrap();
+            }
+            println!(
+                "Loaded {} heroes from roster {} into hero_builder.csv",
+                loaded_qty, name
+            );
+        }
+        RosterCommand::Show { name } => {
+            let roster = load_roster(&get_roster_path(name))
+                .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e));
+            println!("Roster {}:", roster.get_name());
+            for identifier in roster.get_identifiers() {
+                println!("  {}", identifier);
+            }
+        }
+    }
+}
+
+/// Returns a hero of hero_builder.csv, exiting with an input error if there's no hero with the identifier
+fn get_builder_hero<'a>(identifier: &str, context: &'a CommandContext) -> &'a Hero {
+    return context
+        .heroes_from_builder
+        .get(identifier)
+        .unwrap_or_else(|| {
+            exit_with_error(
+                ErrorKind::Input,
+                f!("hero {} not found in input/hero_builder.csv", identifier),
+            )
+        });
+}
+
+/// "show-hero <hero identifier>": print a card of a hero's final stats, gear, and build warnings
+pub fn show_hero(identifier: &str, context: &CommandContext) {
+    let hero_builder_information = &context.hero_builder_information;
+    let mut hero = get_builder_hero(identifier, context).clone();
+    if let Err(e) = hero.calculate_innate_tier(
+        &hero_builder_information.class_innate_skill_names_map,
+        &hero_builder_information.innate_skill_map,
+        &hero_builder_information.champion_data,
+    ) {
+        exit_with_error(ErrorKind::Data, f!("{} for hero {}", e, identifier));
+    }
+    set_error_kind(ErrorKind::Runtime);
+    let breakdown = calculate_stat_breakdown(&mut hero, context);
+    print!(
+        "{}",
+        hero.fmt_card(&breakdown, &hero_builder_information.bp_map)
+    );
+}
+
+/// "craft-odds": print the chance of crafting each quality and the crafts expected to reach it,
+/// and with "--hero <hero identifier>" the crafts expected for each item of a hero in hero_builder.csv
+pub fn print_craft_odds(args: &CraftOddsArgs, context: &CommandContext) {
+    let odds = create_craft_quality_odds(BASE_QUALITY_CHANCES, args.quality_bonus)
+        .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+    print!(
+        "{}",
+        odds.fmt_report(args.crafts, &mut create_sim_rng(context.rng))
+    );
+    if let Some(identifier) = &args.hero {
+        let hero = get_builder_hero(identifier, context);
+        let targets: Vec<(String, ItemQuality)> = hero
+            .get_equipment_equipped()
+            .into_iter()
+            .zip(hero.get_equipment_quality())
+            .filter(|(item, _)| !item.is_empty())
+            .map(|(item, quality)| {
+                (
+                    item,
+                    ItemQuality::from_str(&quality).expect("unknown equipment quality"),
+                )
+            })
+            .collect();
+        print!(
+            "\n{}",
+            odds.fmt_targets(f!("Crafts to reach {}'s gear", identifier), &targets)
+        );
+    }
+}
+
+/// "show-champion <champion>": print a card of a champion's base stats and ability from champions.yaml at a "--level" and "--rank"
+pub fn show_champion(args: &ShowChampionArgs, context: &CommandContext) {
+    print!(
+        "{}",
+        args.champion.fmt_card(
+            find_champion_data(
+                &context.hero_builder_information.champion_data,
+                args.champion
+            ),
+            args.level,
+            args.rank
+        )
+    );
+}
+
+/// "build-code <hero identifier>": print a shareable build code for a hero in hero_builder.csv
+pub fn print_build_code(identifier: &str, context: &CommandContext) {
+    let hero = get_builder_hero(identifier, context);
+    println!("{}", encode_build_code(&HeroInput::from(hero.clone())));
+}
+
+/// "import-build <build code>": add a shared build to hero_builder.csv
+pub fn import_build(code: &str, context: &CommandContext) {
+    let hero = Hero::from(
+        decode_build_code(code)
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string())),
+    );
+    let identifier = hero.get_identifier();
+    if context.heroes_from_builder.contains_key(&identifier) {
+        exit_with_error(
+            ErrorKind::Input,
+            f!(
+                "A hero named {} already exists in hero_builder.csv",
+                identifier
+            ),
+        );
+    }
+    // Validate against the current blueprints and classes before saving
+    if let Err(e) = hero.clone().validate_equipment(
+        &context.hero_builder_information.bp_map,
+        &context.hero_builder_information.hero_classes,
+    ) {
+        exit_with_error(ErrorKind::Input, f!("{} for hero {}", e, identifier));
+    }
+    set_error_kind(ErrorKind::Runtime);
+    save_heroes_to_csv(
+        String::from("input/hero_builder.csv"),
+        HashMap::from([(identifier.to_string(), hero)]),
+    )
+    .unwrap();
+    println!("Imported {} into hero_builder.csv", identifier);
+}
+
+/// "import-heroes <csv path>": add every valid hero of a csv with hero_builder.csv's columns to hero_builder.csv, reporting each row that can't be imported
+pub fn import_heroes(path: &str, context: &CommandContext) {
+    let hero_builder_information = &context.hero_builder_information;
+    let file = std::fs::File::open(path)
+        .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, f!("{}: {}", path, e)));
+    let (heroes, errors) = import_heroes_from_csv(
+        file,
+        &hero_builder_information.bp_map,
+        &hero_builder_information.hero_classes,
+    );
+    for error in &errors {
+        eprintln!("Skipped {}", error);
+    }
+    let mut imported: HashMap<String, Hero> = HashMap::new();
+    for mut hero in heroes {
+        let identifier = hero.get_identifier();
+        if let Err(e) = hero.resolve_skill_references(
+            &hero_builder_information.hero_skill_tier_1_name_map,
+            &hero_builder_information.hero_skill_map,
+        ) {
+            eprintln!("Skipped {}: {}", identifier, e);
+            continue;
+        }
+        if context.heroes_from_builder.contains_key(&identifier) {
+            eprintln!(
+                "Skipped {}: a hero with that identifier already exists in hero_builder.csv",
+                identifier
+            );
+            continue;
+        }
+        imported.insert(identifier, hero);
+    }
+    set_error_kind(ErrorKind::Runtime);
+    let imported_qty = imported.len();
+    if imported_qty > 0 {
+        save_heroes_to_csv(String::from("input/hero_builder.csv"), imported).unwrap();
+    }
+    println!("Imported {} heroes into hero_builder.csv", imported_qty);
+}
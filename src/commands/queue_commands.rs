@@ -0,0 +1,37 @@
+use crate::{
+    cli::QueueCommand,
+    errors::{exit_with_error, set_error_kind, ErrorKind},
+};
+use st_sim::{
+    studies::create_cli_study_output,
+    study_queue::{load_study_queue, run_study_queue, STUDY_QUEUE_PATH},
+};
+
+/// "queue": add, pause, resume, or reprioritize queued studies and print the queue, or run queued studies one at a time until none are waiting
+/// Running skill studies also stop after their current batch when a higher priority study is queued, and resume once it's done
+pub fn run_queue_command(command: &QueueCommand) {
+    set_error_kind(ErrorKind::Input);
+    let mut queue = load_study_queue(STUDY_QUEUE_PATH).unwrap();
+    let result = match command {
+        QueueCommand::Add { priority, args } => {
+            let id = queue.add(args.clone(), *priority);
+            println!("Queued study {} with priority {}", id, priority);
+            Ok(())
+        }
+        QueueCommand::Pause { id } => queue.pause(*id),
+        QueueCommand::Resume { id } => queue.resume(*id),
+        QueueCommand::Priority { id, priority } => queue.set_priority(*id, *priority),
+        QueueCommand::Run => {
+            set_error_kind(ErrorKind::Runtime);
+            let run_qty = run_study_queue(STUDY_QUEUE_PATH, create_cli_study_output()).unwrap();
+            println!("Study queue empty after {} runs", run_qty);
+            return;
+        }
+        QueueCommand::List => Ok(()),
+    };
+    if let Err(e) = result {
+        exit_with_error(ErrorKind::Input, e.to_string());
+    }
+    queue.save(STUDY_QUEUE_PATH).unwrap();
+    print!("{}", queue.fmt_table());
+}
@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    cli::{CompareCommand, DeterminismArgs, EvaluateArgs, SimulateArgs},
+    commands::{get_party, get_trial_dungeon, CommandContext},
+    errors::{exit_with_error, set_error_kind, ErrorKind},
+};
+use st_sim::{
+    comparisons::{compare_heroes, compare_item_tiers, compare_result_sets, fit_tier_curves},
+    determinism::verify_determinism,
+    dungeons::create_trial_dungeon,
+    heroes::{create_team, SimHero},
+    studies::create_cli_study_output,
+    trials::{create_trial, get_trace_msgpack_path},
+    watch::evaluate_hero,
+};
+
+/// "simulate --party <hero identifiers> --dungeon <zone>": simulate a party and print the trial result,
+/// optionally saving every attack of each simulation (or only the lost ones) to a trace csv, also saved as MessagePack with save_msgpack
+pub fn simulate_party(args: &SimulateArgs, save_msgpack: bool, context: &CommandContext) {
+    let SimulateArgs {
+        party,
+        dungeon,
+        sims,
+        targeting,
+        trace,
+        trace_losses,
+    } = args;
+    let party_heroes = get_party(party, &context.heroes);
+    let trial_dungeon =
+        get_trial_dungeon(dungeon, &context.dungeons).with_targeting(targeting.unwrap_or_default());
+    let mut trial = create_trial(
+        String::from("Simulate"),
+        party.join(", "),
+        *sims,
+        create_team(party_heroes, None).unwrap(),
+        trial_dungeon.dungeon.clone(),
+        vec![trial_dungeon.difficulty],
+        trial_dungeon.force_minibosses,
+        trial_dungeon.max_rounds,
+        trial_dungeon.initiative,
+        trial_dungeon.targeting,
+        false,
+    )
+    .unwrap();
+    trial.set_reward_quest(
+        context
+            .hero_builder_information
+            .find_reward_quest(&trial_dungeon.dungeon._get_zone(), &[dungeon.difficulty])
+            .cloned(),
+    );
+    trial.set_trace(trace.is_some());
+    trial.set_rng(context.rng);
+    trial.set_statistics(context.statistics);
+    set_error_kind(ErrorKind::Runtime);
+    trial.run_simulations_single_threaded();
+    print!("{}", trial.create_trial_result().fmt_stats());
+    if let Some(path) = trace {
+        let traced_qty = trial.save_trace(path, *trace_losses, save_msgpack).unwrap();
+        println!("Saved the trace of {} simulations to {}", traced_qty, path);
+        if save_msgpack {
+            println!(
+                "Saved the trace as MessagePack to {}",
+                get_trace_msgpack_path(path)
+            );
+        }
+    }
+}
+
+/// "verify-determinism": run a small trial twice from the same seed, serially and across thread_qty threads (default 4),
+/// exiting nonzero with the first divergence if any simulation differs
+pub fn check_determinism(
+    args: &DeterminismArgs,
+    thread_qty: Option<usize>,
+    context: &CommandContext,
+) {
+    let zone = args.dungeon.clone().unwrap_or_else(|| {
+        context
+            .dungeons
+            .keys()
+            .min()
+            .expect("no dungeons to simulate")
+            .clone()
+    });
+    let dungeon = context
+        .dungeons
+        .get(&zone)
+        .unwrap_or_else(|| {
+            exit_with_error(
+                ErrorKind::Input,
+                f!("dungeon {} not found in input/dungeons.yaml", zone),
+            )
+        })
+        .clone();
+    let party = match args.party.is_empty() {
+        false => get_party(&args.party, &context.heroes),
+        true => context
+            .heroes
+            .iter()
+            .collect::<BTreeMap<&String, &SimHero>>()
+            .into_values()
+            .take(dungeon.get_max_num_heroes() as usize)
+            .cloned()
+            .collect(),
+    };
+    let trial_dungeon = create_trial_dungeon(dungeon, args.difficulty, Some(false), None, None);
+    set_error_kind(ErrorKind::Runtime);
+    let report = verify_determinism(
+        party,
+        &trial_dungeon,
+        args.sims,
+        context.rng,
+        thread_qty.unwrap_or(4),
+    )
+    .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+    print!("{}", report.fmt_report());
+    if !report.is_deterministic() {
+        exit_with_error(
+            ErrorKind::Runtime,
+            String::from("simulation results depend on more than the seed"),
+        );
+    }
+}
+
+/// "evaluate <hero identifier> --dungeon <zone>": quickly simulate a hero in hero_builder.csv, re-evaluating it whenever its build changes with "--watch"
+pub fn evaluate(args: &EvaluateArgs, context: &CommandContext) {
+    let trial_dungeon = get_trial_dungeon(&args.dungeon, &context.dungeons);
+    let party = get_party(&args.party, &context.heroes);
+    set_error_kind(ErrorKind::Runtime);
+    evaluate_hero(
+        "input/hero_builder.csv",
+        &args.hero,
+        party,
+        &trial_dungeon,
+        args.sims,
+        context.rng,
+        context.statistics,
+        &context.hero_builder_information,
+        args.watch,
+        create_cli_study_output(),
+    );
+}
+
+/// "compare hero|tiers|results|curves": compare heroes head to head, a hero against its gear a tier higher, the rankings of two runs,
+/// or fit success rate against difficulty tier for each build of a run
+pub fn compare(command: &CompareCommand, context: &CommandContext) {
+    match command {
+        CompareCommand::Hero {
+            hero_a,
+            hero_b,
+            dungeon,
+            party,
+            sims,
+        } => {
+            let trial_dungeon = get_trial_dungeon(dungeon, &context.dungeons);
+            let party = get_party(party, &context.heroes);
+            let [hero_a, hero_b] = [hero_a, hero_b].map(|identifier| {
+                context.heroes.get(identifier).cloned().unwrap_or_else(|| {
+                    exit_with_error(ErrorKind::Input, f!("hero {} not found", identifier))
+                })
+            });
+            set_error_kind(ErrorKind::Runtime);
+            let report = compare_heroes(
+                hero_a,
+                hero_b,
+                party,
+                &trial_dungeon,
+                *sims,
+                context.rng,
+                context.statistics,
+                &context.hero_builder_information,
+            )
+            .unwrap();
+            print!("{}", report);
+        }
+        CompareCommand::Tiers {
+            hero,
+            dungeon,
+            party,
+            slot,
+            tiers,
+            sims,
+        } => {
+            let trial_dungeon = get_trial_dungeon(dungeon, &context.dungeons);
+            let party = get_party(party, &context.heroes);
+            let hero = context
+                .heroes_from_builder
+                .get(hero)
+                .unwrap_or_else(|| {
+                    exit_with_error(
+                        ErrorKind::Input,
+                        f!("hero {} not found in input/hero_builder.csv", hero),
+                    )
+                })
+                .clone();
+            let slots: Vec<usize> = slot.map_or((0..6).collect(), |slot| vec![slot - 1]);
+            set_error_kind(ErrorKind::Runtime);
+            let report = compare_item_tiers(
+                hero,
+                slots,
+                *tiers,
+                party,
+                &trial_dungeon,
+                *sims,
+                context.rng,
+                context.statistics,
+                &context.hero_builder_information,
+            )
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Runtime, e));
+            print!("{}", report);
+        }
+        CompareCommand::Results { run_a, run_b } => {
+            set_error_kind(ErrorKind::Data);
+            print!("{}", compare_result_sets(run_a, run_b).unwrap());
+        }
+        CompareCommand::Curves { run, target } => {
+            set_error_kind(ErrorKind::Data);
+            print!("{}", fit_tier_curves(run, *target).unwrap());
+        }
+    }
+}
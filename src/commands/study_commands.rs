@@ -0,0 +1,496 @@
+use crate::{
+    cli::{CliCommand, GlobalOptions, StudyCommand},
+    commands::{get_party, get_trial_dungeon, CommandContext},
+    errors::{exit_with_error, set_error_kind, ErrorKind},
+};
+use st_sim::{
+    dungeons::create_trial_dungeon,
+    studies::elemental_coverage_study::create_elemental_coverage_study,
+    studies::endless_study::create_endless_study,
+    studies::gear_optimization_study::create_gear_optimization_study,
+    studies::hero_optimization_study::create_hero_optimization_study,
+    studies::quality_sweep_study::create_quality_sweep_study,
+    studies::quest_line_study::create_quest_line_study,
+    studies::roster_schedule_study::{create_roster_schedule_study, AssignmentStrategy},
+    studies::single_hero_skill_study::create_single_hero_skill_study,
+    studies::static_party_skill_study::create_static_party_skill_study,
+    studies::support_hero_skill_study::create_support_hero_skill_study,
+    studies::team_composition_study::create_team_composition_study,
+    studies::{resume_study, Runnable},
+    study_config::create_study_from_config_file,
+    trials::ResultFormat,
+};
+
+/// Create the study of a study command, or the default Daimyo skill study if no command is given, see --help for each command's arguments
+pub fn create_study_from_args(
+    context: CommandContext,
+    cli_command: Option<CliCommand>,
+    options: &GlobalOptions,
+) -> Box<dyn Runnable> {
+    let CommandContext {
+        data_source,
+        hero_builder_information,
+        heroes,
+        heroes_from_builder,
+        dungeons,
+        rng,
+        statistics,
+    } = context;
+
+    let mut valid_skills: Vec<String> = Default::default();
+    for (k, v) in &hero_builder_information.hero_skill_tier_1_name_map {
+        let ksplit: Vec<&str> = k.split(' ').collect();
+        if ksplit[ksplit.len() - 1] == "T4" {
+            valid_skills.push(v.to_string());
+        }
+    }
+
+    let save_raw_results = options.raw_results;
+    let save_msgpack = options.msgpack;
+    let dataset_path = options.dataset.clone();
+    let result_format = options.result_format.unwrap_or(ResultFormat::Wide);
+    let get_builder_hero = |identifier: &str| {
+        return heroes_from_builder
+            .get(identifier)
+            .unwrap_or_else(|| {
+                exit_with_error(
+                    ErrorKind::Input,
+                    f!("hero {} not found in input/hero_builder.csv", identifier),
+                )
+            })
+            .clone();
+    };
+    let mut study: Box<dyn Runnable> = match cli_command {
+        Some(CliCommand::Optimize(args)) => {
+            let study = create_hero_optimization_study(
+                f!(
+                    "Optimize_{}_{}",
+                    args.hero,
+                    args.dungeon.dungeon.replace(' ', "_")
+                ),
+                f!(
+                    "Optimize {} ({}) against {}",
+                    args.hero,
+                    args.axes
+                        .iter()
+                        .map(|axis| axis.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    args.dungeon.dungeon
+                ),
+                args.sims,
+                get_party(&args.party, &heroes),
+                get_builder_hero(&args.hero),
+                get_trial_dungeon(&args.dungeon, &dungeons),
+                args.axes,
+                args.budget,
+                result_format,
+                save_raw_results,
+                save_msgpack,
+                dataset_path,
+                hero_builder_information,
+            )
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+            Box::new(study)
+        }
+        Some(CliCommand::QuestLine(args)) => {
+            set_error_kind(ErrorKind::Data);
+            let quests = data_source
+                .get_quests()
+                .unwrap_or_else(|e| exit_with_error(ErrorKind::Data, e))
+                .unwrap_or_else(|| {
+                    exit_with_error(ErrorKind::Data, String::from("input/quests.yaml not found"))
+                });
+            let quest_lines = data_source
+                .get_quest_lines()
+                .unwrap_or_else(|e| exit_with_error(ErrorKind::Data, e));
+            set_error_kind(ErrorKind::Input);
+            let party = get_party(&args.party, &heroes);
+            let study = create_quest_line_study(
+                f!("Quest_Line_{}", args.quest_line.replace(' ', "_")),
+                f!(
+                    "Run the {} quest line with {}",
+                    args.quest_line,
+                    party
+                        .iter()
+                        .map(|hero| hero.get_identifier())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                args.sims,
+                party,
+                quest_lines
+                    .get(&args.quest_line)
+                    .unwrap_or_else(|| {
+                        exit_with_error(
+                            ErrorKind::Input,
+                            f!(
+                                "quest line {} not found in input/quest_lines.yaml",
+                                args.quest_line
+                            ),
+                        )
+                    })
+                    .clone(),
+                &quests,
+                &dungeons,
+                hero_builder_information,
+            )
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+            Box::new(study)
+        }
+        Some(CliCommand::Endless(args)) => {
+            let study = create_endless_study(
+                f!("Endless_{}", args.dungeon.dungeon.replace(' ', "_")),
+                f!(
+                    "Climb floors of {} starting at difficulty {}",
+                    args.dungeon.dungeon,
+                    args.dungeon.difficulty
+                ),
+                args.sims,
+                get_party(&args.heroes, &heroes),
+                get_party(&args.party, &heroes),
+                get_trial_dungeon(&args.dungeon, &dungeons),
+                args.scaling,
+                args.max_floors,
+                hero_builder_information,
+            )
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+            Box::new(study)
+        }
+        Some(CliCommand::Study {
+            command: StudyCommand::Run { config },
+        }) => {
+            return create_study_from_config_file(
+                &config,
+                &dungeons,
+                hero_builder_information,
+                rng,
+                statistics,
+            )
+            .unwrap_or_else(|e| panic!("invalid study config {}: {}", config, e));
+        }
+        Some(CliCommand::Study {
+            command: StudyCommand::Resume { checkpoint },
+        }) => {
+            set_error_kind(ErrorKind::Data);
+            let study = resume_study(&checkpoint)
+                .unwrap_or_else(|e| panic!("could not load checkpoint {}: {}", checkpoint, e));
+            set_error_kind(ErrorKind::Input);
+            println!("Resuming from {}", checkpoint);
+            return study;
+        }
+        Some(CliCommand::Gear(args)) => {
+            let slot_candidates = [
+                args.slot_1,
+                args.slot_2,
+                args.slot_3,
+                args.slot_4,
+                args.slot_5,
+                args.slot_6,
+            ];
+            let study = create_gear_optimization_study(
+                f!(
+                    "Gear_{}_{}",
+                    args.hero,
+                    args.dungeon.dungeon.replace(' ', "_")
+                ),
+                f!(
+                    "Rank loadouts of {} against {}",
+                    args.hero,
+                    args.dungeon.dungeon
+                ),
+                args.sims,
+                get_party(&args.party, &heroes),
+                get_builder_hero(&args.hero),
+                slot_candidates,
+                get_trial_dungeon(&args.dungeon, &dungeons),
+                result_format,
+                save_raw_results,
+                save_msgpack,
+                dataset_path,
+                hero_builder_information,
+            )
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+            println!(
+                "Loadouts Remaining to Test: {}",
+                study.count_loadouts_remaining()
+            );
+            Box::new(study)
+        }
+        Some(CliCommand::Quality(args)) => {
+            let study = create_quality_sweep_study(
+                f!(
+                    "Quality_{}_{}",
+                    args.hero,
+                    args.dungeon.dungeon.replace(' ', "_")
+                ),
+                f!(
+                    "Sweep the gear quality of {} against {}",
+                    args.hero,
+                    args.dungeon.dungeon
+                ),
+                args.sims,
+                get_party(&args.party, &heroes),
+                get_builder_hero(&args.hero),
+                args.mode,
+                get_trial_dungeon(&args.dungeon, &dungeons),
+                result_format,
+                save_raw_results,
+                save_msgpack,
+                dataset_path,
+                hero_builder_information,
+            )
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+            println!(
+                "Builds Remaining to Test: {}",
+                study.count_trials_remaining()
+            );
+            Box::new(study)
+        }
+        Some(CliCommand::Team(args)) => {
+            let roster = get_party(&args.roster, &heroes);
+            set_error_kind(ErrorKind::Data);
+            let quest = data_source
+                .get_quests()
+                .unwrap_or_else(|e| exit_with_error(ErrorKind::Data, e))
+                .unwrap_or_else(|| {
+                    exit_with_error(ErrorKind::Data, String::from("input/quests.yaml not found"))
+                })
+                .remove(&args.quest)
+                .unwrap_or_else(|| {
+                    exit_with_error(
+                        ErrorKind::Input,
+                        f!("quest {} not found in input/quests.yaml", args.quest),
+                    )
+                });
+            set_error_kind(ErrorKind::Input);
+            let party_size = args.size.unwrap_or_else(|| {
+                dungeons
+                    .get(&quest.get_dungeon())
+                    .map_or(1, |dungeon| dungeon.get_max_num_heroes() as usize)
+            });
+            let study = create_team_composition_study(
+                f!("Team_{}", args.quest.replace(' ', "_")),
+                f!(
+                    "Rank teams of {} from a roster of {} heroes on {}",
+                    party_size,
+                    roster.len(),
+                    args.quest
+                ),
+                args.sims,
+                roster,
+                party_size,
+                args.unique_classes,
+                quest,
+                &dungeons,
+                result_format,
+                save_raw_results,
+                save_msgpack,
+                dataset_path,
+                hero_builder_information,
+            )
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+            println!("Teams Remaining to Test: {}", study.count_teams_remaining());
+            Box::new(study)
+        }
+        Some(CliCommand::Single(args)) => {
+            let skill_pool = match args.skills.is_empty() {
+                true => valid_skills,
+                false => args.skills,
+            };
+            let study = create_single_hero_skill_study(
+                f!(
+                    "Single_{}_{}",
+                    args.hero,
+                    args.dungeon.dungeon.replace(' ', "_")
+                ),
+                f!(
+                    "Rank skill combinations of {} alone against {}",
+                    args.hero,
+                    args.dungeon.dungeon
+                ),
+                args.sims,
+                get_builder_hero(&args.hero),
+                skill_pool,
+                args.rarities,
+                get_trial_dungeon(&args.dungeon, &dungeons),
+                result_format,
+                save_raw_results,
+                save_msgpack,
+                dataset_path,
+                hero_builder_information,
+            )
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+            println!(
+                "Skill Variations Remaining to Test: {}",
+                study.count_skill_variations_remaining()
+            );
+            Box::new(study)
+        }
+        Some(CliCommand::Schedule) => {
+            set_error_kind(ErrorKind::Data);
+            let mut quests: Vec<_> = data_source
+                .get_quests()
+                .unwrap_or_else(|e| exit_with_error(ErrorKind::Data, e))
+                .unwrap_or_else(|| {
+                    exit_with_error(ErrorKind::Data, String::from("input/quests.yaml not found"))
+                })
+                .into_values()
+                .collect();
+            set_error_kind(ErrorKind::Input);
+            quests.sort_by_key(|quest| quest.get_identifier());
+            let study = create_roster_schedule_study(
+                String::from("Roster_Daily_Schedule"),
+                String::from("Compare quest assignment strategies for the full roster"),
+                20,
+                vec![
+                    heroes["Akana"].clone(),
+                    heroes["Tammy"].clone(),
+                    heroes["Lord_Control"].clone(),
+                    heroes["Daimyo-Atk_Control"].clone(),
+                ],
+                quests,
+                &dungeons,
+                2,
+                24.0 * 60.0,
+                vec![
+                    AssignmentStrategy::RoundRobin,
+                    AssignmentStrategy::MaxXpPerMinute,
+                    AssignmentStrategy::MaxLootPerMinute,
+                    AssignmentStrategy::SafestFirst,
+                ],
+                50,
+                hero_builder_information,
+            )
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+            Box::new(study)
+        }
+        Some(CliCommand::Coverage) => {
+            let study = create_elemental_coverage_study(
+                String::from("Lord_Daimyo_Elemental_Coverage"),
+                String::from("Elemental barrier coverage of the Lord/Daimyo duo"),
+                50,
+                vec![
+                    heroes["Lord_Control"].clone(),
+                    heroes["Daimyo-Atk_Control"].clone(),
+                ],
+                None,
+                dungeons
+                    .values()
+                    .map(|dungeon| {
+                        create_trial_dungeon(dungeon.clone(), 4, Some(false), None, None)
+                    })
+                    .collect(),
+                0.5,
+                result_format,
+                save_raw_results,
+                save_msgpack,
+                dataset_path,
+                hero_builder_information,
+            )
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+            println!("Elemental Trials to Run: {}", study.count_trials_total());
+            Box::new(study)
+        }
+        Some(CliCommand::Support) => {
+            let study = create_support_hero_skill_study(
+                String::from("Lord_Support_For_Daimyo"),
+                String::from("Optimize Lord as support for a Daimyo ATK carry"),
+                50,
+                heroes["Daimyo-Atk_Control"].clone(),
+                None,
+                valid_skills,
+                vec![],
+                heroes_from_builder["Lord_Control"].clone(),
+                vec![create_trial_dungeon(
+                    dungeons["Bleakspire Peak"].clone(),
+                    7,
+                    Some(false),
+                    None,
+                    None,
+                )],
+                result_format,
+                save_raw_results,
+                save_msgpack,
+                dataset_path,
+                hero_builder_information,
+            )
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+            println!(
+                "Skill Variations Remaining to Test: {}",
+                study.count_skill_variations_remaining()
+            );
+            Box::new(study)
+        }
+        None => {
+            let study = create_static_party_skill_study(
+                String::from("Daimyo_Atk_Main"),
+                String::from("Optimize Daimyo for ATK with Lord Duo"),
+                50,
+                100.0,
+                vec![heroes["Lord_Control"].clone()],
+                None,
+                valid_skills,
+                vec!["Sword Master".into(), "Warlord".into()],
+                heroes_from_builder["Daimyo-Atk_Test_Main"].clone(),
+                vec![create_trial_dungeon(
+                    dungeons["Bleakspire Peak"].clone(),
+                    7,
+                    Some(false),
+                    None,
+                    None,
+                )],
+                false,
+                result_format,
+                save_raw_results,
+                save_msgpack,
+                dataset_path,
+                hero_builder_information,
+            )
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+            println!(
+                "Skill Variations Remaining to Test: {}",
+                study.count_skill_variations_remaining()
+            );
+            Box::new(study)
+        }
+        Some(_) => unreachable!("only study commands create a study"),
+    };
+    study.get_study_mut().set_rng(rng);
+    study
+        .get_study_mut()
+        .set_statistics(statistics)
+        .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+    return study;
+}
+
+/// Attach the study to the queue, set its threads and ranking limit from the global options, then run it and print its summary
+pub fn run_study_from_args(mut study: Box<dyn Runnable>, options: &GlobalOptions) {
+    // Set by "queue run" for the study it runs, so the study can be stopped partway and later resumed
+    if let Some(queue_id) = options.queue_id {
+        study
+            .attach_to_queue(queue_id)
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Data, f!("study queue: {}", e)));
+    }
+    // Seeded results are the same for any thread qty
+    if let Some(thread_qty) = options.threads {
+        let thread_qty = match thread_qty {
+            0 => std::thread::available_parallelism().map_or(1, |qty| qty.get()),
+            qty => qty,
+        };
+        study
+            .set_thread_qty(thread_qty)
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+    }
+    // Success rates of every trial are still summarized, so sweeps too large to rank in memory run in constant memory
+    if let Some(ranking_limit) = options.ranking_limit {
+        study
+            .set_ranking_limit(ranking_limit)
+            .unwrap_or_else(|e| exit_with_error(ErrorKind::Input, e.to_string()));
+    }
+
+    set_error_kind(ErrorKind::Runtime);
+    let timer = std::time::Instant::now();
+    study.run();
+    print!("{}", study.get_study().fmt_summary(timer.elapsed()));
+}
@@ -0,0 +1,563 @@
+use std::collections::HashMap;
+
+use crate::{
+    dungeons::TrialDungeon,
+    equipment::get_higher_tier_items,
+    formatting::fmt_f64,
+    hero_builder::Hero,
+    heroes::{create_team, SimHero},
+    rng::RngConfig,
+    statistics::{fit_logistic_curve, two_proportion_z_test, StatisticsConfig, SIGNIFICANCE_LEVEL},
+    studies::HeroBuilderInformation,
+    trials::{create_trial, TrialResult, DIFFICULTY_NAMES},
+};
+
+/// Simulate a hero placed after the given party members against a dungeon, drawing from rng and computing intervals as statistics says
+pub fn run_hero_trial(
+    hero: SimHero,
+    party: Vec<SimHero>,
+    trial_dungeon: &TrialDungeon,
+    simulation_qty: usize,
+    rng: RngConfig,
+    statistics: StatisticsConfig,
+    hero_builder_information: &HeroBuilderInformation,
+) -> Result<TrialResult, &'static str> {
+    let mut heroes = party;
+    heroes.push(hero.clone());
+    let mut trial = create_trial(
+        String::from("Hero_Trial"),
+        hero.get_identifier(),
+        simulation_qty,
+        create_team(heroes, None)?,
+        trial_dungeon.dungeon.clone(),
+        vec![trial_dungeon.difficulty],
+        trial_dungeon.force_minibosses,
+        trial_dungeon.max_rounds,
+        trial_dungeon.initiative,
+        trial_dungeon.targeting,
+        false,
+    )?;
+    trial.set_reward_quest(
+        hero_builder_information
+            .find_reward_quest(
+                &trial_dungeon.dungeon._get_zone(),
+                &[trial_dungeon.difficulty],
+            )
+            .cloned(),
+    );
+    trial.set_rng(rng);
+    trial.set_statistics(statistics);
+    trial.run_simulations_single_threaded();
+    return Ok(trial.create_trial_result());
+}
+
+/// Returns the metrics shown when comparing results, with hero metrics for the hero at hero_index only
+fn get_comparison_metrics(result: &TrialResult, hero_index: usize) -> Vec<(&'static str, f64)> {
+    return vec![
+        ("Success Rate", result.get_success_rate()),
+        ("Wipe Rate", result.get_wipe_rate()),
+        ("Timeout Rate", result.get_timeout_rate()),
+        ("Average Rounds", result.get_average_rounds()),
+        ("Avg Polonia Loot", result.get_avg_polonia_loot()),
+        ("Cost Per Success", result.get_expected_cost_per_success()),
+        ("Loot Per Hour", result.get_loot_per_hour()),
+        ("Survival Rate", result.get_hero_survival_rate(hero_index)),
+        (
+            "Avg HP Remaining",
+            result.get_hero_avg_hp_remaining(hero_index),
+        ),
+        ("Avg Damage", result.get_hero_avg_dmg(hero_index)),
+        ("Avg Healing", result.get_hero_avg_healing(hero_index)),
+    ];
+}
+
+/// Format the key metrics of two results side by side with their difference, and whether the change in success rate is significant
+/// When there is no first result only the second result's column is filled in
+pub fn fmt_trial_result_comparison(
+    label_a: &str,
+    label_b: &str,
+    result_a: Option<&TrialResult>,
+    result_b: &TrialResult,
+    hero_index: usize,
+) -> String {
+    let column_width = [label_a, label_b]
+        .iter()
+        .map(|label| label.chars().count())
+        .max()
+        .unwrap_or_default()
+        .max(12)
+        + 2;
+    let mut res = format!(
+        "{:<18}{:>column_width$}{:>column_width$}{:>column_width$}\n",
+        "Metric",
+        label_a,
+        label_b,
+        "Change",
+        column_width = column_width
+    );
+    let metrics_a = result_a.map(|result_a| get_comparison_metrics(result_a, hero_index));
+    for (i, (label, b)) in get_comparison_metrics(result_b, hero_index)
+        .into_iter()
+        .enumerate()
+    {
+        let (a, change) = match &metrics_a {
+            Some(metrics_a) => (fmt_f64(metrics_a[i].1), fmt_f64(b - metrics_a[i].1)),
+            None => (String::from("-"), String::from("-")),
+        };
+        res += &format!(
+            "{:<18}{:>column_width$}{:>column_width$}{:>column_width$}\n",
+            label,
+            a,
+            fmt_f64(b),
+            change,
+            column_width = column_width
+        );
+    }
+    if let Some(result_a) = result_a {
+        if let Ok(test) = two_proportion_z_test(
+            result_a.get_success_rate(),
+            result_a.get_simulation_qty(),
+            result_b.get_success_rate(),
+            result_b.get_simulation_qty(),
+        ) {
+            res += &f!("Success rate change is {}\n", test.fmt_verdict());
+        }
+    }
+    return res;
+}
+
+/// Simulate each hero in the same party slot against the same dungeon and format their results side by side
+/// The compared hero is placed after the party members, and the hero metrics shown are for that hero only
+pub fn compare_heroes(
+    hero_a: SimHero,
+    hero_b: SimHero,
+    party: Vec<SimHero>,
+    trial_dungeon: &TrialDungeon,
+    simulation_qty: usize,
+    rng: RngConfig,
+    statistics: StatisticsConfig,
+    hero_builder_information: &HeroBuilderInformation,
+) -> Result<String, &'static str> {
+    let hbi = hero_builder_information;
+    let hero_index = party.len();
+    let result_a = run_hero_trial(
+        hero_a.clone(),
+        party.clone(),
+        trial_dungeon,
+        simulation_qty,
+        rng,
+        statistics,
+        hbi,
+    )?;
+    let result_b = run_hero_trial(
+        hero_b.clone(),
+        party,
+        trial_dungeon,
+        simulation_qty,
+        rng,
+        statistics,
+        hbi,
+    )?;
+
+    let mut res = f!(
+        "{} simulations per hero against {} (difficulty {})\n\n",
+        simulation_qty,
+        trial_dungeon.dungeon._get_zone(),
+        trial_dungeon.difficulty
+    );
+    res += &fmt_trial_result_comparison(
+        &hero_a.get_identifier(),
+        &hero_b.get_identifier(),
+        Some(&result_a),
+        &result_b,
+        hero_index,
+    );
+    return Ok(res);
+}
+
+/// Simulate a builder hero as built and with the item in each given slot (0-5) replaced by each of its higher tier successors
+/// Successors keep the item's quality and any sockets they can hold, and each is compared side by side with the hero as built
+pub fn compare_item_tiers(
+    hero: Hero,
+    slots: Vec<usize>,
+    tiers_up: u8,
+    party: Vec<SimHero>,
+    trial_dungeon: &TrialDungeon,
+    simulation_qty: usize,
+    rng: RngConfig,
+    statistics: StatisticsConfig,
+    hero_builder_information: &HeroBuilderInformation,
+) -> Result<String, String> {
+    let hbi = hero_builder_information;
+    let hero_index = party.len();
+    let result = run_hero_trial(
+        hbi.build_sim_hero(hero.clone())
+            .map_err(|e| e.to_string())?,
+        party.clone(),
+        trial_dungeon,
+        simulation_qty,
+        rng,
+        statistics,
+        hbi,
+    )?;
+
+    let mut res = f!(
+        "{} simulations per build of {} against {} (difficulty {})\n",
+        simulation_qty,
+        hero.get_identifier(),
+        trial_dungeon.dungeon._get_zone(),
+        trial_dungeon.difficulty
+    );
+    for slot in slots {
+        let current = hero.get_equipment_equipped()[slot].to_string();
+        if current.is_empty() {
+            continue;
+        }
+        let successors = get_higher_tier_items(&hbi.bp_map, &current, tiers_up);
+        if successors.is_empty() {
+            res += &f!(
+                "\nSlot {}: no higher tier items than {}\n",
+                slot + 1,
+                current
+            );
+            continue;
+        }
+        for successor in successors {
+            let blueprint = &hbi.bp_map[&successor];
+            let mut candidate = hero.clone();
+            candidate.set_equipment(slot, successor.to_string());
+            candidate.clear_ineligible_enchantments(slot, blueprint);
+            let successor_result = run_hero_trial(
+                hbi.build_sim_hero(candidate).map_err(|e| e.to_string())?,
+                party.clone(),
+                trial_dungeon,
+                simulation_qty,
+                rng,
+                statistics,
+                hbi,
+            )?;
+            res += &f!(
+                "\nSlot {}: {} (T{}) -> {} (T{})\n",
+                slot + 1,
+                current,
+                hbi.bp_map[&current].get_tier(),
+                successor,
+                blueprint.get_tier()
+            );
+            res += &fmt_trial_result_comparison(
+                &current,
+                &successor,
+                Some(&result),
+                &successor_result,
+                hero_index,
+            );
+        }
+    }
+    return Ok(res);
+}
+
+/// A single row of a trial results csv, identified independently of the run that produced it
+struct RankedResult {
+    key: String, // The build signature, dungeon, and difficulty of the trial
+    description: String,
+    success_rate: f64,
+    simulation_qty: Option<usize>, // Missing from csvs written before the simulation quantity was recorded
+    rank: usize,
+}
+
+/// Accepts a trial results csv, or a study directory containing one at csvs/trial_results.csv
+fn resolve_trial_results_path(path: &str) -> String {
+    let path = std::path::Path::new(path);
+    if path.is_dir() {
+        let nested = path.join("csvs").join("trial_results.csv");
+        if nested.exists() {
+            return nested.to_string_lossy().to_string();
+        }
+        return path.join("trial_results.csv").to_string_lossy().to_string();
+    }
+    return path.to_string_lossy().to_string();
+}
+
+/// Read a wide trial results csv, ranking its rows by success rate
+fn load_ranked_results(path: &str) -> Result<Vec<RankedResult>, csv::Error> {
+    let mut reader = csv::Reader::from_path(resolve_trial_results_path(path))?;
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let (
+        Some(description_col),
+        Some(signature_col),
+        Some(dungeon_col),
+        Some(difficulty_col),
+        Some(success_col),
+    ) = (
+        column("trial_description"),
+        column("build_signature"),
+        column("dungeon_identifier"),
+        column("difficulty_settings"),
+        column("success_rate"),
+    )
+    else {
+        return Err(csv::Error::from(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            f!("{} is not a wide format trial results csv", path),
+        )));
+    };
+
+    let simulation_qty_col = column("trial_simulation_qty");
+
+    let mut results: Vec<RankedResult> = vec![];
+    for record in reader.records() {
+        let record = record?;
+        results.push(RankedResult {
+            key: f!(
+                "{} @ {} {}",
+                &record[signature_col],
+                &record[dungeon_col],
+                &record[difficulty_col]
+            ),
+            description: record[description_col].to_string(),
+            success_rate: record[success_col].parse().unwrap_or_default(),
+            simulation_qty: simulation_qty_col.and_then(|col| record[col].parse().ok()),
+            rank: 0,
+        });
+    }
+    results.sort_by(|a, b| {
+        b.success_rate
+            .partial_cmp(&a.success_rate)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    for (i, result) in results.iter_mut().enumerate() {
+        result.rank = i + 1;
+    }
+    return Ok(results);
+}
+
+/// Compare the rankings of two trial result sets, matching trials by build signature, dungeon, and difficulty
+/// Rows are listed in the order of the second result set, followed by trials only present in the first
+/// The p-value of each change in success rate is shown when both csvs record their simulation quantities
+pub fn compare_result_sets(path_a: &str, path_b: &str) -> Result<String, csv::Error> {
+    let results_a = load_ranked_results(path_a)?;
+    let results_b = load_ranked_results(path_b)?;
+    let by_key_a: HashMap<&String, &RankedResult> = results_a
+        .iter()
+        .map(|result| (&result.key, result))
+        .collect();
+    let keys_b: Vec<&String> = results_b.iter().map(|result| &result.key).collect();
+
+    let fmt_rank = |result: Option<&RankedResult>| {
+        return result.map_or(String::from("-"), |result| result.rank.to_string());
+    };
+    let fmt_success = |result: Option<&RankedResult>| {
+        return result.map_or(String::from("-"), |result| fmt_f64(result.success_rate));
+    };
+
+    let fmt_p_value = |result_a: &RankedResult, result_b: &RankedResult| {
+        let (Some(qty_a), Some(qty_b)) = (result_a.simulation_qty, result_b.simulation_qty) else {
+            return (String::from("-"), false);
+        };
+        return match two_proportion_z_test(
+            result_a.success_rate,
+            qty_a,
+            result_b.success_rate,
+            qty_b,
+        ) {
+            Ok(test) => (f!("{:.3}", test.p_value), test.is_significant()),
+            Err(_) => (String::from("-"), false),
+        };
+    };
+
+    let mut res = format!(
+        "{:>7}{:>7}{:>8}{:>10}{:>10}{:>8}{:>8}  {}\n",
+        "Rank A", "Rank B", "Moved", "Success A", "Success B", "Change", "p", "Trial"
+    );
+    let mut in_both_qty = 0;
+    let mut moved_qty = 0;
+    let mut significant_qty = 0;
+    for result_b in results_b.iter() {
+        let result_a = by_key_a.get(&result_b.key).copied();
+        let (moved, change, p_value) = match result_a {
+            Some(result_a) => {
+                in_both_qty += 1;
+                if result_a.rank != result_b.rank {
+                    moved_qty += 1;
+                }
+                let (p_value, significant) = fmt_p_value(result_a, result_b);
+                if significant {
+                    significant_qty += 1;
+                }
+                (
+                    f!("{:+}", result_a.rank as i64 - result_b.rank as i64),
+                    fmt_f64(result_b.success_rate - result_a.success_rate),
+                    p_value,
+                )
+            }
+            None => (String::from("new"), String::from("-"), String::from("-")),
+        };
+        res += &format!(
+            "{:>7}{:>7}{:>8}{:>10}{:>10}{:>8}{:>8}  {}\n",
+            fmt_rank(result_a),
+            result_b.rank,
+            moved,
+            fmt_success(result_a),
+            fmt_f64(result_b.success_rate),
+            change,
+            p_value,
+            result_b.description
+        );
+    }
+    let only_a: Vec<&RankedResult> = results_a
+        .iter()
+        .filter(|result| !keys_b.contains(&&result.key))
+        .collect();
+    for result_a in only_a.iter() {
+        res += &format!(
+            "{:>7}{:>7}{:>8}{:>10}{:>10}{:>8}{:>8}  {}\n",
+            result_a.rank,
+            "-",
+            "dropped",
+            fmt_f64(result_a.success_rate),
+            "-",
+            "-",
+            "-",
+            result_a.description
+        );
+    }
+
+    res += &f!(
+        "\n{} trials in both, {} changed rank, {} changed success rate significantly (p < {}), {} only in A, {} only in B\n",
+        in_both_qty,
+        moved_qty,
+        significant_qty,
+        SIGNIFICANCE_LEVEL,
+        only_a.len(),
+        results_b.len() - in_both_qty
+    );
+    return Ok(res);
+}
+
+/// A build's trials against one dungeon at a single difficulty each, from a trial results csv
+struct TierPoints {
+    description: String, // The description of the build's first trial
+    dungeon: String,
+    points: Vec<(f64, usize, usize)>, // (difficulty tier, successes, simulation qty)
+}
+
+/// Read a wide trial results csv, grouping trials run at a single difficulty by build signature and dungeon, in order of first appearance
+fn load_tier_points(path: &str) -> Result<Vec<TierPoints>, csv::Error> {
+    let mut reader = csv::Reader::from_path(resolve_trial_results_path(path))?;
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let (
+        Some(description_col),
+        Some(signature_col),
+        Some(dungeon_col),
+        Some(difficulty_col),
+        Some(success_col),
+        Some(simulation_qty_col),
+    ) = (
+        column("trial_description"),
+        column("build_signature"),
+        column("dungeon_identifier"),
+        column("difficulty_settings"),
+        column("success_rate"),
+        column("trial_simulation_qty"),
+    )
+    else {
+        return Err(csv::Error::from(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            f!("{} is not a wide format trial results csv", path),
+        )));
+    };
+
+    let mut groups: Vec<TierPoints> = vec![];
+    let mut group_indices: HashMap<(String, String), usize> = HashMap::new();
+    for record in reader.records() {
+        let record = record?;
+        let difficulty = record[difficulty_col]
+            .trim_start_matches("[\"")
+            .trim_end_matches("\"]");
+        let Some(tier) = DIFFICULTY_NAMES.iter().position(|name| *name == difficulty) else {
+            continue;
+        };
+        let simulation_qty: usize = record[simulation_qty_col].parse().unwrap_or_default();
+        let success_rate: f64 = record[success_col].parse().unwrap_or_default();
+        let key = (
+            record[signature_col].to_string(),
+            record[dungeon_col].to_string(),
+        );
+        let i = *group_indices.entry(key).or_insert_with(|| {
+            groups.push(TierPoints {
+                description: record[description_col].to_string(),
+                dungeon: record[dungeon_col].to_string(),
+                points: vec![],
+            });
+            groups.len() - 1
+        });
+        groups[i].points.push((
+            (tier + 1) as f64,
+            (success_rate * simulation_qty as f64).round() as usize,
+            simulation_qty,
+        ));
+    }
+    return Ok(groups);
+}
+
+/// Fit a logistic curve of success rate against difficulty tier (1-8) for each build and dungeon in a trial results csv
+/// Each build is summarized by the interpolated tier it reaches target_success_rate at, so builds can be ranked by a single number
+/// Tiers outside the range tested are reported as above or below it rather than extrapolated
+pub fn fit_tier_curves(path: &str, target_success_rate: f64) -> Result<String, csv::Error> {
+    let mut rows: Vec<(f64, String, String, String, String)> = vec![];
+    for group in load_tier_points(path)? {
+        let tiers: Vec<f64> = group.points.iter().map(|(tier, _, _)| *tier).collect();
+        let lowest = tiers.iter().copied().fold(f64::INFINITY, f64::min);
+        let highest = tiers.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let tested = f!("{}-{}", lowest, highest);
+        let (sort_key, tier_at_target, fit) = match fit_logistic_curve(&group.points) {
+            Ok(curve) => {
+                let fit = f!(
+                    "{} at T{}, {} at T{}",
+                    fmt_f64(curve.predict(lowest)),
+                    lowest,
+                    fmt_f64(curve.predict(highest)),
+                    highest
+                );
+                match curve.solve(target_success_rate) {
+                    Some(tier) if tier > highest => (highest + 0.5, f!("> {}", highest), fit),
+                    Some(tier) if tier < lowest => (lowest - 0.5, f!("< {}", lowest), fit),
+                    Some(tier) => (tier, fmt_f64(tier), fit),
+                    None => (f64::NEG_INFINITY, String::from("-"), fit),
+                }
+            }
+            Err(_) => (
+                f64::NEG_INFINITY,
+                String::from("-"),
+                String::from("needs 2+ tiers"),
+            ),
+        };
+        rows.push((
+            sort_key,
+            tier_at_target,
+            tested,
+            fit,
+            f!("{} @ {}", group.description, group.dungeon),
+        ));
+    }
+    rows.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let target_label = f!("Tier at {}%", target_success_rate * 100.0);
+    let mut res = format!(
+        "{:>14}{:>8}  {:<28}  {}\n",
+        target_label, "Tested", "Fitted Success", "Trial"
+    );
+    for (_, tier_at_target, tested, fit, trial) in rows.iter() {
+        res += &format!(
+            "{:>14}{:>8}  {:<28}  {}\n",
+            tier_at_target, tested, fit, trial
+        );
+    }
+    res += &f!(
+        "\n{} builds fit, difficulty tiers are 1-4 for Easy to Extreme and 5-8 for Boss Easy to Boss Extreme\n",
+        rows.len()
+    );
+    return Ok(res);
+}
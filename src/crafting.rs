@@ -0,0 +1,223 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::formatting::{create_stats_view, StatsView};
+
+/// Defines the qualities a crafted item can come out as, from worst to best
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    PartialOrd,
+    Ord,
+    Hash,
+    strum::Display,
+    strum::EnumString,
+    strum::EnumIter,
+)]
+pub enum ItemQuality {
+    Normal,
+    Superior,
+    Flawless,
+    Epic,
+    Legendary,
+}
+
+/// The approximate chance of a craft with no quality bonuses being at least Superior, Flawless, Epic, and Legendary
+pub const BASE_QUALITY_CHANCES: [f64; 4] = [0.2, 0.04, 0.01, 0.002];
+
+/// The chances of each quality when crafting an item
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct CraftQualityOdds {
+    at_least_chances: [f64; 4], // The chance of a craft being at least Superior, Flawless, Epic, and Legendary
+}
+
+/// Quality bonuses, such as from workers and furniture, multiply every base chance by (1 + quality_bonus)
+/// A better quality can never be more likely than a worse one, so chances are capped by the chance of the quality below
+pub fn create_craft_quality_odds(
+    base_chances: [f64; 4],
+    quality_bonus: f64,
+) -> Result<CraftQualityOdds, &'static str> {
+    if base_chances
+        .iter()
+        .any(|chance| !(0.0..=1.0).contains(chance))
+    {
+        return Err("base chances must be within range 0-1 inclusive");
+    }
+    if quality_bonus < 0.0 {
+        return Err("quality_bonus must be >= 0");
+    }
+    let mut at_least_chances = [0.0; 4];
+    let mut cap = 1.0f64;
+    for (i, chance) in base_chances.iter().enumerate() {
+        at_least_chances[i] = f64::min(chance * (1.0 + quality_bonus), cap);
+        cap = at_least_chances[i];
+    }
+    return Ok(CraftQualityOdds { at_least_chances });
+}
+
+impl CraftQualityOdds {
+    /// Returns the chance of a craft being the given quality or better
+    pub fn get_chance_at_least(&self, quality: ItemQuality) -> f64 {
+        return match quality {
+            ItemQuality::Normal => 1.0,
+            _ => self.at_least_chances[quality as usize - 1],
+        };
+    }
+
+    /// Returns the chance of a craft being exactly the given quality
+    pub fn get_chance(&self, quality: ItemQuality) -> f64 {
+        let better = ItemQuality::iter()
+            .find(|other| *other > quality)
+            .map_or(0.0, |better| self.get_chance_at_least(better));
+        return self.get_chance_at_least(quality) - better;
+    }
+
+    /// Returns the expected number of crafts until one is at least the given quality, infinite if it can't be crafted
+    pub fn calculate_expected_crafts(&self, quality: ItemQuality) -> f64 {
+        let chance = self.get_chance_at_least(quality);
+        if chance <= 0.0 {
+            return f64::INFINITY;
+        }
+        return 1.0 / chance;
+    }
+
+    /// Returns the number of crafts needed for the given chance (0-1) of at least one being at least the given quality
+    pub fn calculate_crafts_for_confidence(
+        &self,
+        quality: ItemQuality,
+        confidence: f64,
+    ) -> Option<u32> {
+        let chance = self.get_chance_at_least(quality);
+        if chance <= 0.0 {
+            return None;
+        }
+        if chance >= 1.0 {
+            return Some(1);
+        }
+        return Some(
+            ((1.0 - confidence).ln() / (1.0 - chance).ln())
+                .ceil()
+                .max(1.0) as u32,
+        );
+    }
+
+    /// Roll the quality of a single craft
+    pub fn roll_quality(&self, rng: &mut impl Rng) -> ItemQuality {
+        let roll = rng.gen::<f64>();
+        return ItemQuality::iter()
+            .rev()
+            .find(|quality| roll < self.get_chance_at_least(*quality))
+            .unwrap_or(ItemQuality::Normal);
+    }
+
+    /// Simulate the given number of crafts, returning how many came out as each quality from Normal to Legendary
+    pub fn simulate_crafts(&self, craft_qty: usize, rng: &mut impl Rng) -> [usize; 5] {
+        let mut counts = [0usize; 5];
+        for _ in 0..craft_qty {
+            counts[self.roll_quality(rng) as usize] += 1;
+        }
+        return counts;
+    }
+
+    /// Format the chance of each quality alongside its simulated frequency over craft_qty crafts, and the crafts expected to reach it
+    pub fn fmt_report(&self, craft_qty: usize, rng: &mut impl Rng) -> String {
+        let counts = self.simulate_crafts(craft_qty, rng);
+        let mut res = f!(
+            "{:<10} {:>9} {:>10} {:>10} {:>16} {:>16}\n",
+            "Quality",
+            "Chance",
+            "At Least",
+            "Simulated",
+            "Expected Crafts",
+            "Crafts For 90%"
+        );
+        for quality in ItemQuality::iter() {
+            res += &f!(
+                "{:<10} {:>8.2}% {:>9.2}% {:>9.2}% {:>16.1} {:>16}\n",
+                quality.to_string(),
+                self.get_chance(quality) * 100.0,
+                self.get_chance_at_least(quality) * 100.0,
+                counts[quality as usize] as f64 / (craft_qty as f64).max(1.0) * 100.0,
+                self.calculate_expected_crafts(quality),
+                self.calculate_crafts_for_confidence(quality, 0.9)
+                    .map_or(String::from("-"), |crafts| crafts.to_string())
+            );
+        }
+        return res;
+    }
+
+    /// Format the crafts expected to reach each item's quality target, such as the qualities of a hero's equipped gear
+    pub fn fmt_targets(&self, title: String, targets: &[(String, ItemQuality)]) -> StatsView {
+        let mut view = create_stats_view(title);
+        for (item, quality) in targets {
+            view = view.text(
+                item,
+                f!(
+                    "{} ({:.1} crafts expected, {} for 90%)",
+                    quality,
+                    self.calculate_expected_crafts(*quality),
+                    self.calculate_crafts_for_confidence(*quality, 0.9)
+                        .map_or(String::from("-"), |crafts| crafts.to_string())
+                ),
+            );
+        }
+        return view;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{create_sim_rng, RngConfig};
+
+    #[test]
+    fn quality_bonuses_raise_chances_without_inverting_qualities() {
+        let odds = create_craft_quality_odds([0.5, 0.4, 0.1, 0.01], 1.0).unwrap();
+
+        assert_eq!(odds.get_chance_at_least(ItemQuality::Normal), 1.0);
+        assert_eq!(odds.get_chance_at_least(ItemQuality::Superior), 1.0);
+        assert_eq!(odds.get_chance_at_least(ItemQuality::Flawless), 0.8);
+        assert_eq!(odds.get_chance_at_least(ItemQuality::Epic), 0.2);
+        assert_eq!(odds.get_chance_at_least(ItemQuality::Legendary), 0.02);
+        assert_eq!(odds.get_chance(ItemQuality::Normal), 0.0);
+        let total: f64 = ItemQuality::iter().map(|q| odds.get_chance(q)).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        assert!(create_craft_quality_odds([0.5, 0.4, 0.1, 1.5], 0.0).is_err());
+        assert!(create_craft_quality_odds(BASE_QUALITY_CHANCES, -0.5).is_err());
+    }
+
+    #[test]
+    fn expected_crafts_follow_the_chance_of_each_quality() {
+        let odds = create_craft_quality_odds([0.5, 0.25, 0.1, 0.0], 0.0).unwrap();
+
+        assert_eq!(odds.calculate_expected_crafts(ItemQuality::Flawless), 4.0);
+        assert_eq!(
+            odds.calculate_expected_crafts(ItemQuality::Legendary),
+            f64::INFINITY
+        );
+        assert_eq!(
+            odds.calculate_crafts_for_confidence(ItemQuality::Superior, 0.75),
+            Some(2)
+        );
+        assert_eq!(
+            odds.calculate_crafts_for_confidence(ItemQuality::Legendary, 0.75),
+            None
+        );
+
+        let mut rng = create_sim_rng(RngConfig {
+            seed: Some(7),
+            ..Default::default()
+        });
+        let counts = odds.simulate_crafts(10000, &mut rng);
+        assert_eq!(counts.iter().sum::<usize>(), 10000);
+        assert_eq!(counts[ItemQuality::Legendary as usize], 0);
+        assert!((4500..5500).contains(&counts[ItemQuality::Normal as usize]));
+    }
+}
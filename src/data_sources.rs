@@ -0,0 +1,354 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{Cursor, Read},
+    path::PathBuf,
+};
+
+use crate::{
+    champions::{Champion, ChampionData},
+    dungeons::Dungeon,
+    equipment::{
+        apply_blueprint_export, apply_enchant_eligibility, load_blueprints_from_export, Blueprint,
+        EnchantEligibility,
+    },
+    hero_builder::{Element, HeroClass, SeedValues, Spirit},
+    inputs::{
+        load_bosses_from_yaml, load_champions_from_yaml, load_dungeons_from_csv,
+        load_dungeons_from_yaml, load_elements_from_yaml, load_enchant_eligibility_from_yaml,
+        load_hero_classes_from_yaml, load_pet_skills_from_yaml, load_quest_lines_from_yaml,
+        load_quests_from_yaml, load_seed_values_from_yaml, load_skill_effects_from_yaml,
+        load_spirits_from_yaml, BossInput,
+    },
+    quests::{Quest, QuestLine},
+    sheet_processing::{
+        _get_hero_equipment_data, _get_hero_skills_data, _get_innate_skills_data, HeroSkillsData,
+        InnateSkillsData,
+    },
+    skills::{apply_skill_effects, PetSkill},
+};
+
+pub const HERO_SKILLS_SHEET: &str = "data_sheets/greensim_hero_skills_v_10.2.1_slash_1.0.1.773.tsv";
+pub const INNATE_SKILLS_SHEET: &str =
+    "data_sheets/greensim_innate_skills_v_10.2.1_slash_1.0.1.773.tsv";
+pub const BLUEPRINTS_SHEET: &str = "data_sheets/blueprints_v_11.1.1_slash_1.0.1.868.tsv";
+
+/// Where game data is loaded from. Files are named by their path from the repository root, such as "input/dungeons.yaml",
+/// so a source only has to provide the files, and every source shares the same parsing
+/// Like the loaders, the getters return an error naming the file on missing or invalid data
+pub trait DataSource {
+    /// Returns a reader of the named file, or None if the source doesn't have it
+    fn open(&self, name: &str) -> Result<Option<Box<dyn Read>>, std::io::Error>;
+
+    /// Returns a reader of a file the simulator can't run without
+    fn open_required(&self, name: &str) -> Result<Box<dyn Read>, String> {
+        return self
+            .open_optional(name)?
+            .ok_or_else(|| f!("{} not found", name));
+    }
+
+    /// Returns a reader of a file that is allowed to be missing
+    fn open_optional(&self, name: &str) -> Result<Option<Box<dyn Read>>, String> {
+        return self
+            .open(name)
+            .map_err(|e| f!("could not read {}: {}", name, e));
+    }
+
+    /// Blueprints keyed by name, with the enchantments each item can hold applied
+    /// A blueprint export in the optional input/blueprints.csv refreshes the data sheet's blueprints, such as after a game patch
+    fn get_blueprints(&self) -> Result<HashMap<String, Blueprint>, String> {
+        let mut bp_map = _get_hero_equipment_data(self.open_required(BLUEPRINTS_SHEET)?)
+            .map_err(|e| f!("{}: {}", BLUEPRINTS_SHEET, e))?;
+        if let Some(reader) = self.open_optional("input/blueprints.csv")? {
+            let blueprints = load_blueprints_from_export(reader)
+                .map_err(|e| f!("input/blueprints.csv: {}", e))?;
+            apply_blueprint_export(&mut bp_map, blueprints);
+        }
+        apply_enchant_eligibility(&mut bp_map, &self.get_enchant_eligibility()?);
+        return Ok(bp_map);
+    }
+
+    /// Which enchantments items can hold, keyed by blueprint name or item type. Every item can hold every enchantment if the file is missing
+    fn get_enchant_eligibility(&self) -> Result<HashMap<String, EnchantEligibility>, String> {
+        return load_optional(
+            self,
+            "input/enchant_eligibility.yaml",
+            load_enchant_eligibility_from_yaml,
+        );
+    }
+
+    /// Classes keyed by name, with the champions that champions.yaml gives base stats, an element type, and equipment for added as classes
+    fn get_hero_classes(&self) -> Result<HashMap<String, HeroClass>, String> {
+        let mut hero_classes =
+            load_hero_classes_from_yaml(self.open_required("input/hero_classes.yaml")?)
+                .map_err(|e| f!("input/hero_classes.yaml: {}", e))?;
+        for (champion, data) in self.get_champion_data()? {
+            if let Some(hero_class) = data.create_hero_class(champion) {
+                if hero_classes.contains_key(&champion.to_string()) {
+                    return Err(f!(
+                        "{} is in both hero_classes.yaml and champions.yaml",
+                        champion
+                    ));
+                }
+                hero_classes.insert(champion.to_string(), hero_class);
+            }
+        }
+        return Ok(hero_classes);
+    }
+
+    /// The names of each hero skill's first tier, and the hero skills, keyed by skill name
+    /// Skills named in the optional input/skill_effects.yaml are given their combat effects
+    fn get_hero_skills(&self) -> Result<HeroSkillsData, String> {
+        let (hero_skill_tier_1_name_map, mut hero_skill_map) =
+            _get_hero_skills_data(self.open_required(HERO_SKILLS_SHEET)?)
+                .map_err(|e| f!("{}: {}", HERO_SKILLS_SHEET, e))?;
+        if let Some(reader) = self.open_optional("input/skill_effects.yaml")? {
+            load_skill_effects_from_yaml(reader)
+                .and_then(|skill_effects| apply_skill_effects(&mut hero_skill_map, skill_effects))
+                .map_err(|e| f!("input/skill_effects.yaml: {}", e))?;
+        }
+        return Ok((hero_skill_tier_1_name_map, hero_skill_map));
+    }
+
+    /// The names of each innate skill's first tier, the first innate skill of each class, and the innate skills, keyed by skill name
+    fn get_innate_skills(&self) -> Result<InnateSkillsData, String> {
+        return _get_innate_skills_data(self.open_required(INNATE_SKILLS_SHEET)?)
+            .map_err(|e| f!("{}: {}", INNATE_SKILLS_SHEET, e));
+    }
+
+    /// Pet skills keyed by the blueprint name of the familiar that grants them. Pets are optional, so a missing file loads none
+    fn get_pet_skills(&self) -> Result<HashMap<String, PetSkill>, String> {
+        return load_optional(self, "input/pet_skills.yaml", load_pet_skills_from_yaml);
+    }
+
+    /// The bonuses each kind of spirit gives the whole hero, keyed by spirit name without its tier
+    fn get_spirits(&self) -> Result<HashMap<String, Spirit>, String> {
+        return load_spirits_from_yaml(self.open_required("input/spirits.yaml")?)
+            .map_err(|e| f!("input/spirits.yaml: {}", e));
+    }
+
+    /// The stats each seed adds, the game's values if the source doesn't have any
+    fn get_seed_values(&self) -> Result<SeedValues, String> {
+        return load_optional(self, "input/seeds.yaml", load_seed_values_from_yaml);
+    }
+
+    /// The flat stats elements of each grade add to their items
+    fn get_elements(&self) -> Result<Vec<Element>, String> {
+        return load_elements_from_yaml(self.open_required("input/elements.yaml")?)
+            .map_err(|e| f!("input/elements.yaml: {}", e));
+    }
+
+    /// Champions missing from the data have no ability effects
+    fn get_champion_data(&self) -> Result<HashMap<Champion, ChampionData>, String> {
+        return load_optional(self, "input/champions.yaml", load_champions_from_yaml);
+    }
+
+    /// Bosses that dungeons can reference by name. Bosses are optional, so a missing file loads none
+    fn get_bosses(&self) -> Result<HashMap<String, BossInput>, String> {
+        return load_optional(self, "input/bosses.yaml", load_bosses_from_yaml);
+    }
+
+    /// Dungeons keyed by zone, with the boss fields of dungeons that name a boss taken from the bosses
+    /// Dungeons can also be given in the optional dungeons.csv, laid out like the community dungeon spreadsheet, but a zone can't be in both
+    fn get_dungeons(&self) -> Result<HashMap<String, Dungeon>, String> {
+        let mut dungeons = load_dungeons_from_yaml(
+            self.open_required("input/dungeons.yaml")?,
+            &self.get_bosses()?,
+        )
+        .map_err(|e| f!("input/dungeons.yaml: {}", e))?;
+        if let Some(reader) = self.open_optional("input/dungeons.csv")? {
+            for (zone, dungeon) in
+                load_dungeons_from_csv(reader).map_err(|e| f!("input/dungeons.csv: {}", e))?
+            {
+                if dungeons.contains_key(&zone) {
+                    return Err(f!("{} is in both dungeons.yaml and dungeons.csv", zone));
+                }
+                dungeons.insert(zone, dungeon);
+            }
+        }
+        return Ok(dungeons);
+    }
+
+    /// Quests keyed by identifier, or None if the source has no quests
+    fn get_quests(&self) -> Result<Option<HashMap<String, Quest>>, String> {
+        return self
+            .open_optional("input/quests.yaml")?
+            .map(|reader| load_quests_from_yaml(reader).map_err(|e| f!("input/quests.yaml: {}", e)))
+            .transpose();
+    }
+
+    fn get_quest_lines(&self) -> Result<HashMap<String, QuestLine>, String> {
+        return load_quest_lines_from_yaml(self.open_required("input/quest_lines.yaml")?)
+            .map_err(|e| f!("input/quest_lines.yaml: {}", e));
+    }
+}
+
+/// Load an optional file with the given loader, or the default value if the source doesn't have it
+fn load_optional<T: Default, R: DataSource + ?Sized>(
+    data_source: &R,
+    name: &str,
+    loader: fn(Box<dyn Read>) -> Result<T, String>,
+) -> Result<T, String> {
+    return match data_source.open_optional(name)? {
+        Some(reader) => loader(reader).map_err(|e| f!("{}: {}", name, e)),
+        None => Ok(T::default()),
+    };
+}
+
+/// Loads game data from files under a root directory, as the command line tool does by default
+pub struct FileDataSource {
+    root: PathBuf,
+}
+
+pub fn create_file_data_source(root: &str) -> FileDataSource {
+    return FileDataSource {
+        root: PathBuf::from(root),
+    };
+}
+
+impl DataSource for FileDataSource {
+    fn open(&self, name: &str) -> Result<Option<Box<dyn Read>>, std::io::Error> {
+        let path = self.root.join(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        return Ok(Some(Box::new(std::fs::File::open(path)?)));
+    }
+}
+
+/// Copy the data sheets, every yaml file of the input folder, dungeons.csv, and blueprints.csv from one checkout to another, returning the files copied
+/// Hero csvs are left alone, since they hold the player's own heroes rather than game data
+pub fn import_game_data(
+    source_root: &str,
+    target_root: &str,
+) -> Result<Vec<String>, std::io::Error> {
+    let mut names: Vec<String> = vec![
+        String::from(HERO_SKILLS_SHEET),
+        String::from(INNATE_SKILLS_SHEET),
+        String::from(BLUEPRINTS_SHEET),
+    ];
+    let mut input_names: Vec<String> = std::fs::read_dir(PathBuf::from(source_root).join("input"))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|file_name| {
+            file_name.ends_with(".yaml")
+                || file_name == "dungeons.csv"
+                || file_name == "blueprints.csv"
+        })
+        .map(|file_name| f!("input/{}", file_name))
+        .collect();
+    input_names.sort();
+    names.extend(input_names);
+
+    for name in &names {
+        let target = PathBuf::from(target_root).join(name);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(PathBuf::from(source_root).join(name), target)?;
+    }
+    return Ok(names);
+}
+
+/// Holds game data in memory, such as files compiled into an application with include_bytes, or built by a test
+pub struct EmbeddedDataSource {
+    files: HashMap<String, Vec<u8>>, // The contents of each file, keyed by name
+}
+
+pub fn create_embedded_data_source(files: HashMap<String, Vec<u8>>) -> EmbeddedDataSource {
+    return EmbeddedDataSource { files };
+}
+
+impl DataSource for EmbeddedDataSource {
+    fn open(&self, name: &str) -> Result<Option<Box<dyn Read>>, std::io::Error> {
+        return Ok(self
+            .files
+            .get(name)
+            .map(|contents| Box::new(Cursor::new(contents.clone())) as Box<dyn Read>));
+    }
+}
+
+/// Fetches game data from a url that mirrors the repository's layout, such as a raw file host, when built with the remote-data feature
+/// Each file is fetched at most once, and a 404 is treated as the file being missing
+pub struct RemoteDataSource {
+    base_url: String,
+    fetched: RefCell<HashMap<String, Option<Vec<u8>>>>, // The contents of each file fetched so far, None if it was missing
+}
+
+pub fn create_remote_data_source(base_url: &str) -> RemoteDataSource {
+    return RemoteDataSource {
+        base_url: base_url.trim_end_matches('/').to_string(),
+        fetched: RefCell::new(HashMap::new()),
+    };
+}
+
+impl RemoteDataSource {
+    /// Fetch a file, returning its contents or None if the server doesn't have it
+    #[cfg(feature = "remote-data")]
+    fn fetch(&self, name: &str) -> Result<Option<Vec<u8>>, std::io::Error> {
+        let url = f!("{}/{}", self.base_url, name);
+        let response = match ureq::get(&url).call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(404, _)) => return Ok(None),
+            Err(ureq::Error::Status(code, _)) => {
+                return Err(std::io::Error::other(f!("{}: status {}", url, code)));
+            }
+            Err(e) => return Err(std::io::Error::other(f!("{}: {}", url, e))),
+        };
+        let mut body: Vec<u8> = vec![];
+        response.into_reader().read_to_end(&mut body)?;
+        return Ok(Some(body));
+    }
+
+    /// Without the remote-data feature there is no http client to fetch with
+    #[cfg(not(feature = "remote-data"))]
+    fn fetch(&self, name: &str) -> Result<Option<Vec<u8>>, std::io::Error> {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            f!(
+                "{}/{}: fetching game data from a url needs st_sim built with --features remote-data",
+                self.base_url,
+                name
+            ),
+        ));
+    }
+}
+
+impl DataSource for RemoteDataSource {
+    fn open(&self, name: &str) -> Result<Option<Box<dyn Read>>, std::io::Error> {
+        if !self.fetched.borrow().contains_key(name) {
+            let contents = self.fetch(name)?;
+            self.fetched.borrow_mut().insert(name.to_string(), contents);
+        }
+        return Ok(self.fetched.borrow()[name]
+            .clone()
+            .map(|contents| Box::new(Cursor::new(contents)) as Box<dyn Read>));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn malformed_data_is_an_error_naming_the_file() {
+        let data_source = create_embedded_data_source(HashMap::from([
+            (
+                String::from("input/spirits.yaml"),
+                b"Wolf: [not a spirit".to_vec(),
+            ),
+            (String::from("input/seeds.yaml"), b"atk: -1".to_vec()),
+        ]));
+        assert!(data_source
+            .get_spirits()
+            .is_err_and(|e| e.starts_with("input/spirits.yaml: ")));
+        assert!(data_source
+            .get_seed_values()
+            .is_err_and(|e| e.starts_with("input/seeds.yaml: ")));
+        assert_eq!(
+            data_source.get_elements(),
+            Err(String::from("input/elements.yaml not found"))
+        );
+        assert!(crate::simulator::load_game_data(&data_source).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{
+    dungeons::TrialDungeon,
+    heroes::{create_team, SimHero},
+    rng::RngConfig,
+    trials::{create_trial, SimulationDivergence},
+};
+
+/// The outcome of running the same seeded trial serially and in parallel
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeterminismReport {
+    pub seed: u64,
+    pub simulation_qty: usize,
+    pub thread_qty: usize,
+    pub divergence: Option<SimulationDivergence>, // The first difference between the serial and parallel runs, None if every simulation matched
+}
+
+impl DeterminismReport {
+    pub fn is_deterministic(&self) -> bool {
+        return self.divergence.is_none();
+    }
+
+    pub fn fmt_report(&self) -> String {
+        let res = f!(
+            "Ran {} simulations with seed {} serially and on {} threads\n",
+            self.simulation_qty,
+            self.seed,
+            self.thread_qty
+        );
+        return match &self.divergence {
+            None => res + "Every simulation matched\n",
+            Some(divergence) => {
+                res + &f!(
+                    "First divergence at simulation {}, {}: {} serially, {} in parallel\n",
+                    divergence.simulation,
+                    divergence.field,
+                    divergence.value,
+                    divergence.other_value
+                )
+            }
+        };
+    }
+}
+
+/// Run a trial of the heroes twice from the same seed, once on the current thread and once split across thread_qty threads,
+/// and compare every simulation's raw result, so anything that makes results depend on more than the seed is caught
+/// An unseeded rng config is checked with seed 0
+pub fn verify_determinism(
+    heroes: Vec<SimHero>,
+    trial_dungeon: &TrialDungeon,
+    simulation_qty: usize,
+    rng: RngConfig,
+    thread_qty: usize,
+) -> Result<DeterminismReport, &'static str> {
+    if thread_qty < 2 {
+        return Err("thread_qty must be > 1 to compare against a serial run");
+    }
+    let mut serial_trial = create_trial(
+        String::from("Determinism_Trial"),
+        String::from("Determinism check"),
+        simulation_qty,
+        create_team(heroes, None)?,
+        trial_dungeon.dungeon.clone(),
+        vec![trial_dungeon.difficulty],
+        trial_dungeon.force_minibosses,
+        trial_dungeon.max_rounds,
+        trial_dungeon.initiative,
+        trial_dungeon.targeting,
+        false,
+    )?;
+    let seed = rng.seed.unwrap_or(0);
+    serial_trial.set_rng(RngConfig {
+        seed: Some(seed),
+        ..rng
+    });
+    let mut parallel_trial = serial_trial.clone();
+    serial_trial.run_simulations_in_streams(1);
+    parallel_trial.run_simulations_in_streams(thread_qty);
+
+    return Ok(DeterminismReport {
+        seed,
+        simulation_qty,
+        thread_qty,
+        divergence: serial_trial.find_first_divergence(&parallel_trial),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_data::{create_test_party, create_test_trial_dungeon},
+        trials::Trial,
+    };
+
+    fn load_party_and_dungeon() -> (Vec<SimHero>, TrialDungeon) {
+        return (
+            create_test_party(&["Hero_1", "Hero_2", "Hero_3"]),
+            create_test_trial_dungeon(1),
+        );
+    }
+
+    #[test]
+    fn thread_count_does_not_change_seeded_results() {
+        let (party, trial_dungeon) = load_party_and_dungeon();
+        for thread_qty in [2, 3, 8] {
+            let report = verify_determinism(
+                party.clone(),
+                &trial_dungeon,
+                50,
+                RngConfig {
+                    seed: Some(42),
+                    ..Default::default()
+                },
+                thread_qty,
+            )
+            .unwrap();
+            assert!(report.is_deterministic(), "{}", report.fmt_report());
+        }
+    }
+
+    fn run_seeded_trial(party: Vec<SimHero>, trial_dungeon: &TrialDungeon, seed: u64) -> Trial {
+        let mut trial = create_trial(
+            String::from("Seeded_Trial"),
+            String::from("Seeded trial"),
+            50,
+            create_team(party, None).unwrap(),
+            trial_dungeon.dungeon.clone(),
+            vec![trial_dungeon.difficulty],
+            trial_dungeon.force_minibosses,
+            trial_dungeon.max_rounds,
+            trial_dungeon.initiative,
+            trial_dungeon.targeting,
+            false,
+        )
+        .unwrap();
+        trial.set_rng(RngConfig {
+            seed: Some(seed),
+            ..Default::default()
+        });
+        trial.run_simulations_single_threaded();
+        return trial;
+    }
+
+    #[test]
+    fn same_seed_gives_identical_results() {
+        let (party, trial_dungeon) = load_party_and_dungeon();
+        let trial = run_seeded_trial(party.clone(), &trial_dungeon, 7);
+
+        let rerun = run_seeded_trial(party.clone(), &trial_dungeon, 7);
+        assert_eq!(trial.find_first_divergence(&rerun), None);
+        let other_seed = run_seeded_trial(party, &trial_dungeon, 8);
+        assert!(trial.find_first_divergence(&other_seed).is_some());
+    }
+}
@@ -1,14 +1,20 @@
-use crate::decimals::round_to_2;
+use crate::abilities::{create_enemy_ability, create_enrage, EnemyAbility, Enrage, EnrageEffect};
+use crate::formatting::{create_stats_view, StatsView};
 use crate::inputs::{create_dungeon_input, DungeonInput};
+use crate::simulations::{InitiativeModel, TargetingModel, DEFAULT_MAX_ROUNDS};
 
 use super::equipment::ElementType;
 
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
-use serde::{Deserialize, Serialize};
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
 
+use std::collections::HashMap;
 use std::string::ToString;
 
+/// The element quantity at which a hero receives the full effect of an enemy's elemental resistance or weakness
+pub const ELEMENTAL_FULL_EFFECT_QTY: f64 = 100.0;
+
 /// Defines the valid types of mini boss
 pub enum MiniBossType {
     Agile,
@@ -28,6 +34,52 @@ impl Distribution<MiniBossType> for Standard {
     }
 }
 
+/// The resource spent to enter a dungeon at each difficulty, such as keys for limited content
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EntryCost {
+    resource: String, // What is spent to enter, such as Keys
+    #[serde(default)]
+    costs: [f64; 4], // The cost of each normal difficulty, easy to extreme
+    #[serde(default)]
+    boss_costs: [f64; 4], // The cost of each boss difficulty, easy to extreme
+}
+
+pub fn create_entry_cost(
+    resource: String,
+    costs: [f64; 4],
+    boss_costs: [f64; 4],
+) -> Result<EntryCost, &'static str> {
+    if resource.is_empty() {
+        return Err("entry cost resource must not be empty");
+    }
+    if costs
+        .iter()
+        .chain(boss_costs.iter())
+        .any(|cost| *cost < 0.0)
+    {
+        return Err("entry costs must be >= 0");
+    }
+    return Ok(EntryCost {
+        resource,
+        costs,
+        boss_costs,
+    });
+}
+
+impl EntryCost {
+    pub fn get_resource(&self) -> String {
+        return self.resource.to_string();
+    }
+
+    /// Returns the cost of entering at the given difficulty setting, 1-8
+    pub fn get_cost(&self, difficulty: usize) -> f64 {
+        if difficulty <= 4 {
+            return self.costs[difficulty - 1];
+        }
+        return self.boss_costs[difficulty - 5];
+    }
+}
+
 /// A specific combat encounter for a simulation
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Encounter {
@@ -50,6 +102,31 @@ pub struct Encounter {
     crit_chance: f64,
     barrier_modifier: f64,
     aoe_damage: f64,
+    elemental_modifiers: HashMap<ElementType, f64>, // Damage multiplier taken from heroes of each element, < 1.0 is a resistance and > 1.0 a weakness
+    abilities: Vec<EnemyAbility>, // Special attacks and effects used in addition to the regular attack
+    enrage: Option<Enrage>,       // Punishes fights that last too long, only set for bosses
+    entry_cost: f64,              // The resource spent to enter this encounter, 0 if entry is free
+    #[serde(deserialize_with = "deserialize_pack_size")]
+    pack_size: u8, // The enemies fought at once, sharing the encounter's hp pool and each attacking every round while alive
+}
+
+/// Encounters and dungeons loaded from checkpoints and results skip their create functions, so an empty pack is rejected as they're read
+fn deserialize_pack_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+    let pack_size = u8::deserialize(deserializer)?;
+    if pack_size < 1 {
+        return Err(D::Error::custom("pack size must be > 0"));
+    }
+    return Ok(pack_size);
+}
+
+fn deserialize_pack_sizes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<[u8; 4]>, D::Error> {
+    let pack_sizes = Option::<[u8; 4]>::deserialize(deserializer)?;
+    if pack_sizes.is_some_and(|pack_sizes| pack_sizes.contains(&0)) {
+        return Err(D::Error::custom("pack sizes must be > 0"));
+    }
+    return Ok(pack_sizes);
 }
 
 impl Encounter {
@@ -66,6 +143,23 @@ impl Encounter {
         return self.is_extreme;
     }
 
+    pub fn get_entry_cost(&self) -> f64 {
+        return self.entry_cost;
+    }
+
+    pub fn get_pack_size(&self) -> u8 {
+        return self.pack_size;
+    }
+
+    /// Returns the enemies of the pack still alive. Heroes focus one enemy at a time, so an enemy falls with each share of the pack's max hp lost
+    pub fn count_enemies_alive(&self) -> u8 {
+        if self.hp <= 0.0 {
+            return 0;
+        }
+        let enemy_hp_max = self.hp_max / self.pack_size as f64;
+        return ((self.hp / enemy_hp_max).ceil() as u8).clamp(1, self.pack_size);
+    }
+
     pub fn get_defense_cap(&self) -> f64 {
         return self.defense_cap;
     }
@@ -108,6 +202,58 @@ impl Encounter {
         return self.evasion;
     }
 
+    pub fn get_elemental_modifiers(&self) -> HashMap<ElementType, f64> {
+        return self.elemental_modifiers.clone();
+    }
+
+    /// Returns the abilities to be used this round, marking one-time triggers as used
+    pub fn take_triggered_abilities(&mut self, round: i16) -> Vec<EnemyAbility> {
+        let (hp, hp_max) = (self.hp, self.hp_max);
+        return self
+            .abilities
+            .iter_mut()
+            .filter_map(|ability| {
+                if ability.check_trigger(round, hp, hp_max) {
+                    Some(ability.clone())
+                } else {
+                    None
+                }
+            })
+            .collect();
+    }
+
+    /// Returns the enrage effect if the encounter enrages at the start of this round
+    pub fn check_enrage(&self, round: i16) -> Option<EnrageEffect> {
+        return self.enrage.and_then(|enrage| enrage.check_enrage(round));
+    }
+
+    /// Multiply the encounter's damage, damage taken by the team must be recalculated afterwards
+    pub fn multiply_damage(&mut self, multiplier: f64) {
+        self.damage *= multiplier;
+    }
+
+    /// Multiply the encounter's hp, barrier hp, damage, and defense cap, used to make each floor of endless content harder than the last
+    pub fn scale_stats(&mut self, multiplier: f64) {
+        self.hp *= multiplier;
+        self.hp_max *= multiplier;
+        self.barrier_hp *= multiplier;
+        self.barrier_hp_max *= multiplier;
+        self.damage *= multiplier;
+        self.aoe_damage_base *= multiplier;
+        self.defense_cap *= multiplier;
+    }
+
+    /// Restore a fraction of the encounter's max hp, without exceeding max hp
+    pub fn heal(&mut self, hp_fraction: f64) -> String {
+        let before_hp = self.hp;
+        self.hp = f64::min(self.hp + hp_fraction * self.hp_max, self.hp_max);
+        return f!(
+            "Mob heals {:.2}, bringing hp to {:.2}",
+            self.hp - before_hp,
+            self.hp
+        );
+    }
+
     pub fn init_barrier_modifier(&mut self) {
         if self.barrier_hp == 0.0 {
             self.barrier_modifier = 1.0;
@@ -116,22 +262,44 @@ impl Encounter {
         }
     }
 
-    pub fn round_floats_for_display(&self) -> Encounter {
-        let mut e2 = self.clone();
-        e2.hp = round_to_2(e2.hp);
-        e2.hp_max = round_to_2(e2.hp_max);
-        e2.damage = round_to_2(e2.damage);
-        e2.defense_cap = round_to_2(e2.defense_cap);
-        e2.aoe_damage_base = round_to_2(e2.aoe_damage_base);
-        e2.aoe_chance = round_to_2(e2.aoe_chance);
-        e2.barrier_hp = round_to_2(e2.barrier_hp);
-        e2.barrier_hp_max = round_to_2(e2.barrier_hp_max);
-        e2.evasion = round_to_2(e2.evasion);
-        e2.crit_chance_modifier = round_to_2(e2.crit_chance_modifier);
-        e2.crit_chance = round_to_2(e2.crit_chance);
-        e2.barrier_modifier = round_to_2(e2.barrier_modifier);
-        e2.aoe_damage = round_to_2(e2.aoe_damage);
-        return e2;
+    pub fn fmt_stats(&self) -> StatsView {
+        return create_stats_view(f!("Encounter ({})", self.zone))
+            .text("Miniboss", self.is_miniboss)
+            .text("Boss", self.is_boss)
+            .text("Extreme", self.is_extreme)
+            .float("HP", self.hp)
+            .float("HP Max", self.hp_max)
+            .float("Damage", self.damage)
+            .float("Defense Cap", self.defense_cap)
+            .float("AOE Damage Base", self.aoe_damage_base)
+            .float("AOE Damage", self.aoe_damage)
+            .float("AOE Chance", self.aoe_chance)
+            .text("Barrier Type", f!("{:?}", self.barrier_type))
+            .float("Barrier HP", self.barrier_hp)
+            .float("Barrier HP Max", self.barrier_hp_max)
+            .float("Barrier Modifier", self.barrier_modifier)
+            .float("Evasion", self.evasion)
+            .float("Crit Chance", self.crit_chance)
+            .float("Crit Chance Modifier", self.crit_chance_modifier)
+            .text("Pack Size", self.pack_size)
+            .text("Elemental Modifiers", f!("{:?}", self.elemental_modifiers))
+            .text(
+                "Abilities",
+                f!(
+                    "{:?}",
+                    self.abilities
+                        .iter()
+                        .map(|ability| ability.get_name())
+                        .collect::<Vec<String>>()
+                ),
+            )
+            .text("Enrage", f!("{:?}", self.enrage));
+    }
+}
+
+impl std::fmt::Display for Encounter {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.fmt_stats())
     }
 }
 
@@ -149,10 +317,24 @@ pub fn create_encounter(
     barrier_type: Option<ElementType>,
     barrier_hp: f64,
     max_num_heroes: u8,
+    elemental_modifiers: HashMap<ElementType, f64>,
+    abilities: Vec<EnemyAbility>,
+    enrage: Option<Enrage>,
+    entry_cost: f64,
+    pack_size: u8,
 ) -> Result<Encounter, &'static str> {
     if damage <= 0.0 {
         return Err("Damage <= 0");
     }
+    if pack_size < 1 {
+        return Err("pack size must be > 0");
+    }
+    if entry_cost < 0.0 {
+        return Err("entry cost must be >= 0");
+    }
+    if elemental_modifiers.values().any(|m| *m < 0.0) {
+        return Err("elemental modifiers must be >= 0");
+    }
 
     let mut evasion = -1.0;
     let mut hp_modifier = 1.0;
@@ -188,10 +370,11 @@ pub fn create_encounter(
         _ => is_miniboss = false,
     }
 
+    // Hp is given per enemy, so the pack's pool holds every enemy's hp
     let encounter = Encounter {
         zone,
-        hp: hp * hp_modifier,
-        hp_max: hp * hp_modifier,
+        hp: hp * hp_modifier * pack_size as f64,
+        hp_max: hp * hp_modifier * pack_size as f64,
         damage: damage * damage_modifier,
         defense_cap,
         aoe_damage_base,
@@ -208,6 +391,11 @@ pub fn create_encounter(
         crit_chance,
         barrier_modifier,
         aoe_damage,
+        elemental_modifiers,
+        abilities,
+        enrage,
+        entry_cost,
+        pack_size,
     };
 
     return Ok(encounter);
@@ -225,6 +413,19 @@ pub struct TrialDungeon {
     pub dungeon: Dungeon,
     pub difficulty: usize,
     pub force_minibosses: Option<bool>,
+    pub max_rounds: i16, // Fights still going after this many rounds end as a timeout
+    pub initiative: InitiativeModel, // The order in which the mob and heroes act within each round
+    #[serde(default)]
+    pub targeting: TargetingModel, // How the mob chooses the hero hit by single target attacks
+}
+
+impl TrialDungeon {
+    /// Returns a copy of this trial dungeon where the mob chooses its targets with the given model
+    pub fn with_targeting(&self, targeting: TargetingModel) -> TrialDungeon {
+        let mut trial_dungeon = self.clone();
+        trial_dungeon.targeting = targeting;
+        return trial_dungeon;
+    }
 }
 
 /// Difficulty settings (choose one):
@@ -233,15 +434,26 @@ pub struct TrialDungeon {
 ///
 /// force_minibosses:
 /// false - No Minibosses, true - Only Minibosses, none - Random Chance of Minibosses
+///
+/// max_rounds:
+/// none - DEFAULT_MAX_ROUNDS
+///
+/// initiative:
+/// none - MobFirst
 pub fn create_trial_dungeon(
     dungeon: Dungeon,
     difficulty: usize,
     force_minibosses: Option<bool>,
+    max_rounds: Option<i16>,
+    initiative: Option<InitiativeModel>,
 ) -> TrialDungeon {
     return TrialDungeon {
         dungeon,
         difficulty,
         force_minibosses,
+        max_rounds: max_rounds.unwrap_or(DEFAULT_MAX_ROUNDS),
+        initiative: initiative.unwrap_or(InitiativeModel::MobFirst),
+        targeting: TargetingModel::ThreatWeighted,
     };
 }
 
@@ -274,6 +486,43 @@ pub struct Dungeon {
     boss_minimum_power: [u32; 4],
     boss_barrier_type: ElementType,
     boss_barrier_healths: [f64; 4],
+
+    // Elemental Resistances/Weaknesses
+    elemental_modifiers: HashMap<ElementType, f64>,
+    boss_elemental_modifiers: HashMap<ElementType, f64>,
+
+    // Enemy Abilities
+    abilities: Vec<EnemyAbility>,
+    boss_abilities: Vec<EnemyAbility>,
+    boss_enrage: Option<Enrage>,
+
+    // Entry Cost, skipped when absent so the data version of free dungeons is unchanged
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    entry_cost: Option<EntryCost>,
+
+    // Pack Sizes, the enemies fought at once per difficulty, 1 when absent and skipped like entry cost
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_pack_sizes"
+    )]
+    pack_sizes: Option<[u8; 4]>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_pack_sizes"
+    )]
+    boss_pack_sizes: Option<[u8; 4]>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_pack_sizes"
+    )]
+    max_pack_sizes: Option<[u8; 4]>, // When set, each normal encounter's pack size is drawn between pack_sizes and this
+
+    // Miniboss Chances, the percent chance per normal difficulty of a random miniboss, 50 when absent and skipped like entry cost
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    miniboss_chances: Option<[f64; 4]>,
 }
 
 impl Dungeon {
@@ -281,6 +530,41 @@ impl Dungeon {
         return self.zone.clone();
     }
 
+    pub fn get_max_num_heroes(&self) -> u8 {
+        return self.max_num_heroes;
+    }
+
+    /// Returns the resource spent to enter this dungeon, if entry is not free
+    pub fn get_entry_cost_resource(&self) -> Option<String> {
+        return self
+            .entry_cost
+            .as_ref()
+            .map(|entry_cost| entry_cost.get_resource());
+    }
+
+    /// Returns the distinct barrier elements that can appear on extreme encounters in this dungeon
+    pub fn get_barrier_elements(&self) -> Vec<ElementType> {
+        let mut elements: Vec<ElementType> = vec![];
+        for element in self
+            .barrier_types
+            .iter()
+            .chain([self.boss_barrier_type].iter())
+        {
+            if *element != ElementType::Any && !elements.contains(element) {
+                elements.push(*element);
+            }
+        }
+        return elements;
+    }
+
+    /// Returns a copy of this dungeon where every extreme encounter has a barrier of the given element
+    pub fn with_barrier_type(&self, element: ElementType) -> Dungeon {
+        let mut dungeon = self.clone();
+        dungeon.barrier_types = [element; 3];
+        dungeon.boss_barrier_type = element;
+        return dungeon;
+    }
+
     /// Difficulty settings (include all that should apply):
     /// 1 - Easy, 2 - Medium, 3 - Hard, 4 - Extreme,
     /// 5 - Boss Easy, 6 - Boss Medium, 7 - Boss Hard, 8 - Boss Extreme
@@ -291,17 +575,21 @@ impl Dungeon {
         &self,
         difficulty_settings: &Vec<usize>,
         force_minibosses: Option<bool>,
+        rng: &mut impl Rng,
     ) -> Result<Encounter, &'static str> {
         // Check for out of bounds
         for &difficulty in difficulty_settings {
-            if difficulty > 8 || difficulty < 1 {
+            if !(1..=8).contains(&difficulty) {
                 return Err("difficulty settings must be within range 1-8 inclusive");
             }
         }
 
-        let mut rng = rand::thread_rng();
         let diff_rand = rng.gen_range(0..difficulty_settings.len());
         let mut sel_diff = difficulty_settings[diff_rand];
+        let entry_cost = self
+            .entry_cost
+            .as_ref()
+            .map_or(0.0, |entry_cost| entry_cost.get_cost(sel_diff));
         let encounter: Encounter;
 
         if sel_diff <= 4 {
@@ -313,19 +601,30 @@ impl Dungeon {
             match force_minibosses {
                 Some(setting) => {
                     miniboss = if setting {
-                        Some(rand::random::<MiniBossType>())
+                        Some(rng.gen::<MiniBossType>())
                     } else {
                         None
                     }
                 }
                 _ => {
-                    if rng.gen_range(0..2) == 1 {
-                        miniboss = Some(rand::random::<MiniBossType>());
+                    let is_miniboss = match self.miniboss_chances {
+                        Some(miniboss_chances) => {
+                            rng.gen::<f64>() * 100.0 < miniboss_chances[sel_diff]
+                        }
+                        None => rng.gen_range(0..2) == 1,
+                    };
+                    if is_miniboss {
+                        miniboss = Some(rng.gen::<MiniBossType>());
                     } else {
                         miniboss = None;
                     }
                 }
             }
+            let pack_size = self.pack_sizes.map_or(1, |pack_sizes| pack_sizes[sel_diff]);
+            let pack_size = match self.max_pack_sizes {
+                Some(max_pack_sizes) => rng.gen_range(pack_size..=max_pack_sizes[sel_diff]),
+                None => pack_size,
+            };
 
             encounter = create_encounter(
                 self.zone.to_string(),
@@ -335,15 +634,20 @@ impl Dungeon {
                 self.aoe_damage[sel_diff],
                 self.aoe_chance[sel_diff],
                 false,
-                sel_diff == 4,
+                sel_diff == 3,
                 miniboss,
-                if sel_diff == 4 {
+                if sel_diff == 3 {
                     Some(self.barrier_types[rng.gen_range(0..3)])
                 } else {
                     None
                 },
                 self.barrier_healths[sel_diff],
                 self.max_num_heroes,
+                self.elemental_modifiers.clone(),
+                self.abilities.clone(),
+                None,
+                entry_cost,
+                pack_size,
             )
             .unwrap();
         } else {
@@ -359,15 +663,21 @@ impl Dungeon {
                 self.boss_aoe_damage[sel_diff],
                 self.boss_aoe_chance[sel_diff],
                 true,
-                sel_diff == 4,
+                sel_diff == 3,
                 None,
-                if sel_diff == 4 {
+                if sel_diff == 3 {
                     Some(self.boss_barrier_type)
                 } else {
                     None
                 },
                 self.boss_barrier_healths[sel_diff],
                 self.max_num_heroes,
+                self.boss_elemental_modifiers.clone(),
+                self.boss_abilities.clone(),
+                self.boss_enrage,
+                entry_cost,
+                self.boss_pack_sizes
+                    .map_or(1, |pack_sizes| pack_sizes[sel_diff]),
             )
             .unwrap();
         }
@@ -387,6 +697,16 @@ impl From<Dungeon> for DungeonInput {
             barrier_types[i] = bt.to_string();
         }
         let boss_barrier_type = item.boss_barrier_type.to_string();
+        let elemental_modifiers = item
+            .elemental_modifiers
+            .iter()
+            .map(|(element, modifier)| (element.to_string(), *modifier))
+            .collect();
+        let boss_elemental_modifiers = item
+            .boss_elemental_modifiers
+            .iter()
+            .map(|(element, modifier)| (element.to_string(), *modifier))
+            .collect();
         return create_dungeon_input(
             item.zone,
             item.max_num_heroes,
@@ -406,6 +726,16 @@ impl From<Dungeon> for DungeonInput {
             item.boss_minimum_power,
             boss_barrier_type,
             item.boss_barrier_healths,
+            elemental_modifiers,
+            boss_elemental_modifiers,
+            item.abilities,
+            item.boss_abilities,
+            item.boss_enrage,
+            item.entry_cost,
+            item.pack_sizes,
+            item.boss_pack_sizes,
+            item.max_pack_sizes,
+            item.miniboss_chances,
         );
     }
 }
@@ -430,7 +760,64 @@ pub fn create_dungeon(
     boss_minimum_power: [u32; 4],
     boss_barrier_type: ElementType,
     boss_barrier_healths: [f64; 4],
+    elemental_modifiers: HashMap<ElementType, f64>,
+    boss_elemental_modifiers: HashMap<ElementType, f64>,
+    abilities: Vec<EnemyAbility>,
+    boss_abilities: Vec<EnemyAbility>,
+    boss_enrage: Option<Enrage>,
+    entry_cost: Option<EntryCost>,
+    pack_sizes: Option<[u8; 4]>,
+    boss_pack_sizes: Option<[u8; 4]>,
+    max_pack_sizes: Option<[u8; 4]>,
+    miniboss_chances: Option<[f64; 4]>,
 ) -> Result<Dungeon, &'static str> {
+    if pack_sizes
+        .iter()
+        .chain(boss_pack_sizes.iter())
+        .flatten()
+        .any(|pack_size| *pack_size < 1)
+    {
+        return Err("pack sizes must be > 0");
+    }
+    if let Some(max_pack_sizes) = max_pack_sizes {
+        let min_pack_sizes = pack_sizes.unwrap_or([1; 4]);
+        if (0..4).any(|i| max_pack_sizes[i] < min_pack_sizes[i]) {
+            return Err("max pack sizes must be >= pack sizes");
+        }
+    }
+    if miniboss_chances
+        .iter()
+        .flatten()
+        .any(|chance| !(0.0..=100.0).contains(chance))
+    {
+        return Err("miniboss chances must be within range 0-100 inclusive");
+    }
+    // Revalidate abilities as they are deserialized directly from input
+    let mut validated_abilities: [Vec<EnemyAbility>; 2] = Default::default();
+    for (i, ability_list) in [abilities, boss_abilities].iter().enumerate() {
+        for ability in ability_list {
+            validated_abilities[i].push(create_enemy_ability(
+                ability.get_name(),
+                ability.get_trigger(),
+                ability.get_effect(),
+            )?);
+        }
+    }
+    let [abilities, boss_abilities] = validated_abilities;
+    let boss_enrage = match boss_enrage {
+        Some(enrage) => Some(create_enrage(enrage.get_rounds(), enrage.get_effect())?),
+        None => None,
+    };
+    // Revalidate the entry cost as it is deserialized directly from input
+    let entry_cost = match entry_cost {
+        Some(entry_cost) => Some(create_entry_cost(
+            entry_cost.resource,
+            entry_cost.costs,
+            entry_cost.boss_costs,
+        )?),
+        None => None,
+    };
+
     let dungeon = Dungeon {
         zone,
         max_num_heroes,
@@ -450,7 +837,66 @@ pub fn create_dungeon(
         boss_minimum_power,
         boss_barrier_type,
         boss_barrier_healths,
+        elemental_modifiers,
+        boss_elemental_modifiers,
+        abilities,
+        boss_abilities,
+        boss_enrage,
+        entry_cost,
+        pack_sizes,
+        boss_pack_sizes,
+        max_pack_sizes,
+        miniboss_chances,
     };
 
     return Ok(dungeon);
 }
+
+/// Calculate the damage multiplier for a hero of the given element against an enemy's elemental modifiers
+/// The enemy's resistance or weakness scales in with the hero's socketed element quantity, reaching full effect at ELEMENTAL_FULL_EFFECT_QTY
+pub fn calculate_elemental_damage_modifier(
+    elemental_modifiers: &HashMap<ElementType, f64>,
+    element_type: ElementType,
+    element_qty: u16,
+) -> f64 {
+    let modifier = match elemental_modifiers.get(&element_type) {
+        Some(modifier) => *modifier,
+        None => return 1.0,
+    };
+    let effect = f64::min(f64::from(element_qty) / ELEMENTAL_FULL_EFFECT_QTY, 1.0);
+    return 1.0 + (modifier - 1.0) * effect;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elemental_modifiers_scale_in_with_element_qty() {
+        let elemental_modifiers =
+            HashMap::from([(ElementType::Fire, 1.5), (ElementType::Water, 0.5)]);
+
+        assert_eq!(
+            calculate_elemental_damage_modifier(&elemental_modifiers, ElementType::Air, 100),
+            1.0
+        );
+        assert_eq!(
+            calculate_elemental_damage_modifier(&elemental_modifiers, ElementType::Fire, 0),
+            1.0
+        );
+        assert!(
+            (calculate_elemental_damage_modifier(&elemental_modifiers, ElementType::Fire, 50)
+                - 1.25)
+                .abs()
+                < 1e-9
+        );
+        assert_eq!(
+            calculate_elemental_damage_modifier(&elemental_modifiers, ElementType::Fire, 250),
+            1.5
+        );
+        assert_eq!(
+            calculate_elemental_damage_modifier(&elemental_modifiers, ElementType::Water, 100),
+            0.5
+        );
+    }
+}
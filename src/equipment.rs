@@ -1,9 +1,20 @@
+use std::{collections::HashMap, io::Read};
+
 use serde::{Deserialize, Serialize};
 use strum;
 
 /// Defines valid element types
 #[derive(
-    Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, strum::Display, strum::EnumString,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    strum::Display,
+    strum::EnumString,
 )]
 pub enum ElementType {
     #[strum(serialize = "Fire")]
@@ -42,6 +53,23 @@ pub enum BoosterType {
     MegaPowerBooster,
 }
 
+/// Which enchantments an item can hold, every item can hold both unless enchant_eligibility.yaml says otherwise
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[serde(default)]
+pub struct EnchantEligibility {
+    pub element: bool,
+    pub spirit: bool,
+}
+
+impl Default for EnchantEligibility {
+    fn default() -> Self {
+        return EnchantEligibility {
+            element: true,
+            spirit: true,
+        };
+    }
+}
+
 /// Information on blueprints/gear
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Blueprint {
@@ -95,6 +123,8 @@ pub struct Blueprint {
 
     elemental_affinity: String,
     spirit_affinity: String,
+    #[serde(default)]
+    enchant_eligibility: EnchantEligibility, // Not in the data sheet, set from enchant_eligibility.yaml
 
     // crafting upgrades
 
@@ -118,6 +148,10 @@ impl Blueprint {
         return self.unlock_prerequisite.to_string();
     }
 
+    pub fn get_tier(&self) -> u8 {
+        return self.tier;
+    }
+
     pub fn get_atk(&self) -> f64 {
         return self.atk.clone();
     }
@@ -140,6 +174,234 @@ impl Blueprint {
     pub fn get_spirit_affinity(&self) -> String {
         return self.spirit_affinity.clone();
     }
+    pub fn get_enchant_eligibility(&self) -> EnchantEligibility {
+        return self.enchant_eligibility;
+    }
+}
+
+/// Set which enchantments each blueprint can hold, from eligibility keyed by blueprint name or item type
+/// Eligibility for a blueprint's name takes precedence over eligibility for its type
+pub fn apply_enchant_eligibility(
+    bp_map: &mut HashMap<String, Blueprint>,
+    eligibility: &HashMap<String, EnchantEligibility>,
+) {
+    for (name, blueprint) in bp_map.iter_mut() {
+        if let Some(item_eligibility) = eligibility
+            .get(name)
+            .or_else(|| eligibility.get(&blueprint.type_))
+        {
+            blueprint.enchant_eligibility = *item_eligibility;
+        }
+    }
+}
+
+/// A row of a blueprint export, such as the community blueprint spreadsheet saved as csv or tsv, holding only the columns simulations need
+/// Cells are read as text so numbers with thousands separators, percent strings, and "---" placeholders can be normalized
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+struct BlueprintExportRow {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Type")]
+    type_: String,
+    #[serde(rename = "Unlock Prerequisite", default)]
+    unlock_prerequisite: String,
+    #[serde(rename = "Tier")]
+    tier: String,
+    #[serde(rename = "ATK", default)]
+    atk: String,
+    #[serde(rename = "DEF", default)]
+    def: String,
+    #[serde(rename = "HP", default)]
+    hp: String,
+    #[serde(rename = "EVA", alias = "EVA %", alias = "Evasion", default)]
+    eva: String,
+    #[serde(rename = "CRIT", alias = "CRIT %", alias = "Crit Chance", default)]
+    crit: String,
+    #[serde(rename = "Elemental Affinity", alias = "Element Affinity", default)]
+    elemental_affinity: String,
+    #[serde(rename = "Spirit Affinity", default)]
+    spirit_affinity: String,
+}
+
+/// Parse a numeric cell of a blueprint export, treating blank and "---" cells as 0 and ignoring thousands separators
+/// Percent cells ending in % are divided by 100, as are plain percent values above 1, so "5%", "5", and "0.05" all give 0.05
+fn parse_export_number(cell: &str, is_percent: bool) -> Result<f64, String> {
+    let cell = cell.trim();
+    if cell.is_empty() || cell.chars().all(|c| c == '-') {
+        return Ok(0.0);
+    }
+    let (number, has_percent_sign) = match cell.strip_suffix('%') {
+        Some(number) => (number.trim(), true),
+        None => (cell, false),
+    };
+    let value: f64 = number
+        .replace(',', "")
+        .parse()
+        .map_err(|_| f!("{:?} is not a number", cell))?;
+    if has_percent_sign || (is_percent && value > 1.0) {
+        return Ok(value / 100.0);
+    }
+    return Ok(value);
+}
+
+/// Blank affinities are stored as "---", as in the data sheet
+fn normalize_export_affinity(cell: &str) -> String {
+    let cell = cell.trim();
+    if cell.is_empty() || cell.chars().all(|c| c == '-') {
+        return String::from("---");
+    }
+    return cell.to_string();
+}
+
+/// Load the blueprints of a blueprint export with a header row, tab separated if the header has tabs and comma separated otherwise
+/// Only the name, type, unlock prerequisite, tier, stats, and affinities are read, every other field of the blueprints is left at 0
+pub fn load_blueprints_from_export(mut reader: impl Read) -> Result<Vec<Blueprint>, String> {
+    let mut contents = String::new();
+    reader
+        .read_to_string(&mut contents)
+        .map_err(|e| e.to_string())?;
+    let delimiter = match contents.lines().next() {
+        Some(header) if header.contains('\t') => b'\t',
+        _ => b',',
+    };
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(contents.as_bytes());
+    let mut blueprints: Vec<Blueprint> = vec![];
+    for (i, result) in rdr.deserialize::<BlueprintExportRow>().enumerate() {
+        // Row 1 is the header
+        let row = i + 2;
+        let bp_in = result.map_err(|e| f!("row {}: {}", row, e))?;
+        if bp_in.name.trim().is_empty() {
+            continue;
+        }
+        let number = |column: &str, cell: &str, is_percent: bool| -> Result<f64, String> {
+            return parse_export_number(cell, is_percent)
+                .map_err(|e| f!("row {} ({}): {} {}", row, bp_in.name, column, e));
+        };
+        let tier = number("Tier", &bp_in.tier, false)?;
+        if tier < 1.0 || tier > f64::from(u8::MAX) || tier.fract() != 0.0 {
+            return Err(f!(
+                "row {} ({}): Tier {:?} is not a whole number from 1",
+                row,
+                bp_in.name,
+                bp_in.tier
+            ));
+        }
+        let blueprint = create_blueprint(
+            bp_in.name.trim().to_string(),
+            bp_in.type_.trim().to_string(),
+            bp_in.unlock_prerequisite.trim().to_string(),
+            0,
+            0,
+            tier as u8,
+            0,
+            0,
+            String::new(),
+            0.0,
+            0,
+            0.0,
+            0,
+            0,
+            0,
+            0,
+            String::new(),
+            0,
+            String::new(),
+            0,
+            String::new(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            String::new(),
+            String::new(),
+            0,
+            String::new(),
+            String::new(),
+            0,
+            number("ATK", &bp_in.atk, false)?,
+            number("DEF", &bp_in.def, false)?,
+            number("HP", &bp_in.hp, false)?,
+            number("EVA", &bp_in.eva, true)?,
+            number("CRIT", &bp_in.crit, true)?,
+            normalize_export_affinity(&bp_in.elemental_affinity),
+            normalize_export_affinity(&bp_in.spirit_affinity),
+            0,
+            0,
+            0,
+            0,
+        );
+        if blueprint.type_.is_empty() {
+            return Err(f!("row {} ({}): Type is blank", row, blueprint.name));
+        }
+        if blueprints.iter().any(|other| other.name == blueprint.name) {
+            return Err(f!("row {}: duplicate blueprint {}", row, blueprint.name));
+        }
+        blueprints.push(blueprint);
+    }
+    return Ok(blueprints);
+}
+
+/// Refresh blueprints from an export, such as after a game patch. Blueprints already loaded keep their crafting details but take the
+/// export's type, tier, stats, affinities, and any unlock prerequisite, while blueprints new to the export are added as they are
+pub fn apply_blueprint_export(bp_map: &mut HashMap<String, Blueprint>, blueprints: Vec<Blueprint>) {
+    for blueprint in blueprints {
+        match bp_map.get_mut(&blueprint.name) {
+            Some(existing) => {
+                existing.type_ = blueprint.type_;
+                if !blueprint.unlock_prerequisite.is_empty() {
+                    existing.unlock_prerequisite = blueprint.unlock_prerequisite;
+                }
+                existing.tier = blueprint.tier;
+                existing.atk = blueprint.atk;
+                existing.def = blueprint.def;
+                existing.hp = blueprint.hp;
+                existing.eva = blueprint.eva;
+                existing.crit = blueprint.crit;
+                existing.elemental_affinity = blueprint.elemental_affinity;
+                existing.spirit_affinity = blueprint.spirit_affinity;
+            }
+            None => {
+                bp_map.insert(blueprint.name.to_string(), blueprint);
+            }
+        }
+    }
+}
+
+/// Returns the items of the same type as the given item that are up to tiers_up tiers above it, ordered by tier then name
+/// Blueprints have no explicit lines, so every item of the same type is treated as part of the item's line
+pub fn get_higher_tier_items(
+    bp_map: &HashMap<String, Blueprint>,
+    item: &str,
+    tiers_up: u8,
+) -> Vec<String> {
+    let Some(current) = bp_map.get(item) else {
+        return vec![];
+    };
+    let max_tier = current.tier.saturating_add(tiers_up);
+    let mut items: Vec<(&String, u8)> = bp_map
+        .iter()
+        .filter(|(_, blueprint)| {
+            blueprint.type_ == current.type_
+                && blueprint.tier > current.tier
+                && blueprint.tier <= max_tier
+        })
+        .map(|(name, blueprint)| (name, blueprint.tier))
+        .collect();
+    items.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(b.0)));
+    return items
+        .into_iter()
+        .map(|(name, _)| name.to_string())
+        .collect();
 }
 
 pub fn create_blueprint(
@@ -253,6 +515,7 @@ pub fn create_blueprint(
 
         elemental_affinity,
         spirit_affinity,
+        enchant_eligibility: Default::default(),
 
         // crafting upgrades
 
@@ -0,0 +1,124 @@
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        OnceLock,
+    },
+};
+
+use serde::Serialize;
+
+/// Defines the categories of failure, each reported with a distinct process exit code
+#[derive(Serialize, Debug, Clone, Copy, Eq, PartialEq, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ErrorKind {
+    Input, // Invalid command line arguments, or heroes, dungeons, or studies that they name incorrectly
+    Data,  // Missing or invalid input files or data sheets
+    Runtime, // A failure while running simulations or saving results
+}
+
+impl ErrorKind {
+    pub fn get_exit_code(&self) -> i32 {
+        return match self {
+            ErrorKind::Input => 2,
+            ErrorKind::Data => 3,
+            ErrorKind::Runtime => 4,
+        };
+    }
+
+    fn from_u8(value: u8) -> ErrorKind {
+        return match value {
+            0 => ErrorKind::Input,
+            1 => ErrorKind::Data,
+            _ => ErrorKind::Runtime,
+        };
+    }
+}
+
+/// Defines how failures are reported on stderr
+#[derive(Debug, Clone, Copy, Eq, PartialEq, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum ErrorFormat {
+    Human, // The standard panic message
+    Json,  // A single line json object with the kind, exit code, message, and location
+}
+
+/// The format failures are reported in, set once when error reporting is installed
+static ERROR_FORMAT: OnceLock<ErrorFormat> = OnceLock::new();
+
+/// The kind any failure is currently reported as, updated by main as it moves between parsing arguments, loading data, and running
+static CURRENT_ERROR_KIND: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_error_kind(kind: ErrorKind) {
+    CURRENT_ERROR_KIND.store(kind as u8, Ordering::SeqCst);
+}
+
+pub fn get_error_kind() -> ErrorKind {
+    return ErrorKind::from_u8(CURRENT_ERROR_KIND.load(Ordering::SeqCst));
+}
+
+/// A structured error as emitted by the json error format
+#[derive(Serialize, Debug, Clone, PartialEq)]
+struct ErrorRecord {
+    kind: ErrorKind,
+    exit_code: i32,
+    message: String,
+    location: Option<String>, // The source location of the panic, if the error came from one
+}
+
+/// Returns the message of a panic payload, which is a String or &str for panic! and unwrap
+pub fn get_panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<String>() {
+        return message.to_string();
+    }
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+    return String::from("unknown error");
+}
+
+/// Install a panic hook that reports failures in the given format and exits with the exit code of the current error kind
+pub fn install_error_reporting(format: ErrorFormat) {
+    let _ = ERROR_FORMAT.set(format);
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let kind = get_error_kind();
+        match format {
+            ErrorFormat::Human => default_hook(info),
+            ErrorFormat::Json => eprintln!(
+                "{}",
+                serde_json::to_string(&ErrorRecord {
+                    kind,
+                    exit_code: kind.get_exit_code(),
+                    message: get_panic_message(info.payload()),
+                    location: info.location().map(|location| f!(
+                        "{}:{}",
+                        location.file(),
+                        location.line()
+                    )),
+                })
+                .unwrap()
+            ),
+        }
+        std::process::exit(kind.get_exit_code());
+    }));
+}
+
+/// Report a failure that was handled without panicking, then exit with the exit code of its kind
+pub fn exit_with_error(kind: ErrorKind, message: String) -> ! {
+    match ERROR_FORMAT.get().copied().unwrap_or(ErrorFormat::Human) {
+        ErrorFormat::Human => eprintln!("error: {}", message),
+        ErrorFormat::Json => eprintln!(
+            "{}",
+            serde_json::to_string(&ErrorRecord {
+                kind,
+                exit_code: kind.get_exit_code(),
+                message,
+                location: None,
+            })
+            .unwrap()
+        ),
+    }
+    std::process::exit(kind.get_exit_code());
+}
@@ -0,0 +1,68 @@
+use serde::Serializer;
+
+use crate::decimals::round_to_2;
+
+/// Serialize a f64 rounded to 2 decimal places without touching the value being serialized
+pub fn serialize_rounded<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    return serializer.serialize_f64(round_to_2(*value));
+}
+
+/// Serialize an optional f64 rounded to 2 decimal places, leaving the field blank when there is no value
+pub fn serialize_rounded_option<S: Serializer>(
+    value: &Option<f64>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    return match value {
+        Some(value) => serializer.serialize_some(&round_to_2(*value)),
+        None => serializer.serialize_none(),
+    };
+}
+
+/// Format a f64 rounded to 2 decimal places for display
+pub fn fmt_f64(value: f64) -> String {
+    return f!("{:.2}", round_to_2(value));
+}
+
+/// A read-only, display-ready view of the stats of some object (hero, encounter, etc.)
+/// Views only ever hold formatted strings, so rounded values can never leak back into computation
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsView {
+    title: String,
+    rows: Vec<(String, String)>,
+}
+
+impl StatsView {
+    pub fn float(mut self, label: &str, value: f64) -> StatsView {
+        self.rows.push((label.to_string(), fmt_f64(value)));
+        return self;
+    }
+
+    pub fn text<T: std::fmt::Display>(mut self, label: &str, value: T) -> StatsView {
+        self.rows.push((label.to_string(), value.to_string()));
+        return self;
+    }
+}
+
+/// Create an empty stats view with the given title
+pub fn create_stats_view(title: String) -> StatsView {
+    return StatsView {
+        title,
+        rows: vec![],
+    };
+}
+
+impl std::fmt::Display for StatsView {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        let label_width = self
+            .rows
+            .iter()
+            .map(|(label, _)| label.len())
+            .max()
+            .unwrap_or_default();
+        writeln!(f, "{}", self.title)?;
+        for (label, value) in &self.rows {
+            writeln!(f, "  {:<width$} : {}", label, value, width = label_width)?;
+        }
+        return Ok(());
+    }
+}
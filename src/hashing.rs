@@ -0,0 +1,15 @@
+use serde::Serialize;
+
+/// Hash any serializable value to a hex string that is stable across runs, platforms, and compiler versions
+/// Values are normalized through serde_json::Value first so map keys are always hashed in sorted order
+pub fn stable_hash<T: Serialize>(value: &T) -> String {
+    let json = serde_json::to_value(value).unwrap().to_string();
+
+    // 64 bit FNV-1a
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in json.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    return f!("{:016x}", hash);
+}
@@ -1,15 +1,136 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, str::FromStr};
 
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
 
 use crate::{
-    decimals::round_to_2,
-    equipment::Blueprint,
-    heroes::{create_sim_hero, SimHero},
+    champions::{find_champion_data, Champion, ChampionData},
+    crafting::ItemQuality,
+    equipment::{Blueprint, ElementType},
+    formatting::{create_stats_view, fmt_f64, StatsView},
+    heroes::{create_sim_hero, get_class_archetype, HeroArchetype, Pet, SimHero},
     inputs::{create_hero_input, HeroInput},
-    skills::{HeroSkill, InnateSkill},
+    skills::{
+        create_innate_skill, find_skill_conflict, is_empty_skill, resolve_skill_reference,
+        HeroSkill, InnateSkill, PetSkill, SkillEffect, EMPTY_SKILL,
+    },
+    studies::HeroBuilderInformation,
 };
 
+/// The item type of pets, which are equipped in a gear slot like any other item
+pub const PET_ITEM_TYPE: &str = "Familiar";
+
+/// The hero rank each of the four skill slots unlocks at
+pub const SKILL_SLOT_UNLOCK_RANKS: [u8; 4] = [1, 1, 2, 3];
+
+/// The level cap and base stat multiplier a hero reaches at a rank
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeroRank {
+    pub level_cap: u8, // The highest level a hero of the rank can reach before ranking up
+    pub stat_multiplier: f64, // Multiplies the class base hp, atk, and def of the hero's level
+}
+
+/// Each hero rank from 1, so a hero's rank can be no higher than the number listed. Skill slots also unlock by rank, see SKILL_SLOT_UNLOCK_RANKS
+pub const HERO_RANKS: [HeroRank; 6] = [
+    HeroRank {
+        level_cap: 20,
+        stat_multiplier: 1.0,
+    },
+    HeroRank {
+        level_cap: 30,
+        stat_multiplier: 1.05,
+    },
+    HeroRank {
+        level_cap: 40,
+        stat_multiplier: 1.1,
+    },
+    HeroRank {
+        level_cap: 40,
+        stat_multiplier: 1.15,
+    },
+    HeroRank {
+        level_cap: 40,
+        stat_multiplier: 1.2,
+    },
+    HeroRank {
+        level_cap: 40,
+        stat_multiplier: 1.25,
+    },
+];
+
+/// Returns the level cap and stat multiplier of a hero rank, or None if there is no such rank
+pub fn get_hero_rank(rank: u8) -> Option<HeroRank> {
+    if rank == 0 {
+        return None;
+    }
+    return HERO_RANKS.get(usize::from(rank - 1)).copied();
+}
+
+/// The levels from which a class's per level stat growth steps up by another multiple of its growth rate
+pub const CLASS_GROWTH_BREAKPOINTS: [u8; 3] = [10, 21, 31];
+
+/// The highest level a hero can reach, and so the number of base stat values each class lists
+pub const MAX_HERO_LEVEL: u8 = 40;
+
+/// The level a hero must reach before it can be promoted, so heroes of promoted classes are never below it
+pub const PROMOTION_MIN_LEVEL: u8 = 20;
+
+/// Generate a class's base stat at each level from 1 to max_level, rounded to whole numbers
+/// Each level adds growth_rate times one more than the number of breakpoints reached, so growth steps up at each breakpoint
+pub fn generate_stat_curve(
+    base: f64,
+    growth_rate: f64,
+    breakpoints: &[u8],
+    max_level: u8,
+) -> Vec<f64> {
+    let mut curve: Vec<f64> = vec![];
+    let mut value = base;
+    for level in 1..=max_level {
+        if level > 1 {
+            let steps = 1 + breakpoints
+                .iter()
+                .filter(|breakpoint| level >= **breakpoint)
+                .count();
+            value += growth_rate * steps as f64;
+        }
+        curve.push(value.round());
+    }
+    return curve;
+}
+
+/// The per level base stats of a class, generated from growth parameters in the same layout as hero_classes.yaml
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClassStatCurves {
+    pub base_hp: Vec<f64>,
+    pub base_atk: Vec<f64>,
+    pub base_def: Vec<f64>,
+}
+
+/// Each stat's growth is given as (base value at level 1, growth rate)
+pub fn create_class_stat_curves(
+    hp: (f64, f64),
+    atk: (f64, f64),
+    def: (f64, f64),
+    breakpoints: &[u8],
+    max_level: u8,
+) -> Result<ClassStatCurves, &'static str> {
+    if max_level < 1 {
+        return Err("max_level must be > 0");
+    }
+    if [hp, atk, def]
+        .iter()
+        .any(|(base, growth_rate)| *base < 0.0 || *growth_rate < 0.0)
+    {
+        return Err("base values and growth rates must be >= 0");
+    }
+    return Ok(ClassStatCurves {
+        base_hp: generate_stat_curve(hp.0, hp.1, breakpoints, max_level),
+        base_atk: generate_stat_curve(atk.0, atk.1, breakpoints, max_level),
+        base_def: generate_stat_curve(def.0, def.1, breakpoints, max_level),
+    });
+}
+
 /// Defines a HeroClass that contains info on base stats, allowed equipment, etc.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct HeroClass {
@@ -28,8 +149,14 @@ pub struct HeroClass {
 
     element_type: String,
     equipment_allowed: [Vec<String>; 6],
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    slot_rules: Vec<SlotRule>, // Rules the class's equipment must follow across slots
 
     innate_skills: [String; 4],
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    estimated: bool, // Whether the class was scaffolded from partial data, so its stats are estimates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    archetype: Option<HeroArchetype>, // Only needed for classes the simulator doesn't know by name, such as scaffolded classes
 }
 
 pub fn _create_hero_class(
@@ -48,8 +175,11 @@ pub fn _create_hero_class(
 
     element_type: String,
     equipment_allowed: [Vec<String>; 6],
+    slot_rules: Vec<SlotRule>,
 
     innate_skills: [String; 4],
+    estimated: bool,
+    archetype: Option<HeroArchetype>,
 ) -> HeroClass {
     return HeroClass {
         class,
@@ -67,11 +197,1189 @@ pub fn _create_hero_class(
 
         element_type,
         equipment_allowed,
+        slot_rules,
 
         innate_skills,
+        estimated,
+        archetype,
+    };
+}
+
+/// Defines a rule a class's equipment must follow across slots (0-5), beyond the item types allowed in each slot
+/// An empty item_types list matches items of every type
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum SlotRule {
+    // At most max items of the listed types, such as one weapon for a class that can't dual-wield
+    MaxOfTypes {
+        item_types: Vec<String>,
+        max: usize,
+    },
+    // No item of the listed types equipped twice, such as two of the same ring
+    Distinct {
+        #[serde(default)]
+        item_types: Vec<String>,
+    },
+    // An item of item_types in slot needs an item of required_types in required_slot, such as an off-hand weapon needing a main-hand weapon
+    Requires {
+        slot: usize,
+        #[serde(default)]
+        item_types: Vec<String>,
+        required_slot: usize,
+        required_types: Vec<String>,
+    },
+}
+
+impl SlotRule {
+    /// Returns the problems with the rule's slots and item types, which can't be checked against equipment
+    pub fn get_validation_errors(&self) -> Vec<String> {
+        let mut errors: Vec<String> = vec![];
+        match self {
+            SlotRule::MaxOfTypes { item_types, .. } if item_types.is_empty() => {
+                errors.push(String::from(
+                    "max_of_types rule must list the item types it limits",
+                ));
+            }
+            SlotRule::Requires {
+                slot,
+                required_slot,
+                required_types,
+                ..
+            } => {
+                for rule_slot in [slot, required_slot] {
+                    if *rule_slot > 5 {
+                        errors.push(f!(
+                            "requires rule names slot {} but slots are 0-5",
+                            rule_slot
+                        ));
+                    }
+                }
+                if slot == required_slot {
+                    errors.push(f!("requires rule makes slot {} require itself", slot));
+                }
+                if required_types.is_empty() {
+                    errors.push(String::from(
+                        "requires rule must list the item types it requires",
+                    ));
+                }
+            }
+            _ => {}
+        }
+        return errors;
+    }
+
+    /// Check the items equipped in each slot, given with their item types, against the rule
+    fn check(&self, equipment: &[String], item_types: &[String]) -> Result<(), HeroBuilderError> {
+        let matches = |types: &Vec<String>, slot: usize| -> bool {
+            return types.is_empty() || types.contains(&item_types[slot]);
+        };
+        match self {
+            SlotRule::MaxOfTypes {
+                item_types: limited,
+                max,
+            } => {
+                let slots: Vec<usize> = (0..equipment.len())
+                    .filter(|slot| matches(limited, *slot))
+                    .collect();
+                if slots.len() > *max {
+                    return Err(HeroBuilderError::TooManyOfTypes {
+                        item_types: limited.clone(),
+                        max: *max,
+                        slots,
+                    });
+                }
+            }
+            SlotRule::Distinct {
+                item_types: distinct,
+            } => {
+                for slot in 0..equipment.len() {
+                    if !matches(distinct, slot) {
+                        continue;
+                    }
+                    if let Some(first_slot) =
+                        (0..slot).find(|other| equipment[*other] == equipment[slot])
+                    {
+                        return Err(HeroBuilderError::DuplicateEquipment {
+                            equipment: equipment[slot].to_string(),
+                            slots: (first_slot, slot),
+                        });
+                    }
+                }
+            }
+            SlotRule::Requires {
+                slot,
+                item_types: requiring,
+                required_slot,
+                required_types,
+            } => {
+                // Rules naming slots past 5 are reported by validation instead
+                if *slot >= equipment.len() || *required_slot >= equipment.len() {
+                    return Ok(());
+                }
+                if matches(requiring, *slot)
+                    && !required_types.contains(&item_types[*required_slot])
+                {
+                    return Err(HeroBuilderError::UnmetSlotRequirement {
+                        equipment: equipment[*slot].to_string(),
+                        slot: *slot,
+                        required_slot: *required_slot,
+                        required_types: required_types.clone(),
+                    });
+                }
+            }
+        }
+        return Ok(());
+    }
+}
+
+impl HeroClass {
+    pub fn get_class(&self) -> String {
+        return self.class.to_string();
+    }
+
+    pub fn is_estimated(&self) -> bool {
+        return self.estimated;
+    }
+
+    /// Returns the class's archetype, or None if the class is unknown and has no archetype set
+    pub fn get_archetype(&self) -> Option<HeroArchetype> {
+        return self.archetype.or_else(|| get_class_archetype(&self.class));
+    }
+
+    /// Returns the highest level the class has base stats for
+    pub fn get_max_level(&self) -> usize {
+        return self.base_hp.len();
+    }
+
+    /// Returns whether the class is the promotion of another class
+    pub fn is_promoted(&self) -> bool {
+        return self.prerequisite.starts_with("Titan Soul");
+    }
+
+    /// Returns the class the class is promoted from, named by its Titan Soul prerequisite, or None if the class isn't promoted
+    pub fn get_promoted_from(&self) -> Option<String> {
+        return self
+            .prerequisite
+            .strip_prefix("Titan Soul (")
+            .and_then(|base_class| base_class.strip_suffix(')'))
+            .map(|base_class| base_class.to_string());
+    }
+
+    /// Returns the merchant level that unlocks the class, or that unlocks the first class of its promotion chain
+    fn get_unlock_level(&self, hero_classes: &HashMap<String, HeroClass>) -> u16 {
+        let first_class = get_promotion_chain(&self.class, hero_classes)
+            .first()
+            .and_then(|class| hero_classes.get(class))
+            .unwrap_or(self);
+        return first_class
+            .prerequisite
+            .trim_start_matches("Merchant Lv.")
+            .trim()
+            .parse()
+            .unwrap_or(0);
+    }
+
+    /// Returns the class's tier from 1, counting the distinct merchant levels classes unlock at
+    /// Promoted classes share the tier of the class they are promoted from
+    pub fn get_tier(&self, hero_classes: &HashMap<String, HeroClass>) -> u8 {
+        let unlock_level = self.get_unlock_level(hero_classes);
+        return 1 + hero_classes
+            .values()
+            .map(|hero_class| hero_class.get_unlock_level(hero_classes))
+            .filter(|level| *level < unlock_level)
+            .unique()
+            .count() as u8;
+    }
+
+    /// Returns the equipment types allowed in the given slot (0-5)
+    pub fn get_equipment_allowed(&self, slot: usize) -> Vec<String> {
+        return self.equipment_allowed[slot].clone();
+    }
+
+    pub fn get_slot_rules(&self) -> Vec<SlotRule> {
+        return self.slot_rules.clone();
+    }
+
+    /// Check the items equipped in each slot against the class's slot rules, returning the first rule broken
+    pub fn check_slot_rules(
+        &self,
+        equipment: &[String; 6],
+        bp_map: &HashMap<String, Blueprint>,
+    ) -> Result<(), HeroBuilderError> {
+        if self.slot_rules.is_empty() {
+            return Ok(());
+        }
+        let item_types = equipment
+            .iter()
+            .map(|equipment| {
+                return bp_map
+                    .get(equipment)
+                    .map(|blueprint| blueprint.get_type())
+                    .ok_or_else(|| HeroBuilderError::UnknownEquipment(equipment.to_string()));
+            })
+            .collect::<Result<Vec<String>, HeroBuilderError>>()?;
+        for rule in &self.slot_rules {
+            rule.check(equipment, &item_types)?;
+        }
+        return Ok(());
+    }
+
+    /// Returns every problem with the class data that would cause heroes of this class to fail to scale
+    pub fn get_validation_errors(&self) -> Vec<String> {
+        let mut errors: Vec<String> = vec![];
+        let levels = self.base_hp.len();
+        if levels == 0 {
+            errors.push(f!("{} has no base stats", self.class));
+        }
+        if self.base_atk.len() != levels || self.base_def.len() != levels {
+            errors.push(f!(
+                "{} has {} hp, {} atk, and {} def levels but they must be equal",
+                self.class,
+                levels,
+                self.base_atk.len(),
+                self.base_def.len()
+            ));
+        }
+        if ElementType::from_str(&self.element_type).is_err() {
+            errors.push(f!(
+                "{} has unknown element type {}",
+                self.class,
+                self.element_type
+            ));
+        }
+        if self.get_archetype().is_none() {
+            errors.push(f!(
+                "{} is not a known class and has no archetype",
+                self.class
+            ));
+        }
+        for (i, allowed) in self.equipment_allowed.iter().enumerate() {
+            if allowed.is_empty() {
+                errors.push(f!("{} allows no equipment in slot {}", self.class, i + 1));
+            }
+        }
+        for rule in &self.slot_rules {
+            for error in rule.get_validation_errors() {
+                errors.push(f!("{} has a {}", self.class, error));
+            }
+        }
+        return errors;
+    }
+}
+
+/// Add the innate skill of each class missing from the innate skills sheet, such as a scaffolded class, using the first of its innate_skills
+pub fn add_missing_class_innate_skills(
+    class_innate_skill_names_map: &mut HashMap<String, String>,
+    hero_classes: &HashMap<String, HeroClass>,
+) {
+    for (class, hero_class) in hero_classes.iter() {
+        if hero_class.get_archetype() == Some(HeroArchetype::Champion) {
+            // Champions reach innate tiers by rank and have no innate skill, see Hero::calculate_innate_tier
+            continue;
+        }
+        if !class_innate_skill_names_map.contains_key(class) {
+            class_innate_skill_names_map
+                .insert(class.to_string(), hero_class.innate_skills[0].to_string());
+        }
+    }
+}
+
+/// Returns the promotion chain the class is part of, from the class first hired to its last promotion (e.g. Barbarian, Chieftain)
+/// Classes missing from the chain's links are left out, so an unknown class has an empty chain
+pub fn get_promotion_chain(class: &str, hero_classes: &HashMap<String, HeroClass>) -> Vec<String> {
+    if !hero_classes.contains_key(class) {
+        return vec![];
+    }
+    let mut chain: Vec<String> = vec![class.to_string()];
+    while let Some(base_class) = hero_classes
+        .get(&chain[0])
+        .and_then(|hero_class| hero_class.get_promoted_from())
+        .filter(|base_class| hero_classes.contains_key(base_class) && !chain.contains(base_class))
+    {
+        chain.insert(0, base_class);
+    }
+    while let Some(promotion) = get_promotions(chain.last().unwrap(), hero_classes)
+        .into_iter()
+        .find(|promotion| !chain.contains(promotion))
+    {
+        chain.push(promotion);
+    }
+    return chain;
+}
+
+/// Returns the classes promoted from the class, sorted by name
+pub fn get_promotions(class: &str, hero_classes: &HashMap<String, HeroClass>) -> Vec<String> {
+    return hero_classes
+        .iter()
+        .filter(|(_, hero_class)| hero_class.get_promoted_from().as_deref() == Some(class))
+        .map(|(class_name, _)| class_name.to_string())
+        .sorted()
+        .collect();
+}
+
+/// Returns a description of each broken link between classes and their promotions: a promotion from an unknown class,
+/// a class promoted more than one way, or a chain of promotions that loops back on itself
+pub fn get_promotion_errors(hero_classes: &HashMap<String, HeroClass>) -> Vec<String> {
+    let mut errors: Vec<String> = vec![];
+    for (class_name, hero_class) in hero_classes
+        .iter()
+        .sorted_by_key(|(class_name, _)| *class_name)
+    {
+        if let Some(base_class) = hero_class.get_promoted_from() {
+            if !hero_classes.contains_key(&base_class) {
+                errors.push(f!(
+                    "{} is promoted from {}, which is not a class",
+                    class_name,
+                    base_class
+                ));
+            }
+        }
+        let promotions = get_promotions(class_name, hero_classes);
+        if promotions.len() > 1 {
+            errors.push(f!(
+                "{} has more than one promotion: {}",
+                class_name,
+                promotions.join(", ")
+            ));
+        }
+        let mut visited: Vec<String> = vec![class_name.to_string()];
+        let mut current = hero_class.get_promoted_from();
+        while let Some(base_class) = current {
+            if visited.contains(&base_class) {
+                errors.push(f!(
+                    "{} is in a chain of promotions that loops back on itself",
+                    class_name
+                ));
+                break;
+            }
+            current = hero_classes
+                .get(&base_class)
+                .and_then(|hero_class| hero_class.get_promoted_from());
+            visited.push(base_class);
+        }
+    }
+    return errors;
+}
+
+/// Fit a per level stat curve to the known (level, value) points of a stat, following the shape of a reference curve
+/// No points returns the reference, one point scales it, and more fit it with a least squares offset and scale
+fn fit_stat_curve(reference: &[f64], known: &[(u8, f64)]) -> Vec<f64> {
+    let points: Vec<(f64, f64)> = known
+        .iter()
+        .map(|(level, value)| (reference[usize::from(*level) - 1], *value))
+        .collect();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n.max(1.0);
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n.max(1.0);
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let (offset, scale) = match points.len() {
+        0 => (0.0, 1.0),
+        _ if variance <= 0.0 => (0.0, mean_y / mean_x.max(1.0)),
+        _ => {
+            let scale = points
+                .iter()
+                .map(|(x, y)| (x - mean_x) * (y - mean_y))
+                .sum::<f64>()
+                / variance;
+            (mean_y - scale * mean_x, scale)
+        }
+    };
+    return reference
+        .iter()
+        .map(|x| (offset + scale * x).round().max(1.0))
+        .collect();
+}
+
+/// Scaffold a class from partial data, estimating whatever isn't known from the existing classes of the same archetype and promotion closest in tier
+/// Base stats follow the average growth of those classes, fit to any known stats given as (level, [hp, atk, def])
+/// Equipment and innate skills are borrowed from the first of those classes by name, and the class is flagged as estimated
+pub fn scaffold_hero_class(
+    class: String,
+    archetype: HeroArchetype,
+    tier: u8,
+    promoted: bool,
+    element_type: Option<String>,
+    known_stats: &[(u8, [f64; 3])],
+    hero_classes: &HashMap<String, HeroClass>,
+) -> Result<(HeroClass, Vec<String>), &'static str> {
+    let candidates: Vec<&HeroClass> = hero_classes
+        .values()
+        .filter(|hero_class| {
+            hero_class.get_archetype() == Some(archetype) && hero_class.is_promoted() == promoted
+        })
+        .collect();
+    let closest_tier = match candidates
+        .iter()
+        .map(|hero_class| hero_class.get_tier(hero_classes).abs_diff(tier))
+        .min()
+    {
+        Some(closest_tier) => closest_tier,
+        None => {
+            return Err("no existing classes share the archetype and promotion to scaffold from")
+        }
+    };
+    let peers: Vec<&HeroClass> = candidates
+        .into_iter()
+        .filter(|hero_class| hero_class.get_tier(hero_classes).abs_diff(tier) == closest_tier)
+        .sorted_by_key(|hero_class| hero_class.class.to_string())
+        .collect();
+
+    let levels = peers
+        .iter()
+        .map(|hero_class| hero_class.base_hp.len())
+        .min()
+        .unwrap_or_default();
+    if known_stats
+        .iter()
+        .any(|(level, _)| *level < 1 || usize::from(*level) > levels)
+    {
+        return Err("known stats must be for levels the existing classes have stats for");
+    }
+    let average_curve = |get_curve: fn(&HeroClass) -> &Vec<f64>| -> Vec<f64> {
+        return (0..levels)
+            .map(|i| peers.iter().map(|peer| get_curve(peer)[i]).sum::<f64>() / peers.len() as f64)
+            .collect();
+    };
+    let known_stat = |stat: usize| -> Vec<(u8, f64)> {
+        return known_stats
+            .iter()
+            .map(|(level, values)| (*level, values[stat]))
+            .collect();
+    };
+    let average = |get_stat: fn(&HeroClass) -> f64| -> f64 {
+        return peers.iter().map(|peer| get_stat(peer)).sum::<f64>() / peers.len() as f64;
+    };
+
+    let first_peer = peers[0];
+    let scaffold = HeroClass {
+        class,
+        prerequisite: String::from("Unknown"),
+        gold_hire_cost: 0,
+        gem_hire_cost: 0,
+
+        base_hp: fit_stat_curve(&average_curve(|peer| &peer.base_hp), &known_stat(0)),
+        base_atk: fit_stat_curve(&average_curve(|peer| &peer.base_atk), &known_stat(1)),
+        base_def: fit_stat_curve(&average_curve(|peer| &peer.base_def), &known_stat(2)),
+        base_eva: average(|peer| peer.base_eva),
+        base_crit_chance: average(|peer| peer.base_crit_chance),
+        base_crit_mult: average(|peer| peer.base_crit_mult),
+        base_threat_rating: average(|peer| f64::from(peer.base_threat_rating)).round() as u16,
+
+        element_type: element_type.unwrap_or(first_peer.element_type.to_string()),
+        equipment_allowed: first_peer.equipment_allowed.clone(),
+        slot_rules: first_peer.slot_rules.clone(),
+
+        innate_skills: first_peer.innate_skills.clone(),
+        estimated: true,
+        archetype: Some(archetype),
     };
+    return Ok((
+        scaffold,
+        peers.iter().map(|peer| peer.class.to_string()).collect(),
+    ));
+}
+
+/// Defines the stats tracked by a StatBreakdown, in display order
+#[derive(Debug, Clone, Copy, PartialEq, strum::Display, strum::EnumIter)]
+pub enum StatType {
+    #[strum(serialize = "HP")]
+    Hp,
+    #[strum(serialize = "ATK")]
+    Atk,
+    #[strum(serialize = "DEF")]
+    Def,
+    #[strum(serialize = "EVA")]
+    Eva,
+    #[strum(serialize = "Crit Chance")]
+    CritChance,
+    #[strum(serialize = "Crit Multiplier")]
+    CritMult,
+    #[strum(serialize = "Threat Rating")]
+    ThreatRating,
+}
+
+/// Defines where a hero's stats come from, in display order
+#[derive(Debug, Clone, Copy, PartialEq, strum::Display, strum::EnumIter)]
+pub enum StatSource {
+    Base,     // Class base stats for the hero's level, scaled by its rank
+    Seeds,    // Stat seeds
+    Gear,     // Equipment base stats scaled by quality
+    Elements, // Flat bonuses from socketed elements
+    Spirits,  // Flat bonuses from socketed spirits and spirit percent bonuses
+    Skills,   // Hero, innate, and pet skill bonuses, including their bonuses to equipment
+}
+
+/// The contribution of each source to each of a hero's final stats
+/// Percent bonuses are credited to the source that grants them, so the sources of a stat always sum to its final value
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatBreakdown {
+    sources: [[f64; 6]; 7],           // Indexed by [StatType][StatSource]
+    items: Vec<(f64, f64, f64)>,      // The final (ATK, DEF, HP) of each equipped item
+    item_sources: Vec<[[f64; 3]; 3]>, // The (gear, element, spirit) sources of the ATK, DEF, and HP of each equipped item, before skill bonuses
+    skills: Vec<(String, [f64; 7])>, // Each skill's share of the Skills source, indexed by StatType, with the innate skill first and the pet's skill last
+}
+
+impl StatBreakdown {
+    pub fn get(&self, stat: StatType, source: StatSource) -> f64 {
+        return self.sources[stat as usize][source as usize];
+    }
+
+    pub fn get_total(&self, stat: StatType) -> f64 {
+        return self.sources[stat as usize].iter().sum();
+    }
+
+    /// Returns the skills credited with a share of the Skills source, in the order they are applied
+    pub fn get_skill_names(&self) -> Vec<String> {
+        return self
+            .skills
+            .iter()
+            .map(|(skill, _)| skill.to_string())
+            .collect();
+    }
+
+    /// Returns the skill's share of the Skills source of a stat, 0 for a skill not credited
+    pub fn get_skill_contribution(&self, skill: &str, stat: StatType) -> f64 {
+        return self
+            .skills
+            .iter()
+            .find(|(name, _)| name == skill)
+            .map_or(0.0, |(_, stats)| stats[stat as usize]);
+    }
+
+    fn add(&mut self, stat: StatType, source: StatSource, value: f64) {
+        self.sources[stat as usize][source as usize] += value;
+    }
+
+    fn add_skill(&mut self, skill: &str, stat: StatType, value: f64) {
+        match self.skills.iter_mut().find(|(name, _)| name == skill) {
+            Some((_, stats)) => stats[stat as usize] += value,
+            None => {
+                let mut stats = [0.0; 7];
+                stats[stat as usize] = value;
+                self.skills.push((skill.to_string(), stats));
+            }
+        }
+    }
+
+    /// Credit each skill its share of an item's skill bonuses, given the (skill, ATK %, DEF %, all stats %) each gives the item
+    /// The Spellknight's boost to chest items multiplies the item after the other bonuses, so the boost is credited to the innate skill, listed first
+    fn add_item_skill_shares(
+        &mut self,
+        slot: &SlotStats,
+        item_percents: &[(String, f64, f64, f64)],
+        spellknight_bonus: f64,
+    ) {
+        let atk: f64 = slot.atk_sources.iter().sum();
+        let def: f64 = slot.def_sources.iter().sum();
+        let hp: f64 = slot.hp_sources.iter().sum();
+        for (skill, atk_percent, def_percent, all_stats_percent) in item_percents.iter() {
+            self.add_skill(
+                skill,
+                StatType::Atk,
+                atk * (atk_percent + all_stats_percent) * spellknight_bonus,
+            );
+            self.add_skill(
+                skill,
+                StatType::Def,
+                def * (def_percent + all_stats_percent) * spellknight_bonus,
+            );
+            self.add_skill(
+                skill,
+                StatType::Hp,
+                hp * all_stats_percent * spellknight_bonus,
+            );
+            self.add_skill(skill, StatType::Eva, slot.eva * all_stats_percent);
+            self.add_skill(
+                skill,
+                StatType::CritChance,
+                slot.crit_chance * all_stats_percent,
+            );
+        }
+        if let Some((innate_skill, ..)) = item_percents.first() {
+            for (stat, value) in [
+                (StatType::Atk, atk),
+                (StatType::Def, def),
+                (StatType::Hp, hp),
+            ] {
+                self.add_skill(innate_skill, stat, value * (spellknight_bonus - 1.0));
+            }
+        }
+    }
+
+    /// Credit each skill its flat bonus to a stat and its percent bonus of the stat's flat total so far, before apply_percent_bonuses adds the total
+    fn add_skill_shares(&mut self, stat: StatType, skill_bonuses: &[SkillBonus]) {
+        let flat_total = self.get_total(stat);
+        for skill_bonus in skill_bonuses.iter() {
+            self.add_skill(
+                &skill_bonus.skill,
+                stat,
+                skill_bonus.flat[stat as usize] + flat_total * skill_bonus.percent[stat as usize],
+            );
+        }
+    }
+
+    /// Add an item's (gear, element, spirit) stat sources, crediting any difference from the item's final value to skills
+    fn add_item_sources(&mut self, stat: StatType, item_sources: [f64; 3], item_final: f64) {
+        self.add(stat, StatSource::Gear, item_sources[0]);
+        self.add(stat, StatSource::Elements, item_sources[1]);
+        self.add(stat, StatSource::Spirits, item_sources[2]);
+        self.add(
+            stat,
+            StatSource::Skills,
+            item_final - item_sources.iter().sum::<f64>(),
+        );
+    }
+
+    /// Apply percent bonuses to the flat total of a stat, crediting each bonus to its source
+    fn apply_percent_bonuses(&mut self, stat: StatType, skills_percent: f64, spirits_percent: f64) {
+        let flat_total = self.get_total(stat);
+        self.add(stat, StatSource::Skills, flat_total * skills_percent);
+        self.add(stat, StatSource::Spirits, flat_total * spirits_percent);
+    }
+}
+
+/// The flat HP, ATK, and DEF each stat seed fed to a hero adds to its base stat, before any percent bonuses
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct SeedValues {
+    pub hp: f64,
+    pub atk: f64,
+    pub def: f64,
+}
+
+impl Default for SeedValues {
+    fn default() -> SeedValues {
+        return SeedValues {
+            hp: 1.0,
+            atk: 4.0,
+            def: 4.0,
+        };
+    }
+}
+
+impl SeedValues {
+    /// Returns a description of each problem with the seed values, empty if they are valid
+    pub fn get_validation_errors(&self) -> Vec<String> {
+        let mut errors: Vec<String> = vec![];
+        for (stat, value) in [("hp", self.hp), ("atk", self.atk), ("def", self.def)] {
+            if value < 0.0 {
+                errors.push(f!("{} must not be negative", stat));
+            }
+        }
+        return errors;
+    }
+}
+
+/// Bonuses from socketed spirits that apply to the whole hero rather than to the item they are socketed in
+/// Percents are fractions, such as 0.05 for 5%, and bonuses missing from spirits.yaml are 0
+/// Spirits stack: each socket adds its spirit's bonuses, so two Wolf spirits give twice the bonus of one, and percents are summed before being applied
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(default)]
+pub struct SpiritBonuses {
+    atk_value: f64,
+    atk_percent: f64,
+    def_value: f64,
+    def_percent: f64,
+    hp_value: f64,
+    hp_percent: f64,
+    hp_regen_value: f64,
+    eva_percent: f64,
+    crit_dmg_percent: f64,
+    crit_chance_percent: f64,
+    threat_rating_value: u16,
+    survive_fatal_blow_chance_percent: f64,
+}
+
+impl SpiritBonuses {
+    fn is_negative(&self) -> bool {
+        return [
+            self.atk_value,
+            self.atk_percent,
+            self.def_value,
+            self.def_percent,
+            self.hp_value,
+            self.hp_percent,
+            self.hp_regen_value,
+            self.eva_percent,
+            self.crit_dmg_percent,
+            self.crit_chance_percent,
+            self.survive_fatal_blow_chance_percent,
+        ]
+        .iter()
+        .any(|bonus| *bonus < 0.0);
+    }
+
+    fn add(&mut self, other: &SpiritBonuses) {
+        self.atk_value += other.atk_value;
+        self.atk_percent += other.atk_percent;
+        self.def_value += other.def_value;
+        self.def_percent += other.def_percent;
+        self.hp_value += other.hp_value;
+        self.hp_percent += other.hp_percent;
+        self.hp_regen_value += other.hp_regen_value;
+        self.eva_percent += other.eva_percent;
+        self.crit_dmg_percent += other.crit_dmg_percent;
+        self.crit_chance_percent += other.crit_chance_percent;
+        self.threat_rating_value += other.threat_rating_value;
+        self.survive_fatal_blow_chance_percent += other.survive_fatal_blow_chance_percent;
+    }
+}
+
+/// A kind of spirit's bonuses to the whole hero for each one socketed, keyed in spirits.yaml by the spirit's name without its tier
+/// A spirit socketed in an item with its affinity gives its affinity bonuses instead, decided separately for each socket
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Spirit {
+    #[serde(default)]
+    bonuses: SpiritBonuses,
+    #[serde(default)]
+    affinity_bonuses: SpiritBonuses,
+}
+
+impl Spirit {
+    pub fn get_bonuses(&self, has_affinity: bool) -> &SpiritBonuses {
+        if has_affinity {
+            return &self.affinity_bonuses;
+        }
+        return &self.bonuses;
+    }
+
+    /// Returns a description of each problem with the spirit's data, empty if it is valid
+    pub fn get_validation_errors(&self) -> Vec<String> {
+        let mut errors: Vec<String> = vec![];
+        if self.bonuses.is_negative() {
+            errors.push(String::from("bonuses must not be negative"));
+        }
+        if self.affinity_bonuses.is_negative() {
+            errors.push(String::from("affinity_bonuses must not be negative"));
+        }
+        return errors;
+    }
+}
+
+/// The flat stats an element of a grade (1-4) adds to the item it is socketed in, loaded from elements.yaml
+/// An element with an element type, such as Luxurious, only applies to elements of that type, in place of the element of its grade without one
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Element {
+    #[serde(default)]
+    element_type: Option<String>,
+    grade: u8,
+    atk_bonus: f64,
+    def_bonus: f64,
+    hp_bonus: f64,
+    affinity_multiplier: f64, // Multiplies the bonuses when socketed in an item with an elemental affinity of the element's type
+}
+
+impl Element {
+    /// Returns a description of each problem with the element's data, empty if it is valid
+    pub fn get_validation_errors(&self) -> Vec<String> {
+        let mut errors: Vec<String> = vec![];
+        if get_element_grade_qty(&self.grade.to_string()).is_none() {
+            errors.push(f!("grade {} must be 1-4", self.grade));
+        }
+        if self.atk_bonus < 0.0 || self.def_bonus < 0.0 || self.hp_bonus < 0.0 {
+            errors.push(String::from("bonuses must not be negative"));
+        }
+        if self.affinity_multiplier < 1.0 {
+            errors.push(String::from("affinity_multiplier must be at least 1"));
+        }
+        return errors;
+    }
+
+    /// Returns the element as it is written in a socket, such as "Luxurious 1", or "Any 1" for an element of any type
+    pub fn get_name(&self) -> String {
+        return f!(
+            "{} {}",
+            self.element_type.as_deref().unwrap_or("Any"),
+            self.grade
+        );
+    }
+}
+
+/// Returns the element for a socketed element's type and grade, preferring one specific to the type
+pub fn find_element<'a>(
+    elements: &'a [Element],
+    element_type: &str,
+    grade: u8,
+) -> Option<&'a Element> {
+    return elements
+        .iter()
+        .filter(|element| element.grade == grade)
+        .find(|element| element.element_type.as_deref() == Some(element_type))
+        .or_else(|| {
+            elements
+                .iter()
+                .find(|element| element.grade == grade && element.element_type.is_none())
+        });
+}
+
+/// The stats an equipped item gives before skill bonuses, which depend only on the item, its quality, and what is socketed in it
+#[derive(Debug, Clone, Default, PartialEq)]
+struct SlotStats {
+    item_type: String,
+    atk_sources: [f64; 3], // The (gear, element, spirit) sources of the item's attack
+    def_sources: [f64; 3],
+    hp_sources: [f64; 3],
+    eva: f64,
+    crit_chance: f64,
+    from_chest: bool, // Items from chests have an innate element, which the Spellknight's innate skill boosts
+    spirit_bonuses: SpiritBonuses,
+}
+
+/// The bonuses a single skill gives the whole hero, indexed by StatType, so its share of the Skills source can be credited to it
+struct SkillBonus {
+    skill: String,
+    flat: [f64; 7],
+    percent: [f64; 7], // Multiplies the stat's flat total, only given for HP, ATK, and DEF
+}
+
+/// Create a skill's bonuses from its flat bonus to each stat, in StatType order, and its (HP, ATK, DEF) percent bonuses
+fn create_skill_bonus(skill: String, flat: [f64; 7], percent: [f64; 3]) -> SkillBonus {
+    let mut skill_bonus = SkillBonus {
+        skill,
+        flat,
+        percent: [0.0; 7],
+    };
+    for (stat, value) in [StatType::Hp, StatType::Atk, StatType::Def]
+        .into_iter()
+        .zip(percent)
+    {
+        skill_bonus.percent[stat as usize] = value;
+    }
+    return skill_bonus;
+}
+
+/// Returns the element qty an element enchantment of the given grade (1-4) adds, or None for an unknown grade
+fn get_element_grade_qty(grade: &str) -> Option<u16> {
+    return match grade {
+        "1" => Some(5),
+        "2" => Some(10),
+        "3" => Some(15),
+        "4" => Some(25),
+        _ => None,
+    };
+}
+
+/// Returns whether an element or spirit socket holds nothing, written as an empty string, "None", or dashes
+pub fn is_empty_socket(socket: &str) -> bool {
+    let socket = socket.trim();
+    return socket.is_empty()
+        || socket.eq_ignore_ascii_case("none")
+        || socket.chars().all(|c| c == '-');
+}
+
+/// Returns an empty string for any empty socket, so empty sockets are all written the same way
+fn clear_empty_socket(socket: String) -> String {
+    if is_empty_socket(&socket) {
+        return String::new();
+    }
+    return socket;
+}
+
+/// Returns a socket as it is written in build warnings, "nothing" for an empty socket
+fn describe_socket(socket: &str) -> &str {
+    if is_empty_socket(socket) {
+        return "nothing";
+    }
+    return socket;
+}
+
+/// Split a socketed element or spirit, such as "Fire 3" or "Wolf T7", into its name and its grade or tier
+/// Returns None for an empty socket or one not of that form
+fn split_socket(socket: &str) -> Option<(&str, &str)> {
+    if is_empty_socket(socket) {
+        return None;
+    }
+    return socket.split_whitespace().collect_tuple();
+}
+
+/// Returns the name and tier of a socketed spirit, None for an empty socket, failing unless it's of the form [name] [tier] with a known tier
+fn check_spirit_socket(socket: &str) -> Result<Option<(&str, &str)>, HeroBuilderError> {
+    if is_empty_socket(socket) {
+        return Ok(None);
+    }
+    let (name, tier) = split_socket(socket)
+        .ok_or_else(|| HeroBuilderError::MalformedSpirit(socket.to_string()))?;
+    get_spirit_tier_bonuses(socket)?;
+    return Ok(Some((name, tier)));
+}
+
+/// The multiplier an item's quality gives its base stats
+fn get_quality_bonus(quality: &str) -> Result<f64, HeroBuilderError> {
+    return match quality {
+        "Normal" => Ok(1.0),
+        "Superior" => Ok(1.25),
+        "Flawless" => Ok(1.5),
+        "Epic" => Ok(2.0),
+        "Legendary" => Ok(3.0),
+        _ => Err(HeroBuilderError::UnknownQuality(quality.to_string())),
+    };
+}
+
+/// The (ATK, DEF, HP) a spirit, such as "Wolf T7", adds to its item by its tier, before its item's spirit affinity
+fn get_spirit_tier_bonuses(spirit: &str) -> Result<(f64, f64, f64), HeroBuilderError> {
+    let tier = split_socket(spirit)
+        .ok_or_else(|| HeroBuilderError::MalformedSpirit(spirit.to_string()))?
+        .1;
+    return match tier {
+        "T4" => Ok((16.0, 11.0, 3.0)),  // Low-Tier Spirits
+        "T5" => Ok((26.0, 18.0, 5.0)),  // Xolotl Spirit
+        "T7" => Ok((41.0, 27.0, 8.0)),  // Mid-Tier Spirits
+        "T9" => Ok((48.0, 32.0, 10.0)), // High-Tier Spirits
+        "TM" => Ok((50.0, 33.0, 10.0)), // Mundra Spirit
+        // Quetzalcoatl Spirit, HP only gives 10 on banana gun T6? only 6 on T5 imperial scutum? 10 on T5 silver thistle?? must be the min stuff from ress' sheet
+        "T11" => Ok((63.0, 42.0, 13.0)),
+        "T12" => Ok((89.0, 59.0, 18.0)), // Max-Tier Spirits
+        _ => Err(HeroBuilderError::UnknownSpiritTier(spirit.to_string())),
+    };
+}
+
+/// An element socketed in an item, such as "Fire 3". Items may hold elements of any type, but only those of the hero's element type add to its element qty
+#[derive(Debug, Clone, PartialEq)]
+pub struct SocketedElement {
+    pub element_type: String,
+    pub grade: u8, // 1-4
+}
+
+impl SocketedElement {
+    /// Returns the element qty the element adds when it is of the hero's element type, before any affinity bonus
+    pub fn get_element_qty(&self) -> u16 {
+        return get_element_grade_qty(&self.grade.to_string()).unwrap();
+    }
+}
+
+/// Parse an element socket, returning None for an empty socket
+pub fn parse_element_socket(socket: &str) -> Result<Option<SocketedElement>, HeroBuilderError> {
+    if is_empty_socket(socket) {
+        return Ok(None);
+    }
+    let (element_type, grade) = split_socket(socket)
+        .ok_or_else(|| HeroBuilderError::MalformedElement(socket.to_string()))?;
+    if !matches!(ElementType::from_str(element_type), Ok(parsed) if parsed != ElementType::Any) {
+        return Err(HeroBuilderError::UnknownElementType(
+            element_type.to_string(),
+        ));
+    }
+    if get_element_grade_qty(grade).is_none() {
+        return Err(HeroBuilderError::UnknownElementGrade(grade.to_string()));
+    }
+    return Ok(Some(SocketedElement {
+        element_type: element_type.to_string(),
+        grade: grade.parse().unwrap(),
+    }));
+}
+
+/// Defines the ways a hero's class, equipment, or enchantments can fail to validate against the game data
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeroBuilderError {
+    UnknownClass(String),
+    UnknownEquipment(String),
+    DisallowedSlot {
+        equipment: String,
+        item_type: String,
+        slot: usize,
+        allowed: Vec<String>, // The item types the class can equip in the slot
+    },
+    IneligibleEnchantment {
+        equipment: String,
+        enchantment: String,
+        slot: usize,
+    },
+    MalformedElement(String), // An element not of the form [type] [grade: 1-4]
+    MalformedSpirit(String),  // A spirit not of the form [name] [tier]
+    UnknownQuality(String), // An equipment quality other than Normal, Superior, Flawless, Epic, or Legendary
+    UnknownSpiritTier(String), // A spirit tier other than those of the game's spirits, such as T42
+    UnknownElement(String), // An element with no bonuses in the elements of the game data
+    UnknownElementType(String), // An element type other than those of ElementType, such as a misspelling
+    UnknownElementGrade(String),
+    UnresolvedSkill(String), // Why a skill reference, such as "Cleave T4", names no known skill
+    SkillConflict(String), // Why the hero's skills can't be equipped together, such as a repeated skill
+    SkillSlotLocked {
+        skill: String,
+        slot: usize, // 1-4
+        rank: u8,
+    }, // A skill in a slot that unlocks at a higher rank than the hero's
+    SkillOnChampion {
+        skill: String,
+        slot: usize, // 1-4
+        champion: String,
+    }, // A skill equipped on a champion, which has no skill slots
+    MissingInnateSkill(String), // A class with no innate skill in the game data
+    UnknownRank(u8),       // A rank outside 1 to the number of HERO_RANKS
+    LevelAboveRankCap {
+        level: u8,
+        rank: u8,
+        level_cap: u8,
+    },
+    LevelBelowPromotion {
+        class: String,
+        level: u8,
+    }, // A hero of a promoted class below PROMOTION_MIN_LEVEL
+    NoInnateTier {
+        class: String,
+        innate_skill: String,
+        element_qty: u16,
+    }, // A class whose innate skill has neither a tier reached at the hero's element qty nor a tier 1 in the game data
+    TooManyOfTypes {
+        item_types: Vec<String>,
+        max: usize,
+        slots: Vec<usize>, // The slots holding items of the limited types
+    },
+    DuplicateEquipment {
+        equipment: String,
+        slots: (usize, usize),
+    },
+    UnmetSlotRequirement {
+        equipment: String,
+        slot: usize,
+        required_slot: usize,
+        required_types: Vec<String>,
+    },
+}
+
+impl std::fmt::Display for HeroBuilderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        return match self {
+            HeroBuilderError::UnknownClass(class) => write!(f, "Unknown class {}", class),
+            HeroBuilderError::UnknownEquipment(equipment) => write!(
+                f,
+                "Equipment {} could not be validated as a known item",
+                equipment
+            ),
+            HeroBuilderError::DisallowedSlot {
+                equipment,
+                item_type,
+                slot,
+                allowed,
+            } => write!(
+                f,
+                "Equipment {} is of type {} that is not allowed for this class in this slot (# {}). Valid options: {}",
+                equipment,
+                item_type,
+                slot,
+                allowed.join(", ")
+            ),
+            HeroBuilderError::IneligibleEnchantment {
+                equipment,
+                enchantment,
+                slot,
+            } => write!(
+                f,
+                "Equipment {} cannot hold the enchantment {} socketed (# {})",
+                equipment, enchantment, slot
+            ),
+            HeroBuilderError::MalformedElement(element) => write!(
+                f,
+                "Element {} must conform to format [type] [grade: 1-4]",
+                element
+            ),
+            HeroBuilderError::MalformedSpirit(spirit) => write!(
+                f,
+                "Spirit {} must conform to format [name] [tier]",
+                spirit
+            ),
+            HeroBuilderError::UnknownQuality(quality) => write!(
+                f,
+                "Unknown equipment quality {}, qualities are Normal, Superior, Flawless, Epic, and Legendary",
+                quality
+            ),
+            HeroBuilderError::UnknownSpiritTier(spirit) => {
+                write!(f, "Unknown tier of spirit {}", spirit)
+            }
+            HeroBuilderError::UnknownElement(element) => {
+                write!(f, "No bonuses in elements.yaml for element {}", element)
+            }
+            HeroBuilderError::UnknownElementType(element_type) => {
+                write!(f, "Unknown element type {}", element_type)
+            }
+            HeroBuilderError::UnknownElementGrade(grade) => {
+                write!(f, "Unknown element grade {}", grade)
+            }
+            HeroBuilderError::UnresolvedSkill(reason) => write!(f, "{}", reason),
+            HeroBuilderError::SkillConflict(conflict) => write!(f, "{}", conflict),
+            HeroBuilderError::SkillSlotLocked { skill, slot, rank } => write!(
+                f,
+                "Skill {} is in slot {} which unlocks at rank {}, but the hero is rank {}",
+                skill,
+                slot,
+                SKILL_SLOT_UNLOCK_RANKS[slot - 1],
+                rank
+            ),
+            HeroBuilderError::SkillOnChampion {
+                skill,
+                slot,
+                champion,
+            } => write!(
+                f,
+                "Skill {} is in slot {}, but the hero is the champion {} which has no skill slots",
+                skill, slot, champion
+            ),
+            HeroBuilderError::MissingInnateSkill(class) => {
+                write!(f, "Class {} has no innate skill", class)
+            }
+            HeroBuilderError::UnknownRank(rank) => write!(
+                f,
+                "Unknown rank {}, heroes are ranks 1-{}",
+                rank,
+                HERO_RANKS.len()
+            ),
+            HeroBuilderError::LevelAboveRankCap {
+                level,
+                rank,
+                level_cap,
+            } => write!(
+                f,
+                "Level {} is above the level cap of rank {}, which is {}",
+                level, rank, level_cap
+            ),
+            HeroBuilderError::LevelBelowPromotion { class, level } => write!(
+                f,
+                "Level {} is too low for {}, a promoted class, since heroes are promoted at level {} or higher. Raise the level or use the class it is promoted from",
+                level, class, PROMOTION_MIN_LEVEL
+            ),
+            HeroBuilderError::NoInnateTier {
+                class,
+                innate_skill,
+                element_qty,
+            } => write!(
+                f,
+                "No innate found for class {} at element qty {}: innate skill {} has no tier reached at that qty and no tier 1",
+                class, element_qty, innate_skill
+            ),
+            HeroBuilderError::TooManyOfTypes {
+                item_types,
+                max,
+                slots,
+            } => write!(
+                f,
+                "This class can equip at most {} items of type {} but slots (# {}) hold them. Unequip {} of them",
+                max,
+                item_types.join(", "),
+                slots.iter().join(", # "),
+                slots.len() - max
+            ),
+            HeroBuilderError::DuplicateEquipment { equipment, slots } => write!(
+                f,
+                "Equipment {} is equipped in both slot (# {}) and slot (# {}) but this class can only equip one. Replace one of them",
+                equipment, slots.0, slots.1
+            ),
+            HeroBuilderError::UnmetSlotRequirement {
+                equipment,
+                slot,
+                required_slot,
+                required_types,
+            } => write!(
+                f,
+                "Equipment {} in slot (# {}) needs an item of type {} in slot (# {}) for this class. Change either slot",
+                equipment,
+                slot,
+                required_types.join(" or "),
+                required_slot
+            ),
+        };
+    }
 }
 
+impl std::error::Error for HeroBuilderError {}
+
 /// Defines a Hero that contains info on base stats, equipment, and skills
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Hero {
@@ -106,6 +1414,17 @@ pub struct Hero {
     equipment_quality: [String; 6],
     elements_socketed: [String; 6],
     spirits_socketed: [String; 6],
+
+    #[serde(skip)]
+    pet: Option<Pet>, // Set with the hero's final stats if its pet skill acts in combat
+    #[serde(skip)]
+    skill_effects: Vec<SkillEffect>, // Set with the hero's final stats from the combat effects of its skills
+    #[serde(skip)]
+    rest_time_percent: f64, // Set with the hero's final stats, the fraction its skills reduce rest time by
+    #[serde(skip)]
+    xp_percent: f64, // Set with the hero's final stats, the extra fraction of quest xp its skills earn
+    #[serde(skip)]
+    archetype: Option<HeroArchetype>, // Set from the class data when the hero is scaled by class, for classes not known by name
 }
 
 pub fn create_hero(
@@ -167,106 +1486,358 @@ pub fn create_hero(
         atk_seeds,
         def_seeds,
 
-        skills,
+        skills: skills.map(|skill| {
+            if is_empty_skill(&skill) {
+                return String::from(EMPTY_SKILL);
+            }
+            return skill;
+        }),
 
         equipment_equipped,
         equipment_quality,
-        elements_socketed,
-        spirits_socketed,
+        elements_socketed: elements_socketed.map(clear_empty_socket),
+        spirits_socketed: spirits_socketed.map(clear_empty_socket),
+
+        pet: None,
+        skill_effects: vec![],
+        rest_time_percent: 0.0,
+        xp_percent: 0.0,
+        archetype: None,
     };
 }
 
 impl Hero {
+    pub fn get_identifier(&self) -> String {
+        return self.identifier.to_string();
+    }
+
+    pub fn set_identifier(&mut self, identifier: String) {
+        self.identifier = identifier;
+    }
+
+    pub fn get_class(&self) -> String {
+        return self.class.to_string();
+    }
+
+    /// Replace the hero's class, keeping its build, such as to compare the hero before and after promotion. Validate the hero's equipment again after
+    pub fn set_class(&mut self, class: String) {
+        self.class = class;
+    }
+
+    pub fn get_skills(&self) -> [String; 4] {
+        return self.skills.clone();
+    }
+
+    pub fn get_equipment_equipped(&self) -> [String; 6] {
+        return self.equipment_equipped.clone();
+    }
+
+    pub fn get_equipment_quality(&self) -> [String; 6] {
+        return self.equipment_quality.clone();
+    }
+
+    /// Replace the item in the given slot (0-5), keeping its quality and socketed element and spirit
+    pub fn set_equipment(&mut self, slot: usize, equipment: String) {
+        self.equipment_equipped[slot] = equipment;
+    }
+
+    /// Set the quality of the item in the given slot (0-5), such as "Epic"
+    pub fn set_equipment_quality(&mut self, slot: usize, quality: String) {
+        self.equipment_quality[slot] = quality;
+    }
+
+    /// Empty the sockets of the given slot that its new item can't hold, used after set_equipment
+    pub fn clear_ineligible_enchantments(&mut self, slot: usize, blueprint: &Blueprint) {
+        let eligibility = blueprint.get_enchant_eligibility();
+        if !eligibility.element {
+            self.elements_socketed[slot] = String::new();
+        }
+        if !eligibility.spirit {
+            self.spirits_socketed[slot] = String::new();
+        }
+    }
+
+    /// Returns the slot (0-5) and name of the hero's pet, the first equipped familiar, if it has one
+    pub fn get_pet(&self, bp_map: &HashMap<String, Blueprint>) -> Option<(usize, String)> {
+        return self
+            .equipment_equipped
+            .iter()
+            .position(|equipment| {
+                bp_map
+                    .get(equipment)
+                    .is_some_and(|blueprint| blueprint.get_type() == PET_ITEM_TYPE)
+            })
+            .map(|slot| (slot, self.equipment_equipped[slot].to_string()));
+    }
+
+    /// Returns a short description of how this build differs from another build of the same hero, e.g. "+Skill A -Skill B; slot 1: Item A -> Item B"
+    pub fn describe_changes_from(&self, original: &Hero) -> String {
+        let mut changes: Vec<String> = vec![];
+        let added = self
+            .skills
+            .iter()
+            .filter(|skill| !is_empty_skill(skill) && !original.skills.contains(skill))
+            .map(|skill| f!("+{}", skill));
+        let removed = original
+            .skills
+            .iter()
+            .filter(|skill| !is_empty_skill(skill) && !self.skills.contains(skill))
+            .map(|skill| f!("-{}", skill));
+        let skill_changes = added.chain(removed).join(" ");
+        if !skill_changes.is_empty() {
+            changes.push(skill_changes);
+        }
+        for (i, equipment) in self.equipment_equipped.iter().enumerate() {
+            if *equipment != original.equipment_equipped[i] {
+                changes.push(f!(
+                    "slot {}: {} -> {}",
+                    i + 1,
+                    original.equipment_equipped[i],
+                    equipment
+                ));
+            }
+        }
+        if changes.is_empty() {
+            return String::from("Original");
+        }
+        return changes.join("; ");
+    }
+
+    /// Returns a stable, human-readable signature of the build in the form class|gear|qualities|sockets|skills
+    /// Gear, qualities, and sockets are listed in equipment slot order, skills are sorted since their slot order has no effect
+    pub fn get_build_signature(&self) -> String {
+        let or_dash = |s: &String| {
+            if s.is_empty() {
+                return String::from("-");
+            }
+            return s.to_string();
+        };
+        let mut skills: Vec<String> = self
+            .skills
+            .iter()
+            .filter(|skill| !is_empty_skill(skill))
+            .cloned()
+            .collect();
+        skills.sort();
+        let sockets: Vec<String> = self
+            .elements_socketed
+            .iter()
+            .zip(self.spirits_socketed.iter())
+            .map(|(element, spirit)| f!("{}/{}", or_dash(element), or_dash(spirit)))
+            .collect();
+
+        return [
+            self.class.to_string(),
+            self.equipment_equipped.iter().map(or_dash).join(","),
+            self.equipment_quality.iter().map(or_dash).join(","),
+            sockets.join(","),
+            or_dash(&skills.join(",")),
+        ]
+        .join("|");
+    }
+
+    /// Set the hero's skills in slot order, padding any slots not given with the empty skill
     pub fn set_hero_skills(&mut self, new_skills: Vec<String>) {
-        self.skills[0] = new_skills.get(0).unwrap_or(&String::from("")).to_string();
-        self.skills[1] = new_skills.get(1).unwrap_or(&String::from("")).to_string();
-        self.skills[2] = new_skills.get(2).unwrap_or(&String::from("")).to_string();
-        self.skills[3] = new_skills.get(3).unwrap_or(&String::from("")).to_string();
+        for (i, skill) in self.skills.iter_mut().enumerate() {
+            *skill = match new_skills.get(i) {
+                Some(new_skill) if !is_empty_skill(new_skill) => new_skill.to_string(),
+                _ => String::from(EMPTY_SKILL),
+            };
+        }
     }
 
+    /// Returns the champion the hero is, if its class is a champion rather than a hero class
+    pub fn get_champion(&self) -> Option<Champion> {
+        return Champion::from_str(&self.class).ok();
+    }
+
+    /// Returns the number of skill slots unlocked at the hero's rank, which are always the first slots. Champions have none
+    pub fn count_skill_slots_unlocked(&self) -> usize {
+        if self.get_champion().is_some() {
+            return 0;
+        }
+        return SKILL_SLOT_UNLOCK_RANKS
+            .iter()
+            .filter(|unlock_rank| self.rank >= **unlock_rank)
+            .count();
+    }
+
+    /// Returns the level cap and stat multiplier of the hero's rank, or an error if the rank is unknown or the hero's level is above its cap
+    /// Champions rank up their ability rather than their stats, so their rank neither caps their level nor scales their stats
+    pub fn get_rank_scaling(&self) -> Result<HeroRank, HeroBuilderError> {
+        if self.get_champion().is_some() {
+            return Ok(HeroRank {
+                level_cap: u8::MAX,
+                stat_multiplier: 1.0,
+            });
+        }
+        let hero_rank = get_hero_rank(self.rank).ok_or(HeroBuilderError::UnknownRank(self.rank))?;
+        if self.level > hero_rank.level_cap {
+            return Err(HeroBuilderError::LevelAboveRankCap {
+                level: self.level,
+                rank: self.rank,
+                level_cap: hero_rank.level_cap,
+            });
+        }
+        return Ok(hero_rank);
+    }
+
+    /// Checks the hero's class, level, equipment, and enchantments against the game data, then sets its element qty
     pub fn validate_equipment(
         &mut self,
         bp_map: &HashMap<String, Blueprint>,
         hero_classes: &HashMap<String, HeroClass>,
-    ) {
-        if !hero_classes.contains_key(&self.class) {
-            panic!(
-                "Encountered unknown class {} for hero {}",
-                self.class, self.identifier
-            );
+    ) -> Result<(), HeroBuilderError> {
+        let class = hero_classes
+            .get(&self.class)
+            .ok_or_else(|| HeroBuilderError::UnknownClass(self.class.to_string()))?;
+        if class.is_promoted() && self.level < PROMOTION_MIN_LEVEL {
+            return Err(HeroBuilderError::LevelBelowPromotion {
+                class: self.class.to_string(),
+                level: self.level,
+            });
         }
-        let class = hero_classes.get(&self.class).unwrap();
-
-        let mut element_qty = 0u16;
 
         for (i, equipment) in self.equipment_equipped.iter().enumerate() {
-            if !bp_map.contains_key(equipment) {
-                panic!(
-                    "Equipment {} could not be validated as a known item",
-                    equipment
-                );
-            }
-            let blueprint = bp_map.get(equipment).unwrap();
+            let blueprint = bp_map
+                .get(equipment)
+                .ok_or_else(|| HeroBuilderError::UnknownEquipment(equipment.to_string()))?;
             if !class.equipment_allowed[i].contains(&blueprint.get_type()) {
-                panic!(
-                    "Equipment {} is of type {} that is not allowed for this class in this slot (# {}). Valid options: {:#?}",
-                    equipment,
-                    blueprint.get_type(),
-                    i,
-                    class.equipment_allowed,
-                )
+                return Err(HeroBuilderError::DisallowedSlot {
+                    equipment: equipment.to_string(),
+                    item_type: blueprint.get_type(),
+                    slot: i,
+                    allowed: class.equipment_allowed[i].clone(),
+                });
             }
 
-            let split_vec = self.elements_socketed[i].split(" ").collect::<Vec<&str>>();
-            if split_vec.len() < 2 {
-                panic!(
-                    "Element {} must conform to format [type] [grade: 1-4]",
-                    self.elements_socketed[i]
-                );
-            }
-            let element = split_vec[0];
-            let grade = split_vec[1];
-            if element == self.element_type {
-                match grade {
-                    "1" => element_qty += 5,
-                    "2" => element_qty += 10,
-                    "3" => element_qty += 15,
-                    "4" => element_qty += 25,
-                    _ => panic!("Unknown element grade {}", grade),
-                }
-                if element == blueprint.get_elemental_affinity() {
-                    element_qty += 5;
+            let eligibility = blueprint.get_enchant_eligibility();
+            for (eligible, enchantment) in [
+                (eligibility.element, &self.elements_socketed[i]),
+                (eligibility.spirit, &self.spirits_socketed[i]),
+            ] {
+                if !eligible && !is_empty_socket(enchantment) {
+                    return Err(HeroBuilderError::IneligibleEnchantment {
+                        equipment: equipment.to_string(),
+                        enchantment: enchantment.to_string(),
+                        slot: i,
+                    });
                 }
-            } else {
-                panic!("Unknown element type {}", element);
             }
+            get_quality_bonus(&self.equipment_quality[i])?;
+            check_spirit_socket(&self.spirits_socketed[i])?;
+            parse_element_socket(&self.elements_socketed[i])?;
         }
+        class.check_slot_rules(&self.equipment_equipped, bp_map)?;
 
-        self.element_qty = element_qty;
+        self.element_qty = self.calculate_element_qty(bp_map)?;
+        return Ok(());
     }
 
-    pub fn calculate_innate_skill_name(
+    /// Returns the element socketed in each slot, None for empty sockets
+    pub fn get_socketed_elements(&self) -> Result<[Option<SocketedElement>; 6], HeroBuilderError> {
+        let sockets = self
+            .elements_socketed
+            .iter()
+            .map(|socket| parse_element_socket(socket))
+            .collect::<Result<Vec<Option<SocketedElement>>, HeroBuilderError>>()?;
+        return Ok(sockets.try_into().unwrap());
+    }
+
+    /// Returns the element qty the hero's socketed elements add up to, counting only elements of the hero's element type
+    /// Elements matching their item's elemental affinity add 5 more. Elements of other types still add their stats to their items
+    pub fn calculate_element_qty(
         &self,
-        class_innate_skill_names_map: &HashMap<String, String>,
-    ) -> String {
-        if !class_innate_skill_names_map.contains_key(&self.class) {
-            // Class not found in map
-            panic!(
-                "Class {} could not be found in keys for class_innate_skill_names_map",
-                self.class
-            );
+        bp_map: &HashMap<String, Blueprint>,
+    ) -> Result<u16, HeroBuilderError> {
+        let mut element_qty = 0u16;
+        for (equipment, socket) in self
+            .equipment_equipped
+            .iter()
+            .zip(self.get_socketed_elements()?)
+        {
+            let Some(element) = socket.filter(|element| element.element_type == self.element_type)
+            else {
+                continue;
+            };
+            element_qty += element.get_element_qty();
+            let blueprint = bp_map
+                .get(equipment)
+                .ok_or_else(|| HeroBuilderError::UnknownEquipment(equipment.to_string()))?;
+            if element.element_type == blueprint.get_elemental_affinity() {
+                element_qty += 5;
+            }
         }
+        return Ok(element_qty);
+    }
 
-        let innate_skill = class_innate_skill_names_map[&self.class].clone();
-        return innate_skill;
+    /// Replace any skill references, such as "Cleave T4" or a tier 1 name given a tier in a hero csv, with the names of the skills they refer to
+    pub fn resolve_skill_references(
+        &mut self,
+        hero_skill_tier_1_name_map: &HashMap<String, String>,
+        hero_skill_map: &HashMap<String, HeroSkill>,
+    ) -> Result<(), HeroBuilderError> {
+        for skill in self.skills.iter_mut() {
+            *skill = resolve_skill_reference(skill, hero_skill_tier_1_name_map, hero_skill_map)
+                .map_err(HeroBuilderError::UnresolvedSkill)?;
+        }
+        return Ok(());
     }
 
+    /// Fails if the hero's skills can't be equipped together, such as a repeated skill or two tiers of the same skill line,
+    /// or if a skill is in a slot that is still locked at the hero's rank
+    pub fn validate_skills(
+        &self,
+        hero_skill_map: &HashMap<String, HeroSkill>,
+    ) -> Result<(), HeroBuilderError> {
+        let unlocked_qty = self.count_skill_slots_unlocked();
+        for (i, skill) in self.skills.iter().enumerate().skip(unlocked_qty) {
+            if is_empty_skill(skill) {
+                continue;
+            }
+            if self.get_champion().is_some() {
+                return Err(HeroBuilderError::SkillOnChampion {
+                    skill: skill.to_string(),
+                    slot: i + 1,
+                    champion: self.class.to_string(),
+                });
+            }
+            return Err(HeroBuilderError::SkillSlotLocked {
+                skill: skill.to_string(),
+                slot: i + 1,
+                rank: self.rank,
+            });
+        }
+        return match find_skill_conflict(&self.skills, hero_skill_map) {
+            Some(conflict) => Err(HeroBuilderError::SkillConflict(conflict)),
+            None => Ok(()),
+        };
+    }
+
+    pub fn calculate_innate_skill_name(
+        &self,
+        class_innate_skill_names_map: &HashMap<String, String>,
+    ) -> Result<String, HeroBuilderError> {
+        return class_innate_skill_names_map
+            .get(&self.class)
+            .cloned()
+            .ok_or_else(|| HeroBuilderError::MissingInnateSkill(self.class.to_string()));
+    }
+
+    /// Sets the hero's innate tier to the highest tier of its class's innate skill its element qty reaches, or to tier 1 if it reaches none
+    /// Champions instead reach the innate tier of their rank, see ChampionData::calculate_innate_tier
     pub fn calculate_innate_tier(
         &mut self,
         class_innate_skill_names_map: &HashMap<String, String>,
         innate_skill_map: &HashMap<String, InnateSkill>,
-    ) {
-        let innate_skill = self.calculate_innate_skill_name(class_innate_skill_names_map);
+        champion_data: &HashMap<Champion, ChampionData>,
+    ) -> Result<(), HeroBuilderError> {
+        if let Some(champion) = self.get_champion() {
+            self.innate_tier =
+                find_champion_data(champion_data, champion).calculate_innate_tier(self.rank);
+            return Ok(());
+        }
+        let innate_skill = self.calculate_innate_skill_name(class_innate_skill_names_map)?;
 
         let mut innate_skill_variants: Vec<&InnateSkill> = innate_skill_map
             .values()
@@ -277,31 +1848,49 @@ impl Hero {
 
         innate_skill_variants.sort_unstable_by_key(|is| is.get_skill_tier());
 
-        let innate_skill_info = innate_skill_variants[innate_skill_variants.len() - 1];
+        let innate_skill_info = match innate_skill_variants.last() {
+            Some(innate_skill_info) => *innate_skill_info,
+            None => innate_skill_map
+                .values()
+                .find(|is| is.get_tier_1_name() == innate_skill && is.get_skill_tier() == 1)
+                .ok_or_else(|| HeroBuilderError::NoInnateTier {
+                    class: self.class.to_string(),
+                    innate_skill: innate_skill.to_string(),
+                    element_qty: self.element_qty,
+                })?,
+        };
 
         self.innate_tier = innate_skill_info.get_skill_tier();
+        return Ok(());
     }
 
-    /// Calculate skill tier and get the correct skill
+    /// Calculate skill tier and get the correct skill, failing if the skill or one of its tiers is missing from the game data
     pub fn calculate_hero_skill_tier(
         &self,
         hero_skill_tier_1_name_map: &HashMap<String, String>,
         hero_skill_map: &HashMap<String, HeroSkill>,
         base_skill_name: String,
-    ) -> (u8, HeroSkill) {
-        if !hero_skill_map.contains_key(&base_skill_name) {
-            panic!("Unknown skill name: {}", base_skill_name);
-        }
-
-        let mut skill = &hero_skill_map[&base_skill_name];
+    ) -> Result<(u8, HeroSkill), HeroBuilderError> {
+        let find_tier = |tier_1_name: &str, tier: u8| {
+            let tier_formatted_skill_name = f!("{} T{}", tier_1_name, tier);
+            return hero_skill_tier_1_name_map
+                .get(&tier_formatted_skill_name)
+                .and_then(|skill_name| hero_skill_map.get(skill_name))
+                .ok_or_else(|| {
+                    HeroBuilderError::UnresolvedSkill(f!(
+                        "Unknown skill name: {}",
+                        tier_formatted_skill_name
+                    ))
+                });
+        };
+        let mut skill = hero_skill_map.get(&base_skill_name).ok_or_else(|| {
+            HeroBuilderError::UnresolvedSkill(f!("Unknown skill name: {}", base_skill_name))
+        })?;
         let mut tier = skill.get_skill_tier();
         let mut checked_upgrade = false;
 
         loop {
-            let tier_formatted_skill_name = f!("{} T{}", skill.get_tier_1_name(), tier);
-            let tier_adjusted_skill_name =
-                hero_skill_tier_1_name_map[&tier_formatted_skill_name].to_string();
-            skill = &hero_skill_map[&tier_adjusted_skill_name];
+            skill = find_tier(&skill.get_tier_1_name(), tier)?;
             let skill_tier_ele_req = skill.get_element_qty_req();
 
             if self.element_qty < skill_tier_ele_req {
@@ -321,18 +1910,15 @@ impl Hero {
             }
         }
 
-        let tier_formatted_skill_name = f!("{} T{}", skill.get_tier_1_name(), tier);
-        let tier_adjusted_skill_name =
-            hero_skill_tier_1_name_map[&tier_formatted_skill_name].to_string();
-
-        return (tier, hero_skill_map[&tier_adjusted_skill_name].clone());
+        return Ok((tier, find_tier(&skill.get_tier_1_name(), tier)?.clone()));
     }
 
-    pub fn calculate_spirit_qty(&self, spirit_name: String) -> u8 {
+    /// Returns the sockets holding a spirit of the given name, such as "Armadillo", at any tier
+    pub fn calculate_spirit_qty(&self, spirit_name: &str) -> u8 {
         let spirit_qty = u8::try_from(
             self.spirits_socketed
                 .iter()
-                .filter(|x| **x == spirit_name)
+                .filter(|socket| split_socket(socket).is_some_and(|(name, _)| name == spirit_name))
                 .count(),
         )
         .unwrap_or_default();
@@ -394,27 +1980,39 @@ impl Hero {
     //     self.def_modifier = defense_modifier;
     // }
 
-    pub fn scale_by_class(&mut self, hero_classes: &HashMap<String, HeroClass>) {
-        if !hero_classes.contains_key(&self.class) {
-            panic!(
-                "Encountered unknown class {} for hero {}",
-                self.class, self.identifier
-            );
-        }
-        let class = hero_classes.get(&self.class).unwrap();
+    /// Sets the hero's base stats to its class's at its level, with hp, atk, and def scaled by its rank
+    /// Fails if the class is unknown, the rank is unknown, or the level is above the rank's level cap
+    pub fn scale_by_class(
+        &mut self,
+        hero_classes: &HashMap<String, HeroClass>,
+    ) -> Result<(), HeroBuilderError> {
+        let class = hero_classes
+            .get(&self.class)
+            .ok_or_else(|| HeroBuilderError::UnknownClass(self.class.to_string()))?;
+        let hero_rank = self.get_rank_scaling()?;
 
         let level_index = usize::from(self.level - 1);
-        self.hp = class.base_hp[level_index];
-        self.atk = class.base_atk[level_index];
-        self.def = class.base_def[level_index];
+        self.hp = (class.base_hp[level_index] * hero_rank.stat_multiplier).round();
+        self.atk = (class.base_atk[level_index] * hero_rank.stat_multiplier).round();
+        self.def = (class.base_def[level_index] * hero_rank.stat_multiplier).round();
         self.eva = class.base_eva;
         self.crit_chance = class.base_crit_chance;
         self.crit_mult = class.base_crit_mult;
         self.threat_rating = class.base_threat_rating;
 
         self.element_type = class.element_type.to_string();
+        self.archetype = class.get_archetype();
+        return Ok(());
+    }
+
+    /// Take the archetype of the hero's class from the class data, for heroes not scaled by class since being loaded, such as from a checkpoint
+    pub fn resolve_archetype(&mut self, hero_classes: &HashMap<String, HeroClass>) {
+        self.archetype = hero_classes
+            .get(&self.class)
+            .and_then(|class| class.get_archetype());
     }
 
+    /// Fails if a skill reference can't be resolved or the class has no innate skill tier for the hero
     pub fn calculate_stat_improvements_from_gear_and_skills(
         &mut self,
         bp_map: &HashMap<String, Blueprint>,
@@ -422,430 +2020,343 @@ impl Hero {
         hero_skill_map: &HashMap<String, HeroSkill>,
         class_innate_skill_names_map: &HashMap<String, String>,
         innate_skill_map: &HashMap<String, InnateSkill>,
-    ) {
-        let mut blueprints: Vec<Blueprint> = Default::default();
-        for equip_name in &self.equipment_equipped {
-            blueprints.push(bp_map[equip_name].clone());
-        }
+        pet_skill_map: &HashMap<String, PetSkill>,
+        spirit_map: &HashMap<String, Spirit>,
+        elements: &[Element],
+        seed_values: &SeedValues,
+    ) -> Result<StatBreakdown, HeroBuilderError> {
+        self.resolve_skill_references(hero_skill_tier_1_name_map, hero_skill_map)?;
+        self.validate_skills(hero_skill_map)?;
+        let innate_skill =
+            self.find_innate_skill(class_innate_skill_names_map, innate_skill_map)?;
+        let slots = self
+            .equipment_equipped
+            .iter()
+            .enumerate()
+            .map(|(slot, equip_name)| {
+                let blueprint = bp_map
+                    .get(equip_name)
+                    .ok_or_else(|| HeroBuilderError::UnknownEquipment(equip_name.to_string()))?;
+                return self.calculate_slot_stats(slot, blueprint, spirit_map, elements);
+            })
+            .collect::<Result<Vec<SlotStats>, HeroBuilderError>>()?;
+        let skills = self.resolve_hero_skills(hero_skill_tier_1_name_map, hero_skill_map)?;
+        let pet_skill = self
+            .get_pet(bp_map)
+            .and_then(|(_, pet)| pet_skill_map.get(&pet))
+            .cloned();
+        return Ok(self.apply_stat_improvements(
+            &slots,
+            &innate_skill,
+            &skills,
+            pet_skill.as_ref(),
+            seed_values,
+        ));
+    }
 
-        let innate_skill_name = self.calculate_innate_skill_name(class_innate_skill_names_map);
-        let innate_skill = innate_skill_map
+    /// Returns the hero's innate skill at its innate tier
+    /// A champion's ability applies to the whole team when it is simulated, so champions get an innate skill with no effect on their own stats
+    fn find_innate_skill(
+        &self,
+        class_innate_skill_names_map: &HashMap<String, String>,
+        innate_skill_map: &HashMap<String, InnateSkill>,
+    ) -> Result<InnateSkill, HeroBuilderError> {
+        if self.get_champion().is_some() {
+            return Ok(create_innate_skill(
+                f!("{} T{}", self.class, self.innate_tier),
+                String::from("Champion"),
+                self.innate_tier,
+                0,
+                self.class.to_string(),
+                false,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                vec![],
+                vec![],
+            ));
+        }
+        let innate_skill_name = self.calculate_innate_skill_name(class_innate_skill_names_map)?;
+        return innate_skill_map
             .values()
-            .filter(|v| {
+            .find(|v| {
                 v.get_tier_1_name() == innate_skill_name && v.get_skill_tier() == self.innate_tier
             })
-            .collect::<Vec<&InnateSkill>>()[0];
-
-        let mut equip_atk_value = 0.0f64;
-        let mut equip_hp_value = 0.0f64;
-        let mut equip_def_value = 0.0f64;
-        let mut equip_eva_percent = 0.0f64;
-        let mut equip_crit_chance_percent = 0.0f64;
-
-        let mut spirit_bonus_atk_value: f64 = 0.0;
-        let mut spirit_bonus_atk_percent: f64 = 0.0;
-        let mut spirit_bonus_def_value: f64 = 0.0;
-        let mut spirit_bonus_def_percent: f64 = 0.0;
-        let mut spirit_bonus_hp_value: f64 = 0.0;
-        let mut spirit_bonus_hp_percent: f64 = 0.0;
-        let mut spirit_bonus_hp_regen_value: f64 = 0.0;
-        let mut spirit_bonus_eva_percent: f64 = 0.0;
-        let mut spirit_bonus_crit_dmg_percent: f64 = 0.0;
-        let mut spirit_bonus_crit_chance_percent: f64 = 0.0;
-        let mut spirit_bonus_threat_rating_value: u16 = 0;
-        let _spirit_bonus_rest_time_percent: f64 = 0.0;
-        let _spirit_bonus_xp_percent: f64 = 0.0;
-        let mut spirit_bonus_survive_fatal_blow_chance_percent = 0.0f64;
-
-        // Calculate gear bonuses
-        for (gear_index, blueprint) in blueprints.iter().enumerate() {
-            let mut bonus_item_all_stats_percent = 0.0f64;
-            let mut bonus_item_atk_percent = 0.0f64;
-            let mut bonus_item_def_percent = 0.0f64;
-
-            // Check for bonus stats from innate skill
-            bonus_item_all_stats_percent +=
-                innate_skill.get_bonus_stats_from_all_equipment_percent();
-
-            if innate_skill.get_item_types().len() > 0 {
-                // Has bonuses associated with atleast one item type
-                for itype in innate_skill.get_item_types() {
-                    if blueprint.get_type() == itype {
-                        // Have that type equipped, apply bonus(es)
-                        bonus_item_atk_percent += innate_skill.get_attack_with_item_percent();
-                        bonus_item_def_percent += innate_skill.get_defense_with_item_percent();
-                        bonus_item_all_stats_percent +=
-                            innate_skill.get_all_stats_with_item_percent();
-                    }
-                }
-            }
-
-            // Check for skills that give bonus stats to gear
-            for skill_name in &self.skills {
-                if skill_name == "" {
-                    continue;
-                }
-                if !hero_skill_map.contains_key(skill_name) {
-                    panic!(
-                        "Skill {} could not be found in keys for hero_skill_map",
-                        skill_name
-                    );
-                }
-
-                // Calculate skill tier and get the correct skill
-                let (_, skill) = self.calculate_hero_skill_tier(
-                    hero_skill_tier_1_name_map,
-                    hero_skill_map,
-                    skill_name.to_string(),
-                );
-
-                // Get all stats bonus if applicable
-                bonus_item_all_stats_percent += skill.get_bonus_stats_from_all_equipment_percent();
+            .cloned()
+            .ok_or(HeroBuilderError::NoInnateTier {
+                class: self.class.to_string(),
+                innate_skill: innate_skill_name,
+                element_qty: self.element_qty,
+            });
+    }
 
-                if skill.get_item_types().len() > 0 {
-                    // Has bonuses associated with atleast one item type
-                    for itype in skill.get_item_types() {
-                        if blueprint.get_type() == itype {
-                            // Have that type equipped, apply bonus(es)
-                            bonus_item_atk_percent += skill.get_attack_with_item_percent();
-                            bonus_item_def_percent += skill.get_defense_with_item_percent();
-                        }
-                    }
-                }
-            }
+    /// Returns the skill in each skill slot at the tier the hero's element qty reaches, None for empty slots
+    fn resolve_hero_skills(
+        &self,
+        hero_skill_tier_1_name_map: &HashMap<String, String>,
+        hero_skill_map: &HashMap<String, HeroSkill>,
+    ) -> Result<[Option<HeroSkill>; 4], HeroBuilderError> {
+        let skills = self
+            .skills
+            .clone()
+            .map(|skill_name| {
+                self.resolve_hero_skill(hero_skill_tier_1_name_map, hero_skill_map, skill_name)
+            })
+            .into_iter()
+            .collect::<Result<Vec<Option<HeroSkill>>, HeroBuilderError>>()?;
+        return Ok(skills.try_into().unwrap());
+    }
 
-            let gear_quality = self.equipment_quality[gear_index].as_str();
-            let gear_quality_bonus: f64;
-            match gear_quality {
-                "Normal" => gear_quality_bonus = 1.0,
-                "Superior" => gear_quality_bonus = 1.25,
-                "Flawless" => gear_quality_bonus = 1.5,
-                "Epic" => gear_quality_bonus = 2.0,
-                "Legendary" => gear_quality_bonus = 3.0,
-                _ => panic!("Unknown gear_quality {}", gear_quality),
-            }
+    fn resolve_hero_skill(
+        &self,
+        hero_skill_tier_1_name_map: &HashMap<String, String>,
+        hero_skill_map: &HashMap<String, HeroSkill>,
+        skill_name: String,
+    ) -> Result<Option<HeroSkill>, HeroBuilderError> {
+        if is_empty_skill(&skill_name) {
+            return Ok(None);
+        }
+        let (_, skill) =
+            self.calculate_hero_skill_tier(hero_skill_tier_1_name_map, hero_skill_map, skill_name)?;
+        return Ok(Some(skill));
+    }
 
-            let gear_element = &self.elements_socketed[gear_index];
-            let gear_element_split = gear_element.split_whitespace().collect::<Vec<&str>>();
-            let gear_element_tier = gear_element_split[1].parse::<u8>().unwrap();
-
-            let mut gear_element_atk_bonus: f64;
-            let mut gear_element_def_bonus: f64;
-            let mut gear_element_hp_bonus: f64;
-
-            match gear_element_tier {
-                1u8 => {
-                    // Check 5 / Tier 5 (Luxurious)
-                    if *gear_element == String::from("Luxurious 1") {
-                        gear_element_atk_bonus = 26.0;
-                        gear_element_def_bonus = 18.0;
-                        gear_element_hp_bonus = 5.0;
-                    } else {
-                        gear_element_atk_bonus = 14.0;
-                        gear_element_def_bonus = 10.0;
-                        gear_element_hp_bonus = 3.0;
-                    }
-                }
-                2u8 => {
-                    gear_element_atk_bonus = 38.0;
-                    gear_element_def_bonus = 25.0;
-                    gear_element_hp_bonus = 8.0;
-                }
-                3u8 => {
-                    // Check 15 / Tier 10 (Opulent)
-                    if *gear_element == String::from("Opulent 3") {
-                        gear_element_atk_bonus = 63.0;
-                        gear_element_def_bonus = 42.0;
-                        gear_element_hp_bonus = 13.0;
-                    } else {
-                        gear_element_atk_bonus = 48.0;
-                        gear_element_def_bonus = 32.0;
-                        gear_element_hp_bonus = 10.0;
-                    }
-                }
-                4u8 => {
-                    gear_element_atk_bonus = 89.0;
-                    gear_element_def_bonus = 59.0;
-                    gear_element_hp_bonus = 18.0;
-                }
-                _ => panic!("Unknown gear_element_tier {}", gear_element_tier),
-            }
+    /// Calculate the stats the item in a slot (0-5) gives before skill bonuses, from its quality and socketed element and spirit
+    fn calculate_slot_stats(
+        &self,
+        slot: usize,
+        blueprint: &Blueprint,
+        spirit_map: &HashMap<String, Spirit>,
+        elements: &[Element],
+    ) -> Result<SlotStats, HeroBuilderError> {
+        let mut spirit_bonuses = SpiritBonuses::default();
+
+        let gear_quality_bonus = get_quality_bonus(&self.equipment_quality[slot])?;
+
+        // Empty sockets, such as those of items that can't hold an enchantment, give no bonus
+        let mut gear_element_atk_bonus = 0.0f64;
+        let mut gear_element_def_bonus = 0.0f64;
+        let mut gear_element_hp_bonus = 0.0f64;
+        if let Some(gear_element) = parse_element_socket(&self.elements_socketed[slot])? {
+            let element = find_element(elements, &gear_element.element_type, gear_element.grade)
+                .ok_or_else(|| {
+                    HeroBuilderError::UnknownElement(self.elements_socketed[slot].to_string())
+                })?;
+            gear_element_atk_bonus = element.atk_bonus;
+            gear_element_def_bonus = element.def_bonus;
+            gear_element_hp_bonus = element.hp_bonus;
             let element_affinity = blueprint.get_elemental_affinity();
-            if element_affinity.as_str() == gear_element_split[0] {
-                gear_element_atk_bonus *= 1.5;
-                gear_element_def_bonus *= 1.5;
-                gear_element_hp_bonus *= 1.5;
+            if element_affinity == gear_element.element_type {
+                gear_element_atk_bonus *= element.affinity_multiplier;
+                gear_element_def_bonus *= element.affinity_multiplier;
+                gear_element_hp_bonus *= element.affinity_multiplier;
             }
+        }
 
-            let gear_spirit = &self.spirits_socketed[gear_index];
-            let gear_spirit_split = gear_spirit.split_whitespace().collect::<Vec<&str>>();
-            let gear_spirit_name = gear_spirit_split[0];
-            let gear_spirit_tier = gear_spirit_split[1];
-
+        let mut gear_spirit_atk_bonus = 0.0f64;
+        let mut gear_spirit_def_bonus = 0.0f64;
+        let mut gear_spirit_hp_bonus = 0.0f64;
+        let gear_spirit = &self.spirits_socketed[slot];
+        if let Some((gear_spirit_name, _)) = check_spirit_socket(gear_spirit)? {
             let spirit_affinity = blueprint.get_spirit_affinity();
-
-            let mut gear_spirit_atk_bonus: f64;
-            let mut gear_spirit_def_bonus: f64;
-            let mut gear_spirit_hp_bonus: f64;
-
-            match gear_spirit_tier {
-                "T4" => {
-                    // Low-Tier Spirits
-                    gear_spirit_atk_bonus = 16.0;
-                    gear_spirit_def_bonus = 11.0;
-                    gear_spirit_hp_bonus = 3.0;
-                }
-                "T5" => {
-                    // Xolotl Spirit
-                    gear_spirit_atk_bonus = 26.0;
-                    gear_spirit_def_bonus = 18.0;
-                    gear_spirit_hp_bonus = 5.0;
-                }
-                "T7" => {
-                    // Mid-Tier Spirits
-                    gear_spirit_atk_bonus = 41.0;
-                    gear_spirit_def_bonus = 27.0;
-                    gear_spirit_hp_bonus = 8.0;
-                }
-                "T9" => {
-                    // High-Tier Spirits
-                    gear_spirit_atk_bonus = 48.0;
-                    gear_spirit_def_bonus = 32.0;
-                    gear_spirit_hp_bonus = 10.0;
-                }
-                "TM" => {
-                    // Mundra Spirit
-                    gear_spirit_atk_bonus = 50.0;
-                    gear_spirit_def_bonus = 33.0;
-                    gear_spirit_hp_bonus = 10.0;
-                }
-                "T11" => {
-                    // Quetzalcoatl Spirit
-                    gear_spirit_atk_bonus = 63.0;
-                    gear_spirit_def_bonus = 42.0;
-                    gear_spirit_hp_bonus = 13.0; // only gives 10 on banana gun T6? only 6 on T5 imperial scutum? 10 on T5 silver thistle?? must be the min stuff from ress' sheet
-                }
-                "T12" => {
-                    // Max-Tier Spirits
-                    gear_spirit_atk_bonus = 89.0;
-                    gear_spirit_def_bonus = 59.0;
-                    gear_spirit_hp_bonus = 18.0;
-                }
-                _ => panic!("Unknown gear_spirit_tier {}", gear_spirit_tier),
-            }
-
-            let spirit_affinity_split: &str;
-            if spirit_affinity.as_str() != "---" {
-                spirit_affinity_split = spirit_affinity
+            (
+                gear_spirit_atk_bonus,
+                gear_spirit_def_bonus,
+                gear_spirit_hp_bonus,
+            ) = get_spirit_tier_bonuses(gear_spirit)?;
+
+            let spirit_affinity_split: &str = if spirit_affinity.as_str() != "---" {
+                spirit_affinity
                     .as_str()
                     .split_whitespace()
-                    .collect::<Vec<&str>>()[0];
+                    .collect::<Vec<&str>>()[0]
             } else {
-                spirit_affinity_split = "NO_SPIRIT_AFFINITY";
+                "NO_SPIRIT_AFFINITY"
+            };
+
+            // Spirits missing from spirits.yaml, such as those whose effects are simulated in combat, give no bonus to the whole hero
+            if let Some(spirit) = spirit_map.get(gear_spirit_name) {
+                spirit_bonuses.add(spirit.get_bonuses(spirit_affinity_split == gear_spirit_name));
             }
 
-            match gear_spirit_name {
-                "Armadillo" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_survive_fatal_blow_chance_percent += 0.25;
-                    } else {
-                        spirit_bonus_survive_fatal_blow_chance_percent += 0.15;
-                    }
-                }
-                "Rhino" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_threat_rating_value += 10;
-                    } else {
-                        spirit_bonus_threat_rating_value += 5;
-                    }
-                }
-                "Lizard" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_hp_regen_value += 5.0;
-                    } else {
-                        spirit_bonus_hp_regen_value += 3.0;
-                    }
-                }
-                "Wolf" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_atk_percent += 0.1;
-                    } else {
-                        spirit_bonus_atk_percent += 0.05;
-                    }
-                }
-                "Ram" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_def_percent += 0.1;
-                    } else {
-                        spirit_bonus_def_percent += 0.05;
-                    }
-                }
-                "Eagle" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_crit_chance_percent += 0.03;
-                    } else {
-                        spirit_bonus_crit_chance_percent += 0.02;
-                    }
-                }
-                "Ox" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_hp_percent += 0.05;
-                    } else {
-                        spirit_bonus_hp_percent += 0.03;
-                    }
-                }
-                "Viper" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_crit_dmg_percent += 0.2;
-                    } else {
-                        spirit_bonus_crit_dmg_percent += 0.15;
-                    }
-                }
-                "Cat" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_eva_percent += 0.03;
-                    } else {
-                        spirit_bonus_eva_percent += 0.02;
-                    }
-                }
-                "Bear" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_atk_percent += 0.07;
-                        spirit_bonus_hp_value += 20.0;
-                    } else {
-                        spirit_bonus_atk_percent += 0.05;
-                        spirit_bonus_hp_value += 15.0;
-                    }
-                }
-                "Walrus" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_hp_percent += 0.08;
-                    } else {
-                        spirit_bonus_hp_percent += 0.05;
-                    }
-                }
-                "Mammoth" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_def_percent += 0.13;
-                        spirit_bonus_threat_rating_value += 15;
-                    } else {
-                        spirit_bonus_def_percent += 0.1;
-                        spirit_bonus_threat_rating_value += 10;
-                    }
-                }
-                "Lion" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_atk_percent += 0.07;
-                        spirit_bonus_eva_percent += 0.02;
-                    } else {
-                        spirit_bonus_atk_percent += 0.05;
-                        spirit_bonus_eva_percent += 0.01;
-                    }
-                }
-                "Tiger" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_def_percent += 0.07;
-                        spirit_bonus_eva_percent += 0.02;
-                    } else {
-                        spirit_bonus_def_percent += 0.05;
-                        spirit_bonus_eva_percent += 0.01;
-                    }
-                }
-                "Phoenix" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_hp_percent += 0.05;
-                        spirit_bonus_hp_regen_value += 5.0;
-                    } else {
-                        spirit_bonus_hp_percent += 0.04;
-                        spirit_bonus_hp_regen_value += 3.0;
-                    }
-                }
-                "Hydra" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_def_value += 125.0;
-                        spirit_bonus_hp_value += 35.0;
-                    } else {
-                        spirit_bonus_def_value += 100.0;
-                        spirit_bonus_hp_value += 25.0;
-                    }
-                }
-                "Tarrasque" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_def_percent += 0.25;
-                    } else {
-                        spirit_bonus_def_percent += 0.2;
-                    }
-                }
-                "Carbuncle" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_crit_chance_percent += 0.03;
-                        spirit_bonus_eva_percent += 0.03;
-                    } else {
-                        spirit_bonus_crit_chance_percent += 0.02;
-                        spirit_bonus_eva_percent += 0.02;
-                    }
-                }
-                "Chimera" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_atk_percent += 0.15;
-                        spirit_bonus_crit_dmg_percent += 0.15;
-                    } else {
-                        spirit_bonus_atk_percent += 0.1;
-                        spirit_bonus_crit_dmg_percent += 0.1;
+            if spirit_affinity_split == gear_spirit_name {
+                gear_spirit_atk_bonus *= 1.5;
+                gear_spirit_def_bonus *= 1.5;
+                gear_spirit_hp_bonus *= 1.5;
+            }
+        }
+
+        return Ok(SlotStats {
+            item_type: blueprint.get_type(),
+            atk_sources: [
+                blueprint.get_atk() * gear_quality_bonus,
+                f64::min(gear_element_atk_bonus, blueprint.get_atk()),
+                f64::min(gear_spirit_atk_bonus, blueprint.get_atk()),
+            ],
+            def_sources: [
+                blueprint.get_def() * gear_quality_bonus,
+                f64::min(gear_element_def_bonus, blueprint.get_def()),
+                f64::min(gear_spirit_def_bonus, blueprint.get_def()),
+            ],
+            hp_sources: [
+                blueprint.get_hp() * gear_quality_bonus,
+                f64::min(gear_element_hp_bonus, blueprint.get_hp()),
+                f64::min(gear_spirit_hp_bonus, blueprint.get_hp()),
+            ],
+            eva: blueprint.get_eva(),
+            crit_chance: blueprint.get_crit(),
+            // Items from chests have innate elements, and official data sheet doesn't have innate element as a field, so this is the best we've got
+            from_chest: blueprint.get_unlock_prerequisite().contains("Chest"),
+            spirit_bonuses,
+        });
+    }
+
+    /// Combine the stats of each slot with the bonuses of the hero's skills into its final stats
+    fn apply_stat_improvements(
+        &mut self,
+        slots: &[SlotStats],
+        innate_skill: &InnateSkill,
+        skills: &[Option<HeroSkill>; 4],
+        pet_skill: Option<&PetSkill>,
+        seed_values: &SeedValues,
+    ) -> StatBreakdown {
+        let mut breakdown = StatBreakdown::default();
+        let skills: Vec<&HeroSkill> = skills.iter().flatten().collect();
+        self.pet = pet_skill.and_then(|pet_skill| pet_skill.create_pet());
+        self.skill_effects = skills
+            .iter()
+            .flat_map(|skill| skill.get_effects())
+            .collect();
+
+        let mut equip_atk_value = 0.0f64;
+        let mut equip_hp_value = 0.0f64;
+        let mut equip_def_value = 0.0f64;
+        let mut equip_eva_percent = 0.0f64;
+        let mut equip_crit_chance_percent = 0.0f64;
+        let mut spirit_bonuses = SpiritBonuses::default();
+        let innate_skill_label = f!("{} (innate)", innate_skill._get_name());
+
+        // Calculate gear bonuses
+        for slot in slots.iter() {
+            let mut bonus_item_all_stats_percent = 0.0f64;
+            let mut bonus_item_atk_percent = 0.0f64;
+            let mut bonus_item_def_percent = 0.0f64;
+            // The (skill, ATK %, DEF %, all stats %) each skill gives the item, to credit each its share
+            let mut item_percents: Vec<(String, f64, f64, f64)> = vec![];
+
+            // Check for bonus stats from innate skill
+            bonus_item_all_stats_percent +=
+                innate_skill.get_bonus_stats_from_all_equipment_percent();
+            item_percents.push((
+                innate_skill_label.to_string(),
+                0.0,
+                0.0,
+                innate_skill.get_bonus_stats_from_all_equipment_percent(),
+            ));
+
+            if !innate_skill.get_item_types().is_empty() {
+                // Has bonuses associated with atleast one item type
+                for itype in innate_skill.get_item_types() {
+                    if slot.item_type == itype {
+                        // Have that type equipped, apply bonus(es)
+                        bonus_item_atk_percent += innate_skill.get_attack_with_item_percent();
+                        bonus_item_def_percent += innate_skill.get_defense_with_item_percent();
+                        bonus_item_all_stats_percent +=
+                            innate_skill.get_all_stats_with_item_percent();
+                        item_percents[0].1 += innate_skill.get_attack_with_item_percent();
+                        item_percents[0].2 += innate_skill.get_defense_with_item_percent();
+                        item_percents[0].3 += innate_skill.get_all_stats_with_item_percent();
                     }
                 }
-                "Kraken" => {
-                    if spirit_affinity_split == gear_spirit_name {
-                        spirit_bonus_atk_value += 125.0;
-                        spirit_bonus_atk_percent += 0.15;
-                    } else {
-                        spirit_bonus_atk_value += 100.0;
-                        spirit_bonus_atk_percent += 0.1;
+            }
+
+            // Check for skills that give bonus stats to gear
+            for skill in skills.iter() {
+                // Get all stats bonus if applicable
+                bonus_item_all_stats_percent += skill.get_bonus_stats_from_all_equipment_percent();
+                let mut skill_item_percents = (
+                    skill.get_name(),
+                    0.0,
+                    0.0,
+                    skill.get_bonus_stats_from_all_equipment_percent(),
+                );
+
+                if !skill.get_item_types().is_empty() {
+                    // Has bonuses associated with atleast one item type
+                    for itype in skill.get_item_types() {
+                        if slot.item_type == itype {
+                            // Have that type equipped, apply bonus(es)
+                            bonus_item_atk_percent += skill.get_attack_with_item_percent();
+                            bonus_item_def_percent += skill.get_defense_with_item_percent();
+                            skill_item_percents.1 += skill.get_attack_with_item_percent();
+                            skill_item_percents.2 += skill.get_defense_with_item_percent();
+                        }
                     }
                 }
-                _ => (),
+                item_percents.push(skill_item_percents);
             }
 
-            if spirit_affinity_split == gear_spirit_name {
-                gear_spirit_atk_bonus *= 1.5;
-                gear_spirit_def_bonus *= 1.5;
-                gear_spirit_hp_bonus *= 1.5;
-            }
+            spirit_bonuses.add(&slot.spirit_bonuses);
 
-            let spellknight_bonus: f64;
-            // Items from chests have innate elements, and official data sheet doesn't have innate element as a field, so this is the best we've got
-            if blueprint.get_unlock_prerequisite().contains("Chest") {
-                spellknight_bonus =
-                    1.0 + innate_skill.get_all_stats_for_equipment_with_innate_element_percent();
+            let spellknight_bonus: f64 = if slot.from_chest {
+                1.0 + innate_skill.get_all_stats_for_equipment_with_innate_element_percent()
             } else {
-                spellknight_bonus = 1.0;
-            }
+                1.0
+            };
 
             // Calculate and apply gear bonus to running totals
-            let item_attack_final = ((blueprint.get_atk() * gear_quality_bonus)
-                + f64::min(gear_element_atk_bonus, blueprint.get_atk())
-                + f64::min(gear_spirit_atk_bonus, blueprint.get_atk()))
-                * (1.0 + bonus_item_atk_percent + bonus_item_all_stats_percent)
-                * spellknight_bonus;
-            let item_defense_final = ((blueprint.get_def() * gear_quality_bonus)
-                + f64::min(gear_element_def_bonus, blueprint.get_def())
-                + f64::min(gear_spirit_def_bonus, blueprint.get_def()))
-                * (1.0 + bonus_item_def_percent + bonus_item_all_stats_percent)
-                * spellknight_bonus;
-            let item_hp_final = ((blueprint.get_hp() * gear_quality_bonus)
-                + f64::min(gear_element_hp_bonus, blueprint.get_hp())
-                + f64::min(gear_spirit_hp_bonus, blueprint.get_hp()))
+            let item_attack_final =
+                (slot.atk_sources[0] + slot.atk_sources[1] + slot.atk_sources[2])
+                    * (1.0 + bonus_item_atk_percent + bonus_item_all_stats_percent)
+                    * spellknight_bonus;
+            let item_defense_final =
+                (slot.def_sources[0] + slot.def_sources[1] + slot.def_sources[2])
+                    * (1.0 + bonus_item_def_percent + bonus_item_all_stats_percent)
+                    * spellknight_bonus;
+            let item_hp_final = (slot.hp_sources[0] + slot.hp_sources[1] + slot.hp_sources[2])
                 * (1.0 + bonus_item_all_stats_percent)
                 * spellknight_bonus;
-            // bonus_atk_value += blueprint.get_atk() * gear_quality_bonus * (1.0 + bonus_item_atk_percent + bonus_item_all_stats_percent);
-            // bonus_def_value += blueprint.get_def() * gear_quality_bonus * (1.0 + bonus_item_def_percent + bonus_item_all_stats_percent);
-            // bonus_hp_value += blueprint.get_hp() * gear_quality_bonus * (1.0 + bonus_item_all_stats_percent);
             equip_atk_value += item_attack_final;
             equip_def_value += item_defense_final;
             equip_hp_value += item_hp_final;
-            equip_eva_percent += blueprint.get_eva() * (1.0 + bonus_item_all_stats_percent);
-            equip_crit_chance_percent +=
-                blueprint.get_crit() * (1.0 + bonus_item_all_stats_percent);
+            equip_eva_percent += slot.eva * (1.0 + bonus_item_all_stats_percent);
+            equip_crit_chance_percent += slot.crit_chance * (1.0 + bonus_item_all_stats_percent);
+
+            // Track where each item's stats come from, crediting item bonuses from skills to skills
+            breakdown.add_item_sources(StatType::Atk, slot.atk_sources, item_attack_final);
+            breakdown.add_item_sources(StatType::Def, slot.def_sources, item_defense_final);
+            breakdown.add_item_sources(StatType::Hp, slot.hp_sources, item_hp_final);
+            breakdown.add(StatType::Eva, StatSource::Gear, slot.eva);
+            breakdown.add(
+                StatType::Eva,
+                StatSource::Skills,
+                slot.eva * bonus_item_all_stats_percent,
+            );
+            breakdown.add(StatType::CritChance, StatSource::Gear, slot.crit_chance);
+            breakdown.add(
+                StatType::CritChance,
+                StatSource::Skills,
+                slot.crit_chance * bonus_item_all_stats_percent,
+            );
+            breakdown.add_item_skill_shares(slot, &item_percents, spellknight_bonus);
+            breakdown
+                .items
+                .push((item_attack_final, item_defense_final, item_hp_final));
+            breakdown
+                .item_sources
+                .push([slot.atk_sources, slot.def_sources, slot.hp_sources]);
         }
 
         // Calculate hero-wide skill bonuses
@@ -859,8 +2370,8 @@ impl Hero {
         let mut skill_bonus_crit_chance_percent: f64 = 0.0;
         let mut skill_bonus_crit_damage_percent: f64 = 0.0;
         let mut skill_bonus_threat_rating_value: u16 = 0;
-        let mut _skill_bonus_rest_time_percent: f64 = 0.0;
-        let mut _skill_bonus_xp_percent_percent: f64 = 0.0;
+        let mut skill_bonus_rest_time_percent: f64 = 0.0;
+        let mut skill_bonus_xp_percent: f64 = 0.0;
         let mut skill_bonus_survive_fatal_blow_chance_percent: f64 = 0.0;
 
         // Get bonuses from innate skill
@@ -873,27 +2384,27 @@ impl Hero {
         skill_bonus_crit_chance_percent += innate_skill.get_crit_chance_percent();
         skill_bonus_crit_damage_percent += innate_skill.get_crit_damage_percent();
         skill_bonus_threat_rating_value += innate_skill.get_threat_rating_value();
-        _skill_bonus_rest_time_percent += innate_skill.get_rest_time_percent();
+        skill_bonus_rest_time_percent += innate_skill.get_rest_time_percent();
+        let mut skill_bonuses: Vec<SkillBonus> = vec![create_skill_bonus(
+            innate_skill_label,
+            [
+                innate_skill.get_hp_value(),
+                0.0,
+                0.0,
+                innate_skill.get_evasion_percent(),
+                innate_skill.get_crit_chance_percent(),
+                innate_skill.get_crit_damage_percent(),
+                f64::from(innate_skill.get_threat_rating_value()),
+            ],
+            [
+                innate_skill.get_hp_percent(),
+                innate_skill.get_attack_percent(),
+                innate_skill.get_defense_percent(),
+            ],
+        )];
 
         // Get bonuses from hero skills
-        for skill_name in &self.skills {
-            if skill_name == "" {
-                continue;
-            }
-            if !hero_skill_map.contains_key(skill_name) {
-                panic!(
-                    "Skill {} could not be found in keys for hero_skill_map",
-                    skill_name
-                );
-            }
-
-            // Calculate skill tier and get the correct skill
-            let (_, skill) = self.calculate_hero_skill_tier(
-                hero_skill_tier_1_name_map,
-                hero_skill_map,
-                skill_name.to_string(),
-            );
-
+        for skill in skills.iter() {
             skill_bonus_atk_percent += skill.get_attack_percent();
             skill_bonus_atk_value += skill.get_attack_value();
             skill_bonus_hp_percent += skill.get_hp_percent();
@@ -902,15 +2413,82 @@ impl Hero {
             skill_bonus_eva_percent += skill.get_evasion_percent();
             skill_bonus_crit_chance_percent += skill.get_crit_chance_percent();
             skill_bonus_crit_damage_percent += skill.get_crit_damage_percent();
-            _skill_bonus_rest_time_percent += skill.get_rest_time_percent();
-            _skill_bonus_xp_percent_percent += skill.get_xp_percent();
+            skill_bonus_rest_time_percent += skill.get_rest_time_percent();
+            skill_bonus_xp_percent += skill.get_xp_percent();
             skill_bonus_survive_fatal_blow_chance_percent +=
                 skill.get_survive_fatal_blow_chance_percent();
+            skill_bonuses.push(create_skill_bonus(
+                skill.get_name(),
+                [
+                    skill.get_hp_value(),
+                    skill.get_attack_value(),
+                    0.0,
+                    skill.get_evasion_percent(),
+                    skill.get_crit_chance_percent(),
+                    skill.get_crit_damage_percent(),
+                    0.0,
+                ],
+                [
+                    skill.get_hp_percent(),
+                    skill.get_attack_percent(),
+                    skill.get_defense_percent(),
+                ],
+            ));
+        }
+
+        // Get bonuses from the pet's skill, if it has one
+        if let Some(pet_skill) = pet_skill {
+            skill_bonus_atk_percent += pet_skill.get_attack_percent();
+            skill_bonus_atk_value += pet_skill.get_attack_value();
+            skill_bonus_hp_percent += pet_skill.get_hp_percent();
+            skill_bonus_hp_value += pet_skill.get_hp_value();
+            skill_bonus_hp_regen_value += pet_skill.get_hp_regen_value();
+            skill_bonus_def_percent += pet_skill.get_defense_percent();
+            skill_bonus_eva_percent += pet_skill.get_evasion_percent();
+            skill_bonus_crit_chance_percent += pet_skill.get_crit_chance_percent();
+            skill_bonus_crit_damage_percent += pet_skill.get_crit_damage_percent();
+            skill_bonus_threat_rating_value += pet_skill.get_threat_rating_value();
+            skill_bonus_survive_fatal_blow_chance_percent +=
+                pet_skill.get_survive_fatal_blow_chance_percent();
+            skill_bonuses.push(create_skill_bonus(
+                f!("{} (pet)", pet_skill._get_name()),
+                [
+                    pet_skill.get_hp_value(),
+                    pet_skill.get_attack_value(),
+                    0.0,
+                    pet_skill.get_evasion_percent(),
+                    pet_skill.get_crit_chance_percent(),
+                    pet_skill.get_crit_damage_percent(),
+                    f64::from(pet_skill.get_threat_rating_value()),
+                ],
+                [
+                    pet_skill.get_hp_percent(),
+                    pet_skill.get_attack_percent(),
+                    pet_skill.get_defense_percent(),
+                ],
+            ));
         }
 
+        breakdown.add(
+            StatType::ThreatRating,
+            StatSource::Base,
+            f64::from(self.threat_rating),
+        );
+        breakdown.add(
+            StatType::ThreatRating,
+            StatSource::Skills,
+            f64::from(skill_bonus_threat_rating_value),
+        );
+        breakdown.add(
+            StatType::ThreatRating,
+            StatSource::Spirits,
+            f64::from(spirit_bonuses.threat_rating_value),
+        );
+
         // Adjust threat_rating
-        let final_threat_rating =
-            self.threat_rating + skill_bonus_threat_rating_value + spirit_bonus_threat_rating_value;
+        let final_threat_rating = self.threat_rating
+            + skill_bonus_threat_rating_value
+            + spirit_bonuses.threat_rating_value;
         self.threat_rating = final_threat_rating;
 
         let mut geo_astramancer_element_qty_or_chieftain_threat_bonus: f64 = 0.0;
@@ -927,97 +2505,463 @@ impl Hero {
             }
             _ => (),
         }
+        // Class bonuses are credited to the class's innate skill
+        skill_bonuses[0].percent[StatType::Atk as usize] +=
+            geo_astramancer_element_qty_or_chieftain_threat_bonus;
 
         // println!("--{}--", self.identifier);
+
         // ATK calc
         let base_atk = self.atk;
-        let seeded_atk = base_atk + f64::from(self.atk_seeds * 4);
-        let summarized_base_atk_value = seeded_atk + spirit_bonus_atk_value + skill_bonus_atk_value;
+        let seeded_atk = base_atk + f64::from(self.atk_seeds) * seed_values.atk;
+        let summarized_base_atk_value =
+            seeded_atk + spirit_bonuses.atk_value + skill_bonus_atk_value;
         let summarized_atk_percent_modifier = 1.0
             + skill_bonus_atk_percent
             + geo_astramancer_element_qty_or_chieftain_threat_bonus
-            + spirit_bonus_atk_percent;
+            + spirit_bonuses.atk_percent;
         let modified_atk_value = summarized_base_atk_value * summarized_atk_percent_modifier;
         let modified_atk_gear_value = equip_atk_value * summarized_atk_percent_modifier;
         let final_atk = modified_atk_value + modified_atk_gear_value;
         self.atk = final_atk;
+        breakdown.add(StatType::Atk, StatSource::Base, base_atk);
+        breakdown.add(StatType::Atk, StatSource::Seeds, seeded_atk - base_atk);
+        breakdown.add(StatType::Atk, StatSource::Spirits, spirit_bonuses.atk_value);
+        breakdown.add(StatType::Atk, StatSource::Skills, skill_bonus_atk_value);
+        breakdown.add_skill_shares(StatType::Atk, &skill_bonuses);
+        breakdown.apply_percent_bonuses(
+            StatType::Atk,
+            skill_bonus_atk_percent + geo_astramancer_element_qty_or_chieftain_threat_bonus,
+            spirit_bonuses.atk_percent,
+        );
         // println!("final_atk: {}", final_atk);
         // ((seeded_atk + gear_spirit_bonus_atk_value + sum(skill_bonus_atk_value)) * (1 + ((skill_atk_percent + geo_astramancer_element_qty_or_chieftain_threat_bonus) + bonus_spirit_atk_percent)/100)) + (bonus_atk_value * (1 + ((skill_atk_percent + geo_astramancer_element_qty_or_chieftain_threat_bonus) + bonus_spirit_atk_percent)/100)))
 
         // ATK mod calc
         let final_atk_mod = skill_bonus_atk_percent
             + geo_astramancer_element_qty_or_chieftain_threat_bonus
-            + spirit_bonus_atk_percent;
+            + spirit_bonuses.atk_percent;
         self.atk_modifier = final_atk_mod;
 
         // DEF
         let base_def = self.def;
-        let seeded_def = base_def + f64::from(self.def_seeds * 4);
-        let final_def = (seeded_def + equip_def_value + spirit_bonus_def_value)
-            * (1.0 + skill_bonus_def_percent + spirit_bonus_def_percent);
+        let seeded_def = base_def + f64::from(self.def_seeds) * seed_values.def;
+        let final_def = (seeded_def + equip_def_value + spirit_bonuses.def_value)
+            * (1.0 + skill_bonus_def_percent + spirit_bonuses.def_percent);
         self.def = final_def;
+        breakdown.add(StatType::Def, StatSource::Base, base_def);
+        breakdown.add(StatType::Def, StatSource::Seeds, seeded_def - base_def);
+        breakdown.add(StatType::Def, StatSource::Spirits, spirit_bonuses.def_value);
+        breakdown.add_skill_shares(StatType::Def, &skill_bonuses);
+        breakdown.apply_percent_bonuses(
+            StatType::Def,
+            skill_bonus_def_percent,
+            spirit_bonuses.def_percent,
+        );
         // println!("final_def: {}", final_def);
 
         // DEF mod
-        let final_def_mod = skill_bonus_atk_percent + spirit_bonus_def_percent;
+        let final_def_mod = skill_bonus_atk_percent + spirit_bonuses.def_percent;
         self.def_modifier = final_def_mod;
 
         // HP
         let base_hp = self.hp;
-        let seeded_hp = base_hp + f64::from(self.hp_seeds);
-        let final_hp = (seeded_hp + equip_hp_value + skill_bonus_hp_value + spirit_bonus_hp_value)
-            * (1.0 + skill_bonus_hp_percent + spirit_bonus_hp_percent);
+        let seeded_hp = base_hp + f64::from(self.hp_seeds) * seed_values.hp;
+        let final_hp =
+            (seeded_hp + equip_hp_value + skill_bonus_hp_value + spirit_bonuses.hp_value)
+                * (1.0 + skill_bonus_hp_percent + spirit_bonuses.hp_percent);
         self.hp = final_hp;
+        breakdown.add(StatType::Hp, StatSource::Base, base_hp);
+        breakdown.add(StatType::Hp, StatSource::Seeds, seeded_hp - base_hp);
+        breakdown.add(StatType::Hp, StatSource::Skills, skill_bonus_hp_value);
+        breakdown.add(StatType::Hp, StatSource::Spirits, spirit_bonuses.hp_value);
+        breakdown.add_skill_shares(StatType::Hp, &skill_bonuses);
+        breakdown.apply_percent_bonuses(
+            StatType::Hp,
+            skill_bonus_hp_percent,
+            spirit_bonuses.hp_percent,
+        );
         // println!("final_hp: {}", final_hp);
 
         // HP Regen
-        let final_hp_regen = skill_bonus_hp_regen_value + spirit_bonus_hp_regen_value;
+        let final_hp_regen = skill_bonus_hp_regen_value + spirit_bonuses.hp_regen_value;
         self.hp_regen = final_hp_regen;
 
         // Other Stats
+        breakdown.add(StatType::Eva, StatSource::Base, self.eva);
+        breakdown.add(StatType::Eva, StatSource::Skills, skill_bonus_eva_percent);
+        breakdown.add(
+            StatType::Eva,
+            StatSource::Spirits,
+            spirit_bonuses.eva_percent,
+        );
+        breakdown.add(StatType::CritChance, StatSource::Base, self.crit_chance);
+        breakdown.add(
+            StatType::CritChance,
+            StatSource::Skills,
+            skill_bonus_crit_chance_percent,
+        );
+        breakdown.add(
+            StatType::CritChance,
+            StatSource::Spirits,
+            spirit_bonuses.crit_chance_percent,
+        );
+        breakdown.add(StatType::CritMult, StatSource::Base, self.crit_mult);
+        breakdown.add(
+            StatType::CritMult,
+            StatSource::Skills,
+            skill_bonus_crit_damage_percent,
+        );
+        breakdown.add(
+            StatType::CritMult,
+            StatSource::Spirits,
+            spirit_bonuses.crit_dmg_percent,
+        );
+        for stat in [
+            StatType::Eva,
+            StatType::CritChance,
+            StatType::CritMult,
+            StatType::ThreatRating,
+        ] {
+            breakdown.add_skill_shares(stat, &skill_bonuses);
+        }
+
         // EVA
         let final_eva =
-            self.eva + equip_eva_percent + skill_bonus_eva_percent + spirit_bonus_eva_percent;
+            self.eva + equip_eva_percent + skill_bonus_eva_percent + spirit_bonuses.eva_percent;
         self.eva = final_eva;
 
         // Crit Chance
         let final_crit_chance = self.crit_chance
             + equip_crit_chance_percent
             + skill_bonus_crit_chance_percent
-            + spirit_bonus_crit_chance_percent;
+            + spirit_bonuses.crit_chance_percent;
         self.crit_chance = final_crit_chance;
 
         // Crit Damage
         let final_crit_damage =
-            self.crit_mult + skill_bonus_crit_damage_percent + spirit_bonus_crit_dmg_percent;
+            self.crit_mult + skill_bonus_crit_damage_percent + spirit_bonuses.crit_dmg_percent;
         self.crit_mult = final_crit_damage;
 
-        // Rest Time
-        // let final_rest_time = self.rest_time + skill_bonus_rest_time_percent + spirit_bonus_rest_time_percent;
-        // self.rest_time = final_rest_time;
-
-        // XP Percent
-        // let final_xp = self.xp + skill_bonus_xp_percent + spirit_bonus_xp_percent;
-        // self.xp = final_xp;
+        // Rest Time and XP, which only matter between quests
+        self.rest_time_percent = f64::min(skill_bonus_rest_time_percent, 1.0);
+        self.xp_percent = skill_bonus_xp_percent;
 
         // Survive Fatal Blow Chance
         let final_survive_fatal_blow_chance = self.survive_fatal_blow_chance
             + skill_bonus_survive_fatal_blow_chance_percent
-            + spirit_bonus_survive_fatal_blow_chance_percent;
+            + spirit_bonuses.survive_fatal_blow_chance_percent;
         self.survive_fatal_blow_chance = final_survive_fatal_blow_chance;
 
         // println!("\n");
+        return breakdown;
+    }
+
+    pub fn fmt_stats(&self) -> StatsView {
+        return create_stats_view(f!("{} ({} {})", self.identifier, self.class, self.level))
+            .text("Rank", self.rank)
+            .text("Innate Tier", self.innate_tier)
+            .float("HP", self.hp)
+            .float("HP Regen", self.hp_regen)
+            .float("ATK", self.atk)
+            .float("DEF", self.def)
+            .float("EVA", self.eva)
+            .float("Crit Chance", self.crit_chance)
+            .float("Crit Multiplier", self.crit_mult)
+            .text("Threat Rating", self.threat_rating)
+            .text("Element", f!("{} {}", self.element_type, self.element_qty))
+            .float("Survive Fatal Blow", self.survive_fatal_blow_chance)
+            .float("Rest Time Reduction", self.rest_time_percent)
+            .float("XP Bonus", self.xp_percent)
+            .float("ATK Modifier", self.atk_modifier)
+            .float("DEF Modifier", self.def_modifier);
+    }
+
+    /// Returns warnings about parts of the build that are likely mistakes or leave bonuses unused
+    pub fn get_build_warnings(&self, bp_map: &HashMap<String, Blueprint>) -> Vec<String> {
+        let mut warnings: Vec<String> = vec![];
+
+        let equipped_skills: Vec<&String> = self
+            .skills
+            .iter()
+            .filter(|skill| !is_empty_skill(skill))
+            .collect();
+        let unlocked_qty = self.count_skill_slots_unlocked();
+        if equipped_skills.len() < unlocked_qty {
+            warnings.push(f!(
+                "{} of {} unlocked skill slots are empty",
+                unlocked_qty - equipped_skills.len(),
+                unlocked_qty
+            ));
+        }
+
+        for (i, equipment) in self.equipment_equipped.iter().enumerate() {
+            let blueprint = match bp_map.get(equipment) {
+                Some(blueprint) => blueprint,
+                None => continue,
+            };
+            let element = self.elements_socketed[i]
+                .split_whitespace()
+                .next()
+                .unwrap_or_default();
+            if !is_empty_socket(element) && element != self.element_type {
+                warnings.push(f!(
+                    "Slot {} ({}) has {} socketed, which adds its stats but nothing to the hero's {} element qty",
+                    i + 1,
+                    equipment,
+                    self.elements_socketed[i],
+                    self.element_type
+                ));
+            }
+            let eligibility = blueprint.get_enchant_eligibility();
+            let elemental_affinity = blueprint.get_elemental_affinity();
+            if eligibility.element && elemental_affinity != "---" && elemental_affinity != element {
+                warnings.push(f!(
+                    "Slot {} ({}) has {} affinity but {} is socketed, missing the affinity bonus",
+                    i + 1,
+                    equipment,
+                    elemental_affinity,
+                    describe_socket(&self.elements_socketed[i])
+                ));
+            }
+            let spirit = self.spirits_socketed[i]
+                .split_whitespace()
+                .next()
+                .unwrap_or_default();
+            let spirit_affinity = blueprint.get_spirit_affinity();
+            let spirit_affinity_name = spirit_affinity
+                .split_whitespace()
+                .next()
+                .unwrap_or_default();
+            if eligibility.spirit && spirit_affinity != "---" && spirit_affinity_name != spirit {
+                warnings.push(f!(
+                    "Slot {} ({}) has {} affinity but {} is socketed, missing the affinity bonus",
+                    i + 1,
+                    equipment,
+                    spirit_affinity,
+                    describe_socket(&self.spirits_socketed[i])
+                ));
+            }
+        }
+
+        return warnings;
+    }
+
+    /// Render a card of the hero's final stats with their breakdown by source, equipped gear, and build warnings
+    /// The hero must have already had calculate_stat_improvements_from_gear_and_skills applied, which returns the breakdown
+    pub fn fmt_card(
+        &self,
+        breakdown: &StatBreakdown,
+        bp_map: &HashMap<String, Blueprint>,
+    ) -> String {
+        let mut card = String::new();
+        card += &f!(
+            "=== {} | {} Lv {} | Rank {} | Innate Tier {} ===\n",
+            self.identifier,
+            self.class,
+            self.level,
+            self.rank,
+            self.innate_tier
+        );
+        card += &f!(
+            "Element: {} {} | Skills: {}",
+            self.element_type,
+            self.element_qty,
+            self.skills
+                .iter()
+                .filter(|skill| !is_empty_skill(skill))
+                .join(", ")
+        );
+        if let Some((_, pet)) = self.get_pet(bp_map) {
+            card += &f!(" | Pet: {}", pet);
+        }
+        card += "\n\n";
+
+        // Stat breakdown table
+        card += &f!("{:<16}{:>10}", "Stat", "Final");
+        for source in StatSource::iter() {
+            card += &f!("{:>10}", source.to_string());
+        }
+        card += "\n";
+        for stat in StatType::iter() {
+            card += &f!(
+                "{:<16}{:>10}",
+                stat.to_string(),
+                fmt_f64(breakdown.get_total(stat))
+            );
+            for source in StatSource::iter() {
+                card += &f!("{:>10}", fmt_f64(breakdown.get(stat, source)));
+            }
+            card += "\n";
+        }
+        card += &f!(
+            "{:<16}{:>10}\n{:<16}{:>10}\n{:<16}{:>10}\n{:<16}{:>10}\n{:<16}{:>10}\n{:<16}{:>10}\n\n",
+            "HP Regen",
+            fmt_f64(self.hp_regen),
+            "Survive Fatal",
+            fmt_f64(self.survive_fatal_blow_chance),
+            "Rest Time Cut",
+            fmt_f64(self.rest_time_percent),
+            "XP Bonus",
+            fmt_f64(self.xp_percent),
+            "ATK Modifier",
+            fmt_f64(self.atk_modifier),
+            "DEF Modifier",
+            fmt_f64(self.def_modifier)
+        );
+
+        // Skill contribution table, splitting the Skills column between the skills
+        let skill_names = breakdown.get_skill_names();
+        if !skill_names.is_empty() {
+            let skill_width = skill_names
+                .iter()
+                .map(|skill| skill.chars().count())
+                .max()
+                .unwrap_or_default()
+                .max(5)
+                + 2;
+            card += &format!("{:<skill_width$}", "Skill", skill_width = skill_width);
+            for stat in StatType::iter() {
+                card += &format!(
+                    "{:>stat_width$}",
+                    stat.to_string(),
+                    stat_width = stat.to_string().len().max(8) + 2
+                );
+            }
+            card += "\n";
+            for skill in skill_names.iter() {
+                card += &format!("{:<skill_width$}", skill, skill_width = skill_width);
+                for stat in StatType::iter() {
+                    card += &format!(
+                        "{:>stat_width$}",
+                        fmt_f64(breakdown.get_skill_contribution(skill, stat)),
+                        stat_width = stat.to_string().len().max(8) + 2
+                    );
+                }
+                card += "\n";
+            }
+            card += "\n";
+        }
+
+        // Gear table
+        let name_width = self
+            .equipment_equipped
+            .iter()
+            .map(|equipment| equipment.chars().count())
+            .max()
+            .unwrap_or_default()
+            .max(4);
+        let item_types: Vec<String> = self
+            .equipment_equipped
+            .iter()
+            .map(|equipment| {
+                bp_map
+                    .get(equipment)
+                    .map(|blueprint| blueprint.get_type())
+                    .unwrap_or_default()
+            })
+            .collect();
+        let type_width = item_types
+            .iter()
+            .map(|item_type| item_type.chars().count())
+            .max()
+            .unwrap_or_default()
+            .max(4)
+            + 2;
+        card += &format!(
+            "{:<5}{:<name_width$}  {:<type_width$}{:<10}{:<12}{:<14}{:>10}{:>10}{:>10}\n",
+            "Slot",
+            "Item",
+            "Type",
+            "Quality",
+            "Element",
+            "Spirit",
+            "ATK",
+            "DEF",
+            "HP",
+            name_width = name_width,
+            type_width = type_width
+        );
+        for (i, equipment) in self.equipment_equipped.iter().enumerate() {
+            let (item_atk, item_def, item_hp) = breakdown.items.get(i).cloned().unwrap_or_default();
+            card += &format!(
+                "{:<5}{:<name_width$}  {:<type_width$}{:<10}{:<12}{:<14}{:>10}{:>10}{:>10}\n",
+                i + 1,
+                equipment,
+                item_types[i],
+                self.equipment_quality[i],
+                self.elements_socketed[i],
+                self.spirits_socketed[i],
+                fmt_f64(item_atk),
+                fmt_f64(item_def),
+                fmt_f64(item_hp),
+                name_width = name_width,
+                type_width = type_width
+            );
+        }
+
+        // Socket table, the flat stats each socketed element and spirit adds to its item before skill bonuses
+        let element_width = self
+            .elements_socketed
+            .iter()
+            .map(|element| element.chars().count())
+            .max()
+            .unwrap_or_default()
+            .max(7)
+            + 2;
+        let spirit_width = self
+            .spirits_socketed
+            .iter()
+            .map(|spirit| spirit.chars().count())
+            .max()
+            .unwrap_or_default()
+            .max(6)
+            + 2;
+        card += &format!(
+            "\n{:<5}{:<element_width$}{:>8}{:>8}{:>8}  {:<spirit_width$}{:>8}{:>8}{:>8}\n",
+            "Slot",
+            "Element",
+            "ATK",
+            "DEF",
+            "HP",
+            "Spirit",
+            "ATK",
+            "DEF",
+            "HP",
+            element_width = element_width,
+            spirit_width = spirit_width
+        );
+        for (i, item_sources) in breakdown.item_sources.iter().enumerate() {
+            card += &format!(
+                "{:<5}{:<element_width$}{:>8}{:>8}{:>8}  {:<spirit_width$}{:>8}{:>8}{:>8}\n",
+                i + 1,
+                self.elements_socketed[i],
+                fmt_f64(item_sources[0][1]),
+                fmt_f64(item_sources[1][1]),
+                fmt_f64(item_sources[2][1]),
+                self.spirits_socketed[i],
+                fmt_f64(item_sources[0][2]),
+                fmt_f64(item_sources[1][2]),
+                fmt_f64(item_sources[2][2]),
+                element_width = element_width,
+                spirit_width = spirit_width
+            );
+        }
+
+        let warnings = self.get_build_warnings(bp_map);
+        if !warnings.is_empty() {
+            card += "\nWarnings:\n";
+            for warning in warnings {
+                card += &f!("  - {}\n", warning);
+            }
+        }
+        return card;
     }
+}
 
-    pub fn _round_floats_for_display(&self) -> Hero {
-        let mut h2 = self.clone();
-        h2.hp = round_to_2(h2.hp);
-        h2.atk = round_to_2(h2.atk);
-        h2.def = round_to_2(h2.def);
-        h2.eva = round_to_2(h2.eva);
-        h2.crit_chance = round_to_2(h2.crit_chance);
-        h2.crit_mult = round_to_2(h2.crit_mult);
-        return h2;
+impl std::fmt::Display for Hero {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.fmt_stats())
     }
 }
 
@@ -1025,7 +2969,7 @@ impl From<Hero> for SimHero {
     /// Create a hero from the input object performing type validation and calculating certain fields
     fn from(item: Hero) -> Self {
         let i2 = item.clone();
-        return create_sim_hero(
+        let mut sim_hero = create_sim_hero(
             item.identifier,
             item.class,
             item.level,
@@ -1042,15 +2986,21 @@ impl From<Hero> for SimHero {
             item.survive_fatal_blow_chance,
             item.element_qty,
             item.element_type,
-            i2.calculate_spirit_qty(String::from("Armadillo T7")),
-            i2.calculate_spirit_qty(String::from("Lizard T7")),
-            i2.calculate_spirit_qty(String::from("Shark T9")),
-            i2.calculate_spirit_qty(String::from("Dinosaur T9")),
-            i2.calculate_spirit_qty(String::from("Mundra T10")),
+            i2.calculate_spirit_qty("Armadillo"),
+            i2.calculate_spirit_qty("Lizard"),
+            i2.calculate_spirit_qty("Shark"),
+            i2.calculate_spirit_qty("Dinosaur"),
+            i2.calculate_spirit_qty("Mundra"),
             item.atk_modifier,
             item.def_modifier,
+            item.archetype,
         )
         .unwrap();
+        sim_hero.set_build_signature(i2.get_build_signature());
+        sim_hero.set_pet(i2.pet);
+        sim_hero.set_skill_effects(i2.skill_effects);
+        sim_hero.set_quest_bonuses(i2.rest_time_percent, i2.xp_percent);
+        return sim_hero;
     }
 }
 
@@ -1073,3 +3023,759 @@ impl From<Hero> for HeroInput {
         );
     }
 }
+
+/// A builder hero with the stats of each gear slot and its skills at their tiers cached, so a single change to a gear slot,
+/// socket, or skill only recomputes what it affects before the final stats are combined, for optimizers that try many small changes
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedHero {
+    hero: Hero, // The hero before stat improvements from gear and skills
+    slots: Vec<SlotStats>,
+    innate_skill: InnateSkill,
+    skills: [Option<HeroSkill>; 4],
+    pet_skill: Option<PetSkill>,
+    seed_values: SeedValues,
+    champion_data: Option<ChampionData>, // The ability of a champion hero, None for other heroes
+}
+
+/// The hero's equipment must already be validated, as it is when loaded from a hero csv
+/// Like HeroBuilderInformation::build_sim_hero, fails if the hero's skills can't be equipped together
+pub fn create_cached_hero(
+    mut hero: Hero,
+    hbi: &HeroBuilderInformation,
+) -> Result<CachedHero, HeroBuilderError> {
+    hero.calculate_innate_tier(
+        &hbi.class_innate_skill_names_map,
+        &hbi.innate_skill_map,
+        &hbi.champion_data,
+    )?;
+    hero.resolve_skill_references(&hbi.hero_skill_tier_1_name_map, &hbi.hero_skill_map)?;
+    hero.validate_skills(&hbi.hero_skill_map)?;
+    hero.resolve_archetype(&hbi.hero_classes);
+    let slots = (0..6)
+        .map(|slot| {
+            hero.calculate_slot_stats(
+                slot,
+                &hbi.bp_map[&hero.equipment_equipped[slot]],
+                &hbi.spirit_map,
+                &hbi.elements,
+            )
+        })
+        .collect::<Result<Vec<SlotStats>, HeroBuilderError>>()?;
+    let innate_skill =
+        hero.find_innate_skill(&hbi.class_innate_skill_names_map, &hbi.innate_skill_map)?;
+    let skills = hero.resolve_hero_skills(&hbi.hero_skill_tier_1_name_map, &hbi.hero_skill_map)?;
+    let champion_data = hbi.find_champion_data(&hero.class);
+    let mut cached_hero = CachedHero {
+        hero,
+        slots,
+        innate_skill,
+        skills,
+        pet_skill: None,
+        seed_values: hbi.seed_values,
+        champion_data,
+    };
+    cached_hero.update_pet_skill(hbi);
+    return Ok(cached_hero);
+}
+
+impl CachedHero {
+    pub fn get_hero(&self) -> &Hero {
+        return &self.hero;
+    }
+
+    /// Combine the cached stats into the hero's final stats, giving the same SimHero as building the changed hero from scratch
+    pub fn build_sim_hero(&self) -> SimHero {
+        let mut hero = self.hero.clone();
+        hero.apply_stat_improvements(
+            &self.slots,
+            &self.innate_skill,
+            &self.skills,
+            self.pet_skill.as_ref(),
+            &self.seed_values,
+        );
+        let mut sim_hero = SimHero::from(hero);
+        sim_hero.set_champion_data(self.champion_data.clone());
+        return sim_hero;
+    }
+
+    /// Replace the item in a slot (0-5), keeping its quality and any socketed element and spirit the new item can hold
+    pub fn set_equipment(
+        &mut self,
+        slot: usize,
+        equipment: String,
+        hbi: &HeroBuilderInformation,
+    ) -> Result<(), &'static str> {
+        check_slot(slot)?;
+        let blueprint = hbi
+            .bp_map
+            .get(&equipment)
+            .ok_or("equipment not found in blueprints")?;
+        let class = hbi
+            .hero_classes
+            .get(&self.hero.class)
+            .ok_or("hero's class not found in hero classes")?;
+        if !class
+            .get_equipment_allowed(slot)
+            .contains(&blueprint.get_type())
+        {
+            return Err("equipment type is not allowed for the hero's class in that slot");
+        }
+        let mut equipment_equipped = self.hero.equipment_equipped.clone();
+        equipment_equipped[slot] = equipment.to_string();
+        if class
+            .check_slot_rules(&equipment_equipped, &hbi.bp_map)
+            .is_err()
+        {
+            return Err("equipment breaks one of the slot rules of the hero's class");
+        }
+        return self.edit_slot(slot, hbi, |hero| {
+            hero.set_equipment(slot, equipment);
+            hero.clear_ineligible_enchantments(slot, blueprint);
+        });
+    }
+
+    pub fn set_quality(
+        &mut self,
+        slot: usize,
+        quality: String,
+        hbi: &HeroBuilderInformation,
+    ) -> Result<(), &'static str> {
+        check_slot(slot)?;
+        if ItemQuality::from_str(&quality).is_err() {
+            return Err("unknown item quality");
+        }
+        return self.edit_slot(slot, hbi, |hero| hero.equipment_quality[slot] = quality);
+    }
+
+    /// Socket an element, such as "Fire 3", in a slot, or empty the socket with an empty string
+    pub fn set_element(
+        &mut self,
+        slot: usize,
+        element: String,
+        hbi: &HeroBuilderInformation,
+    ) -> Result<(), &'static str> {
+        check_slot(slot)?;
+        let element = clear_empty_socket(element);
+        if !element.is_empty() {
+            if !self
+                .get_blueprint(slot, hbi)
+                .get_enchant_eligibility()
+                .element
+            {
+                return Err("the item in that slot can't hold an element enchantment");
+            }
+            if parse_element_socket(&element).is_err() {
+                return Err("element must conform to format [type] [grade: 1-4]");
+            }
+        }
+        return self.edit_slot(slot, hbi, |hero| hero.elements_socketed[slot] = element);
+    }
+
+    /// Socket a spirit, such as "Wolf T7", in a slot, or empty the socket with an empty string
+    pub fn set_spirit(
+        &mut self,
+        slot: usize,
+        spirit: String,
+        hbi: &HeroBuilderInformation,
+    ) -> Result<(), &'static str> {
+        check_slot(slot)?;
+        let spirit = clear_empty_socket(spirit);
+        if !spirit.is_empty() {
+            if !self
+                .get_blueprint(slot, hbi)
+                .get_enchant_eligibility()
+                .spirit
+            {
+                return Err("the item in that slot can't hold a spirit enchantment");
+            }
+            match check_spirit_socket(&spirit) {
+                Err(HeroBuilderError::UnknownSpiritTier(_)) => {
+                    return Err("unknown tier of spirit");
+                }
+                Err(_) => return Err("spirit must conform to format [name] [tier]"),
+                Ok(_) => (),
+            }
+        }
+        return self.edit_slot(slot, hbi, |hero| hero.spirits_socketed[slot] = spirit);
+    }
+
+    /// Replace the skill in a skill slot (0-3), or empty it with an empty string
+    pub fn set_skill(
+        &mut self,
+        skill_slot: usize,
+        skill: String,
+        hbi: &HeroBuilderInformation,
+    ) -> Result<(), &'static str> {
+        if skill_slot >= 4 {
+            return Err("skill slot must be within range 0-3 inclusive");
+        }
+        let skill = match is_empty_skill(&skill) {
+            true => String::from(EMPTY_SKILL),
+            false => skill,
+        };
+        if !is_empty_skill(&skill) {
+            if skill_slot >= self.hero.count_skill_slots_unlocked() {
+                return Err("skill slot is locked at the hero's rank");
+            }
+            if !hbi.hero_skill_map.contains_key(&skill) {
+                return Err("skill not found in hero skills");
+            }
+        }
+        let mut skills = self.hero.skills.clone();
+        skills[skill_slot] = skill.clone();
+        if find_skill_conflict(&skills, &hbi.hero_skill_map).is_some() {
+            return Err("skill can't be equipped alongside the hero's other skills");
+        }
+        self.skills[skill_slot] = self
+            .hero
+            .resolve_hero_skill(
+                &hbi.hero_skill_tier_1_name_map,
+                &hbi.hero_skill_map,
+                skill.clone(),
+            )
+            .map_err(|_| "skill not found in hero skills")?;
+        self.hero.skills[skill_slot] = skill;
+        return Ok(());
+    }
+
+    fn get_blueprint<'a>(&self, slot: usize, hbi: &'a HeroBuilderInformation) -> &'a Blueprint {
+        return &hbi.bp_map[&self.hero.equipment_equipped[slot]];
+    }
+
+    /// Edit a slot (0-5) of a copy of the hero and recompute what the edit changes, only replacing the cached hero once all of it is computed
+    /// so an edit that fails leaves the cached hero unchanged
+    fn edit_slot(
+        &mut self,
+        slot: usize,
+        hbi: &HeroBuilderInformation,
+        edit: impl FnOnce(&mut Hero),
+    ) -> Result<(), &'static str> {
+        let mut hero = self.hero.clone();
+        edit(&mut hero);
+        let slot_stats = hero
+            .calculate_slot_stats(
+                slot,
+                &hbi.bp_map[&hero.equipment_equipped[slot]],
+                &hbi.spirit_map,
+                &hbi.elements,
+            )
+            .map_err(|_| "the item in that slot could not be built")?;
+        let skill_tiers = calculate_skill_tiers(&mut hero, hbi)?;
+
+        self.hero = hero;
+        self.slots[slot] = slot_stats;
+        if let Some((innate_skill, skills)) = skill_tiers {
+            self.innate_skill = innate_skill;
+            self.skills = skills;
+        }
+        self.update_pet_skill(hbi);
+        return Ok(());
+    }
+
+    fn update_pet_skill(&mut self, hbi: &HeroBuilderInformation) {
+        self.pet_skill = self
+            .hero
+            .get_pet(&hbi.bp_map)
+            .and_then(|(_, pet)| hbi.pet_skill_map.get(&pet))
+            .cloned();
+    }
+}
+
+/// The innate skill and hero skills of a hero at the tiers its element qty reaches
+type SkillTiers = (InnateSkill, [Option<HeroSkill>; 4]);
+
+/// Socketed elements set the hero's element qty, which sets the tiers of its innate and hero skills, so those are only recomputed when it changes
+/// Returns the innate and hero skills at the hero's new tiers, or None if its element qty is unchanged
+fn calculate_skill_tiers(
+    hero: &mut Hero,
+    hbi: &HeroBuilderInformation,
+) -> Result<Option<SkillTiers>, &'static str> {
+    let element_qty = hero
+        .calculate_element_qty(&hbi.bp_map)
+        .map_err(|_| "the hero's elements could not be counted")?;
+    if element_qty == hero.element_qty {
+        return Ok(None);
+    }
+    hero.element_qty = element_qty;
+    hero.calculate_innate_tier(
+        &hbi.class_innate_skill_names_map,
+        &hbi.innate_skill_map,
+        &hbi.champion_data,
+    )
+    .map_err(|_| "no innate found for the hero's class at its element qty")?;
+    let innate_skill = hero
+        .find_innate_skill(&hbi.class_innate_skill_names_map, &hbi.innate_skill_map)
+        .map_err(|_| "no innate found for the hero's class at its element qty")?;
+    let skills = hero
+        .resolve_hero_skills(&hbi.hero_skill_tier_1_name_map, &hbi.hero_skill_map)
+        .map_err(|_| "the hero's skills could not be found at their tiers")?;
+    return Ok(Some((innate_skill, skills)));
+}
+
+fn check_slot(slot: usize) -> Result<(), &'static str> {
+    if slot >= 6 {
+        return Err("slot must be within range 0-5 inclusive");
+    }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        equipment::create_blueprint,
+        inputs::load_seed_values_from_yaml,
+        test_data::{create_prepared_test_hero, create_test_game_data},
+    };
+
+    fn create_test_hero(spirits_socketed: [&str; 6]) -> Hero {
+        return create_hero(
+            String::from("Test_Hero"),
+            String::from("Mercenary"),
+            1,
+            1,
+            1,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            0,
+            String::from("Fire"),
+            0,
+            0.0,
+            0.0,
+            0.0,
+            0,
+            0,
+            0,
+            std::array::from_fn(|_| String::from(EMPTY_SKILL)),
+            std::array::from_fn(|slot| f!("Item {}", slot + 1)),
+            std::array::from_fn(|_| String::from("Normal")),
+            std::array::from_fn(|_| String::from("---")),
+            spirits_socketed.map(String::from),
+        );
+    }
+
+    fn create_test_blueprint(item_type: &str, spirit_affinity: &str) -> Blueprint {
+        return create_blueprint(
+            String::from("Test Item"),
+            String::from(item_type),
+            String::new(),
+            0,
+            0,
+            1,
+            0,
+            0,
+            String::new(),
+            0.0,
+            0,
+            0.0,
+            0,
+            0,
+            0,
+            0,
+            String::new(),
+            0,
+            String::new(),
+            0,
+            String::new(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            String::new(),
+            String::new(),
+            0,
+            String::new(),
+            String::new(),
+            0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            String::from("---"),
+            String::from(spirit_affinity),
+            0,
+            0,
+            0,
+            0,
+        );
+    }
+
+    /// The Mercenary, able to equip a sword in every slot
+    fn create_test_hero_classes() -> HashMap<String, HeroClass> {
+        return HashMap::from([(
+            String::from("Mercenary"),
+            _create_hero_class(
+                String::from("Mercenary"),
+                String::new(),
+                0,
+                0,
+                vec![10.0],
+                vec![10.0],
+                vec![10.0],
+                0.05,
+                0.05,
+                2.0,
+                10,
+                String::from("Fire"),
+                std::array::from_fn(|_| vec![String::from("Sword")]),
+                vec![],
+                std::array::from_fn(|_| String::from("Test Innate")),
+                false,
+                None,
+            ),
+        )]);
+    }
+
+    /// The items of create_test_hero's slots
+    fn create_test_bp_map() -> HashMap<String, Blueprint> {
+        return (1..=6)
+            .map(|slot| (f!("Item {}", slot), create_test_blueprint("Sword", "---")))
+            .collect();
+    }
+
+    /// Wolf gives 5% ATK, or 10% when socketed in an item with its affinity
+    fn create_test_spirit_map() -> HashMap<String, Spirit> {
+        return HashMap::from([(
+            String::from("Wolf"),
+            Spirit {
+                bonuses: SpiritBonuses {
+                    atk_percent: 0.05,
+                    ..SpiritBonuses::default()
+                },
+                affinity_bonuses: SpiritBonuses {
+                    atk_percent: 0.1,
+                    ..SpiritBonuses::default()
+                },
+            },
+        )]);
+    }
+
+    /// Sum the whole-hero bonuses of the spirit in each slot, the item of each slot having the given spirit affinity
+    fn sum_spirit_bonuses(hero: &Hero, spirit_affinities: [&str; 6]) -> SpiritBonuses {
+        let spirit_map = create_test_spirit_map();
+        let mut spirit_bonuses = SpiritBonuses::default();
+        for (slot, spirit_affinity) in spirit_affinities.iter().enumerate() {
+            let blueprint = create_test_blueprint("Sword", spirit_affinity);
+            spirit_bonuses.add(
+                &hero
+                    .calculate_slot_stats(slot, &blueprint, &spirit_map, &[])
+                    .unwrap()
+                    .spirit_bonuses,
+            );
+        }
+        return spirit_bonuses;
+    }
+
+    #[test]
+    fn spirits_stack_across_sockets() {
+        let one_wolf = create_test_hero(["Wolf T7", "---", "---", "---", "---", "---"]);
+        let two_wolves = create_test_hero(["Wolf T7", "Wolf T9", "---", "---", "---", "---"]);
+        let no_affinities = ["---"; 6];
+        assert_eq!(
+            sum_spirit_bonuses(&one_wolf, no_affinities).atk_percent,
+            0.05
+        );
+        assert_eq!(
+            sum_spirit_bonuses(&two_wolves, no_affinities).atk_percent,
+            0.1
+        );
+    }
+
+    #[test]
+    fn spirit_affinity_is_decided_for_each_socket() {
+        let hero = create_test_hero(["Wolf T7", "Wolf T7", "---", "---", "---", "---"]);
+        let affinities = ["Wolf Spirit", "---", "---", "---", "---", "---"];
+        assert_eq!(
+            sum_spirit_bonuses(&hero, affinities).atk_percent,
+            0.1 + 0.05
+        );
+    }
+
+    #[test]
+    fn spirits_are_counted_by_name_at_any_tier() {
+        let hero = create_test_hero([
+            "Armadillo T7",
+            "Armadillo T9",
+            "Lizard T7",
+            "---",
+            "Shark T9",
+            "None",
+        ]);
+        assert_eq!(hero.calculate_spirit_qty("Armadillo"), 2);
+        assert_eq!(hero.calculate_spirit_qty("Lizard"), 1);
+        assert_eq!(hero.calculate_spirit_qty("Shark"), 1);
+        assert_eq!(hero.calculate_spirit_qty("Mundra"), 0);
+        assert_eq!(hero.calculate_spirit_qty("Armadillo T7"), 0);
+    }
+
+    #[test]
+    fn element_socket_parses_type_and_grade() {
+        assert_eq!(
+            parse_element_socket("Fire 3"),
+            Ok(Some(SocketedElement {
+                element_type: String::from("Fire"),
+                grade: 3,
+            }))
+        );
+        assert_eq!(parse_element_socket("--"), Ok(None));
+    }
+
+    #[test]
+    fn element_socket_of_unknown_type_is_rejected() {
+        assert_eq!(
+            parse_element_socket("Fier 3"),
+            Err(HeroBuilderError::UnknownElementType(String::from("Fier")))
+        );
+        assert_eq!(
+            parse_element_socket("Any 3"),
+            Err(HeroBuilderError::UnknownElementType(String::from("Any")))
+        );
+    }
+
+    #[test]
+    fn equipment_of_unknown_quality_is_rejected() {
+        let mut hero = create_test_hero(["---"; 6]);
+        hero.equipment_quality[2] = String::from("Mythic");
+        let expected = Err(HeroBuilderError::UnknownQuality(String::from("Mythic")));
+        assert_eq!(
+            hero.validate_equipment(&create_test_bp_map(), &create_test_hero_classes()),
+            expected
+        );
+        assert_eq!(
+            hero.calculate_slot_stats(
+                2,
+                &create_test_blueprint("Sword", "---"),
+                &HashMap::new(),
+                &[]
+            )
+            .map(|_| ()),
+            expected
+        );
+    }
+
+    #[test]
+    fn spirit_of_unknown_tier_is_rejected() {
+        let mut hero = create_test_hero(["---", "Mammoth T42", "---", "---", "---", "---"]);
+        let expected = Err(HeroBuilderError::UnknownSpiritTier(String::from(
+            "Mammoth T42",
+        )));
+        assert_eq!(
+            hero.validate_equipment(&create_test_bp_map(), &create_test_hero_classes()),
+            expected
+        );
+        assert_eq!(
+            hero.calculate_slot_stats(
+                1,
+                &create_test_blueprint("Sword", "---"),
+                &HashMap::new(),
+                &[]
+            )
+            .map(|_| ()),
+            expected
+        );
+        hero.spirits_socketed[1] = String::from("Mammoth T9");
+        assert_eq!(
+            hero.validate_equipment(&create_test_bp_map(), &create_test_hero_classes()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn element_without_bonuses_is_rejected() {
+        let mut hero = create_test_hero(["---"; 6]);
+        hero.elements_socketed[0] = String::from("Fire 3");
+        assert_eq!(
+            hero.calculate_slot_stats(
+                0,
+                &create_test_blueprint("Sword", "---"),
+                &HashMap::new(),
+                &[]
+            )
+            .map(|_| ()),
+            Err(HeroBuilderError::UnknownElement(String::from("Fire 3")))
+        );
+    }
+
+    #[test]
+    fn cached_hero_edits_build_like_the_edited_hero() {
+        let mut hbi = create_test_game_data();
+        hbi.bp_map.insert(
+            String::from("Wolf Sword"),
+            create_test_blueprint("Sword", "Wolf Spirit"),
+        );
+        hbi.spirit_map = create_test_spirit_map();
+        let hero = create_prepared_test_hero("Hero_1", 3, ["Strike T1", "Empty", "Empty", "Empty"]);
+        let mut cached_hero = create_cached_hero(hero, &hbi).unwrap();
+
+        cached_hero
+            .set_equipment(0, String::from("Wolf Sword"), &hbi)
+            .unwrap();
+        cached_hero
+            .set_quality(1, String::from("Epic"), &hbi)
+            .unwrap();
+        cached_hero
+            .set_element(2, String::from("Fire 1"), &hbi)
+            .unwrap();
+        cached_hero
+            .set_spirit(0, String::from("Wolf T7"), &hbi)
+            .unwrap();
+        cached_hero
+            .set_skill(1, String::from("Guard T2"), &hbi)
+            .unwrap();
+
+        let edited_hero = cached_hero.get_hero().clone();
+        assert_eq!(edited_hero.element_qty, 5);
+        assert_eq!(
+            cached_hero.build_sim_hero(),
+            hbi.build_sim_hero_uncached(edited_hero.clone()).unwrap()
+        );
+
+        // A rejected edit leaves the hero as it was
+        assert_eq!(
+            cached_hero.set_spirit(1, String::from("Wolf T3"), &hbi),
+            Err("unknown tier of spirit")
+        );
+        assert_eq!(cached_hero.get_hero(), &edited_hero);
+    }
+
+    #[test]
+    fn slot_rules_reject_equipment_that_breaks_them() {
+        let mut hero_class = create_test_hero_classes().remove("Mercenary").unwrap();
+        hero_class.slot_rules = vec![
+            SlotRule::MaxOfTypes {
+                item_types: vec![String::from("Sword")],
+                max: 1,
+            },
+            SlotRule::Distinct { item_types: vec![] },
+            SlotRule::Requires {
+                slot: 1,
+                item_types: vec![String::from("Shield")],
+                required_slot: 0,
+                required_types: vec![String::from("Sword")],
+            },
+        ];
+        let mut bp_map: HashMap<String, Blueprint> = (1..=6)
+            .map(|i| (f!("Shield {}", i), create_test_blueprint("Shield", "---")))
+            .collect();
+        for i in 1..=2 {
+            bp_map.insert(f!("Sword {}", i), create_test_blueprint("Sword", "---"));
+        }
+        let equipment = |items: [&str; 6]| -> [String; 6] { items.map(String::from) };
+
+        assert_eq!(
+            hero_class.check_slot_rules(
+                &equipment(["Sword 1", "Shield 1", "Shield 2", "Shield 3", "Shield 4", "Shield 5"]),
+                &bp_map
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            hero_class.check_slot_rules(
+                &equipment(["Sword 1", "Sword 2", "Shield 1", "Shield 2", "Shield 3", "Shield 4"]),
+                &bp_map
+            ),
+            Err(HeroBuilderError::TooManyOfTypes {
+                item_types: vec![String::from("Sword")],
+                max: 1,
+                slots: vec![0, 1],
+            })
+        );
+        assert_eq!(
+            hero_class.check_slot_rules(
+                &equipment(["Sword 1", "Shield 1", "Shield 1", "Shield 3", "Shield 4", "Shield 5"]),
+                &bp_map
+            ),
+            Err(HeroBuilderError::DuplicateEquipment {
+                equipment: String::from("Shield 1"),
+                slots: (1, 2),
+            })
+        );
+        assert_eq!(
+            hero_class.check_slot_rules(
+                &equipment([
+                    "Shield 6", "Shield 1", "Shield 2", "Shield 3", "Shield 4", "Shield 5"
+                ]),
+                &bp_map
+            ),
+            Err(HeroBuilderError::UnmetSlotRequirement {
+                equipment: String::from("Shield 1"),
+                slot: 1,
+                required_slot: 0,
+                required_types: vec![String::from("Sword")],
+            })
+        );
+        assert_eq!(
+            hero_class.check_slot_rules(
+                &equipment(["Axe 1", "Shield 1", "Shield 2", "Shield 3", "Shield 4", "Shield 5"]),
+                &bp_map
+            ),
+            Err(HeroBuilderError::UnknownEquipment(String::from("Axe 1")))
+        );
+    }
+
+    #[test]
+    fn seed_values_set_the_stats_each_seed_adds() {
+        let hbi = create_test_game_data();
+        let hero = create_prepared_test_hero("Hero_1", 1, ["Strike T1", "Empty", "Empty", "Empty"]);
+        let build_stats = |seed_values: SeedValues| -> [f64; 3] {
+            let mut hero = hero.clone();
+            hero.calculate_stat_improvements_from_gear_and_skills(
+                &hbi.bp_map,
+                &hbi.hero_skill_tier_1_name_map,
+                &hbi.hero_skill_map,
+                &hbi.class_innate_skill_names_map,
+                &hbi.innate_skill_map,
+                &hbi.pet_skill_map,
+                &hbi.spirit_map,
+                &hbi.elements,
+                &seed_values,
+            )
+            .unwrap();
+            return [hero.atk, hero.def, hero.hp];
+        };
+        let unseeded = build_stats(SeedValues {
+            hp: 0.0,
+            atk: 0.0,
+            def: 0.0,
+        });
+        let seeded = build_stats(SeedValues::default());
+        let double_seeded = build_stats(SeedValues {
+            hp: 2.0,
+            atk: 8.0,
+            def: 8.0,
+        });
+
+        for stat in 0..3 {
+            assert!(seeded[stat] > unseeded[stat]);
+            assert!(
+                ((double_seeded[stat] - unseeded[stat]) - 2.0 * (seeded[stat] - unseeded[stat]))
+                    .abs()
+                    < 1e-9
+            );
+        }
+
+        let seed_values = load_seed_values_from_yaml("hp: 2\natk: 8\ndef: 8".as_bytes()).unwrap();
+        assert_eq!(
+            seed_values,
+            SeedValues {
+                hp: 2.0,
+                atk: 8.0,
+                def: 8.0,
+            }
+        );
+        assert!(load_seed_values_from_yaml("hp: -1\natk: 4\ndef: 4".as_bytes()).is_err());
+    }
+}
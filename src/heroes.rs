@@ -1,14 +1,23 @@
 use crate::{
-    decimals::round_to_2,
+    champions::{Champion, ChampionData, NO_CHAMPION_DATA},
+    combat_trace::{CombatAction, CombatTrace, HitKind, MOB_ACTOR},
+    dungeons::calculate_elemental_damage_modifier,
+    formatting::{create_stats_view, StatsView},
+    hashing::stable_hash,
     inputs::{create_sim_hero_input, SimHeroInput},
+    simulations::TargetingModel,
+    skills::{SkillEffect, MAX_DAMAGE_REDUCTION},
 };
 
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::string::ToString;
 
 use crate::equipment::{BoosterType, ElementType};
+use crate::rng::RoundRolls;
 
-use rand::{thread_rng, Rng};
+use rand::seq::SliceRandom;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 /// One or more Heroes fighting together in a dungeon and what booster they have
@@ -20,20 +29,57 @@ pub struct Team {
     num_rogues: u8,
     num_spellcasters: u8,
     num_tricksters: u8,
-    champion: String,
+    champion: Option<Champion>, // The team's champion, if any, whose ability applies to every hero
     champion_innate_tier: u8,
+    #[serde(skip)]
+    last_target: Option<usize>, // The index of the hero last hit by a single target attack, for round robin targeting
 }
 
 /// Defines valid hero archetypes
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, strum::EnumString)]
+#[strum(ascii_case_insensitive)]
 pub enum HeroArchetype {
+    #[strum(serialize = "RedFighter", serialize = "Fighter")]
     RedFighter,
+    #[strum(serialize = "GreenRogue", serialize = "Rogue")]
     GreenRogue,
+    #[strum(serialize = "BlueSpellcaster", serialize = "Spellcaster")]
     BlueSpellcaster,
     Champion,
 }
 
 impl Team {
+    /// Returns a stable hash of the heroes' builds and the booster, ignoring hero identifiers so identical builds share a hash
+    pub fn get_build_hash(&self) -> String {
+        let mut heroes = self.heroes.clone();
+        for hero in heroes.iter_mut() {
+            hero.identifier = String::new();
+            hero.champion_data = None;
+        }
+        return stable_hash(&(heroes, self.booster));
+    }
+
+    /// Returns a stable hash of the game data behind the heroes that their stats don't capture, their class, archetype, and champion ability
+    pub fn get_hero_data_hash(&self) -> String {
+        let hero_data: Vec<(&String, &HeroArchetype, &Option<ChampionData>)> = self
+            .heroes
+            .iter()
+            .map(|hero| (&hero.class, &hero.archetype, &hero.champion_data))
+            .collect();
+        return stable_hash(&hero_data);
+    }
+
+    /// Returns the build signatures of each hero sorted and joined with " + ", so the same party always has the same signature regardless of hero order
+    pub fn get_build_signature(&self) -> String {
+        let mut signatures: Vec<String> = self
+            .heroes
+            .iter()
+            .map(|hero| hero.get_build_signature())
+            .collect();
+        signatures.sort();
+        return signatures.join(" + ");
+    }
+
     pub fn get_team_hero_names(&self) -> Vec<String> {
         let mut res: Vec<String> = Default::default();
         for hero in self.heroes.iter() {
@@ -42,7 +88,7 @@ impl Team {
         return res;
     }
 
-    pub fn get_index_of_hero_with_identifier(&self, identifier: &String) -> Option<usize> {
+    pub fn _get_index_of_hero_with_identifier(&self, identifier: &String) -> Option<usize> {
         let mut index = 0;
         for hero in self.heroes.iter() {
             if hero.get_identifier().eq(identifier) {
@@ -53,22 +99,25 @@ impl Team {
         return None;
     }
 
+    /// Returns the identifier and build stats of each hero in team order
+    pub fn get_heroes_build_stats(&self) -> Vec<(String, [(&'static str, f64); 8])> {
+        return self
+            .heroes
+            .iter()
+            .map(|hero| (hero.get_identifier(), hero.get_build_stats()))
+            .collect();
+    }
+
     pub fn _get_hero_at_index(&self, index: usize) -> SimHero {
         return self.heroes[index].clone();
     }
 
-    pub fn set_hero_at_index(&mut self, index: usize, new_hero: SimHero) {
+    pub fn _set_hero_at_index(&mut self, index: usize, new_hero: SimHero) {
         self.heroes[index] = new_hero;
     }
 
-    pub fn round_floats_for_display(&self) -> Team {
-        let mut t2 = self.clone();
-        let mut heroes: Vec<SimHero> = vec![];
-        for hero in &t2.heroes {
-            heroes.push(hero.round_floats_for_display());
-        }
-        t2.heroes = heroes;
-        return t2;
+    pub fn fmt_stats(&self) -> Vec<StatsView> {
+        return self.heroes.iter().map(|hero| hero.fmt_stats()).collect();
     }
 
     pub fn normalize_percents(&mut self, is_extreme: bool, is_boss: bool) {
@@ -84,6 +133,7 @@ impl Team {
     }
 
     pub fn calculate_damage_from_encounter(&mut self, defense_cap: f64, damage: f64) {
+        let damage_reduction = self.get_damage_reduction();
         // Calc the amount of damage taken by each hero in encounter
         for hero in &mut self.heroes {
             if hero.defense <= defense_cap / 6.0 {
@@ -101,9 +151,93 @@ impl Team {
                         * (0.25 * damage - 0.3 * damage);
             }
             hero.crit_damage_taken_when_hit = f64::max(hero.damage_taken_when_hit, damage) * 1.5;
+            hero.damage_taken_when_hit *= 1.0 - damage_reduction;
+            hero.crit_damage_taken_when_hit *= 1.0 - damage_reduction;
         }
     }
 
+    /// Returns the fraction of damage prevented by the damage reduction auras of living heroes
+    fn get_damage_reduction(&self) -> f64 {
+        let reduction: f64 = self
+            .heroes
+            .iter()
+            .filter(|hero| hero.hp > 0.0)
+            .flat_map(|hero| hero.skill_effects.iter())
+            .map(|effect| match effect {
+                SkillEffect::DamageReductionAura { reduction } => *reduction,
+                _ => 0.0,
+            })
+            .sum();
+        return reduction.min(MAX_DAMAGE_REDUCTION);
+    }
+
+    /// Returns whether any hero has a damage reduction aura, so damage taken must be recalculated when they fall
+    pub fn has_damage_reduction_aura(&self) -> bool {
+        return self.heroes.iter().any(|hero| {
+            hero.skill_effects
+                .iter()
+                .any(|effect| matches!(effect, SkillEffect::DamageReductionAura { .. }))
+        });
+    }
+
+    /// Returns the indices of heroes whose skills let them strike first in the first round
+    pub fn get_first_strikers(&self) -> Vec<usize> {
+        return (0..self.heroes.len())
+            .filter(|i| {
+                self.heroes[*i]
+                    .skill_effects
+                    .contains(&SkillEffect::FirstStrike)
+            })
+            .collect();
+    }
+
+    /// Each living hero with an unused revive brings back the first fallen hero, once per fight
+    /// Returns the number of heroes revived
+    pub fn calculate_revives(&mut self, trace: &mut CombatTrace) -> (usize, Vec<String>) {
+        let mut log_queue: Vec<String> = vec![];
+        let mut revived_qty = 0;
+        for i in 0..self.heroes.len() {
+            let hp_fraction =
+                match self.heroes[i]
+                    .skill_effects
+                    .iter()
+                    .find_map(|effect| match effect {
+                        SkillEffect::Revive { hp_fraction } => Some(*hp_fraction),
+                        _ => None,
+                    }) {
+                    Some(hp_fraction) if self.heroes[i].hp > 0.0 && !self.heroes[i].revive_used => {
+                        hp_fraction
+                    }
+                    _ => continue,
+                };
+            let fallen = match self.heroes.iter().position(|hero| hero.hp <= 0.0) {
+                Some(fallen) => fallen,
+                None => break,
+            };
+            let revived_hp = (hp_fraction * self.heroes[fallen].hp_max).ceil();
+            self.heroes[fallen].hp = revived_hp;
+            self.heroes[i].revive_used = true;
+            self.heroes[i].procs += 1;
+            self.heroes[i].healing_done += revived_hp;
+            revived_qty += 1;
+            log_queue.push(f!(
+                "Hero {} revives hero {} with {:.2} hp",
+                self.heroes[i].identifier,
+                self.heroes[fallen].identifier,
+                revived_hp
+            ));
+            trace.record(
+                CombatAction::Revive,
+                &self.heroes[i].identifier,
+                &self.heroes[fallen].identifier,
+                HitKind::Normal,
+                0.0,
+                revived_hp,
+            );
+        }
+        return (revived_qty, log_queue);
+    }
+
     pub fn apply_champion_and_booster_bonuses(&mut self, is_boss: bool) -> (f64, bool, f64, u8) {
         let (champion, champion_innate_tier) = self.get_champion_info();
         let (num_spellcasters, num_rogues, num_fighters, num_tricksters) =
@@ -120,119 +254,43 @@ impl Team {
         let mut booster_attack_bonus = 0f64;
         let mut booster_defense_bonus = 0f64;
 
-        match champion.as_str() {
-            "Argon" => {
-                champion_attack_bonus = 0.1f64 * f64::from(champion_innate_tier);
-                champion_defense_bonus = champion_attack_bonus;
-            }
-            "Ashley" => {
-                champion_attack_bonus = 0.05 + 0.05 * f64::from(champion_innate_tier);
-                if is_boss {
-                    champion_attack_bonus = champion_attack_bonus * 2.0;
-                }
-                champion_defense_bonus = champion_attack_bonus;
-            }
-            "Donovan" => {
-                match champion_innate_tier {
-                    1u8 => {
-                        champion_attack_bonus = 0.05 * f64::from(num_spellcasters);
-                    }
-                    2u8 => {
-                        champion_attack_bonus = 0.08 * f64::from(num_spellcasters);
-                    }
-                    3u8 => {
-                        champion_attack_bonus = 0.10 * f64::from(num_spellcasters);
-                    }
-                    4u8 => {
-                        champion_attack_bonus = 0.14 * f64::from(num_spellcasters);
-                    }
-                    _ => (),
-                }
-
-                for hero in &mut self.heroes {
-                    hero.hp = hero.hp
-                        * (1.0
-                            + (0.04
-                                + 0.01 * f64::from(champion_innate_tier)
-                                + 0.02 * f64::from(std::cmp::max(champion_innate_tier - 3, 0)))
-                                * f64::from(num_fighters));
-                    hero.critical_chance = hero.critical_chance
-                        + (0.02
-                            + 0.01 * f64::from(champion_innate_tier)
-                            + 0.01 * f64::from(std::cmp::max(champion_innate_tier - 3, 0)))
-                            * f64::from(num_rogues);
-                    hero.evasion = hero.evasion
-                        + (0.02
-                            + 0.01 * f64::from(champion_innate_tier)
-                            + 0.01 * f64::from(std::cmp::max(champion_innate_tier - 3, 0)))
-                            * f64::from(num_rogues);
-                    if hero.class == "Mercenary" {
-                        // it looks like mercenaries get an extra 1.25x cause of the +25% effect from champ skills
-                        hero.hp *= 1.25;
-                        hero.critical_chance *= 1.25;
-                        hero.evasion *= 1.25;
+        if champion.is_some() {
+            let data = self.get_champion_data();
+            (champion_attack_bonus, champion_defense_bonus) =
+                data.get_attack_and_defense_bonus(champion_innate_tier, num_spellcasters, is_boss);
+            let (hp_bonus, crit_chance_bonus, evasion_bonus) =
+                data.get_hero_bonuses(champion_innate_tier, num_fighters, num_rogues);
+            let (boosts_hp, boosts_crit_chance, boosts_evasion) =
+                data.get_boosted_stats(champion_innate_tier);
+            let mercenary_multiplier = data.get_mercenary_multiplier();
+            hemma_mult = data.get_drain(champion_innate_tier).1;
+            // Donovan's crit chance is an aura applied to each roll, see Champion::get_crit_chance_aura
+            for hero in &mut self.heroes {
+                hero.hp = hero.hp * (1.0 + hp_bonus);
+                hero.critical_chance = hero.critical_chance + crit_chance_bonus;
+                hero.evasion = hero.evasion + evasion_bonus;
+                if hero.class == "Mercenary" {
+                    // it looks like mercenaries get an extra 1.25x cause of the +25% effect from champ skills
+                    if boosts_hp {
+                        hero.hp *= mercenary_multiplier;
                     }
-                }
-            }
-            "Hemma" => {
-                for hero in &mut self.heroes {
-                    hero.hp = hero.hp
-                        * (1.0
-                            + 0.05 * f64::from(champion_innate_tier)
-                            + 0.05 * f64::from(std::cmp::max(champion_innate_tier - 3, 0)));
-                    if hero.class == "Mercenary" {
-                        hero.hp *= 1.328;
+                    if boosts_crit_chance {
+                        hero.critical_chance *= mercenary_multiplier;
                     }
-                }
-                hemma_mult = 0.04 + f64::from(champion_innate_tier) * 0.02;
-            }
-            "Lilu" => {
-                for hero in &mut self.heroes {
-                    hero.hp = hero.hp * (1.05 + 0.05 * f64::from(champion_innate_tier));
-                    if hero.class == "Mercenary" {
-                        hero.hp *= 1.25;
+                    if boosts_evasion {
+                        hero.evasion *= mercenary_multiplier;
                     }
                 }
             }
-            "Polonia" => {
-                champion_defense_bonus = 0.05 + 0.05 * f64::from(champion_innate_tier);
-                for hero in &mut self.heroes {
-                    if champion_innate_tier < 3 {
-                        hero.evasion = hero.evasion + 0.05 * 1.25;
-                    } else {
-                        hero.evasion = hero.evasion + 0.1 * 1.25;
-                    }
+        }
 
-                    if hero.class == "Mercenary" {
-                        hero.evasion *= 1.25;
-                    }
-                }
-                count_loot = true;
-                match champion_innate_tier {
-                    1u8 => loot_chance = 0.3,
-                    2u8 => loot_chance = 0.35,
-                    3u8 => loot_chance = 0.4,
-                    4u8 => loot_chance = 0.5,
-                    _ => (),
-                }
-                loot_chance = loot_chance + f64::from(num_tricksters) * 0.02;
-                polonia_loot_cap = polonia_loot_cap + num_tricksters * 2;
-            }
-            "Sia" => {
-                champion_attack_bonus = 0.05 + 0.05 * f64::from(champion_innate_tier);
-            }
-            "Yami" => {
-                for hero in &mut self.heroes {
-                    hero.critical_chance =
-                        hero.critical_chance + 0.05 * f64::from(champion_innate_tier);
-                    hero.evasion = hero.evasion + 0.05 * f64::from(champion_innate_tier);
-                    if hero.class == "Mercenary" {
-                        hero.critical_chance *= 1.25;
-                        hero.evasion *= 1.25;
-                    }
-                }
-            }
-            _ => (),
+        if let Some((chance, cap)) = champion.and_then(|_| {
+            self.get_champion_data()
+                .get_loot_stealing(champion_innate_tier, num_tricksters)
+        }) {
+            count_loot = true;
+            loot_chance = chance;
+            polonia_loot_cap = cap;
         }
 
         // Calculate Booster Bonuses
@@ -276,12 +334,20 @@ impl Team {
 
     pub fn initialize_survive_chance_hemma_guaranteed_crit_and_berserker_stage(&mut self) {
         for hero in &mut self.heroes {
-            hero.survive_chance = f64::from(hero.armadillo_qty) * 15.0 / 100.0;
+            // Builder heroes' survive fatal blow stat includes their Armadillo spirits along with skills, pets, and affinity bonuses,
+            // heroes entered without the stat fall back to 15% per Armadillo
+            hero.survive_chance = f64::min(
+                f64::max(
+                    hero.survive_fatal_blow_chance,
+                    f64::from(hero.armadillo_qty) * 15.0 / 100.0,
+                ),
+                1.0,
+            );
             if hero.class == "Cleric" || hero.class == "Bishop" {
                 hero.survive_chance = 1.2;
             }
 
-            if hero.class == "Hemma" {
+            if hero.class == Champion::Hemma.to_string() {
                 hero.hemma_bonus = 0.0;
             }
 
@@ -339,6 +405,58 @@ impl Team {
         return target_chance_heroes;
     }
 
+    /// Choose the hero hit by a single target attack. Only threat weighted targeting draws a roll
+    fn select_target(
+        &mut self,
+        targeting: TargetingModel,
+        target_chance_heroes: [f64; 4],
+        rng: &mut RoundRolls<'_>,
+    ) -> usize {
+        let mut target = 0;
+        match targeting {
+            TargetingModel::ThreatWeighted => {
+                // The roll lands in the first living hero's share of the cumulative chances,
+                // so a hero with zero threat is never targeted. The fifth hero takes what is left
+                let target_rng = rng.gen::<f64>();
+                let heroes_len = std::cmp::min(self.heroes.len(), target_chance_heroes.len());
+                match (0..heroes_len)
+                    .find(|i| target_rng < target_chance_heroes[*i] && self.heroes[*i].hp > 0.0)
+                {
+                    Some(i) => target = i,
+                    None => {
+                        if let Some(i) = (0..self.heroes.len())
+                            .rev()
+                            .find(|i| self.heroes[*i].hp > 0.0)
+                        {
+                            target = i;
+                        }
+                    }
+                }
+            }
+            TargetingModel::LowestHp => {
+                let mut lowest_hp = f64::INFINITY;
+                for (i, hero) in self.heroes.iter().enumerate() {
+                    if hero.hp > 0.0 && hero.hp < lowest_hp {
+                        lowest_hp = hero.hp;
+                        target = i;
+                    }
+                }
+            }
+            TargetingModel::RoundRobin => {
+                let heroes_len = self.heroes.len();
+                let start = self.last_target.map_or(0, |last| last + 1);
+                if let Some(i) = (start..start + heroes_len)
+                    .map(|i| i % heroes_len)
+                    .find(|i| self.heroes[*i].hp > 0.0)
+                {
+                    target = i;
+                }
+            }
+        }
+        self.last_target = Some(target);
+        return target;
+    }
+
     pub fn update_ninja_bonus_and_extreme_crit_bonus(
         &mut self,
         round: i16,
@@ -389,11 +507,13 @@ impl Team {
         round: i16,
         mut update_target: bool,
         target_chance_heroes: [f64; 4],
+        targeting: TargetingModel,
         crit_chance: f64,
         crit_chance_modifier: f64,
+        rng: &mut RoundRolls<'_>,
+        trace: &mut CombatTrace,
     ) -> (usize, bool, bool, Vec<String>) {
         let mut log_queue: Vec<String> = vec![];
-        let mut rng = thread_rng();
 
         let lord_present: bool;
         let lord_index: usize;
@@ -429,10 +549,19 @@ impl Team {
                             )
                     {
                         log_queue.push(f!("Hero {} evades AOE attack", hero.identifier));
+                        trace.record(
+                            CombatAction::MobAoe,
+                            MOB_ACTOR,
+                            &hero.identifier,
+                            HitKind::Evaded,
+                            0.0,
+                            hero.hp,
+                        );
                         hero.dodges += 1;
                         if hero.class == "Dancer" || hero.class == "Acrobat" {
                             log_queue.push(f!("Hero {} gains guaranteed crit", hero.identifier));
                             hero.guaranteed_crit = true;
+                            hero.procs += 1;
                         }
                     } else {
                         let damage = (hero.damage_taken_when_hit * aoe_damage).ceil();
@@ -458,6 +587,7 @@ impl Team {
                                     // Lord Saves
                                     log_queue.push(f!("Hero {} saved by Lord", hero.identifier));
                                     lord_save = false;
+                                    lord_hero.procs += 1;
                                     hero.hp += (hero.damage_taken_when_hit * aoe_damage).ceil();
                                     lord_hero.hp -=
                                         (lord_hero.damage_taken_when_hit * aoe_damage).ceil();
@@ -492,8 +622,17 @@ impl Team {
                                             ));
                                             lord_hero.hp = 1.0;
                                             lord_hero.survive_chance = 0.0;
+                                            lord_hero.procs += 1;
                                         }
                                     }
+                                    trace.record(
+                                        CombatAction::LordProtect,
+                                        MOB_ACTOR,
+                                        &lord_hero.identifier,
+                                        HitKind::Normal,
+                                        (lord_hero.damage_taken_when_hit * aoe_damage).ceil(),
+                                        lord_hero.hp,
+                                    );
                                 } else {
                                     // lord doesnt save
                                     log_queue.push(f!("Hero {} dies", hero.identifier));
@@ -509,8 +648,17 @@ impl Team {
                                 ));
                                 hero.hp = 1.0;
                                 hero.survive_chance = 0.0;
+                                hero.procs += 1;
                             }
                         }
+                        trace.record(
+                            CombatAction::MobAoe,
+                            MOB_ACTOR,
+                            &hero.identifier,
+                            HitKind::Normal,
+                            damage,
+                            hero.hp,
+                        );
 
                         // Check if innate lost
                         log_queue.push(f!("Checking if Hero {} is sensei and didn't already lose innate last round", hero.identifier));
@@ -527,15 +675,7 @@ impl Team {
         } else {
             // Mob attacks only one hero
             log_queue.push("Mob Attempting Single Target Attack".to_string());
-            let mut target = 0;
-            let target_rng = rng.gen::<f64>();
-            for i in (0..target_chance_heroes.len()).rev() {
-                if target_rng > target_chance_heroes[i] && self.heroes[i].hp > 0.0 {
-                    // Hero i targeted
-                    target = i;
-                    break;
-                }
-            }
+            let target = self.select_target(targeting, target_chance_heroes, rng);
             // check hit/evade
             let hero = &mut self.heroes[target];
             log_queue.push(f!(
@@ -551,10 +691,19 @@ impl Team {
                     )
             {
                 log_queue.push(f!("Hero {} evades single target attack", hero.identifier));
+                trace.record(
+                    CombatAction::MobAttack,
+                    MOB_ACTOR,
+                    &hero.identifier,
+                    HitKind::Evaded,
+                    0.0,
+                    hero.hp,
+                );
                 hero.dodges += 1;
                 if hero.class == "Danger" || hero.class == "Acrobat" {
                     log_queue.push(f!("Hero {} gains guaranteed crit", hero.identifier));
                     hero.guaranteed_crit = true;
+                    hero.procs += 1;
                 }
             } else {
                 log_queue.push(f!(
@@ -562,8 +711,12 @@ impl Team {
                     hero.identifier
                 ));
                 // Hit, check crit
+                let hit: HitKind;
+                let damage: f64;
                 if rng.gen::<f64>() > crit_chance * crit_chance_modifier + hero.extreme_crit_bonus {
                     // not crit
+                    hit = HitKind::Normal;
+                    damage = hero.damage_taken_when_hit;
                     hero.hp -= hero.damage_taken_when_hit;
                     log_queue.push(f!(
                         "Hero {} is hit with a NORMAL attack, takes {:.2} damage bringing hp to {:.2}",
@@ -572,6 +725,8 @@ impl Team {
                         hero.hp
                     ));
                 } else {
+                    hit = HitKind::Critical;
+                    damage = hero.crit_damage_taken_when_hit;
                     hero.hp -= hero.crit_damage_taken_when_hit;
                     hero.crits_taken += 1;
                     log_queue.push(f!(
@@ -594,6 +749,7 @@ impl Team {
                             // Lord Saves
                             log_queue.push(f!("Hero {} is saved by lord", hero.identifier));
                             lord_save = false;
+                            lord_hero.procs += 1;
                             hero.hp += hero.damage_taken_when_hit;
                             lord_hero.hp -= lord_hero.damage_taken_when_hit;
                             log_queue.push(f!(
@@ -627,8 +783,17 @@ impl Team {
                                     ));
                                     lord_hero.hp = 1.0;
                                     lord_hero.survive_chance = 0.0;
+                                    lord_hero.procs += 1;
                                 }
                             }
+                            trace.record(
+                                CombatAction::LordProtect,
+                                MOB_ACTOR,
+                                &lord_hero.identifier,
+                                HitKind::Normal,
+                                lord_hero.damage_taken_when_hit,
+                                lord_hero.hp,
+                            );
                         } else {
                             // lord doesnt save
                             log_queue.push(f!("Hero {} dies", hero.identifier));
@@ -642,8 +807,17 @@ impl Team {
                             .push(f!("Hero {} survived fatal blow with 1 HP", hero.identifier));
                         hero.hp = 1.0;
                         hero.survive_chance = 0.0;
+                        hero.procs += 1;
                     }
                 }
+                trace.record(
+                    CombatAction::MobAttack,
+                    MOB_ACTOR,
+                    &hero.identifier,
+                    hit,
+                    damage,
+                    hero.hp,
+                );
 
                 // check sensei lost innate
                 log_queue.push(f!(
@@ -678,7 +852,7 @@ impl Team {
         let mut log_queue: Vec<String> = vec![];
         log_queue.push("Calculating Hemma Drain".to_string());
         let mut hemma_index = 0usize;
-        match self.get_class_index("Hemma".to_string()) {
+        match self.get_class_index(Champion::Hemma.to_string()) {
             Some(index) => hemma_index = index,
             _ => (),
         }
@@ -690,19 +864,18 @@ impl Team {
 
         if self.heroes[hemma_index].hp > 0.0 {
             log_queue.push("Hemma is alive".to_string());
+            let (drain_hp_fraction, _, drain_self_heal) =
+                self.get_champion_data().get_drain(champion_innate_tier);
             let mut hemma_hero = self.heroes[hemma_index].clone();
             for (i, hero) in self.heroes.iter_mut().enumerate() {
-                if i != hemma_index
-                    && hero.hp > (0.11 - 0.01 * f64::from(champion_innate_tier)) * hero.hp_max
-                {
+                if i != hemma_index && hero.hp > drain_hp_fraction * hero.hp_max {
                     log_queue.push(f!(
                         "Hero {} is not hemma and has enough HP ({:.2}) to steal",
                         hero.identifier,
                         hero.hp
                     ));
                     hemma_hero.hemma_bonus += hemma_hero.attack * hemma_mult;
-                    hero.hp =
-                        hero.hp - (0.11 - 0.01 * f64::from(champion_innate_tier)) * hero.hp_max;
+                    hero.hp = hero.hp - drain_hp_fraction * hero.hp_max;
                     if hero.class == "Sensei" && hero.lost_innate != round - 1 {
                         log_queue.push(f!("Hero {} is sensei and loses innate", hero.identifier));
                         hero.lost_innate = round;
@@ -717,11 +890,7 @@ impl Team {
             }
             self.heroes[hemma_index] = hemma_hero;
             self.heroes[hemma_index].hp = f64::min(
-                self.heroes[hemma_index].hp
-                    + 1.0
-                    + f64::from(champion_innate_tier)
-                    + f64::from(std::cmp::min(champion_innate_tier - 2, 0))
-                    + f64::from(std::cmp::min(champion_innate_tier - 3, 0)),
+                self.heroes[hemma_index].hp + drain_self_heal,
                 self.heroes[hemma_index].hp_max,
             );
             log_queue.push(f!(
@@ -786,11 +955,116 @@ impl Team {
         return log_queue;
     }
 
+    /// Instantly defeat every living hero, ignoring evasion and fatal blow survival
+    pub fn wipe(&mut self) -> Vec<String> {
+        let mut log_queue: Vec<String> = vec![];
+        for hero in &mut self.heroes {
+            if hero.hp > 0.0 {
+                hero.hp = 0.0;
+                log_queue.push(f!("Hero {} is wiped out", hero.identifier));
+            }
+        }
+        return log_queue;
+    }
+
+    /// Reduce the defense of every living hero by a fraction, damage taken must be recalculated afterwards
+    pub fn apply_armor_shred(&mut self, defense_reduction: f64) -> Vec<String> {
+        let mut log_queue: Vec<String> = vec![];
+        for hero in &mut self.heroes {
+            if hero.hp > 0.0 {
+                hero.defense *= 1.0 - defense_reduction;
+                log_queue.push(f!(
+                    "Hero {} has armor shredded, defense is now {:.2}",
+                    hero.identifier,
+                    hero.defense
+                ));
+            }
+        }
+        return log_queue;
+    }
+
+    /// Attack up to max_targets random living heroes for a multiple of the mob's normal attack damage
+    /// Slams can be evaded and survived like regular attacks, but the Lord cannot protect against them
+    pub fn calculate_mob_slam(
+        &mut self,
+        damage_multiplier: f64,
+        max_targets: u8,
+        mut heroes_alive: usize,
+        rng: &mut RoundRolls<'_>,
+        trace: &mut CombatTrace,
+    ) -> (usize, bool, Vec<String>) {
+        let mut log_queue: Vec<String> = vec![];
+        let mut update_target = false;
+
+        let mut targets: Vec<usize> = (0..self.heroes.len())
+            .filter(|i| self.heroes[*i].hp > 0.0)
+            .collect();
+        targets.shuffle(rng);
+        targets.truncate(max_targets as usize);
+        log_queue.push(f!("Mob slams heroes at indices {:?}", targets));
+
+        for i in targets {
+            let hero = &mut self.heroes[i];
+            if hero.guaranteed_evade
+                || rng.gen::<f64>()
+                    < f64::min(
+                        hero.evasion + f64::from(hero.berserker_stage) * 0.1 + hero.ninja_evasion,
+                        hero.evasion_cap,
+                    )
+            {
+                log_queue.push(f!("Hero {} evades slam", hero.identifier));
+                trace.record(
+                    CombatAction::MobSlam,
+                    MOB_ACTOR,
+                    &hero.identifier,
+                    HitKind::Evaded,
+                    0.0,
+                    hero.hp,
+                );
+                hero.dodges += 1;
+                continue;
+            }
+
+            let damage = (hero.damage_taken_when_hit * damage_multiplier).ceil();
+            hero.hp -= damage;
+            log_queue.push(f!(
+                "Hero {} is hit by slam, takes {:.2} damage bringing hp to {:.2}",
+                hero.identifier,
+                damage,
+                hero.hp
+            ));
+            if hero.hp <= 0.0 {
+                if rng.gen::<f64>() >= hero.survive_chance {
+                    log_queue.push(f!("Hero {} dies", hero.identifier));
+                    hero.hp = 0.0;
+                    heroes_alive -= 1;
+                    update_target = true;
+                } else {
+                    log_queue.push(f!("Hero {} survived fatal blow with 1 HP", hero.identifier));
+                    hero.hp = 1.0;
+                    hero.survive_chance = 0.0;
+                    hero.procs += 1;
+                }
+            }
+            trace.record(
+                CombatAction::MobSlam,
+                MOB_ACTOR,
+                &hero.identifier,
+                HitKind::Normal,
+                damage,
+                hero.hp,
+            );
+        }
+
+        return (heroes_alive, update_target, log_queue);
+    }
+
     pub fn calculate_heroes_attack(
         &mut self,
         attack_order: Vec<usize>,
         round: i16,
         rudo_bonus: f64,
+        crit_chance_aura: f64,
         mut shark_active: i32,
         dinosaur_active: i32,
         mut barrier_modifier: f64,
@@ -802,16 +1076,17 @@ impl Team {
         barrier_hp_max: f64,
         encounter_hp_max: f64,
         barrier_type: Option<ElementType>,
+        elemental_modifiers: &HashMap<ElementType, f64>,
+        rng: &mut RoundRolls<'_>,
+        trace: &mut CombatTrace,
     ) -> (u8, f64, f64, f64, i32, Vec<String>) {
         let mut log_queue: Vec<String> = vec![];
         log_queue.push("Calculate Heroes Attack".to_string());
 
         let mut polonia_loot: u8 = 0;
-        let mut rng = thread_rng();
 
         log_queue.push(f!("Attack order is {:?}", attack_order));
-        for i in 0..self.get_heroes_len() {
-            let jj = attack_order[i];
+        for jj in attack_order {
             let hero = &mut self.heroes[jj];
 
             log_queue.push(f!("Hero {} has hp of {:.2}", hero.identifier, hero.hp));
@@ -820,8 +1095,29 @@ impl Team {
                 if rng.gen::<f64>() > encounter_evasion {
                     // hit mob, check crit
                     log_queue.push(f!("Hero {} hits mob, checking crit", hero.identifier));
+                    let elemental_modifier = calculate_elemental_damage_modifier(
+                        elemental_modifiers,
+                        hero.element_type,
+                        hero.element_qty,
+                    );
+                    log_queue.push(f!(
+                        "Hero {} has element {} against mob, elemental damage modifier is {:.2}",
+                        hero.identifier,
+                        hero.element_type,
+                        elemental_modifier
+                    ));
+                    // Mercenaries get an extra 1.25x of champion auras, as with other champion bonuses
+                    let hero_crit_chance_aura = if hero.class == "Mercenary" {
+                        crit_chance_aura * 1.25
+                    } else {
+                        crit_chance_aura
+                    };
                     if hero.guaranteed_crit
-                        || rng.gen::<f64>() < hero.critical_chance + hero.ninja_bonus + rudo_bonus
+                        || rng.gen::<f64>()
+                            < hero.critical_chance
+                                + hero.ninja_bonus
+                                + rudo_bonus
+                                + hero_crit_chance_aura
                     {
                         // crit, if samurai variant ignore barrier else reduce damage by barrier mod
                         hero.crits_dealt += 1;
@@ -844,7 +1140,8 @@ impl Team {
                                     * f64::from(1 + hero.berserker_level)
                                     * f64::from(hero.berserker_stage))
                             + hero.hemma_bonus)
-                            * (hero.critical_multiplier + hero.consecutive_crit_bonus);
+                            * (hero.critical_multiplier + hero.consecutive_crit_bonus)
+                            * elemental_modifier;
                         if round != 1 || (hero.class != "Samurai" && hero.class != "Damiyo") {
                             log_queue.push(f!(
                                 "Hero {} is class {} and round is not 1 so do not pierce barrier",
@@ -867,6 +1164,14 @@ impl Team {
                             damage,
                             encounter_hp
                         ));
+                        trace.record(
+                            CombatAction::HeroAttack,
+                            &hero.identifier,
+                            MOB_ACTOR,
+                            HitKind::Critical,
+                            damage,
+                            encounter_hp,
+                        );
                         if hero.class == "Conquistador" {
                             hero.consecutive_crit_bonus =
                                 f64::min(hero.consecutive_crit_bonus + 0.25, 1.0);
@@ -897,6 +1202,7 @@ impl Team {
                                     * f64::from(1 + hero.berserker_level)
                                     * f64::from(hero.berserker_stage))
                             + hero.hemma_bonus)
+                            * elemental_modifier
                             * barrier_modifier;
                         encounter_hp -= damage;
                         hero.damage_dealt += damage;
@@ -906,6 +1212,14 @@ impl Team {
                             damage,
                             encounter_hp
                         ));
+                        trace.record(
+                            CombatAction::HeroAttack,
+                            &hero.identifier,
+                            MOB_ACTOR,
+                            HitKind::Normal,
+                            damage,
+                            encounter_hp,
+                        );
                         if hero.class == "Conquistador" {
                             hero.consecutive_crit_bonus = 0.0;
                             log_queue.push(f!(
@@ -949,6 +1263,14 @@ impl Team {
                     // Missed
                     hero.attacks_missed += 1;
                     log_queue.push(f!("Hero {} missed attack", hero.identifier));
+                    trace.record(
+                        CombatAction::HeroAttack,
+                        &hero.identifier,
+                        MOB_ACTOR,
+                        HitKind::Evaded,
+                        0.0,
+                        encounter_hp,
+                    );
                 }
             }
 
@@ -981,7 +1303,35 @@ impl Team {
         );
     }
 
-    pub fn calculate_healing(&mut self, champion: String, champion_innate_tier: u8) -> Vec<String> {
+    pub fn calculate_healing(
+        &mut self,
+        champion: Option<Champion>,
+        champion_innate_tier: u8,
+    ) -> Vec<String> {
+        // The champion's healing is credited to the champion, and scales with their max hp
+        let healer_index = champion.and_then(|champion| self.get_class_index(champion.to_string()));
+        let champion_heal = match (champion, healer_index) {
+            (Some(_), Some(i)) => self
+                .get_champion_data()
+                .get_round_heal(champion_innate_tier, self.heroes[i].hp_max),
+            _ => 0.0,
+        };
+        // Heroes with round heal skills heal every living hero by a share of their own max hp, credited to them
+        let skill_heals: Vec<(usize, f64)> = (0..self.heroes.len())
+            .filter(|i| self.heroes[*i].hp > 0.0)
+            .flat_map(|i| {
+                self.heroes[i]
+                    .skill_effects
+                    .iter()
+                    .filter_map(move |effect| match effect {
+                        SkillEffect::RoundHeal { hp_fraction } => Some((i, *hp_fraction)),
+                        _ => None,
+                    })
+            })
+            .map(|(i, hp_fraction)| (i, hp_fraction * self.heroes[i].hp_max))
+            .collect();
+        let mut skill_healing_done = vec![0f64; self.heroes.len()];
+        let mut healing_done = 0f64;
         let mut log_queue: Vec<String> = vec![];
         log_queue.push("Calculating Healing".to_string());
         for hero in &mut self.heroes {
@@ -1002,21 +1352,29 @@ impl Team {
 
                 before_hp = hero.hp;
 
-                if champion == "Lilu" {
-                    match champion_innate_tier {
-                        1 => hero.hp = f64::min(hero.hp + 3.0, hero.hp_max),
-                        2 => hero.hp = f64::min(hero.hp + 5.0, hero.hp_max),
-                        3 => hero.hp = f64::min(hero.hp + 10.0, hero.hp_max),
-                        4 => hero.hp = f64::min(hero.hp + 20.0, hero.hp_max),
-                        _ => (),
-                    }
+                if champion_heal > 0.0 {
+                    hero.hp = f64::min(hero.hp + champion_heal, hero.hp_max);
+                    healing_done += hero.hp - before_hp;
                     log_queue.push(f!(
-                        "Hero {} is healed by Lilu for {:.2}",
+                        "Hero {} is healed by {} for {:.2}",
                         hero.identifier,
+                        champion.unwrap(),
                         hero.hp - before_hp
                     ));
                     // running_hp = hero.hp;
                 }
+
+                for (healer_index, heal) in skill_heals.iter() {
+                    before_hp = hero.hp;
+                    hero.hp = f64::min(hero.hp + heal, hero.hp_max);
+                    skill_healing_done[*healer_index] += hero.hp - before_hp;
+                    log_queue.push(f!(
+                        "Hero {} is healed by a skill of the hero at index {} for {:.2}",
+                        hero.identifier,
+                        healer_index,
+                        hero.hp - before_hp
+                    ));
+                }
                 log_queue.push(f!(
                     "Hero {} has new hp of {:.2} total",
                     hero.identifier,
@@ -1024,9 +1382,62 @@ impl Team {
                 ));
             }
         }
+        if let Some(i) = healer_index {
+            self.heroes[i].healing_done += healing_done;
+        }
+        for (hero, skill_healing_done) in self.heroes.iter_mut().zip(skill_healing_done) {
+            hero.healing_done += skill_healing_done;
+        }
         return log_queue;
     }
 
+    /// Roll each living hero's pet, which on a proc strikes the mob through any barrier and heals the hero
+    /// Pets deal no-element damage, so the mob's elemental modifiers never apply to it, only the barrier modifier
+    /// Returns the mob's hp after the pets' damage. Heroes without a pet draw no rolls, so their results are unchanged
+    pub fn calculate_pet_procs(
+        &mut self,
+        mut encounter_hp: f64,
+        barrier_modifier: f64,
+        rng: &mut RoundRolls<'_>,
+        trace: &mut CombatTrace,
+    ) -> (f64, Vec<String>) {
+        let mut log_queue: Vec<String> = vec![];
+        for hero in &mut self.heroes {
+            let pet = match &hero.pet {
+                Some(pet) if hero.hp > 0.0 => pet,
+                _ => continue,
+            };
+            if rng.gen::<f64>() >= pet.proc_chance {
+                continue;
+            }
+            hero.procs += 1;
+            let damage =
+                hero.attack * hero.attack_modifier * pet.proc_damage_percent * barrier_modifier;
+            encounter_hp -= damage;
+            hero.damage_dealt += damage;
+            let before_hp = hero.hp;
+            hero.hp = f64::min(hero.hp + pet.proc_heal_percent * hero.hp_max, hero.hp_max);
+            hero.healing_done += hero.hp - before_hp;
+            log_queue.push(f!(
+                "Pet {} of hero {} acts, dealing {:.2} to mob, bringing hp to {:.2}, and healing for {:.2}",
+                pet.name,
+                hero.identifier,
+                damage,
+                encounter_hp,
+                hero.hp - before_hp
+            ));
+            trace.record(
+                CombatAction::PetAttack,
+                &hero.identifier,
+                MOB_ACTOR,
+                HitKind::Normal,
+                damage,
+                encounter_hp,
+            );
+        }
+        return (encounter_hp, log_queue);
+    }
+
     pub fn check_berserker_activation(&mut self) -> Vec<String> {
         let mut log_queue: Vec<String> = vec![];
         log_queue.push("Checking Berserker Activation".to_string());
@@ -1069,6 +1480,15 @@ impl Team {
         return res;
     }
 
+    /// Returns the hp each hero ended below their max, net of any healing received
+    pub fn get_heroes_hp_lost(&self) -> Vec<f64> {
+        let mut res: Vec<f64> = vec![];
+        for hero in &self.heroes {
+            res.push(hero.hp_max - hero.hp.max(0.0));
+        }
+        return res;
+    }
+
     pub fn get_heroes_hp(&self) -> Vec<f64> {
         let mut res: Vec<f64> = vec![];
         for hero in &self.heroes {
@@ -1077,8 +1497,36 @@ impl Team {
         return res;
     }
 
-    pub fn get_heroes_accuracy_stats(&self) -> (Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>) {
-        let mut res: (Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>) = (vec![], vec![], vec![], vec![]);
+    /// Returns the fraction the party's rest time is reduced by, that of its slowest resting hero
+    pub fn get_rest_time_percent(&self) -> f64 {
+        return self
+            .heroes
+            .iter()
+            .map(|hero| hero.rest_time_percent)
+            .reduce(f64::min)
+            .unwrap_or(0.0);
+    }
+
+    /// Returns the party's average xp bonus
+    pub fn get_xp_percent(&self) -> f64 {
+        return self.heroes.iter().map(|hero| hero.xp_percent).sum::<f64>()
+            / self.heroes.len().max(1) as f64;
+    }
+
+    pub fn get_heroes_xp_percent(&self) -> Vec<f64> {
+        return self.heroes.iter().map(|hero| hero.xp_percent).collect();
+    }
+
+    pub fn get_heroes_evasion(&self) -> Vec<f64> {
+        let mut res: Vec<f64> = vec![];
+        for hero in &self.heroes {
+            res.push(hero.evasion);
+        }
+        return res;
+    }
+
+    pub fn get_heroes_accuracy_stats(&self) -> (Vec<u32>, Vec<u32>, Vec<u32>, Vec<u32>) {
+        let mut res: (Vec<u32>, Vec<u32>, Vec<u32>, Vec<u32>) = (vec![], vec![], vec![], vec![]);
 
         for hero in &self.heroes {
             res.0.push(hero.crits_taken);
@@ -1099,8 +1547,17 @@ impl Team {
         return None;
     }
 
-    pub fn get_champion_info(&self) -> (String, u8) {
-        return (self.champion.to_string(), self.champion_innate_tier);
+    pub fn get_champion_info(&self) -> (Option<Champion>, u8) {
+        return (self.champion, self.champion_innate_tier);
+    }
+
+    /// Returns the data of the team's champion, or data with no effect if the team has no champion
+    pub fn get_champion_data(&self) -> &ChampionData {
+        return self
+            .heroes
+            .iter()
+            .find(|hero| hero.archetype == HeroArchetype::Champion)
+            .map_or(&NO_CHAMPION_DATA, |hero| hero.get_champion_data());
     }
 
     pub fn get_num_archetypes(&self) -> (u8, u8, u8, u8) {
@@ -1119,6 +1576,39 @@ impl Team {
         }
         return res;
     }
+
+    pub fn get_heroes_healing_done(&self) -> Vec<f64> {
+        let mut res: Vec<f64> = vec![];
+        for hero in &self.heroes {
+            res.push(hero.healing_done);
+        }
+        return res;
+    }
+
+    /// Called at the end of each round, so heroes defeated during a round keep the last round they ended alive
+    pub fn record_rounds_survived(&mut self, round: i16) {
+        for hero in &mut self.heroes {
+            if hero.hp > 0.0 {
+                hero.rounds_survived = round;
+            }
+        }
+    }
+
+    pub fn get_heroes_rounds_survived(&self) -> Vec<i16> {
+        let mut res: Vec<i16> = vec![];
+        for hero in &self.heroes {
+            res.push(hero.rounds_survived);
+        }
+        return res;
+    }
+
+    pub fn get_heroes_procs(&self) -> Vec<u32> {
+        let mut res: Vec<u32> = vec![];
+        for hero in &self.heroes {
+            res.push(hero.procs);
+        }
+        return res;
+    }
 }
 
 /// Create a team performing type validation and calculating certain fields
@@ -1126,7 +1616,7 @@ pub fn create_team(
     heroes: Vec<SimHero>,
     booster: Option<BoosterType>,
 ) -> Result<Team, &'static str> {
-    if heroes.len() < 1 {
+    if heroes.is_empty() {
         return Err("cannot form team with < 1 hero");
     }
 
@@ -1134,7 +1624,7 @@ pub fn create_team(
     let mut num_rogues = 0u8;
     let mut num_spellcasters = 0u8;
     let mut num_tricksters = 0u8;
-    let mut champion = "None".to_string();
+    let mut champion: Option<Champion> = None;
     let mut champion_innate_tier = 1u8;
 
     for hero in &heroes {
@@ -1143,14 +1633,8 @@ pub fn create_team(
             HeroArchetype::GreenRogue => num_rogues += 1,
             HeroArchetype::BlueSpellcaster => num_spellcasters += 1,
             HeroArchetype::Champion => {
-                champion = hero.class.to_string();
-                if hero.rank >= 11 {
-                    champion_innate_tier = 4u8;
-                } else if hero.rank >= 7 {
-                    champion_innate_tier = 3u8;
-                } else if hero.rank >= 4 {
-                    champion_innate_tier = 2u8;
-                }
+                champion = Champion::from_str(&hero.class).ok();
+                champion_innate_tier = hero.get_champion_data().calculate_innate_tier(hero.rank);
             }
         }
         if hero.class == "Trickster" {
@@ -1167,6 +1651,7 @@ pub fn create_team(
         num_tricksters,
         champion,
         champion_innate_tier,
+        last_target: None,
     };
 
     return Ok(team);
@@ -1176,6 +1661,7 @@ pub fn create_team(
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SimHero {
     identifier: String,
+    build_signature: String, // Heroes loaded from stats rather than the hero builder only know their class
     class: String,
     archetype: HeroArchetype,
     level: u8,
@@ -1220,12 +1706,58 @@ pub struct SimHero {
     damage_taken_when_hit: f64,
     crit_damage_taken_when_hit: f64,
     damage_dealt: f64,
+    #[serde(skip)]
+    healing_done: f64, // Hp restored to the team by this hero, not serialized so build hashes are unaffected
     // skills: Vec<Skill>,
     // accuracy_tracking
-    crits_taken: u8,
-    crits_dealt: u8,
-    dodges: u8,
-    attacks_missed: u8,
+    crits_taken: u32,
+    crits_dealt: u32,
+    dodges: u32,
+    attacks_missed: u32,
+    #[serde(skip)]
+    rounds_survived: i16, // The last round this hero ended alive, not serialized so build hashes are unaffected
+    #[serde(skip)]
+    procs: u32, // Chance and class effects this hero triggered: fatal blows survived, lord saves, guaranteed crits gained from evading, and pet procs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pet: Option<Pet>, // Only builder heroes with a pet skill that acts in combat have one, so build hashes of other heroes are unaffected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    skill_effects: Vec<SkillEffect>, // The combat effects of the hero's skills, empty for most heroes so their build hashes are unaffected
+    #[serde(skip)]
+    revive_used: bool, // Whether the hero has revived an ally this fight
+    #[serde(default, skip_serializing_if = "is_zero")]
+    rest_time_percent: f64, // The fraction rest time is reduced by, from skills. 0 for most heroes so their build hashes are unaffected
+    #[serde(default, skip_serializing_if = "is_zero")]
+    xp_percent: f64, // The extra fraction of quest xp earned, from skills
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    champion_data: Option<ChampionData>, // The ability of a champion, set when the hero is built from the game data. None for other heroes
+}
+
+fn is_zero(value: &f64) -> bool {
+    return *value == 0.0;
+}
+
+/// A hero's pet acting in combat, which may strike the mob and heal the hero at the end of each round the hero is alive
+/// Built from the pet skill of the familiar the hero has equipped, see PetSkill::create_pet
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Pet {
+    name: String,
+    proc_chance: f64,
+    proc_damage_percent: f64, // Share of the hero's attack
+    proc_heal_percent: f64,   // Share of the hero's max hp
+}
+
+pub fn create_pet(
+    name: String,
+    proc_chance: f64,
+    proc_damage_percent: f64,
+    proc_heal_percent: f64,
+) -> Pet {
+    return Pet {
+        name,
+        proc_chance,
+        proc_damage_percent,
+        proc_heal_percent,
+    };
 }
 
 impl SimHero {
@@ -1233,6 +1765,75 @@ impl SimHero {
         return self.identifier.to_string();
     }
 
+    pub fn get_build_signature(&self) -> String {
+        return self.build_signature.to_string();
+    }
+
+    pub fn get_class(&self) -> String {
+        return self.class.to_string();
+    }
+
+    pub fn get_hp_max(&self) -> f64 {
+        return self.hp_max;
+    }
+
+    /// Returns the stats that differ between builds before any fight modifiers, with attack including its modifier
+    pub fn get_build_stats(&self) -> [(&'static str, f64); 8] {
+        return [
+            ("HP", self.hp_max),
+            ("ATK", self.attack * self.attack_modifier),
+            ("DEF", self.defense),
+            ("Threat", f64::from(self.threat)),
+            ("Crit Chance", self.critical_chance),
+            ("Crit Multiplier", self.critical_multiplier),
+            ("Evasion", self.evasion),
+            ("Survive Fatal Blow", self.survive_fatal_blow_chance),
+        ];
+    }
+
+    /// Set the hp the hero starts its next fight with as a fraction of its max hp, capped at full hp
+    pub fn set_hp_percent(&mut self, hp_percent: f64) {
+        self.hp = self.hp_max * hp_percent.clamp(0.0, 1.0);
+    }
+
+    pub fn set_pet(&mut self, pet: Option<Pet>) {
+        self.pet = pet;
+    }
+
+    pub fn set_skill_effects(&mut self, skill_effects: Vec<SkillEffect>) {
+        self.skill_effects = skill_effects;
+    }
+
+    pub fn set_quest_bonuses(&mut self, rest_time_percent: f64, xp_percent: f64) {
+        self.rest_time_percent = rest_time_percent;
+        self.xp_percent = xp_percent;
+    }
+
+    pub fn set_champion_data(&mut self, champion_data: Option<ChampionData>) {
+        self.champion_data = champion_data;
+    }
+
+    /// Returns the champion's data, or data with no effect if the hero isn't a champion or was built without it
+    pub fn get_champion_data(&self) -> &ChampionData {
+        return self.champion_data.as_ref().unwrap_or(&NO_CHAMPION_DATA);
+    }
+
+    pub fn get_rest_time_percent(&self) -> f64 {
+        return self.rest_time_percent;
+    }
+
+    pub fn get_xp_percent(&self) -> f64 {
+        return self.xp_percent;
+    }
+
+    pub fn set_identifier(&mut self, identifier: String) {
+        self.identifier = identifier;
+    }
+
+    pub fn set_build_signature(&mut self, build_signature: String) {
+        self.build_signature = build_signature;
+    }
+
     fn modify_for_extreme_encounter(&mut self) {
         self.evasion -= 0.2;
     }
@@ -1240,32 +1841,60 @@ impl SimHero {
         self.defense = (self.defense / self.defense_modifier)
             * (self.defense_modifier + 0.2 * f64::from(self.mundra_qty));
     }
-    pub fn round_floats_for_display(&self) -> SimHero {
-        let mut h2 = self.clone();
-        h2.hp = round_to_2(h2.hp);
-        h2.hp_max = round_to_2(h2.hp_max);
-        h2.hp_regen = round_to_2(h2.hp_regen);
-        h2.attack = round_to_2(h2.attack);
-        h2.defense = round_to_2(h2.defense);
-        h2.critical_chance = round_to_2(h2.critical_chance);
-        h2.critical_multiplier = round_to_2(h2.critical_multiplier);
-        h2.evasion = round_to_2(h2.evasion);
-        h2.attack_modifier = round_to_2(h2.attack_modifier);
-        h2.defense_modifier = round_to_2(h2.defense_modifier);
-        h2.extreme_crit_bonus = round_to_2(h2.extreme_crit_bonus);
-        h2.survive_chance = round_to_2(h2.survive_chance);
-        h2.consecutive_crit_bonus = round_to_2(h2.consecutive_crit_bonus);
-        h2.jarl_hp_stage_1 = round_to_2(h2.jarl_hp_stage_1);
-        h2.jarl_hp_stage_2 = round_to_2(h2.jarl_hp_stage_2);
-        h2.jarl_hp_stage_3 = round_to_2(h2.jarl_hp_stage_3);
-        h2.ninja_bonus = round_to_2(h2.ninja_bonus);
-        h2.ninja_evasion = round_to_2(h2.ninja_evasion);
-        h2.evasion_cap = round_to_2(h2.evasion_cap);
-        h2.hemma_bonus = round_to_2(h2.hemma_bonus);
-        h2.damage_taken_when_hit = round_to_2(h2.damage_taken_when_hit);
-        h2.crit_damage_taken_when_hit = round_to_2(h2.crit_damage_taken_when_hit);
-        h2.damage_dealt = round_to_2(h2.damage_dealt);
-        return h2;
+    pub fn fmt_stats(&self) -> StatsView {
+        return create_stats_view(f!("{} ({} {})", self.identifier, self.class, self.level))
+            .text("Rank", self.rank)
+            .text("Innate Tier", self.innate_tier)
+            .float("HP", self.hp)
+            .float("HP Max", self.hp_max)
+            .float("HP Regen", self.hp_regen)
+            .float("Attack", self.attack)
+            .float("Defense", self.defense)
+            .text("Threat", self.threat)
+            .float("Crit Chance", self.critical_chance)
+            .float("Crit Multiplier", self.critical_multiplier)
+            .float("Evasion", self.evasion)
+            .float("Evasion Cap", self.evasion_cap)
+            .float("Survive Fatal Blow", self.survive_fatal_blow_chance)
+            .text("Element", f!("{} {}", self.element_type, self.element_qty))
+            .float("Attack Modifier", self.attack_modifier)
+            .float("Defense Modifier", self.defense_modifier)
+            .float("Damage Taken When Hit", self.damage_taken_when_hit)
+            .float(
+                "Crit Damage Taken When Hit",
+                self.crit_damage_taken_when_hit,
+            )
+            .float("Damage Dealt", self.damage_dealt)
+            .float("Healing Done", self.healing_done);
+    }
+
+    /// Extends the stats view with the build and spirit information used to derive the stats
+    pub fn fmt_inspect(&self) -> StatsView {
+        return self
+            .fmt_stats()
+            .text("Archetype", f!("{:?}", self.archetype))
+            .text("Build Signature", &self.build_signature)
+            .text("Armadillo Qty", self.armadillo_qty)
+            .text("Lizard Qty", self.lizard_qty)
+            .text("Shark Qty", self.shark_qty)
+            .text("Dinosaur Qty", self.dinosaur_qty)
+            .text("Mundra Qty", self.mundra_qty)
+            .float("Extreme Crit Bonus", self.extreme_crit_bonus);
+    }
+}
+
+impl std::fmt::Display for SimHero {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.fmt_stats())
+    }
+}
+
+impl std::fmt::Display for Team {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        for view in self.fmt_stats() {
+            write!(f, "{}", view)?;
+        }
+        return Ok(());
     }
 }
 
@@ -1299,7 +1928,65 @@ impl From<SimHero> for SimHeroInput {
     }
 }
 
+/// Returns the archetype of a class or champion known by name, or None if the class is unknown
+pub fn get_class_archetype(class: &str) -> Option<HeroArchetype> {
+    let red_list: [&str; 12] = [
+        "Soldier",
+        "Mercenary",
+        "Barbarian",
+        "Chieftain",
+        "Knight",
+        "Lord",
+        "Ranger",
+        "Warden",
+        "Samurai",
+        "Daimyo",
+        "Berserker",
+        "Jarl",
+    ];
+    let green_list: [&str; 12] = [
+        "Thief",
+        "Trickster",
+        "Monk",
+        "Grandmaster",
+        "Musketeer",
+        "Conquistador",
+        "Wanderer",
+        "Pathfinder",
+        "Ninja",
+        "Sensei",
+        "Dancer",
+        "Acrobat",
+    ];
+    let blue_list: [&str; 12] = [
+        "Mage",
+        "Archmage",
+        "Cleric",
+        "Bishop",
+        "Druid",
+        "Arch Druid",
+        "Sorcerer",
+        "Warlock",
+        "Spellblade",
+        "Spellknight",
+        "Geomancer",
+        "Astramancer",
+    ];
+
+    if red_list.contains(&class) {
+        return Some(HeroArchetype::RedFighter);
+    } else if green_list.contains(&class) {
+        return Some(HeroArchetype::GreenRogue);
+    } else if blue_list.contains(&class) {
+        return Some(HeroArchetype::BlueSpellcaster);
+    } else if Champion::from_str(class).is_ok() {
+        return Some(HeroArchetype::Champion);
+    }
+    return None;
+}
+
 /// Create a hero performing type validation and calculating certain fields
+/// The archetype is only used for classes not known by name, such as scaffolded classes
 pub fn create_sim_hero(
     identifier: String,
     class: String,
@@ -1324,79 +2011,15 @@ pub fn create_sim_hero(
     mundra_qty: u8,
     attack_modifier: f64,
     defense_modifier: f64,
+    archetype: Option<HeroArchetype>,
 ) -> Result<SimHero, &'static str> {
     let atk_mod = 1.0 + attack_modifier;
     let def_mod = 1.0 + defense_modifier;
 
-    let archetype: HeroArchetype;
-    let red_list: [String; 12] = [
-        String::from("Soldier"),
-        String::from("Mercenary"),
-        String::from("Barbarian"),
-        String::from("Chieftain"),
-        String::from("Knight"),
-        String::from("Lord"),
-        String::from("Ranger"),
-        String::from("Warden"),
-        String::from("Samurai"),
-        String::from("Daimyo"),
-        String::from("Berserker"),
-        String::from("Jarl"),
-    ];
-    let green_list: [String; 12] = [
-        String::from("Thief"),
-        String::from("Trickster"),
-        String::from("Monk"),
-        String::from("Grandmaster"),
-        String::from("Musketeer"),
-        String::from("Conquistador"),
-        String::from("Wanderer"),
-        String::from("Pathfinder"),
-        String::from("Ninja"),
-        String::from("Sensei"),
-        String::from("Dancer"),
-        String::from("Acrobat"),
-    ];
-    let blue_list: [String; 12] = [
-        String::from("Mage"),
-        String::from("Archmage"),
-        String::from("Cleric"),
-        String::from("Bishop"),
-        String::from("Druid"),
-        String::from("Arch Druid"),
-        String::from("Sorcerer"),
-        String::from("Warlock"),
-        String::from("Spellblade"),
-        String::from("Spellknight"),
-        String::from("Geomancer"),
-        String::from("Astramancer"),
-    ];
-    let champion_list: [String; 12] = [
-        String::from("Argon"),
-        String::from("Lilu"),
-        String::from("Polonia"),
-        String::from("Yami"),
-        String::from("Rudo"),
-        String::from("Sia"),
-        String::from("Donovan"),
-        String::from("Ashley"),
-        String::from("Hemma"),
-        String::from("Aang"),
-        String::from("Sokka"),
-        String::from("King Reinholdt"),
-    ];
-
-    if red_list.contains(&class) {
-        archetype = HeroArchetype::RedFighter;
-    } else if green_list.contains(&class) {
-        archetype = HeroArchetype::GreenRogue;
-    } else if blue_list.contains(&class) {
-        archetype = HeroArchetype::BlueSpellcaster;
-    } else if champion_list.contains(&class) {
-        archetype = HeroArchetype::Champion;
-    } else {
-        return Err("Unknown Class, Could Not Create Hero");
-    }
+    let archetype = match get_class_archetype(&class).or(archetype) {
+        Some(archetype) => archetype,
+        None => return Err("Unknown Class, Could Not Create Hero"),
+    };
 
     let element_type: ElementType = ElementType::from_str(element_type_string.as_str()).unwrap();
     // match element_type_string.as_str() {
@@ -1412,6 +2035,7 @@ pub fn create_sim_hero(
 
     let mut hero = SimHero {
         identifier,
+        build_signature: f!("{}|-|-|-|-", class),
         class,
         archetype,
         level,
@@ -1454,10 +2078,19 @@ pub fn create_sim_hero(
         damage_taken_when_hit: 0.0,
         crit_damage_taken_when_hit: 0.0,
         damage_dealt: 0.0,
+        healing_done: 0.0,
         crits_taken: 0,
         crits_dealt: 0,
         dodges: 0,
         attacks_missed: 0,
+        rounds_survived: 0,
+        procs: 0,
+        pet: None,
+        skill_effects: vec![],
+        revive_used: false,
+        rest_time_percent: 0.0,
+        xp_percent: 0.0,
+        champion_data: None,
     };
 
     if hero.rank == 4 {
@@ -1496,3 +2129,101 @@ impl std::fmt::Display for StringVec {
         write!(f, "[{}]", comma_separated)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        combat_trace::create_combat_trace,
+        rng::{create_round_rolls, create_sim_rng, RngConfig},
+        test_data::create_test_party,
+    };
+    use rand::RngCore;
+
+    fn load_team(identifiers: &[&str]) -> Team {
+        return create_team(create_test_party(identifiers), None).unwrap();
+    }
+
+    #[test]
+    fn threat_weighted_targeting_never_picks_a_zero_threat_hero() {
+        let mut team = load_team(&["Hero_1", "Hero_2", "Hero_3", "Hero_4", "Hero_5"]);
+        team.heroes[1].threat = 0;
+
+        let mut targeted = [0u32; 5];
+        let mut sim_rng = create_sim_rng(RngConfig {
+            seed: Some(7),
+            ..Default::default()
+        });
+        let mut rolls = create_round_rolls(&mut sim_rng, 64);
+        let target_chance_heroes = team.calculate_targeting_chances();
+        for _ in 0..10000 {
+            let target = team.select_target(
+                TargetingModel::ThreatWeighted,
+                target_chance_heroes,
+                &mut rolls,
+            );
+            targeted[target] += 1;
+        }
+
+        assert_eq!(targeted[1], 0);
+        for (i, count) in targeted.iter().enumerate() {
+            if i != 1 {
+                assert!(*count > 0, "hero {} was never targeted", i);
+            }
+        }
+    }
+
+    #[test]
+    fn survive_chance_uses_the_survive_fatal_blow_stat_and_falls_back_to_armadillos() {
+        let mut team = load_team(&["Hero_1", "Hero_2", "Hero_3"]);
+        team.heroes[0].survive_fatal_blow_chance = 0.4;
+        team.heroes[0].armadillo_qty = 0;
+        team.heroes[1].survive_fatal_blow_chance = 0.0;
+        team.heroes[1].armadillo_qty = 2;
+        team.heroes[2].survive_fatal_blow_chance = 1.5;
+        team.initialize_survive_chance_hemma_guaranteed_crit_and_berserker_stage();
+
+        assert_eq!(team.heroes[0].survive_chance, 0.4);
+        assert!((team.heroes[1].survive_chance - 0.3).abs() < 1e-9);
+        assert_eq!(team.heroes[2].survive_chance, 1.0);
+    }
+
+    #[test]
+    fn pet_procs_strike_the_mob_cap_healing_and_skip_heroes_without_pets() {
+        let mut team = load_team(&["Hero_1", "Hero_2"]);
+        team.heroes[0].pet = Some(create_pet(String::from("Test Pet"), 1.0, 0.5, 0.5));
+        team.heroes[0].hp = team.heroes[0].hp_max - 1.0;
+        let expected_damage = team.heroes[0].attack * team.heroes[0].attack_modifier * 0.5 * 0.8;
+
+        let mut sim_rng = create_sim_rng(RngConfig {
+            seed: Some(7),
+            ..Default::default()
+        });
+        let mut rolls = create_round_rolls(&mut sim_rng, 64);
+        let mut trace = create_combat_trace(false);
+        let (encounter_hp, _) = team.calculate_pet_procs(1000.0, 0.8, &mut rolls, &mut trace);
+
+        assert!(expected_damage > 0.0);
+        assert!((encounter_hp - (1000.0 - expected_damage)).abs() < 1e-9);
+        assert_eq!(team.heroes[0].hp, team.heroes[0].hp_max);
+        assert_eq!(team.heroes[0].procs, 1);
+        assert_eq!(team.heroes[1].procs, 0);
+
+        let mut petless_team = load_team(&["Hero_1", "Hero_2"]);
+        let mut sim_rng = create_sim_rng(RngConfig {
+            seed: Some(7),
+            ..Default::default()
+        });
+        let mut rolls = create_round_rolls(&mut sim_rng, 64);
+        let mut fresh_sim_rng = create_sim_rng(RngConfig {
+            seed: Some(7),
+            ..Default::default()
+        });
+        let mut fresh_rolls = create_round_rolls(&mut fresh_sim_rng, 64);
+        let (encounter_hp, _) =
+            petless_team.calculate_pet_procs(1000.0, 0.8, &mut rolls, &mut trace);
+
+        assert_eq!(encounter_hp, 1000.0);
+        assert_eq!(rolls.next_u64(), fresh_rolls.next_u64());
+    }
+}
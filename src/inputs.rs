@@ -1,16 +1,24 @@
 use serde::{Deserialize, Serialize};
 
-use crate::decimals::{_round_array_of_len_4_to_2, round_to_2};
-use crate::equipment::{Blueprint, ElementType};
-use crate::hero_builder::{create_hero, Hero, HeroClass};
-use crate::skills::{HeroSkill, InnateSkill};
+use crate::abilities::{create_enemy_ability, create_enrage, EnemyAbility, Enrage};
+use crate::champions::{Champion, ChampionData};
+use crate::equipment::{Blueprint, ElementType, EnchantEligibility};
+use crate::formatting::serialize_rounded;
+use crate::hero_builder::{
+    _create_hero_class, create_hero, generate_stat_curve, Element, Hero, HeroBuilderError,
+    HeroClass, SeedValues, Spirit, CLASS_GROWTH_BREAKPOINTS, MAX_HERO_LEVEL,
+};
+use crate::quests::{create_quest, create_quest_line, Quest, QuestLine};
+use crate::skills::{is_empty_skill, PetSkill, SkillEffect};
+use crate::studies::HeroBuilderInformation;
 
 use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
 use std::str::FromStr;
 
-use super::heroes::{create_sim_hero, SimHero};
+use super::heroes::{create_sim_hero, HeroArchetype, SimHero};
 
-use super::dungeons::{create_dungeon, Dungeon};
+use super::dungeons::{create_dungeon, Dungeon, EntryCost};
 
 /// Defines HeroeInput format for deserialization from CSV
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -20,14 +28,22 @@ pub struct SimHeroInput {
     level: u8,
     rank: u8,
     innate_tier: u8,
+    #[serde(serialize_with = "serialize_rounded")]
     hp: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hp_regen: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     attack: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     defense: f64,
     threat: u16,
+    #[serde(serialize_with = "serialize_rounded")]
     critical_chance: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     critical_multiplier: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     evasion: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     survive_fatal_blow_chance: f64,
     element_qty: u16,
     element_type: String,
@@ -36,30 +52,23 @@ pub struct SimHeroInput {
     shark_qty: u8,
     dinosaur_qty: u8,
     mundra_qty: u8,
+    #[serde(serialize_with = "serialize_rounded")]
     attack_modifier: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     defense_modifier: f64,
 }
 
 impl SimHeroInput {
-    pub fn _round_floats_for_display(&self) -> SimHeroInput {
-        let mut hi2 = self.clone();
-        hi2.hp = round_to_2(hi2.hp);
-        hi2.hp_regen = round_to_2(hi2.hp_regen);
-        hi2.attack = round_to_2(hi2.attack);
-        hi2.defense = round_to_2(hi2.defense);
-        hi2.critical_chance = round_to_2(hi2.critical_chance);
-        hi2.critical_multiplier = round_to_2(hi2.critical_multiplier);
-        hi2.evasion = round_to_2(hi2.evasion);
-        hi2.survive_fatal_blow_chance = round_to_2(hi2.survive_fatal_blow_chance);
-        hi2.attack_modifier = round_to_2(hi2.attack_modifier);
-        hi2.defense_modifier = round_to_2(hi2.defense_modifier);
-        return hi2;
-    }
-}
-
-impl From<SimHeroInput> for SimHero {
     /// Create a hero from the input object performing type validation and calculating certain fields
-    fn from(item: SimHeroInput) -> Self {
+    /// Classes not known by name, such as scaffolded classes, take their archetype from the class data
+    pub fn into_sim_hero(
+        self,
+        hero_classes: &HashMap<String, HeroClass>,
+    ) -> Result<SimHero, &'static str> {
+        let item = self;
+        let archetype = hero_classes
+            .get(&item.class)
+            .and_then(|hero_class| hero_class.get_archetype());
         return create_sim_hero(
             item.identifier,
             item.class,
@@ -84,8 +93,8 @@ impl From<SimHeroInput> for SimHero {
             item.mundra_qty,
             item.attack_modifier,
             item.defense_modifier,
-        )
-        .unwrap();
+            archetype,
+        );
     }
 }
 
@@ -141,12 +150,21 @@ pub fn create_sim_hero_input(
     };
 }
 
-pub fn load_sim_heroes_from_csv(path: String) -> Vec<SimHero> {
+/// Champions are given their ability from the game data, since heroes loaded from stats only know their class
+pub fn load_sim_heroes_from_csv(
+    path: String,
+    hero_builder_information: &HeroBuilderInformation,
+) -> Vec<SimHero> {
     let mut heroes: Vec<SimHero> = vec![];
     let mut reader = csv::Reader::from_path(path).unwrap();
     for result in reader.deserialize() {
         let hero_in: SimHeroInput = result.unwrap();
-        heroes.push(SimHero::from(hero_in));
+        let mut sim_hero = hero_in
+            .into_sim_hero(&hero_builder_information.hero_classes)
+            .unwrap();
+        sim_hero
+            .set_champion_data(hero_builder_information.find_champion_data(&sim_hero.get_class()));
+        heroes.push(sim_hero);
     }
     return heroes;
 }
@@ -165,7 +183,7 @@ pub fn _save_sim_heroes_to_csv(path: String, heroes: Vec<SimHero>) -> Result<(),
         .from_writer(writer);
 
     for hero in heroes {
-        wtr.serialize(SimHeroInput::from(hero)._round_floats_for_display())?;
+        wtr.serialize(SimHeroInput::from(hero))?;
     }
 
     wtr.flush()?;
@@ -185,46 +203,143 @@ pub struct DungeonInput {
     minimum_power: [u32; 4],
     barrier_types: [String; 3],
     barrier_healths: [f64; 4],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    boss: Option<String>, // A boss from bosses.yaml, replacing every boss field below when given
+    #[serde(default)]
     boss_hp: [f64; 4],
+    #[serde(default)]
     boss_damage: [f64; 4],
+    #[serde(default)]
     boss_defense_cap: [f64; 4],
+    #[serde(default)]
     boss_aoe_damage: [f64; 4],
+    #[serde(default)]
     boss_aoe_chance: [f64; 4],
+    #[serde(default)]
     boss_minimum_power: [u32; 4],
+    #[serde(default)]
     boss_barrier_type: String,
+    #[serde(default)]
     boss_barrier_healths: [f64; 4],
+    #[serde(default)]
+    elemental_modifiers: HashMap<String, f64>,
+    #[serde(default)]
+    boss_elemental_modifiers: HashMap<String, f64>,
+    #[serde(default)]
+    abilities: Vec<EnemyAbility>,
+    #[serde(default)]
+    boss_abilities: Vec<EnemyAbility>,
+    #[serde(default)]
+    boss_enrage: Option<Enrage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    entry_cost: Option<EntryCost>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pack_sizes: Option<[u8; 4]>, // The enemies fought at once on each difficulty, with hp, damage, and aoe given per enemy. 1 when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    boss_pack_sizes: Option<[u8; 4]>, // As pack_sizes, for the boss difficulties. Not replaced by a named boss
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_pack_sizes: Option<[u8; 4]>, // The most enemies fought at once on each difficulty, each encounter drawing its pack size between pack_sizes and this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    miniboss_chances: Option<[f64; 4]>, // The percent chance of a random miniboss on each difficulty. 50 when absent
 }
 
 impl DungeonInput {
-    pub fn _round_floats_for_display(&self) -> DungeonInput {
-        let mut di2 = self.clone();
-        di2.hp = _round_array_of_len_4_to_2(di2.hp);
-        di2.damage = _round_array_of_len_4_to_2(di2.damage);
-        di2.defense_cap = _round_array_of_len_4_to_2(di2.defense_cap);
-        di2.aoe_damage = _round_array_of_len_4_to_2(di2.aoe_damage);
-        di2.aoe_chance = _round_array_of_len_4_to_2(di2.aoe_chance);
-        di2.barrier_healths = _round_array_of_len_4_to_2(di2.barrier_healths);
+    /// Replace the boss fields with those of the named boss, if the dungeon names one
+    pub fn apply_boss(&mut self, bosses: &HashMap<String, BossInput>) -> Result<(), &'static str> {
+        let boss = match &self.boss {
+            Some(name) => match bosses.get(name) {
+                Some(boss) => boss.clone(),
+                None => return Err("boss not found in bosses.yaml"),
+            },
+            None => return Ok(()),
+        };
+        self.boss_hp = boss.hp;
+        self.boss_damage = boss.damage;
+        self.boss_defense_cap = boss.defense_cap;
+        self.boss_aoe_damage = boss.aoe_damage;
+        self.boss_aoe_chance = boss.aoe_chance;
+        self.boss_minimum_power = boss.minimum_power;
+        self.boss_barrier_type = boss.barrier_type;
+        self.boss_barrier_healths = boss.barrier_healths;
+        self.boss_elemental_modifiers = boss.elemental_modifiers;
+        self.boss_abilities = boss.abilities;
+        self.boss_enrage = boss.enrage;
+        return Ok(());
+    }
+}
 
-        di2.boss_hp = _round_array_of_len_4_to_2(di2.boss_hp);
-        di2.boss_damage = _round_array_of_len_4_to_2(di2.boss_damage);
-        di2.boss_defense_cap = _round_array_of_len_4_to_2(di2.boss_defense_cap);
-        di2.boss_aoe_damage = _round_array_of_len_4_to_2(di2.boss_aoe_damage);
-        di2.boss_aoe_chance = _round_array_of_len_4_to_2(di2.boss_aoe_chance);
-        di2.boss_barrier_healths = _round_array_of_len_4_to_2(di2.boss_barrier_healths);
+/// Defines BossInput format for deserialization from YAML, a boss that any dungeon can reference by name
+/// Stats are listed per boss difficulty, from Boss Easy to Boss Extreme
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BossInput {
+    tier: u8, // The boss's tier in game, for reference
+    hp: [f64; 4],
+    damage: [f64; 4],
+    defense_cap: [f64; 4],
+    aoe_damage: [f64; 4],
+    aoe_chance: [f64; 4],
+    minimum_power: [u32; 4],
+    barrier_type: String,
+    barrier_healths: [f64; 4],
+    #[serde(default)]
+    elemental_modifiers: HashMap<String, f64>,
+    #[serde(default)]
+    abilities: Vec<EnemyAbility>,
+    #[serde(default)]
+    enrage: Option<Enrage>,
+}
 
-        return di2;
+impl BossInput {
+    pub fn _get_tier(&self) -> u8 {
+        return self.tier;
+    }
+
+    /// Check the boss's elements and abilities, which are otherwise only checked in the dungeons that reference it
+    pub fn validate(&self) -> Result<(), &'static str> {
+        for element in std::iter::once(&self.barrier_type).chain(self.elemental_modifiers.keys()) {
+            if ElementType::from_str(element).is_err() {
+                return Err("unknown element type");
+            }
+        }
+        for ability in self.abilities.iter() {
+            create_enemy_ability(
+                ability.get_name(),
+                ability.get_trigger(),
+                ability.get_effect(),
+            )?;
+        }
+        if let Some(enrage) = self.enrage {
+            create_enrage(enrage.get_rounds(), enrage.get_effect())?;
+        }
+        return Ok(());
     }
 }
 
-impl From<DungeonInput> for Dungeon {
-    /// Create a hero from the input object performing type validation and calculating certain fields
-    fn from(item: DungeonInput) -> Self {
+fn parse_element_type(element: &str) -> Result<ElementType, String> {
+    return ElementType::from_str(element).map_err(|_| f!("Unknown element type {}", element));
+}
+
+impl TryFrom<DungeonInput> for Dungeon {
+    type Error = String;
+
+    /// Create a dungeon from the input object performing type validation and calculating certain fields
+    fn try_from(item: DungeonInput) -> Result<Self, Self::Error> {
         let mut barrier_types: [ElementType; 3] =
             [ElementType::Any, ElementType::Any, ElementType::Any];
         for (i, bt) in item.barrier_types.iter().enumerate() {
-            barrier_types[i] = ElementType::from_str(bt.as_str()).unwrap();
+            barrier_types[i] = parse_element_type(bt)?;
         }
-        let boss_barrier_type = ElementType::from_str(item.boss_barrier_type.as_str()).unwrap();
+        let boss_barrier_type = parse_element_type(&item.boss_barrier_type)?;
+        let elemental_modifiers = item
+            .elemental_modifiers
+            .iter()
+            .map(|(element, modifier)| Ok((parse_element_type(element)?, *modifier)))
+            .collect::<Result<HashMap<ElementType, f64>, String>>()?;
+        let boss_elemental_modifiers = item
+            .boss_elemental_modifiers
+            .iter()
+            .map(|(element, modifier)| Ok((parse_element_type(element)?, *modifier)))
+            .collect::<Result<HashMap<ElementType, f64>, String>>()?;
         return create_dungeon(
             item.zone,
             item.max_num_heroes,
@@ -244,8 +359,18 @@ impl From<DungeonInput> for Dungeon {
             item.boss_minimum_power,
             boss_barrier_type,
             item.boss_barrier_healths,
+            elemental_modifiers,
+            boss_elemental_modifiers,
+            item.abilities,
+            item.boss_abilities,
+            item.boss_enrage,
+            item.entry_cost,
+            item.pack_sizes,
+            item.boss_pack_sizes,
+            item.max_pack_sizes,
+            item.miniboss_chances,
         )
-        .unwrap();
+        .map_err(|e| e.to_string());
     }
 }
 
@@ -268,6 +393,16 @@ pub fn create_dungeon_input(
     boss_minimum_power: [u32; 4],
     boss_barrier_type: String,
     boss_barrier_healths: [f64; 4],
+    elemental_modifiers: HashMap<String, f64>,
+    boss_elemental_modifiers: HashMap<String, f64>,
+    abilities: Vec<EnemyAbility>,
+    boss_abilities: Vec<EnemyAbility>,
+    boss_enrage: Option<Enrage>,
+    entry_cost: Option<EntryCost>,
+    pack_sizes: Option<[u8; 4]>,
+    boss_pack_sizes: Option<[u8; 4]>,
+    max_pack_sizes: Option<[u8; 4]>,
+    miniboss_chances: Option<[f64; 4]>,
 ) -> DungeonInput {
     return DungeonInput {
         zone,
@@ -280,6 +415,7 @@ pub fn create_dungeon_input(
         minimum_power,
         barrier_types,
         barrier_healths,
+        boss: None,
         boss_hp,
         boss_damage,
         boss_defense_cap,
@@ -288,18 +424,217 @@ pub fn create_dungeon_input(
         boss_minimum_power,
         boss_barrier_type,
         boss_barrier_healths,
+        elemental_modifiers,
+        boss_elemental_modifiers,
+        abilities,
+        boss_abilities,
+        boss_enrage,
+        entry_cost,
+        pack_sizes,
+        boss_pack_sizes,
+        max_pack_sizes,
+        miniboss_chances,
     };
 }
 
-pub fn load_dungeons_from_yaml(path: String) -> HashMap<String, Dungeon> {
+/// Load every dungeon, taking the boss fields of dungeons that name a boss from the given bosses
+pub fn load_dungeons_from_yaml(
+    reader: impl Read,
+    bosses: &HashMap<String, BossInput>,
+) -> Result<HashMap<String, Dungeon>, String> {
     let mut dungeons: HashMap<String, Dungeon> = Default::default();
-    let reader = std::fs::File::open(path).unwrap();
-    for (dungeon_key, dungeon_in) in
-        serde_yaml::from_reader::<std::fs::File, HashMap<String, DungeonInput>>(reader).unwrap()
+    for (dungeon_key, mut dungeon_in) in
+        serde_yaml::from_reader::<_, HashMap<String, DungeonInput>>(reader)
+            .map_err(|e| e.to_string())?
     {
-        dungeons.insert(dungeon_key, Dungeon::from(dungeon_in));
+        dungeon_in
+            .apply_boss(bosses)
+            .map_err(|e| f!("{}: {}", dungeon_key, e))?;
+        let dungeon = Dungeon::try_from(dungeon_in).map_err(|e| f!("{}: {}", dungeon_key, e))?;
+        dungeons.insert(dungeon_key, dungeon);
     }
-    return dungeons;
+    return Ok(dungeons);
+}
+
+/// Load the bosses that dungeons can reference by name, keyed by boss name
+pub fn load_bosses_from_yaml(reader: impl Read) -> Result<HashMap<String, BossInput>, String> {
+    return serde_yaml::from_reader::<_, HashMap<String, BossInput>>(reader)
+        .map_err(|e| e.to_string());
+}
+
+/// The difficulties of a dungeons csv, in the order of the dungeon's stat arrays
+const DUNGEON_DIFFICULTIES: [&str; 8] = [
+    "Easy",
+    "Medium",
+    "Hard",
+    "Extreme",
+    "Boss Easy",
+    "Boss Medium",
+    "Boss Hard",
+    "Boss Extreme",
+];
+
+/// Defines DungeonTierInput format for deserialization from CSV, one row per dungeon difficulty as laid out in the community dungeon spreadsheet
+/// Barrier elements are separated by "/", with three on the Extreme row, one on the Boss Extreme row, and none on the others
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DungeonTierInput {
+    #[serde(rename = "Zone")]
+    zone: String,
+    #[serde(rename = "Difficulty")]
+    difficulty: String, // One of DUNGEON_DIFFICULTIES
+    #[serde(rename = "Max Heroes")]
+    max_num_heroes: u8,
+    #[serde(rename = "HP")]
+    hp: f64,
+    #[serde(rename = "ATK")]
+    damage: f64,
+    #[serde(rename = "DEF Cap")]
+    defense_cap: f64,
+    #[serde(rename = "AOE ATK")]
+    aoe_damage: f64,
+    #[serde(rename = "AOE Chance")]
+    aoe_chance: f64,
+    #[serde(rename = "Min Power")]
+    minimum_power: u32,
+    #[serde(rename = "Barrier Elements")]
+    barrier_elements: String,
+    #[serde(rename = "Barrier HP")]
+    barrier_hp: Option<f64>, // 0 when blank
+    #[serde(rename = "Miniboss Chance")]
+    miniboss_chance: Option<f64>, // Percent, 50 when blank. Bosses have no minibosses, so blank on boss rows
+    #[serde(rename = "Min Mobs")]
+    min_pack_size: Option<u8>, // 1 when blank
+    #[serde(rename = "Max Mobs")]
+    max_pack_size: Option<u8>, // The min when blank. Boss rows can't have a range
+}
+
+/// Read a dungeons csv into a dungeon input per zone, each needing a row for every difficulty
+/// Enemy abilities, enrages, elemental modifiers, and entry costs aren't in the spreadsheet, so dungeons with them belong in dungeons.yaml
+pub fn load_dungeon_inputs_from_csv(
+    reader: impl Read,
+) -> Result<BTreeMap<String, DungeonInput>, String> {
+    let mut zone_tiers: BTreeMap<String, [Option<DungeonTierInput>; 8]> = BTreeMap::new();
+    let mut rdr = csv::Reader::from_reader(reader);
+    for result in rdr.deserialize::<DungeonTierInput>() {
+        let tier = result.map_err(|e| e.to_string())?;
+        let index = DUNGEON_DIFFICULTIES
+            .iter()
+            .position(|difficulty| *difficulty == tier.difficulty)
+            .ok_or(f!("{}: unknown difficulty {}", tier.zone, tier.difficulty))?;
+        let tiers = zone_tiers.entry(tier.zone.to_string()).or_default();
+        if tiers[index].is_some() {
+            return Err(f!("{}: more than one {} row", tier.zone, tier.difficulty));
+        }
+        tiers[index] = Some(tier);
+    }
+
+    let mut dungeon_inputs: BTreeMap<String, DungeonInput> = BTreeMap::new();
+    for (zone, tiers) in zone_tiers {
+        let mut complete_tiers: Vec<DungeonTierInput> = vec![];
+        for (difficulty, tier) in DUNGEON_DIFFICULTIES.iter().zip(tiers) {
+            complete_tiers.push(tier.ok_or(f!("{}: missing its {} row", zone, difficulty))?);
+        }
+        let dungeon_input =
+            create_dungeon_input_from_tiers(&complete_tiers).map_err(|e| f!("{}: {}", zone, e))?;
+        dungeon_inputs.insert(zone, dungeon_input);
+    }
+    return Ok(dungeon_inputs);
+}
+
+/// Combine a dungeon's rows, in the order of DUNGEON_DIFFICULTIES, into a dungeon input
+fn create_dungeon_input_from_tiers(tiers: &[DungeonTierInput]) -> Result<DungeonInput, String> {
+    let (normal, boss) = tiers.split_at(4);
+    if tiers
+        .iter()
+        .any(|tier| tier.max_num_heroes != tiers[0].max_num_heroes)
+    {
+        return Err(String::from("every row must have the same max heroes"));
+    }
+    let barrier_types: [String; 3] = normal[3]
+        .barrier_elements
+        .split('/')
+        .map(|element| element.trim().to_string())
+        .collect::<Vec<String>>()
+        .try_into()
+        .map_err(|_| String::from("the Extreme row must have 3 barrier elements"))?;
+    let boss_barrier_type = boss[3].barrier_elements.trim().to_string();
+    if boss_barrier_type.is_empty() || boss_barrier_type.contains('/') {
+        return Err(String::from(
+            "the Boss Extreme row must have 1 barrier element",
+        ));
+    }
+    if boss.iter().any(|tier| {
+        tier.max_pack_size
+            .is_some_and(|max| Some(max) != tier.min_pack_size)
+    }) {
+        return Err(String::from("boss rows can't have a range of mobs"));
+    }
+
+    // Optional columns are only set on the dungeon if any row fills them, so dungeons without them match those loaded from yaml
+    let pack_sizes = |tiers: &[DungeonTierInput]| -> Option<[u8; 4]> {
+        if tiers.iter().all(|tier| tier.min_pack_size.is_none()) {
+            return None;
+        }
+        return Some(std::array::from_fn(|i| tiers[i].min_pack_size.unwrap_or(1)));
+    };
+    let max_pack_sizes = if normal.iter().all(|tier| tier.max_pack_size.is_none()) {
+        None
+    } else {
+        Some(std::array::from_fn(|i| {
+            normal[i]
+                .max_pack_size
+                .unwrap_or(normal[i].min_pack_size.unwrap_or(1))
+        }))
+    };
+    let miniboss_chances = if normal.iter().all(|tier| tier.miniboss_chance.is_none()) {
+        None
+    } else {
+        Some(std::array::from_fn(|i| {
+            normal[i].miniboss_chance.unwrap_or(50.0)
+        }))
+    };
+
+    return Ok(create_dungeon_input(
+        tiers[0].zone.to_string(),
+        tiers[0].max_num_heroes,
+        std::array::from_fn(|i| normal[i].hp),
+        std::array::from_fn(|i| normal[i].damage),
+        std::array::from_fn(|i| normal[i].defense_cap),
+        std::array::from_fn(|i| normal[i].aoe_damage),
+        std::array::from_fn(|i| normal[i].aoe_chance),
+        std::array::from_fn(|i| normal[i].minimum_power),
+        barrier_types,
+        std::array::from_fn(|i| normal[i].barrier_hp.unwrap_or(0.0)),
+        std::array::from_fn(|i| boss[i].hp),
+        std::array::from_fn(|i| boss[i].damage),
+        std::array::from_fn(|i| boss[i].defense_cap),
+        std::array::from_fn(|i| boss[i].aoe_damage),
+        std::array::from_fn(|i| boss[i].aoe_chance),
+        std::array::from_fn(|i| boss[i].minimum_power),
+        boss_barrier_type,
+        std::array::from_fn(|i| boss[i].barrier_hp.unwrap_or(0.0)),
+        Default::default(),
+        Default::default(),
+        vec![],
+        vec![],
+        None,
+        None,
+        pack_sizes(normal),
+        pack_sizes(boss),
+        max_pack_sizes,
+        miniboss_chances,
+    ));
+}
+
+/// Load every dungeon of a dungeons csv laid out like the community dungeon spreadsheet, keyed by zone
+pub fn load_dungeons_from_csv(reader: impl Read) -> Result<HashMap<String, Dungeon>, String> {
+    return load_dungeon_inputs_from_csv(reader)?
+        .into_iter()
+        .map(|(zone, dungeon_in)| {
+            let dungeon = Dungeon::try_from(dungeon_in).map_err(|e| f!("{}: {}", zone, e))?;
+            return Ok((zone, dungeon));
+        })
+        .collect();
 }
 
 pub fn _save_dungeons_to_yaml(
@@ -318,6 +653,83 @@ pub fn _save_dungeons_to_yaml(
     return Ok(());
 }
 
+/// Defines QuestInput format for deserialization from YAML
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QuestInput {
+    dungeon: String,
+    difficulty: usize,
+    duration_minutes: f64,
+    rest_minutes: f64,
+    injury_rest_minutes: f64,
+    xp: f64,
+    gold: f64,
+    loot_qty: f64,
+}
+
+/// Load quests keyed by identifier, performing type validation
+pub fn load_quests_from_yaml(reader: impl Read) -> Result<HashMap<String, Quest>, String> {
+    let mut quests: HashMap<String, Quest> = Default::default();
+    for (quest_key, quest_in) in serde_yaml::from_reader::<_, HashMap<String, QuestInput>>(reader)
+        .map_err(|e| e.to_string())?
+    {
+        let quest = create_quest_from_input(quest_key.to_string(), quest_in)
+            .map_err(|e| f!("{}: {}", quest_key, e))?;
+        quests.insert(quest_key, quest);
+    }
+    return Ok(quests);
+}
+
+/// Create a quest from its input, performing type validation
+pub fn create_quest_from_input(
+    identifier: String,
+    quest_in: QuestInput,
+) -> Result<Quest, &'static str> {
+    return create_quest(
+        identifier,
+        quest_in.dungeon,
+        quest_in.difficulty,
+        quest_in.duration_minutes,
+        quest_in.rest_minutes,
+        quest_in.injury_rest_minutes,
+        quest_in.xp,
+        quest_in.gold,
+        quest_in.loot_qty,
+    );
+}
+
+/// Defines QuestLineInput format for deserialization from YAML
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QuestLineInput {
+    quests: Vec<String>,
+    recovery_percent: f64,
+}
+
+/// Load quest lines keyed by identifier, performing type validation
+pub fn load_quest_lines_from_yaml(reader: impl Read) -> Result<HashMap<String, QuestLine>, String> {
+    let mut quest_lines: HashMap<String, QuestLine> = Default::default();
+    for (quest_line_key, quest_line_in) in
+        serde_yaml::from_reader::<_, HashMap<String, QuestLineInput>>(reader)
+            .map_err(|e| e.to_string())?
+    {
+        let quest_line = create_quest_line_from_input(quest_line_key.to_string(), quest_line_in)
+            .map_err(|e| f!("{}: {}", quest_line_key, e))?;
+        quest_lines.insert(quest_line_key, quest_line);
+    }
+    return Ok(quest_lines);
+}
+
+/// Create a quest line from its input, performing type validation
+pub fn create_quest_line_from_input(
+    identifier: String,
+    quest_line_in: QuestLineInput,
+) -> Result<QuestLine, &'static str> {
+    return create_quest_line(
+        identifier,
+        quest_line_in.quests,
+        quest_line_in.recovery_percent,
+    );
+}
+
 /// Defines HeroInput format for deserialization from CSV
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct HeroInput {
@@ -337,6 +749,16 @@ pub struct HeroInput {
     skill_3: String,
     skill_4: String,
 
+    // Optional tiers of each skill's line, from 1 or max, so skills can be given by their tier 1 name. Written skills are always resolved to their names
+    #[serde(default, skip_serializing)]
+    skill_1_tier: String,
+    #[serde(default, skip_serializing)]
+    skill_2_tier: String,
+    #[serde(default, skip_serializing)]
+    skill_3_tier: String,
+    #[serde(default, skip_serializing)]
+    skill_4_tier: String,
+
     equipment_equipped_1: String,
     equipment_quality_1: String,
     elements_socketed_1: String,
@@ -384,7 +806,20 @@ pub struct HeroInput {
 impl From<HeroInput> for Hero {
     /// Create a hero from the input object performing type validation and calculating certain fields
     fn from(item: HeroInput) -> Self {
-        let skills: [String; 4] = [item.skill_1, item.skill_2, item.skill_3, item.skill_4];
+        // A skill with a tier becomes a skill reference such as "Cleave T4", resolved once the skill data is loaded
+        let skills: [String; 4] = [
+            (item.skill_1, item.skill_1_tier),
+            (item.skill_2, item.skill_2_tier),
+            (item.skill_3, item.skill_3_tier),
+            (item.skill_4, item.skill_4_tier),
+        ]
+        .map(|(skill, tier)| {
+            let tier = tier.trim().trim_start_matches(['T', 't']);
+            if tier.is_empty() || is_empty_skill(&skill) {
+                return skill;
+            }
+            return f!("{} T{}", skill, tier);
+        });
         let equipment_equipped: [String; 6] = [
             item.equipment_equipped_1,
             item.equipment_equipped_2,
@@ -484,6 +919,10 @@ pub fn create_hero_input(
         skill_2: skills[1].clone(),
         skill_3: skills[2].clone(),
         skill_4: skills[3].clone(),
+        skill_1_tier: String::new(),
+        skill_2_tier: String::new(),
+        skill_3_tier: String::new(),
+        skill_4_tier: String::new(),
 
         equipment_equipped_1: equipment_equipped[0].clone(),
         equipment_equipped_2: equipment_equipped[1].clone(),
@@ -519,87 +958,143 @@ pub fn load_heroes_from_csv(
     path: String,
     bp_map: HashMap<String, Blueprint>,
     hero_classes: HashMap<String, HeroClass>,
-) -> HashMap<String, Hero> {
+) -> Result<HashMap<String, Hero>, String> {
     let mut heroes: HashMap<String, Hero> = Default::default();
-    let mut reader = csv::Reader::from_path(path).unwrap();
+    let mut reader = csv::Reader::from_path(&path).map_err(|e| f!("{}: {}", path, e))?;
     for result in reader.deserialize() {
-        let hero_in: HeroInput = result.unwrap();
+        let hero_in: HeroInput = result.map_err(|e| f!("{}: {}", path, e))?;
         let identifier = hero_in.identifier.to_string();
         let mut hero = Hero::from(hero_in);
-        hero.validate_equipment(&bp_map, &hero_classes);
-        hero.scale_by_class(&hero_classes);
+        hero.validate_equipment(&bp_map, &hero_classes)
+            .and_then(|_| hero.scale_by_class(&hero_classes))
+            .map_err(|e| f!("{} for hero {}", e, identifier))?;
         heroes.insert(identifier, hero);
     }
-    return heroes;
+    return Ok(heroes);
 }
 
-pub fn convert_loaded_heroes_to_sim_heroes(
-    mut heroes: HashMap<String, Hero>,
-    bp_map: HashMap<String, Blueprint>,
-    hero_skill_tier_1_name_map: HashMap<String, String>,
-    hero_skill_map: HashMap<String, HeroSkill>,
-    class_innate_skill_names_map: HashMap<String, String>,
-    innate_skill_map: HashMap<String, InnateSkill>,
-) -> HashMap<String, SimHero> {
+/// A row of a heroes or hero classes csv that could not be imported, numbered as in a spreadsheet with the header as row 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeroImportError {
+    pub row: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for HeroImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "row {}: {}", self.row, self.message);
+    }
+}
+
+/// Import builder heroes from a csv with the columns of hero_builder.csv, returning the heroes of every valid row in order
+/// A row that fails to parse, names an unknown class, level, or rank, is above its rank's level cap, fails equipment validation, or repeats an identifier is reported
+/// with its row number instead, so a single bad row doesn't stop the rest from importing
+pub fn import_heroes_from_csv(
+    reader: impl Read,
+    bp_map: &HashMap<String, Blueprint>,
+    hero_classes: &HashMap<String, HeroClass>,
+) -> (Vec<Hero>, Vec<HeroImportError>) {
+    let mut heroes: Vec<Hero> = vec![];
+    let mut errors: Vec<HeroImportError> = vec![];
+    let mut reader = csv::Reader::from_reader(reader);
+    for (i, result) in reader.deserialize::<HeroInput>().enumerate() {
+        // Row 1 is the header
+        let row = i + 2;
+        let hero = result.map_err(|e| e.to_string()).and_then(|hero_in| {
+            let level = hero_in.level;
+            let mut hero = Hero::from(hero_in);
+            hero.validate_equipment(bp_map, hero_classes)
+                .map_err(|e| e.to_string())?;
+            let max_level = hero_classes[&hero.get_class()].get_max_level();
+            if level == 0 || usize::from(level) > max_level {
+                return Err(f!(
+                    "level {} is outside class {}'s levels 1-{}",
+                    level,
+                    hero.get_class(),
+                    max_level
+                ));
+            }
+            hero.get_rank_scaling().map_err(|e| e.to_string())?;
+            if heroes
+                .iter()
+                .any(|other| other.get_identifier() == hero.get_identifier())
+            {
+                return Err(f!("duplicate identifier {}", hero.get_identifier()));
+            }
+            hero.scale_by_class(hero_classes)
+                .map_err(|e| e.to_string())?;
+            return Ok(hero);
+        });
+        match hero {
+            Ok(hero) => heroes.push(hero),
+            Err(message) => errors.push(HeroImportError { row, message }),
+        }
+    }
+    return (heroes, errors);
+}
+
+pub fn _convert_loaded_heroes_to_sim_heroes(
+    heroes: HashMap<String, Hero>,
+    hero_builder_information: &HeroBuilderInformation,
+) -> Result<HashMap<String, SimHero>, String> {
     let mut result: HashMap<String, SimHero> = Default::default();
-    for (identifier, hero) in &mut heroes {
-        hero.calculate_innate_tier(&class_innate_skill_names_map, &innate_skill_map);
-        hero.calculate_stat_improvements_from_gear_and_skills(
-            &bp_map,
-            &hero_skill_tier_1_name_map,
-            &hero_skill_map,
-            &class_innate_skill_names_map,
-            &innate_skill_map,
-        );
-        result.insert(identifier.to_string(), SimHero::from(hero.clone()));
+    for (identifier, hero) in heroes {
+        let sim_hero = hero_builder_information
+            .build_sim_hero_uncached(hero)
+            .map_err(|e| f!("{} for hero {}", e, identifier))?;
+        result.insert(identifier, sim_hero);
     }
-    return result;
+    return Ok(result);
 }
 
 pub fn load_heroes_as_sim_heroes_from_csv(
     path: String,
-    bp_map: HashMap<String, Blueprint>,
-    hero_classes: HashMap<String, HeroClass>,
-    hero_skill_tier_1_name_map: HashMap<String, String>,
-    hero_skill_map: HashMap<String, HeroSkill>,
-    class_innate_skill_names_map: HashMap<String, String>,
-    innate_skill_map: HashMap<String, InnateSkill>,
-) -> HashMap<String, SimHero> {
+    hero_builder_information: &HeroBuilderInformation,
+) -> Result<HashMap<String, SimHero>, String> {
     let mut heroes: HashMap<String, SimHero> = Default::default();
-    let mut reader = csv::Reader::from_path(path).unwrap();
+    let mut reader = csv::Reader::from_path(&path).map_err(|e| f!("{}: {}", path, e))?;
     for result in reader.deserialize() {
-        let hero_in: HeroInput = result.unwrap();
+        let hero_in: HeroInput = result.map_err(|e| f!("{}: {}", path, e))?;
         let identifier = hero_in.identifier.to_string();
+        let for_hero = |e: HeroBuilderError| f!("{} for hero {}", e, identifier);
         let mut hero = Hero::from(hero_in);
-        hero.validate_equipment(&bp_map, &hero_classes);
-        hero.scale_by_class(&hero_classes);
-        hero.calculate_innate_tier(&class_innate_skill_names_map, &innate_skill_map);
+        hero.validate_equipment(
+            &hero_builder_information.bp_map,
+            &hero_builder_information.hero_classes,
+        )
+        .map_err(for_hero)?;
+        hero.scale_by_class(&hero_builder_information.hero_classes)
+            .map_err(for_hero)?;
         // hero.calculate_attack_modifier(&hero_skill_map, &class_innate_skill_names_map, &innate_skill_map);
         // hero.calculate_defense_modifier(&hero_skill_map, &class_innate_skill_names_map, &innate_skill_map);
-        hero.calculate_stat_improvements_from_gear_and_skills(
-            &bp_map,
-            &hero_skill_tier_1_name_map,
-            &hero_skill_map,
-            &class_innate_skill_names_map,
-            &innate_skill_map,
-        );
-        heroes.insert(identifier, SimHero::from(hero));
+        let sim_hero = hero_builder_information
+            .build_sim_hero_uncached(hero)
+            .map_err(for_hero)?;
+        heroes.insert(identifier, sim_hero);
     }
-    return heroes;
+    return Ok(heroes);
 }
 
-pub fn _save_heroes_to_csv(
+pub fn save_heroes_to_csv(
     path: String,
     heroes: HashMap<String, Hero>,
 ) -> Result<(), std::io::Error> {
     let heroes_vec: Vec<Hero> = heroes.values().cloned().collect();
     let already_exists = std::path::Path::new(&path).exists();
-    let writer = std::fs::OpenOptions::new()
+    // Hand edited csvs often lack a trailing newline, which would merge the first appended hero into the last row
+    let needs_newline = already_exists
+        && std::fs::read(&path)?
+            .last()
+            .is_some_and(|byte| *byte != b'\n');
+    let mut writer = std::fs::OpenOptions::new()
         .write(true)
         .create(true)
         .append(true)
         .open(path)
         .unwrap();
+    if needs_newline {
+        std::io::Write::write_all(&mut writer, b"\n")?;
+    }
 
     let mut wtr = csv::WriterBuilder::new()
         .has_headers(!already_exists)
@@ -613,15 +1108,259 @@ pub fn _save_heroes_to_csv(
     return Ok(());
 }
 
-pub fn load_hero_classes_from_yaml(path: String) -> HashMap<String, HeroClass> {
-    let mut hero_classes: HashMap<String, HeroClass> = Default::default();
-    let reader = std::fs::File::open(path).unwrap();
-    for (class_name, hero_class) in
-        serde_yaml::from_reader::<std::fs::File, HashMap<String, HeroClass>>(reader).unwrap()
-    {
-        hero_classes.insert(class_name, hero_class);
+/// Import hero classes from a csv with a row per class, returning the classes of every valid row in order
+/// Columns are named after the fields of hero_classes.yaml, with item types of each slot in slot_1 to slot_6 separated by semicolons,
+/// innate_skill_1 to innate_skill_4, and an optional archetype. Base stats are given per level in hp_1, atk_1, def_1 and so on up to the max hero level,
+/// or, for a stat whose level columns are missing or blank, generated from its <stat>_base and <stat>_growth columns as with class-curve
+/// A row that fails to parse, has the wrong number of levels, fails class validation, or repeats a class is reported with its row number instead
+pub fn import_hero_classes_from_csv(reader: impl Read) -> (Vec<HeroClass>, Vec<HeroImportError>) {
+    let mut hero_classes: Vec<HeroClass> = vec![];
+    let mut errors: Vec<HeroImportError> = vec![];
+    let mut reader = csv::Reader::from_reader(reader);
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            errors.push(HeroImportError {
+                row: 1,
+                message: e.to_string(),
+            });
+            return (hero_classes, errors);
+        }
+    };
+    for (i, result) in reader.records().enumerate() {
+        // Row 1 is the header
+        let row = i + 2;
+        let hero_class = result.map_err(|e| e.to_string()).and_then(|record| {
+            let hero_class = parse_hero_class_record(&headers, &record)?;
+            let errors = hero_class.get_validation_errors();
+            if !errors.is_empty() {
+                return Err(errors.join(", "));
+            }
+            if hero_classes
+                .iter()
+                .any(|other| other.get_class() == hero_class.get_class())
+            {
+                return Err(f!("duplicate class {}", hero_class.get_class()));
+            }
+            return Ok(hero_class);
+        });
+        match hero_class {
+            Ok(hero_class) => hero_classes.push(hero_class),
+            Err(message) => errors.push(HeroImportError { row, message }),
+        }
+    }
+    return (hero_classes, errors);
+}
+
+/// Returns the trimmed value of a column, or None if the csv has no such column or the cell is blank
+fn get_record_value<'a>(
+    headers: &csv::StringRecord,
+    record: &'a csv::StringRecord,
+    column: &str,
+) -> Option<&'a str> {
+    return headers
+        .iter()
+        .position(|header| header.trim() == column)
+        .and_then(|i| record.get(i))
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty());
+}
+
+fn parse_record_value<T: FromStr>(
+    headers: &csv::StringRecord,
+    record: &csv::StringRecord,
+    column: &str,
+) -> Result<T, String> {
+    let value =
+        get_record_value(headers, record, column).ok_or_else(|| f!("{} is missing", column))?;
+    return value
+        .parse()
+        .map_err(|_| f!("{} {} is not a valid value", column, value));
+}
+
+/// Returns a stat's base value at each level, from its level columns or generated from its growth columns
+fn parse_class_stat_levels(
+    headers: &csv::StringRecord,
+    record: &csv::StringRecord,
+    stat: &str,
+) -> Result<Vec<f64>, String> {
+    let level_columns: Vec<String> = (1..)
+        .map(|level| f!("{}_{}", stat, level))
+        .take_while(|column| headers.iter().any(|header| header.trim() == column))
+        .collect();
+    let filled_qty = level_columns
+        .iter()
+        .filter(|column| get_record_value(headers, record, column).is_some())
+        .count();
+    let levels: Vec<f64> = if filled_qty == 0 {
+        let base = parse_record_value(headers, record, &f!("{}_base", stat))
+            .map_err(|e| f!("{} levels are blank and {}", stat, e))?;
+        let growth_rate = parse_record_value(headers, record, &f!("{}_growth", stat))
+            .map_err(|e| f!("{} levels are blank and {}", stat, e))?;
+        generate_stat_curve(base, growth_rate, &CLASS_GROWTH_BREAKPOINTS, MAX_HERO_LEVEL)
+    } else {
+        level_columns
+            .iter()
+            .map(|column| parse_record_value(headers, record, column))
+            .collect::<Result<Vec<f64>, String>>()?
+    };
+    if levels.len() != usize::from(MAX_HERO_LEVEL) {
+        return Err(f!(
+            "{} has {} levels but must have one for each level up to {}",
+            stat,
+            levels.len(),
+            MAX_HERO_LEVEL
+        ));
+    }
+    return Ok(levels);
+}
+
+fn parse_hero_class_record(
+    headers: &csv::StringRecord,
+    record: &csv::StringRecord,
+) -> Result<HeroClass, String> {
+    let get_text = |column: &str| -> Result<String, String> {
+        return get_record_value(headers, record, column)
+            .map(|value| value.to_string())
+            .ok_or_else(|| f!("{} is missing", column));
+    };
+    let mut equipment_allowed: [Vec<String>; 6] = Default::default();
+    for (i, allowed) in equipment_allowed.iter_mut().enumerate() {
+        *allowed = get_text(&f!("slot_{}", i + 1))?
+            .split(';')
+            .map(|item_type| item_type.trim().to_string())
+            .filter(|item_type| !item_type.is_empty())
+            .collect();
+    }
+    let mut innate_skills: [String; 4] = Default::default();
+    for (i, innate_skill) in innate_skills.iter_mut().enumerate() {
+        *innate_skill = get_text(&f!("innate_skill_{}", i + 1))?;
+    }
+    let archetype = match get_record_value(headers, record, "archetype") {
+        Some(archetype) => Some(
+            HeroArchetype::from_str(archetype)
+                .map_err(|_| f!("archetype {} is not a valid archetype", archetype))?,
+        ),
+        None => None,
+    };
+    return Ok(_create_hero_class(
+        get_text("class")?,
+        get_record_value(headers, record, "prerequisite")
+            .unwrap_or_default()
+            .to_string(),
+        parse_record_value(headers, record, "gold_hire_cost")?,
+        parse_record_value(headers, record, "gem_hire_cost")?,
+        parse_class_stat_levels(headers, record, "hp")?,
+        parse_class_stat_levels(headers, record, "atk")?,
+        parse_class_stat_levels(headers, record, "def")?,
+        parse_record_value(headers, record, "base_eva")?,
+        parse_record_value(headers, record, "base_crit_chance")?,
+        parse_record_value(headers, record, "base_crit_mult")?,
+        parse_record_value(headers, record, "base_threat_rating")?,
+        get_text("element_type")?,
+        equipment_allowed,
+        vec![],
+        innate_skills,
+        false,
+        archetype,
+    ));
+}
+
+pub fn load_hero_classes_from_yaml(
+    reader: impl Read,
+) -> Result<HashMap<String, HeroClass>, String> {
+    return serde_yaml::from_reader::<_, HashMap<String, HeroClass>>(reader)
+        .map_err(|e| e.to_string());
+}
+
+/// Load the combat effects of hero skills keyed by skill name, rejecting data that fails validation
+pub fn load_skill_effects_from_yaml(
+    reader: impl Read,
+) -> Result<HashMap<String, Vec<SkillEffect>>, String> {
+    let skill_effects = serde_yaml::from_reader::<_, HashMap<String, Vec<SkillEffect>>>(reader)
+        .map_err(|e| e.to_string())?;
+    for (skill, effects) in skill_effects.iter() {
+        let errors: Vec<String> = effects
+            .iter()
+            .flat_map(|effect| effect.get_validation_errors())
+            .collect();
+        if !errors.is_empty() {
+            return Err(f!("{}: {}", skill, errors.join(", ")));
+        }
+    }
+    return Ok(skill_effects);
+}
+
+/// Load the bonuses each kind of spirit gives the whole hero, keyed by spirit name without its tier, rejecting data that fails validation
+pub fn load_spirits_from_yaml(reader: impl Read) -> Result<HashMap<String, Spirit>, String> {
+    let spirits =
+        serde_yaml::from_reader::<_, HashMap<String, Spirit>>(reader).map_err(|e| e.to_string())?;
+    for (name, spirit) in spirits.iter() {
+        let errors = spirit.get_validation_errors();
+        if !errors.is_empty() {
+            return Err(f!("{}: {}", name, errors.join(", ")));
+        }
+    }
+    return Ok(spirits);
+}
+
+/// Load the stats each seed adds, rejecting data that fails validation
+pub fn load_seed_values_from_yaml(reader: impl Read) -> Result<SeedValues, String> {
+    let seed_values =
+        serde_yaml::from_reader::<_, SeedValues>(reader).map_err(|e| e.to_string())?;
+    let errors = seed_values.get_validation_errors();
+    if !errors.is_empty() {
+        return Err(errors.join(", "));
+    }
+    return Ok(seed_values);
+}
+
+/// Load the flat stats elements of each grade add to their items, rejecting data that fails validation
+pub fn load_elements_from_yaml(reader: impl Read) -> Result<Vec<Element>, String> {
+    let elements = serde_yaml::from_reader::<_, Vec<Element>>(reader).map_err(|e| e.to_string())?;
+    for element in elements.iter() {
+        let errors = element.get_validation_errors();
+        if !errors.is_empty() {
+            return Err(f!("{}: {}", element.get_name(), errors.join(", ")));
+        }
+    }
+    return Ok(elements);
+}
+
+/// Load pet skills keyed by the blueprint name of the familiar that grants them
+pub fn load_pet_skills_from_yaml(reader: impl Read) -> Result<HashMap<String, PetSkill>, String> {
+    let pet_skills = serde_yaml::from_reader::<_, HashMap<String, PetSkill>>(reader)
+        .map_err(|e| e.to_string())?;
+    for (pet, pet_skill) in pet_skills.iter() {
+        let errors = pet_skill.get_validation_errors();
+        if !errors.is_empty() {
+            return Err(f!("{}: {}", pet, errors.join(", ")));
+        }
+    }
+    return Ok(pet_skills);
+}
+
+/// Load which enchantments items can hold, keyed by blueprint name or item type
+pub fn load_enchant_eligibility_from_yaml(
+    reader: impl Read,
+) -> Result<HashMap<String, EnchantEligibility>, String> {
+    return serde_yaml::from_reader::<_, HashMap<String, EnchantEligibility>>(reader)
+        .map_err(|e| e.to_string());
+}
+
+/// Load the base stats and ability parameters of every champion, rejecting data that fails validation
+pub fn load_champions_from_yaml(
+    reader: impl Read,
+) -> Result<HashMap<Champion, ChampionData>, String> {
+    let champion_data = serde_yaml::from_reader::<_, HashMap<Champion, ChampionData>>(reader)
+        .map_err(|e| e.to_string())?;
+    for (champion, data) in champion_data.iter() {
+        let errors = data.get_validation_errors();
+        if !errors.is_empty() {
+            return Err(f!("{}: {}", champion, errors.join(", ")));
+        }
     }
-    return hero_classes;
+    return Ok(champion_data);
 }
 
 pub fn _save_hero_classes_to_yaml(
@@ -631,7 +1370,8 @@ pub fn _save_hero_classes_to_yaml(
     let already_exists = std::path::Path::new(&path).exists();
     let mut hashmap: HashMap<String, HeroClass>;
     if already_exists {
-        hashmap = load_hero_classes_from_yaml(path.to_string());
+        hashmap = load_hero_classes_from_yaml(std::fs::File::open(&path)?)
+            .map_err(std::io::Error::other)?;
         hashmap.extend(hero_classes);
     } else {
         hashmap = hero_classes;
@@ -640,6 +1380,7 @@ pub fn _save_hero_classes_to_yaml(
     let writer = std::fs::OpenOptions::new()
         .write(true)
         .create(true)
+        .truncate(true)
         .open(path)
         .unwrap();
 
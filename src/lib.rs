@@ -0,0 +1,85 @@
+//! Shop Titans combat simulator engine, used by the st_sim command line tool and embeddable in other applications
+//!
+//! Load a `GameData` with `load_game_data` from any `DataSource`, then create a `Simulator` with `create_simulator`.
+//! Its `build_hero` turns a hero input (such as one from `build_codes::decode_build_code`) into a `SimHero`,
+//! `run_trial` and `simulate` run a party against a dungeon, returning a `TrialResult`,
+//! and `run_study` runs a study described by a `StudyConfig` on the simulator's heroes, returning its `StudyResults` without saving or printing anything. The items re-exported here are the stable API,
+//! while the modules below are public for callers that need the lower level pieces
+
+#[macro_use]
+extern crate fstrings;
+
+pub use data_sources::{DataSource, EmbeddedDataSource, FileDataSource, RemoteDataSource};
+pub use dungeons::{Dungeon, TrialDungeon};
+pub use heroes::SimHero;
+pub use inputs::HeroInput;
+pub use simulator::{create_simulator, load_game_data, GameData, Simulator, StudyResults};
+pub use study_config::{load_study_config, StudyConfig};
+pub use trials::TrialResult;
+
+pub mod abilities;
+
+pub mod champions;
+
+pub mod crafting;
+
+pub mod equipment;
+
+pub mod heroes;
+
+pub mod dungeons;
+
+pub mod simulations;
+
+pub mod combat_trace;
+
+pub mod trials;
+
+pub mod inputs;
+
+pub mod data_sources;
+
+pub mod decimals;
+
+pub mod formatting;
+
+pub mod hashing;
+
+pub mod build_codes;
+
+pub mod skills;
+
+pub mod hero_builder;
+
+pub mod sheet_processing;
+
+pub mod studies;
+
+pub mod combinations;
+
+pub mod quests;
+
+pub mod validation;
+
+pub mod comparisons;
+
+pub mod watch;
+
+pub mod rng;
+
+pub mod statistics;
+
+pub mod study_queue;
+
+pub mod simulator;
+
+pub mod determinism;
+
+pub mod study_config;
+
+pub mod roster;
+
+pub mod progress;
+
+#[cfg(test)]
+mod test_data;
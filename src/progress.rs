@@ -0,0 +1,161 @@
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Whether a study is still running, has finished, or stopped partway for the study queue
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ProgressState {
+    Running,
+    Finished,
+    Stopped,
+}
+
+/// A study's progress, reported when it starts running and each time it completes work
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProgressEvent {
+    pub completed: u64, // The trials completed, or the builds, quest line runs, or days of studies that don't count trials, including any completed before the study was resumed
+    pub total: Option<u64>, // None for studies that search until they stop improving, such as the hero optimization study
+    pub eta: Option<Duration>, // Estimated from the rate of work completed since the study started running, None until any is or if the total is unknown
+    pub best_score: Option<f64>, // The best success rate of the trials completed, for studies that rank their trials
+    pub state: ProgressState,
+}
+
+/// Receives a running study's progress events, such as to draw a progress bar or pass them to another thread
+pub trait ProgressReporter {
+    fn report(&mut self, event: &ProgressEvent);
+}
+
+/// Any closure taking a progress event can be used as a progress callback
+impl<F: FnMut(&ProgressEvent)> ProgressReporter for F {
+    fn report(&mut self, event: &ProgressEvent) {
+        self(event);
+    }
+}
+
+/// Sends each event down a channel, so progress can be read from another thread. Events are discarded once the receiver is dropped
+impl ProgressReporter for Sender<ProgressEvent> {
+    fn report(&mut self, event: &ProgressEvent) {
+        let _ = self.send(*event);
+    }
+}
+
+/// Draws a study's progress as a progress bar in the terminal, or as a spinner counting the work completed when the total is unknown
+#[derive(Debug)]
+pub struct TerminalProgressReporter {
+    pb: Option<ProgressBar>, // Created by the first event, once the total is known
+}
+
+pub fn create_terminal_progress_reporter() -> TerminalProgressReporter {
+    return TerminalProgressReporter { pb: None };
+}
+
+impl ProgressReporter for TerminalProgressReporter {
+    fn report(&mut self, event: &ProgressEvent) {
+        let pb = self.pb.get_or_insert_with(|| match event.total {
+            Some(total) => {
+                let pb = ProgressBar::new(total);
+                pb.set_style(ProgressStyle::with_template("{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {human_pos}/{len} ({eta_precise}) {msg}")
+                    .unwrap()
+                    .progress_chars("#>-"));
+                pb
+            }
+            None => {
+                let pb = ProgressBar::new_spinner();
+                pb.set_style(
+                    ProgressStyle::with_template(
+                        "{spinner:.green} [{elapsed_precise}] {human_pos} completed {msg}",
+                    )
+                    .unwrap(),
+                );
+                pb
+            }
+        });
+        pb.set_position(event.completed);
+        if let Some(best_score) = event.best_score {
+            pb.set_message(f!("(best success rate {:.2})", best_score));
+        }
+        match event.state {
+            ProgressState::Running => {}
+            ProgressState::Finished => pb.finish_with_message("Study Complete"),
+            ProgressState::Stopped => pb.abandon_with_message("Study Stopped"),
+        }
+    }
+}
+
+/// Tracks a running study's progress, reporting an event with the estimated time remaining each time it changes
+pub struct StudyProgress<'a> {
+    reporter: &'a mut dyn ProgressReporter,
+    total: Option<u64>,
+    completed: u64,
+    completed_at_start: u64, // The work already completed when the study started running, so a resumed study's estimate only counts the work done since
+    timer: Instant,
+    best_score: Option<f64>,
+}
+
+/// Reports the study's starting progress, with completed above 0 if it was resumed
+pub fn create_study_progress(
+    reporter: &mut dyn ProgressReporter,
+    total: Option<u64>,
+    completed: u64,
+) -> StudyProgress<'_> {
+    let mut progress = StudyProgress {
+        reporter,
+        total,
+        completed,
+        completed_at_start: completed,
+        timer: Instant::now(),
+        best_score: None,
+    };
+    progress.report(ProgressState::Running);
+    return progress;
+}
+
+impl StudyProgress<'_> {
+    /// Report the work completed so far and the best score among it, if the study ranks its trials
+    pub fn update(&mut self, completed: u64, best_score: Option<f64>) {
+        self.completed = completed;
+        self.best_score = best_score;
+        self.report(ProgressState::Running);
+    }
+
+    pub fn inc(&mut self, qty: u64) {
+        self.update(self.completed + qty, self.best_score);
+    }
+
+    pub fn finish(&mut self) {
+        self.report(ProgressState::Finished);
+    }
+
+    /// Report that the study stopped partway for the study queue
+    pub fn stop(&mut self) {
+        self.report(ProgressState::Stopped);
+    }
+
+    fn get_eta(&self) -> Option<Duration> {
+        let total = self.total?;
+        let completed_since_start = self.completed.checked_sub(self.completed_at_start)?;
+        if completed_since_start == 0 {
+            return None;
+        }
+        return Some(
+            self.timer.elapsed().mul_f64(
+                total.saturating_sub(self.completed) as f64 / completed_since_start as f64,
+            ),
+        );
+    }
+
+    fn report(&mut self, state: ProgressState) {
+        let event = ProgressEvent {
+            completed: self.completed,
+            total: self.total,
+            eta: match state {
+                ProgressState::Running => self.get_eta(),
+                _ => None,
+            },
+            best_score: self.best_score,
+            state,
+        };
+        self.reporter.report(&event);
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// A quest heroes can be sent on, combining a dungeon difficulty with its duration, rest times, and rewards
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Quest {
+    identifier: String,
+    dungeon: String, // The zone of the dungeon the quest takes place in
+    difficulty: usize,
+    duration_minutes: f64,
+    rest_minutes: f64,        // Rest time for heroes that survive the quest
+    injury_rest_minutes: f64, // Rest time for heroes that are defeated during the quest
+    xp: f64,                  // Rewards are only granted when the quest succeeds
+    gold: f64,
+    loot_qty: f64,
+}
+
+impl Quest {
+    pub fn get_identifier(&self) -> String {
+        return self.identifier.to_string();
+    }
+
+    pub fn get_dungeon(&self) -> String {
+        return self.dungeon.to_string();
+    }
+
+    pub fn get_difficulty(&self) -> usize {
+        return self.difficulty;
+    }
+
+    pub fn get_duration_minutes(&self) -> f64 {
+        return self.duration_minutes;
+    }
+
+    /// rest_time_percent is the fraction the hero's rest time is reduced by
+    pub fn get_rest_minutes(&self, injured: bool, rest_time_percent: f64) -> f64 {
+        let rest_time_multiplier = 1.0 - rest_time_percent.clamp(0.0, 1.0);
+        if injured {
+            return self.injury_rest_minutes * rest_time_multiplier;
+        }
+        return self.rest_minutes * rest_time_multiplier;
+    }
+
+    /// Returns the (xp, gold, loot_qty) rewards for completing the quest
+    pub fn get_rewards(&self) -> (f64, f64, f64) {
+        return (self.xp, self.gold, self.loot_qty);
+    }
+
+    /// Returns the average minutes between starts when running the quest repeatedly with the same party
+    /// The party is ready again once its slowest hero has rested, so a run with any defeated hero takes the injury rest time
+    /// rest_time_percent is the fraction the party's rest time is reduced by, see Team::get_rest_time_percent
+    pub fn calculate_cycle_minutes(&self, injury_rate: f64, rest_time_percent: f64) -> f64 {
+        let rest_minutes = self.get_rest_minutes(false, rest_time_percent);
+        let injury_rest_minutes =
+            f64::max(self.get_rest_minutes(true, rest_time_percent), rest_minutes);
+        return self.duration_minutes
+            + injury_rate * injury_rest_minutes
+            + (1.0 - injury_rate) * rest_minutes;
+    }
+
+    /// Returns the expected runs per day of running the quest repeatedly with the same party
+    pub fn calculate_quests_per_day(&self, injury_rate: f64, rest_time_percent: f64) -> f64 {
+        return 24.0 * 60.0 / self.calculate_cycle_minutes(injury_rate, rest_time_percent);
+    }
+
+    /// Returns the expected xp a hero earns per run of the quest, counting lost runs as none
+    pub fn calculate_xp_per_quest(&self, success_rate: f64, xp_percent: f64) -> f64 {
+        return success_rate * self.xp * (1.0 + xp_percent);
+    }
+
+    /// Returns the expected (xp, gold, loot_qty) earned per hour of running the quest repeatedly with the same party
+    /// Rewards are only earned on success, and extra_loot_qty is any loot earned per run on top of the quest's, such as Polonia's
+    /// Xp is earned by each hero, so xp_percent is the party's average xp bonus
+    pub fn calculate_rewards_per_hour(
+        &self,
+        success_rate: f64,
+        injury_rate: f64,
+        extra_loot_qty: f64,
+        rest_time_percent: f64,
+        xp_percent: f64,
+    ) -> (f64, f64, f64) {
+        let runs_per_hour = 60.0 / self.calculate_cycle_minutes(injury_rate, rest_time_percent);
+        return (
+            self.calculate_xp_per_quest(success_rate, xp_percent) * runs_per_hour,
+            success_rate * self.gold * runs_per_hour,
+            (success_rate * self.loot_qty + extra_loot_qty) * runs_per_hour,
+        );
+    }
+}
+
+/// Returns the quests trial results are matched against to estimate rewards per hour, sorted by identifier so the first match is stable
+pub fn create_reward_quests(quests: HashMap<String, Quest>) -> Vec<Quest> {
+    let mut quests: Vec<Quest> = quests.into_values().collect();
+    quests.sort_by_key(|quest| quest.get_identifier());
+    return quests;
+}
+
+/// Returns the first of the reward quests that takes place in the given dungeon at the only difficulty of a trial
+/// Trials mixing several difficulties don't match any quest
+pub fn find_reward_quest<'a>(
+    reward_quests: &'a [Quest],
+    zone: &str,
+    difficulty_settings: &[usize],
+) -> Option<&'a Quest> {
+    let [difficulty] = difficulty_settings else {
+        return None;
+    };
+    return reward_quests.iter().find(|quest| {
+        return quest.dungeon == zone && quest.difficulty == *difficulty;
+    });
+}
+
+/// Create a quest performing type validation
+/// Difficulty settings (choose one):
+/// 1 - Easy, 2 - Medium, 3 - Hard, 4 - Extreme,
+/// 5 - Boss Easy, 6 - Boss Medium, 7 - Boss Hard, 8 - Boss Extreme
+pub fn create_quest(
+    identifier: String,
+    dungeon: String,
+    difficulty: usize,
+    duration_minutes: f64,
+    rest_minutes: f64,
+    injury_rest_minutes: f64,
+    xp: f64,
+    gold: f64,
+    loot_qty: f64,
+) -> Result<Quest, &'static str> {
+    if !(1..=8).contains(&difficulty) {
+        return Err("difficulty must be within range 1-8 inclusive");
+    }
+    if duration_minutes <= 0.0 {
+        return Err("duration_minutes must be > 0");
+    }
+    if rest_minutes < 0.0 || injury_rest_minutes < 0.0 {
+        return Err("rest_minutes and injury_rest_minutes must be >= 0");
+    }
+    if xp < 0.0 || gold < 0.0 || loot_qty < 0.0 {
+        return Err("rewards must be >= 0");
+    }
+
+    return Ok(Quest {
+        identifier,
+        dungeon,
+        difficulty,
+        duration_minutes,
+        rest_minutes,
+        injury_rest_minutes,
+        xp,
+        gold,
+        loot_qty,
+    });
+}
+
+/// A multi-part quest line, where the same party runs several quests back to back
+/// Heroes defeated in a part sit out the rest of the line, and survivors carry their remaining hp into the next part
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QuestLine {
+    identifier: String,
+    quests: Vec<String>, // The identifiers of the quests making up each part, in order
+    recovery_percent: f64, // The fraction of max hp survivors recover between parts, 1.0 for a full recovery
+}
+
+impl QuestLine {
+    pub fn get_identifier(&self) -> String {
+        return self.identifier.to_string();
+    }
+
+    pub fn get_quests(&self) -> Vec<String> {
+        return self.quests.clone();
+    }
+
+    pub fn get_recovery_percent(&self) -> f64 {
+        return self.recovery_percent;
+    }
+}
+
+/// Create a quest line performing type validation
+pub fn create_quest_line(
+    identifier: String,
+    quests: Vec<String>,
+    recovery_percent: f64,
+) -> Result<QuestLine, &'static str> {
+    if quests.is_empty() {
+        return Err("quest line must have at least one quest");
+    }
+    if !(0.0..=1.0).contains(&recovery_percent) {
+        return Err("recovery_percent must be within range 0-1 inclusive");
+    }
+
+    return Ok(QuestLine {
+        identifier,
+        quests,
+        recovery_percent,
+    });
+}
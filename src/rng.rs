@@ -0,0 +1,167 @@
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use rand_pcg::Pcg64Mcg;
+use rand_xoshiro::Xoshiro256PlusPlus;
+use serde::{Deserialize, Serialize};
+
+/// Defines the random number generators the simulation engine can draw from
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Default,
+    strum::Display,
+    strum::EnumString,
+)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum RngAlgorithm {
+    #[default]
+    ChaCha, // Cryptographically strong and portable, the same algorithm as rand's StdRng. Results are identical across platforms for a given seed
+    Xoshiro, // Xoshiro256++, faster with good statistical quality
+    Pcg,     // Pcg64Mcg, fastest with good statistical quality
+}
+
+/// The generator and seed every simulation draws from
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub struct RngConfig {
+    pub algorithm: RngAlgorithm,
+    pub seed: Option<u64>, // Seeds the generator so runs can be reproduced exactly. Seeded from entropy when None
+}
+
+impl RngConfig {
+    /// Returns how results were generated, such as "chacha" or "pcg seed 42", for recording alongside them
+    pub fn describe(&self) -> String {
+        return match self.seed {
+            Some(seed) => f!("{} seed {}", self.algorithm, seed),
+            None => self.algorithm.to_string(),
+        };
+    }
+}
+
+enum SimRngState {
+    ChaCha(Box<ChaCha12Rng>), // Boxed since its buffered state is much larger than the other generators
+    Xoshiro(Xoshiro256PlusPlus),
+    Pcg(Pcg64Mcg),
+}
+
+/// The configured generator, owned by whatever is running simulations and passed down to every roll
+pub struct SimRng {
+    state: SimRngState,
+}
+
+pub fn create_sim_rng(config: RngConfig) -> SimRng {
+    let state = match (config.algorithm, config.seed) {
+        (RngAlgorithm::ChaCha, Some(seed)) => {
+            SimRngState::ChaCha(Box::new(ChaCha12Rng::seed_from_u64(seed)))
+        }
+        (RngAlgorithm::ChaCha, None) => SimRngState::ChaCha(Box::new(ChaCha12Rng::from_entropy())),
+        (RngAlgorithm::Xoshiro, Some(seed)) => {
+            SimRngState::Xoshiro(Xoshiro256PlusPlus::seed_from_u64(seed))
+        }
+        (RngAlgorithm::Xoshiro, None) => SimRngState::Xoshiro(Xoshiro256PlusPlus::from_entropy()),
+        (RngAlgorithm::Pcg, Some(seed)) => SimRngState::Pcg(Pcg64Mcg::seed_from_u64(seed)),
+        (RngAlgorithm::Pcg, None) => SimRngState::Pcg(Pcg64Mcg::from_entropy()),
+    };
+    return SimRng { state };
+}
+
+/// Run f with a generator of its own for the given stream of the configuration
+/// Each stream of a seeded configuration is seeded from the seed and the stream's index alone, so work drawing from a stream gets the same numbers on any thread
+pub fn with_stream_rng<T>(config: RngConfig, stream: u64, f: impl FnOnce(&mut SimRng) -> T) -> T {
+    let mut rng = create_sim_rng(RngConfig {
+        algorithm: config.algorithm,
+        seed: config
+            .seed
+            .map(|seed| seed ^ stream.wrapping_mul(0x9e3779b97f4a7c15)),
+    });
+    return f(&mut rng);
+}
+
+impl RngCore for SimRng {
+    fn next_u32(&mut self) -> u32 {
+        return match &mut self.state {
+            SimRngState::ChaCha(rng) => rng.next_u32(),
+            SimRngState::Xoshiro(rng) => rng.next_u32(),
+            SimRngState::Pcg(rng) => rng.next_u32(),
+        };
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        return match &mut self.state {
+            SimRngState::ChaCha(rng) => rng.next_u64(),
+            SimRngState::Xoshiro(rng) => rng.next_u64(),
+            SimRngState::Pcg(rng) => rng.next_u64(),
+        };
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match &mut self.state {
+            SimRngState::ChaCha(rng) => rng.fill_bytes(dest),
+            SimRngState::Xoshiro(rng) => rng.fill_bytes(dest),
+            SimRngState::Pcg(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        return Ok(());
+    }
+}
+
+/// Random values generated in one batch at the start of each combat round and drawn from by every roll in the round
+/// Drawing from a buffer avoids going through the generator for each of the many hit, crit, evade, and proc rolls
+pub struct RoundRolls<'a> {
+    rng: &'a mut SimRng,
+    rolls: Vec<u64>,
+    next: usize, // The index of the next unused roll
+}
+
+/// Creates a buffer of batch_qty rolls, which should be enough for a typical round so that only one batch is generated per round
+pub fn create_round_rolls(rng: &mut SimRng, batch_qty: usize) -> RoundRolls<'_> {
+    let mut round_rolls = RoundRolls {
+        rng,
+        rolls: vec![0; batch_qty.max(1)],
+        next: 0,
+    };
+    round_rolls.refill();
+    return round_rolls;
+}
+
+impl RoundRolls<'_> {
+    /// Replace every roll with a new batch, discarding any left unused from the previous round
+    pub fn refill(&mut self) {
+        self.rng.fill(&mut self.rolls[..]);
+        self.next = 0;
+    }
+}
+
+impl RngCore for RoundRolls<'_> {
+    fn next_u32(&mut self) -> u32 {
+        return (self.next_u64() >> 32) as u32;
+    }
+
+    /// Rounds that need more rolls than a batch holds generate another batch
+    fn next_u64(&mut self) -> u64 {
+        if self.next >= self.rolls.len() {
+            self.refill();
+        }
+        self.next += 1;
+        return self.rolls[self.next - 1];
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        return Ok(());
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    equipment::Blueprint,
+    hero_builder::{Hero, HeroClass},
+    inputs::HeroInput,
+};
+
+/// The version written to every saved roster, bump when the layout of a roster or of HeroInput changes
+pub const ROSTER_VERSION: u32 = 1;
+
+/// The folder rosters are saved to and looked up by name in
+pub const ROSTERS_DIR: &str = "input/rosters";
+
+/// A named collection of hero builds, saved as a versioned json file so builds can be kept and shared apart from hero_builder.csv
+/// Heroes are kept in the order they were added, and are stored as their builder input like hero_builder.csv rows and build codes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Roster {
+    version: u32,
+    name: String,
+    heroes: Vec<HeroInput>,
+}
+
+/// Only the version of a roster, read first so a roster from a newer simulator is reported as such rather than as malformed
+#[derive(Deserialize)]
+struct RosterVersion {
+    version: u32,
+}
+
+pub fn create_roster(name: String) -> Roster {
+    return Roster {
+        version: ROSTER_VERSION,
+        name,
+        heroes: vec![],
+    };
+}
+
+/// Returns the path of the roster with the given name
+pub fn get_roster_path(name: &str) -> String {
+    return f!("{}/{}.json", ROSTERS_DIR, name);
+}
+
+/// Load a roster from a json file
+pub fn load_roster(path: &str) -> Result<Roster, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| f!("{}: {}", path, e))?;
+    let version = serde_json::from_str::<RosterVersion>(&contents)
+        .map_err(|e| f!("{}: {}", path, e))?
+        .version;
+    if version > ROSTER_VERSION {
+        return Err(f!(
+            "{}: roster version {} is newer than this simulator supports ({})",
+            path,
+            version,
+            ROSTER_VERSION
+        ));
+    }
+    return serde_json::from_str(&contents).map_err(|e| f!("{}: {}", path, e));
+}
+
+/// Load the roster with the given name, or a new empty roster if none has been saved
+pub fn load_or_create_roster(name: &str) -> Result<Roster, String> {
+    let path = get_roster_path(name);
+    if !Path::new(&path).exists() {
+        return Ok(create_roster(name.to_string()));
+    }
+    return load_roster(&path);
+}
+
+impl Roster {
+    pub fn get_name(&self) -> String {
+        return self.name.to_string();
+    }
+
+    pub fn get_identifiers(&self) -> Vec<String> {
+        return self
+            .heroes
+            .iter()
+            .map(|hero| Hero::from(hero.clone()).get_identifier())
+            .collect();
+    }
+
+    /// Save the roster, replacing the file in one step so it is never left half written
+    pub fn save(&self, path: &str) -> Result<(), std::io::Error> {
+        if let Some(p) = Path::new(path).parent() {
+            std::fs::create_dir_all(p)?;
+        }
+        let temp_path = f!("{}.tmp", path);
+        let writer = std::fs::File::create(&temp_path)?;
+        serde_json::to_writer_pretty(writer, self).map_err(std::io::Error::other)?;
+        return std::fs::rename(temp_path, path);
+    }
+
+    /// Add a hero's build to the roster, replacing any build of the same identifier in place
+    pub fn add_hero(&mut self, hero: Hero) {
+        let identifier = hero.get_identifier();
+        let input = HeroInput::from(hero);
+        match self
+            .get_identifiers()
+            .iter()
+            .position(|other| *other == identifier)
+        {
+            Some(i) => self.heroes[i] = input,
+            None => self.heroes.push(input),
+        }
+    }
+
+    pub fn remove_hero(&mut self, identifier: &str) -> Result<(), &'static str> {
+        let i = self
+            .get_identifiers()
+            .iter()
+            .position(|other| other == identifier)
+            .ok_or("no hero in the roster has that identifier")?;
+        self.heroes.remove(i);
+        return Ok(());
+    }
+
+    /// Returns each hero of the roster by identifier, validated and scaled by class like the heroes of hero_builder.csv
+    pub fn load_heroes(
+        &self,
+        bp_map: &HashMap<String, Blueprint>,
+        hero_classes: &HashMap<String, HeroClass>,
+    ) -> Result<Vec<(String, Hero)>, String> {
+        let mut heroes: Vec<(String, Hero)> = vec![];
+        for input in self.heroes.iter() {
+            let mut hero = Hero::from(input.clone());
+            let identifier = hero.get_identifier();
+            hero.validate_equipment(bp_map, hero_classes)
+                .and_then(|_| hero.scale_by_class(hero_classes))
+                .map_err(|e| f!("{} for hero {} in roster {}", e, identifier, self.name))?;
+            heroes.push((identifier, hero));
+        }
+        return Ok(heroes);
+    }
+}
@@ -1,16 +1,48 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, io::Read, str::FromStr};
+
+use csv::ByteRecord;
 
 use crate::equipment::{create_blueprint, Blueprint};
 use crate::skills::{create_hero_skill, create_innate_skill, HeroSkill, InnateSkill};
 
-/// Get the info on innate skills
-pub fn _get_innate_skills_data(
-    path: String,
-) -> (
+/// Returns a cell of a data sheet row without copying it
+/// Rows are read as bytes and only the cells that are used are checked to be valid utf-8
+fn get_cell(record: &ByteRecord, col: usize) -> Result<&str, String> {
+    let cell = record
+        .get(col)
+        .ok_or_else(|| f!("row has no column {}", col))?;
+    return std::str::from_utf8(cell).map_err(|_| f!("column {} is not valid utf-8", col));
+}
+
+/// Parse a numeric cell of a data sheet row, treating blank or invalid cells as the default value
+fn parse_cell<T: FromStr + Default>(record: &ByteRecord, col: usize) -> Result<T, String> {
+    return Ok(get_cell(record, col)?.parse::<T>().unwrap_or_default());
+}
+
+/// Parse a TRUE/FALSE cell of a data sheet row, ignoring case
+fn parse_bool_cell(record: &ByteRecord, col: usize) -> Result<bool, String> {
+    let cell = get_cell(record, col)?;
+    if cell.eq_ignore_ascii_case("true") {
+        return Ok(true);
+    }
+    if cell.eq_ignore_ascii_case("false") {
+        return Ok(false);
+    }
+    return Err(f!("column {} must be TRUE or FALSE, found {:?}", col, cell));
+}
+
+/// The names of each innate skill's first tier, the first innate skill of each class, and the innate skills, keyed by skill name
+pub type InnateSkillsData = (
     HashMap<String, String>,
     HashMap<String, String>,
     HashMap<String, InnateSkill>,
-) {
+);
+
+/// The names of each hero skill's first tier, and the hero skills, keyed by skill name
+pub type HeroSkillsData = (HashMap<String, String>, HashMap<String, HeroSkill>);
+
+/// Get the info on innate skills
+pub fn _get_innate_skills_data(sheet: impl Read) -> Result<InnateSkillsData, String> {
     let mut skill_tier_1_name_map: HashMap<String, String> = Default::default();
     let mut class_skill_names_map: HashMap<String, String> = Default::default();
     let mut hs_map: HashMap<String, InnateSkill> = Default::default();
@@ -18,65 +50,59 @@ pub fn _get_innate_skills_data(
     let mut reader = csv::ReaderBuilder::new()
         .delimiter(b'\t')
         .has_headers(true)
-        .from_path(path)
-        .unwrap();
-
-    let headers = reader.headers().unwrap().clone();
-    for result in reader.records() {
-        let record = result.unwrap();
+        .from_reader(sheet);
 
+    let headers = reader.byte_headers().map_err(|e| e.to_string())?.clone();
+    let mut record = ByteRecord::new();
+    while reader
+        .read_byte_record(&mut record)
+        .map_err(|e| e.to_string())?
+    {
         let mut classes_allowed: Vec<String> = Default::default();
 
         for col in 24..60usize {
-            if record[col]
-                .to_string()
-                .to_lowercase()
-                .parse::<bool>()
-                .unwrap()
-            {
-                classes_allowed.push(headers[col].to_string());
-                if record[3].to_string().parse::<u8>().unwrap_or_default() == 1 {
-                    class_skill_names_map.insert(headers[col].to_string(), record[1].to_string());
+            if parse_bool_cell(&record, col)? {
+                classes_allowed.push(get_cell(&headers, col)?.to_string());
+                if parse_cell::<u8>(&record, 3)? == 1 {
+                    class_skill_names_map.insert(
+                        get_cell(&headers, col)?.to_string(),
+                        get_cell(&record, 1)?.to_string(),
+                    );
                 }
             }
         }
 
         skill_tier_1_name_map.insert(
-            record[1].to_string(),
-            f!("{} T{}", record[6].to_string(), record[3].to_string()),
+            get_cell(&record, 1)?.to_string(),
+            f!("{} T{}", get_cell(&record, 6)?, get_cell(&record, 3)?),
         );
 
         hs_map.insert(
-            record[1].to_string(),
+            get_cell(&record, 1)?.to_string(),
             create_innate_skill(
-                record[1].to_string(),
-                record[2].to_string(),
-                record[3].to_string().parse::<u8>().unwrap_or_default(),
+                get_cell(&record, 1)?.to_string(),
+                get_cell(&record, 2)?.to_string(),
+                parse_cell::<u8>(&record, 3)?,
                 // 4: description
-                record[5].to_string().parse::<u16>().unwrap_or_default(),
-                record[6].to_string(),
-                record[7]
-                    .to_string()
-                    .to_lowercase()
-                    .parse::<bool>()
-                    .unwrap(),
-                record[8].to_string().parse::<f64>().unwrap_or_default(),
-                record[9].to_string().parse::<f64>().unwrap_or_default(),
-                record[10].to_string().parse::<f64>().unwrap_or_default(),
-                record[11].to_string().parse::<f64>().unwrap_or_default(),
-                record[12].to_string().parse::<f64>().unwrap_or_default(),
-                record[13].to_string().parse::<f64>().unwrap_or_default(),
-                record[14].to_string().parse::<f64>().unwrap_or_default(),
-                record[15].to_string().parse::<f64>().unwrap_or_default(),
-                record[16].to_string().parse::<u16>().unwrap_or_default(),
-                record[17].to_string().parse::<f64>().unwrap_or_default(),
-                record[18].to_string().parse::<f64>().unwrap_or_default(),
-                record[19].to_string().parse::<f64>().unwrap_or_default(),
-                record[20].to_string().parse::<f64>().unwrap_or_default(),
-                record[21].to_string().parse::<f64>().unwrap_or_default(),
-                record[22].to_string().parse::<f64>().unwrap_or_default(),
-                record[23]
-                    .to_string()
+                parse_cell::<u16>(&record, 5)?,
+                get_cell(&record, 6)?.to_string(),
+                parse_bool_cell(&record, 7)?,
+                parse_cell::<f64>(&record, 8)?,
+                parse_cell::<f64>(&record, 9)?,
+                parse_cell::<f64>(&record, 10)?,
+                parse_cell::<f64>(&record, 11)?,
+                parse_cell::<f64>(&record, 12)?,
+                parse_cell::<f64>(&record, 13)?,
+                parse_cell::<f64>(&record, 14)?,
+                parse_cell::<f64>(&record, 15)?,
+                parse_cell::<u16>(&record, 16)?,
+                parse_cell::<f64>(&record, 17)?,
+                parse_cell::<f64>(&record, 18)?,
+                parse_cell::<f64>(&record, 19)?,
+                parse_cell::<f64>(&record, 20)?,
+                parse_cell::<f64>(&record, 21)?,
+                parse_cell::<f64>(&record, 22)?,
+                get_cell(&record, 23)?
                     .split(';')
                     .map(|s| s.to_owned())
                     .collect::<Vec<String>>(),
@@ -85,76 +111,65 @@ pub fn _get_innate_skills_data(
         );
     }
 
-    return (skill_tier_1_name_map, class_skill_names_map, hs_map);
+    return Ok((skill_tier_1_name_map, class_skill_names_map, hs_map));
 }
 
 /// Get the info on hero skills
-pub fn _get_hero_skills_data(
-    path: String,
-) -> (HashMap<String, String>, HashMap<String, HeroSkill>) {
+pub fn _get_hero_skills_data(sheet: impl Read) -> Result<HeroSkillsData, String> {
     let mut skill_tier_1_name_map: HashMap<String, String> = Default::default();
     let mut hs_map: HashMap<String, HeroSkill> = Default::default();
 
     let mut reader = csv::ReaderBuilder::new()
         .delimiter(b'\t')
         .has_headers(true)
-        .from_path(path)
-        .unwrap();
-
-    let headers = reader.headers().unwrap().clone();
-    for result in reader.records() {
-        let record = result.unwrap();
+        .from_reader(sheet);
 
+    let headers = reader.byte_headers().map_err(|e| e.to_string())?.clone();
+    let mut record = ByteRecord::new();
+    while reader
+        .read_byte_record(&mut record)
+        .map_err(|e| e.to_string())?
+    {
         let mut classes_allowed: Vec<String> = Default::default();
 
         for col in 25..61usize {
-            if record[col]
-                .to_string()
-                .to_lowercase()
-                .parse::<bool>()
-                .unwrap()
-            {
-                classes_allowed.push(headers[col].to_string());
+            if parse_bool_cell(&record, col)? {
+                classes_allowed.push(get_cell(&headers, col)?.to_string());
             }
         }
 
         skill_tier_1_name_map.insert(
-            f!("{} T{}", record[6].to_string(), record[3].to_string()),
-            record[1].to_string(),
+            f!("{} T{}", get_cell(&record, 6)?, get_cell(&record, 3)?),
+            get_cell(&record, 1)?.to_string(),
         );
 
         hs_map.insert(
-            record[1].to_string(),
+            get_cell(&record, 1)?.to_string(),
             create_hero_skill(
-                record[1].to_string(),
-                record[2].to_string(),
-                record[3].to_string().parse::<u8>().unwrap_or_default(),
-                record[4].to_string(),
-                record[5].to_string().parse::<u16>().unwrap_or_default(),
-                record[6].to_string(),
-                record[7]
-                    .to_string()
-                    .to_lowercase()
-                    .parse::<bool>()
-                    .unwrap(),
-                record[8].to_string(),
-                record[9].to_string().parse::<f64>().unwrap_or_default(),
-                record[10].to_string().parse::<f64>().unwrap_or_default(),
-                record[11].to_string().parse::<f64>().unwrap_or_default(),
-                record[12].to_string().parse::<f64>().unwrap_or_default(),
-                record[13].to_string().parse::<f64>().unwrap_or_default(),
-                record[14].to_string().parse::<f64>().unwrap_or_default(),
-                record[15].to_string().parse::<f64>().unwrap_or_default(),
-                record[16].to_string().parse::<f64>().unwrap_or_default(),
-                record[17].to_string().parse::<f64>().unwrap_or_default(),
-                record[18].to_string().parse::<f64>().unwrap_or_default(),
-                record[19].to_string().parse::<f64>().unwrap_or_default(),
-                record[20].to_string().parse::<f64>().unwrap_or_default(),
-                record[21].to_string().parse::<f64>().unwrap_or_default(),
-                record[22].to_string().parse::<f64>().unwrap_or_default(),
-                record[23].to_string().parse::<f64>().unwrap_or_default(),
-                record[24]
-                    .to_string()
+                get_cell(&record, 1)?.to_string(),
+                get_cell(&record, 2)?.to_string(),
+                parse_cell::<u8>(&record, 3)?,
+                get_cell(&record, 4)?.to_string(),
+                parse_cell::<u16>(&record, 5)?,
+                get_cell(&record, 6)?.to_string(),
+                parse_bool_cell(&record, 7)?,
+                get_cell(&record, 8)?.to_string(),
+                parse_cell::<f64>(&record, 9)?,
+                parse_cell::<f64>(&record, 10)?,
+                parse_cell::<f64>(&record, 11)?,
+                parse_cell::<f64>(&record, 12)?,
+                parse_cell::<f64>(&record, 13)?,
+                parse_cell::<f64>(&record, 14)?,
+                parse_cell::<f64>(&record, 15)?,
+                parse_cell::<f64>(&record, 16)?,
+                parse_cell::<f64>(&record, 17)?,
+                parse_cell::<f64>(&record, 18)?,
+                parse_cell::<f64>(&record, 19)?,
+                parse_cell::<f64>(&record, 20)?,
+                parse_cell::<f64>(&record, 21)?,
+                parse_cell::<f64>(&record, 22)?,
+                parse_cell::<f64>(&record, 23)?,
+                get_cell(&record, 24)?
                     .split(';')
                     .map(|s| s.to_owned())
                     .collect::<Vec<String>>(),
@@ -163,86 +178,87 @@ pub fn _get_hero_skills_data(
         );
     }
 
-    return (skill_tier_1_name_map, hs_map);
+    return Ok((skill_tier_1_name_map, hs_map));
 }
 
 /// Get the info on hero equipment (e.g. atk, def, etc.) from the Blueprints tab of the Official ST Sheet
-pub fn _get_hero_equipment_data(path: String) -> HashMap<String, Blueprint> {
+pub fn _get_hero_equipment_data(sheet: impl Read) -> Result<HashMap<String, Blueprint>, String> {
     let mut bp_map: HashMap<String, Blueprint> = Default::default();
 
     let mut reader = csv::ReaderBuilder::new()
         .delimiter(b'\t')
         .has_headers(true)
-        .from_path(path)
-        .unwrap();
-    for result in reader.records() {
-        let record = result.unwrap();
-
+        .from_reader(sheet);
+    let mut record = ByteRecord::new();
+    while reader
+        .read_byte_record(&mut record)
+        .map_err(|e| e.to_string())?
+    {
         bp_map.insert(
-            record[0].to_string(),
+            get_cell(&record, 0)?.to_string(),
             create_blueprint(
-                record[0].to_string(),
-                record[1].to_string(),
-                record[2].to_string(),
-                record[3].to_string().parse::<u16>().unwrap_or_default(),
-                record[4].to_string().parse::<u16>().unwrap_or_default(),
-                record[5].to_string().parse::<u8>().unwrap_or_default(),
-                record[6].to_string().parse::<u32>().unwrap_or_default(),
-                record[7].to_string().parse::<u32>().unwrap_or_default(),
-                record[8].to_string(),
-                record[9].to_string().parse::<f64>().unwrap_or_default(),
-                record[10].to_string().parse::<u32>().unwrap_or_default(),
-                record[11].to_string().parse::<f64>().unwrap_or_default(),
-                record[12].to_string().parse::<u32>().unwrap_or_default(),
-                record[13].to_string().parse::<u32>().unwrap_or_default(),
-                record[14].to_string().parse::<u32>().unwrap_or_default(),
-                record[15].to_string().parse::<u32>().unwrap_or_default(),
+                get_cell(&record, 0)?.to_string(),
+                get_cell(&record, 1)?.to_string(),
+                get_cell(&record, 2)?.to_string(),
+                parse_cell::<u16>(&record, 3)?,
+                parse_cell::<u16>(&record, 4)?,
+                parse_cell::<u8>(&record, 5)?,
+                parse_cell::<u32>(&record, 6)?,
+                parse_cell::<u32>(&record, 7)?,
+                get_cell(&record, 8)?.to_string(),
+                parse_cell::<f64>(&record, 9)?,
+                parse_cell::<u32>(&record, 10)?,
+                parse_cell::<f64>(&record, 11)?,
+                parse_cell::<u32>(&record, 12)?,
+                parse_cell::<u32>(&record, 13)?,
+                parse_cell::<u32>(&record, 14)?,
+                parse_cell::<u32>(&record, 15)?,
                 // 16 blank
-                record[17].to_string(),
-                record[18].to_string().parse::<u8>().unwrap_or_default(),
-                record[19].to_string(),
-                record[20].to_string().parse::<u8>().unwrap_or_default(),
-                record[21].to_string(),
-                record[22].to_string().parse::<u8>().unwrap_or_default(),
+                get_cell(&record, 17)?.to_string(),
+                parse_cell::<u8>(&record, 18)?,
+                get_cell(&record, 19)?.to_string(),
+                parse_cell::<u8>(&record, 20)?,
+                get_cell(&record, 21)?.to_string(),
+                parse_cell::<u8>(&record, 22)?,
                 // 23 blank
-                record[24].to_string().parse::<u16>().unwrap_or_default(),
-                record[25].to_string().parse::<u16>().unwrap_or_default(),
-                record[26].to_string().parse::<u16>().unwrap_or_default(),
-                record[27].to_string().parse::<u16>().unwrap_or_default(),
-                record[28].to_string().parse::<u16>().unwrap_or_default(),
-                record[29].to_string().parse::<u16>().unwrap_or_default(),
-                record[30].to_string().parse::<u16>().unwrap_or_default(),
-                record[31].to_string().parse::<u16>().unwrap_or_default(),
-                record[32].to_string().parse::<u16>().unwrap_or_default(),
-                record[33].to_string().parse::<u16>().unwrap_or_default(),
+                parse_cell::<u16>(&record, 24)?,
+                parse_cell::<u16>(&record, 25)?,
+                parse_cell::<u16>(&record, 26)?,
+                parse_cell::<u16>(&record, 27)?,
+                parse_cell::<u16>(&record, 28)?,
+                parse_cell::<u16>(&record, 29)?,
+                parse_cell::<u16>(&record, 30)?,
+                parse_cell::<u16>(&record, 31)?,
+                parse_cell::<u16>(&record, 32)?,
+                parse_cell::<u16>(&record, 33)?,
                 // 34 blank
-                record[35].to_string(),
-                record[36].to_string(),
-                record[37].to_string().parse::<u8>().unwrap_or_default(),
-                record[38].to_string(),
-                record[39].to_string(),
-                record[40].to_string().parse::<u8>().unwrap_or_default(),
+                get_cell(&record, 35)?.to_string(),
+                get_cell(&record, 36)?.to_string(),
+                parse_cell::<u8>(&record, 37)?,
+                get_cell(&record, 38)?.to_string(),
+                get_cell(&record, 39)?.to_string(),
+                parse_cell::<u8>(&record, 40)?,
                 // 41 blank
-                record[42].to_string().parse::<f64>().unwrap_or_default(),
-                record[43].to_string().parse::<f64>().unwrap_or_default(),
-                record[44].to_string().parse::<f64>().unwrap_or_default(),
-                record[45].to_string().parse::<f64>().unwrap_or_default(),
-                record[46].to_string().parse::<f64>().unwrap_or_default(),
+                parse_cell::<f64>(&record, 42)?,
+                parse_cell::<f64>(&record, 43)?,
+                parse_cell::<f64>(&record, 44)?,
+                parse_cell::<f64>(&record, 45)?,
+                parse_cell::<f64>(&record, 46)?,
                 // 47 blank
-                record[48].to_string(),
-                record[49].to_string(),
+                get_cell(&record, 48)?.to_string(),
+                get_cell(&record, 49)?.to_string(),
                 // 50 blank
                 // 51-60: crafting upgrades
                 // 61 blank
                 // 61-67: ascension upgrades
                 // 68 blank
-                record[69].to_string().parse::<u16>().unwrap_or_default(),
-                record[70].to_string().parse::<u16>().unwrap_or_default(),
-                record[71].to_string().parse::<u16>().unwrap_or_default(),
-                record[72].to_string().parse::<u16>().unwrap_or_default(),
+                parse_cell::<u16>(&record, 69)?,
+                parse_cell::<u16>(&record, 70)?,
+                parse_cell::<u16>(&record, 71)?,
+                parse_cell::<u16>(&record, 72)?,
             ),
         );
     }
 
-    return bp_map;
+    return Ok(bp_map);
 }
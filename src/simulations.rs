@@ -1,25 +1,170 @@
+use super::abilities::{EnemyAbilityEffect, EnrageEffect};
+use super::champions::Champion;
+use super::combat_trace::{create_combat_trace, CombatEvent};
 use super::dungeons::Encounter;
 use super::heroes::Team;
+use super::rng::{create_round_rolls, SimRng};
 
 use serde::{Deserialize, Serialize};
 
 use rand::seq::SliceRandom;
-use rand::thread_rng;
 
 use log::info;
 
+/// The number of random rolls generated per hero and for the mob at the start of each round
+const ROLLS_PER_COMBATANT: usize = 6;
+
+/// The number of rounds a fight may last before it ends as a timeout, unless overridden by the trial dungeon
+pub const DEFAULT_MAX_ROUNDS: i16 = 100;
+
+/// The most rounds a fight can be allowed to last, so every per-round tally of a fight stays bounded
+pub const MAX_ROUND_LIMIT: i16 = 1000;
+
+/// Defines the order in which the mob and heroes act within each round
+/// Heroes acting in the same phase always act in the simulation's random attack order
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, strum::Display, strum::EnumString,
+)]
+pub enum InitiativeModel {
+    MobFirst,    // The mob acts, then every hero
+    HeroesFirst, // Every hero acts, then the mob
+    Interleaved, // The first half of the heroes act, then the mob, then the remaining heroes
+    SpeedBased, // Heroes act in order of evasion, the closest analogue to a speed stat, and the mob acts after any heroes with higher evasion than it
+}
+
+/// Defines how the mob chooses the hero hit by its single target attacks, aoe attacks hit every hero regardless
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    Eq,
+    PartialEq,
+    strum::Display,
+    strum::EnumString,
+    strum::EnumIter,
+)]
+pub enum TargetingModel {
+    #[default]
+    ThreatWeighted, // A random living hero, weighted by threat rating
+    LowestHp,   // The living hero with the least hp, the earliest in the party on ties
+    RoundRobin, // Each living hero in party order, continuing after the hero last targeted
+}
+
+/// A step of a combat round, either the mob acting or a group of heroes acting in order
+#[derive(Debug, Clone, PartialEq)]
+enum CombatPhase {
+    Mob,
+    Heroes(Vec<usize>),
+}
+
+impl InitiativeModel {
+    /// Split the random attack order into the phases of a round
+    fn arrange_phases(
+        &self,
+        mut attack_order: Vec<usize>,
+        heroes_evasion: &[f64],
+        mob_evasion: f64,
+    ) -> Vec<CombatPhase> {
+        match self {
+            InitiativeModel::MobFirst => {
+                return vec![CombatPhase::Mob, CombatPhase::Heroes(attack_order)];
+            }
+            InitiativeModel::HeroesFirst => {
+                return vec![CombatPhase::Heroes(attack_order), CombatPhase::Mob];
+            }
+            InitiativeModel::Interleaved => {
+                let second_half = attack_order.split_off(attack_order.len() / 2);
+                return vec![
+                    CombatPhase::Heroes(attack_order),
+                    CombatPhase::Mob,
+                    CombatPhase::Heroes(second_half),
+                ];
+            }
+            InitiativeModel::SpeedBased => {
+                // Stable sort so heroes with equal evasion keep their random order
+                attack_order.sort_by(|a, b| heroes_evasion[*b].total_cmp(&heroes_evasion[*a]));
+                let faster_count = attack_order
+                    .iter()
+                    .filter(|i| heroes_evasion[**i] > mob_evasion)
+                    .count();
+                let slower = attack_order.split_off(faster_count);
+                return vec![
+                    CombatPhase::Heroes(attack_order),
+                    CombatPhase::Mob,
+                    CombatPhase::Heroes(slower),
+                ];
+            }
+        }
+    }
+}
+
+/// Move the heroes that strike first into a phase ahead of the rest of the round, keeping their order
+fn arrange_first_round_phases(
+    phases: &[CombatPhase],
+    first_strikers: &[usize],
+) -> Vec<CombatPhase> {
+    let mut first_round_phases = vec![CombatPhase::Heroes(
+        phases
+            .iter()
+            .flat_map(|phase| match phase {
+                CombatPhase::Heroes(order) => order.clone(),
+                CombatPhase::Mob => vec![],
+            })
+            .filter(|i| first_strikers.contains(i))
+            .collect(),
+    )];
+    for phase in phases {
+        match phase {
+            CombatPhase::Heroes(order) => {
+                let rest: Vec<usize> = order
+                    .iter()
+                    .filter(|i| !first_strikers.contains(i))
+                    .copied()
+                    .collect();
+                if !rest.is_empty() {
+                    first_round_phases.push(CombatPhase::Heroes(rest));
+                }
+            }
+            CombatPhase::Mob => first_round_phases.push(CombatPhase::Mob),
+        }
+    }
+    return first_round_phases;
+}
+
+/// Defines the ways a simulation can end
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, strum::Display)]
+pub enum SimOutcome {
+    Victory, // The mob was reduced to 0 hp
+    Wipe,    // No heroes remain alive
+    Timeout, // The fight was still going after max_rounds
+}
+
 /// A simulated encounter between a Team and a Dungeon
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Simulation {
     team: Team,
     encounter: Encounter,
     metrics: Vec<String>,
+    max_rounds: i16,
+    initiative: InitiativeModel,
+    #[serde(default)]
+    targeting: TargetingModel,
     log_all: bool,
+    #[serde(default)]
+    trace: bool, // Whether to record every attack into the result's combat trace
 }
 
 impl Simulation {
-    pub fn run(&mut self) -> Result<SimResult, &'static str> {
+    pub fn set_trace(&mut self, trace: bool) {
+        self.trace = trace;
+    }
+
+    pub fn run(&mut self, rng: &mut SimRng) -> Result<SimResult, &'static str> {
         let mut log_queue: Vec<String> = vec![];
+        let mut trace = create_combat_trace(self.trace);
         log_queue.push("Start of Simulation".to_string());
         // If encounter.is_boss then ignore Mundra
         // Error if more heroes in team than encounter allows
@@ -28,8 +173,9 @@ impl Simulation {
         let (is_extreme, is_boss) = self.encounter.is_extreme_or_boss();
         self.team.normalize_percents(is_extreme, is_boss);
 
-        // Polonia Loot
-        let mut polonia_loot_cap_hit = 0;
+        // Polonia Loot, stolen throughout the fight but only received if the fight is won
+        let mut polonia_loot = 0;
+        let mut polonia_loot_cap_hit = false;
         let mut polonia_loot_total = 0;
 
         let (champion, champion_innate_tier) = self.team.get_champion_info();
@@ -47,6 +193,7 @@ impl Simulation {
         // Simulate Encounter
         let mut cont_fight = true;
         let mut won_fight = false;
+        let mut timed_out = false;
 
         self.team
             .initialize_survive_chance_hemma_guaranteed_crit_and_berserker_stage();
@@ -56,24 +203,50 @@ impl Simulation {
         let mut shark_active = 0;
         let mut dinosaur_active = 1;
         let mut lord_save = true;
-        let mut rudo_bonus = 0f64;
-
-        if champion == "Rudo" {
-            match champion_innate_tier {
-                1u8 => rudo_bonus = 0.3,
-                2u8 => rudo_bonus = 0.4,
-                3u8 => rudo_bonus = 0.4,
-                4u8 => rudo_bonus = 0.5,
-                _ => (),
-            }
-        }
 
         self.team.apply_class_special_effects();
 
-        // Generate Random Attack Order
+        let (_, num_rogues, _, _) = self.team.get_num_archetypes();
+        let crit_chance_aura = champion.map_or(0.0, |_| {
+            return self
+                .team
+                .get_champion_data()
+                .get_crit_chance_aura(champion_innate_tier, num_rogues);
+        });
+        if crit_chance_aura > 0.0 {
+            log_queue.push(f!(
+                "Champion aura adds {:.2} to every hero's crit chance",
+                crit_chance_aura
+            ));
+        }
+
+        // Generate Random Attack Order and arrange it into phases according to the initiative model
         let mut attack_order: Vec<usize> = (0..self.team.get_heroes_len()).collect();
-        let mut rng = thread_rng();
-        attack_order.shuffle(&mut rng);
+        attack_order.shuffle(rng);
+        let phases = self.initiative.arrange_phases(
+            attack_order,
+            &self.team.get_heroes_evasion(),
+            self.encounter.get_evasion(),
+        );
+        log_queue.push(f!(
+            "Initiative is {}, round phases are {:?}",
+            self.initiative,
+            phases
+        ));
+        log_queue.push(f!("Mob targeting is {}", self.targeting));
+        let first_strikers = self.team.get_first_strikers();
+        let first_round_phases = if first_strikers.is_empty() {
+            phases.clone()
+        } else {
+            let first_round_phases = arrange_first_round_phases(&phases, &first_strikers);
+            log_queue.push(f!(
+                "Heroes at indices {:?} strike first, first round phases are {:?}",
+                first_strikers,
+                first_round_phases
+            ));
+            first_round_phases
+        };
+        let has_damage_reduction_aura = self.team.has_damage_reduction_aura();
 
         self.encounter.init_barrier_modifier();
 
@@ -84,12 +257,29 @@ impl Simulation {
         let mut heroes_alive = self.team.get_heroes_len();
 
         log_queue.push("Ready to start quest with:".to_string());
-        log_queue.push(f!("{:#?}", self.encounter.round_floats_for_display()));
-        log_queue.push(f!("{:#?}", self.team.round_floats_for_display()));
+        log_queue.push(f!("{}", self.encounter));
+        log_queue.push(f!("{}", self.team));
+        if let Some(champion) = champion.filter(|champion| !champion.is_modeled()) {
+            log_queue.push(f!("Champion {}'s ability is not simulated", champion));
+        }
+
+        // Rolls are generated in one batch per round, sized for a few rolls per combatant
+        let mut rolls =
+            create_round_rolls(rng, ROLLS_PER_COMBATANT * (self.team.get_heroes_len() + 1));
 
         // START QUEST
         while cont_fight {
             round += 1;
+            if round > 1 {
+                rolls.refill();
+            }
+            trace.set_round(round);
+            let rudo_bonus = champion.map_or(0.0, |_| {
+                return self
+                    .team
+                    .get_champion_data()
+                    .get_break_chance_bonus(champion_innate_tier, round);
+            });
             let heroes_hp_strings = self.team.get_heroes_hp_as_strings();
             let (temp_ehp, temp_mehp) = self.encounter.get_hp_info();
             log_queue.push(f!(
@@ -100,6 +290,23 @@ impl Simulation {
                 heroes_hp_strings
             ));
 
+            // Check Enrage
+            match self.encounter.check_enrage(round) {
+                Some(EnrageEffect::Wipe) => {
+                    log_queue.push("Mob enrages and wipes out the team".to_string());
+                    log_queue.extend(self.team.wipe());
+                    break;
+                }
+                Some(EnrageEffect::DamageMultiplier { multiplier }) => {
+                    log_queue.push(f!("Mob enrages, damage multiplied by {}", multiplier));
+                    self.encounter.multiply_damage(multiplier);
+                    let (enraged_damage, _) = self.encounter.get_damage_info();
+                    self.team
+                        .calculate_damage_from_encounter(encounter_defense_cap, enraged_damage);
+                }
+                None => (),
+            }
+
             if update_target {
                 target_chance_heroes = self.team.calculate_targeting_chances();
                 update_target = false;
@@ -113,76 +320,184 @@ impl Simulation {
                 .update_ninja_bonus_and_extreme_crit_bonus(round, is_extreme);
             log_queue.extend(update_ninja_extreme_bonuses_logs);
 
-            // Mob Attacks
+            let mut round_effects_applied = false;
+            let round_phases = if round == 1 {
+                &first_round_phases
+            } else {
+                &phases
+            };
+            for phase in round_phases.iter() {
+                match phase {
+                    CombatPhase::Mob => {
+                        // Damage reduction auras only protect the team while the heroes providing them are alive
+                        if has_damage_reduction_aura {
+                            let (current_damage, _) = self.encounter.get_damage_info();
+                            self.team.calculate_damage_from_encounter(
+                                encounter_defense_cap,
+                                current_damage,
+                            );
+                        }
+                        // Mob AOE, each enemy of the pack attacks in turn
+                        let (aoe_chance, aoe_damage) = self.encounter.get_aoe_info();
+                        let (crit_chance, crit_chance_modifier) = self.encounter.get_crit_info();
+                        let enemies_alive = self.encounter.count_enemies_alive();
+                        if self.encounter.get_pack_size() > 1 {
+                            log_queue.push(f!("{} enemies of the pack attack", enemies_alive));
+                        }
+                        for _ in 0..enemies_alive {
+                            if heroes_alive == 0 {
+                                break;
+                            }
+                            if update_target {
+                                target_chance_heroes = self.team.calculate_targeting_chances();
+                                update_target = false;
+                            }
+                            let (temp1, temp2, temp3, temp4) = self.team.calculate_mob_attack(
+                                aoe_chance,
+                                aoe_damage,
+                                heroes_alive,
+                                lord_save,
+                                round,
+                                update_target,
+                                target_chance_heroes,
+                                self.targeting,
+                                crit_chance,
+                                crit_chance_modifier,
+                                &mut rolls,
+                                &mut trace,
+                            );
+                            heroes_alive = temp1;
+                            lord_save = temp2;
+                            update_target = temp3;
+                            log_queue.extend(temp4);
+                        }
+
+                        // Mob Abilities
+                        for ability in self.encounter.take_triggered_abilities(round) {
+                            log_queue.push(f!("Mob uses ability {}", ability.get_name()));
+                            match ability.get_effect() {
+                                EnemyAbilityEffect::ArmorShred { defense_reduction } => {
+                                    log_queue
+                                        .extend(self.team.apply_armor_shred(defense_reduction));
+                                    let (current_damage, _) = self.encounter.get_damage_info();
+                                    self.team.calculate_damage_from_encounter(
+                                        encounter_defense_cap,
+                                        current_damage,
+                                    );
+                                }
+                                EnemyAbilityEffect::Slam {
+                                    damage_multiplier,
+                                    max_targets,
+                                } => {
+                                    let (temp1, temp2, temp3) = self.team.calculate_mob_slam(
+                                        damage_multiplier,
+                                        max_targets,
+                                        heroes_alive,
+                                        &mut rolls,
+                                        &mut trace,
+                                    );
+                                    heroes_alive = temp1;
+                                    update_target = update_target || temp2;
+                                    log_queue.extend(temp3);
+                                }
+                                EnemyAbilityEffect::SelfHeal { hp_fraction } => {
+                                    log_queue.push(self.encounter.heal(hp_fraction));
+                                }
+                            }
+                        }
+                    }
+                    CombatPhase::Heroes(phase_order) => {
+                        // Round effects apply once, before the first heroes attack of the round
+                        if !round_effects_applied {
+                            if champion == Some(Champion::Hemma) {
+                                let hemma_log_queue = self.team.calculate_hemma_drain(
+                                    champion_innate_tier,
+                                    hemma_mult,
+                                    round,
+                                );
+                                log_queue.extend(hemma_log_queue);
+                            }
+
+                            let bnsroundeffects_log_queue = self
+                                .team
+                                .calculate_berserker_ninja_samurai_round_effects(round);
+                            log_queue.extend(bnsroundeffects_log_queue);
+                            round_effects_applied = true;
+                        }
+
+                        // Heroes Attack
+                        let (barrier_hp, barrier_hp_max, barrier_modifier, barrier_type) =
+                            self.encounter.get_barrier_info();
+                        let encounter_evasion = self.encounter.get_evasion();
+                        let (encounter_hp, encounter_hp_max) = self.encounter.get_hp_info();
+                        let (
+                            phase_polonia_loot,
+                            barrier_modifier,
+                            barrier_hp,
+                            encounter_hp,
+                            temp1,
+                            hero_attack_log_queue,
+                        ) = self.team.calculate_heroes_attack(
+                            phase_order.clone(),
+                            round,
+                            rudo_bonus,
+                            crit_chance_aura,
+                            shark_active,
+                            dinosaur_active,
+                            barrier_modifier,
+                            count_loot,
+                            loot_chance,
+                            encounter_evasion,
+                            encounter_hp,
+                            barrier_hp,
+                            barrier_hp_max,
+                            encounter_hp_max,
+                            barrier_type,
+                            &self.encounter.get_elemental_modifiers(),
+                            &mut rolls,
+                            &mut trace,
+                        );
+                        polonia_loot += phase_polonia_loot;
+                        shark_active = temp1;
+                        log_queue.extend(hero_attack_log_queue);
+
+                        self.encounter
+                            .set_barrier_hp_and_modifier(barrier_hp, barrier_modifier);
+                        self.encounter.set_hp(encounter_hp);
+                        log_queue.push("(Meta-Info) Barrier HP, Modifier and Encounter HP have been applied back to their objects".to_string());
+                    }
+                }
 
-            // Mob AOE
-            let (aoe_chance, aoe_damage) = self.encounter.get_aoe_info();
-            let (crit_chance, crit_chance_modifier) = self.encounter.get_crit_info();
-            let (temp1, temp2, temp3, temp4) = self.team.calculate_mob_attack(
-                aoe_chance,
-                aoe_damage,
-                heroes_alive,
-                lord_save,
-                round,
-                update_target,
-                target_chance_heroes,
-                crit_chance,
-                crit_chance_modifier,
-            );
-            heroes_alive = temp1;
-            lord_save = temp2;
-            update_target = temp3;
-            log_queue.extend(temp4);
-
-            if champion == "Hemma" {
-                let hemma_log_queue =
-                    self.team
-                        .calculate_hemma_drain(champion_innate_tier, hemma_mult, round);
-                log_queue.extend(hemma_log_queue);
+                // A side that has been defeated takes no further actions this round
+                if heroes_alive == 0 || self.encounter.get_hp_info().0 <= 0.0 {
+                    break;
+                }
             }
 
-            let bnsroundeffects_log_queue = self
-                .team
-                .calculate_berserker_ninja_samurai_round_effects(round);
-            log_queue.extend(bnsroundeffects_log_queue);
-
-            // Heroes Attack
-            let (barrier_hp, barrier_hp_max, barrier_modifier, barrier_type) =
-                self.encounter.get_barrier_info();
-            let encounter_evasion = self.encounter.get_evasion();
-            let (encounter_hp, encounter_hp_max) = self.encounter.get_hp_info();
-            let (
-                polonia_loot,
-                barrier_modifier,
-                barrier_hp,
-                encounter_hp,
-                temp1,
-                hero_attack_log_queue,
-            ) = self.team.calculate_heroes_attack(
-                attack_order.clone(),
-                round,
-                rudo_bonus,
-                shark_active,
-                dinosaur_active,
-                barrier_modifier,
-                count_loot,
-                loot_chance,
-                encounter_evasion,
-                encounter_hp,
-                barrier_hp,
-                barrier_hp_max,
-                encounter_hp_max,
-                barrier_type,
-            );
-            shark_active = temp1;
-            log_queue.extend(hero_attack_log_queue);
-
-            self.encounter
-                .set_barrier_hp_and_modifier(barrier_hp, barrier_modifier);
-            self.encounter.set_hp(encounter_hp);
-            log_queue.push("(Meta-Info) Barrier HP, Modifier and Encounter HP have been applied back to their objects".to_string());
+            // Pets act after both sides, while the fight continues
+            if heroes_alive > 0 && self.encounter.get_hp_info().0 > 0.0 {
+                let (_, _, barrier_modifier, _) = self.encounter.get_barrier_info();
+                let (encounter_hp, pet_log_queue) = self.team.calculate_pet_procs(
+                    self.encounter.get_hp_info().0,
+                    barrier_modifier,
+                    &mut rolls,
+                    &mut trace,
+                );
+                self.encounter.set_hp(encounter_hp);
+                log_queue.extend(pet_log_queue);
+
+                // Revives bring fallen heroes back once the round's attacks are over
+                let (revived_qty, revive_log_queue) = self.team.calculate_revives(&mut trace);
+                if revived_qty > 0 {
+                    heroes_alive += revived_qty;
+                    update_target = true;
+                }
+                log_queue.extend(revive_log_queue);
+            }
 
+            self.team.record_rounds_survived(round);
             dinosaur_active = 0;
+            let (encounter_hp, _) = self.encounter.get_hp_info();
 
             // Check won
             if encounter_hp <= 0.0 {
@@ -197,29 +512,14 @@ impl Simulation {
                 log_queue.push("No heroes remain alive".to_string());
             }
 
-            // Calculate polonia loot
-            if cont_fight == false {
-                polonia_loot_total += std::cmp::min(polonia_loot, polonia_loot_cap);
-                if polonia_loot >= polonia_loot_cap {
-                    polonia_loot_cap_hit += 1;
-                }
-                log_queue.push(f!(
-                    "Polonia loot received {} of {}",
-                    polonia_loot,
-                    polonia_loot_cap
-                ));
+            // Check timeout
+            if cont_fight && round >= self.max_rounds {
+                cont_fight = false;
+                timed_out = true;
+                log_queue.push(f!("Fight timed out after {} rounds", round));
             }
 
-            if champion_innate_tier == 1 && round == 2 {
-                rudo_bonus = 0.0;
-            }
-            if (champion_innate_tier == 2 || champion_innate_tier == 3) && round == 3 {
-                rudo_bonus = 0.0;
-            }
-            if champion_innate_tier == 4 && round == 4 {
-                rudo_bonus = 0.0;
-            }
-            if champion == "Rudo" {
+            if champion == Some(Champion::Rudo) {
                 log_queue.push(f!(
                     "Round is {}, Rudo bonus to break chance is: {}",
                     round,
@@ -229,9 +529,7 @@ impl Simulation {
 
             // Healing from Lizard, Cleric, and Lilo
             if cont_fight {
-                let healing_log_queue = self
-                    .team
-                    .calculate_healing(champion.clone(), champion_innate_tier);
+                let healing_log_queue = self.team.calculate_healing(champion, champion_innate_tier);
                 log_queue.extend(healing_log_queue);
             }
 
@@ -240,12 +538,34 @@ impl Simulation {
             log_queue.extend(berserker_log_queue);
         }
 
+        // Calculate polonia loot, after the loop so fights ended by an enrage wipe are counted too
+        if count_loot {
+            if won_fight {
+                polonia_loot_total = std::cmp::min(polonia_loot, polonia_loot_cap);
+            }
+            polonia_loot_cap_hit = polonia_loot >= polonia_loot_cap;
+            log_queue.push(f!(
+                "Polonia loot stolen {} of {}, received {}",
+                polonia_loot,
+                polonia_loot_cap,
+                polonia_loot_total
+            ));
+        }
+
         // TODO If key in metrics then add else skip
         let (ehprem, emaxhp) = self.encounter.get_hp_info();
         let (team_crits_taken, team_crits_dealt, team_dodges, team_attacks_missed) =
             self.team.get_heroes_accuracy_stats();
+        let outcome = if won_fight {
+            SimOutcome::Victory
+        } else if timed_out {
+            SimOutcome::Timeout
+        } else {
+            SimOutcome::Wipe
+        };
         let res = SimResult {
             success: won_fight,
+            outcome,
             rounds_elapsed: round,
             team_dmg_taken: vec![0i16],
             team_dmg_dealt: vec![0i16],
@@ -270,18 +590,18 @@ impl Simulation {
             team_crits_dealt,
             team_dodges,
             team_attacks_missed,
+            trace: trace.into_events(),
         };
 
         if won_fight {
             log_queue.push("Won Simulation".to_string());
         } else {
-            log_queue.push("Lost Simulation".to_string());
+            log_queue.push(f!("Lost Simulation ({})", outcome));
         }
 
+        // The whole trace is logged as a single record, rather than a record per line
         if self.log_all || !won_fight {
-            for item in log_queue {
-                info!("{}", item);
-            }
+            info!("\n\n{}", log_queue.join("\n"));
         }
         return Ok(res);
     }
@@ -293,13 +613,24 @@ pub fn create_simulation(
     team: &Team,
     encounter: Encounter,
     metrics: Vec<String>,
+    max_rounds: i16,
+    initiative: InitiativeModel,
+    targeting: TargetingModel,
     log_all: bool,
 ) -> Result<Simulation, &'static str> {
+    if !(1..=MAX_ROUND_LIMIT).contains(&max_rounds) {
+        return Err("max_rounds must be within range 1-1000 inclusive");
+    }
+
     let simulation = Simulation {
         team: team.clone(),
         encounter,
         metrics,
+        max_rounds,
+        initiative,
+        targeting,
         log_all,
+        trace: false,
     };
 
     return Ok(simulation);
@@ -309,6 +640,7 @@ pub fn create_simulation(
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SimResult {
     success: bool,
+    outcome: SimOutcome,
     rounds_elapsed: i16,
     team_dmg_taken: Vec<i16>,
     team_dmg_dealt: Vec<i16>,
@@ -327,15 +659,17 @@ pub struct SimResult {
     // other
     team: Team,
     encounter: Encounter,
-    polonia_loot_total: u8,
-    polonia_loot_cap_hit: i32,
+    polonia_loot_total: u8, // The loot stolen by Polonia and received for winning, 0 if the fight was lost
+    polonia_loot_cap_hit: bool, // Whether Polonia stole as much loot as she can in one fight
     encounter_hp_remaining: f64,
     encounter_max_hp: f64,
     // team accuracy stats
-    team_crits_taken: Vec<u8>,
-    team_crits_dealt: Vec<u8>,
-    team_dodges: Vec<u8>,
-    team_attacks_missed: Vec<u8>,
+    team_crits_taken: Vec<u32>,
+    team_crits_dealt: Vec<u32>,
+    team_dodges: Vec<u32>,
+    team_attacks_missed: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    trace: Vec<CombatEvent>, // Every attack of the fight, empty unless the simulation was traced
 }
 
 impl SimResult {
@@ -343,10 +677,18 @@ impl SimResult {
         return self.success;
     }
 
+    pub fn get_outcome(&self) -> SimOutcome {
+        return self.outcome;
+    }
+
     pub fn get_rounds(&self) -> i16 {
         return self.rounds_elapsed;
     }
 
+    pub fn get_trace(&self) -> &[CombatEvent] {
+        return &self.trace;
+    }
+
     pub fn get_encounter(&self) -> Encounter {
         return self.encounter.clone();
     }
@@ -368,6 +710,19 @@ impl SimResult {
         return self.damage_dealt_during_fight.clone();
     }
 
+    /// Returns whether any hero was defeated during the fight, which sends them to rest for longer
+    pub fn is_any_hero_defeated(&self) -> bool {
+        return self.team.get_heroes_hp().iter().any(|hp| *hp <= 0.0);
+    }
+
+    pub fn get_polonia_loot(&self) -> u8 {
+        return self.polonia_loot_total;
+    }
+
+    pub fn is_polonia_loot_cap_hit(&self) -> bool {
+        return self.polonia_loot_cap_hit;
+    }
+
     pub fn get_encounter_hp_remaining(&self) -> f64 {
         return self.encounter_hp_remaining;
     }
@@ -376,25 +731,41 @@ impl SimResult {
         return convert_vec_to_max_team_sized_array(self.team.get_heroes_hp());
     }
 
+    pub fn get_team_hp_lost(&self) -> [f64; 5] {
+        return convert_vec_to_max_team_sized_array(self.team.get_heroes_hp_lost());
+    }
+
     pub fn get_team_damage_dealt(&self) -> [f64; 5] {
         return convert_vec_to_max_team_sized_array(self.get_damage_dealt_during_fight());
     }
 
-    pub fn get_team_crits_taken(&self) -> [u8; 5] {
+    pub fn get_team_healing_done(&self) -> [f64; 5] {
+        return convert_vec_to_max_team_sized_array(self.team.get_heroes_healing_done());
+    }
+
+    pub fn get_team_crits_taken(&self) -> [u32; 5] {
         return convert_vec_to_max_team_sized_array(self.team.get_heroes_accuracy_stats().0);
     }
 
-    pub fn get_team_crits_dealt(&self) -> [u8; 5] {
+    pub fn get_team_crits_dealt(&self) -> [u32; 5] {
         return convert_vec_to_max_team_sized_array(self.team.get_heroes_accuracy_stats().1);
     }
 
-    pub fn get_team_dodges(&self) -> [u8; 5] {
+    pub fn get_team_dodges(&self) -> [u32; 5] {
         return convert_vec_to_max_team_sized_array(self.team.get_heroes_accuracy_stats().2);
     }
 
-    pub fn get_team_attacks_missed(&self) -> [u8; 5] {
+    pub fn get_team_attacks_missed(&self) -> [u32; 5] {
         return convert_vec_to_max_team_sized_array(self.team.get_heroes_accuracy_stats().3);
     }
+
+    pub fn get_team_rounds_survived(&self) -> [i16; 5] {
+        return convert_vec_to_max_team_sized_array(self.team.get_heroes_rounds_survived());
+    }
+
+    pub fn get_team_procs(&self) -> [u32; 5] {
+        return convert_vec_to_max_team_sized_array(self.team.get_heroes_procs());
+    }
 }
 
 /// input_vector is converted to an array sized to match the max team size.
@@ -411,3 +782,39 @@ fn convert_vec_to_max_team_sized_array<T: Default + Clone>(input_vector: Vec<T>)
     }
     return res_array;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn initiative_models_arrange_the_attack_order_into_phases() {
+        let attack_order = vec![2, 0, 3, 1];
+        let heroes_evasion = [0.1, 0.4, 0.3, 0.05];
+
+        assert_eq!(
+            InitiativeModel::MobFirst.arrange_phases(attack_order.clone(), &heroes_evasion, 0.2),
+            vec![CombatPhase::Mob, CombatPhase::Heroes(vec![2, 0, 3, 1])]
+        );
+        assert_eq!(
+            InitiativeModel::HeroesFirst.arrange_phases(attack_order.clone(), &heroes_evasion, 0.2),
+            vec![CombatPhase::Heroes(vec![2, 0, 3, 1]), CombatPhase::Mob]
+        );
+        assert_eq!(
+            InitiativeModel::Interleaved.arrange_phases(attack_order.clone(), &heroes_evasion, 0.2),
+            vec![
+                CombatPhase::Heroes(vec![2, 0]),
+                CombatPhase::Mob,
+                CombatPhase::Heroes(vec![3, 1])
+            ]
+        );
+        assert_eq!(
+            InitiativeModel::SpeedBased.arrange_phases(attack_order, &heroes_evasion, 0.2),
+            vec![
+                CombatPhase::Heroes(vec![1, 2]),
+                CombatPhase::Mob,
+                CombatPhase::Heroes(vec![0, 3])
+            ]
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+
+use crate::{
+    data_sources::DataSource,
+    dungeons::{Dungeon, TrialDungeon},
+    equipment::BoosterType,
+    hero_builder::{add_missing_class_innate_skills, Hero},
+    heroes::{create_team, SimHero},
+    inputs::HeroInput,
+    progress::ProgressReporter,
+    quests::create_reward_quests,
+    rng::RngConfig,
+    statistics::StatisticsConfig,
+    studies::{create_skill_variations, HeroBuilderInformation, StudyOutput},
+    study_config::StudyConfig,
+    trials::{create_trial, TrialResult},
+};
+
+/// Every piece of game data simulations draw on, as the command line tool loads from the data sheets and input files
+/// The hero builder information holds it all, including champion data, reward quests, and seed values
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameData {
+    pub hero_builder_information: HeroBuilderInformation,
+}
+
+/// Load every piece of game data from a data source, such as an embedded source holding the files in memory
+/// Fails with the name of the file on missing or malformed data
+pub fn load_game_data(data_source: &dyn DataSource) -> Result<GameData, String> {
+    let hero_classes = data_source.get_hero_classes()?;
+    let (hero_skill_tier_1_name_map, hero_skill_map) = data_source.get_hero_skills()?;
+    let (_, mut class_innate_skill_names_map, innate_skill_map) =
+        data_source.get_innate_skills()?;
+    add_missing_class_innate_skills(&mut class_innate_skill_names_map, &hero_classes);
+    return Ok(GameData {
+        hero_builder_information: HeroBuilderInformation {
+            bp_map: data_source.get_blueprints()?,
+            hero_classes,
+            hero_skill_tier_1_name_map,
+            hero_skill_map,
+            class_innate_skill_names_map,
+            innate_skill_map,
+            pet_skill_map: data_source.get_pet_skills()?,
+            spirit_map: data_source.get_spirits()?,
+            elements: data_source.get_elements()?,
+            seed_values: data_source.get_seed_values()?,
+            champion_data: data_source.get_champion_data()?,
+            reward_quests: data_source
+                .get_quests()?
+                .map(create_reward_quests)
+                .unwrap_or_default(),
+            build_cache: Default::default(),
+        },
+    });
+}
+
+/// Runs simulations of builder heroes entirely in memory, reading and writing no files, so the simulator can be embedded in other applications
+pub struct Simulator {
+    game_data: HeroBuilderInformation,
+    heroes: HashMap<String, Hero>,
+    rng: RngConfig, // The generator and seed every trial and study of the simulator draws from
+    statistics: StatisticsConfig, // How every trial and study of the simulator computes its intervals
+}
+
+/// Each simulator keeps its own game data, so simulators created from different game data don't affect each other
+/// Every hero is validated and scaled by its class like heroes loaded from a hero csv, failing on the first hero that can't be built
+pub fn create_simulator(game_data: GameData, heroes: Vec<Hero>) -> Result<Simulator, String> {
+    let game_data = game_data.hero_builder_information;
+    let mut heroes_map: HashMap<String, Hero> = HashMap::new();
+    for mut hero in heroes {
+        prepare_hero(&mut hero, &game_data)?;
+        if heroes_map.insert(hero.get_identifier(), hero).is_some() {
+            return Err(String::from("hero identifiers must be unique"));
+        }
+    }
+    return Ok(Simulator {
+        game_data,
+        heroes: heroes_map,
+        rng: RngConfig::default(),
+        statistics: StatisticsConfig::default(),
+    });
+}
+
+/// Validate a hero's equipment and rank, and set its innate tier and its base stats from its class at its level
+fn prepare_hero(hero: &mut Hero, game_data: &HeroBuilderInformation) -> Result<(), String> {
+    let identifier = hero.get_identifier();
+    hero.validate_equipment(&game_data.bp_map, &game_data.hero_classes)
+        .and_then(|_| hero.get_rank_scaling())
+        .map_err(|e| f!("{} for hero {}", e, identifier))?;
+    hero.calculate_innate_tier(
+        &game_data.class_innate_skill_names_map,
+        &game_data.innate_skill_map,
+        &game_data.champion_data,
+    )
+    .map_err(|e| f!("{} for hero {}", e, identifier))?;
+    return hero
+        .scale_by_class(&game_data.hero_classes)
+        .map_err(|e| f!("{} for hero {}", e, identifier));
+}
+
+impl Simulator {
+    /// Draw every simulation from the given generator and seed, so results can be reproduced exactly
+    pub fn set_rng(&mut self, rng: RngConfig) {
+        self.rng = rng;
+    }
+
+    /// Compute trial intervals and adaptive simulation qtys from the given config
+    pub fn set_statistics(&mut self, statistics: StatisticsConfig) -> Result<(), &'static str> {
+        statistics.validate()?;
+        self.statistics = statistics;
+        return Ok(());
+    }
+
+    pub fn get_hero(&self, identifier: &str) -> Option<&Hero> {
+        return self.heroes.get(identifier);
+    }
+
+    /// Validate a hero's build, such as one decoded from a build code, and run the gear/skill stat pipeline on it, returning the SimHero it fights as
+    /// Unlike build_sim_hero the hero doesn't need to be one the simulator was created with
+    pub fn build_hero(&self, input: HeroInput) -> Result<SimHero, String> {
+        return self.build(Hero::from(input));
+    }
+
+    /// Run the gear/skill stat pipeline on one of the simulator's heroes, returning the SimHero it fights as
+    pub fn build_sim_hero(&self, identifier: &str) -> Result<SimHero, String> {
+        let hero = self
+            .get_hero(identifier)
+            .ok_or("no hero has that identifier")?;
+        return self.build(hero.clone());
+    }
+
+    /// The pipeline build_hero and build_sim_hero share, so a hero builds the same whether it's passed in or one of the simulator's
+    fn build(&self, mut hero: Hero) -> Result<SimHero, String> {
+        prepare_hero(&mut hero, &self.game_data)?;
+        let identifier = hero.get_identifier();
+        return self
+            .game_data
+            .build_sim_hero(hero)
+            .map_err(|e| f!("{} for hero {}", e, identifier));
+    }
+
+    /// Simulate a party of heroes, in the given order, against a dungeon
+    pub fn simulate(
+        &self,
+        hero_identifiers: &[&str],
+        booster: Option<BoosterType>,
+        trial_dungeon: &TrialDungeon,
+        simulation_qty: usize,
+    ) -> Result<TrialResult, String> {
+        let party = hero_identifiers
+            .iter()
+            .map(|identifier| self.build_sim_hero(identifier))
+            .collect::<Result<Vec<SimHero>, String>>()?;
+        return Ok(self.run_trial(
+            hero_identifiers.join(", "),
+            party,
+            booster,
+            trial_dungeon,
+            simulation_qty,
+        )?);
+    }
+
+    /// Simulate every combination of skills for a subject hero, returning the result of each combination with the best success rate first
+    /// Like the static party skill study, but nothing is saved, the results are only returned
+    pub fn run_skill_study(
+        &self,
+        study: &SkillStudyDefinition,
+    ) -> Result<Vec<TrialResult>, String> {
+        let subject_hero = self
+            .get_hero(&study.subject_hero)
+            .ok_or("no hero has the subject hero's identifier")?;
+        let skill_slot_qty = subject_hero.count_skill_slots_unlocked();
+        if study.preset_skills.len() > skill_slot_qty {
+            return Err(String::from(
+                "more preset skills than the subject hero has skill slots unlocked at its rank",
+            ));
+        }
+        let party = study
+            .party
+            .iter()
+            .map(|identifier| self.build_sim_hero(identifier))
+            .collect::<Result<Vec<SimHero>, String>>()?;
+
+        let mut skill_variations = create_skill_variations(
+            study.valid_skills.clone(),
+            study.preset_skills.clone(),
+            skill_slot_qty,
+            &self.game_data.hero_skill_map,
+        );
+        let mut results: Vec<TrialResult> = vec![];
+        while skill_variations.count_remaining() > 0 {
+            let skill_variation =
+                skill_variations.get_full_translated_skillset_at_current_combination_index();
+            let mut new_hero = subject_hero.clone();
+            new_hero.set_hero_skills(skill_variation.clone());
+            let mut heroes = party.clone();
+            heroes.push(self.build(new_hero)?);
+            results.push(self.run_trial(
+                format!("{:?}", skill_variation),
+                heroes,
+                study.booster,
+                &study.trial_dungeon,
+                study.simulation_qty,
+            )?);
+            skill_variations.increment_combination_index();
+        }
+        results.sort_by(|a, b| b.get_success_rate().total_cmp(&a.get_success_rate()));
+        return Ok(results);
+    }
+
+    /// Simulate a party of heroes already built, such as by build_hero, in the given order against a dungeon
+    pub fn run_trial(
+        &self,
+        description: String,
+        heroes: Vec<SimHero>,
+        booster: Option<BoosterType>,
+        trial_dungeon: &TrialDungeon,
+        simulation_qty: usize,
+    ) -> Result<TrialResult, &'static str> {
+        let mut trial = create_trial(
+            String::from("Simulator"),
+            description,
+            simulation_qty,
+            create_team(heroes, booster)?,
+            trial_dungeon.dungeon.clone(),
+            vec![trial_dungeon.difficulty],
+            trial_dungeon.force_minibosses,
+            trial_dungeon.max_rounds,
+            trial_dungeon.initiative,
+            trial_dungeon.targeting,
+            false,
+        )?;
+        trial.set_reward_quest(
+            self.game_data
+                .find_reward_quest(
+                    &trial_dungeon.dungeon._get_zone(),
+                    &[trial_dungeon.difficulty],
+                )
+                .cloned(),
+        );
+        trial.set_rng(self.rng);
+        trial.set_statistics(self.statistics);
+        trial.run_simulations_single_threaded();
+        return Ok(trial.create_trial_result());
+    }
+
+    /// Run the study a study config describes on the simulator's heroes, reporting its progress, and return its results
+    /// The config's rosters are ignored, and like the simulator's other studies nothing is saved or printed
+    pub fn run_study(
+        &self,
+        config: &StudyConfig,
+        dungeons: &HashMap<String, Dungeon>,
+        reporter: &mut dyn ProgressReporter,
+    ) -> Result<StudyResults, String> {
+        let mut study = config.create_study_with_heroes(
+            &self.heroes,
+            dungeons,
+            self.game_data.clone(),
+            self.rng,
+            self.statistics,
+        )?;
+        study.get_study_mut().set_output(StudyOutput {
+            files: false,
+            stdout: false,
+        });
+        study.run_with_progress(reporter);
+        let study = study.get_study();
+        return Ok(StudyResults {
+            trial_qty: study.get_trial_qty(),
+            best_trial_result: study.get_best_trial_result().cloned(),
+            runner_up_trial_result: study.get_runner_up_trial_result().cloned(),
+        });
+    }
+}
+
+/// What a study run by the simulator found, kept in memory rather than saved
+#[derive(Debug, Clone, PartialEq)]
+pub struct StudyResults {
+    pub trial_qty: usize,
+    pub best_trial_result: Option<TrialResult>, // The trial with the highest success rate
+    pub runner_up_trial_result: Option<TrialResult>, // The trial with the second highest success rate
+}
+
+/// Defines a skill study run in memory: the subject hero is varied over every combination of valid skills, after any preset skills,
+/// and placed after the fixed party members in the team
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkillStudyDefinition {
+    pub subject_hero: String,
+    pub party: Vec<String>,
+    pub booster: Option<BoosterType>,
+    pub valid_skills: Vec<String>,
+    pub preset_skills: Vec<String>,
+    pub trial_dungeon: TrialDungeon,
+    pub simulation_qty: usize, // The simulations per combination of skills
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        inputs::create_hero_input,
+        test_data::{create_test_simulator, TEST_CLASS, TEST_ITEM},
+    };
+
+    /// A hero input of the test class with the given quality and spirit in every slot
+    fn create_socketed_hero_input(quality: &str, spirit: &str) -> HeroInput {
+        return create_hero_input(
+            String::from("Socketed_Hero"),
+            String::from(TEST_CLASS),
+            1,
+            1,
+            String::from("Fire"),
+            0,
+            0,
+            0,
+            std::array::from_fn(|_| String::from("Empty")),
+            std::array::from_fn(|_| String::from(TEST_ITEM)),
+            std::array::from_fn(|_| quality.to_string()),
+            std::array::from_fn(|_| String::from("---")),
+            std::array::from_fn(|_| spirit.to_string()),
+        );
+    }
+
+    #[test]
+    fn build_hero_rejects_unknown_qualities_and_spirit_tiers() {
+        let simulator = create_test_simulator(vec![]);
+        assert!(simulator
+            .build_hero(create_socketed_hero_input("Normal", "---"))
+            .is_ok());
+        assert!(simulator
+            .build_hero(create_socketed_hero_input("Shiny", "---"))
+            .is_err_and(|e| e.contains("Unknown equipment quality Shiny")));
+        assert!(simulator
+            .build_hero(create_socketed_hero_input("Normal", "Wolf T3"))
+            .is_err_and(|e| e.contains("Unknown tier of spirit Wolf T3")));
+    }
+}
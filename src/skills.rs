@@ -1,5 +1,9 @@
+use std::{collections::HashMap, str::FromStr};
+
 use serde::{Deserialize, Serialize};
 
+use crate::heroes::{create_pet, Pet};
+
 /// Information on hero skills
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct HeroSkill {
@@ -28,9 +32,15 @@ pub struct HeroSkill {
     defense_with_item_percent: f64,
     item_types: Vec<String>,
     classes_allowed: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    effects: Vec<SkillEffect>, // What the skill does in combat, from input/skill_effects.yaml rather than the skills sheet
 }
 
 impl HeroSkill {
+    pub fn get_name(&self) -> String {
+        return self.name.to_string();
+    }
+
     pub fn _get_type(&self) -> String {
         return self.type_.to_string();
     }
@@ -106,6 +116,26 @@ impl HeroSkill {
     pub fn get_item_types(&self) -> Vec<String> {
         return self.item_types.clone();
     }
+
+    pub fn get_incompatible_with_t1_name(&self) -> String {
+        return self.incompatible_with_t1_name.to_string();
+    }
+
+    pub fn get_classes_allowed(&self) -> Vec<String> {
+        return self.classes_allowed.clone();
+    }
+
+    pub fn get_rarity(&self) -> String {
+        return self.rarity.to_string();
+    }
+
+    pub fn get_requires_class_promotion(&self) -> bool {
+        return self.requires_class_promotion;
+    }
+
+    pub fn get_effects(&self) -> Vec<SkillEffect> {
+        return self.effects.clone();
+    }
 }
 
 pub fn create_hero_skill(
@@ -161,7 +191,197 @@ pub fn create_hero_skill(
         defense_with_item_percent,
         item_types,
         classes_allowed,
+        effects: vec![],
+    };
+}
+
+/// The most damage that damage reduction auras can prevent together
+pub const MAX_DAMAGE_REDUCTION: f64 = 0.75;
+
+/// Defines what a hero skill does in combat, beyond the stat bonuses of the skills sheet
+/// Defined in input/skill_effects.yaml as a list of effects for each skill, keyed by the skill's name at each tier
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "type")]
+pub enum SkillEffect {
+    RoundHeal { hp_fraction: f64 }, // At the end of each round the hero is alive, every living hero is healed by this fraction of the hero's max hp
+    DamageReductionAura { reduction: f64 }, // While the hero is alive every hero takes this fraction less damage from the mob, stacking with other auras up to MAX_DAMAGE_REDUCTION
+    FirstStrike, // The hero attacks before anyone else acts in the first round, whatever the initiative
+    Revive { hp_fraction: f64 }, // Once per fight, at the end of a round the hero is alive, the first fallen hero is revived with this fraction of their max hp
+}
+
+impl SkillEffect {
+    /// Returns every problem with the effect, such as a fraction outside 0-1
+    pub fn get_validation_errors(&self) -> Vec<String> {
+        let mut errors: Vec<String> = vec![];
+        match self {
+            SkillEffect::RoundHeal { hp_fraction } | SkillEffect::Revive { hp_fraction } => {
+                if *hp_fraction <= 0.0 || *hp_fraction > 1.0 {
+                    errors.push(String::from("hp_fraction must be > 0 and <= 1"));
+                }
+            }
+            SkillEffect::DamageReductionAura { reduction } => {
+                if *reduction <= 0.0 || *reduction > MAX_DAMAGE_REDUCTION {
+                    errors.push(f!("reduction must be > 0 and <= {}", MAX_DAMAGE_REDUCTION));
+                }
+            }
+            SkillEffect::FirstStrike => (),
+        }
+        return errors;
+    }
+}
+
+/// Give each skill named in the skill effects its combat effects, failing if a skill is not in the skills sheet
+pub fn apply_skill_effects(
+    hero_skill_map: &mut HashMap<String, HeroSkill>,
+    skill_effects: HashMap<String, Vec<SkillEffect>>,
+) -> Result<(), String> {
+    for (skill, effects) in skill_effects {
+        hero_skill_map
+            .get_mut(&skill)
+            .ok_or_else(|| f!("{}: no hero skill with this name was found", skill))?
+            .effects = effects;
+    }
+    return Ok(());
+}
+
+/// The skill name of a skill slot with no skill in it. Blank skill names are also treated as empty
+pub const EMPTY_SKILL: &str = "Empty";
+
+pub fn is_empty_skill(skill: &str) -> bool {
+    return skill.is_empty() || skill == EMPTY_SKILL;
+}
+
+/// The tier a skill reference asks for, a tier from 1 or the highest tier of the skill's line
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SkillTier {
+    Tier(u8),
+    Max,
+}
+
+impl FromStr for SkillTier {
+    type Err = String;
+
+    /// Parse a tier such as "3" or "T3", or "max" or "Tmax"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tier = s.trim().trim_start_matches(['T', 't']);
+        if tier.eq_ignore_ascii_case("max") {
+            return Ok(SkillTier::Max);
+        }
+        return tier
+            .parse::<u8>()
+            .ok()
+            .filter(|tier| *tier > 0)
+            .map(SkillTier::Tier)
+            .ok_or(f!("Skill tier {} must be a number from 1 or max", s));
+    }
+}
+
+/// Returns the highest tier of the skill line whose tier 1 skill has the given name, 0 if there is no such line
+pub fn get_max_skill_tier(
+    tier_1_name: &str,
+    hero_skill_tier_1_name_map: &HashMap<String, String>,
+) -> u8 {
+    return (1..=u8::MAX)
+        .take_while(|tier| {
+            hero_skill_tier_1_name_map.contains_key(&f!("{} T{}", tier_1_name, tier))
+        })
+        .count() as u8;
+}
+
+/// Look up a skill by the name of any tier of its line, such as its tier 1 name, returning the skill of its line at the desired tier,
+/// or the named skill itself if no tier is given
+pub fn resolve_skill(
+    name_or_tier_1_name: &str,
+    desired_tier: Option<SkillTier>,
+    hero_skill_tier_1_name_map: &HashMap<String, String>,
+    hero_skill_map: &HashMap<String, HeroSkill>,
+) -> Result<HeroSkill, String> {
+    let skill = hero_skill_map
+        .get(name_or_tier_1_name)
+        .ok_or(f!("Unknown skill {}", name_or_tier_1_name))?;
+    let tier = match desired_tier {
+        None => return Ok(skill.clone()),
+        Some(SkillTier::Tier(tier)) => tier,
+        Some(SkillTier::Max) => get_max_skill_tier(&skill.tier_1_name, hero_skill_tier_1_name_map),
+    };
+    return hero_skill_tier_1_name_map
+        .get(&f!("{} T{}", skill.tier_1_name, tier))
+        .and_then(|name| hero_skill_map.get(name))
+        .cloned()
+        .ok_or(f!(
+            "The {} skill line has no tier {}",
+            skill.tier_1_name,
+            tier
+        ));
+}
+
+/// Resolve a skill reference from a hero csv to the name of the skill it refers to
+/// A reference is the name of a skill at any tier, optionally followed by the tier of its line wanted, such as "Cleave T4" or "Cleave Tmax"
+/// Empty skills are returned as they are
+pub fn resolve_skill_reference(
+    reference: &str,
+    hero_skill_tier_1_name_map: &HashMap<String, String>,
+    hero_skill_map: &HashMap<String, HeroSkill>,
+) -> Result<String, String> {
+    if is_empty_skill(reference) || hero_skill_map.contains_key(reference) {
+        return Ok(reference.to_string());
+    }
+    let (name, desired_tier) = match reference.rsplit_once(' ') {
+        Some((name, tier)) if tier.starts_with(['T', 't']) && tier.parse::<SkillTier>().is_ok() => {
+            (name, tier.parse().ok())
+        }
+        _ => (reference, None),
     };
+    return resolve_skill(
+        name,
+        desired_tier,
+        hero_skill_tier_1_name_map,
+        hero_skill_map,
+    )
+    .map(|skill| skill.name);
+}
+
+/// Returns why the given skills can't be equipped together, if they can't
+/// A skill may not be equipped twice, alongside another tier of its skill line, or alongside a skill line it is incompatible with
+/// Empty skill slots and unknown skills are ignored
+pub fn find_skill_conflict(
+    skills: &[String],
+    hero_skill_map: &HashMap<String, HeroSkill>,
+) -> Option<String> {
+    let equipped: Vec<&String> = skills
+        .iter()
+        .filter(|skill| !is_empty_skill(skill))
+        .collect();
+    for (i, skill) in equipped.iter().enumerate() {
+        for other in equipped[..i].iter() {
+            if skill == other {
+                return Some(f!("Skill {} is equipped more than once", skill));
+            }
+            let (Some(skill_info), Some(other_info)) =
+                (hero_skill_map.get(*skill), hero_skill_map.get(*other))
+            else {
+                continue;
+            };
+            if skill_info.get_tier_1_name() == other_info.get_tier_1_name() {
+                return Some(f!(
+                    "Skills {} and {} are both from the {} skill line",
+                    other,
+                    skill,
+                    skill_info.get_tier_1_name()
+                ));
+            }
+            if skill_info.get_incompatible_with_t1_name() == other_info.get_tier_1_name()
+                || other_info.get_incompatible_with_t1_name() == skill_info.get_tier_1_name()
+            {
+                return Some(f!(
+                    "Skills {} and {} cannot be equipped together",
+                    other,
+                    skill
+                ));
+            }
+        }
+    }
+    return None;
 }
 
 /// Information on innate skills
@@ -332,3 +552,104 @@ pub fn create_innate_skill(
         classes_allowed,
     };
 }
+
+/// Information on the skill of a pet, the familiar item equipped in one of a hero's gear slots
+/// Loaded from input/pet_skills.yaml keyed by the familiar's blueprint name, with any bonus left out defaulting to 0
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct PetSkill {
+    name: String,
+    attack_percent: f64,
+    attack_value: f64,
+    hp_percent: f64,
+    hp_value: f64,
+    hp_regen_value: f64,
+    defense_percent: f64,
+    evasion_percent: f64,
+    crit_chance_percent: f64,
+    crit_damage_percent: f64,
+    threat_rating_value: u16,
+    survive_fatal_blow_chance_percent: f64,
+    proc_chance: f64, // The chance each round, while the hero is alive, that the pet acts in combat
+    proc_damage_percent: f64, // Share of the hero's attack dealt to the mob when the pet acts
+    proc_heal_percent: f64, // Share of the hero's max hp restored when the pet acts
+}
+
+impl PetSkill {
+    /// Returns every problem with the pet skill, such as a proc chance outside 0-1
+    pub fn get_validation_errors(&self) -> Vec<String> {
+        let mut errors: Vec<String> = vec![];
+        if !(0.0..=1.0).contains(&self.proc_chance) {
+            errors.push(String::from(
+                "proc_chance must be within range 0-1 inclusive",
+            ));
+        }
+        if self.proc_damage_percent < 0.0 || self.proc_heal_percent < 0.0 {
+            errors.push(String::from(
+                "proc_damage_percent and proc_heal_percent must be >= 0",
+            ));
+        }
+        return errors;
+    }
+
+    /// Returns the pet that fights alongside the hero, or None if the pet skill only improves the hero's stats
+    pub fn create_pet(&self) -> Option<Pet> {
+        if self.proc_chance <= 0.0 {
+            return None;
+        }
+        return Some(create_pet(
+            self.name.to_string(),
+            self.proc_chance,
+            self.proc_damage_percent,
+            self.proc_heal_percent,
+        ));
+    }
+
+    pub fn _get_name(&self) -> String {
+        return self.name.to_string();
+    }
+
+    pub fn get_attack_percent(&self) -> f64 {
+        return self.attack_percent;
+    }
+
+    pub fn get_attack_value(&self) -> f64 {
+        return self.attack_value;
+    }
+
+    pub fn get_hp_percent(&self) -> f64 {
+        return self.hp_percent;
+    }
+
+    pub fn get_hp_value(&self) -> f64 {
+        return self.hp_value;
+    }
+
+    pub fn get_hp_regen_value(&self) -> f64 {
+        return self.hp_regen_value;
+    }
+
+    pub fn get_defense_percent(&self) -> f64 {
+        return self.defense_percent;
+    }
+
+    pub fn get_evasion_percent(&self) -> f64 {
+        return self.evasion_percent;
+    }
+
+    pub fn get_crit_chance_percent(&self) -> f64 {
+        return self.crit_chance_percent;
+    }
+
+    pub fn get_crit_damage_percent(&self) -> f64 {
+        return self.crit_damage_percent;
+    }
+
+    pub fn get_threat_rating_value(&self) -> u16 {
+        return self.threat_rating_value;
+    }
+
+    pub fn get_survive_fatal_blow_chance_percent(&self) -> f64 {
+        return self.survive_fatal_blow_chance_percent;
+    }
+}
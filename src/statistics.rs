@@ -0,0 +1,505 @@
+use itertools::Itertools;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use serde::{Deserialize, Serialize};
+
+use crate::rng::RngConfig;
+
+/// The p-value below which a difference between two success rates is reported as significant
+pub const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// The result of testing whether two success rates differ by more than chance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProportionTest {
+    pub z: f64,       // The difference of the second rate from the first, in standard errors
+    pub p_value: f64, // The chance of a difference at least this large if both rates were really the same, two-sided
+}
+
+impl ProportionTest {
+    pub fn is_significant(&self) -> bool {
+        return self.p_value < SIGNIFICANCE_LEVEL;
+    }
+
+    /// Format the test as a single line verdict on whether the rates can be told apart
+    pub fn fmt_verdict(&self) -> String {
+        if self.z == 0.0 {
+            return String::from("not significant, the rates are equal");
+        }
+        return match self.is_significant() {
+            true => f!("significant (z = {:.2}, p = {:.3})", self.z, self.p_value),
+            false => f!(
+                "not significant (z = {:.2}, p = {:.3}), could be chance at this many simulations",
+                self.z,
+                self.p_value
+            ),
+        };
+    }
+}
+
+/// Two-proportion z-test of the success rates of two trials of qty_a and qty_b simulations, using the pooled rate
+/// Rates that are both 0 or both 1 have no variance, and are treated as no difference
+pub fn two_proportion_z_test(
+    rate_a: f64,
+    qty_a: usize,
+    rate_b: f64,
+    qty_b: usize,
+) -> Result<ProportionTest, &'static str> {
+    if qty_a == 0 || qty_b == 0 {
+        return Err("both trials must have run at least one simulation");
+    }
+    if !(0.0..=1.0).contains(&rate_a) || !(0.0..=1.0).contains(&rate_b) {
+        return Err("rates must be within range 0-1 inclusive");
+    }
+    let (n_a, n_b) = (qty_a as f64, qty_b as f64);
+    let pooled = (rate_a * n_a + rate_b * n_b) / (n_a + n_b);
+    let standard_error = (pooled * (1.0 - pooled) * (1.0 / n_a + 1.0 / n_b)).sqrt();
+    if standard_error <= 0.0 {
+        return Ok(ProportionTest {
+            z: 0.0,
+            p_value: 1.0,
+        });
+    }
+    let z = (rate_b - rate_a) / standard_error;
+    return Ok(ProportionTest {
+        z,
+        p_value: (2.0 * (1.0 - standard_normal_cdf(z.abs()))).clamp(0.0, 1.0),
+    });
+}
+
+/// The standard error of a success rate measured over qty simulations, 0 if no simulations were run
+pub fn proportion_standard_error(rate: f64, qty: usize) -> f64 {
+    if qty == 0 {
+        return 0.0;
+    }
+    return (rate * (1.0 - rate) / qty as f64).sqrt();
+}
+
+/// The 95% Wilson score interval of a success rate measured over qty simulations, which stays within 0-1 even for rates near 0 or 1 or few simulations
+pub fn wilson_interval(rate: f64, qty: usize) -> (f64, f64) {
+    if qty == 0 {
+        return (0.0, 1.0);
+    }
+    let z = 1.96;
+    let n = qty as f64;
+    let denominator = 1.0 + z * z / n;
+    let center = (rate + z * z / (2.0 * n)) / denominator;
+    let margin = z * (rate * (1.0 - rate) / n + z * z / (4.0 * n * n)).sqrt() / denominator;
+    return ((center - margin).max(0.0), (center + margin).min(1.0));
+}
+
+/// The standard normal cumulative distribution, via the Abramowitz and Stegun 7.1.26 approximation of erf (error below 1.5e-7)
+fn standard_normal_cdf(x: f64) -> f64 {
+    let t_x = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * t_x);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-t_x * t_x).exp();
+    return match x >= 0.0 {
+        true => 0.5 * (1.0 + erf),
+        false => 0.5 * (1.0 - erf),
+    };
+}
+
+/// How the confidence intervals reported alongside trial metrics are computed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct StatisticsConfig {
+    pub bootstrap_resamples: usize, // The resamples drawn per trial to bootstrap metric intervals, none are bootstrapped when 0
+    pub confidence_level: f64,      // The fraction of resampled values each interval covers
+    pub target_interval_width: Option<f64>, // Trials keep simulating until their success rate's 95% interval is narrower than this, None to run a fixed simulation_qty
+    pub max_simulation_qty: usize, // The most simulations a trial runs while narrowing its interval
+}
+
+impl Default for StatisticsConfig {
+    fn default() -> StatisticsConfig {
+        return StatisticsConfig {
+            bootstrap_resamples: 0,
+            confidence_level: 0.95,
+            target_interval_width: None,
+            max_simulation_qty: 100000,
+        };
+    }
+}
+
+impl StatisticsConfig {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if !(0.0..1.0).contains(&self.confidence_level) {
+            return Err("confidence_level must be within range 0-1, such as 0.95");
+        }
+        if self
+            .target_interval_width
+            .is_some_and(|width| width <= 0.0 || width > 1.0)
+        {
+            return Err("target_interval_width must be within range 0-1, such as 0.05");
+        }
+        if self.max_simulation_qty < 1 {
+            return Err("max_simulation_qty must be > 0");
+        }
+        return Ok(());
+    }
+}
+
+/// Percentile bootstrap intervals of several statistics of the same samples, or None if bootstrapping is off or there are no samples
+/// Each resample draws sample_qty indices into the samples with replacement, and statistics returns every statistic of the resampled indices
+/// Resamples draw from their own generator, seeded with the simulations' seed when rng has one, so bootstrapping never changes simulation results
+pub fn bootstrap_intervals(
+    config: &StatisticsConfig,
+    rng: &RngConfig,
+    sample_qty: usize,
+    statistics: impl Fn(&[usize]) -> Vec<f64>,
+) -> Option<Vec<(f64, f64)>> {
+    if config.bootstrap_resamples == 0 || sample_qty == 0 {
+        return None;
+    }
+    let mut rng = match rng.seed {
+        Some(seed) => Pcg64Mcg::seed_from_u64(seed),
+        None => Pcg64Mcg::from_entropy(),
+    };
+    let mut indices = vec![0usize; sample_qty];
+    let mut resampled: Vec<Vec<f64>> = vec![];
+    for _ in 0..config.bootstrap_resamples {
+        for index in indices.iter_mut() {
+            *index = rng.gen_range(0..sample_qty);
+        }
+        for (i, statistic) in statistics(&indices).into_iter().enumerate() {
+            if resampled.len() <= i {
+                resampled.push(vec![]);
+            }
+            resampled[i].push(statistic);
+        }
+    }
+
+    let tail = (1.0 - config.confidence_level) / 2.0;
+    let last = (config.bootstrap_resamples - 1) as f64;
+    return Some(
+        resampled
+            .into_iter()
+            .map(|mut values| {
+                values.sort_by(|a, b| a.total_cmp(b));
+                (
+                    values[(tail * last).round() as usize],
+                    values[((1.0 - tail) * last).round() as usize],
+                )
+            })
+            .collect(),
+    );
+}
+
+/// A logistic curve of success rate against some level, such as dungeon tier, falling as the level rises when slope is negative
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LogisticCurve {
+    pub intercept: f64,
+    pub slope: f64,
+}
+
+impl LogisticCurve {
+    pub fn predict(&self, level: f64) -> f64 {
+        return 1.0 / (1.0 + (-(self.intercept + self.slope * level)).exp());
+    }
+
+    /// Returns the level at which the curve reaches the given success rate, or None if the curve is flat
+    pub fn solve(&self, success_rate: f64) -> Option<f64> {
+        if self.slope == 0.0 || success_rate <= 0.0 || success_rate >= 1.0 {
+            return None;
+        }
+        return Some(((success_rate / (1.0 - success_rate)).ln() - self.intercept) / self.slope);
+    }
+}
+
+/// Fit a logistic curve to (level, successes, simulation_qty) points by maximum likelihood, using Newton's method
+/// Half a success and half a failure are added to every point so rates of exactly 0 or 1 still give a finite curve
+pub fn fit_logistic_curve(points: &[(f64, usize, usize)]) -> Result<LogisticCurve, &'static str> {
+    if points
+        .iter()
+        .map(|(level, _, _)| level.to_bits())
+        .unique()
+        .count()
+        < 2
+    {
+        return Err("at least two distinct levels are needed to fit a curve");
+    }
+    if points.iter().any(|(_, successes, qty)| successes > qty) {
+        return Err("successes must not exceed the simulation quantity");
+    }
+    let mut curve = LogisticCurve {
+        intercept: 0.0,
+        slope: 0.0,
+    };
+    for _ in 0..100 {
+        // Gradient and Hessian of the log likelihood with respect to (intercept, slope)
+        let (mut g0, mut g1, mut h00, mut h01, mut h11) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        for (level, successes, qty) in points.iter() {
+            let n = *qty as f64 + 1.0;
+            let y = *successes as f64 + 0.5;
+            let p = curve.predict(*level);
+            let weight = n * p * (1.0 - p);
+            g0 += y - n * p;
+            g1 += (y - n * p) * level;
+            h00 += weight;
+            h01 += weight * level;
+            h11 += weight * level * level;
+        }
+        let determinant = h00 * h11 - h01 * h01;
+        if determinant.abs() < 1e-12 {
+            break;
+        }
+        let step0 = (h11 * g0 - h01 * g1) / determinant;
+        let step1 = (h00 * g1 - h01 * g0) / determinant;
+        curve.intercept += step0;
+        curve.slope += step1;
+        if step0.abs() < 1e-9 && step1.abs() < 1e-9 {
+            break;
+        }
+    }
+    return Ok(curve);
+}
+
+/// A least squares line through (x, y) points with the correlation of x and y
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearFit {
+    pub correlation: f64, // Pearson's r, from -1 to 1
+    pub slope: f64,       // The change in y per unit of x
+    pub intercept: f64,
+}
+
+/// Running means and co-moments of (x, y) points, so a line can be fit without keeping every point
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct RunningRegression {
+    qty: usize,
+    mean_x: f64,
+    mean_y: f64,
+    m2_x: f64, // The sum of squared differences from the mean of x
+    m2_y: f64,
+    c_xy: f64, // The sum of products of differences from the means of x and y
+}
+
+impl RunningRegression {
+    /// Add a point, updating the moments with Welford's method to stay accurate for large values
+    pub fn add(&mut self, x: f64, y: f64) {
+        self.qty += 1;
+        let n = self.qty as f64;
+        let dx = x - self.mean_x;
+        let dy = y - self.mean_y;
+        self.mean_x += dx / n;
+        self.mean_y += dy / n;
+        self.m2_x += dx * (x - self.mean_x);
+        self.m2_y += dy * (y - self.mean_y);
+        self.c_xy += dx * (y - self.mean_y);
+    }
+
+    pub fn get_qty(&self) -> usize {
+        return self.qty;
+    }
+
+    pub fn get_mean_x(&self) -> f64 {
+        return self.mean_x;
+    }
+
+    /// Returns the fit of the points added, or None if there are fewer than 3 or either x or y never varies
+    pub fn fit(&self) -> Option<LinearFit> {
+        if self.qty < 3 || self.m2_x <= 1e-12 || self.m2_y <= 1e-12 {
+            return None;
+        }
+        let slope = self.c_xy / self.m2_x;
+        return Some(LinearFit {
+            correlation: (self.c_xy / (self.m2_x * self.m2_y).sqrt()).clamp(-1.0, 1.0),
+            slope,
+            intercept: self.mean_y - slope * self.mean_x,
+        });
+    }
+}
+
+/// The equal width buckets a RunningDistribution counts rates in
+pub const DISTRIBUTION_BUCKET_QTY: usize = 20;
+
+/// Running moments, extremes, and a histogram of rates from 0 to 1, so the distribution of many trials' success rates can be reported without keeping them
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct RunningDistribution {
+    qty: usize,
+    mean: f64,
+    m2: f64, // The sum of squared differences from the mean
+    min: f64,
+    max: f64,
+    buckets: [usize; DISTRIBUTION_BUCKET_QTY], // The rates in each bucket, the last including 1
+}
+
+impl RunningDistribution {
+    /// Add a rate, updating the moments with Welford's method
+    pub fn add(&mut self, rate: f64) {
+        self.qty += 1;
+        let delta = rate - self.mean;
+        self.mean += delta / self.qty as f64;
+        self.m2 += delta * (rate - self.mean);
+        if self.qty == 1 {
+            self.min = rate;
+            self.max = rate;
+        } else {
+            self.min = self.min.min(rate);
+            self.max = self.max.max(rate);
+        }
+        let bucket = (rate.clamp(0.0, 1.0) * DISTRIBUTION_BUCKET_QTY as f64) as usize;
+        self.buckets[bucket.min(DISTRIBUTION_BUCKET_QTY - 1)] += 1;
+    }
+
+    pub fn get_qty(&self) -> usize {
+        return self.qty;
+    }
+
+    pub fn get_mean(&self) -> f64 {
+        return self.mean;
+    }
+
+    /// Returns the sample standard deviation, 0 with fewer than 2 rates
+    pub fn get_std_dev(&self) -> f64 {
+        if self.qty < 2 {
+            return 0.0;
+        }
+        return (self.m2 / (self.qty - 1) as f64).sqrt();
+    }
+
+    pub fn get_min(&self) -> f64 {
+        return self.min;
+    }
+
+    pub fn get_max(&self) -> f64 {
+        return self.max;
+    }
+
+    /// Estimate a quantile (0-1) of the rates added, interpolating within the histogram bucket it falls in
+    pub fn estimate_quantile(&self, quantile: f64) -> Option<f64> {
+        if self.qty == 0 {
+            return None;
+        }
+        let target = quantile.clamp(0.0, 1.0) * self.qty as f64;
+        let width = 1.0 / DISTRIBUTION_BUCKET_QTY as f64;
+        let mut counted = 0.0;
+        for (i, count) in self.buckets.iter().enumerate() {
+            let count = *count as f64;
+            if count > 0.0 && counted + count >= target {
+                let estimate = (i as f64 + (target - counted) / count) * width;
+                return Some(estimate.clamp(self.min, self.max));
+            }
+            counted += count;
+        }
+        return Some(self.max);
+    }
+
+    /// Returns the (lower bound, upper bound, rates) of each histogram bucket
+    pub fn get_buckets(&self) -> Vec<(f64, f64, usize)> {
+        let width = 1.0 / DISTRIBUTION_BUCKET_QTY as f64;
+        return self
+            .buckets
+            .iter()
+            .enumerate()
+            .map(|(i, count)| (i as f64 * width, (i + 1) as f64 * width, *count))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wilson_interval_of_no_successes_starts_at_0() {
+        let (lower, upper) = wilson_interval(0.0, 100);
+        assert_eq!(lower, 0.0);
+        assert!(upper > 0.0 && upper < 0.05, "{}", upper);
+    }
+
+    #[test]
+    fn wilson_interval_of_all_successes_ends_at_1() {
+        let (lower, upper) = wilson_interval(1.0, 100);
+        assert!(lower > 0.95 && lower < 1.0, "{}", lower);
+        assert!((upper - 1.0).abs() < 1e-12, "{}", upper);
+    }
+
+    #[test]
+    fn z_test_is_symmetric() {
+        let ab = two_proportion_z_test(0.4, 500, 0.5, 400).unwrap();
+        let ba = two_proportion_z_test(0.5, 400, 0.4, 500).unwrap();
+        assert!((ab.z + ba.z).abs() < 1e-12);
+        assert!((ab.p_value - ba.p_value).abs() < 1e-12);
+        assert!(ab.is_significant());
+    }
+
+    #[test]
+    fn z_test_of_equal_rates_is_not_significant() {
+        let test = two_proportion_z_test(0.5, 200, 0.5, 300).unwrap();
+        assert_eq!(test.z, 0.0);
+        assert!(!test.is_significant());
+    }
+
+    #[test]
+    fn seeded_bootstrap_is_reproducible() {
+        let samples: Vec<f64> = (0..200).map(|i| (i % 7) as f64).collect();
+        let config = StatisticsConfig {
+            bootstrap_resamples: 500,
+            ..StatisticsConfig::default()
+        };
+        let rng = RngConfig {
+            seed: Some(42),
+            ..RngConfig::default()
+        };
+        let mean = |indices: &[usize]| {
+            return vec![indices.iter().map(|i| samples[*i]).sum::<f64>() / indices.len() as f64];
+        };
+        let intervals = bootstrap_intervals(&config, &rng, samples.len(), mean).unwrap();
+        assert_eq!(
+            bootstrap_intervals(&config, &rng, samples.len(), mean).unwrap(),
+            intervals
+        );
+        let sample_mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        assert!(intervals[0].0 < sample_mean && sample_mean < intervals[0].1);
+    }
+
+    #[test]
+    fn bootstrap_is_off_without_resamples() {
+        let config = StatisticsConfig::default();
+        let intervals = bootstrap_intervals(&config, &RngConfig::default(), 10, |_| vec![0.0]);
+        assert_eq!(intervals, None);
+    }
+
+    #[test]
+    fn logistic_fit_recovers_a_falling_curve() {
+        let truth = LogisticCurve {
+            intercept: 4.0,
+            slope: -1.0,
+        };
+        let points: Vec<(f64, usize, usize)> = (1..=8)
+            .map(|level| {
+                let level = level as f64;
+                return (
+                    level,
+                    (truth.predict(level) * 10000.0).round() as usize,
+                    10000,
+                );
+            })
+            .collect();
+        let curve = fit_logistic_curve(&points).unwrap();
+        assert!((curve.slope - truth.slope).abs() < 0.05, "{:?}", curve);
+        assert!((curve.solve(0.5).unwrap() - 4.0).abs() < 0.1, "{:?}", curve);
+    }
+
+    #[test]
+    fn regression_fits_a_line() {
+        let mut regression = RunningRegression::default();
+        for x in 0..10 {
+            regression.add(x as f64, 3.0 + 2.0 * x as f64);
+        }
+        let fit = regression.fit().unwrap();
+        assert!((fit.slope - 2.0).abs() < 1e-9);
+        assert!((fit.intercept - 3.0).abs() < 1e-9);
+        assert!((fit.correlation - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn regression_needs_variation() {
+        let mut regression = RunningRegression::default();
+        for _ in 0..10 {
+            regression.add(1.0, 2.0);
+        }
+        assert_eq!(regression.fit(), None);
+    }
+}
@@ -1,14 +1,56 @@
-// pub mod single_hero_skill_study;
-pub mod static_duo_skill_study;
+pub mod difficulty_ladder_study;
+pub mod elemental_coverage_study;
+pub mod endless_study;
+pub mod gear_optimization_study;
+pub mod hero_optimization_study;
+pub mod promotion_comparison_study;
+pub mod quality_sweep_study;
+pub mod quest_line_study;
+pub mod roster_schedule_study;
+pub mod single_hero_skill_study;
+pub mod static_party_skill_study;
+pub mod support_hero_skill_study;
+pub mod targeting_sensitivity_study;
+pub mod team_composition_study;
 
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use serde::{Deserialize, Serialize};
+use log::info;
+use rayon::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
+    champions::{find_champion_data, Champion, ChampionData},
+    decimals::round_to_2,
+    dungeons::TrialDungeon,
     equipment::Blueprint,
-    hero_builder::HeroClass,
-    skills::{HeroSkill, InnateSkill},
+    formatting::{create_stats_view, fmt_f64, serialize_rounded},
+    hashing::stable_hash,
+    hero_builder::{Element, Hero, HeroBuilderError, HeroClass, SeedValues, Spirit},
+    heroes::{SimHero, Team},
+    progress::{
+        create_study_progress, create_terminal_progress_reporter, ProgressReporter, StudyProgress,
+    },
+    quests::{find_reward_quest, Quest},
+    rng::{with_stream_rng, RngConfig},
+    skills::{find_skill_conflict, HeroSkill, InnateSkill, PetSkill},
+    statistics::{two_proportion_z_test, RunningDistribution, RunningRegression, StatisticsConfig},
+    studies::{
+        gear_optimization_study::GearOptimizationStudy, quality_sweep_study::QualitySweepStudy,
+        single_hero_skill_study::SingleHeroSkillStudy,
+        static_party_skill_study::StaticPartySkillStudy,
+        support_hero_skill_study::SupportHeroSkillStudy,
+        team_composition_study::TeamCompositionStudy,
+    },
+    study_queue::{get_checkpoint_path, load_study_queue, save_checkpoint, STUDY_QUEUE_PATH},
+    trials::{
+        create_result_writer, create_trial, MessagePackPaths, ResultFormat, ResultWriter, Trial,
+        TrialResult, RESULT_BATCH_SIZE,
+    },
 };
 
 extern crate csv;
@@ -20,16 +62,72 @@ pub struct Study {
     identifier: String,
     description: String,
     simulation_qty: i32,
-    runoff_scoring_threshold: f64, // The top X% of the results will be re-tested on the n+1 dungeon in the dungeons vec until either there are no successes or the vec is exhausted. Pass 100.0 to disable runoff scoring
+    runoff_scoring_threshold: f64, // The top X% of the results will be re-tested on the n+1 dungeon in the dungeon ladder until either there are no successes or the ladder is exhausted. Pass 100.0 to disable runoff scoring
+    #[serde(default)]
+    dungeon_ladder: Vec<TrialDungeon>, // The dungeons of each runoff round in order, the first being the dungeon every trial is run on. Empty for studies without runoff scoring
+    #[serde(default)]
+    runoff_entrants: Vec<RunoffEntrant>, // The saved trials entering the first runoff round, only kept while runoff scoring is enabled
     status: StudyStatus,
+    result_format: ResultFormat, // Whether trial results are saved one row per trial (wide), one row per trial per metric (long), or one json object per trial with per-hero detail (json)
+    save_raw_results: bool, // Whether to also save one row per simulation to a compressed csv for external analysis
+    save_msgpack: bool, // Whether to also save trial results, and raw results if saved, as MessagePack for tools that want faster parsing
+    dataset_path: Option<String>, // A long-lived csv that trial results are appended to across studies, skipping results already present
     hero_builder_information: HeroBuilderInformation,
+    trial_qty: usize,                            // The number of trials saved so far
+    total_simulation_qty: usize, // The number of simulations run so far, across all trials
+    best_trial_result: Option<TrialResult>, // The saved trial with the highest success rate
+    runner_up_trial_result: Option<TrialResult>, // The saved trial with the second highest success rate
+    stat_regressions: Vec<(String, String, RunningRegression)>, // Each hero and stat of the saved trials' teams against success rate, in order first seen
+    #[serde(default)]
+    trial_rankings: Vec<TrialRankingCSVRecord>, // A summary of each saved trial, ranked once the study has run all of its trials
+    #[serde(default)]
+    ranking_limit: Option<usize>, // Keep only this many of the best trial rankings, and of the study's own rankings, so huge sweeps run in constant memory. None keeps every trial
+    #[serde(default)]
+    success_rate_distribution: RunningDistribution, // The success rates of every saved trial, kept even once their rankings are dropped
+    output_paths: Vec<String>, // Every file written by the study, in the order first written
+    result_writer: Option<ResultWriter>, // Buffers trial results until they are written in a batch, created when the first trial is saved
+    queue_id: Option<usize>, // The study queue entry the study is running as, if it was started by the queue
+    #[serde(default)]
+    rng: RngConfig, // The generator each trial draws a stream of, keyed by its index in the study. Seeded, the study gives identical results on any thread qty and when resumed
+    #[serde(default)]
+    statistics: StatisticsConfig, // How the study's trials compute their intervals
+    #[serde(skip, default = "default_thread_qty")]
+    thread_qty: usize, // The threads trials are run on, set for each run rather than saved with a queue checkpoint. 1 runs them on the current thread
+    #[serde(skip)]
+    thread_pool: Option<StudyThreadPool>, // The pool of thread_qty threads every batch of trials is run on, None when they are run on the current thread
+    #[serde(skip, default = "create_cli_study_output")]
+    output: StudyOutput, // Where the study reports its results, set for each run like thread_qty
 }
 
+fn default_thread_qty() -> usize {
+    return 1;
+}
+
+/// The threads a study runs its trials on, built once when its thread_qty is set rather than for each batch
+#[derive(Debug, Clone)]
+struct StudyThreadPool(Arc<rayon::ThreadPool>);
+
+impl PartialEq for StudyThreadPool {
+    fn eq(&self, other: &StudyThreadPool) -> bool {
+        return self.0.current_num_threads() == other.0.current_num_threads();
+    }
+}
+
+/// The trials a study runs at once on each of its threads, so threads aren't left idle waiting on the slowest trial of a small batch
+const TRIALS_PER_THREAD_BATCH: usize = 4;
+
+/// The most builds a SimHeroCache holds before it is cleared, bounding its memory in large sweeps
+const SIM_HERO_CACHE_CAPACITY: usize = 10_000;
+
 pub fn create_study(
     identifier: String,
     description: String,
     simulation_qty: i32,
     runoff_scoring_threshold: f64,
+    result_format: ResultFormat,
+    save_raw_results: bool,
+    save_msgpack: bool,
+    dataset_path: Option<String>,
     hero_builder_information: HeroBuilderInformation,
 ) -> Study {
     return Study {
@@ -37,20 +135,915 @@ pub fn create_study(
         description,
         simulation_qty,
         runoff_scoring_threshold,
+        dungeon_ladder: vec![],
+        runoff_entrants: vec![],
         status: StudyStatus::Created,
+        result_format,
+        save_raw_results,
+        save_msgpack,
+        dataset_path,
         hero_builder_information,
+        trial_qty: 0,
+        total_simulation_qty: 0,
+        best_trial_result: None,
+        runner_up_trial_result: None,
+        stat_regressions: vec![],
+        trial_rankings: vec![],
+        ranking_limit: None,
+        success_rate_distribution: RunningDistribution::default(),
+        output_paths: vec![],
+        result_writer: None,
+        queue_id: None,
+        rng: RngConfig::default(),
+        statistics: StatisticsConfig::default(),
+        thread_qty: default_thread_qty(),
+        thread_pool: None,
+        output: create_cli_study_output(),
     };
 }
 
-/// Runnable studies must have a run function
+/// Where a study reports its results, so callers embedding the simulator can run studies without touching the filesystem or stdout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StudyOutput {
+    pub files: bool, // Whether results, rankings, and resume checkpoints are saved under target/simulations
+    pub stdout: bool, // Whether the study prints what it finds as it runs
+}
+
+/// The command line tool saves every file and prints as studies run
+pub fn create_cli_study_output() -> StudyOutput {
+    return StudyOutput {
+        files: true,
+        stdout: true,
+    };
+}
+
+impl StudyOutput {
+    /// Print text found while running, if the output includes stdout
+    pub fn print(&self, text: &str) {
+        if self.stdout {
+            print!("{}", text);
+        }
+    }
+}
+
+impl Study {
+    pub fn set_output(&mut self, output: StudyOutput) {
+        self.output = output;
+    }
+
+    /// Print text the study found as it ran, if its output includes stdout
+    fn print(&self, text: &str) {
+        self.output.print(text);
+    }
+
+    /// Create and run a trial of the given team against the given dungeon, as the next trial of the study
+    fn run_trial(&self, description: String, team: Team, trial_dungeon: &TrialDungeon) -> Trial {
+        return self.run_trial_at(self.trial_qty, description, team, trial_dungeon);
+    }
+
+    /// Create and run a trial of the given team against the given dungeon
+    /// With a seed, the trial draws from the seed's stream for its index in the study, so its results don't depend on the trials run before it
+    fn run_trial_at(
+        &self,
+        trial_index: usize,
+        description: String,
+        team: Team,
+        trial_dungeon: &TrialDungeon,
+    ) -> Trial {
+        let mut trial = create_trial(
+            self.identifier.to_string(),
+            description,
+            self.simulation_qty as usize,
+            team,
+            trial_dungeon.dungeon.clone(),
+            [trial_dungeon.difficulty].to_vec(),
+            trial_dungeon.force_minibosses,
+            trial_dungeon.max_rounds,
+            trial_dungeon.initiative,
+            trial_dungeon.targeting,
+            false,
+        )
+        .unwrap();
+        trial.set_reward_quest(
+            self.hero_builder_information
+                .find_reward_quest(
+                    &trial_dungeon.dungeon._get_zone(),
+                    &[trial_dungeon.difficulty],
+                )
+                .cloned(),
+        );
+        trial.set_statistics(self.statistics);
+        trial.set_rng(self.rng);
+
+        let timer = Instant::now();
+        with_stream_rng(self.rng, trial_index as u64, |rng| {
+            trial.run_simulations_with_rng(rng);
+        });
+        let timer_duration = timer.elapsed().as_nanos() as f32 / 1000000.0f32;
+        info!("Completed trial in {:#?}ms.", timer_duration,);
+
+        return trial;
+    }
+
+    pub fn set_thread_qty(&mut self, thread_qty: usize) -> Result<(), &'static str> {
+        if thread_qty < 1 {
+            return Err("thread_qty must be > 0");
+        }
+        self.thread_qty = thread_qty;
+        self.thread_pool = match thread_qty {
+            1 => None,
+            thread_qty => Some(StudyThreadPool(Arc::new(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(thread_qty)
+                    .build()
+                    .map_err(|_| "could not start the study's threads")?,
+            ))),
+        };
+        return Ok(());
+    }
+
+    /// The success rate of the best trial saved so far
+    pub fn get_best_success_rate(&self) -> Option<f64> {
+        return self
+            .best_trial_result
+            .as_ref()
+            .map(|best| best.get_success_rate());
+    }
+
+    /// The saved trial with the highest success rate
+    pub fn get_best_trial_result(&self) -> Option<&TrialResult> {
+        return self.best_trial_result.as_ref();
+    }
+
+    /// The saved trial with the second highest success rate
+    pub fn get_runner_up_trial_result(&self) -> Option<&TrialResult> {
+        return self.runner_up_trial_result.as_ref();
+    }
+
+    /// The number of trials saved so far
+    pub fn get_trial_qty(&self) -> usize {
+        return self.trial_qty;
+    }
+
+    /// Draw the study's trials from the given generator and seed, replacing the unseeded default it was created with
+    pub fn set_rng(&mut self, rng: RngConfig) {
+        self.rng = rng;
+    }
+
+    /// Compute the study's trial intervals from the given config, replacing the defaults it was created with
+    pub fn set_statistics(&mut self, statistics: StatisticsConfig) -> Result<(), &'static str> {
+        statistics.validate()?;
+        self.statistics = statistics;
+        return Ok(());
+    }
+
+    /// Keep only the best ranking_limit trials in the study's rankings, dropping the rest as trials are saved
+    pub fn set_ranking_limit(&mut self, ranking_limit: usize) -> Result<(), &'static str> {
+        if ranking_limit < 1 {
+            return Err("ranking_limit must be > 0");
+        }
+        self.ranking_limit = Some(ranking_limit);
+        return Ok(());
+    }
+
+    pub fn get_ranking_limit(&self) -> Option<usize> {
+        return self.ranking_limit;
+    }
+
+    /// The number of trials to create before running them together with run_trials
+    fn get_trial_batch_size(&self) -> usize {
+        return match self.thread_qty {
+            1 => 1,
+            thread_qty => thread_qty * TRIALS_PER_THREAD_BATCH,
+        };
+    }
+
+    /// Create and run a trial of each (description, team) against the given dungeon, spread across the study's threads, returning the trials in order
+    /// With a seed, each trial draws from a stream of its own keyed by its index in the study, so results don't depend on which thread ran it
+    fn run_trials(
+        &self,
+        variations: Vec<(String, Team)>,
+        trial_dungeon: &TrialDungeon,
+    ) -> Vec<Trial> {
+        return self.run_trials_from(self.trial_qty, variations, trial_dungeon);
+    }
+
+    /// Like run_trials, but the trials are indexed from first_trial_index rather than following the saved trials
+    fn run_trials_from(
+        &self,
+        first_trial_index: usize,
+        variations: Vec<(String, Team)>,
+        trial_dungeon: &TrialDungeon,
+    ) -> Vec<Trial> {
+        let Some(StudyThreadPool(pool)) = &self.thread_pool else {
+            return variations
+                .into_iter()
+                .enumerate()
+                .map(|(i, (description, team))| {
+                    self.run_trial_at(first_trial_index + i, description, team, trial_dungeon)
+                })
+                .collect();
+        };
+        return pool.install(|| {
+            variations
+                .into_par_iter()
+                .enumerate()
+                .map(|(i, (description, team))| {
+                    self.run_trial_at(first_trial_index + i, description, team, trial_dungeon)
+                })
+                .collect()
+        });
+    }
+
+    /// Set the dungeons of each runoff round, the first being the dungeon every trial is run on
+    fn set_dungeon_ladder(&mut self, dungeon_ladder: Vec<TrialDungeon>) {
+        self.dungeon_ladder = dungeon_ladder;
+    }
+
+    fn is_runoff_scoring_enabled(&self) -> bool {
+        return self.runoff_scoring_threshold < 100.0 && self.dungeon_ladder.len() > 1;
+    }
+
+    /// Enter a saved trial into the first runoff round, with the variation needed to rebuild its team for later rounds
+    fn add_runoff_entrant(&mut self, variation: Vec<String>, trial_result: &TrialResult) {
+        if !self.is_runoff_scoring_enabled() {
+            return;
+        }
+        self.runoff_entrants.push(RunoffEntrant {
+            variation,
+            record: create_runoff_csv_record(0, trial_result),
+        });
+    }
+
+    /// Rank a round's entrants by success rate, then by the fewest rounds, marking the top runoff_scoring_threshold% that had any success as survivors
+    /// Returns the survivors in rank order
+    fn rank_runoff_round(&self, entrants: &mut [RunoffEntrant]) -> Vec<RunoffEntrant> {
+        entrants.sort_by(|a, b| {
+            b.record
+                .success_rate
+                .total_cmp(&a.record.success_rate)
+                .then(a.record.average_rounds.total_cmp(&b.record.average_rounds))
+        });
+        let survivor_qty = ((entrants.len() as f64 * self.runoff_scoring_threshold / 100.0).ceil()
+            as usize)
+            .max(1);
+        for (i, entrant) in entrants.iter_mut().enumerate() {
+            entrant.record.rank = i + 1;
+            entrant.record.survived = i < survivor_qty && entrant.record.success_rate > 0.0;
+        }
+        return entrants
+            .iter()
+            .filter(|entrant| entrant.record.survived)
+            .cloned()
+            .collect();
+    }
+
+    /// Re-test the survivors of each runoff round on the next dungeon of the ladder, until a round has no survivors or the ladder is exhausted,
+    /// then save every round's entrants to runoff_rounds.csv. create_team rebuilds an entrant's team from its variation
+    fn run_runoff_rounds(
+        &mut self,
+        create_team: impl Fn(&HeroBuilderInformation, &[String]) -> Team,
+    ) {
+        if !self.is_runoff_scoring_enabled() || self.runoff_entrants.is_empty() {
+            return;
+        }
+        let mut entrants = std::mem::take(&mut self.runoff_entrants);
+        let mut records: Vec<RunoffCSVRecord> = vec![];
+        let mut trial_index = self.trial_qty; // Runoff trials draw from the streams after the saved trials'
+        for round in 0..self.dungeon_ladder.len() {
+            let survivors = self.rank_runoff_round(&mut entrants);
+            self.print(&f!(
+                "Runoff round {} on {}: {} of {} builds survived\n",
+                round,
+                entrants[0].record.dungeon,
+                survivors.len(),
+                entrants.len()
+            ));
+            records.extend(entrants.iter().map(|entrant| entrant.record.clone()));
+            if survivors.is_empty() || round + 1 == self.dungeon_ladder.len() {
+                if let Some(winner) = survivors.first() {
+                    self.print(&f!(
+                        "Runoff winner: {} with success rate {:.2} on {}\n",
+                        winner.record.trial_description,
+                        winner.record.success_rate,
+                        winner.record.dungeon
+                    ));
+                }
+                break;
+            }
+
+            let variations: Vec<(String, Team)> = survivors
+                .iter()
+                .map(|survivor| {
+                    (
+                        survivor.record.trial_description.to_string(),
+                        create_team(&self.hero_builder_information, &survivor.variation),
+                    )
+                })
+                .collect();
+            let trials =
+                self.run_trials_from(trial_index, variations, &self.dungeon_ladder[round + 1]);
+            trial_index += trials.len();
+            entrants = survivors
+                .into_iter()
+                .zip(trials.iter())
+                .map(|(survivor, trial)| {
+                    let trial_result = trial.create_trial_result();
+                    self.add_simulations(trial_result.get_simulation_qty());
+                    RunoffEntrant {
+                        variation: survivor.variation,
+                        record: create_runoff_csv_record(round + 1, &trial_result),
+                    }
+                })
+                .collect();
+        }
+        self.save_runoff_rounds(&records).unwrap();
+    }
+
+    fn save_runoff_rounds(&mut self, records: &[RunoffCSVRecord]) -> Result<(), std::io::Error> {
+        let Some(path) = self.create_output_path("runoff_rounds.csv")? else {
+            return Ok(());
+        };
+        let mut wtr = csv::Writer::from_path(path)?;
+        for record in records.iter() {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+
+    /// Add the result of a trial to the study's trial results csv, which is written in batches, returning the result
+    /// Long format results are kept in a separate file since the two shapes can't share a header
+    fn save_trial_result(&mut self, trial: &Trial) -> TrialResult {
+        if self.output.files && self.result_writer.is_none() {
+            self.result_writer = Some(self.create_result_writer().unwrap());
+        }
+        let trial_result = trial.create_trial_result();
+        if let Some(result_writer) = self.result_writer.as_mut() {
+            result_writer.add_trial(trial, &trial_result).unwrap();
+        }
+
+        self.trial_qty += 1;
+        self.add_simulations(trial_result.get_simulation_qty());
+        self.add_stat_samples(trial.get_team(), trial_result.get_success_rate());
+        self.success_rate_distribution
+            .add(trial_result.get_success_rate());
+        self.trial_rankings
+            .push(create_trial_ranking_csv_record(&trial_result));
+        retain_top_rankings(
+            &mut self.trial_rankings,
+            self.ranking_limit,
+            compare_trial_rankings,
+        );
+        if self
+            .best_trial_result
+            .as_ref()
+            .is_none_or(|best| trial_result.get_success_rate() > best.get_success_rate())
+        {
+            self.runner_up_trial_result = self.best_trial_result.replace(trial_result.clone());
+        } else if self
+            .runner_up_trial_result
+            .as_ref()
+            .is_none_or(|runner_up| trial_result.get_success_rate() > runner_up.get_success_rate())
+        {
+            self.runner_up_trial_result = Some(trial_result.clone());
+        }
+        return trial_result;
+    }
+
+    fn create_result_writer(&mut self) -> Result<ResultWriter, std::io::Error> {
+        let file_name = match self.result_format {
+            ResultFormat::Wide => "trial_results.csv",
+            ResultFormat::Long => "trial_results_long.csv",
+            ResultFormat::Json => "trial_results.jsonl",
+        };
+        let results_path = self.create_file_path(file_name)?;
+        let raw_results_path = match self.save_raw_results {
+            true => Some(self.create_file_path("simulation_results.csv.gz")?),
+            false => None,
+        };
+        let msgpack_paths = match self.save_msgpack {
+            true => Some(MessagePackPaths {
+                results_path: self.create_file_path(
+                    &file_name
+                        .replace(".csv", ".msgpack")
+                        .replace(".jsonl", ".msgpack"),
+                )?,
+                raw_results_path: match self.save_raw_results {
+                    true => Some(self.create_file_path("simulation_results.msgpack")?),
+                    false => None,
+                },
+            }),
+            false => None,
+        };
+        if let Some(dataset_path) = self.dataset_path.clone() {
+            if let Some(p) = std::path::Path::new(&dataset_path).parent() {
+                std::fs::create_dir_all(p)?;
+            }
+            self.record_output_path(&dataset_path);
+        }
+        return Ok(create_result_writer(
+            self.result_format,
+            results_path,
+            raw_results_path,
+            msgpack_paths,
+            self.dataset_path.clone(),
+        ));
+    }
+
+    /// Write any trial results still buffered, the trial rankings, and the stat correlations, called once a study has run all of its trials
+    fn flush_trial_results(&mut self) {
+        if let Some(result_writer) = self.result_writer.as_mut() {
+            result_writer.flush().unwrap();
+        }
+        if !self.trial_rankings.is_empty() {
+            self.save_trial_rankings().unwrap();
+        }
+        if !self.create_stat_correlation_records().is_empty() {
+            self.save_stat_correlations().unwrap();
+        }
+        if self.success_rate_distribution.get_qty() > 0 {
+            self.save_success_rate_distribution().unwrap();
+        }
+    }
+
+    /// Add each hero's build stats in a saved trial as a sample against the trial's success rate
+    fn add_stat_samples(&mut self, team: &Team, success_rate: f64) {
+        for (hero, stats) in team.get_heroes_build_stats() {
+            for (stat, value) in stats {
+                let position = self
+                    .stat_regressions
+                    .iter()
+                    .position(|(h, s, _)| *h == hero && s == stat);
+                let i = position.unwrap_or_else(|| {
+                    self.stat_regressions.push((
+                        hero.to_string(),
+                        stat.to_string(),
+                        RunningRegression::default(),
+                    ));
+                    self.stat_regressions.len() - 1
+                });
+                self.stat_regressions[i].2.add(value, success_rate);
+            }
+        }
+    }
+
+    /// The fit of success rate against each hero stat that varied across saved trials, strongest correlation first
+    fn create_stat_correlation_records(&self) -> Vec<StatCorrelationCSVRecord> {
+        let mut records: Vec<StatCorrelationCSVRecord> = self
+            .stat_regressions
+            .iter()
+            .filter_map(|(hero, stat, regression)| {
+                regression.fit().map(|fit| StatCorrelationCSVRecord {
+                    hero: hero.to_string(),
+                    stat: stat.to_string(),
+                    trials: regression.get_qty(),
+                    mean_stat: regression.get_mean_x(),
+                    correlation: fit.correlation,
+                    r_squared: fit.correlation * fit.correlation,
+                    success_rate_per_unit: fit.slope,
+                })
+            })
+            .collect();
+        records.sort_by(|a, b| b.r_squared.total_cmp(&a.r_squared));
+        return records;
+    }
+
+    /// Rank the saved trials by success rate, then by the most heroes surviving, then by the fewest rounds, and save them
+    fn save_trial_rankings(&mut self) -> Result<(), std::io::Error> {
+        rank_top_rankings(
+            &mut self.trial_rankings,
+            self.ranking_limit,
+            compare_trial_rankings,
+        );
+        for (i, record) in self.trial_rankings.iter_mut().enumerate() {
+            record.rank = i + 1;
+        }
+        let Some(path) = self.create_output_path("trial_rankings.csv")? else {
+            return Ok(());
+        };
+        let mut wtr = csv::Writer::from_path(path)?;
+        for record in self.trial_rankings.iter() {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+
+    /// Save the histogram of every saved trial's success rate
+    fn save_success_rate_distribution(&mut self) -> Result<(), std::io::Error> {
+        let Some(path) = self.create_output_path("success_rate_distribution.csv")? else {
+            return Ok(());
+        };
+        let mut wtr = csv::Writer::from_path(path)?;
+        for (lower, upper, trials) in self.success_rate_distribution.get_buckets() {
+            wtr.serialize(SuccessRateBucketCSVRecord {
+                lower,
+                upper,
+                trials,
+            })?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+
+    fn save_stat_correlations(&mut self) -> Result<(), std::io::Error> {
+        let records = self.create_stat_correlation_records();
+        let Some(path) = self.create_output_path("stat_correlations.csv")? else {
+            return Ok(());
+        };
+        let mut wtr = csv::Writer::from_path(path)?;
+        for record in records.iter() {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+
+    /// Returns the path of a file in the study's csvs folder, creating the folder and recording the file for the summary,
+    /// or None if the study's output doesn't include files
+    fn create_output_path(&mut self, file_name: &str) -> Result<Option<String>, std::io::Error> {
+        if !self.output.files {
+            return Ok(None);
+        }
+        return self.create_file_path(file_name).map(Some);
+    }
+
+    fn create_file_path(&mut self, file_name: &str) -> Result<String, std::io::Error> {
+        let path = f!("target/simulations/{}/csvs/{}", self.identifier, file_name);
+        if let Some(p) = std::path::Path::new(&path).parent() {
+            std::fs::create_dir_all(p)?;
+        }
+        self.record_output_path(&path);
+        return Ok(path);
+    }
+
+    fn record_output_path(&mut self, path: &str) {
+        if !self.output_paths.iter().any(|p| p == path) {
+            self.output_paths.push(path.to_string());
+        }
+    }
+
+    /// Write a skill pair matrix to the study's csvs folder, as one row per pair and as a json matrix for heatmaps
+    fn save_skill_pair_matrix(&mut self, matrix: &SkillPairMatrix) -> Result<(), std::io::Error> {
+        let Some(path) = self.create_output_path("skill_pairs.csv")? else {
+            return Ok(());
+        };
+        let mut wtr = csv::Writer::from_path(path)?;
+        for record in matrix.create_csv_records() {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+
+        let Some(path) = self.create_output_path("skill_pairs.json")? else {
+            return Ok(());
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&matrix.create_json())?)?;
+        return Ok(());
+    }
+
+    /// Whether a study running from the queue should stop after the trial just saved, because it was paused or a higher priority study is waiting
+    /// The queue is only checked once a full batch of results has been written, so stopping never leaves results unwritten
+    fn should_stop_for_queue(&self) -> bool {
+        let Some(queue_id) = self.queue_id else {
+            return false;
+        };
+        if !self.is_batch_written() {
+            return false;
+        }
+        return load_study_queue(STUDY_QUEUE_PATH).is_ok_and(|queue| queue.should_stop(queue_id));
+    }
+
+    /// Whether every saved trial's result has been written, as it is right after a full batch is written
+    fn is_batch_written(&self) -> bool {
+        return self.trial_qty.is_multiple_of(RESULT_BATCH_SIZE);
+    }
+
+    /// The checkpoint a study saves its progress to while running, which resume_study continues it from
+    pub fn get_resume_checkpoint_path(&self) -> String {
+        return f!("target/simulations/{}/checkpoint.msgpack", self.identifier);
+    }
+
+    /// Save the state of a running runnable study after each batch of results is written, so if the process is interrupted
+    /// at most the batch in progress is lost. Called after every saved trial, it only saves once a batch has been written
+    fn save_resume_checkpoint<T: Serialize>(&self, kind: ResumableStudyKind, runnable: &T) {
+        if !self.output.files || !self.is_batch_written() {
+            return;
+        }
+        save_checkpoint(&self.get_resume_checkpoint_path(), &(kind, runnable)).unwrap();
+    }
+
+    /// Remove the resume checkpoint of a finished study, which has nothing left to resume
+    fn remove_resume_checkpoint(&self) {
+        let path = self.get_resume_checkpoint_path();
+        if self.output.files && std::path::Path::new(&path).exists() {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    /// Stop a study running from the queue partway, writing every buffered result
+    /// The runnable study holding it must then be saved with save_queue_checkpoint to resume from
+    fn stop_for_queue(&mut self) {
+        self.flush_trial_results();
+        self.status = StudyStatus::Stopped;
+    }
+
+    /// Save the state of a stopped runnable study as its queue entry's checkpoint, then mark the entry stopped so the queue moves on
+    fn save_queue_checkpoint<T: Serialize>(&self, runnable: &T) {
+        let queue_id = self.queue_id.unwrap();
+        save_checkpoint(&get_checkpoint_path(queue_id), runnable).unwrap();
+        let mut queue = load_study_queue(STUDY_QUEUE_PATH).unwrap();
+        queue.mark_stopped(queue_id).unwrap();
+        queue.save(STUDY_QUEUE_PATH).unwrap();
+    }
+
+    /// Count simulations run outside of saved trials towards the study's total
+    fn add_simulations(&mut self, simulation_qty: usize) {
+        self.total_simulation_qty += simulation_qty;
+    }
+
+    /// Format the headline results of a finished study: its best build and that build's success rate with a 95% confidence interval and whether it beats the runner-up by more than chance, the hero stat that best predicts success rate, the simulations run, the wall time, and the files written
+    pub fn fmt_summary(&self, wall_time: Duration) -> String {
+        let mut view = create_stats_view(match self.status {
+            StudyStatus::Stopped => f!("Study {} stopped for the queue", self.identifier),
+            _ => f!("Study {} finished", self.identifier),
+        });
+        if let Some(best) = &self.best_trial_result {
+            let (lower, upper) = best.get_success_rate_interval();
+            view = view
+                .text("Best Build", best.get_build_signature())
+                .text("Best Trial", best.get_trial_description())
+                .text(
+                    "Success Rate",
+                    f!(
+                        "{} (95% CI {} - {}, {} simulations)",
+                        fmt_f64(best.get_success_rate()),
+                        fmt_f64(lower),
+                        fmt_f64(upper),
+                        best.get_simulation_qty()
+                    ),
+                );
+            if let Some(runner_up) = &self.runner_up_trial_result {
+                if let Ok(test) = two_proportion_z_test(
+                    runner_up.get_success_rate(),
+                    runner_up.get_simulation_qty(),
+                    best.get_success_rate(),
+                    best.get_simulation_qty(),
+                ) {
+                    view = view.text(
+                        "Vs Runner-Up",
+                        f!(
+                            "{} ({}), {}",
+                            fmt_f64(runner_up.get_success_rate()),
+                            runner_up.get_build_signature(),
+                            test.fmt_verdict()
+                        ),
+                    );
+                }
+            }
+        }
+        if let Some(top) = self.create_stat_correlation_records().first() {
+            view = view.text(
+                "Top Predictor",
+                f!(
+                    "{} {} (r = {}, explains {}% of success rate variation)",
+                    top.hero,
+                    top.stat,
+                    fmt_f64(top.correlation),
+                    fmt_f64(top.r_squared * 100.0)
+                ),
+            );
+        }
+        let distribution = &self.success_rate_distribution;
+        if distribution.get_qty() > 1 {
+            view = view.text(
+                "Success Rates",
+                f!(
+                    "mean {} (sd {}), median ~{}, range {} - {}",
+                    fmt_f64(distribution.get_mean()),
+                    fmt_f64(distribution.get_std_dev()),
+                    fmt_f64(distribution.estimate_quantile(0.5).unwrap()),
+                    fmt_f64(distribution.get_min()),
+                    fmt_f64(distribution.get_max())
+                ),
+            );
+        }
+        if let Some(ranking_limit) = self.ranking_limit {
+            view = view.text(
+                "Rankings Kept",
+                f!(
+                    "best {} of {} trials",
+                    ranking_limit.min(self.trial_qty),
+                    self.trial_qty
+                ),
+            );
+        }
+        view = view
+            .text(
+                "Simulations",
+                f!(
+                    "{} across {} trials",
+                    self.total_simulation_qty,
+                    self.trial_qty
+                ),
+            )
+            .text("Wall Time", f!("{:.1?}", wall_time));
+        if let Some(seed) = self.rng.seed {
+            view = view.text("Seed", seed.to_string());
+        }
+        for path in self.output_paths.iter() {
+            view = view.text("Output", path);
+        }
+        return view.to_string();
+    }
+}
+
+/// Runnable studies must have a run function, and expose the underlying study for its summary once run
 pub trait Runnable {
-    fn run(&mut self);
+    /// Run the study, drawing its progress as a progress bar in the terminal
+    fn run(&mut self) {
+        self.run_with_progress(&mut create_terminal_progress_reporter());
+    }
+
+    /// Run the study, reporting its progress to the reporter as it completes work
+    fn run_with_progress(&mut self, reporter: &mut dyn ProgressReporter);
+    fn get_study(&self) -> &Study;
+    fn get_study_mut(&mut self) -> &mut Study;
+
+    /// Run the study as a study queue entry, resuming from the entry's checkpoint if it was stopped partway before
+    /// Only studies that can stop partway and resume implement this, others run to completion once started
+    fn attach_to_queue(&mut self, _queue_id: usize) -> Result<(), std::io::Error> {
+        return Ok(());
+    }
+
+    /// Run the study's trials across thread_qty threads
+    /// Only studies whose trials don't depend on each other implement this, others run their trials one at a time
+    fn set_thread_qty(&mut self, _thread_qty: usize) -> Result<(), &'static str> {
+        return Ok(());
+    }
+
+    /// Keep only the best ranking_limit trials in the study's rankings
+    /// Only studies that sweep many permutations implement this, others keep every trial
+    fn set_ranking_limit(&mut self, _ranking_limit: usize) -> Result<(), &'static str> {
+        return Ok(());
+    }
+}
+
+/// A study that trials its variations in batches, one trial per thread at a time, and can be resumed or stopped by the queue after any trial
+/// Each study describes its variations and records their trials, while run_trial_batches runs the loop they share
+trait BatchedStudy: Runnable + Serialize {
+    type Variation;
+    const RESUMABLE_KIND: ResumableStudyKind;
+
+    /// Returns the (total, completed) work shown by the study's progress
+    fn count_progress(&self) -> (usize, usize);
+    fn count_variations_remaining(&self) -> usize;
+
+    /// Returns up to batch_size of the next variations, in order, each with the description and team it is trialed as
+    fn get_next_variations(&self, batch_size: usize) -> Vec<(Self::Variation, String, Team)>;
+
+    /// The dungeon each batch is trialed against
+    fn get_batch_dungeon(&self) -> &TrialDungeon;
+
+    /// Record a variation's trial and move past the variation, so a resumed study doesn't trial it again
+    fn record_trial(
+        &mut self,
+        variation: &Self::Variation,
+        trial: &Trial,
+        trial_result: &TrialResult,
+    );
+
+    /// Move past the next variations if they can't be trialed, such as loadouts that break the class's slot rules
+    /// Only studies that skip variations implement this, before each batch is created
+    fn skip_untrialable_variations(&mut self) {}
+}
+
+/// Run a batched study's remaining variations, saving a resume checkpoint as each trial is saved
+/// Returns the study's progress to be finished once the study has saved its results, or None if the study queue stopped it partway
+fn run_trial_batches<'a, T: BatchedStudy>(
+    runnable: &mut T,
+    reporter: &'a mut dyn ProgressReporter,
+) -> Option<StudyProgress<'a>> {
+    runnable.get_study_mut().status = StudyStatus::Running;
+
+    let (total, completed) = runnable.count_progress();
+    let mut progress = create_study_progress(reporter, Some(total as u64), completed as u64);
+
+    loop {
+        runnable.skip_untrialable_variations();
+        if runnable.count_variations_remaining() == 0 {
+            return Some(progress);
+        }
+        // Create the next variations to test, one per trial the study runs at once
+        let (variations, teams): (Vec<T::Variation>, Vec<(String, Team)>) = runnable
+            .get_next_variations(runnable.get_study().get_trial_batch_size())
+            .into_iter()
+            .map(|(variation, description, team)| (variation, (description, team)))
+            .unzip();
+
+        // Create and run a new trial with each new team, then save the trial results in order
+        let trials = runnable
+            .get_study()
+            .run_trials(teams, runnable.get_batch_dungeon());
+        for (variation, trial) in variations.iter().zip(trials.iter()) {
+            let trial_result = runnable.get_study_mut().save_trial_result(trial);
+            runnable.record_trial(variation, trial, &trial_result);
+            progress.update(
+                runnable.count_progress().1 as u64,
+                runnable.get_study().get_best_success_rate(),
+            );
+
+            // Save progress after each batch written, so the study can be resumed if the process is interrupted
+            runnable
+                .get_study()
+                .save_resume_checkpoint(T::RESUMABLE_KIND, &*runnable);
+
+            // Stop after this batch if the study queue paused this study or has a higher priority study waiting
+            if runnable.get_study().should_stop_for_queue() {
+                runnable.get_study_mut().stop_for_queue();
+                runnable.get_study().save_queue_checkpoint(&*runnable);
+                progress.stop();
+                return None;
+            }
+        }
+    }
+}
+
+/// Sort rankings best first once twice the ranking limit are kept and drop all but the best, so they take bounded memory however many trials are saved
+fn retain_top_rankings<T>(
+    rankings: &mut Vec<T>,
+    ranking_limit: Option<usize>,
+    compare: fn(&T, &T) -> Ordering,
+) {
+    if let Some(ranking_limit) = ranking_limit {
+        if rankings.len() >= ranking_limit * 2 {
+            rank_top_rankings(rankings, Some(ranking_limit), compare);
+        }
+    }
+}
+
+/// Sort rankings best first, keeping only the best ranking_limit if there is one
+fn rank_top_rankings<T>(
+    rankings: &mut Vec<T>,
+    ranking_limit: Option<usize>,
+    compare: fn(&T, &T) -> Ordering,
+) {
+    rankings.sort_by(compare);
+    if let Some(ranking_limit) = ranking_limit {
+        rankings.truncate(ranking_limit);
+    }
+}
+
+/// Defines the studies that save resume checkpoints, each recorded in the checkpoint alongside the study's state
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, strum::Display)]
+pub enum ResumableStudyKind {
+    StaticPartySkill,
+    SupportHeroSkill,
+    SingleHeroSkill,
+    GearOptimization,
+    TeamComposition,
+    QualitySweep,
+}
+
+/// Load a study from a resume checkpoint, continuing after the last batch of results it wrote when run
+/// The study keeps writing to the same result files, and runs on a single thread unless set otherwise
+pub fn resume_study(path: &str) -> Result<Box<dyn Runnable>, std::io::Error> {
+    let bytes = std::fs::read(path)?;
+    let (kind, _): (ResumableStudyKind, serde::de::IgnoredAny) =
+        rmp_serde::from_slice(&bytes).map_err(std::io::Error::other)?;
+    return match kind {
+        ResumableStudyKind::StaticPartySkill => Ok(Box::new(load_resume_checkpoint::<
+            StaticPartySkillStudy,
+        >(&bytes)?)),
+        ResumableStudyKind::SupportHeroSkill => Ok(Box::new(load_resume_checkpoint::<
+            SupportHeroSkillStudy,
+        >(&bytes)?)),
+        ResumableStudyKind::SingleHeroSkill => Ok(Box::new(load_resume_checkpoint::<
+            SingleHeroSkillStudy,
+        >(&bytes)?)),
+        ResumableStudyKind::GearOptimization => Ok(Box::new(load_resume_checkpoint::<
+            GearOptimizationStudy,
+        >(&bytes)?)),
+        ResumableStudyKind::TeamComposition => Ok(Box::new(load_resume_checkpoint::<
+            TeamCompositionStudy,
+        >(&bytes)?)),
+        ResumableStudyKind::QualitySweep => Ok(Box::new(load_resume_checkpoint::<
+            QualitySweepStudy,
+        >(&bytes)?)),
+    };
+}
+
+fn load_resume_checkpoint<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, std::io::Error> {
+    let (_, runnable): (ResumableStudyKind, T) =
+        rmp_serde::from_slice(bytes).map_err(std::io::Error::other)?;
+    return Ok(runnable);
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 enum StudyStatus {
     Created,
     Running,
+    Stopped, // Stopped partway for the study queue, to be resumed from a checkpoint
     Finished,
 }
 
@@ -63,4 +1056,461 @@ pub struct HeroBuilderInformation {
     pub hero_skill_map: HashMap<String, HeroSkill>,
     pub class_innate_skill_names_map: HashMap<String, String>,
     pub innate_skill_map: HashMap<String, InnateSkill>,
+    pub pet_skill_map: HashMap<String, PetSkill>,
+    pub spirit_map: HashMap<String, Spirit>,
+    pub elements: Vec<Element>,
+    #[serde(default)]
+    pub seed_values: SeedValues, // The stats each seed adds to builder heroes
+    #[serde(default)]
+    pub champion_data: HashMap<Champion, ChampionData>, // Champions missing from the map have no ability effects
+    #[serde(default)]
+    pub reward_quests: Vec<Quest>, // The quests trial results are matched against to estimate rewards per hour, sorted by identifier
+    #[serde(skip)]
+    pub build_cache: SimHeroCache, // Heroes already built, so permutations repeating a build skip the stat pipeline
+}
+
+/// Defines a memo of the SimHeroes built from each hero build (everything but the identifier), shared by the threads of a study
+#[derive(Debug, Default)]
+pub struct SimHeroCache {
+    built: Arc<Mutex<HashMap<String, SimHero>>>,
+}
+
+/// Builds depend on the game data as well as the hero, so each clone of the game data starts with an empty memo
+/// and editing a clone's game data never returns builds made from the original's
+impl Clone for SimHeroCache {
+    fn clone(&self) -> Self {
+        return SimHeroCache::default();
+    }
+}
+
+/// The memo only saves rebuilding heroes, so it doesn't affect equality
+impl PartialEq for SimHeroCache {
+    fn eq(&self, _other: &Self) -> bool {
+        return true;
+    }
+}
+
+impl SimHeroCache {
+    /// Returns the key of a hero's build, which ignores its identifier since the stat pipeline doesn't use it
+    fn get_key(hero: &Hero) -> String {
+        let mut build = hero.clone();
+        build.set_identifier(String::new());
+        return stable_hash(&build);
+    }
+
+    fn get(&self, key: &str) -> Option<SimHero> {
+        return self.built.lock().unwrap().get(key).cloned();
+    }
+
+    fn insert(&self, key: String, sim_hero: SimHero) {
+        let mut built = self.built.lock().unwrap();
+        if built.len() >= SIM_HERO_CACHE_CAPACITY {
+            built.clear();
+        }
+        built.insert(key, sim_hero);
+    }
+
+    pub fn len(&self) -> usize {
+        return self.built.lock().unwrap().len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+}
+
+impl HeroBuilderInformation {
+    /// Run the gear/skill stat pipeline on a variation of a hero and convert it to a SimHero
+    /// Builds seen before are copied from the build cache under the hero's identifier instead of rebuilt
+    pub fn build_sim_hero(&self, hero: Hero) -> Result<SimHero, HeroBuilderError> {
+        let identifier = hero.get_identifier();
+        let key = SimHeroCache::get_key(&hero);
+        if let Some(mut sim_hero) = self.build_cache.get(&key) {
+            sim_hero.set_identifier(identifier);
+            return Ok(sim_hero);
+        }
+        let sim_hero = self.build_sim_hero_uncached(hero)?;
+        self.build_cache.insert(key, sim_hero.clone());
+        return Ok(sim_hero);
+    }
+
+    /// Run the gear/skill stat pipeline on a variation of a hero without checking the build cache
+    pub fn build_sim_hero_uncached(&self, mut hero: Hero) -> Result<SimHero, HeroBuilderError> {
+        hero.calculate_innate_tier(
+            &self.class_innate_skill_names_map,
+            &self.innate_skill_map,
+            &self.champion_data,
+        )?;
+        hero.resolve_archetype(&self.hero_classes);
+        hero.calculate_stat_improvements_from_gear_and_skills(
+            &self.bp_map,
+            &self.hero_skill_tier_1_name_map,
+            &self.hero_skill_map,
+            &self.class_innate_skill_names_map,
+            &self.innate_skill_map,
+            &self.pet_skill_map,
+            &self.spirit_map,
+            &self.elements,
+            &self.seed_values,
+        )?;
+        let champion_data = self.find_champion_data(&hero.get_class());
+        let mut sim_hero = SimHero::from(hero);
+        sim_hero.set_champion_data(champion_data);
+        return Ok(sim_hero);
+    }
+
+    /// Returns the data of a champion class, or None if the class isn't a champion
+    pub fn find_champion_data(&self, class: &str) -> Option<ChampionData> {
+        return Champion::from_str(class)
+            .ok()
+            .map(|champion| find_champion_data(&self.champion_data, champion).clone());
+    }
+
+    /// Returns the first quest, by identifier, that takes place in the given dungeon at the only difficulty of a trial
+    pub fn find_reward_quest(&self, zone: &str, difficulty_settings: &[usize]) -> Option<&Quest> {
+        return find_reward_quest(&self.reward_quests, zone, difficulty_settings);
+    }
+}
+
+/// Tracks the combinations of valid skills to be varied upon for a subject hero
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SkillVariations {
+    valid_skills: Vec<String>,     // The vector of all skills to be varied upon
+    valid_skills_count: i64,       // The number of valid skills to vary upon
+    preset_skills: Vec<String>, // A vector containing 0-3 innate skills that are preset and unchanging
+    varying_skill_slot_count: i64, // The number of unlocked skill slots to vary, any locked slots are left empty
+    skill_combination_index: i64, // The current index of the combinations of the valid_skills list being trialed
+    skills_info: HashMap<String, HeroSkill>, // The valid and preset skills, used to skip combinations that can't be equipped together
+    legal_combination_count: i64, // The number of combinations that can be equipped together, which are the only ones trialed
+    legal_combinations_completed: i64,
+}
+
+/// Combinations with a repeated skill, two tiers of the same skill line, or incompatible skills are skipped and not counted
+/// Only the hero's unlocked skill slots are varied, so there must be no more preset skills than skill_slot_qty
+pub fn create_skill_variations(
+    valid_skills: Vec<String>,
+    preset_skills: Vec<String>,
+    skill_slot_qty: usize,
+    hero_skill_map: &HashMap<String, HeroSkill>,
+) -> SkillVariations {
+    let mut vs = valid_skills.clone();
+    vs.retain(|x| !preset_skills.contains(x));
+    vs.sort_by_key(|a| a.to_lowercase());
+    vs.dedup();
+    let skills_info: HashMap<String, HeroSkill> = vs
+        .iter()
+        .chain(preset_skills.iter())
+        .filter_map(|skill| {
+            hero_skill_map
+                .get(skill)
+                .map(|info| (skill.to_string(), info.clone()))
+        })
+        .collect();
+    let mut skill_variations = SkillVariations {
+        valid_skills_count: vs.len() as i64,
+        valid_skills: vs,
+        varying_skill_slot_count: skill_slot_qty as i64 - preset_skills.len() as i64,
+        preset_skills,
+        skill_combination_index: 0,
+        skills_info,
+        legal_combination_count: 0,
+        legal_combinations_completed: 0,
+    };
+    skill_variations.legal_combination_count = (0..skill_variations.count_combinations())
+        .filter(|index| skill_variations.is_legal_combination(*index))
+        .count() as i64;
+    skill_variations.skip_illegal_combinations();
+    return skill_variations;
+}
+
+impl SkillVariations {
+    pub fn count_completed(&self) -> i64 {
+        return self.legal_combinations_completed;
+    }
+    pub fn count_total(&self) -> i64 {
+        return self.legal_combination_count;
+    }
+    pub fn count_remaining(&self) -> i64 {
+        return self.count_total() - self.legal_combinations_completed;
+    }
+    /// Returns the number of combinations of the valid skills, including those that can't be equipped together
+    fn count_combinations(&self) -> i64 {
+        return crate::combinations::count_combinations(
+            self.valid_skills_count,
+            self.varying_skill_slot_count,
+        );
+    }
+    fn is_legal_combination(&self, combination_index: i64) -> bool {
+        let mut skills = self.preset_skills.clone();
+        skills.append(&mut self.translate_skillset_from_indices(
+            self.get_skillset_at_specific_combination_index(combination_index),
+        ));
+        return find_skill_conflict(&skills, &self.skills_info).is_none();
+    }
+    /// Advance the combination index to the next combination that can be equipped together, if any remain
+    fn skip_illegal_combinations(&mut self) {
+        while self.skill_combination_index < self.count_combinations()
+            && !self.is_legal_combination(self.skill_combination_index)
+        {
+            self.skill_combination_index += 1;
+        }
+    }
+    pub fn get_skillset_at_specific_combination_index(&self, combination_index: i64) -> Vec<i64> {
+        return crate::combinations::iter_combination(
+            combination_index,
+            self.valid_skills_count,
+            self.varying_skill_slot_count,
+        );
+    }
+    pub fn get_skillset_at_current_combination_index(&self) -> Vec<i64> {
+        return crate::combinations::iter_combination(
+            self.skill_combination_index,
+            self.valid_skills_count,
+            self.varying_skill_slot_count,
+        );
+    }
+    pub fn increment_combination_index(&mut self) {
+        self.skill_combination_index += 1;
+        self.legal_combinations_completed += 1;
+        self.skip_illegal_combinations();
+    }
+    pub fn translate_skillset_from_indices(&self, indices_array: Vec<i64>) -> Vec<String> {
+        let mut res = vec![];
+        for idx in indices_array {
+            res.push(self.valid_skills[idx as usize].clone());
+        }
+        return res;
+    }
+    pub fn get_full_translated_skillset_at_current_combination_index(&self) -> Vec<String> {
+        let mut res = self.preset_skills.clone();
+        let mut translated_skillset =
+            self.translate_skillset_from_indices(self.get_skillset_at_current_combination_index());
+        res.append(&mut translated_skillset);
+        return res;
+    }
+    /// Returns up to qty full translated skillsets of the legal combinations starting at the current one, without advancing the index
+    pub fn get_next_full_translated_skillsets(&self, qty: usize) -> Vec<Vec<String>> {
+        let mut upcoming = self.clone();
+        let mut res = vec![];
+        while res.len() < qty && upcoming.count_remaining() > 0 {
+            res.push(upcoming.get_full_translated_skillset_at_current_combination_index());
+            upcoming.increment_combination_index();
+        }
+        return res;
+    }
+    pub fn get_valid_skills(&self) -> Vec<String> {
+        return self.valid_skills.clone();
+    }
+}
+
+/// Running totals of the success rate of every trial containing each pair of varied skills, marginalized over the other slots
+/// The diagonal holds each skill on its own, so a heatmap shows both how skills pair and how they do alone
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SkillPairMatrix {
+    skills: Vec<String>,              // The varied skills, in matrix order
+    success_rate_sums: Vec<Vec<f64>>, // Symmetric, indexed by the skills' positions
+    trial_counts: Vec<Vec<usize>>,
+}
+
+pub fn create_skill_pair_matrix(skills: Vec<String>) -> SkillPairMatrix {
+    let skill_qty = skills.len();
+    return SkillPairMatrix {
+        skills,
+        success_rate_sums: vec![vec![0.0; skill_qty]; skill_qty],
+        trial_counts: vec![vec![0; skill_qty]; skill_qty],
+    };
+}
+
+impl SkillPairMatrix {
+    /// Add a trial's success rate to every pair of varied skills in its skillset, ignoring preset skills
+    pub fn add_trial(&mut self, skillset: &[String], success_rate: f64) {
+        let positions: Vec<usize> = skillset
+            .iter()
+            .filter_map(|skill| self.skills.iter().position(|s| s == skill))
+            .collect();
+        for a in positions.iter() {
+            for b in positions.iter() {
+                self.success_rate_sums[*a][*b] += success_rate;
+                self.trial_counts[*a][*b] += 1;
+            }
+        }
+    }
+
+    /// Returns the average success rate of trials containing both skills, or None if none were run
+    fn get_average_success_rate(&self, a: usize, b: usize) -> Option<f64> {
+        if self.trial_counts[a][b] == 0 {
+            return None;
+        }
+        return Some(self.success_rate_sums[a][b] / self.trial_counts[a][b] as f64);
+    }
+
+    /// One row per pair of skills trialed together, including each skill with itself
+    fn create_csv_records(&self) -> Vec<SkillPairCSVRecord> {
+        let mut records: Vec<SkillPairCSVRecord> = vec![];
+        for a in 0..self.skills.len() {
+            for b in a..self.skills.len() {
+                if let Some(avg_success_rate) = self.get_average_success_rate(a, b) {
+                    records.push(SkillPairCSVRecord {
+                        skill_a: self.skills[a].to_string(),
+                        skill_b: self.skills[b].to_string(),
+                        avg_success_rate,
+                        trials: self.trial_counts[a][b],
+                    });
+                }
+            }
+        }
+        return records;
+    }
+
+    fn create_json(&self) -> SkillPairJson {
+        return SkillPairJson {
+            skills: self.skills.clone(),
+            avg_success_rate: (0..self.skills.len())
+                .map(|a| {
+                    (0..self.skills.len())
+                        .map(|b| self.get_average_success_rate(a, b).map(round_to_2))
+                        .collect()
+                })
+                .collect(),
+            trials: self.trial_counts.clone(),
+        };
+    }
+}
+
+/// The average success rate of trials containing a pair of skills
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct SkillPairCSVRecord {
+    skill_a: String,
+    skill_b: String,
+    #[serde(serialize_with = "serialize_rounded")]
+    avg_success_rate: f64,
+    trials: usize,
+}
+
+/// A skill pair matrix as rows and columns in skill order, with null for pairs never trialed together
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct SkillPairJson {
+    skills: Vec<String>,
+    avg_success_rate: Vec<Vec<Option<f64>>>,
+    trials: Vec<Vec<usize>>,
+}
+
+/// A summary of a single trial, ranked against the study's other trials in trial_rankings.csv
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct TrialRankingCSVRecord {
+    rank: usize, // 0 until the study ranks its trials
+    trial_description: String,
+    team: String, // The team's hero identifiers, in party order
+    dungeon: String,
+    #[serde(serialize_with = "serialize_rounded")]
+    success_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    average_rounds: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    avg_dmg_taken: f64, // The hp lost by the whole team per simulation
+    #[serde(serialize_with = "serialize_rounded")]
+    avg_survivors: f64, // The heroes expected to survive a simulation
+}
+
+/// Orders trial rankings by success rate, then by the most heroes surviving, then by the fewest rounds
+fn compare_trial_rankings(a: &TrialRankingCSVRecord, b: &TrialRankingCSVRecord) -> Ordering {
+    return b
+        .success_rate
+        .total_cmp(&a.success_rate)
+        .then(b.avg_survivors.total_cmp(&a.avg_survivors))
+        .then(a.average_rounds.total_cmp(&b.average_rounds));
+}
+
+fn create_trial_ranking_csv_record(trial_result: &TrialResult) -> TrialRankingCSVRecord {
+    return TrialRankingCSVRecord {
+        rank: 0,
+        trial_description: trial_result.get_trial_description(),
+        team: trial_result.get_hero_names().join(", "),
+        dungeon: trial_result.describe_dungeon(),
+        success_rate: trial_result.get_success_rate(),
+        average_rounds: trial_result.get_average_rounds(),
+        avg_dmg_taken: trial_result.get_team_avg_hp_lost(),
+        avg_survivors: trial_result.get_avg_survivors(),
+    };
+}
+
+/// A trial entered into a runoff round, with the variation its team is rebuilt from for the next round
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct RunoffEntrant {
+    variation: Vec<String>, // Such as the subject hero's skills
+    record: RunoffCSVRecord,
+}
+
+/// A trial's result in one round of runoff scoring
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct RunoffCSVRecord {
+    round: usize, // 0 for the study's own trials, then one more for each dungeon up the ladder
+    rank: usize,  // Within the round, 0 until the round is ranked
+    trial_description: String,
+    dungeon: String,
+    #[serde(serialize_with = "serialize_rounded")]
+    success_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    average_rounds: f64,
+    survived: bool, // Whether the trial made the round's cut, and so was re-tested on the next dungeon if there was one
+}
+
+fn create_runoff_csv_record(round: usize, trial_result: &TrialResult) -> RunoffCSVRecord {
+    return RunoffCSVRecord {
+        round,
+        rank: 0,
+        trial_description: trial_result.get_trial_description(),
+        dungeon: trial_result.describe_dungeon(),
+        success_rate: trial_result.get_success_rate(),
+        average_rounds: trial_result.get_average_rounds(),
+        survived: false,
+    };
+}
+
+/// The saved trials whose success rate falls in a histogram bucket, including the lower bound
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct SuccessRateBucketCSVRecord {
+    #[serde(serialize_with = "serialize_rounded")]
+    lower: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    upper: f64,
+    trials: usize,
+}
+
+/// How well a hero stat predicts success rate across a study's trials
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct StatCorrelationCSVRecord {
+    hero: String,
+    stat: String,
+    trials: usize,
+    #[serde(serialize_with = "serialize_rounded")]
+    mean_stat: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    correlation: f64, // Pearson's r of the stat and success rate, negative when more of the stat goes with lower success rates
+    #[serde(serialize_with = "serialize_rounded")]
+    r_squared: f64, // The fraction of the variation in success rate explained by the stat alone
+    success_rate_per_unit: f64, // The slope of the fit line, unrounded since it is tiny for large stats such as HP
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_data::{create_prepared_test_hero, create_test_game_data};
+
+    #[test]
+    fn game_data_edited_on_a_clone_builds_a_different_hero() {
+        let hbi = create_test_game_data();
+        let hero = create_prepared_test_hero("Hero_1", 1, ["Strike T1", "Empty", "Empty", "Empty"]);
+        let original = hbi.build_sim_hero(hero.clone()).unwrap();
+
+        let mut edited_hbi = hbi.clone();
+        edited_hbi.seed_values.hp *= 2.0;
+        let edited = edited_hbi.build_sim_hero(hero.clone()).unwrap();
+
+        assert!(edited.get_hp_max() > original.get_hp_max());
+        assert_eq!(
+            edited,
+            edited_hbi.build_sim_hero_uncached(hero.clone()).unwrap()
+        );
+        assert_eq!(hbi.build_sim_hero(hero).unwrap(), original);
+    }
 }
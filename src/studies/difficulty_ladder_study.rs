@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dungeons::TrialDungeon,
+    equipment::BoosterType,
+    formatting::{fmt_f64, serialize_rounded},
+    heroes::{create_team, SimHero, Team},
+    progress::{create_study_progress, ProgressReporter},
+    studies::*,
+};
+
+/// Defines how a difficulty ladder study finds the highest rung a team clears
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default, strum::Display)]
+#[serde(rename_all = "snake_case")]
+pub enum LadderSearch {
+    // Halve the ladder with each trial, assuming a team that fails a rung fails every rung above it
+    #[default]
+    BinarySearch,
+    // Trial each rung from the bottom, stopping at the first the team fails to clear
+    Scan,
+}
+
+/// An extension of Study for finding the highest rung of a ladder of dungeons, ordered easiest first, that a fixed team clears at a target success rate
+pub struct DifficultyLadderStudy {
+    study: Study,
+    team: Team,
+    ladder: Vec<TrialDungeon>,
+    target_success_rate: f64, // The success rate a rung must reach to count as cleared
+    search: LadderSearch,
+    rungs: Vec<LadderRungCSVRecord>, // Each rung trialed, in the order trialed
+}
+
+pub fn create_difficulty_ladder_study(
+    identifier: String,
+    description: String,
+    simulation_qty: i32,
+    party: Vec<SimHero>,
+    booster: Option<BoosterType>,
+    ladder: Vec<TrialDungeon>,
+    target_success_rate: f64,
+    search: LadderSearch,
+    result_format: ResultFormat,
+    save_raw_results: bool,
+    save_msgpack: bool,
+    dataset_path: Option<String>,
+    hero_builder_information: HeroBuilderInformation,
+) -> Result<DifficultyLadderStudy, &'static str> {
+    if ladder.is_empty() {
+        return Err("difficulty ladder study must have at least one dungeon");
+    }
+    if !(target_success_rate > 0.0 && target_success_rate <= 1.0) {
+        return Err("target_success_rate must be > 0 and <= 1");
+    }
+
+    return Ok(DifficultyLadderStudy {
+        study: create_study(
+            identifier,
+            description,
+            simulation_qty,
+            100.0,
+            result_format,
+            save_raw_results,
+            save_msgpack,
+            dataset_path,
+            hero_builder_information,
+        ),
+        team: create_team(party, booster)?,
+        ladder,
+        target_success_rate,
+        search,
+        rungs: vec![],
+    });
+}
+
+impl Runnable for DifficultyLadderStudy {
+    /// Search the ladder for the highest rung cleared, then save every rung trialed and print the breakpoint
+    fn run_with_progress(&mut self, reporter: &mut dyn ProgressReporter) {
+        self.study.status = StudyStatus::Running;
+
+        let mut progress = create_study_progress(reporter, Some(self.count_trials_max() as u64), 0);
+
+        match self.search {
+            LadderSearch::Scan => {
+                for rung in 0..self.ladder.len() {
+                    let clears = self.run_rung(rung);
+                    progress.inc(1);
+                    if !clears {
+                        break;
+                    }
+                }
+            }
+            LadderSearch::BinarySearch => {
+                // Every rung below low is cleared and every rung from high up is failed
+                let (mut low, mut high) = (0, self.ladder.len());
+                while low < high {
+                    let middle = (low + high) / 2;
+                    if self.run_rung(middle) {
+                        low = middle + 1;
+                    } else {
+                        high = middle;
+                    }
+                    progress.inc(1);
+                }
+            }
+        }
+
+        self.study.flush_trial_results();
+        self.save_rungs_to_csv().unwrap();
+        self.study.print(&self.fmt_breakpoint());
+
+        self.study.status = StudyStatus::Finished;
+        progress.finish();
+    }
+
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+}
+
+impl DifficultyLadderStudy {
+    /// The most trials the search can run, every rung for a scan or one per halving for a binary search
+    pub fn count_trials_max(&self) -> usize {
+        return match self.search {
+            LadderSearch::Scan => self.ladder.len(),
+            LadderSearch::BinarySearch => {
+                (usize::BITS - self.ladder.len().leading_zeros()) as usize
+            }
+        };
+    }
+
+    /// Trial the team on a rung of the ladder, returning whether it cleared the rung
+    fn run_rung(&mut self, rung: usize) -> bool {
+        let trial_dungeon = &self.ladder[rung];
+        let trial = self.study.run_trial(
+            f!(
+                "Rung {} {} {}",
+                rung + 1,
+                trial_dungeon.dungeon._get_zone(),
+                trial_dungeon.difficulty
+            ),
+            self.team.clone(),
+            trial_dungeon,
+        );
+        let trial_result = self.study.save_trial_result(&trial);
+        let (lower, upper) = trial_result.get_success_rate_interval();
+        let clears = trial_result.get_success_rate() >= self.target_success_rate;
+        self.rungs.push(LadderRungCSVRecord {
+            rung: rung + 1,
+            zone: trial_dungeon.dungeon._get_zone(),
+            difficulty: trial_dungeon.difficulty,
+            success_rate: trial_result.get_success_rate(),
+            success_rate_lower: lower,
+            success_rate_upper: upper,
+            simulations: trial_result.get_simulation_qty(),
+            clears,
+        });
+        return clears;
+    }
+
+    /// Returns the highest rung trialed that the team cleared, if any
+    pub fn get_breakpoint(&self) -> Option<&LadderRungCSVRecord> {
+        return self
+            .rungs
+            .iter()
+            .filter(|record| record.clears)
+            .max_by_key(|record| record.rung);
+    }
+
+    /// Returns the lowest rung trialed that the team failed, if any
+    fn get_first_failure(&self) -> Option<&LadderRungCSVRecord> {
+        return self
+            .rungs
+            .iter()
+            .filter(|record| !record.clears)
+            .min_by_key(|record| record.rung);
+    }
+
+    /// Describe the highest rung cleared and the rung above it, with whether each result is outside the noise of its simulations
+    fn fmt_breakpoint(&self) -> String {
+        let describe = |record: &LadderRungCSVRecord| -> String {
+            return f!(
+                "{} at difficulty {} (success rate {}, 95% CI {} - {})",
+                record.zone,
+                record.difficulty,
+                fmt_f64(record.success_rate),
+                fmt_f64(record.success_rate_lower),
+                fmt_f64(record.success_rate_upper)
+            );
+        };
+        let target = fmt_f64(self.target_success_rate);
+        let mut res = match self.get_breakpoint() {
+            Some(record) => {
+                let mut line = f!(
+                    "Highest rung cleared at >= {}: {}\n",
+                    target,
+                    describe(record)
+                );
+                if record.success_rate_lower < self.target_success_rate {
+                    line += "  Its confidence interval reaches below the target, so run more simulations to confirm it\n";
+                }
+                line
+            }
+            None => f!("No rung cleared at >= {}\n", target),
+        };
+        match self.get_first_failure() {
+            Some(record) => {
+                res += &f!("First rung failed: {}\n", describe(record));
+                if record.success_rate_upper >= self.target_success_rate {
+                    res += "  Its confidence interval reaches the target, so run more simulations to confirm it\n";
+                }
+            }
+            None => res += "Every rung of the ladder was cleared\n",
+        }
+        return res;
+    }
+
+    /// Write the rungs trialed, in ladder order
+    fn save_rungs_to_csv(&mut self) -> Result<(), std::io::Error> {
+        let Some(path) = self.study.create_output_path("difficulty_ladder.csv")? else {
+            return Ok(());
+        };
+
+        let mut rungs = self.rungs.clone();
+        rungs.sort_by_key(|record| record.rung);
+        let mut wtr = csv::Writer::from_path(path)?;
+        for record in rungs.iter() {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+}
+
+/// A rung of the ladder the team was trialed on
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LadderRungCSVRecord {
+    rung: usize, // From 1, easiest first
+    zone: String,
+    difficulty: usize,
+    #[serde(serialize_with = "serialize_rounded")]
+    success_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    success_rate_lower: f64, // The 95% Wilson score interval of the success rate
+    #[serde(serialize_with = "serialize_rounded")]
+    success_rate_upper: f64,
+    simulations: usize,
+    clears: bool, // Whether the success rate reached the target
+}
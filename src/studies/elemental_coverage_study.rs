@@ -0,0 +1,183 @@
+use crate::{
+    dungeons::{create_trial_dungeon, TrialDungeon},
+    equipment::{BoosterType, ElementType},
+    formatting::fmt_f64,
+    heroes::{create_team, SimHero, Team},
+    progress::{create_study_progress, ProgressReporter},
+    studies::*,
+};
+
+/// The elements a dungeon barrier can take, in the column order of the coverage matrix
+const BARRIER_ELEMENTS: [ElementType; 6] = [
+    ElementType::Fire,
+    ElementType::Water,
+    ElementType::Air,
+    ElementType::Earth,
+    ElementType::Light,
+    ElementType::Dark,
+];
+
+/// An extension of Study for evaluating a fixed party against each elemental barrier of each dungeon
+/// Success rates are simulated per element rather than derived from a static type chart
+pub struct ElementalCoverageStudy {
+    study: Study,
+    team: Team,
+    dungeons: Vec<TrialDungeon>, // The extreme difficulty dungeons to evaluate the party against
+    coverage_threshold: f64, // Success rates below this are reported as lacking an effective answer for the element
+    coverage: Vec<(String, Vec<(ElementType, f64)>)>, // The simulated success rate for each barrier element of each completed dungeon
+}
+
+/// Dungeons must be extreme difficulty (4 - Extreme or 8 - Boss Extreme) since barriers only appear on extreme encounters
+pub fn create_elemental_coverage_study(
+    identifier: String,
+    description: String,
+    simulation_qty: i32,
+    party: Vec<SimHero>,
+    booster: Option<BoosterType>,
+    dungeons: Vec<TrialDungeon>,
+    coverage_threshold: f64,
+    result_format: ResultFormat,
+    save_raw_results: bool,
+    save_msgpack: bool,
+    dataset_path: Option<String>,
+    hero_builder_information: HeroBuilderInformation,
+) -> Result<ElementalCoverageStudy, &'static str> {
+    if dungeons
+        .iter()
+        .any(|td| td.difficulty != 4 && td.difficulty != 8)
+    {
+        return Err("elemental coverage dungeons must be difficulty 4 or 8 (extreme)");
+    }
+    if !(0.0..=1.0).contains(&coverage_threshold) {
+        return Err("coverage_threshold must be within range 0.0-1.0 inclusive");
+    }
+
+    return Ok(ElementalCoverageStudy {
+        study: create_study(
+            identifier,
+            description,
+            simulation_qty,
+            100.0,
+            result_format,
+            save_raw_results,
+            save_msgpack,
+            dataset_path,
+            hero_builder_information,
+        ),
+        team: create_team(party, booster)?,
+        dungeons,
+        coverage_threshold,
+        coverage: vec![],
+    });
+}
+
+impl Runnable for ElementalCoverageStudy {
+    /// Run a trial for each barrier element of each dungeon, then save the coverage matrix
+    fn run_with_progress(&mut self, reporter: &mut dyn ProgressReporter) {
+        self.study.status = StudyStatus::Running;
+
+        let mut progress =
+            create_study_progress(reporter, Some(self.count_trials_total() as u64), 0);
+
+        for trial_dungeon in self.dungeons.iter() {
+            let mut row: Vec<(ElementType, f64)> = vec![];
+            for element in trial_dungeon.dungeon.get_barrier_elements() {
+                // Force every extreme encounter in the dungeon to have this barrier element
+                let element_dungeon = create_trial_dungeon(
+                    trial_dungeon.dungeon.with_barrier_type(element),
+                    trial_dungeon.difficulty,
+                    trial_dungeon.force_minibosses,
+                    Some(trial_dungeon.max_rounds),
+                    Some(trial_dungeon.initiative),
+                )
+                .with_targeting(trial_dungeon.targeting);
+
+                let trial = self.study.run_trial(
+                    f!("{} Barrier", element),
+                    self.team.clone(),
+                    &element_dungeon,
+                );
+                self.study.save_trial_result(&trial);
+                row.push((element, trial.create_trial_result().get_success_rate()));
+                progress.inc(1);
+            }
+            self.coverage.push((trial_dungeon.dungeon._get_zone(), row));
+        }
+
+        self.study.flush_trial_results();
+        self.save_coverage_to_csv().unwrap();
+        for (zone, element) in self.get_lacking_coverage() {
+            self.study.print(&f!(
+                "No effective answer for {} barriers in {}\n",
+                element,
+                zone
+            ));
+        }
+
+        self.study.status = StudyStatus::Finished;
+        progress.finish();
+    }
+
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+}
+
+impl ElementalCoverageStudy {
+    pub fn count_trials_total(&self) -> usize {
+        return self
+            .dungeons
+            .iter()
+            .map(|td| td.dungeon.get_barrier_elements().len())
+            .sum();
+    }
+
+    /// Returns each (zone, element) pair where the party's success rate falls below the coverage threshold
+    pub fn get_lacking_coverage(&self) -> Vec<(String, ElementType)> {
+        let mut res = vec![];
+        for (zone, row) in self.coverage.iter() {
+            for (element, success_rate) in row {
+                if *success_rate < self.coverage_threshold {
+                    res.push((zone.to_string(), *element));
+                }
+            }
+        }
+        return res;
+    }
+
+    /// Write one row per dungeon and one column per element, leaving elements the dungeon never uses blank
+    fn save_coverage_to_csv(&mut self) -> Result<(), std::io::Error> {
+        let Some(path) = self.study.create_output_path("elemental_coverage.csv")? else {
+            return Ok(());
+        };
+
+        let mut wtr = csv::Writer::from_path(path)?;
+        let mut header = vec![
+            String::from("dungeon_identifier"),
+            String::from("build_signature"),
+        ];
+        header.extend(BARRIER_ELEMENTS.iter().map(|e| f!("{}_success_rate", e)));
+        wtr.write_record(&header)?;
+
+        let mut rows = self.coverage.clone();
+        rows.sort_by(|a, b| a.0.cmp(&b.0));
+        for (zone, row) in rows {
+            let mut record = vec![zone, self.team.get_build_signature()];
+            for element in BARRIER_ELEMENTS {
+                record.push(
+                    row.iter()
+                        .find(|(e, _)| *e == element)
+                        .map(|(_, success_rate)| fmt_f64(*success_rate))
+                        .unwrap_or_default(),
+                );
+            }
+            wtr.write_record(&record)?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+}
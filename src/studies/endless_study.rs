@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dungeons::TrialDungeon,
+    formatting::serialize_rounded,
+    heroes::{create_team, SimHero, Team},
+    progress::{create_study_progress, ProgressReporter},
+    rng::{create_sim_rng, SimRng},
+    simulations::create_simulation,
+    studies::*,
+};
+
+/// An extension of Study for simulating builds against event-style content, where each floor's enemy is stronger than the last
+/// Like runoff scoring, each build is retested on the next floor until it no longer succeeds or max_floors is reached
+/// Study simulation_qty is the number of simulations per build per floor
+pub struct EndlessStudy {
+    study: Study,
+    builds: Vec<SimHero>, // Each build is simulated alongside the fixed party members
+    party: Vec<SimHero>,
+    trial_dungeon: TrialDungeon, // The encounter of the first floor
+    floor_scaling: f64, // The fraction each floor's enemy hp, damage, and defense cap grow by over the previous floor, compounding
+    max_floors: usize,
+}
+
+/// Every build must fit in the dungeon alongside the fixed party members
+pub fn create_endless_study(
+    identifier: String,
+    description: String,
+    simulation_qty: i32,
+    builds: Vec<SimHero>,
+    party: Vec<SimHero>,
+    trial_dungeon: TrialDungeon,
+    floor_scaling: f64,
+    max_floors: usize,
+    hero_builder_information: HeroBuilderInformation,
+) -> Result<EndlessStudy, &'static str> {
+    if builds.is_empty() {
+        return Err("builds must not be empty");
+    }
+    if party.len() + 1 > trial_dungeon.dungeon.get_max_num_heroes() as usize {
+        return Err("party has more heroes than the dungeon allows alongside a build");
+    }
+    if floor_scaling < 0.0 {
+        return Err("floor_scaling must be >= 0");
+    }
+    if max_floors < 1 {
+        return Err("max_floors must be > 0");
+    }
+
+    return Ok(EndlessStudy {
+        study: create_study(
+            identifier,
+            description,
+            simulation_qty,
+            100.0,
+            ResultFormat::Wide,
+            false,
+            false,
+            None,
+            hero_builder_information,
+        ),
+        builds,
+        party,
+        trial_dungeon,
+        floor_scaling,
+        max_floors,
+    });
+}
+
+impl Runnable for EndlessStudy {
+    /// Run every build up the floors, then print and save the expected deepest floor of each build
+    fn run_with_progress(&mut self, reporter: &mut dyn ProgressReporter) {
+        self.study.status = StudyStatus::Running;
+
+        let mut progress = create_study_progress(reporter, Some(self.builds.len() as u64), 0);
+
+        let mut floor_records: Vec<EndlessFloorCSVRecord> = vec![];
+        let mut build_records: Vec<EndlessBuildCSVRecord> = vec![];
+        let mut rng = create_sim_rng(self.study.rng);
+        for build in self.builds.clone() {
+            let records = self.run_build(&build, &mut rng);
+            build_records.push(create_build_record(build.get_identifier(), &records));
+            floor_records.extend(records);
+            progress.inc(1);
+        }
+        progress.finish();
+
+        build_records.sort_by(|a, b| {
+            b.expected_deepest_floor
+                .total_cmp(&a.expected_deepest_floor)
+        });
+        for record in build_records.iter() {
+            self.study.print(&f!(
+                "{}: expected deepest floor {:.2}, deepest floor cleared {}, floors cleared in at least half of runs {}\n",
+                record.build,
+                record.expected_deepest_floor,
+                record.deepest_floor_cleared,
+                record.reliable_floor
+            ));
+        }
+
+        self.save_records_to_csv("endless_floors.csv", &floor_records)
+            .unwrap();
+        self.save_records_to_csv("endless_builds.csv", &build_records)
+            .unwrap();
+        self.study.status = StudyStatus::Finished;
+    }
+
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+}
+
+impl EndlessStudy {
+    /// Simulate a build on each floor in turn, stopping after the first floor it never clears
+    /// Heroes start every floor at full hp, so a run's deepest floor is cleared with the product of each floor's success rate up to it
+    fn run_build(&mut self, build: &SimHero, rng: &mut SimRng) -> Vec<EndlessFloorCSVRecord> {
+        let mut heroes = self.party.clone();
+        heroes.push(build.clone());
+        let team = create_team(heroes, None).unwrap();
+
+        let mut records: Vec<EndlessFloorCSVRecord> = vec![];
+        let mut reach_rate = 1.0;
+        for floor in 1..=self.max_floors {
+            let stat_multiplier = (1.0 + self.floor_scaling).powi(floor as i32 - 1);
+            let mut successes = 0usize;
+            for _ in 0..self.study.simulation_qty {
+                if self.run_floor_simulation(&team, stat_multiplier, rng) {
+                    successes += 1;
+                }
+            }
+            self.study
+                .add_simulations(self.study.simulation_qty.max(0) as usize);
+
+            let success_rate = successes as f64 / (self.study.simulation_qty as f64).max(1.0);
+            records.push(EndlessFloorCSVRecord {
+                build: build.get_identifier(),
+                floor,
+                stat_multiplier,
+                success_rate,
+                reach_rate,
+            });
+            reach_rate *= success_rate;
+            if successes == 0 {
+                break;
+            }
+        }
+        return records;
+    }
+
+    fn run_floor_simulation(&self, team: &Team, stat_multiplier: f64, rng: &mut SimRng) -> bool {
+        let mut encounter = self
+            .trial_dungeon
+            .dungeon
+            .generate_encounter_from_dungeon(
+                &vec![self.trial_dungeon.difficulty],
+                self.trial_dungeon.force_minibosses,
+                rng,
+            )
+            .unwrap();
+        encounter.scale_stats(stat_multiplier);
+        let mut simulation = create_simulation(
+            team,
+            encounter,
+            vec![],
+            self.trial_dungeon.max_rounds,
+            self.trial_dungeon.initiative,
+            self.trial_dungeon.targeting,
+            false,
+        )
+        .unwrap();
+        return simulation.run(rng).unwrap().is_success();
+    }
+
+    fn save_records_to_csv<T: Serialize>(
+        &mut self,
+        file_name: &str,
+        records: &[T],
+    ) -> Result<(), std::io::Error> {
+        let Some(path) = self.study.create_output_path(file_name)? else {
+            return Ok(());
+        };
+
+        let mut wtr = csv::Writer::from_path(path)?;
+        for record in records.iter() {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+}
+
+/// Summarize a build's floors, where the expected deepest floor cleared is the sum over floors of the chance of clearing every floor up to it
+fn create_build_record(build: String, floors: &[EndlessFloorCSVRecord]) -> EndlessBuildCSVRecord {
+    return EndlessBuildCSVRecord {
+        build,
+        expected_deepest_floor: floors
+            .iter()
+            .map(|floor| floor.reach_rate * floor.success_rate)
+            .sum(),
+        deepest_floor_cleared: floors
+            .iter()
+            .filter(|floor| floor.success_rate > 0.0)
+            .map(|floor| floor.floor)
+            .max()
+            .unwrap_or(0),
+        reliable_floor: floors
+            .iter()
+            .take_while(|floor| floor.reach_rate * floor.success_rate >= 0.5)
+            .count(),
+    };
+}
+
+/// The success rate of a build on a single floor
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct EndlessFloorCSVRecord {
+    build: String,
+    floor: usize,
+    #[serde(serialize_with = "serialize_rounded")]
+    stat_multiplier: f64, // The multiplier on the first floor's enemy hp, damage, and defense cap
+    #[serde(serialize_with = "serialize_rounded")]
+    success_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    reach_rate: f64, // The chance a run clears every floor before this one
+}
+
+/// How deep a build is expected to get
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct EndlessBuildCSVRecord {
+    build: String,
+    #[serde(serialize_with = "serialize_rounded")]
+    expected_deepest_floor: f64, // The expected deepest floor cleared by a run, 0 if the first floor is failed
+    deepest_floor_cleared: usize, // The deepest floor cleared by any run
+    reliable_floor: usize,        // The deepest floor cleared by at least half of runs
+}
@@ -0,0 +1,319 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    build_codes::encode_build_code,
+    dungeons::TrialDungeon,
+    formatting::serialize_rounded,
+    hero_builder::Hero,
+    heroes::{create_team, SimHero, Team},
+    inputs::HeroInput,
+    progress::ProgressReporter,
+    studies::*,
+    study_queue::load_checkpoint,
+};
+
+/// An extension of Study for ranking every loadout that can be made from candidate items for each equipment slot of a single hero
+/// Loadouts are ranked by the subject hero's survival rate, then its average damage
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GearOptimizationStudy {
+    study: Study,
+    static_heroes: Vec<SimHero>, // The fixed party members that fight alongside the subject hero in every trial
+    subject_hero_builder: Hero, // The starting build of the hero, whose class, level, skills, and enchantments every loadout shares
+    slot_candidates: Vec<Vec<String>>, // The items to try in each slot (0-5), a slot with no candidates keeps its current item
+    trial_dungeon: TrialDungeon,
+    loadout_index: usize, // The index of the next loadout to trial, counting through the slots' candidates like the digits of a number
+    rankings: Vec<GearRankingCSVRecord>, // The subject hero's performance with each completed loadout
+}
+
+/// Candidates are filtered to the items the subject hero's class allows in each slot, and the subject hero is always placed after the static heroes
+pub fn create_gear_optimization_study(
+    identifier: String,
+    description: String,
+    simulation_qty: i32,
+    static_heroes: Vec<SimHero>,
+    subject_hero_builder: Hero,
+    slot_candidates: [Vec<String>; 6],
+    trial_dungeon: TrialDungeon,
+    result_format: ResultFormat,
+    save_raw_results: bool,
+    save_msgpack: bool,
+    dataset_path: Option<String>,
+    hero_builder_information: HeroBuilderInformation,
+) -> Result<GearOptimizationStudy, &'static str> {
+    if static_heroes.len() > 4 {
+        return Err("cannot form party with > 4 static heroes plus the subject hero");
+    }
+    let class = hero_builder_information
+        .hero_classes
+        .get(&subject_hero_builder.get_class())
+        .ok_or("subject hero has an unknown class")?;
+    let mut filtered_candidates: Vec<Vec<String>> = vec![];
+    for (slot, candidates) in slot_candidates.iter().enumerate() {
+        let mut filtered: Vec<String> = vec![];
+        for candidate in candidates {
+            let blueprint = hero_builder_information
+                .bp_map
+                .get(candidate)
+                .ok_or("candidate item not found in blueprints")?;
+            if class
+                .get_equipment_allowed(slot)
+                .contains(&blueprint.get_type())
+                && !filtered.contains(candidate)
+            {
+                filtered.push(candidate.to_string());
+            }
+        }
+        if !candidates.is_empty() && filtered.is_empty() {
+            return Err("no candidate for a slot is allowed for the subject hero's class");
+        }
+        filtered_candidates.push(filtered);
+    }
+    if filtered_candidates
+        .iter()
+        .all(|candidates| candidates.is_empty())
+    {
+        return Err("at least one slot must have candidate items");
+    }
+
+    return Ok(GearOptimizationStudy {
+        study: create_study(
+            identifier,
+            description,
+            simulation_qty,
+            100.0,
+            result_format,
+            save_raw_results,
+            save_msgpack,
+            dataset_path,
+            hero_builder_information,
+        ),
+        static_heroes,
+        subject_hero_builder,
+        slot_candidates: filtered_candidates,
+        trial_dungeon,
+        loadout_index: 0,
+        rankings: vec![],
+    });
+}
+
+impl Runnable for GearOptimizationStudy {
+    /// Handle running trials for the study, then save and print the loadout rankings
+    fn run_with_progress(&mut self, reporter: &mut dyn ProgressReporter) {
+        let Some(mut progress) = run_trial_batches(self, reporter) else {
+            return;
+        };
+
+        self.study.flush_trial_results();
+        self.save_rankings_to_csv().unwrap();
+        self.study.remove_resume_checkpoint();
+        self.study.status = StudyStatus::Finished;
+        progress.finish();
+        self.print_rankings(5);
+    }
+
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+
+    fn attach_to_queue(&mut self, queue_id: usize) -> Result<(), std::io::Error> {
+        if let Some(checkpoint) = load_checkpoint(&get_checkpoint_path(queue_id))? {
+            *self = checkpoint;
+        }
+        self.study.queue_id = Some(queue_id);
+        return Ok(());
+    }
+
+    fn set_thread_qty(&mut self, thread_qty: usize) -> Result<(), &'static str> {
+        return self.study.set_thread_qty(thread_qty);
+    }
+
+    fn set_ranking_limit(&mut self, ranking_limit: usize) -> Result<(), &'static str> {
+        return self.study.set_ranking_limit(ranking_limit);
+    }
+}
+
+impl BatchedStudy for GearOptimizationStudy {
+    type Variation = (usize, Hero);
+    const RESUMABLE_KIND: ResumableStudyKind = ResumableStudyKind::GearOptimization;
+
+    fn count_progress(&self) -> (usize, usize) {
+        return (self.count_loadouts_total(), self.loadout_index);
+    }
+
+    fn count_variations_remaining(&self) -> usize {
+        return self.count_loadouts_remaining();
+    }
+
+    /// Add each of the next loadouts to the static party, skipping loadouts that break the class's slot rules
+    fn get_next_variations(&self, batch_size: usize) -> Vec<((usize, Hero), String, Team)> {
+        return (self.loadout_index..self.count_loadouts_total())
+            .filter_map(|loadout_index| {
+                return self
+                    .create_loadout(loadout_index)
+                    .map(|hero| (loadout_index, hero));
+            })
+            .take(batch_size)
+            .map(|(loadout_index, hero)| {
+                let mut party = self.static_heroes.clone();
+                party.push(
+                    self.study
+                        .hero_builder_information
+                        .build_sim_hero(hero.clone())
+                        .unwrap(),
+                );
+                let description = hero.describe_changes_from(&self.subject_hero_builder);
+                (
+                    (loadout_index, hero),
+                    description,
+                    create_team(party, None).unwrap(),
+                )
+            })
+            .collect();
+    }
+
+    fn get_batch_dungeon(&self) -> &TrialDungeon {
+        return &self.trial_dungeon;
+    }
+
+    fn record_trial(
+        &mut self,
+        (loadout_index, hero): &(usize, Hero),
+        _trial: &Trial,
+        trial_result: &TrialResult,
+    ) {
+        let hero_index = self.static_heroes.len();
+        self.rankings.push(GearRankingCSVRecord {
+            rank: 0,
+            changes: trial_result.get_trial_description(),
+            subject_survival_rate: trial_result.get_hero_survival_rate(hero_index),
+            subject_avg_dmg: trial_result.get_hero_avg_dmg(hero_index),
+            success_rate: trial_result.get_success_rate(),
+            build_signature: trial_result.get_build_signature(),
+            build_code: encode_build_code(&HeroInput::from(hero.clone())),
+        });
+        retain_top_rankings(
+            &mut self.rankings,
+            self.study.get_ranking_limit(),
+            compare_gear_rankings,
+        );
+        self.loadout_index = *loadout_index + 1;
+    }
+
+    /// Loadouts that break the class's slot rules are skipped
+    fn skip_untrialable_variations(&mut self) {
+        while self.count_loadouts_remaining() > 0
+            && self.create_loadout(self.loadout_index).is_none()
+        {
+            self.loadout_index += 1;
+        }
+    }
+}
+
+impl GearOptimizationStudy {
+    pub fn count_loadouts_total(&self) -> usize {
+        return self
+            .slot_candidates
+            .iter()
+            .map(|candidates| candidates.len().max(1))
+            .product();
+    }
+
+    pub fn count_loadouts_remaining(&self) -> usize {
+        return self.count_loadouts_total() - self.loadout_index;
+    }
+
+    /// Returns the subject hero wearing the loadout at the given index, with any enchantments its new items can't hold removed
+    /// The hero is revalidated, since a new item's elemental affinity can change its element qty. Returns None if the loadout breaks its class's slot rules
+    fn create_loadout(&self, loadout_index: usize) -> Option<Hero> {
+        let hbi = &self.study.hero_builder_information;
+        let mut hero = self.subject_hero_builder.clone();
+        let mut remainder = loadout_index;
+        for (slot, candidates) in self.slot_candidates.iter().enumerate() {
+            if candidates.is_empty() {
+                continue;
+            }
+            let item = &candidates[remainder % candidates.len()];
+            remainder /= candidates.len();
+            hero.set_equipment(slot, item.to_string());
+            hero.clear_ineligible_enchantments(slot, &hbi.bp_map[item]);
+        }
+        hero.set_identifier(f!(
+            "{}_Gear{}",
+            self.subject_hero_builder.get_identifier(),
+            loadout_index + 1
+        ));
+        hbi.hero_classes[&hero.get_class()]
+            .check_slot_rules(&hero.get_equipment_equipped(), &hbi.bp_map)
+            .ok()?;
+        hero.validate_equipment(&hbi.bp_map, &hbi.hero_classes)
+            .unwrap();
+        return Some(hero);
+    }
+
+    /// Sort the loadouts by the subject hero's survival rate, then its average damage, and number them
+    fn sort_rankings(&mut self) {
+        rank_top_rankings(
+            &mut self.rankings,
+            self.study.get_ranking_limit(),
+            compare_gear_rankings,
+        );
+        for (i, record) in self.rankings.iter_mut().enumerate() {
+            record.rank = i + 1;
+        }
+    }
+
+    fn save_rankings_to_csv(&mut self) -> Result<(), std::io::Error> {
+        self.sort_rankings();
+
+        let Some(path) = self.study.create_output_path("gear_rankings.csv")? else {
+            return Ok(());
+        };
+
+        let mut wtr = csv::Writer::from_path(path)?;
+        for record in self.rankings.iter() {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+
+    fn print_rankings(&self, qty: usize) {
+        for record in self.rankings.iter().take(qty) {
+            self.study.print(&f!(
+                "{}. survival rate {:.2}, average damage {:.0}: {}\n   build code: {}\n",
+                record.rank,
+                record.subject_survival_rate,
+                record.subject_avg_dmg,
+                record.changes,
+                record.build_code
+            ));
+        }
+    }
+}
+
+/// Orders loadouts by the subject hero's survival rate, then its average damage
+fn compare_gear_rankings(a: &GearRankingCSVRecord, b: &GearRankingCSVRecord) -> Ordering {
+    return b
+        .subject_survival_rate
+        .total_cmp(&a.subject_survival_rate)
+        .then(b.subject_avg_dmg.total_cmp(&a.subject_avg_dmg));
+}
+
+/// A single ranked loadout, with a build code that can be passed to import-build
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct GearRankingCSVRecord {
+    rank: usize,
+    changes: String, // How the loadout differs from the starting build
+    #[serde(serialize_with = "serialize_rounded")]
+    subject_survival_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    subject_avg_dmg: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    success_rate: f64,
+    build_signature: String,
+    build_code: String,
+}
@@ -0,0 +1,395 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    build_codes::encode_build_code,
+    dungeons::TrialDungeon,
+    formatting::serialize_rounded,
+    hero_builder::{Hero, PET_ITEM_TYPE},
+    heroes::{create_team, SimHero},
+    inputs::HeroInput,
+    progress::{create_study_progress, ProgressReporter},
+    skills::find_skill_conflict,
+    studies::*,
+    trials::TrialResult,
+};
+
+/// Defines the parts of a hero's build the optimizer may change
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, strum::Display, strum::EnumString,
+)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum OptimizationAxis {
+    Skills, // Swap one skill at a time for another skill the class can use
+    Gear, // Swap one item at a time for another allowed item, keeping quality and any sockets the new item can hold
+    Pet, // Swap the familiar in each slot that allows one for every other familiar, keeping quality and any sockets the new familiar can hold
+}
+
+/// The highest tier items of each allowed type considered for a gear slot
+const GEAR_CANDIDATES_PER_TYPE: usize = 8;
+
+/// An extension of Study for searching for a better build of a single hero within a time budget
+/// Uses coordinate ascent: each skill slot and gear slot in turn is set to whichever candidate scores best, repeating until a full pass finds no improvement or the budget runs out
+pub struct HeroOptimizationStudy {
+    study: Study,
+    static_heroes: Vec<SimHero>, // The fixed party members that fight alongside the subject hero in every trial
+    subject_hero_builder: Hero,  // The starting build of the hero being optimized
+    trial_dungeon: TrialDungeon,
+    axes: Vec<OptimizationAxis>,
+    budget: Duration, // The search stops starting new trials once this much time has passed
+    evaluated: Vec<(Hero, TrialResult)>, // Every build trialed so far, in the order it was trialed
+}
+
+/// The subject hero is always placed after the static heroes in the team
+pub fn create_hero_optimization_study(
+    identifier: String,
+    description: String,
+    simulation_qty: i32,
+    static_heroes: Vec<SimHero>,
+    subject_hero_builder: Hero,
+    trial_dungeon: TrialDungeon,
+    axes: Vec<OptimizationAxis>,
+    budget: Duration,
+    result_format: ResultFormat,
+    save_raw_results: bool,
+    save_msgpack: bool,
+    dataset_path: Option<String>,
+    hero_builder_information: HeroBuilderInformation,
+) -> Result<HeroOptimizationStudy, &'static str> {
+    if static_heroes.len() > 4 {
+        return Err("cannot form party with > 4 static heroes plus the subject hero");
+    }
+    if axes.is_empty() {
+        return Err("at least one optimization axis must be given");
+    }
+    if !hero_builder_information
+        .hero_classes
+        .contains_key(&subject_hero_builder.get_class())
+    {
+        return Err("subject hero has an unknown class");
+    }
+    if axes.contains(&OptimizationAxis::Pet)
+        && !(0..6).any(|slot| {
+            hero_builder_information.hero_classes[&subject_hero_builder.get_class()]
+                .get_equipment_allowed(slot)
+                .iter()
+                .any(|item_type| item_type == PET_ITEM_TYPE)
+        })
+    {
+        return Err("subject hero's class cannot equip a pet");
+    }
+
+    return Ok(HeroOptimizationStudy {
+        study: create_study(
+            identifier,
+            description,
+            simulation_qty,
+            100.0,
+            result_format,
+            save_raw_results,
+            save_msgpack,
+            dataset_path,
+            hero_builder_information,
+        ),
+        static_heroes,
+        subject_hero_builder,
+        trial_dungeon,
+        axes,
+        budget,
+        evaluated: vec![],
+    });
+}
+
+/// Parse a time budget such as "90s", "30m", or "2h", treating a bare number as seconds
+pub fn parse_budget(budget: &str) -> Result<Duration, &'static str> {
+    let (number, unit_seconds) = match budget.chars().last() {
+        Some('s') => (&budget[..budget.len() - 1], 1.0),
+        Some('m') => (&budget[..budget.len() - 1], 60.0),
+        Some('h') => (&budget[..budget.len() - 1], 3600.0),
+        _ => (budget, 1.0),
+    };
+    return match number.parse::<f64>() {
+        Ok(qty) if qty > 0.0 => Ok(Duration::from_secs_f64(qty * unit_seconds)),
+        _ => Err("budget must be a positive number of seconds, or end in s, m, or h"),
+    };
+}
+
+impl Runnable for HeroOptimizationStudy {
+    /// Search for better builds until no improvement is found or the budget runs out, then save the ranked recommendations
+    fn run_with_progress(&mut self, reporter: &mut dyn ProgressReporter) {
+        self.study.status = StudyStatus::Running;
+
+        let mut progress = create_study_progress(reporter, None, 0);
+
+        let timer = Instant::now();
+        let mut best = self.subject_hero_builder.clone();
+        let mut best_score = self.evaluate(&best);
+        let mut evaluation_qty: u64 = 1;
+        progress.update(evaluation_qty, Some(best_score.0));
+
+        'search: loop {
+            let mut improved = false;
+            for axis in self.axes.clone() {
+                let slot_qty = match axis {
+                    OptimizationAxis::Skills => 4,
+                    OptimizationAxis::Gear => 6,
+                    OptimizationAxis::Pet => 6,
+                };
+                for slot in 0..slot_qty {
+                    for candidate in self.get_candidates(&best, axis, slot) {
+                        if timer.elapsed() >= self.budget {
+                            break 'search;
+                        }
+                        let score = self.evaluate(&candidate);
+                        evaluation_qty += 1;
+                        if score > best_score {
+                            best = candidate;
+                            best_score = score;
+                            improved = true;
+                        }
+                        progress.update(evaluation_qty, Some(best_score.0));
+                    }
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+
+        self.study.flush_trial_results();
+        self.save_ranking_to_csv().unwrap();
+        self.print_recommendations(5);
+
+        self.study.status = StudyStatus::Finished;
+        progress.finish();
+    }
+
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+}
+
+impl HeroOptimizationStudy {
+    fn subject_hero_index(&self) -> usize {
+        return self.static_heroes.len();
+    }
+
+    /// Ranks builds by success rate, then the subject hero's survival rate, then its average damage
+    fn score(&self, result: &TrialResult) -> (f64, f64, f64) {
+        let hero_index = self.subject_hero_index();
+        return (
+            result.get_success_rate(),
+            result.get_hero_survival_rate(hero_index),
+            result.get_hero_avg_dmg(hero_index),
+        );
+    }
+
+    /// Trial a build, reusing the result if the same build was already trialed
+    fn evaluate(&mut self, hero: &Hero) -> (f64, f64, f64) {
+        let signature = hero.get_build_signature();
+        if let Some((_, result)) = self
+            .evaluated
+            .iter()
+            .find(|(evaluated, _)| evaluated.get_build_signature() == signature)
+        {
+            return self.score(result);
+        }
+
+        let mut party = self.static_heroes.clone();
+        party.push(
+            self.study
+                .hero_builder_information
+                .build_sim_hero(hero.clone())
+                .unwrap(),
+        );
+        let team = create_team(party, None).unwrap();
+        let trial = self.study.run_trial(
+            hero.describe_changes_from(&self.subject_hero_builder),
+            team,
+            &self.trial_dungeon,
+        );
+        self.study.save_trial_result(&trial);
+
+        let result = trial.create_trial_result();
+        let score = self.score(&result);
+        self.evaluated.push((hero.clone(), result));
+        return score;
+    }
+
+    /// Returns the variations of a build that change only the given slot of the given axis
+    fn get_candidates(&self, hero: &Hero, axis: OptimizationAxis, slot: usize) -> Vec<Hero> {
+        let hbi = &self.study.hero_builder_information;
+        let mut candidates: Vec<Hero> = vec![];
+        match axis {
+            OptimizationAxis::Skills => {
+                if slot >= hero.count_skill_slots_unlocked() {
+                    return candidates;
+                }
+                let skills = hero.get_skills();
+                let mut valid_skills: Vec<String> = hbi
+                    .hero_skill_tier_1_name_map
+                    .iter()
+                    .filter(|(k, _)| k.ends_with(" T4"))
+                    .map(|(_, v)| v.to_string())
+                    .collect();
+                valid_skills.sort();
+                for skill_name in valid_skills {
+                    let skill = &hbi.hero_skill_map[&skill_name];
+                    let mut new_skills = skills.to_vec();
+                    new_skills[slot] = skill_name;
+                    if skills.contains(&new_skills[slot])
+                        || !skill.get_classes_allowed().contains(&hero.get_class())
+                        || find_skill_conflict(&new_skills, &hbi.hero_skill_map).is_some()
+                    {
+                        continue;
+                    }
+                    let mut candidate = hero.clone();
+                    candidate.set_hero_skills(new_skills);
+                    candidates.push(candidate);
+                }
+            }
+            OptimizationAxis::Gear => {
+                let current = hero.get_equipment_equipped()[slot].to_string();
+                let class = &hbi.hero_classes[&hero.get_class()];
+                for item_type in class.get_equipment_allowed(slot) {
+                    let mut items: Vec<(&String, u8)> = hbi
+                        .bp_map
+                        .iter()
+                        .filter(|(_, blueprint)| blueprint.get_type() == item_type)
+                        .map(|(name, blueprint)| (name, blueprint.get_tier()))
+                        .collect();
+                    items.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+                    for (name, _) in items.into_iter().take(GEAR_CANDIDATES_PER_TYPE) {
+                        if *name == current {
+                            continue;
+                        }
+                        let mut candidate = hero.clone();
+                        candidate.set_equipment(slot, name.to_string());
+                        if class
+                            .check_slot_rules(&candidate.get_equipment_equipped(), &hbi.bp_map)
+                            .is_err()
+                        {
+                            continue;
+                        }
+                        candidate.clear_ineligible_enchantments(slot, &hbi.bp_map[name]);
+                        candidates.push(candidate);
+                    }
+                }
+            }
+            OptimizationAxis::Pet => {
+                let class = &hbi.hero_classes[&hero.get_class()];
+                if !class
+                    .get_equipment_allowed(slot)
+                    .iter()
+                    .any(|item_type| item_type == PET_ITEM_TYPE)
+                {
+                    return candidates;
+                }
+                let current = hero.get_equipment_equipped()[slot].to_string();
+                let mut pets: Vec<&String> = hbi
+                    .bp_map
+                    .iter()
+                    .filter(|(_, blueprint)| blueprint.get_type() == PET_ITEM_TYPE)
+                    .map(|(name, _)| name)
+                    .collect();
+                pets.sort();
+                for pet in pets {
+                    if *pet == current {
+                        continue;
+                    }
+                    let mut candidate = hero.clone();
+                    candidate.set_equipment(slot, pet.to_string());
+                    if class
+                        .check_slot_rules(&candidate.get_equipment_equipped(), &hbi.bp_map)
+                        .is_err()
+                    {
+                        continue;
+                    }
+                    candidate.clear_ineligible_enchantments(slot, &hbi.bp_map[pet]);
+                    candidates.push(candidate);
+                }
+            }
+        }
+        return candidates;
+    }
+
+    /// Returns the evaluated builds ordered from best to worst
+    fn get_ranking(&self) -> Vec<&(Hero, TrialResult)> {
+        let mut ranking: Vec<&(Hero, TrialResult)> = self.evaluated.iter().collect();
+        ranking.sort_by(|a, b| {
+            self.score(&b.1)
+                .partial_cmp(&self.score(&a.1))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        return ranking;
+    }
+
+    /// Returns a build code for a ranked build, renamed so importing it does not clash with the original hero
+    fn get_recommendation_build_code(&self, hero: &Hero, rank: usize) -> String {
+        let mut recommendation = hero.clone();
+        recommendation.set_identifier(f!("{}_Opt{}", hero.get_identifier(), rank));
+        return encode_build_code(&HeroInput::from(recommendation));
+    }
+
+    fn print_recommendations(&self, qty: usize) {
+        let original = self.score(&self.evaluated[0].1);
+        self.study.print(&f!(
+            "Evaluated {} builds of {} against {} (original success rate {:.2})\n",
+            self.evaluated.len(),
+            self.subject_hero_builder.get_identifier(),
+            self.trial_dungeon.dungeon._get_zone(),
+            original.0
+        ));
+        for (i, (hero, result)) in self.get_ranking().into_iter().take(qty).enumerate() {
+            self.study.print(&f!(
+                "{}. success rate {:.2}: {}\n   build code: {}\n",
+                i + 1,
+                result.get_success_rate(),
+                hero.describe_changes_from(&self.subject_hero_builder),
+                self.get_recommendation_build_code(hero, i + 1)
+            ));
+        }
+    }
+
+    fn save_ranking_to_csv(&mut self) -> Result<(), std::io::Error> {
+        let Some(path) = self.study.create_output_path("optimization_ranking.csv")? else {
+            return Ok(());
+        };
+
+        let hero_index = self.subject_hero_index();
+        let mut wtr = csv::Writer::from_path(path)?;
+        for (i, (hero, result)) in self.get_ranking().into_iter().enumerate() {
+            wtr.serialize(OptimizationRankingCSVRecord {
+                rank: i + 1,
+                changes: hero.describe_changes_from(&self.subject_hero_builder),
+                success_rate: result.get_success_rate(),
+                subject_survival_rate: result.get_hero_survival_rate(hero_index),
+                subject_avg_dmg: result.get_hero_avg_dmg(hero_index),
+                build_signature: hero.get_build_signature(),
+                build_code: self.get_recommendation_build_code(hero, i + 1),
+            })?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+}
+
+/// A single ranked build, with a build code that can be passed to import-build
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct OptimizationRankingCSVRecord {
+    rank: usize,
+    changes: String, // How the build differs from the starting build
+    #[serde(serialize_with = "serialize_rounded")]
+    success_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    subject_survival_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    subject_avg_dmg: f64,
+    build_signature: String,
+    build_code: String,
+}
@@ -0,0 +1,283 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dungeons::TrialDungeon,
+    equipment::BoosterType,
+    formatting::{fmt_f64, serialize_rounded},
+    hero_builder::{get_promotion_chain, Hero},
+    heroes::{create_team, SimHero, Team},
+    progress::{create_study_progress, ProgressReporter},
+    skills::{is_empty_skill, EMPTY_SKILL},
+    studies::*,
+};
+
+/// The subject hero rebuilt as a class of its promotion chain
+struct PromotionBuild {
+    class: String,
+    team: Team,
+    skills_dropped: Vec<String>, // The hero's skills the class can't equip, left empty in its build
+}
+
+/// An extension of Study for comparing a builder hero as each class of its promotion chain, such as a Chieftain against the same hero as a Barbarian
+/// Each class keeps the hero's level, rank, gear, and skills, so the comparison shows what promoting the hero (or having promoted it) is worth in each dungeon
+pub struct PromotionComparisonStudy {
+    study: Study,
+    builds: Vec<PromotionBuild>, // The classes of the chain the hero can be built as, first class first
+    skipped_classes: Vec<(String, String)>, // The classes of the chain the hero can't be built as, with why
+    dungeons: Vec<TrialDungeon>,
+    records: Vec<PromotionCSVRecord>, // Each completed trial, in dungeon then chain order
+}
+
+/// The subject hero is always placed after the static heroes
+pub fn create_promotion_comparison_study(
+    identifier: String,
+    description: String,
+    simulation_qty: i32,
+    static_heroes: Vec<SimHero>,
+    booster: Option<BoosterType>,
+    subject_hero_builder: Hero,
+    dungeons: Vec<TrialDungeon>,
+    result_format: ResultFormat,
+    save_raw_results: bool,
+    save_msgpack: bool,
+    dataset_path: Option<String>,
+    hero_builder_information: HeroBuilderInformation,
+) -> Result<PromotionComparisonStudy, String> {
+    if static_heroes.len() > 4 {
+        return Err(String::from(
+            "cannot form party with > 4 static heroes plus the subject hero",
+        ));
+    }
+    let chain = get_promotion_chain(
+        &subject_hero_builder.get_class(),
+        &hero_builder_information.hero_classes,
+    );
+    if chain.len() < 2 {
+        return Err(String::from(
+            "subject hero's class is neither promoted nor has a promotion to compare",
+        ));
+    }
+
+    let mut builds: Vec<PromotionBuild> = vec![];
+    let mut skipped_classes: Vec<(String, String)> = vec![];
+    for class in chain {
+        match create_promotion_build(&subject_hero_builder, &class, &hero_builder_information) {
+            Ok((hero, skills_dropped)) => {
+                let mut party = static_heroes.clone();
+                party.push(
+                    hero_builder_information
+                        .build_sim_hero(hero)
+                        .map_err(|e| e.to_string())?,
+                );
+                builds.push(PromotionBuild {
+                    class,
+                    team: create_team(party, booster)?,
+                    skills_dropped,
+                });
+            }
+            Err(e) => skipped_classes.push((class, e)),
+        }
+    }
+    if builds.len() < 2 {
+        let reasons: Vec<String> = skipped_classes
+            .iter()
+            .map(|(class, reason)| f!("{}: {}", class, reason))
+            .collect();
+        return Err(f!(
+            "subject hero can be built as fewer than two classes of its promotion chain ({})",
+            reasons.join("; ")
+        ));
+    }
+
+    return Ok(PromotionComparisonStudy {
+        study: create_study(
+            identifier,
+            description,
+            simulation_qty,
+            100.0,
+            result_format,
+            save_raw_results,
+            save_msgpack,
+            dataset_path,
+            hero_builder_information,
+        ),
+        builds,
+        skipped_classes,
+        dungeons,
+        records: vec![],
+    });
+}
+
+/// Returns the hero rebuilt as the class, with the skills the class can't equip emptied and returned,
+/// or why the hero can't be the class, such as gear the class can't equip or a level too low to be promoted
+fn create_promotion_build(
+    subject_hero_builder: &Hero,
+    class: &str,
+    hero_builder_information: &HeroBuilderInformation,
+) -> Result<(Hero, Vec<String>), String> {
+    let mut hero = subject_hero_builder.clone();
+    hero.set_class(class.to_string());
+    hero.validate_equipment(
+        &hero_builder_information.bp_map,
+        &hero_builder_information.hero_classes,
+    )
+    .map_err(|e| e.to_string())?;
+
+    let hero_class = &hero_builder_information.hero_classes[class];
+    let mut skills_dropped: Vec<String> = vec![];
+    let skills: Vec<String> = hero
+        .get_skills()
+        .into_iter()
+        .map(|skill_name| {
+            let equippable = is_empty_skill(&skill_name)
+                || hero_builder_information
+                    .hero_skill_map
+                    .get(&skill_name)
+                    .is_none_or(|skill| {
+                        skill.get_classes_allowed().contains(&class.to_string())
+                            && (!skill.get_requires_class_promotion() || hero_class.is_promoted())
+                    });
+            if equippable {
+                return skill_name;
+            }
+            skills_dropped.push(skill_name);
+            return String::from(EMPTY_SKILL);
+        })
+        .collect();
+    hero.set_hero_skills(skills);
+    if class != subject_hero_builder.get_class() {
+        hero.set_identifier(f!("{}_As_{}", subject_hero_builder.get_identifier(), class));
+    }
+    return Ok((hero, skills_dropped));
+}
+
+impl Runnable for PromotionComparisonStudy {
+    /// Run a trial for each class of the chain in each dungeon, then save the comparison and print each dungeon's gains
+    fn run_with_progress(&mut self, reporter: &mut dyn ProgressReporter) {
+        self.study.status = StudyStatus::Running;
+
+        let mut progress =
+            create_study_progress(reporter, Some(self.count_trials_total() as u64), 0);
+
+        for trial_dungeon in self.dungeons.iter() {
+            let mut first_success_rate: Option<f64> = None;
+            for (promotion_step, build) in self.builds.iter().enumerate() {
+                let trial = self.study.run_trial(
+                    build.class.to_string(),
+                    build.team.clone(),
+                    trial_dungeon,
+                );
+                let trial_result = self.study.save_trial_result(&trial);
+                let success_rate = trial_result.get_success_rate();
+                let first_success_rate = *first_success_rate.get_or_insert(success_rate);
+                self.records.push(PromotionCSVRecord {
+                    dungeon_identifier: trial_dungeon.dungeon._get_zone(),
+                    difficulty: trial_dungeon.difficulty,
+                    class: build.class.to_string(),
+                    promotion_step,
+                    success_rate,
+                    gain_over_first_class: success_rate - first_success_rate,
+                    skills_dropped: build.skills_dropped.join(" + "),
+                    build_signature: trial_result.get_build_signature(),
+                });
+                progress.inc(1);
+            }
+        }
+
+        self.study.flush_trial_results();
+        self.save_records_to_csv().unwrap();
+        self.study.print(&self.fmt_comparison());
+
+        self.study.status = StudyStatus::Finished;
+        progress.finish();
+    }
+
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+}
+
+impl PromotionComparisonStudy {
+    pub fn count_trials_total(&self) -> usize {
+        return self.dungeons.len() * self.builds.len();
+    }
+
+    /// Returns the classes the subject hero is trialed as, first class of the chain first
+    pub fn get_classes(&self) -> Vec<String> {
+        return self
+            .builds
+            .iter()
+            .map(|build| build.class.to_string())
+            .collect();
+    }
+
+    /// Describe each class's success rate in each dungeon against the first class, then the classes skipped and skills dropped
+    fn fmt_comparison(&self) -> String {
+        let mut res = String::new();
+        for dungeon_records in self.records.chunks(self.builds.len()) {
+            let classes: Vec<String> = dungeon_records
+                .iter()
+                .map(|record| match record.promotion_step {
+                    0 => f!("{} {}", record.class, fmt_f64(record.success_rate)),
+                    _ => f!(
+                        "{} {} ({:+.2})",
+                        record.class,
+                        fmt_f64(record.success_rate),
+                        record.gain_over_first_class
+                    ),
+                })
+                .collect();
+            res += &f!(
+                "{} at difficulty {}: {}\n",
+                dungeon_records[0].dungeon_identifier,
+                dungeon_records[0].difficulty,
+                classes.join(" -> ")
+            );
+        }
+        for (class, reason) in self.skipped_classes.iter() {
+            res += &f!("Skipped {}: {}\n", class, reason);
+        }
+        for build in self.builds.iter() {
+            if !build.skills_dropped.is_empty() {
+                res += &f!(
+                    "As {} the hero can't equip {}, so those slots were left empty\n",
+                    build.class,
+                    build.skills_dropped.join(", ")
+                );
+            }
+        }
+        return res;
+    }
+
+    fn save_records_to_csv(&mut self) -> Result<(), std::io::Error> {
+        let Some(path) = self.study.create_output_path("promotion_comparison.csv")? else {
+            return Ok(());
+        };
+
+        let mut wtr = csv::Writer::from_path(path)?;
+        for record in self.records.iter() {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+}
+
+/// The success rate of the subject hero as a class of its promotion chain in a single dungeon
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct PromotionCSVRecord {
+    dungeon_identifier: String,
+    difficulty: usize,
+    class: String,
+    promotion_step: usize, // The class's place in the chain trialed, from 0 for the first class
+    #[serde(serialize_with = "serialize_rounded")]
+    success_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    gain_over_first_class: f64, // The success rate gained over the first class trialed in the same dungeon
+    skills_dropped: String, // The hero's skills the class can't equip, joined by " + "
+    build_signature: String,
+}
@@ -0,0 +1,372 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::{
+    crafting::ItemQuality,
+    dungeons::TrialDungeon,
+    formatting::serialize_rounded,
+    hero_builder::Hero,
+    heroes::{create_team, SimHero, Team},
+    progress::ProgressReporter,
+    studies::*,
+    study_queue::load_checkpoint,
+};
+
+/// Whether each slot's quality is swept on its own, keeping the other slots as built, or every slot is set to each quality at once
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, strum::Display, strum::EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum QualitySweepMode {
+    PerSlot,
+    Uniform,
+}
+
+/// A change to the quality of the subject hero's gear, trialed against the hero as built
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+struct QualityVariation {
+    slot: Option<usize>, // The slot (0-5) changed, or None for every slot
+    quality: ItemQuality,
+}
+
+/// An extension of Study for finding how much each quality upgrade of a single hero's gear raises the party's success rate
+/// The hero is trialed as built, then with each slot (or every slot at once) at each quality, to decide which piece is worth rerolling first
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QualitySweepStudy {
+    study: Study,
+    static_heroes: Vec<SimHero>, // The fixed party members that fight alongside the subject hero in every trial
+    subject_hero_builder: Hero,
+    mode: QualitySweepMode,
+    trial_dungeon: TrialDungeon,
+    variations: Vec<QualityVariation>, // The qualities to trial after the hero as built, skipping any a slot already has
+    variation_index: usize, // The index of the next trial, where 0 is the hero as built and the rest are the variations in order
+    success_rates: Vec<f64>, // The success rate of each completed trial, in trial order
+    build_signatures: Vec<String>, // The build signature of each completed trial, in trial order
+}
+
+/// The subject hero is always placed after the static heroes
+pub fn create_quality_sweep_study(
+    identifier: String,
+    description: String,
+    simulation_qty: i32,
+    static_heroes: Vec<SimHero>,
+    subject_hero_builder: Hero,
+    mode: QualitySweepMode,
+    trial_dungeon: TrialDungeon,
+    result_format: ResultFormat,
+    save_raw_results: bool,
+    save_msgpack: bool,
+    dataset_path: Option<String>,
+    hero_builder_information: HeroBuilderInformation,
+) -> Result<QualitySweepStudy, &'static str> {
+    if static_heroes.len() > 4 {
+        return Err("cannot form party with > 4 static heroes plus the subject hero");
+    }
+    let current_qualities = subject_hero_builder
+        .get_equipment_quality()
+        .map(|quality| ItemQuality::from_str(&quality).ok());
+    if current_qualities.iter().any(|quality| quality.is_none()) {
+        return Err("subject hero has an item of unknown quality");
+    }
+
+    let variations: Vec<QualityVariation> = match mode {
+        QualitySweepMode::PerSlot => (0..6)
+            .flat_map(|slot| {
+                ItemQuality::iter()
+                    .filter(move |quality| Some(*quality) != current_qualities[slot])
+                    .map(move |quality| QualityVariation {
+                        slot: Some(slot),
+                        quality,
+                    })
+            })
+            .collect(),
+        QualitySweepMode::Uniform => ItemQuality::iter()
+            .map(|quality| QualityVariation {
+                slot: None,
+                quality,
+            })
+            .collect(),
+    };
+
+    return Ok(QualitySweepStudy {
+        study: create_study(
+            identifier,
+            description,
+            simulation_qty,
+            100.0,
+            result_format,
+            save_raw_results,
+            save_msgpack,
+            dataset_path,
+            hero_builder_information,
+        ),
+        static_heroes,
+        subject_hero_builder,
+        mode,
+        trial_dungeon,
+        variations,
+        variation_index: 0,
+        success_rates: vec![],
+        build_signatures: vec![],
+    });
+}
+
+impl Runnable for QualitySweepStudy {
+    /// Handle running trials for the study, then save and print the gain of each quality upgrade
+    fn run_with_progress(&mut self, reporter: &mut dyn ProgressReporter) {
+        let Some(mut progress) = run_trial_batches(self, reporter) else {
+            return;
+        };
+
+        self.study.flush_trial_results();
+        let records = self.create_gain_records();
+        self.save_gains_to_csv(&records).unwrap();
+        self.study.remove_resume_checkpoint();
+        self.study.status = StudyStatus::Finished;
+        progress.finish();
+        self.print_best_upgrades(&records, 5);
+    }
+
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+
+    fn attach_to_queue(&mut self, queue_id: usize) -> Result<(), std::io::Error> {
+        if let Some(checkpoint) = load_checkpoint(&get_checkpoint_path(queue_id))? {
+            *self = checkpoint;
+        }
+        self.study.queue_id = Some(queue_id);
+        return Ok(());
+    }
+
+    fn set_thread_qty(&mut self, thread_qty: usize) -> Result<(), &'static str> {
+        return self.study.set_thread_qty(thread_qty);
+    }
+
+    fn set_ranking_limit(&mut self, ranking_limit: usize) -> Result<(), &'static str> {
+        return self.study.set_ranking_limit(ranking_limit);
+    }
+}
+
+impl BatchedStudy for QualitySweepStudy {
+    type Variation = usize;
+    const RESUMABLE_KIND: ResumableStudyKind = ResumableStudyKind::QualitySweep;
+
+    fn count_progress(&self) -> (usize, usize) {
+        return (self.count_trials_total(), self.variation_index);
+    }
+
+    fn count_variations_remaining(&self) -> usize {
+        return self.count_trials_remaining();
+    }
+
+    /// Each build is the subject hero with the next qualities, added to the static party
+    fn get_next_variations(&self, batch_size: usize) -> Vec<(usize, String, Team)> {
+        let batch_qty = batch_size.min(self.count_trials_remaining());
+        return (self.variation_index..self.variation_index + batch_qty)
+            .map(|variation_index| {
+                let mut party = self.static_heroes.clone();
+                party.push(
+                    self.study
+                        .hero_builder_information
+                        .build_sim_hero(self.create_build(variation_index))
+                        .unwrap(),
+                );
+                (
+                    variation_index,
+                    self.describe_variation(variation_index),
+                    create_team(party, None).unwrap(),
+                )
+            })
+            .collect();
+    }
+
+    fn get_batch_dungeon(&self) -> &TrialDungeon {
+        return &self.trial_dungeon;
+    }
+
+    fn record_trial(
+        &mut self,
+        variation_index: &usize,
+        _trial: &Trial,
+        trial_result: &TrialResult,
+    ) {
+        self.success_rates.push(trial_result.get_success_rate());
+        self.build_signatures
+            .push(trial_result.get_build_signature());
+        self.variation_index = variation_index + 1;
+    }
+}
+
+impl QualitySweepStudy {
+    pub fn count_trials_total(&self) -> usize {
+        return self.variations.len() + 1;
+    }
+
+    pub fn count_trials_remaining(&self) -> usize {
+        return self.count_trials_total() - self.variation_index;
+    }
+
+    /// Returns the subject hero with the qualities of the trial at the given index, where 0 is the hero as built
+    fn create_build(&self, variation_index: usize) -> Hero {
+        let mut hero = self.subject_hero_builder.clone();
+        if variation_index == 0 {
+            return hero;
+        }
+        let variation = self.variations[variation_index - 1];
+        for slot in 0..6 {
+            if variation
+                .slot
+                .is_none_or(|variation_slot| variation_slot == slot)
+            {
+                hero.set_equipment_quality(slot, variation.quality.to_string());
+            }
+        }
+        hero.set_identifier(f!(
+            "{}_Quality{}",
+            self.subject_hero_builder.get_identifier(),
+            variation_index
+        ));
+        return hero;
+    }
+
+    /// Returns a short description of the trial at the given index, e.g. "slot 1: Flawless -> Epic"
+    fn describe_variation(&self, variation_index: usize) -> String {
+        if variation_index == 0 {
+            return String::from("Original");
+        }
+        let variation = self.variations[variation_index - 1];
+        return match variation.slot {
+            Some(slot) => f!(
+                "slot {}: {} -> {}",
+                slot + 1,
+                self.subject_hero_builder.get_equipment_quality()[slot],
+                variation.quality
+            ),
+            None => f!("all slots: {}", variation.quality),
+        };
+    }
+
+    /// Returns the index of the trial of the slot (or every slot, if None) at a quality, the hero as built if the slot already has it
+    fn get_trial_index(&self, slot: Option<usize>, quality: ItemQuality) -> usize {
+        if let Some(slot) = slot {
+            if self.subject_hero_builder.get_equipment_quality()[slot] == quality.to_string() {
+                return 0;
+            }
+        }
+        return 1 + self
+            .variations
+            .iter()
+            .position(|variation| variation.slot == slot && variation.quality == quality)
+            .unwrap();
+    }
+
+    /// Returns a record for every slot (or for every slot at once) at every quality, in slot and quality order,
+    /// with its gain over one quality lower and over the hero as built
+    fn create_gain_records(&self) -> Vec<QualityGainCSVRecord> {
+        let slots: Vec<Option<usize>> = match self.mode {
+            QualitySweepMode::PerSlot => (0..6).map(Some).collect(),
+            QualitySweepMode::Uniform => vec![None],
+        };
+        let mut records: Vec<QualityGainCSVRecord> = vec![];
+        for slot in slots {
+            let mut previous_success_rate: Option<f64> = None;
+            for quality in ItemQuality::iter() {
+                let trial_index = self.get_trial_index(slot, quality);
+                let success_rate = self.success_rates[trial_index];
+                let (current_quality, item) = match slot {
+                    Some(slot) => (
+                        self.subject_hero_builder.get_equipment_quality()[slot].to_string(),
+                        self.subject_hero_builder.get_equipment_equipped()[slot].to_string(),
+                    ),
+                    None => (String::from("-"), String::from("All")),
+                };
+                records.push(QualityGainCSVRecord {
+                    slot: slot.map_or(String::from("all"), |slot| (slot + 1).to_string()),
+                    item,
+                    current_quality,
+                    quality,
+                    success_rate,
+                    marginal_gain: previous_success_rate
+                        .map_or(0.0, |previous| success_rate - previous),
+                    gain_over_current: success_rate - self.success_rates[0],
+                    build_signature: self.build_signatures[trial_index].to_string(),
+                });
+                previous_success_rate = Some(success_rate);
+            }
+        }
+        return records;
+    }
+
+    fn save_gains_to_csv(
+        &mut self,
+        records: &[QualityGainCSVRecord],
+    ) -> Result<(), std::io::Error> {
+        let Some(path) = self.study.create_output_path("quality_gains.csv")? else {
+            return Ok(());
+        };
+
+        let mut wtr = csv::Writer::from_path(path)?;
+        for record in records.iter() {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+
+    /// Print the upgrades above each slot's current quality that raise the success rate the most over the hero as built
+    fn print_best_upgrades(&self, records: &[QualityGainCSVRecord], qty: usize) {
+        self.study
+            .print(&f!("Original: success rate {:.2}\n", self.success_rates[0]));
+        let mut upgrades: Vec<&QualityGainCSVRecord> = records
+            .iter()
+            .filter(|record| {
+                ItemQuality::from_str(&record.current_quality)
+                    .map_or(true, |current_quality| record.quality > current_quality)
+            })
+            .collect();
+        upgrades.sort_by(|a, b| b.gain_over_current.total_cmp(&a.gain_over_current));
+        for (i, record) in upgrades.iter().take(qty).enumerate() {
+            let change = match self.mode {
+                QualitySweepMode::PerSlot => f!(
+                    "slot {} ({}) {} -> {}",
+                    record.slot,
+                    record.item,
+                    record.current_quality,
+                    record.quality
+                ),
+                QualitySweepMode::Uniform => f!("all slots {}", record.quality),
+            };
+            self.study.print(&f!(
+                "{}. {}: success rate {:.2} ({:+.2})\n",
+                i + 1,
+                change,
+                record.success_rate,
+                record.gain_over_current
+            ));
+        }
+    }
+}
+
+/// The success rate of a slot, or every slot at once, at a single quality
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct QualityGainCSVRecord {
+    slot: String, // 1-6, or "all" when every slot is set to the quality at once
+    item: String,
+    current_quality: String, // The slot's quality as built, or "-" when every slot is set at once
+    quality: ItemQuality,
+    #[serde(serialize_with = "serialize_rounded")]
+    success_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    marginal_gain: f64, // The success rate gained over the same slot one quality lower, 0 for Normal
+    #[serde(serialize_with = "serialize_rounded")]
+    gain_over_current: f64, // The success rate gained over the hero as built, negative for a downgrade
+    build_signature: String,
+}
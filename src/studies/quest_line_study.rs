@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dungeons::{create_trial_dungeon, Dungeon, TrialDungeon},
+    formatting::serialize_rounded,
+    heroes::{create_team, SimHero},
+    progress::{create_study_progress, ProgressReporter},
+    quests::{Quest, QuestLine},
+    rng::{create_sim_rng, SimRng},
+    simulations::{create_simulation, SimOutcome, SimResult},
+    studies::*,
+};
+
+/// An extension of Study for simulating a party running every part of a multi-part quest line back to back
+/// Each run of the line is one simulation per part reached. Study simulation_qty is the number of runs of the whole line
+pub struct QuestLineStudy {
+    study: Study,
+    party: Vec<SimHero>,
+    quest_line: QuestLine,
+    parts: Vec<(Quest, TrialDungeon)>,
+    tallies: Vec<PartTally>, // Running totals for each part, in part order
+    line_successes: usize,   // The number of runs that cleared every part
+}
+
+/// Quests are looked up by identifier and matched to dungeons by zone. The party must fit in every part's dungeon
+pub fn create_quest_line_study(
+    identifier: String,
+    description: String,
+    simulation_qty: i32,
+    party: Vec<SimHero>,
+    quest_line: QuestLine,
+    quests: &HashMap<String, Quest>,
+    dungeons: &HashMap<String, Dungeon>,
+    hero_builder_information: HeroBuilderInformation,
+) -> Result<QuestLineStudy, &'static str> {
+    if party.is_empty() {
+        return Err("party must not be empty");
+    }
+
+    let mut parts: Vec<(Quest, TrialDungeon)> = vec![];
+    for quest_identifier in quest_line.get_quests() {
+        let quest = match quests.get(&quest_identifier) {
+            Some(quest) => quest.clone(),
+            None => return Err("quest line references a quest that was not loaded"),
+        };
+        let dungeon = match dungeons.get(&quest.get_dungeon()) {
+            Some(dungeon) => dungeon.clone(),
+            None => return Err("quest references a dungeon that was not loaded"),
+        };
+        if party.len() > dungeon.get_max_num_heroes() as usize {
+            return Err("party has more heroes than a quest line part's dungeon allows");
+        }
+        let trial_dungeon = create_trial_dungeon(dungeon, quest.get_difficulty(), None, None, None);
+        parts.push((quest, trial_dungeon));
+    }
+
+    return Ok(QuestLineStudy {
+        study: create_study(
+            identifier,
+            description,
+            simulation_qty,
+            100.0,
+            ResultFormat::Wide,
+            false,
+            false,
+            None,
+            hero_builder_information,
+        ),
+        party,
+        tallies: vec![Default::default(); parts.len()],
+        quest_line,
+        parts,
+        line_successes: 0,
+    });
+}
+
+impl Runnable for QuestLineStudy {
+    /// Run the quest line the configured number of times, then print and save where runs failed
+    fn run_with_progress(&mut self, reporter: &mut dyn ProgressReporter) {
+        self.study.status = StudyStatus::Running;
+
+        let runs_qty = self.study.simulation_qty as usize;
+        let mut progress = create_study_progress(reporter, Some(runs_qty as u64), 0);
+
+        let mut rng = create_sim_rng(self.study.rng);
+        for _ in 0..runs_qty {
+            if self.run_quest_line(&mut rng) {
+                self.line_successes += 1;
+            }
+            progress.inc(1);
+        }
+        progress.finish();
+
+        let records = self.create_part_records();
+        for record in records.iter() {
+            self.study.print(&f!(
+                "Part {} ({}): reached {:.2}, cleared {:.2} of runs reaching it, failed here {:.2} (wipe {:.2}, timeout {:.2})\n",
+                record.part,
+                record.quest,
+                record.reached_rate,
+                record.cleared_rate,
+                record.failure_rate,
+                record.wipe_rate,
+                record.timeout_rate
+            ));
+        }
+        self.study.print(&f!(
+            "{} success rate {:.2}\n",
+            self.quest_line.get_identifier(),
+            self.line_successes as f64 / runs_qty.max(1) as f64
+        ));
+
+        self.save_parts_to_csv(&records).unwrap();
+        self.study.status = StudyStatus::Finished;
+    }
+
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+}
+
+impl QuestLineStudy {
+    /// Run every part of the quest line in order until the party fails one, returning whether every part was cleared
+    /// Heroes defeated in a part sit out the rest of the line, and survivors start the next part with their remaining hp plus the line's recovery
+    fn run_quest_line(&mut self, rng: &mut SimRng) -> bool {
+        let mut party: Vec<SimHero> = self.party.clone();
+        let mut hp_percents: Vec<f64> = vec![1.0; party.len()];
+        for part_index in 0..self.parts.len() {
+            let sim_res = self.run_part_simulation(part_index, &party, rng);
+            self.study.add_simulations(1);
+
+            let hp_remaining = sim_res.get_team_hp_remaining();
+            let survivors: Vec<usize> = (0..party.len())
+                .filter(|i| hp_remaining[*i] > 0.0)
+                .collect();
+
+            let tally = &mut self.tallies[part_index];
+            tally.reached += 1;
+            tally.heroes_entering += party.len();
+            tally.hp_percent_entering += hp_percents.iter().sum::<f64>() / party.len() as f64;
+            tally.heroes_lost += party.len() - survivors.len();
+            match sim_res.get_outcome() {
+                SimOutcome::Victory => (),
+                SimOutcome::Wipe => tally.wipes += 1,
+                SimOutcome::Timeout => tally.timeouts += 1,
+            }
+            if !sim_res.is_success() || survivors.is_empty() {
+                return false;
+            }
+            tally.cleared += 1;
+
+            let recovery_percent = self.quest_line.get_recovery_percent();
+            hp_percents = survivors
+                .iter()
+                .map(|i| (hp_remaining[*i] / party[*i].get_hp_max() + recovery_percent).min(1.0))
+                .collect();
+            party = survivors.iter().map(|i| party[*i].clone()).collect();
+            for (hero, hp_percent) in party.iter_mut().zip(hp_percents.iter()) {
+                hero.set_hp_percent(*hp_percent);
+            }
+        }
+        return true;
+    }
+
+    fn run_part_simulation(
+        &self,
+        part_index: usize,
+        party: &[SimHero],
+        rng: &mut SimRng,
+    ) -> SimResult {
+        let trial_dungeon = &self.parts[part_index].1;
+        let team = create_team(party.to_vec(), None).unwrap();
+        let encounter = trial_dungeon
+            .dungeon
+            .generate_encounter_from_dungeon(
+                &vec![trial_dungeon.difficulty],
+                trial_dungeon.force_minibosses,
+                rng,
+            )
+            .unwrap();
+        let mut simulation = create_simulation(
+            &team,
+            encounter,
+            vec![],
+            trial_dungeon.max_rounds,
+            trial_dungeon.initiative,
+            trial_dungeon.targeting,
+            false,
+        )
+        .unwrap();
+        return simulation.run(rng).unwrap();
+    }
+
+    /// Convert the running totals of each part into rates, with failure rates taken over every run of the line
+    fn create_part_records(&self) -> Vec<QuestLinePartCSVRecord> {
+        let runs_qty = (self.study.simulation_qty as f64).max(1.0);
+        let mut records: Vec<QuestLinePartCSVRecord> = vec![];
+        for (i, ((quest, trial_dungeon), tally)) in
+            self.parts.iter().zip(self.tallies.iter()).enumerate()
+        {
+            let reached_qty = (tally.reached as f64).max(1.0);
+            records.push(QuestLinePartCSVRecord {
+                part: i + 1,
+                quest: quest.get_identifier(),
+                dungeon: trial_dungeon.dungeon._get_zone(),
+                difficulty: trial_dungeon.difficulty,
+                runs_reached: tally.reached,
+                reached_rate: tally.reached as f64 / runs_qty,
+                cleared_rate: tally.cleared as f64 / reached_qty,
+                failure_rate: (tally.reached - tally.cleared) as f64 / runs_qty,
+                wipe_rate: tally.wipes as f64 / runs_qty,
+                timeout_rate: tally.timeouts as f64 / runs_qty,
+                avg_heroes_entering: tally.heroes_entering as f64 / reached_qty,
+                avg_hp_percent_entering: tally.hp_percent_entering / reached_qty,
+                avg_heroes_lost: tally.heroes_lost as f64 / reached_qty,
+            });
+        }
+        return records;
+    }
+
+    fn save_parts_to_csv(
+        &mut self,
+        records: &[QuestLinePartCSVRecord],
+    ) -> Result<(), std::io::Error> {
+        let Some(path) = self.study.create_output_path("quest_line_results.csv")? else {
+            return Ok(());
+        };
+
+        let mut wtr = csv::Writer::from_path(path)?;
+        for record in records.iter() {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+}
+
+/// Running totals of every run that reached a part of the quest line
+#[derive(Debug, Clone, Default, PartialEq)]
+struct PartTally {
+    reached: usize,
+    cleared: usize, // A part is failed by a wipe or timeout, or by a victory with no surviving heroes
+    wipes: usize,
+    timeouts: usize,
+    heroes_entering: usize,
+    hp_percent_entering: f64, // The sum over runs of the party's average hp as a fraction of max hp when starting the part
+    heroes_lost: usize,
+}
+
+/// The failure points of a single part of a quest line
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct QuestLinePartCSVRecord {
+    part: usize,
+    quest: String,
+    dungeon: String,
+    difficulty: usize,
+    runs_reached: usize,
+    #[serde(serialize_with = "serialize_rounded")]
+    reached_rate: f64, // The fraction of all runs that started this part
+    #[serde(serialize_with = "serialize_rounded")]
+    cleared_rate: f64, // The fraction of runs starting this part that cleared it
+    #[serde(serialize_with = "serialize_rounded")]
+    failure_rate: f64, // The fraction of all runs that ended at this part
+    #[serde(serialize_with = "serialize_rounded")]
+    wipe_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    timeout_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    avg_heroes_entering: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    avg_hp_percent_entering: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    avg_heroes_lost: f64,
+}
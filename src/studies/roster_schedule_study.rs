@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dungeons::{Dungeon, TrialDungeon},
+    formatting::serialize_rounded,
+    heroes::{create_team, SimHero},
+    progress::{create_study_progress, ProgressReporter},
+    quests::Quest,
+    rng::{create_sim_rng, SimRng},
+    simulations::{create_simulation, SimResult},
+    studies::*,
+};
+
+/// Defines the strategies for choosing which quest the next free party is sent on
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, strum::Display, strum::EnumString,
+)]
+pub enum AssignmentStrategy {
+    RoundRobin,       // Cycle through the quests in order
+    MaxXpPerMinute,   // Expected xp (success rate * xp) per minute of quest duration
+    MaxGoldPerMinute, // Expected gold per minute of quest duration
+    MaxLootPerMinute, // Expected loot per minute of quest duration
+    SafestFirst,      // Highest success rate, minimizing injuries
+}
+
+/// An extension of Study for simulating full days of sending a roster of heroes on quests
+/// Each quest run is a single simulation, so outcomes and injuries vary day to day. Study simulation_qty is the number of days simulated per strategy
+pub struct RosterScheduleStudy {
+    study: Study,
+    roster: Vec<SimHero>, // The heroes available for quests, in order of priority when forming parties
+    quests: Vec<(Quest, TrialDungeon)>,
+    quest_slots: usize, // The maximum number of quests that can be in progress at once
+    day_minutes: f64,   // Quests may only be started within this many minutes
+    strategies: Vec<AssignmentStrategy>,
+    estimate_qty: usize, // The number of simulations used to estimate a party's success rate on a quest when choosing assignments
+    success_rate_cache: HashMap<(usize, Vec<usize>), f64>,
+    totals: Vec<ScheduleCSVRecord>,
+}
+
+/// Quests are matched to dungeons by zone and parties are formed from the first available heroes up to the dungeon's max heroes
+pub fn create_roster_schedule_study(
+    identifier: String,
+    description: String,
+    days_qty: i32,
+    roster: Vec<SimHero>,
+    quests: Vec<Quest>,
+    dungeons: &HashMap<String, Dungeon>,
+    quest_slots: usize,
+    day_minutes: f64,
+    strategies: Vec<AssignmentStrategy>,
+    estimate_qty: usize,
+    hero_builder_information: HeroBuilderInformation,
+) -> Result<RosterScheduleStudy, &'static str> {
+    if roster.is_empty() || quests.is_empty() || strategies.is_empty() {
+        return Err("roster, quests, and strategies must not be empty");
+    }
+    if quest_slots < 1 || estimate_qty < 1 {
+        return Err("quest_slots and estimate_qty must be > 0");
+    }
+    if day_minutes <= 0.0 {
+        return Err("day_minutes must be > 0");
+    }
+
+    let mut quest_dungeons: Vec<(Quest, TrialDungeon)> = vec![];
+    for quest in quests {
+        let dungeon = match dungeons.get(&quest.get_dungeon()) {
+            Some(dungeon) => dungeon.clone(),
+            None => return Err("quest references a dungeon that was not loaded"),
+        };
+        let trial_dungeon = crate::dungeons::create_trial_dungeon(
+            dungeon,
+            quest.get_difficulty(),
+            None,
+            None,
+            None,
+        );
+        quest_dungeons.push((quest, trial_dungeon));
+    }
+
+    return Ok(RosterScheduleStudy {
+        study: create_study(
+            identifier,
+            description,
+            days_qty,
+            100.0,
+            ResultFormat::Wide,
+            false,
+            false,
+            None,
+            hero_builder_information,
+        ),
+        roster,
+        quests: quest_dungeons,
+        quest_slots,
+        day_minutes,
+        strategies,
+        estimate_qty,
+        success_rate_cache: Default::default(),
+        totals: vec![],
+    });
+}
+
+impl Runnable for RosterScheduleStudy {
+    /// Simulate the configured number of days for each strategy, then save the per-strategy daily averages
+    fn run_with_progress(&mut self, reporter: &mut dyn ProgressReporter) {
+        self.study.status = StudyStatus::Running;
+
+        let days_qty = self.study.simulation_qty as usize;
+        let mut progress =
+            create_study_progress(reporter, Some((days_qty * self.strategies.len()) as u64), 0);
+
+        let mut rng = create_sim_rng(self.study.rng);
+        for strategy in self.strategies.clone() {
+            let mut record = ScheduleCSVRecord {
+                strategy: strategy.to_string(),
+                days_simulated: days_qty,
+                ..Default::default()
+            };
+            for _ in 0..days_qty {
+                self.simulate_day(strategy, &mut record, &mut rng);
+                progress.inc(1);
+            }
+            record.average_over_days();
+            self.study.print(&f!(
+                "{}: {:.2} xp, {:.2} gold, {:.2} loot per day\n",
+                record.strategy,
+                record.xp,
+                record.gold,
+                record.loot_qty
+            ));
+            self.totals.push(record);
+        }
+
+        self.save_schedule_to_csv().unwrap();
+        self.study.status = StudyStatus::Finished;
+        progress.finish();
+    }
+
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+}
+
+impl RosterScheduleStudy {
+    /// Run one day of quests, adding the day's totals to the record
+    fn simulate_day(
+        &mut self,
+        strategy: AssignmentStrategy,
+        record: &mut ScheduleCSVRecord,
+        rng: &mut SimRng,
+    ) {
+        let mut hero_available_at = vec![0.0f64; self.roster.len()];
+        let mut quests_in_progress_until: Vec<f64> = vec![];
+        let mut round_robin_index = 0;
+        let mut time = 0.0f64;
+
+        while time < self.day_minutes {
+            quests_in_progress_until.retain(|end| *end > time);
+
+            // Fill free quest slots with parties of available heroes
+            while quests_in_progress_until.len() < self.quest_slots {
+                let available: Vec<usize> = (0..self.roster.len())
+                    .filter(|i| hero_available_at[*i] <= time)
+                    .collect();
+                let quest_index =
+                    match self.choose_quest(strategy, &available, &mut round_robin_index, rng) {
+                        Some(quest_index) => quest_index,
+                        None => break,
+                    };
+                let party = self.form_party(quest_index, &available).unwrap();
+
+                let sim_res = self.run_quest_simulation(quest_index, &party, rng);
+                self.study.add_simulations(1);
+                let quest = &self.quests[quest_index].0;
+                record.quests_started += 1.0;
+                if sim_res.is_success() {
+                    let (xp, gold, loot_qty) = quest.get_rewards();
+                    // Each hero earns xp with its own bonus, recorded as the party's average
+                    let xp_percent = party
+                        .iter()
+                        .map(|hero_index| self.roster[*hero_index].get_xp_percent())
+                        .sum::<f64>()
+                        / party.len() as f64;
+                    record.quests_succeeded += 1.0;
+                    record.xp += xp * (1.0 + xp_percent);
+                    record.gold += gold;
+                    record.loot_qty += loot_qty;
+                }
+
+                let quest_end = time + quest.get_duration_minutes();
+                let hp_remaining = sim_res.get_team_hp_remaining();
+                for (party_index, hero_index) in party.iter().enumerate() {
+                    let injured = hp_remaining[party_index] <= 0.0;
+                    if injured {
+                        record.injuries += 1.0;
+                    }
+                    hero_available_at[*hero_index] = quest_end
+                        + quest.get_rest_minutes(
+                            injured,
+                            self.roster[*hero_index].get_rest_time_percent(),
+                        );
+                }
+                quests_in_progress_until.push(quest_end);
+            }
+
+            // Advance to the next time a quest slot or hero frees up
+            let next_time = quests_in_progress_until
+                .iter()
+                .chain(hero_available_at.iter())
+                .filter(|t| **t > time)
+                .cloned()
+                .reduce(f64::min);
+            match next_time {
+                Some(next_time) => time = next_time,
+                None => break,
+            }
+        }
+    }
+
+    /// Choose the quest to send the next party on, or None if no quest can be filled by the available heroes
+    fn choose_quest(
+        &mut self,
+        strategy: AssignmentStrategy,
+        available: &[usize],
+        round_robin_index: &mut usize,
+        rng: &mut SimRng,
+    ) -> Option<usize> {
+        let fillable: Vec<usize> = (0..self.quests.len())
+            .filter(|qi| self.form_party(*qi, available).is_some())
+            .collect();
+        if fillable.is_empty() {
+            return None;
+        }
+
+        if strategy == AssignmentStrategy::RoundRobin {
+            for offset in 0..self.quests.len() {
+                let quest_index = (*round_robin_index + offset) % self.quests.len();
+                if fillable.contains(&quest_index) {
+                    *round_robin_index = quest_index + 1;
+                    return Some(quest_index);
+                }
+            }
+            return None;
+        }
+
+        let mut best: Option<(usize, f64)> = None;
+        for quest_index in fillable {
+            let party = self.form_party(quest_index, available).unwrap();
+            let success_rate = self.estimate_success_rate(quest_index, &party, rng);
+            let quest = &self.quests[quest_index].0;
+            let (xp, gold, loot_qty) = quest.get_rewards();
+            let duration = quest.get_duration_minutes();
+            let score = match strategy {
+                AssignmentStrategy::MaxXpPerMinute => success_rate * xp / duration,
+                AssignmentStrategy::MaxGoldPerMinute => success_rate * gold / duration,
+                AssignmentStrategy::MaxLootPerMinute => success_rate * loot_qty / duration,
+                _ => success_rate,
+            };
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((quest_index, score));
+            }
+        }
+        return best.map(|(quest_index, _)| quest_index);
+    }
+
+    /// Form a party of the highest priority available heroes, or None if there are not enough to fill the quest
+    fn form_party(&self, quest_index: usize, available: &[usize]) -> Option<Vec<usize>> {
+        let party_size = std::cmp::min(
+            self.quests[quest_index].1.dungeon.get_max_num_heroes() as usize,
+            self.roster.len(),
+        );
+        if available.len() < party_size {
+            return None;
+        }
+        return Some(available[..party_size].to_vec());
+    }
+
+    fn run_quest_simulation(
+        &self,
+        quest_index: usize,
+        party: &[usize],
+        rng: &mut SimRng,
+    ) -> SimResult {
+        let trial_dungeon = &self.quests[quest_index].1;
+        let team = create_team(
+            party.iter().map(|i| self.roster[*i].clone()).collect(),
+            None,
+        )
+        .unwrap();
+        let encounter = trial_dungeon
+            .dungeon
+            .generate_encounter_from_dungeon(
+                &vec![trial_dungeon.difficulty],
+                trial_dungeon.force_minibosses,
+                rng,
+            )
+            .unwrap();
+        let mut simulation = create_simulation(
+            &team,
+            encounter,
+            vec![],
+            trial_dungeon.max_rounds,
+            trial_dungeon.initiative,
+            trial_dungeon.targeting,
+            false,
+        )
+        .unwrap();
+        return simulation.run(rng).unwrap();
+    }
+
+    fn estimate_success_rate(
+        &mut self,
+        quest_index: usize,
+        party: &[usize],
+        rng: &mut SimRng,
+    ) -> f64 {
+        let key = (quest_index, party.to_vec());
+        if let Some(success_rate) = self.success_rate_cache.get(&key) {
+            return *success_rate;
+        }
+        let successes = (0..self.estimate_qty)
+            .filter(|_| {
+                return self
+                    .run_quest_simulation(quest_index, party, rng)
+                    .is_success();
+            })
+            .count();
+        self.study.add_simulations(self.estimate_qty);
+        let success_rate = successes as f64 / self.estimate_qty as f64;
+        self.success_rate_cache.insert(key, success_rate);
+        return success_rate;
+    }
+
+    fn save_schedule_to_csv(&mut self) -> Result<(), std::io::Error> {
+        let Some(path) = self.study.create_output_path("roster_schedule.csv")? else {
+            return Ok(());
+        };
+
+        let mut wtr = csv::Writer::from_path(path)?;
+        for record in self.totals.iter() {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+}
+
+/// The average daily totals of a single assignment strategy
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+struct ScheduleCSVRecord {
+    strategy: String,
+    days_simulated: usize,
+    #[serde(rename = "avg_quests_started", serialize_with = "serialize_rounded")]
+    quests_started: f64,
+    #[serde(rename = "avg_quests_succeeded", serialize_with = "serialize_rounded")]
+    quests_succeeded: f64,
+    #[serde(rename = "avg_injuries", serialize_with = "serialize_rounded")]
+    injuries: f64,
+    #[serde(rename = "avg_xp", serialize_with = "serialize_rounded")]
+    xp: f64,
+    #[serde(rename = "avg_gold", serialize_with = "serialize_rounded")]
+    gold: f64,
+    #[serde(rename = "avg_loot_qty", serialize_with = "serialize_rounded")]
+    loot_qty: f64,
+}
+
+impl ScheduleCSVRecord {
+    /// Convert the accumulated totals into per-day averages
+    fn average_over_days(&mut self) {
+        let days = std::cmp::max(self.days_simulated, 1) as f64;
+        self.quests_started /= days;
+        self.quests_succeeded /= days;
+        self.injuries /= days;
+        self.xp /= days;
+        self.gold /= days;
+        self.loot_qty /= days;
+    }
+}
@@ -1,108 +1,290 @@
-use crate::{dungeons::Dungeon, heroes::Team, studies::*};
+use serde::{Deserialize, Serialize};
 
-/// An extension of Study for generating and ranking Trials for each combination of skills for a single hero on a team
+use crate::{
+    dungeons::TrialDungeon,
+    formatting::serialize_rounded,
+    hero_builder::Hero,
+    heroes::{create_team, Team},
+    progress::ProgressReporter,
+    studies::*,
+    study_queue::load_checkpoint,
+};
+
+/// An extension of Study for ranking every combination of skills for a single hero fighting a dungeon alone
+/// Combinations are ranked by success rate, then by the fewest average rounds to clear
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SingleHeroSkillStudy {
     study: Study,
-    base_team: Team,
-    subject_hero_identifier: String, // The identifier of the hero to vary upon, and whose performance will be analyzed for the purposes of this study
-    valid_skills: Vec<String>,       // The vector of all skills to be varied upon
-    valid_skills_count: i64,         // The number of valid skills to vary upon
-    preset_skills: Vec<String>, // A vector containing 0-3 innate skills that are preset and unchanging
-    varying_skill_slot_count: i64,   // The number of skill slots to vary
-    skill_combination_index: i64, // The current index of the combinations of the valid_skills list being trialed
-    dungeons: Vec<Dungeon>,
+    subject_hero_builder: Hero, // The hero builder representation of the subject hero, to be converted to a simhero for variation
+    skill_variations: SkillVariations, // The combinations of skills to vary the subject hero upon
+    skill_pairs: SkillPairMatrix, // The average success rate of each pair of varied skills
+    trial_dungeon: TrialDungeon,
+    rankings: Vec<SkillRankingCSVRecord>, // The subject hero's performance with each completed combination of skills
 }
 
+/// The skill pool is narrowed to the skills the subject hero can equip, see filter_skill_pool
+/// Every unlocked skill slot is varied, so a hero of a rank with all 4 slots unlocked is trialed with every legal 4 skill combination
 pub fn create_single_hero_skill_study(
     identifier: String,
     description: String,
     simulation_qty: i32,
-    runoff_scoring_threshold: f64,
-    base_team: Team,
-    valid_skills: Vec<String>,
-    preset_skills: Vec<String>,
-    subject_hero_identifier: String,
-    dungeons: Vec<Dungeon>,
-) -> SingleHeroSkillStudy {
-    let mut vs = valid_skills.clone();
-    vs.retain(|x| !preset_skills.contains(x));
-    vs.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
-    return SingleHeroSkillStudy {
+    subject_hero_builder: Hero,
+    skill_pool: Vec<String>,
+    rarities: Vec<String>,
+    trial_dungeon: TrialDungeon,
+    result_format: ResultFormat,
+    save_raw_results: bool,
+    save_msgpack: bool,
+    dataset_path: Option<String>,
+    hero_builder_information: HeroBuilderInformation,
+) -> Result<SingleHeroSkillStudy, &'static str> {
+    let valid_skills = filter_skill_pool(
+        &skill_pool,
+        &rarities,
+        &subject_hero_builder,
+        &hero_builder_information,
+    )?;
+    let skill_slot_qty = subject_hero_builder.count_skill_slots_unlocked();
+    if valid_skills.len() < skill_slot_qty {
+        return Err(
+            "fewer skills in the skill pool than the subject hero has skill slots unlocked",
+        );
+    }
+    let skill_variations = create_skill_variations(
+        valid_skills,
+        vec![],
+        skill_slot_qty,
+        &hero_builder_information.hero_skill_map,
+    );
+
+    return Ok(SingleHeroSkillStudy {
         study: create_study(
             identifier,
             description,
             simulation_qty,
-            runoff_scoring_threshold,
+            100.0,
+            result_format,
+            save_raw_results,
+            save_msgpack,
+            dataset_path,
+            hero_builder_information,
         ),
-        base_team,
-        subject_hero_identifier,
-        valid_skills_count: vs.len() as i64,
-        valid_skills: vs,
-        varying_skill_slot_count: 4 - preset_skills.len() as i64,
-        preset_skills,
-        skill_combination_index: 0,
-        dungeons,
-    };
+        subject_hero_builder,
+        skill_pairs: create_skill_pair_matrix(skill_variations.get_valid_skills()),
+        skill_variations,
+        trial_dungeon,
+        rankings: vec![],
+    });
 }
 
-/* Optimizing Variation Generation
-1. Identify the class of the subject hero and filter out incompatible skills
-2. Identify the build of the subject hero and filter out the mismatched weapon skills
-3. Consider the feasibility of restricting the first two skills to epic/rare and all remaining valid skills for the rest
+/// Returns the skills of the pool the hero can equip: those its class is allowed, those requiring a promoted class only if its class is promoted,
+/// and if any rarities are given, only those of the given rarities (e.g. Common and Rare when epic scrolls are out of reach)
+/// A skill's tier is set by the hero's element qty, so only the first skill of each skill line in the pool is kept
+fn filter_skill_pool(
+    skill_pool: &[String],
+    rarities: &[String],
+    hero: &Hero,
+    hero_builder_information: &HeroBuilderInformation,
+) -> Result<Vec<String>, &'static str> {
+    let class = hero_builder_information
+        .hero_classes
+        .get(&hero.get_class())
+        .ok_or("subject hero's class not found in hero classes")?;
+    let mut skill_lines: Vec<String> = vec![];
+    let mut res: Vec<String> = vec![];
+    for skill_name in skill_pool {
+        let skill = hero_builder_information
+            .hero_skill_map
+            .get(skill_name)
+            .ok_or("skill pool has a skill not found in hero skills")?;
+        if !skill.get_classes_allowed().contains(&hero.get_class())
+            || (skill.get_requires_class_promotion() && !class.is_promoted())
+            || (!rarities.is_empty() && !rarities.contains(&skill.get_rarity()))
+            || skill_lines.contains(&skill.get_tier_1_name())
+        {
+            continue;
+        }
+        skill_lines.push(skill.get_tier_1_name());
+        res.push(skill_name.to_string());
+    }
+    if res.is_empty() {
+        return Err("no skill in the skill pool can be equipped by the subject hero");
+    }
+    return Ok(res);
+}
 
-Limitations
-1. Must be able to resume from mid-generation by saving combination index, the current step, and the current list
-2. Combination generator is naive to the skills themselves, only works on indices of the current list
-2. a. Because of this, must skip incompatible_with skills while executing trials rather than before generation
-2. b. OR Decide a subset of these beforehand, prefiltering the list and yielding far fewer combinations! - In what universe is the bronze ever better than the gold?
-2. b. i. Note however that this does somewhat defeat the purpose of an objective ranking system for all skills...
-*/
+impl Runnable for SingleHeroSkillStudy {
+    /// Handle running trials for the study, then save and print the skill rankings
+    fn run_with_progress(&mut self, reporter: &mut dyn ProgressReporter) {
+        let Some(mut progress) = run_trial_batches(self, reporter) else {
+            return;
+        };
 
-impl SingleHeroSkillStudy {
-    pub fn count_skill_variations_completed(&self) -> i64 {
-        return self.skill_combination_index;
+        self.study.flush_trial_results();
+        self.save_rankings_to_csv().unwrap();
+        self.study
+            .save_skill_pair_matrix(&self.skill_pairs)
+            .unwrap();
+        self.study.remove_resume_checkpoint();
+        self.study.status = StudyStatus::Finished;
+        progress.finish();
+        self.print_rankings(5);
     }
-    pub fn count_skill_variations_total(&self) -> i64 {
-        return crate::combinations::count_combinations(
-            self.valid_skills_count,
-            self.varying_skill_slot_count,
-        );
+
+    fn get_study(&self) -> &Study {
+        return &self.study;
     }
-    pub fn count_skill_variations_remaining(&self) -> i64 {
-        return crate::combinations::count_combinations(
-            self.valid_skills_count,
-            self.varying_skill_slot_count,
-        ) - self.skill_combination_index;
-    }
-    pub fn get_skillset_at_specific_combination_index(&self, combination_index: i64) -> Vec<i64> {
-        return crate::combinations::iter_combination(
-            combination_index,
-            self.valid_skills_count,
-            self.varying_skill_slot_count,
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+
+    fn attach_to_queue(&mut self, queue_id: usize) -> Result<(), std::io::Error> {
+        if let Some(checkpoint) = load_checkpoint(&get_checkpoint_path(queue_id))? {
+            *self = checkpoint;
+        }
+        self.study.queue_id = Some(queue_id);
+        return Ok(());
+    }
+
+    fn set_thread_qty(&mut self, thread_qty: usize) -> Result<(), &'static str> {
+        return self.study.set_thread_qty(thread_qty);
+    }
+
+    fn set_ranking_limit(&mut self, ranking_limit: usize) -> Result<(), &'static str> {
+        return self.study.set_ranking_limit(ranking_limit);
+    }
+}
+
+impl BatchedStudy for SingleHeroSkillStudy {
+    type Variation = Vec<String>;
+    const RESUMABLE_KIND: ResumableStudyKind = ResumableStudyKind::SingleHeroSkill;
+
+    fn count_progress(&self) -> (usize, usize) {
+        return (
+            self.skill_variations.count_total() as usize,
+            self.skill_variations.count_completed() as usize,
         );
     }
-    pub fn get_skillset_at_current_combination_index(&self) -> Vec<i64> {
-        return crate::combinations::iter_combination(
-            self.skill_combination_index,
-            self.valid_skills_count,
-            self.varying_skill_slot_count,
+
+    fn count_variations_remaining(&self) -> usize {
+        return self.skill_variations.count_remaining() as usize;
+    }
+
+    /// Vary the subject hero's skills, the hero fighting alone
+    fn get_next_variations(&self, batch_size: usize) -> Vec<(Vec<String>, String, Team)> {
+        return self
+            .skill_variations
+            .get_next_full_translated_skillsets(batch_size)
+            .into_iter()
+            .map(|skill_variation| {
+                let mut new_hero = self.subject_hero_builder.clone();
+                new_hero.set_hero_skills(skill_variation.clone());
+                let party = vec![self
+                    .study
+                    .hero_builder_information
+                    .build_sim_hero(new_hero)
+                    .unwrap()];
+                let description = format!("{:?}", skill_variation);
+                (
+                    skill_variation,
+                    description,
+                    create_team(party, None).unwrap(),
+                )
+            })
+            .collect();
+    }
+
+    fn get_batch_dungeon(&self) -> &TrialDungeon {
+        return &self.trial_dungeon;
+    }
+
+    fn record_trial(
+        &mut self,
+        skill_variation: &Vec<String>,
+        _trial: &Trial,
+        trial_result: &TrialResult,
+    ) {
+        self.skill_pairs
+            .add_trial(skill_variation, trial_result.get_success_rate());
+        self.rankings.push(SkillRankingCSVRecord {
+            skills: trial_result.get_trial_description(),
+            build_signature: trial_result.get_build_signature(),
+            success_rate: trial_result.get_success_rate(),
+            average_rounds: trial_result.get_average_rounds(),
+            survival_rate: trial_result.get_hero_survival_rate(0),
+            avg_dmg: trial_result.get_hero_avg_dmg(0),
+        });
+        retain_top_rankings(
+            &mut self.rankings,
+            self.study.get_ranking_limit(),
+            compare_skill_rankings,
         );
+        self.skill_variations.increment_combination_index();
     }
-    pub fn increment_combination_index(&mut self) {
-        self.skill_combination_index += 1;
+}
+
+impl SingleHeroSkillStudy {
+    pub fn count_skill_variations_remaining(&self) -> i64 {
+        return self.skill_variations.count_remaining();
     }
-    pub fn translate_skillset_from_indices(&self, indices_array: Vec<i64>) -> Vec<String> {
-        let mut res = vec![];
-        for idx in indices_array {
-            res.push(self.valid_skills[idx as usize].clone());
+
+    /// Sort the combinations by success rate, then by the fewest average rounds
+    fn sort_rankings(&mut self) {
+        rank_top_rankings(
+            &mut self.rankings,
+            self.study.get_ranking_limit(),
+            compare_skill_rankings,
+        );
+    }
+
+    fn save_rankings_to_csv(&mut self) -> Result<(), std::io::Error> {
+        self.sort_rankings();
+
+        let Some(path) = self.study.create_output_path("skill_rankings.csv")? else {
+            return Ok(());
+        };
+
+        let mut wtr = csv::Writer::from_path(path)?;
+        for record in self.rankings.iter() {
+            wtr.serialize(record)?;
         }
-        return res;
+        wtr.flush()?;
+        return Ok(());
     }
-    pub fn get_full_translated_skillset_at_current_combination_index(&self) -> Vec<String> {
-        let mut res = self.preset_skills.clone();
-        let mut translated_skillset =
-            self.translate_skillset_from_indices(self.get_skillset_at_current_combination_index());
-        res.append(&mut translated_skillset);
-        return res;
+
+    fn print_rankings(&self, qty: usize) {
+        for (i, record) in self.rankings.iter().take(qty).enumerate() {
+            self.study.print(&f!(
+                "{}. {}: success rate {:.2}, average rounds {:.2}\n",
+                i + 1,
+                record.skills,
+                record.success_rate,
+                record.average_rounds
+            ));
+        }
     }
 }
+
+/// Orders skill combinations by success rate, then the fewest rounds
+fn compare_skill_rankings(a: &SkillRankingCSVRecord, b: &SkillRankingCSVRecord) -> Ordering {
+    return b
+        .success_rate
+        .total_cmp(&a.success_rate)
+        .then(a.average_rounds.total_cmp(&b.average_rounds));
+}
+
+/// The subject hero's performance with a single combination of skills
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct SkillRankingCSVRecord {
+    skills: String,
+    build_signature: String,
+    #[serde(serialize_with = "serialize_rounded")]
+    success_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    average_rounds: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    survival_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    avg_dmg: f64,
+}
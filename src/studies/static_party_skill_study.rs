@@ -0,0 +1,323 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dungeons::TrialDungeon,
+    equipment::BoosterType,
+    heroes::{create_team, SimHero, Team},
+    progress::ProgressReporter,
+    studies::*,
+    study_queue::load_checkpoint,
+};
+
+/// An extension of Study for generating and ranking Trials for each combination of skills for a single hero with any number of static party members
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StaticPartySkillStudy {
+    study: Study,
+    static_heroes: Vec<SimHero>, // The fixed party members that fight alongside the subject hero in every trial
+    booster: Option<BoosterType>,
+    subject_hero_builder: crate::hero_builder::Hero, // The hero builder representation of the subject hero, to be converted to a simhero for variation
+    skill_variations: SkillVariations, // The combinations of skills to vary the subject hero upon
+    skill_pairs: SkillPairMatrix,      // The average success rate of each pair of varied skills
+    _automatic_rank_difficulty_optimization: bool, // Whether to optimize ranking by testing skills above a certain rank on additional dungeons
+}
+
+/// The subject hero is always placed after the static heroes in the team
+/// Every skill combination is trialed on the first dungeon, which with a runoff scoring threshold below 100.0 starts a ladder up the rest of the dungeons
+pub fn create_static_party_skill_study(
+    identifier: String,
+    description: String,
+    simulation_qty: i32,
+    runoff_scoring_threshold: f64,
+    static_heroes: Vec<SimHero>,
+    booster: Option<BoosterType>,
+    valid_skills: Vec<String>,
+    preset_skills: Vec<String>,
+    subject_hero_builder: crate::hero_builder::Hero,
+    dungeons: Vec<TrialDungeon>,
+    automatic_rank_difficulty_optimization: bool,
+    result_format: ResultFormat,
+    save_raw_results: bool,
+    save_msgpack: bool,
+    dataset_path: Option<String>,
+    hero_builder_information: HeroBuilderInformation,
+) -> Result<StaticPartySkillStudy, &'static str> {
+    if dungeons.is_empty() {
+        return Err("static party skill study needs at least one dungeon");
+    }
+    if runoff_scoring_threshold <= 0.0 || runoff_scoring_threshold > 100.0 {
+        return Err("runoff scoring threshold must be above 0 and at most 100");
+    }
+    if static_heroes.len() > 4 {
+        return Err("cannot form party with > 4 static heroes plus the subject hero");
+    }
+    let skill_slot_qty = subject_hero_builder.count_skill_slots_unlocked();
+    if preset_skills.len() > skill_slot_qty {
+        return Err(
+            "more preset skills than the subject hero has skill slots unlocked at its rank",
+        );
+    }
+    let skill_variations = create_skill_variations(
+        valid_skills,
+        preset_skills,
+        skill_slot_qty,
+        &hero_builder_information.hero_skill_map,
+    );
+
+    let mut study = create_study(
+        identifier,
+        description,
+        simulation_qty,
+        runoff_scoring_threshold,
+        result_format,
+        save_raw_results,
+        save_msgpack,
+        dataset_path,
+        hero_builder_information,
+    );
+    study.set_dungeon_ladder(dungeons);
+    return Ok(StaticPartySkillStudy {
+        study,
+        static_heroes,
+        booster,
+        subject_hero_builder,
+        skill_pairs: create_skill_pair_matrix(skill_variations.get_valid_skills()),
+        skill_variations,
+        _automatic_rank_difficulty_optimization: automatic_rank_difficulty_optimization,
+    });
+}
+
+impl Runnable for StaticPartySkillStudy {
+    /// Handle running trials for the study
+    fn run_with_progress(&mut self, reporter: &mut dyn ProgressReporter) {
+        let Some(mut progress) = run_trial_batches(self, reporter) else {
+            return;
+        };
+
+        self.study.flush_trial_results();
+        self.study
+            .save_skill_pair_matrix(&self.skill_pairs)
+            .unwrap();
+        self.study
+            .run_runoff_rounds(|hero_builder_information, skill_variation| {
+                create_skill_variation_team(
+                    &self.subject_hero_builder,
+                    &self.static_heroes,
+                    self.booster,
+                    hero_builder_information,
+                    skill_variation,
+                )
+            });
+
+        // Outside While, this is assumed but check anyways because why not...
+        if self.count_skill_variations_remaining() == 0 {
+            // TODO: Any other tasks that must be done once finished
+            self.study.remove_resume_checkpoint();
+            self.study.status = StudyStatus::Finished;
+            progress.finish();
+        } else {
+            panic!("This should not occur, while running study managed to escape while loop without study being finished status...")
+        }
+    }
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+
+    fn attach_to_queue(&mut self, queue_id: usize) -> Result<(), std::io::Error> {
+        if let Some(checkpoint) = load_checkpoint(&get_checkpoint_path(queue_id))? {
+            *self = checkpoint;
+        }
+        self.study.queue_id = Some(queue_id);
+        return Ok(());
+    }
+
+    fn set_thread_qty(&mut self, thread_qty: usize) -> Result<(), &'static str> {
+        return self.study.set_thread_qty(thread_qty);
+    }
+
+    fn set_ranking_limit(&mut self, ranking_limit: usize) -> Result<(), &'static str> {
+        return self.study.set_ranking_limit(ranking_limit);
+    }
+}
+
+impl BatchedStudy for StaticPartySkillStudy {
+    type Variation = Vec<String>;
+    const RESUMABLE_KIND: ResumableStudyKind = ResumableStudyKind::StaticPartySkill;
+
+    fn count_progress(&self) -> (usize, usize) {
+        return (
+            self.skill_variations.count_total() as usize,
+            self.skill_variations.count_completed() as usize,
+        );
+    }
+
+    fn count_variations_remaining(&self) -> usize {
+        return self.count_skill_variations_remaining() as usize;
+    }
+
+    /// Vary the subject hero's skills and add them to the static party
+    fn get_next_variations(&self, batch_size: usize) -> Vec<(Vec<String>, String, Team)> {
+        return self
+            .skill_variations
+            .get_next_full_translated_skillsets(batch_size)
+            .into_iter()
+            .map(|skill_variation| {
+                let team = create_skill_variation_team(
+                    &self.subject_hero_builder,
+                    &self.static_heroes,
+                    self.booster,
+                    &self.study.hero_builder_information,
+                    &skill_variation,
+                );
+                let description = format!("{:?}", skill_variation);
+                (skill_variation, description, team)
+            })
+            .collect();
+    }
+
+    fn get_batch_dungeon(&self) -> &TrialDungeon {
+        return &self.study.dungeon_ladder[0];
+    }
+
+    fn record_trial(
+        &mut self,
+        skill_variation: &Vec<String>,
+        _trial: &Trial,
+        trial_result: &TrialResult,
+    ) {
+        self.study
+            .add_runoff_entrant(skill_variation.clone(), trial_result);
+        self.skill_pairs
+            .add_trial(skill_variation, trial_result.get_success_rate());
+        self.skill_variations.increment_combination_index();
+    }
+}
+
+impl StaticPartySkillStudy {
+    pub fn count_skill_variations_remaining(&self) -> i64 {
+        return self.skill_variations.count_remaining();
+    }
+}
+
+/// The static party with the subject hero given the skills of a variation
+fn create_skill_variation_team(
+    subject_hero_builder: &crate::hero_builder::Hero,
+    static_heroes: &[SimHero],
+    booster: Option<BoosterType>,
+    hero_builder_information: &HeroBuilderInformation,
+    skill_variation: &[String],
+) -> Team {
+    let mut new_hero = subject_hero_builder.clone();
+    new_hero.set_hero_skills(skill_variation.to_vec());
+    let mut party = static_heroes.to_vec();
+    party.push(hero_builder_information.build_sim_hero(new_hero).unwrap());
+    return create_team(party, booster).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rng::RngConfig,
+        study_queue::save_checkpoint,
+        test_data::{
+            create_prepared_test_hero, create_test_game_data, create_test_party,
+            create_test_trial_dungeon,
+        },
+        trials::ResultFormat,
+    };
+
+    fn create_seeded_study() -> StaticPartySkillStudy {
+        let hbi = create_test_game_data();
+        let static_hero = create_test_party(&["Static_Hero"]).remove(0);
+        let subject_hero_builder =
+            create_prepared_test_hero("Subject_Hero", 1, ["Empty", "Empty", "Empty", "Empty"]);
+        let mut valid_skills: Vec<String> = hbi
+            .hero_skill_tier_1_name_map
+            .iter()
+            .filter(|(k, _)| k.ends_with(" T4"))
+            .map(|(_, v)| v.to_string())
+            .collect();
+        valid_skills.sort();
+
+        let mut study = create_static_party_skill_study(
+            String::from("Checkpoint_Test"),
+            String::from("Checkpoint round trip"),
+            20,
+            100.0,
+            vec![static_hero],
+            None,
+            valid_skills,
+            vec![],
+            subject_hero_builder,
+            vec![create_test_trial_dungeon(1)],
+            false,
+            ResultFormat::Wide,
+            false,
+            false,
+            None,
+            hbi,
+        )
+        .unwrap();
+        study.study.set_rng(RngConfig {
+            seed: Some(11),
+            ..Default::default()
+        });
+        study.study.set_output(StudyOutput {
+            files: false,
+            stdout: false,
+        });
+        return study;
+    }
+
+    /// The study's trial rankings as they are written to trial_rankings.csv
+    fn fmt_trial_rankings(study: &Study) -> String {
+        let mut wtr = csv::Writer::from_writer(vec![]);
+        for record in study.trial_rankings.iter() {
+            wtr.serialize(record).unwrap();
+        }
+        return String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn study_resumed_from_a_checkpoint_finishes_like_an_uninterrupted_run() {
+        let mut uninterrupted = create_seeded_study();
+        uninterrupted.run_with_progress(&mut |_: &crate::progress::ProgressEvent| {});
+
+        // Trial the first batch, then save and resume it as run_trial_batches does after each written batch
+        let mut interrupted = create_seeded_study();
+        let (variations, teams): (Vec<Vec<String>>, Vec<(String, Team)>) = interrupted
+            .get_next_variations(2)
+            .into_iter()
+            .map(|(variation, description, team)| (variation, (description, team)))
+            .unzip();
+        let trials = interrupted
+            .study
+            .run_trials(teams, interrupted.get_batch_dungeon());
+        for (variation, trial) in variations.iter().zip(trials.iter()) {
+            let trial_result = interrupted.study.save_trial_result(trial);
+            interrupted.record_trial(variation, trial, &trial_result);
+        }
+        let path =
+            std::env::temp_dir().join(f!("st_sim_test_checkpoint_{}.msgpack", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_checkpoint(path, &(StaticPartySkillStudy::RESUMABLE_KIND, &interrupted)).unwrap();
+        let mut resumed = resume_study(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        resumed.get_study_mut().set_output(StudyOutput {
+            files: false,
+            stdout: false,
+        });
+        resumed.run_with_progress(&mut |_: &crate::progress::ProgressEvent| {});
+
+        assert!(uninterrupted.study.trial_qty > 2);
+        assert_eq!(resumed.get_study().trial_qty, uninterrupted.study.trial_qty);
+        assert_eq!(
+            fmt_trial_rankings(resumed.get_study()),
+            fmt_trial_rankings(&uninterrupted.study)
+        );
+    }
+}
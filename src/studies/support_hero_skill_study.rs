@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dungeons::TrialDungeon,
+    equipment::BoosterType,
+    formatting::serialize_rounded,
+    heroes::{create_team, SimHero, Team},
+    progress::ProgressReporter,
+    studies::*,
+    study_queue::load_checkpoint,
+};
+
+/// An extension of Study for ranking each combination of skills for a support hero by the performance of the carry hero they are paired with
+/// The support hero's own output is ignored, only its impact on the carry (threat soaking, buffs, healing) is scored
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SupportHeroSkillStudy {
+    study: Study,
+    carry_hero: SimHero, // The fixed hero whose survival and damage are used to score each support variation
+    booster: Option<BoosterType>,
+    support_hero_builder: crate::hero_builder::Hero, // The hero builder representation of the support hero, to be converted to a simhero for variation
+    skill_variations: SkillVariations, // The combinations of skills to vary the support hero upon
+    skill_pairs: SkillPairMatrix, // The average team success rate of each pair of varied skills
+    dungeons: Vec<TrialDungeon>, // The dungeons to be tested in the study. Only the first is currently used
+    rankings: Vec<SupportRankingCSVRecord>, // The carry's performance alongside each completed support variation
+}
+
+/// The carry hero is always placed first in the team, followed by the support hero
+pub fn create_support_hero_skill_study(
+    identifier: String,
+    description: String,
+    simulation_qty: i32,
+    carry_hero: SimHero,
+    booster: Option<BoosterType>,
+    valid_skills: Vec<String>,
+    preset_skills: Vec<String>,
+    support_hero_builder: crate::hero_builder::Hero,
+    dungeons: Vec<TrialDungeon>,
+    result_format: ResultFormat,
+    save_raw_results: bool,
+    save_msgpack: bool,
+    dataset_path: Option<String>,
+    hero_builder_information: HeroBuilderInformation,
+) -> Result<SupportHeroSkillStudy, &'static str> {
+    if dungeons.is_empty() {
+        return Err("support hero skill study requires at least one dungeon");
+    }
+    let skill_slot_qty = support_hero_builder.count_skill_slots_unlocked();
+    if preset_skills.len() > skill_slot_qty {
+        return Err(
+            "more preset skills than the support hero has skill slots unlocked at its rank",
+        );
+    }
+    let skill_variations = create_skill_variations(
+        valid_skills,
+        preset_skills,
+        skill_slot_qty,
+        &hero_builder_information.hero_skill_map,
+    );
+
+    return Ok(SupportHeroSkillStudy {
+        study: create_study(
+            identifier,
+            description,
+            simulation_qty,
+            100.0,
+            result_format,
+            save_raw_results,
+            save_msgpack,
+            dataset_path,
+            hero_builder_information,
+        ),
+        carry_hero,
+        booster,
+        support_hero_builder,
+        skill_pairs: create_skill_pair_matrix(skill_variations.get_valid_skills()),
+        skill_variations,
+        dungeons,
+        rankings: vec![],
+    });
+}
+
+impl Runnable for SupportHeroSkillStudy {
+    /// Handle running trials for the study, then save the support rankings
+    fn run_with_progress(&mut self, reporter: &mut dyn ProgressReporter) {
+        let Some(mut progress) = run_trial_batches(self, reporter) else {
+            return;
+        };
+
+        self.study.flush_trial_results();
+        self.save_rankings_to_csv().unwrap();
+        self.study
+            .save_skill_pair_matrix(&self.skill_pairs)
+            .unwrap();
+        self.study.remove_resume_checkpoint();
+        self.study.status = StudyStatus::Finished;
+        progress.finish();
+    }
+
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+
+    fn attach_to_queue(&mut self, queue_id: usize) -> Result<(), std::io::Error> {
+        if let Some(checkpoint) = load_checkpoint(&get_checkpoint_path(queue_id))? {
+            *self = checkpoint;
+        }
+        self.study.queue_id = Some(queue_id);
+        return Ok(());
+    }
+
+    fn set_thread_qty(&mut self, thread_qty: usize) -> Result<(), &'static str> {
+        return self.study.set_thread_qty(thread_qty);
+    }
+
+    fn set_ranking_limit(&mut self, ranking_limit: usize) -> Result<(), &'static str> {
+        return self.study.set_ranking_limit(ranking_limit);
+    }
+}
+
+impl BatchedStudy for SupportHeroSkillStudy {
+    type Variation = Vec<String>;
+    const RESUMABLE_KIND: ResumableStudyKind = ResumableStudyKind::SupportHeroSkill;
+
+    fn count_progress(&self) -> (usize, usize) {
+        return (
+            self.skill_variations.count_total() as usize,
+            self.skill_variations.count_completed() as usize,
+        );
+    }
+
+    fn count_variations_remaining(&self) -> usize {
+        return self.skill_variations.count_remaining() as usize;
+    }
+
+    /// Vary the support hero's skills and pair them with the carry
+    fn get_next_variations(&self, batch_size: usize) -> Vec<(Vec<String>, String, Team)> {
+        return self
+            .skill_variations
+            .get_next_full_translated_skillsets(batch_size)
+            .into_iter()
+            .map(|skill_variation| {
+                let mut new_hero = self.support_hero_builder.clone();
+                new_hero.set_hero_skills(skill_variation.clone());
+                let party = vec![
+                    self.carry_hero.clone(),
+                    self.study
+                        .hero_builder_information
+                        .build_sim_hero(new_hero)
+                        .unwrap(),
+                ];
+                let description = format!("{:?}", skill_variation);
+                (
+                    skill_variation,
+                    description,
+                    create_team(party, self.booster).unwrap(),
+                )
+            })
+            .collect();
+    }
+
+    fn get_batch_dungeon(&self) -> &TrialDungeon {
+        return &self.dungeons[0];
+    }
+
+    fn record_trial(
+        &mut self,
+        skill_variation: &Vec<String>,
+        _trial: &Trial,
+        trial_result: &TrialResult,
+    ) {
+        self.skill_pairs
+            .add_trial(skill_variation, trial_result.get_success_rate());
+
+        // Score the support variation by the carry's performance (carry is always index 0)
+        self.rankings.push(SupportRankingCSVRecord {
+            support_skills: trial_result.get_trial_description(),
+            build_signature: trial_result.get_build_signature(),
+            carry_survival_rate: trial_result.get_hero_survival_rate(0),
+            carry_avg_dmg: trial_result.get_hero_avg_dmg(0),
+            team_success_rate: trial_result.get_success_rate(),
+        });
+        retain_top_rankings(
+            &mut self.rankings,
+            self.study.get_ranking_limit(),
+            compare_support_rankings,
+        );
+        self.skill_variations.increment_combination_index();
+    }
+}
+
+impl SupportHeroSkillStudy {
+    pub fn count_skill_variations_remaining(&self) -> i64 {
+        return self.skill_variations.count_remaining();
+    }
+    /// Sort support variations by carry survival rate, then carry damage, and write them to the study's csvs folder
+    fn save_rankings_to_csv(&mut self) -> Result<(), std::io::Error> {
+        rank_top_rankings(
+            &mut self.rankings,
+            self.study.get_ranking_limit(),
+            compare_support_rankings,
+        );
+
+        let Some(path) = self.study.create_output_path("support_rankings.csv")? else {
+            return Ok(());
+        };
+
+        let mut wtr = csv::Writer::from_path(path)?;
+        for record in self.rankings.iter() {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+}
+
+/// Orders support variations by carry survival rate, then carry damage
+fn compare_support_rankings(a: &SupportRankingCSVRecord, b: &SupportRankingCSVRecord) -> Ordering {
+    return b
+        .carry_survival_rate
+        .total_cmp(&a.carry_survival_rate)
+        .then(b.carry_avg_dmg.total_cmp(&a.carry_avg_dmg));
+}
+
+/// The carry's performance alongside a single support variation
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct SupportRankingCSVRecord {
+    support_skills: String,
+    build_signature: String,
+    #[serde(serialize_with = "serialize_rounded")]
+    carry_survival_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    carry_avg_dmg: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    team_success_rate: f64,
+}
@@ -0,0 +1,174 @@
+use strum::IntoEnumIterator;
+
+use crate::{
+    dungeons::TrialDungeon,
+    equipment::BoosterType,
+    formatting::fmt_f64,
+    heroes::{create_team, SimHero, Team},
+    progress::{create_study_progress, ProgressReporter},
+    simulations::TargetingModel,
+    studies::*,
+};
+
+/// An extension of Study for evaluating a fixed party against each dungeon under each mob targeting model
+/// The spread of success rates between models shows how much a result depends on the targeting assumption
+pub struct TargetingSensitivityStudy {
+    study: Study,
+    team: Team,
+    dungeons: Vec<TrialDungeon>,
+    models: Vec<TargetingModel>, // The targeting models each dungeon is trialed with, in the column order of the sensitivity csv
+    sensitivity: Vec<Vec<f64>>, // The simulated success rate under each model for each completed dungeon, in dungeon order
+}
+
+/// An empty models list trials every targeting model
+pub fn create_targeting_sensitivity_study(
+    identifier: String,
+    description: String,
+    simulation_qty: i32,
+    party: Vec<SimHero>,
+    booster: Option<BoosterType>,
+    dungeons: Vec<TrialDungeon>,
+    mut models: Vec<TargetingModel>,
+    result_format: ResultFormat,
+    save_raw_results: bool,
+    save_msgpack: bool,
+    dataset_path: Option<String>,
+    hero_builder_information: HeroBuilderInformation,
+) -> Result<TargetingSensitivityStudy, &'static str> {
+    if models.is_empty() {
+        models = TargetingModel::iter().collect();
+    }
+    if models
+        .iter()
+        .enumerate()
+        .any(|(i, model)| models[..i].contains(model))
+    {
+        return Err("targeting sensitivity models must not repeat");
+    }
+
+    return Ok(TargetingSensitivityStudy {
+        study: create_study(
+            identifier,
+            description,
+            simulation_qty,
+            100.0,
+            result_format,
+            save_raw_results,
+            save_msgpack,
+            dataset_path,
+            hero_builder_information,
+        ),
+        team: create_team(party, booster)?,
+        dungeons,
+        models,
+        sensitivity: vec![],
+    });
+}
+
+impl Runnable for TargetingSensitivityStudy {
+    /// Run a trial for each targeting model on each dungeon, then save the sensitivity table
+    fn run_with_progress(&mut self, reporter: &mut dyn ProgressReporter) {
+        self.study.status = StudyStatus::Running;
+
+        let mut progress =
+            create_study_progress(reporter, Some(self.count_trials_total() as u64), 0);
+
+        for trial_dungeon in self.dungeons.iter() {
+            let mut row: Vec<f64> = vec![];
+            for model in self.models.iter() {
+                let trial = self.study.run_trial(
+                    f!("{} Targeting", model),
+                    self.team.clone(),
+                    &trial_dungeon.with_targeting(*model),
+                );
+                self.study.save_trial_result(&trial);
+                row.push(trial.create_trial_result().get_success_rate());
+                progress.inc(1);
+            }
+            self.sensitivity.push(row);
+        }
+
+        self.study.flush_trial_results();
+        self.save_sensitivity_to_csv().unwrap();
+        for (zone, difficulty, spread) in self.get_spreads() {
+            self.study.print(&f!(
+                "Success rate in {} at difficulty {} varies by {} between targeting models\n",
+                zone,
+                difficulty,
+                fmt_f64(spread)
+            ));
+        }
+
+        self.study.status = StudyStatus::Finished;
+        progress.finish();
+    }
+
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+}
+
+impl TargetingSensitivityStudy {
+    pub fn count_trials_total(&self) -> usize {
+        return self.dungeons.len() * self.models.len();
+    }
+
+    /// Returns the zone, difficulty, and difference between the highest and lowest success rate of each completed dungeon, most sensitive first
+    pub fn get_spreads(&self) -> Vec<(String, usize, f64)> {
+        let mut res: Vec<(String, usize, f64)> = self
+            .dungeons
+            .iter()
+            .zip(self.sensitivity.iter())
+            .map(|(trial_dungeon, row)| {
+                return (
+                    trial_dungeon.dungeon._get_zone(),
+                    trial_dungeon.difficulty,
+                    get_spread(row),
+                );
+            })
+            .collect();
+        res.sort_by(|a, b| b.2.total_cmp(&a.2));
+        return res;
+    }
+
+    /// Write one row per dungeon, with a column per targeting model and the spread between them
+    fn save_sensitivity_to_csv(&mut self) -> Result<(), std::io::Error> {
+        let Some(path) = self.study.create_output_path("targeting_sensitivity.csv")? else {
+            return Ok(());
+        };
+
+        let mut wtr = csv::Writer::from_path(path)?;
+        let mut header = vec![
+            String::from("dungeon_identifier"),
+            String::from("difficulty"),
+            String::from("build_signature"),
+        ];
+        header.extend(self.models.iter().map(|m| f!("{}_success_rate", m)));
+        header.push(String::from("success_rate_spread"));
+        wtr.write_record(&header)?;
+
+        for (trial_dungeon, row) in self.dungeons.iter().zip(self.sensitivity.iter()) {
+            let mut record = vec![
+                trial_dungeon.dungeon._get_zone(),
+                trial_dungeon.difficulty.to_string(),
+                self.team.get_build_signature(),
+            ];
+            record.extend(row.iter().map(|success_rate| fmt_f64(*success_rate)));
+            record.push(fmt_f64(get_spread(row)));
+            wtr.write_record(&record)?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+}
+
+/// The difference between the highest and lowest success rate of a dungeon's row
+fn get_spread(row: &[f64]) -> f64 {
+    let max = row.iter().cloned().fold(f64::MIN, f64::max);
+    let min = row.iter().cloned().fold(f64::MAX, f64::min);
+    return max - min;
+}
@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    combinations::{count_combinations, iter_combination},
+    dungeons::{create_trial_dungeon, Dungeon, TrialDungeon},
+    formatting::serialize_rounded,
+    heroes::{create_team, SimHero, Team},
+    progress::ProgressReporter,
+    quests::Quest,
+    studies::*,
+    study_queue::load_checkpoint,
+};
+
+/// An extension of Study for ranking every team of a given size that can be formed from a roster of built heroes against a quest
+/// Teams are ranked by success rate, then by the fewest average rounds to clear
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TeamCompositionStudy {
+    study: Study,
+    roster: Vec<SimHero>, // Teams keep the heroes in roster order
+    party_size: usize,
+    unique_classes: Vec<String>, // Classes that at most one hero of each team may have
+    quest: Quest,
+    trial_dungeon: TrialDungeon,
+    team_qty: usize, // The teams that satisfy the class constraints, counted when the study is created
+    combination_index: i64, // The index of the next combination of heroes to consider, counting every combination including those skipped for their classes
+    rankings: Vec<TeamRankingCSVRecord>, // The performance of each completed team
+}
+
+/// The quest is looked up by identifier and matched to its dungeon by zone, the team fighting at the quest's difficulty
+pub fn create_team_composition_study(
+    identifier: String,
+    description: String,
+    simulation_qty: i32,
+    roster: Vec<SimHero>,
+    party_size: usize,
+    unique_classes: Vec<String>,
+    quest: Quest,
+    dungeons: &HashMap<String, Dungeon>,
+    result_format: ResultFormat,
+    save_raw_results: bool,
+    save_msgpack: bool,
+    dataset_path: Option<String>,
+    hero_builder_information: HeroBuilderInformation,
+) -> Result<TeamCompositionStudy, &'static str> {
+    let dungeon = dungeons
+        .get(&quest.get_dungeon())
+        .ok_or("quest references a dungeon that was not loaded")?;
+    if party_size < 1 || party_size > dungeon.get_max_num_heroes() as usize {
+        return Err("party size must be between 1 and the max heroes of the quest's dungeon");
+    }
+    if roster.len() < party_size {
+        return Err("roster has fewer heroes than the party size");
+    }
+    for (i, hero) in roster.iter().enumerate() {
+        if roster[..i]
+            .iter()
+            .any(|other| other.get_identifier() == hero.get_identifier())
+        {
+            return Err("roster has a hero more than once");
+        }
+    }
+    let trial_dungeon =
+        create_trial_dungeon(dungeon.clone(), quest.get_difficulty(), None, None, None);
+
+    let mut study = TeamCompositionStudy {
+        study: create_study(
+            identifier,
+            description,
+            simulation_qty,
+            100.0,
+            result_format,
+            save_raw_results,
+            save_msgpack,
+            dataset_path,
+            hero_builder_information,
+        ),
+        roster,
+        party_size,
+        unique_classes,
+        quest,
+        trial_dungeon,
+        team_qty: 0,
+        combination_index: 0,
+        rankings: vec![],
+    };
+    study.team_qty = (0..study.count_combinations_total())
+        .filter(|&combination_index| study.get_team_heroes(combination_index).is_some())
+        .count();
+    if study.team_qty == 0 {
+        return Err("no team of the party size satisfies the unique classes");
+    }
+    return Ok(study);
+}
+
+impl Runnable for TeamCompositionStudy {
+    /// Handle running trials for the study, then save and print the team rankings
+    fn run_with_progress(&mut self, reporter: &mut dyn ProgressReporter) {
+        let Some(mut progress) = run_trial_batches(self, reporter) else {
+            return;
+        };
+
+        self.study.flush_trial_results();
+        self.save_rankings_to_csv().unwrap();
+        self.study.remove_resume_checkpoint();
+        self.study.status = StudyStatus::Finished;
+        progress.finish();
+        self.print_rankings(5);
+    }
+
+    fn get_study(&self) -> &Study {
+        return &self.study;
+    }
+
+    fn get_study_mut(&mut self) -> &mut Study {
+        return &mut self.study;
+    }
+
+    fn attach_to_queue(&mut self, queue_id: usize) -> Result<(), std::io::Error> {
+        if let Some(checkpoint) = load_checkpoint(&get_checkpoint_path(queue_id))? {
+            *self = checkpoint;
+        }
+        self.study.queue_id = Some(queue_id);
+        return Ok(());
+    }
+
+    fn set_thread_qty(&mut self, thread_qty: usize) -> Result<(), &'static str> {
+        return self.study.set_thread_qty(thread_qty);
+    }
+
+    fn set_ranking_limit(&mut self, ranking_limit: usize) -> Result<(), &'static str> {
+        return self.study.set_ranking_limit(ranking_limit);
+    }
+}
+
+impl BatchedStudy for TeamCompositionStudy {
+    type Variation = i64;
+    const RESUMABLE_KIND: ResumableStudyKind = ResumableStudyKind::TeamComposition;
+
+    fn count_progress(&self) -> (usize, usize) {
+        return (self.team_qty, self.rankings.len());
+    }
+
+    fn count_variations_remaining(&self) -> usize {
+        return (self.count_combinations_total() - self.combination_index) as usize;
+    }
+
+    /// Find the next teams to test, skipping combinations with more than one hero of a unique class
+    fn get_next_variations(&self, batch_size: usize) -> Vec<(i64, String, Team)> {
+        return (self.combination_index..self.count_combinations_total())
+            .filter_map(|combination_index| {
+                return self
+                    .get_team_heroes(combination_index)
+                    .map(|heroes| (combination_index, heroes));
+            })
+            .take(batch_size)
+            .map(|(combination_index, heroes)| {
+                let description = heroes
+                    .iter()
+                    .map(|hero| hero.get_identifier())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                (
+                    combination_index,
+                    description,
+                    create_team(heroes, None).unwrap(),
+                )
+            })
+            .collect();
+    }
+
+    fn get_batch_dungeon(&self) -> &TrialDungeon {
+        return &self.trial_dungeon;
+    }
+
+    fn record_trial(
+        &mut self,
+        team_combination_index: &i64,
+        trial: &Trial,
+        trial_result: &TrialResult,
+    ) {
+        let results = trial._get_results_unranked();
+        let injury_rate = results
+            .iter()
+            .filter(|result| result.is_any_hero_defeated())
+            .count() as f64
+            / (results.len() as f64).max(1.0);
+        let (xp_per_hour, gold_per_hour, loot_per_hour) = self.quest.calculate_rewards_per_hour(
+            trial_result.get_success_rate(),
+            injury_rate,
+            trial_result.get_avg_polonia_loot(),
+            trial.get_team().get_rest_time_percent(),
+            trial.get_team().get_xp_percent(),
+        );
+        self.rankings.push(TeamRankingCSVRecord {
+            rank: 0,
+            team: trial_result.get_trial_description(),
+            success_rate: trial_result.get_success_rate(),
+            average_rounds: trial_result.get_average_rounds(),
+            injury_rate,
+            xp_per_hour,
+            gold_per_hour,
+            loot_per_hour,
+            build_signature: trial_result.get_build_signature(),
+        });
+        retain_top_rankings(
+            &mut self.rankings,
+            self.study.get_ranking_limit(),
+            compare_team_rankings,
+        );
+        self.combination_index = team_combination_index + 1;
+    }
+
+    /// Combinations with more than one hero of a unique class are skipped
+    fn skip_untrialable_variations(&mut self) {
+        while self.combination_index < self.count_combinations_total()
+            && self.get_team_heroes(self.combination_index).is_none()
+        {
+            self.combination_index += 1;
+        }
+    }
+}
+
+impl TeamCompositionStudy {
+    pub fn count_teams_remaining(&self) -> usize {
+        return self.team_qty - self.rankings.len();
+    }
+
+    fn count_combinations_total(&self) -> i64 {
+        return count_combinations(self.roster.len() as i64, self.party_size as i64);
+    }
+
+    /// Returns the heroes of the combination at the given index, in roster order, or None if more than one has a unique class
+    fn get_team_heroes(&self, combination_index: i64) -> Option<Vec<SimHero>> {
+        let heroes: Vec<SimHero> = iter_combination(
+            combination_index,
+            self.roster.len() as i64,
+            self.party_size as i64,
+        )
+        .into_iter()
+        .rev()
+        .map(|roster_index| self.roster[roster_index as usize].clone())
+        .collect();
+        for class in self.unique_classes.iter() {
+            if heroes
+                .iter()
+                .filter(|hero| hero.get_class() == *class)
+                .count()
+                > 1
+            {
+                return None;
+            }
+        }
+        return Some(heroes);
+    }
+
+    /// Sort the teams by success rate, then by the fewest average rounds, and number them
+    fn sort_rankings(&mut self) {
+        rank_top_rankings(
+            &mut self.rankings,
+            self.study.get_ranking_limit(),
+            compare_team_rankings,
+        );
+        for (i, record) in self.rankings.iter_mut().enumerate() {
+            record.rank = i + 1;
+        }
+    }
+
+    fn save_rankings_to_csv(&mut self) -> Result<(), std::io::Error> {
+        self.sort_rankings();
+
+        let Some(path) = self.study.create_output_path("team_rankings.csv")? else {
+            return Ok(());
+        };
+
+        let mut wtr = csv::Writer::from_path(path)?;
+        for record in self.rankings.iter() {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+
+    fn print_rankings(&self, qty: usize) {
+        for record in self.rankings.iter().take(qty) {
+            self.study.print(&f!(
+                "{}. {}: success rate {:.2}, average rounds {:.2}, {:.0} xp and {:.0} gold per hour\n",
+                record.rank,
+                record.team,
+                record.success_rate,
+                record.average_rounds,
+                record.xp_per_hour,
+                record.gold_per_hour
+            ));
+        }
+    }
+}
+
+/// Orders teams by success rate, then the fewest rounds
+fn compare_team_rankings(a: &TeamRankingCSVRecord, b: &TeamRankingCSVRecord) -> Ordering {
+    return b
+        .success_rate
+        .total_cmp(&a.success_rate)
+        .then(a.average_rounds.total_cmp(&b.average_rounds));
+}
+
+/// A single ranked team, with its expected rewards per hour from running the quest repeatedly
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct TeamRankingCSVRecord {
+    rank: usize,
+    team: String, // The identifiers of the team's heroes, in roster order
+    #[serde(serialize_with = "serialize_rounded")]
+    success_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    average_rounds: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    injury_rate: f64, // The rate of runs in which any hero is defeated, lengthening the party's rest
+    #[serde(serialize_with = "serialize_rounded")]
+    xp_per_hour: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    gold_per_hour: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    loot_per_hour: f64,
+    build_signature: String,
+}
@@ -0,0 +1,645 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dungeons::{create_trial_dungeon, Dungeon, TrialDungeon},
+    equipment::BoosterType,
+    hero_builder::Hero,
+    heroes::SimHero,
+    inputs::{load_heroes_from_csv, load_sim_heroes_from_csv},
+    rng::RngConfig,
+    roster::{get_roster_path, load_roster},
+    simulations::{TargetingModel, MAX_ROUND_LIMIT},
+    statistics::StatisticsConfig,
+    studies::{
+        difficulty_ladder_study::{create_difficulty_ladder_study, LadderSearch},
+        gear_optimization_study::create_gear_optimization_study,
+        promotion_comparison_study::create_promotion_comparison_study,
+        quality_sweep_study::{create_quality_sweep_study, QualitySweepMode},
+        single_hero_skill_study::create_single_hero_skill_study,
+        static_party_skill_study::create_static_party_skill_study,
+        support_hero_skill_study::create_support_hero_skill_study,
+        targeting_sensitivity_study::create_targeting_sensitivity_study,
+        HeroBuilderInformation, Runnable,
+    },
+    trials::ResultFormat,
+};
+
+/// Fully describes a study, so experiments can be defined in a yaml or toml file and run without recompiling
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StudyConfig {
+    identifier: String,
+    #[serde(default)]
+    description: String,
+    simulation_qty: i32,
+    #[serde(default = "default_runoff_scoring_threshold")]
+    runoff_scoring_threshold: f64, // The top X% of results re-tested on the next dungeon, only used by static party skill studies. 100.0 disables runoff scoring
+    #[serde(default = "default_roster_paths")]
+    rosters: Vec<String>, // Csvs of builder heroes or precomputed sim heroes, detected by their header, roster json files, or the names of rosters in input/rosters. Heroes in later rosters replace heroes of the same identifier
+    dungeons: Vec<DungeonConfig>,
+    #[serde(default = "default_result_format")]
+    result_format: ResultFormat,
+    #[serde(default)]
+    save_raw_results: bool,
+    #[serde(default)]
+    save_msgpack: bool,
+    #[serde(default)]
+    dataset_path: Option<String>,
+    #[serde(default)]
+    seed: Option<u64>, // Seeds the study so it gives identical results each run, taking precedence over --seed
+    #[serde(default)]
+    ranking_limit: Option<usize>, // Keep only this many of the best trials in the study's rankings, so huge sweeps run in constant memory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    statistics: Option<StatisticsConfig>, // How the study's trials compute their intervals, taking precedence over --bootstrap, --confidence, --target-ci-width, and --max-sims
+    sweep: SweepConfig, // What the study varies between trials
+}
+
+fn default_runoff_scoring_threshold() -> f64 {
+    return 100.0;
+}
+
+fn default_roster_paths() -> Vec<String> {
+    return vec![
+        String::from("input/heroes.csv"),
+        String::from("input/hero_builder.csv"),
+    ];
+}
+
+fn default_result_format() -> ResultFormat {
+    return ResultFormat::Wide;
+}
+
+/// A dungeon from dungeons.yaml and the settings it is trialed at
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DungeonConfig {
+    zone: String,
+    #[serde(default = "default_difficulty")]
+    difficulty: usize,
+    #[serde(default)]
+    force_minibosses: Option<bool>,
+    #[serde(default)]
+    max_rounds: Option<i16>,
+    #[serde(default)]
+    targeting: TargetingModel, // How the mob chooses the hero hit by single target attacks
+}
+
+fn default_difficulty() -> usize {
+    return 4;
+}
+
+/// The parameters of each kind of study a config can describe, selected by "kind"
+/// Heroes are named by identifier from the rosters, and an empty skill list means every T4 skill
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SweepConfig {
+    StaticPartySkill {
+        static_heroes: Vec<String>,
+        subject_hero: String, // A builder hero
+        #[serde(default)]
+        booster: Option<BoosterType>,
+        #[serde(default)]
+        valid_skills: Vec<String>,
+        #[serde(default)]
+        preset_skills: Vec<String>,
+        #[serde(default)]
+        automatic_rank_difficulty_optimization: bool,
+    },
+    SupportHeroSkill {
+        carry_hero: String,
+        support_hero: String, // A builder hero
+        #[serde(default)]
+        booster: Option<BoosterType>,
+        #[serde(default)]
+        valid_skills: Vec<String>,
+        #[serde(default)]
+        preset_skills: Vec<String>,
+    },
+    SingleHeroSkill {
+        subject_hero: String, // A builder hero
+        #[serde(default)]
+        skills: Vec<String>,
+        #[serde(default)]
+        rarities: Vec<String>,
+    },
+    GearOptimization {
+        subject_hero: String, // A builder hero
+        #[serde(default)]
+        static_heroes: Vec<String>,
+        slot_candidates: [Vec<String>; 6],
+    },
+    QualitySweep {
+        subject_hero: String, // A builder hero
+        #[serde(default)]
+        static_heroes: Vec<String>,
+        #[serde(default = "default_quality_sweep_mode")]
+        mode: QualitySweepMode,
+    },
+    TargetingSensitivity {
+        party: Vec<String>,
+        #[serde(default)]
+        booster: Option<BoosterType>,
+        #[serde(default)]
+        models: Vec<TargetingModel>, // Empty means every targeting model
+    },
+    DifficultyLadder {
+        party: Vec<String>,
+        #[serde(default)]
+        booster: Option<BoosterType>,
+        #[serde(default = "default_target_success_rate")]
+        target_success_rate: f64,
+        #[serde(default)]
+        search: LadderSearch, // The study's dungeons are the ladder, easiest first
+    },
+    PromotionComparison {
+        subject_hero: String, // A builder hero, trialed as each class of its promotion chain
+        #[serde(default)]
+        static_heroes: Vec<String>,
+        #[serde(default)]
+        booster: Option<BoosterType>,
+    },
+}
+
+fn default_target_success_rate() -> f64 {
+    return 0.9;
+}
+
+fn default_quality_sweep_mode() -> QualitySweepMode {
+    return QualitySweepMode::PerSlot;
+}
+
+/// Load a study config, parsed as toml if the file ends in .toml and as yaml otherwise
+pub fn load_study_config(path: &str) -> Result<StudyConfig, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| f!("{}: {}", path, e))?;
+    if path.ends_with(".toml") {
+        return toml::from_str(&contents).map_err(|e| f!("{}: {}", path, e));
+    }
+    return serde_yaml::from_str(&contents).map_err(|e| f!("{}: {}", path, e));
+}
+
+/// Load a study config and create the study it describes, ready to run
+pub fn create_study_from_config_file(
+    path: &str,
+    dungeons: &HashMap<String, Dungeon>,
+    hero_builder_information: HeroBuilderInformation,
+    rng: RngConfig,
+    statistics: StatisticsConfig,
+) -> Result<Box<dyn Runnable>, String> {
+    return load_study_config(path)?.create_study(
+        dungeons,
+        hero_builder_information,
+        rng,
+        statistics,
+    );
+}
+
+impl StudyConfig {
+    pub fn get_identifier(&self) -> String {
+        return self.identifier.to_string();
+    }
+
+    /// Create the study the config describes, loading its rosters and looking up its heroes and dungeons
+    /// The study draws from rng and computes intervals as statistics says, unless the config sets its own seed or statistics
+    pub fn create_study(
+        &self,
+        dungeons: &HashMap<String, Dungeon>,
+        hero_builder_information: HeroBuilderInformation,
+        rng: RngConfig,
+        statistics: StatisticsConfig,
+    ) -> Result<Box<dyn Runnable>, String> {
+        let roster = self.load_roster(&hero_builder_information)?;
+        return self.create_study_from_roster(
+            roster,
+            dungeons,
+            hero_builder_information,
+            rng,
+            statistics,
+        );
+    }
+
+    /// Create the study the config describes from builder heroes already in memory, ignoring the config's rosters
+    pub fn create_study_with_heroes(
+        &self,
+        heroes: &HashMap<String, Hero>,
+        dungeons: &HashMap<String, Dungeon>,
+        hero_builder_information: HeroBuilderInformation,
+        rng: RngConfig,
+        statistics: StatisticsConfig,
+    ) -> Result<Box<dyn Runnable>, String> {
+        let mut roster = StudyRoster {
+            builder_heroes: HashMap::new(),
+            sim_heroes: HashMap::new(),
+        };
+        for (identifier, hero) in heroes {
+            roster.add_builder_hero(
+                identifier.to_string(),
+                hero.clone(),
+                &hero_builder_information,
+            )?;
+        }
+        return self.create_study_from_roster(
+            roster,
+            dungeons,
+            hero_builder_information,
+            rng,
+            statistics,
+        );
+    }
+
+    fn create_study_from_roster(
+        &self,
+        roster: StudyRoster,
+        dungeons: &HashMap<String, Dungeon>,
+        hero_builder_information: HeroBuilderInformation,
+        rng: RngConfig,
+        statistics: StatisticsConfig,
+    ) -> Result<Box<dyn Runnable>, String> {
+        let trial_dungeons = self.get_trial_dungeons(dungeons)?;
+        let t4_skills: Vec<String> = hero_builder_information
+            .hero_skill_tier_1_name_map
+            .iter()
+            .filter(|(k, _)| k.ends_with(" T4"))
+            .map(|(_, v)| v.to_string())
+            .collect();
+        let skills_or_t4 = |skills: &Vec<String>| -> Vec<String> {
+            if skills.is_empty() {
+                return t4_skills.clone();
+            }
+            return skills.clone();
+        };
+
+        let mut study: Box<dyn Runnable> = match &self.sweep {
+            SweepConfig::StaticPartySkill {
+                static_heroes,
+                subject_hero,
+                booster,
+                valid_skills,
+                preset_skills,
+                automatic_rank_difficulty_optimization,
+            } => Box::new(create_static_party_skill_study(
+                self.get_identifier(),
+                self.description.to_string(),
+                self.simulation_qty,
+                self.runoff_scoring_threshold,
+                roster.get_sim_heroes(static_heroes)?,
+                *booster,
+                skills_or_t4(valid_skills),
+                preset_skills.clone(),
+                roster.get_builder_hero(subject_hero)?,
+                trial_dungeons,
+                *automatic_rank_difficulty_optimization,
+                self.result_format,
+                self.save_raw_results,
+                self.save_msgpack,
+                self.dataset_path.clone(),
+                hero_builder_information,
+            )?),
+            SweepConfig::SupportHeroSkill {
+                carry_hero,
+                support_hero,
+                booster,
+                valid_skills,
+                preset_skills,
+            } => Box::new(create_support_hero_skill_study(
+                self.get_identifier(),
+                self.description.to_string(),
+                self.simulation_qty,
+                roster.get_sim_hero(carry_hero)?,
+                *booster,
+                skills_or_t4(valid_skills),
+                preset_skills.clone(),
+                roster.get_builder_hero(support_hero)?,
+                trial_dungeons,
+                self.result_format,
+                self.save_raw_results,
+                self.save_msgpack,
+                self.dataset_path.clone(),
+                hero_builder_information,
+            )?),
+            SweepConfig::SingleHeroSkill {
+                subject_hero,
+                skills,
+                rarities,
+            } => Box::new(create_single_hero_skill_study(
+                self.get_identifier(),
+                self.description.to_string(),
+                self.simulation_qty,
+                roster.get_builder_hero(subject_hero)?,
+                skills_or_t4(skills),
+                rarities.clone(),
+                get_single_trial_dungeon(trial_dungeons)?,
+                self.result_format,
+                self.save_raw_results,
+                self.save_msgpack,
+                self.dataset_path.clone(),
+                hero_builder_information,
+            )?),
+            SweepConfig::GearOptimization {
+                subject_hero,
+                static_heroes,
+                slot_candidates,
+            } => Box::new(create_gear_optimization_study(
+                self.get_identifier(),
+                self.description.to_string(),
+                self.simulation_qty,
+                roster.get_sim_heroes(static_heroes)?,
+                roster.get_builder_hero(subject_hero)?,
+                slot_candidates.clone(),
+                get_single_trial_dungeon(trial_dungeons)?,
+                self.result_format,
+                self.save_raw_results,
+                self.save_msgpack,
+                self.dataset_path.clone(),
+                hero_builder_information,
+            )?),
+            SweepConfig::QualitySweep {
+                subject_hero,
+                static_heroes,
+                mode,
+            } => Box::new(create_quality_sweep_study(
+                self.get_identifier(),
+                self.description.to_string(),
+                self.simulation_qty,
+                roster.get_sim_heroes(static_heroes)?,
+                roster.get_builder_hero(subject_hero)?,
+                *mode,
+                get_single_trial_dungeon(trial_dungeons)?,
+                self.result_format,
+                self.save_raw_results,
+                self.save_msgpack,
+                self.dataset_path.clone(),
+                hero_builder_information,
+            )?),
+            SweepConfig::TargetingSensitivity {
+                party,
+                booster,
+                models,
+            } => Box::new(create_targeting_sensitivity_study(
+                self.get_identifier(),
+                self.description.to_string(),
+                self.simulation_qty,
+                roster.get_sim_heroes(party)?,
+                *booster,
+                trial_dungeons,
+                models.clone(),
+                self.result_format,
+                self.save_raw_results,
+                self.save_msgpack,
+                self.dataset_path.clone(),
+                hero_builder_information,
+            )?),
+            SweepConfig::DifficultyLadder {
+                party,
+                booster,
+                target_success_rate,
+                search,
+            } => Box::new(create_difficulty_ladder_study(
+                self.get_identifier(),
+                self.description.to_string(),
+                self.simulation_qty,
+                roster.get_sim_heroes(party)?,
+                *booster,
+                trial_dungeons,
+                *target_success_rate,
+                *search,
+                self.result_format,
+                self.save_raw_results,
+                self.save_msgpack,
+                self.dataset_path.clone(),
+                hero_builder_information,
+            )?),
+            SweepConfig::PromotionComparison {
+                subject_hero,
+                static_heroes,
+                booster,
+            } => Box::new(create_promotion_comparison_study(
+                self.get_identifier(),
+                self.description.to_string(),
+                self.simulation_qty,
+                roster.get_sim_heroes(static_heroes)?,
+                *booster,
+                roster.get_builder_hero(subject_hero)?,
+                trial_dungeons,
+                self.result_format,
+                self.save_raw_results,
+                self.save_msgpack,
+                self.dataset_path.clone(),
+                hero_builder_information,
+            )?),
+        };
+        study.get_study_mut().set_rng(RngConfig {
+            seed: self.seed.or(rng.seed),
+            ..rng
+        });
+        if let Some(ranking_limit) = self.ranking_limit {
+            study.set_ranking_limit(ranking_limit)?;
+        }
+        study
+            .get_study_mut()
+            .set_statistics(self.statistics.unwrap_or(statistics))?;
+        return Ok(study);
+    }
+
+    /// Load every roster file, reading builder heroes or sim heroes depending on the file's header
+    fn load_roster(
+        &self,
+        hero_builder_information: &HeroBuilderInformation,
+    ) -> Result<StudyRoster, String> {
+        let mut roster = StudyRoster {
+            builder_heroes: HashMap::new(),
+            sim_heroes: HashMap::new(),
+        };
+        for path in &self.rosters {
+            if !path.ends_with(".csv") {
+                // A saved roster, by path or by name
+                let roster_path = match path.ends_with(".json") {
+                    true => path.to_string(),
+                    false => get_roster_path(path),
+                };
+                let heroes = load_roster(&roster_path)?.load_heroes(
+                    &hero_builder_information.bp_map,
+                    &hero_builder_information.hero_classes,
+                )?;
+                for (identifier, hero) in heroes {
+                    roster.add_builder_hero(identifier, hero, hero_builder_information)?;
+                }
+                continue;
+            }
+            let header = std::fs::read_to_string(path)
+                .map_err(|e| f!("{}: {}", path, e))?
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            if header
+                .split(',')
+                .any(|column| column == "equipment_equipped_1")
+            {
+                for (identifier, hero) in load_heroes_from_csv(
+                    path.to_string(),
+                    hero_builder_information.bp_map.clone(),
+                    hero_builder_information.hero_classes.clone(),
+                )? {
+                    roster.add_builder_hero(identifier, hero, hero_builder_information)?;
+                }
+            } else {
+                for hero in load_sim_heroes_from_csv(path.to_string(), hero_builder_information) {
+                    roster.builder_heroes.remove(&hero.get_identifier());
+                    roster.sim_heroes.insert(hero.get_identifier(), hero);
+                }
+            }
+        }
+        return Ok(roster);
+    }
+
+    fn get_trial_dungeons(
+        &self,
+        dungeons: &HashMap<String, Dungeon>,
+    ) -> Result<Vec<TrialDungeon>, String> {
+        if self.dungeons.is_empty() {
+            return Err(String::from("a study config needs at least one dungeon"));
+        }
+        return self
+            .dungeons
+            .iter()
+            .map(|dungeon_config| {
+                if dungeon_config
+                    .max_rounds
+                    .is_some_and(|max_rounds| !(1..=MAX_ROUND_LIMIT).contains(&max_rounds))
+                {
+                    return Err(f!(
+                        "max_rounds of dungeon {} must be within range 1-{} inclusive",
+                        dungeon_config.zone,
+                        MAX_ROUND_LIMIT
+                    ));
+                }
+                let dungeon = dungeons.get(&dungeon_config.zone).ok_or(f!(
+                    "dungeon {} not found in input/dungeons.yaml",
+                    dungeon_config.zone
+                ))?;
+                return Ok(create_trial_dungeon(
+                    dungeon.clone(),
+                    dungeon_config.difficulty,
+                    dungeon_config.force_minibosses,
+                    dungeon_config.max_rounds,
+                    None,
+                )
+                .with_targeting(dungeon_config.targeting));
+            })
+            .collect();
+    }
+}
+
+/// The single hero skill, gear optimization, and quality sweep studies trial a single dungeon
+fn get_single_trial_dungeon(mut trial_dungeons: Vec<TrialDungeon>) -> Result<TrialDungeon, String> {
+    if trial_dungeons.len() != 1 {
+        return Err(String::from(
+            "single hero skill, gear optimization, and quality sweep study configs take exactly one dungeon",
+        ));
+    }
+    return Ok(trial_dungeons.remove(0));
+}
+
+/// The heroes loaded from a config's rosters, as builder heroes where available so they can be varied
+struct StudyRoster {
+    builder_heroes: HashMap<String, Hero>,
+    sim_heroes: HashMap<String, SimHero>, // Every hero, with builder heroes already converted
+}
+
+impl StudyRoster {
+    /// Add a builder hero, resolving its skill references, along with its sim hero
+    fn add_builder_hero(
+        &mut self,
+        identifier: String,
+        mut hero: Hero,
+        hero_builder_information: &HeroBuilderInformation,
+    ) -> Result<(), String> {
+        hero.resolve_skill_references(
+            &hero_builder_information.hero_skill_tier_1_name_map,
+            &hero_builder_information.hero_skill_map,
+        )
+        .map_err(|e| f!("{} for hero {}", e, identifier))?;
+        self.sim_heroes.insert(
+            identifier.to_string(),
+            hero_builder_information
+                .build_sim_hero(hero.clone())
+                .map_err(|e| f!("{} for hero {}", e, identifier))?,
+        );
+        self.builder_heroes.insert(identifier, hero);
+        return Ok(());
+    }
+
+    fn get_builder_hero(&self, identifier: &str) -> Result<Hero, String> {
+        return self.builder_heroes.get(identifier).cloned().ok_or(f!(
+            "{} not found among the builder heroes of the config's rosters",
+            identifier
+        ));
+    }
+
+    fn get_sim_hero(&self, identifier: &str) -> Result<SimHero, String> {
+        return self
+            .sim_heroes
+            .get(identifier)
+            .cloned()
+            .ok_or(f!("{} not found in the config's rosters", identifier));
+    }
+
+    fn get_sim_heroes(&self, identifiers: &[String]) -> Result<Vec<SimHero>, String> {
+        return identifiers
+            .iter()
+            .map(|identifier| self.get_sim_hero(identifier))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STUDY_CONFIG_YAML: &str = "
+identifier: Test_Study
+simulation_qty: 50
+dungeons:
+  - zone: Bleakspire Peak
+    difficulty: 7
+sweep:
+  kind: static_party_skill
+  static_heroes:
+    - Lord_Control
+  subject_hero: Daimyo-Atk_Test_Main
+  valid_skills: []
+  preset_skills: []
+";
+
+    #[test]
+    fn statistics_section_is_parsed_with_defaults_for_missing_fields() {
+        let yaml = f!(
+            "{}statistics:\n  bootstrap_resamples: 200\n  target_interval_width: 0.05\n",
+            STUDY_CONFIG_YAML
+        );
+        let config: StudyConfig = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(
+            config.statistics,
+            Some(StatisticsConfig {
+                bootstrap_resamples: 200,
+                target_interval_width: Some(0.05),
+                ..StatisticsConfig::default()
+            })
+        );
+    }
+
+    #[test]
+    fn max_rounds_above_the_limit_is_rejected() {
+        let yaml =
+            STUDY_CONFIG_YAML.replace("difficulty: 7", "difficulty: 7\n    max_rounds: 5000");
+        let config: StudyConfig = serde_yaml::from_str(&yaml).unwrap();
+        let error = config.get_trial_dungeons(&HashMap::new()).unwrap_err();
+        assert!(error.contains("max_rounds"), "{}", error);
+    }
+
+    #[test]
+    fn statistics_section_is_optional() {
+        let config: StudyConfig = serde_yaml::from_str(STUDY_CONFIG_YAML).unwrap();
+        assert_eq!(config.statistics, None);
+    }
+}
@@ -0,0 +1,249 @@
+use std::path::Path;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::studies::StudyOutput;
+
+/// The queue of studies waiting to run, re-read by running studies so entries can be paused or reprioritized meanwhile
+pub const STUDY_QUEUE_PATH: &str = "target/simulations/study_queue.yaml";
+
+/// Defines where a queued study is in its lifecycle
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, strum::Display)]
+pub enum QueueStatus {
+    Queued, // Waiting to run, resuming from its checkpoint if it was stopped partway
+    Running,
+    Pausing, // Running, and will stop after its current batch of trials
+    Paused,  // Waiting to be resumed before it runs again
+    Finished,
+    Failed, // The study's process exited with an error
+}
+
+/// A study in the queue, run as if its arguments were passed to the simulator directly
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QueuedStudy {
+    id: usize,
+    args: Vec<String>,
+    priority: i32, // Higher priority studies run first, and preempt running studies of lower priority after their current batch
+    status: QueueStatus,
+}
+
+/// Studies to run one at a time in order of priority
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct StudyQueue {
+    next_id: usize,
+    studies: Vec<QueuedStudy>,
+}
+
+/// Load the study queue, which is empty if it has never been saved
+pub fn load_study_queue(path: &str) -> Result<StudyQueue, std::io::Error> {
+    if !Path::new(path).exists() {
+        return Ok(StudyQueue::default());
+    }
+    let reader = std::fs::File::open(path)?;
+    return serde_yaml::from_reader(reader).map_err(std::io::Error::other);
+}
+
+impl StudyQueue {
+    /// Save the queue, replacing the file in one step so a running study never reads it half written
+    pub fn save(&self, path: &str) -> Result<(), std::io::Error> {
+        if let Some(p) = Path::new(path).parent() {
+            std::fs::create_dir_all(p)?;
+        }
+        let temp_path = f!("{}.tmp", path);
+        let writer = std::fs::File::create(&temp_path)?;
+        serde_yaml::to_writer(writer, self).map_err(std::io::Error::other)?;
+        return std::fs::rename(temp_path, path);
+    }
+
+    /// Add a study to the queue, returning its id
+    pub fn add(&mut self, args: Vec<String>, priority: i32) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.studies.push(QueuedStudy {
+            id,
+            args,
+            priority,
+            status: QueueStatus::Queued,
+        });
+        return id;
+    }
+
+    fn find(&self, id: usize) -> Option<&QueuedStudy> {
+        return self.studies.iter().find(|study| study.id == id);
+    }
+
+    fn find_mut(&mut self, id: usize) -> Result<&mut QueuedStudy, &'static str> {
+        return self
+            .studies
+            .iter_mut()
+            .find(|study| study.id == id)
+            .ok_or("no study in the queue has that id");
+    }
+
+    pub fn get_args(&self, id: usize) -> Option<Vec<String>> {
+        return self.find(id).map(|study| study.args.clone());
+    }
+
+    /// Pause a study so it isn't run until resumed. A running study stops after its current batch of trials
+    pub fn pause(&mut self, id: usize) -> Result<(), &'static str> {
+        let study = self.find_mut(id)?;
+        study.status = match study.status {
+            QueueStatus::Queued | QueueStatus::Paused => QueueStatus::Paused,
+            QueueStatus::Running | QueueStatus::Pausing => QueueStatus::Pausing,
+            QueueStatus::Finished | QueueStatus::Failed => {
+                return Err("only queued or running studies can be paused")
+            }
+        };
+        return Ok(());
+    }
+
+    /// Resume a paused study, or cancel the pause of a running study that hasn't stopped yet
+    pub fn resume(&mut self, id: usize) -> Result<(), &'static str> {
+        let study = self.find_mut(id)?;
+        study.status = match study.status {
+            QueueStatus::Queued | QueueStatus::Paused => QueueStatus::Queued,
+            QueueStatus::Running | QueueStatus::Pausing => QueueStatus::Running,
+            QueueStatus::Finished | QueueStatus::Failed => {
+                return Err("only paused studies can be resumed")
+            }
+        };
+        return Ok(());
+    }
+
+    pub fn set_priority(&mut self, id: usize, priority: i32) -> Result<(), &'static str> {
+        self.find_mut(id)?.priority = priority;
+        return Ok(());
+    }
+
+    /// Returns the id of the next study to run: the highest priority queued study, oldest first among equals
+    pub fn get_next(&self) -> Option<usize> {
+        return self
+            .studies
+            .iter()
+            .filter(|study| study.status == QueueStatus::Queued)
+            .min_by_key(|study| (-study.priority, study.id))
+            .map(|study| study.id);
+    }
+
+    /// Whether a running study should stop after its current batch, because it was paused or a higher priority study is waiting
+    pub fn should_stop(&self, id: usize) -> bool {
+        let Some(study) = self.find(id) else {
+            return false;
+        };
+        return study.status == QueueStatus::Pausing
+            || self.studies.iter().any(|other| {
+                other.status == QueueStatus::Queued && other.priority > study.priority
+            });
+    }
+
+    pub fn mark_running(&mut self, id: usize) -> Result<(), &'static str> {
+        self.find_mut(id)?.status = QueueStatus::Running;
+        return Ok(());
+    }
+
+    /// Record that a running study stopped partway: paused studies wait to be resumed, preempted studies go back in the queue
+    pub fn mark_stopped(&mut self, id: usize) -> Result<(), &'static str> {
+        let study = self.find_mut(id)?;
+        study.status = match study.status {
+            QueueStatus::Pausing => QueueStatus::Paused,
+            _ => QueueStatus::Queued,
+        };
+        return Ok(());
+    }
+
+    /// Record that a study's process exited, finishing it unless it stopped partway
+    /// Studies that can't stop partway finish even if they were paused while running
+    pub fn mark_exited(&mut self, id: usize, success: bool) -> Result<QueueStatus, &'static str> {
+        let study = self.find_mut(id)?;
+        if let QueueStatus::Running | QueueStatus::Pausing = study.status {
+            study.status = match success {
+                true => QueueStatus::Finished,
+                false => QueueStatus::Failed,
+            };
+        }
+        return Ok(study.status);
+    }
+
+    /// Format every study in the queue with its priority, status, and arguments, in the order they were added
+    pub fn fmt_table(&self) -> String {
+        let mut res = f!(
+            "{:>4} {:>8} {:<9} {}\n",
+            "Id",
+            "Priority",
+            "Status",
+            "Arguments"
+        );
+        for study in self.studies.iter() {
+            res += &f!(
+                "{:>4} {:>8} {:<9} {}\n",
+                study.id,
+                study.priority,
+                study.status.to_string(),
+                study.args.join(" ")
+            );
+        }
+        return res;
+    }
+}
+
+/// The file a queued study stopped partway saves its progress to, and resumes from when it's next run
+pub fn get_checkpoint_path(id: usize) -> String {
+    return f!("target/simulations/queue_checkpoints/{}.msgpack", id);
+}
+
+pub fn save_checkpoint<T: Serialize>(path: &str, state: &T) -> Result<(), std::io::Error> {
+    if let Some(p) = Path::new(path).parent() {
+        std::fs::create_dir_all(p)?;
+    }
+    let bytes = rmp_serde::to_vec_named(state).map_err(std::io::Error::other)?;
+    return std::fs::write(path, bytes);
+}
+
+/// Load a checkpoint, or None if the study hasn't been stopped partway before
+pub fn load_checkpoint<T: DeserializeOwned>(path: &str) -> Result<Option<T>, std::io::Error> {
+    if !Path::new(path).exists() {
+        return Ok(None);
+    }
+    let bytes = std::fs::read(path)?;
+    return rmp_serde::from_slice(&bytes)
+        .map(Some)
+        .map_err(std::io::Error::other);
+}
+
+/// Run queued studies one at a time, each in its own process, until none are left waiting, returning the number of runs
+/// The queue is re-read between runs, so studies added, paused, or reprioritized meanwhile are picked up
+/// Each run is announced to the output as it starts and finishes
+pub fn run_study_queue(path: &str, output: StudyOutput) -> Result<usize, std::io::Error> {
+    let executable = std::env::current_exe()?;
+    let mut run_qty = 0;
+    loop {
+        let mut queue = load_study_queue(path)?;
+        let Some(id) = queue.get_next() else {
+            break;
+        };
+        let args = queue.get_args(id).unwrap();
+        queue.mark_running(id).unwrap();
+        queue.save(path)?;
+
+        output.print(&f!("Running queued study {}: {}\n", id, args.join(" ")));
+        let exit_status = std::process::Command::new(&executable)
+            .args(&args)
+            .arg("--queue-id")
+            .arg(id.to_string())
+            .status()?;
+        run_qty += 1;
+
+        let mut queue = load_study_queue(path)?;
+        let status = queue.mark_exited(id, exit_status.success()).unwrap();
+        queue.save(path)?;
+        if status == QueueStatus::Finished && Path::new(&get_checkpoint_path(id)).exists() {
+            std::fs::remove_file(get_checkpoint_path(id))?;
+        }
+        output.print(&f!(
+            "Queued study {} is {}\n",
+            id,
+            status.to_string().to_lowercase()
+        ));
+    }
+    return Ok(run_qty);
+}
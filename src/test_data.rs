@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use crate::{
+    dungeons::{create_trial_dungeon, TrialDungeon},
+    equipment::{create_blueprint, Blueprint},
+    hero_builder::{_create_hero_class, Hero, HeroClass, MAX_HERO_LEVEL},
+    heroes::SimHero,
+    inputs::{create_hero_input, load_dungeons_from_yaml, load_elements_from_yaml, HeroInput},
+    simulator::{create_simulator, GameData, Simulator},
+    skills::{create_hero_skill, create_innate_skill, HeroSkill, InnateSkill, EMPTY_SKILL},
+    studies::HeroBuilderInformation,
+};
+
+/// The class every test hero is hired as, able to equip a sword in every slot
+pub const TEST_CLASS: &str = "Mercenary";
+
+/// The sword every test hero equips in each slot
+pub const TEST_ITEM: &str = "Test Sword";
+
+/// The skill families of the test game data, each with tiers 1-4 named "<family> T<tier>"
+pub const TEST_SKILL_FAMILIES: [&str; 4] = ["Strike", "Guard", "Vigor", "Focus"];
+
+/// The zone of the only test dungeon, a dungeon of up to 4 heroes without barriers
+pub const TEST_ZONE: &str = "Test Cave";
+
+const TEST_DUNGEONS_YAML: &str = "
+Test Cave:
+  zone: Test Cave
+  max_num_heroes: 4
+  hp: [300.0, 600.0, 900.0, 1200.0]
+  damage: [10.0, 20.0, 30.0, 40.0]
+  defense_cap: [500.0, 1000.0, 1500.0, 2000.0]
+  aoe_damage: [5.0, 10.0, 15.0, 20.0]
+  aoe_chance: [25.0, 25.0, 25.0, 25.0]
+  minimum_power: [10, 20, 30, 40]
+  barrier_types: [Any, Any, Any]
+  barrier_healths: [0.0, 0.0, 0.0, 0.0]
+  boss_hp: [3000.0, 6000.0, 9000.0, 12000.0]
+  boss_damage: [20.0, 30.0, 40.0, 50.0]
+  boss_defense_cap: [1000.0, 1500.0, 2000.0, 2500.0]
+  boss_aoe_damage: [10.0, 15.0, 20.0, 25.0]
+  boss_aoe_chance: [25.0, 25.0, 25.0, 25.0]
+  boss_minimum_power: [20, 30, 40, 50]
+  boss_barrier_type: Any
+  boss_barrier_healths: [0.0, 0.0, 0.0, 0.0]
+";
+
+const TEST_ELEMENTS_YAML: &str = "
+- grade: 1
+  atk_bonus: 14.0
+  def_bonus: 10.0
+  hp_bonus: 3.0
+  affinity_multiplier: 1.5
+";
+
+fn create_test_sword() -> Blueprint {
+    return create_blueprint(
+        String::from(TEST_ITEM),
+        String::from("Sword"),
+        String::new(),
+        0,
+        0,
+        1,
+        0,
+        0,
+        String::new(),
+        0.0,
+        0,
+        0.0,
+        0,
+        0,
+        0,
+        0,
+        String::new(),
+        0,
+        String::new(),
+        0,
+        String::new(),
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        String::new(),
+        String::new(),
+        0,
+        String::new(),
+        String::new(),
+        0,
+        20.0,
+        10.0,
+        5.0,
+        0.0,
+        0.0,
+        String::from("---"),
+        String::from("---"),
+        0,
+        0,
+        0,
+        0,
+    );
+}
+
+fn create_test_hero_class() -> HeroClass {
+    let levels = usize::from(MAX_HERO_LEVEL);
+    return _create_hero_class(
+        String::from(TEST_CLASS),
+        String::new(),
+        0,
+        0,
+        vec![100.0; levels],
+        vec![30.0; levels],
+        vec![20.0; levels],
+        0.05,
+        0.05,
+        2.0,
+        10,
+        String::from("Fire"),
+        std::array::from_fn(|_| vec![String::from("Sword")]),
+        vec![],
+        std::array::from_fn(|_| String::from("Test Innate")),
+        false,
+        None,
+    );
+}
+
+/// Each tier of a skill family raises one stat, ATK, DEF, HP, or crit chance, by 10% more than the tier before, and needs 10 more element qty
+fn create_test_hero_skill(family: &str, tier: u8) -> HeroSkill {
+    let bonus = 0.1 * f64::from(tier);
+    return create_hero_skill(
+        f!("{} T{}", family, tier),
+        String::from("Hero"),
+        tier,
+        String::from("Common"),
+        u16::from(tier - 1) * 10,
+        family.to_string(),
+        false,
+        String::new(),
+        if family == "Strike" { bonus } else { 0.0 },
+        0.0,
+        if family == "Vigor" { bonus } else { 0.0 },
+        0.0,
+        if family == "Guard" { bonus } else { 0.0 },
+        0.0,
+        if family == "Focus" { bonus } else { 0.0 },
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        vec![],
+        vec![String::from(TEST_CLASS)],
+    );
+}
+
+fn create_test_innate_skill() -> InnateSkill {
+    return create_innate_skill(
+        String::from("Test Innate"),
+        String::from("Innate"),
+        1,
+        0,
+        String::from("Test Innate"),
+        false,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        vec![],
+        vec![String::from(TEST_CLASS)],
+    );
+}
+
+/// Game data small enough to build in memory: one class, one sword, one innate skill, and the skill families of TEST_SKILL_FAMILIES
+pub fn create_test_game_data() -> HeroBuilderInformation {
+    let mut hero_skill_tier_1_name_map: HashMap<String, String> = HashMap::new();
+    let mut hero_skill_map: HashMap<String, HeroSkill> = HashMap::new();
+    for family in TEST_SKILL_FAMILIES {
+        for tier in 1..=4 {
+            let skill = create_test_hero_skill(family, tier);
+            hero_skill_tier_1_name_map.insert(f!("{} T{}", family, tier), skill.get_name());
+            hero_skill_map.insert(skill.get_name(), skill);
+        }
+    }
+    return HeroBuilderInformation {
+        bp_map: HashMap::from([(String::from(TEST_ITEM), create_test_sword())]),
+        hero_classes: HashMap::from([(String::from(TEST_CLASS), create_test_hero_class())]),
+        hero_skill_tier_1_name_map,
+        hero_skill_map,
+        class_innate_skill_names_map: HashMap::from([(
+            String::from(TEST_CLASS),
+            String::from("Test Innate"),
+        )]),
+        innate_skill_map: HashMap::from([(
+            String::from("Test Innate"),
+            create_test_innate_skill(),
+        )]),
+        pet_skill_map: HashMap::new(),
+        spirit_map: HashMap::new(),
+        elements: load_elements_from_yaml(TEST_ELEMENTS_YAML.as_bytes()).unwrap(),
+        seed_values: Default::default(),
+        champion_data: HashMap::new(),
+        reward_quests: vec![],
+        build_cache: Default::default(),
+    };
+}
+
+/// A hero input of the test class with 10 of each seed, wearing the test sword in every slot, with the given skills and nothing socketed
+pub fn create_test_hero_input(identifier: &str, rank: u8, skills: [&str; 4]) -> HeroInput {
+    return create_hero_input(
+        identifier.to_string(),
+        String::from(TEST_CLASS),
+        1,
+        rank,
+        String::from("Fire"),
+        10,
+        10,
+        10,
+        skills.map(String::from),
+        std::array::from_fn(|_| String::from(TEST_ITEM)),
+        std::array::from_fn(|_| String::from("Normal")),
+        std::array::from_fn(|_| String::from("---")),
+        std::array::from_fn(|_| String::from("---")),
+    );
+}
+
+/// The builder hero of create_test_hero_input
+pub fn create_test_hero(identifier: &str, rank: u8, skills: [&str; 4]) -> Hero {
+    return Hero::from(create_test_hero_input(identifier, rank, skills));
+}
+
+/// Create a simulator of the test game data and the given heroes, so the heroes are validated and scaled by their class
+pub fn create_test_simulator(heroes: Vec<Hero>) -> Simulator {
+    return create_simulator(
+        GameData {
+            hero_builder_information: create_test_game_data(),
+        },
+        heroes,
+    )
+    .unwrap();
+}
+
+/// A test hero validated and scaled by its class, as heroes loaded from a hero csv are, ready to be built or varied by a study
+pub fn create_prepared_test_hero(identifier: &str, rank: u8, skills: [&str; 4]) -> Hero {
+    return create_test_simulator(vec![create_test_hero(identifier, rank, skills)])
+        .get_hero(identifier)
+        .cloned()
+        .unwrap();
+}
+
+/// Build test heroes of the given identifiers, each with one skill of a different family, as the heroes of a party
+pub fn create_test_party(identifiers: &[&str]) -> Vec<SimHero> {
+    let heroes: Vec<Hero> = identifiers
+        .iter()
+        .enumerate()
+        .map(|(i, identifier)| {
+            let skill = f!("{} T1", TEST_SKILL_FAMILIES[i % TEST_SKILL_FAMILIES.len()]);
+            return create_test_hero(
+                identifier,
+                1,
+                [&skill, EMPTY_SKILL, EMPTY_SKILL, EMPTY_SKILL],
+            );
+        })
+        .collect();
+    let simulator = create_test_simulator(heroes);
+    return identifiers
+        .iter()
+        .map(|identifier| simulator.build_sim_hero(identifier).unwrap())
+        .collect();
+}
+
+/// The test dungeon at the given difficulty
+pub fn create_test_trial_dungeon(difficulty: usize) -> TrialDungeon {
+    let dungeon = load_dungeons_from_yaml(TEST_DUNGEONS_YAML.as_bytes(), &HashMap::new())
+        .unwrap()
+        .remove(TEST_ZONE)
+        .unwrap();
+    return create_trial_dungeon(dungeon, difficulty, None, None, None);
+}
@@ -1,105 +1,146 @@
-use crate::decimals::round_to_2;
+use crate::combat_trace::create_trace_csv_record;
+use crate::formatting::{
+    create_stats_view, fmt_f64, serialize_rounded, serialize_rounded_option, StatsView,
+};
+use crate::hashing::stable_hash;
+use crate::quests::Quest;
+use crate::rng::{create_sim_rng, with_stream_rng, RngConfig, SimRng};
+use crate::statistics::{
+    bootstrap_intervals, proportion_standard_error, wilson_interval, StatisticsConfig,
+};
 
 use super::dungeons::Dungeon;
 use super::heroes::Team;
-use super::simulations::{create_simulation, SimResult};
+use super::simulations::{
+    create_simulation, InitiativeModel, SimOutcome, SimResult, TargetingModel, MAX_ROUND_LIMIT,
+};
 
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use log::info;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 extern crate csv;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 struct TrialCSVRecord {
     trial_identifier: String,
+    trial_description: String,
+    build_signature: String,
     simulation_identifier: String,
     success: bool,
+    outcome: String,
     rounds: i16,
+    #[serde(serialize_with = "serialize_rounded")]
     encounter_hp_remaining: f64,
+    polonia_loot: u8,
+    #[serde(serialize_with = "serialize_rounded")]
+    entry_cost: f64,
 
+    #[serde(serialize_with = "serialize_rounded")]
     hp_remaining_hero_1: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     dmg_dealt_hero_1: f64,
-    crits_taken_hero_1: u8,
-    crits_dealt_hero_1: u8,
-    dodges_hero_1: u8,
-    attacks_missed_hero_1: u8,
-
+    #[serde(serialize_with = "serialize_rounded")]
+    healing_done_hero_1: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    hp_lost_hero_1: f64,
+    crits_taken_hero_1: u32,
+    crits_dealt_hero_1: u32,
+    dodges_hero_1: u32,
+    attacks_missed_hero_1: u32,
+
+    #[serde(serialize_with = "serialize_rounded")]
     hp_remaining_hero_2: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     dmg_dealt_hero_2: f64,
-    crits_taken_hero_2: u8,
-    crits_dealt_hero_2: u8,
-    dodges_hero_2: u8,
-    attacks_missed_hero_2: u8,
-
+    #[serde(serialize_with = "serialize_rounded")]
+    healing_done_hero_2: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    hp_lost_hero_2: f64,
+    crits_taken_hero_2: u32,
+    crits_dealt_hero_2: u32,
+    dodges_hero_2: u32,
+    attacks_missed_hero_2: u32,
+
+    #[serde(serialize_with = "serialize_rounded")]
     hp_remaining_hero_3: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     dmg_dealt_hero_3: f64,
-    crits_taken_hero_3: u8,
-    crits_dealt_hero_3: u8,
-    dodges_hero_3: u8,
-    attacks_missed_hero_3: u8,
-
+    #[serde(serialize_with = "serialize_rounded")]
+    healing_done_hero_3: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    hp_lost_hero_3: f64,
+    crits_taken_hero_3: u32,
+    crits_dealt_hero_3: u32,
+    dodges_hero_3: u32,
+    attacks_missed_hero_3: u32,
+
+    #[serde(serialize_with = "serialize_rounded")]
     hp_remaining_hero_4: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     dmg_dealt_hero_4: f64,
-    crits_taken_hero_4: u8,
-    crits_dealt_hero_4: u8,
-    dodges_hero_4: u8,
-    attacks_missed_hero_4: u8,
-
+    #[serde(serialize_with = "serialize_rounded")]
+    healing_done_hero_4: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    hp_lost_hero_4: f64,
+    crits_taken_hero_4: u32,
+    crits_dealt_hero_4: u32,
+    dodges_hero_4: u32,
+    attacks_missed_hero_4: u32,
+
+    #[serde(serialize_with = "serialize_rounded")]
     hp_remaining_hero_5: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     dmg_dealt_hero_5: f64,
-    crits_taken_hero_5: u8,
-    crits_dealt_hero_5: u8,
-    dodges_hero_5: u8,
-    attacks_missed_hero_5: u8,
-}
-
-impl TrialCSVRecord {
-    pub fn _round_floats_for_display(&self) -> TrialCSVRecord {
-        let mut tcr2 = self.clone();
-        tcr2.encounter_hp_remaining = round_to_2(tcr2.encounter_hp_remaining);
-
-        tcr2.hp_remaining_hero_1 = round_to_2(tcr2.hp_remaining_hero_1);
-        tcr2.dmg_dealt_hero_1 = round_to_2(tcr2.dmg_dealt_hero_1);
-
-        tcr2.hp_remaining_hero_2 = round_to_2(tcr2.hp_remaining_hero_2);
-        tcr2.dmg_dealt_hero_2 = round_to_2(tcr2.dmg_dealt_hero_2);
-
-        tcr2.hp_remaining_hero_3 = round_to_2(tcr2.hp_remaining_hero_3);
-        tcr2.dmg_dealt_hero_3 = round_to_2(tcr2.dmg_dealt_hero_3);
-
-        tcr2.hp_remaining_hero_4 = round_to_2(tcr2.hp_remaining_hero_4);
-        tcr2.dmg_dealt_hero_4 = round_to_2(tcr2.dmg_dealt_hero_4);
-
-        tcr2.hp_remaining_hero_5 = round_to_2(tcr2.hp_remaining_hero_5);
-        tcr2.dmg_dealt_hero_5 = round_to_2(tcr2.dmg_dealt_hero_5);
-
-        return tcr2;
-    }
+    #[serde(serialize_with = "serialize_rounded")]
+    healing_done_hero_5: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    hp_lost_hero_5: f64,
+    crits_taken_hero_5: u32,
+    crits_dealt_hero_5: u32,
+    dodges_hero_5: u32,
+    attacks_missed_hero_5: u32,
 }
 
 /// Create a trial csv record performing type validation and calculating certain fields
-fn _create_trial_csv_record(
+fn create_trial_csv_record(
     trial_identifier: String,
+    trial_description: String,
+    build_signature: String,
     simulation_identifier: String,
     result: bool,
+    outcome: SimOutcome,
     rounds: i16,
     encounter_hp_remaining: f64,
+    polonia_loot: u8,
+    entry_cost: f64,
     team_hp_remaining: [f64; 5],
     team_dmg_dealt: [f64; 5],
-    team_crits_taken: [u8; 5],
-    team_crits_dealt: [u8; 5],
-    team_dodges: [u8; 5],
-    team_attacks_missed: [u8; 5],
+    team_healing_done: [f64; 5],
+    team_hp_lost: [f64; 5],
+    team_crits_taken: [u32; 5],
+    team_crits_dealt: [u32; 5],
+    team_dodges: [u32; 5],
+    team_attacks_missed: [u32; 5],
 ) -> TrialCSVRecord {
     let t_csv_rec = TrialCSVRecord {
         trial_identifier,
+        trial_description,
+        build_signature,
         simulation_identifier,
         success: result,
+        outcome: outcome.to_string(),
         rounds,
         encounter_hp_remaining,
+        polonia_loot,
+        entry_cost,
 
         hp_remaining_hero_1: team_hp_remaining[0],
         dmg_dealt_hero_1: team_dmg_dealt[0],
+        healing_done_hero_1: team_healing_done[0],
+        hp_lost_hero_1: team_hp_lost[0],
         crits_taken_hero_1: team_crits_taken[0],
         crits_dealt_hero_1: team_crits_dealt[0],
         dodges_hero_1: team_dodges[0],
@@ -107,6 +148,8 @@ fn _create_trial_csv_record(
 
         hp_remaining_hero_2: team_hp_remaining[1],
         dmg_dealt_hero_2: team_dmg_dealt[1],
+        healing_done_hero_2: team_healing_done[1],
+        hp_lost_hero_2: team_hp_lost[1],
         crits_taken_hero_2: team_crits_taken[1],
         crits_dealt_hero_2: team_crits_dealt[1],
         dodges_hero_2: team_dodges[1],
@@ -114,6 +157,8 @@ fn _create_trial_csv_record(
 
         hp_remaining_hero_3: team_hp_remaining[2],
         dmg_dealt_hero_3: team_dmg_dealt[2],
+        healing_done_hero_3: team_healing_done[2],
+        hp_lost_hero_3: team_hp_lost[2],
         crits_taken_hero_3: team_crits_taken[2],
         crits_dealt_hero_3: team_crits_dealt[2],
         dodges_hero_3: team_dodges[2],
@@ -121,6 +166,8 @@ fn _create_trial_csv_record(
 
         hp_remaining_hero_4: team_hp_remaining[3],
         dmg_dealt_hero_4: team_dmg_dealt[3],
+        healing_done_hero_4: team_healing_done[3],
+        hp_lost_hero_4: team_hp_lost[3],
         crits_taken_hero_4: team_crits_taken[3],
         crits_dealt_hero_4: team_crits_dealt[3],
         dodges_hero_4: team_dodges[3],
@@ -128,6 +175,8 @@ fn _create_trial_csv_record(
 
         hp_remaining_hero_5: team_hp_remaining[4],
         dmg_dealt_hero_5: team_dmg_dealt[4],
+        healing_done_hero_5: team_healing_done[4],
+        hp_lost_hero_5: team_hp_lost[4],
         crits_taken_hero_5: team_crits_taken[4],
         crits_dealt_hero_5: team_crits_dealt[4],
         dodges_hero_5: team_dodges[4],
@@ -137,6 +186,30 @@ fn _create_trial_csv_record(
     return t_csv_rec;
 }
 
+/// The first field of a simulation's raw result that differs between two runs of the same trial
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationDivergence {
+    pub simulation: usize, // The index of the simulation within the trial
+    pub field: String,     // The raw results csv column that differs
+    pub value: String,
+    pub other_value: String,
+}
+
+/// Returns each column of a record as it would be written to csv, paired with its header, in column order
+fn get_csv_fields<T: Serialize>(record: &T) -> Vec<(String, String)> {
+    let mut wtr = csv::Writer::from_writer(vec![]);
+    wtr.serialize(record).unwrap();
+    let bytes = wtr.into_inner().unwrap();
+    let mut rdr = csv::Reader::from_reader(bytes.as_slice());
+    let headers = rdr.headers().unwrap().clone();
+    let row = rdr.records().next().unwrap().unwrap();
+    return headers
+        .iter()
+        .zip(row.iter())
+        .map(|(header, value)| (header.to_string(), value.to_string()))
+        .collect();
+}
+
 /// Defines instructions for running one or more Simulations
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Trial {
@@ -147,26 +220,72 @@ pub struct Trial {
     dungeon: Dungeon,
     difficulty_settings: Vec<usize>,
     force_minibosses: Option<bool>,
+    max_rounds: i16,
+    initiative: InitiativeModel,
+    #[serde(default)]
+    targeting: TargetingModel,
     results: Vec<SimResult>,
     log_all: bool,
+    #[serde(default)]
+    trace: bool, // Whether each simulation records its attacks, see save_trace
+    #[serde(default)]
+    reward_quest: Option<Quest>, // The quest the trial's rewards per hour are estimated from, if any
+    #[serde(default)]
+    statistics: StatisticsConfig, // How the trial's intervals are computed and whether it runs until its success rate's interval is narrow enough
+    #[serde(default)]
+    rng: RngConfig, // The generator and seed the trial's simulations draw from
 }
 
 impl Trial {
+    /// Compute the trial's intervals and adaptive simulation_qty from the given config instead of the defaults
+    pub fn set_statistics(&mut self, statistics: StatisticsConfig) {
+        self.statistics = statistics;
+    }
+
+    /// Draw the trial's simulations from the given generator and seed instead of an unseeded default
+    pub fn set_rng(&mut self, rng: RngConfig) {
+        self.rng = rng;
+    }
+
+    /// Run the trial's simulations on a generator created from its rng config
     pub fn run_simulations_single_threaded(&mut self) {
+        let mut rng = create_sim_rng(self.rng);
+        self.run_simulations_with_rng(&mut rng);
+    }
+
+    /// Run the trial's simulations drawing from rng. With a target interval width configured, keeps running more batches of simulation_qty simulations
+    /// until the success rate's 95% interval is narrower than the target or the configured max simulations are run
+    pub fn run_simulations_with_rng(&mut self, rng: &mut SimRng) {
+        let batch_qty = self.simulation_qty;
+        self.run_simulation_batch(rng);
+        while let Some(simulation_qty) = self.get_next_adaptive_simulation_qty(batch_qty) {
+            self.simulation_qty = simulation_qty;
+            self.run_simulation_batch(rng);
+        }
+    }
+
+    /// The simulation_qty to extend the trial to if its success rate's interval is still wider than the configured target, or None once it's narrow enough
+    fn get_next_adaptive_simulation_qty(&self, batch_qty: usize) -> Option<usize> {
+        let config = self.statistics;
+        let target_width = config.target_interval_width?;
+        let qty = self.results.len();
+        if qty == 0 || qty >= config.max_simulation_qty {
+            return None;
+        }
+        let success_rate =
+            self.results.iter().filter(|res| res.is_success()).count() as f64 / qty as f64;
+        let (lower, upper) = wilson_interval(success_rate, qty);
+        if upper - lower <= target_width {
+            return None;
+        }
+        return Some((qty + batch_qty).min(config.max_simulation_qty));
+    }
+
+    fn run_simulation_batch(&mut self, rng: &mut SimRng) {
         while self.results.len() < self.simulation_qty {
             // let timer = Instant::now();
             // print!("Running simulation iteration:  # {:#?}", self.results.len());
-            info!(
-                "\n\nRunning simulation iteration: # {}\n",
-                self.results.len()
-            );
-            let encounter = self
-                .dungeon
-                .generate_encounter_from_dungeon(&self.difficulty_settings, self.force_minibosses)
-                .unwrap();
-            let mut simulation =
-                create_simulation(&self.team, encounter, vec![], self.log_all).unwrap();
-            let sim_res = simulation.run().unwrap();
+            let sim_res = self.run_simulation(rng);
             // print!(
             //     "\rRunning simulation iteration: # {:#?} | Success: {:#?} in {:#?} rounds | Took {:#?}ms\n",
             //     self.results.len(),
@@ -177,31 +296,197 @@ impl Trial {
             self.results.push(sim_res);
         }
     }
-    pub fn _get_results_unranked(&self) -> Vec<SimResult> {
-        return self.results.clone();
+
+    /// Run the remaining simulations split across thread_qty threads, each simulation drawing from the stream of its index
+    /// With a seeded rng config results depend only on the seed and not on the thread count, so runs on any number of threads can be checked against each other
+    pub fn run_simulations_in_streams(&mut self, thread_qty: usize) {
+        let indices: Vec<usize> = (self.results.len()..self.simulation_qty).collect();
+        if indices.is_empty() {
+            return;
+        }
+        let chunk_size = indices.len().div_ceil(thread_qty.max(1));
+        let trial = &*self;
+        let results: Vec<SimResult> = std::thread::scope(|scope| {
+            let handles: Vec<_> = indices
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|i| {
+                                with_stream_rng(trial.rng, *i as u64, |rng| {
+                                    return trial.run_simulation(rng);
+                                })
+                            })
+                            .collect::<Vec<SimResult>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        self.results.extend(results);
     }
-    pub fn _save_results_to_csv(&self, path: String) -> Result<(), std::io::Error> {
+
+    fn run_simulation(&self, rng: &mut SimRng) -> SimResult {
+        let encounter = self
+            .dungeon
+            .generate_encounter_from_dungeon(&self.difficulty_settings, self.force_minibosses, rng)
+            .unwrap();
+        let mut simulation = create_simulation(
+            &self.team,
+            encounter,
+            vec![],
+            self.max_rounds,
+            self.initiative,
+            self.targeting,
+            self.log_all,
+        )
+        .unwrap();
+        simulation.set_trace(self.trace);
+        return simulation.run(rng).unwrap();
+    }
+
+    /// Record every attack of the trial's simulations run from now on, for saving with save_trace
+    pub fn set_trace(&mut self, trace: bool) {
+        self.trace = trace;
+    }
+
+    /// Estimate the trial's rewards per hour from a quest, such as the one found by HeroBuilderInformation::find_reward_quest
+    pub fn set_reward_quest(&mut self, reward_quest: Option<Quest>) {
+        self.reward_quest = reward_quest;
+    }
+
+    /// Save the combat trace of each traced simulation to a csv with a row per attack, optionally only for lost simulations
+    /// With save_msgpack the same rows are also saved as MessagePack next to the csv, replacing any earlier trace there
+    /// Returns the number of simulations written
+    pub fn save_trace(
+        &self,
+        path: &str,
+        losses_only: bool,
+        save_msgpack: bool,
+    ) -> Result<usize, std::io::Error> {
+        let mut records = vec![];
+        let mut simulation_qty = 0;
+        for (i, res) in self.results.iter().enumerate() {
+            if res.get_trace().is_empty() || (losses_only && res.is_success()) {
+                continue;
+            }
+            for event in res.get_trace() {
+                records.push(create_trace_csv_record(
+                    i,
+                    res.get_outcome().to_string(),
+                    event,
+                ));
+            }
+            simulation_qty += 1;
+        }
+
         let mut wtr = csv::Writer::from_path(path)?;
+        for record in records.iter() {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+        if save_msgpack {
+            let msgpack_path = get_trace_msgpack_path(path);
+            if std::path::Path::new(&msgpack_path).exists() {
+                std::fs::remove_file(&msgpack_path)?;
+            }
+            append_records_to_msgpack(&msgpack_path, &records)?;
+        }
+        return Ok(simulation_qty);
+    }
 
+    /// Returns the first simulation whose raw result differs from the other trial's, comparing fields in csv column order
+    pub fn find_first_divergence(&self, other: &Trial) -> Option<SimulationDivergence> {
+        let records = self.create_raw_result_records();
+        let other_records = other.create_raw_result_records();
+        for (i, (record, other_record)) in records.iter().zip(other_records.iter()).enumerate() {
+            let divergence = get_csv_fields(record)
+                .into_iter()
+                .zip(get_csv_fields(other_record))
+                .find(|((_, value), (_, other_value))| value != other_value);
+            if let Some(((field, value), (_, other_value))) = divergence {
+                return Some(SimulationDivergence {
+                    simulation: i,
+                    field,
+                    value,
+                    other_value,
+                });
+            }
+        }
+        if records.len() != other_records.len() {
+            return Some(SimulationDivergence {
+                simulation: records.len().min(other_records.len()),
+                field: String::from("simulation count"),
+                value: records.len().to_string(),
+                other_value: other_records.len().to_string(),
+            });
+        }
+        return None;
+    }
+
+    pub fn get_team(&self) -> &Team {
+        return &self.team;
+    }
+
+    pub fn _get_results_unranked(&self) -> Vec<SimResult> {
+        return self.results.clone();
+    }
+    /// Returns one raw result record per simulation
+    fn create_raw_result_records(&self) -> Vec<TrialCSVRecord> {
+        let mut records: Vec<TrialCSVRecord> = vec![];
         for (i, res) in self.results.iter().enumerate() {
-            let record = _create_trial_csv_record(
+            records.push(create_trial_csv_record(
                 self.identifier.to_string(),
+                self.description.to_string(),
+                self.team.get_build_signature(),
                 i.to_string(),
                 res.is_success(),
+                res.get_outcome(),
                 res.get_rounds(),
                 res.get_encounter_hp_remaining(),
+                res.get_polonia_loot(),
+                res.get_encounter().get_entry_cost(),
                 res.get_team_hp_remaining(),
                 res.get_team_damage_dealt(),
+                res.get_team_healing_done(),
+                res.get_team_hp_lost(),
                 res.get_team_crits_taken(),
                 res.get_team_crits_dealt(),
                 res.get_team_dodges(),
                 res.get_team_attacks_missed(),
-            );
-            wtr.serialize(record._round_floats_for_display())?;
+            ));
         }
+        return records;
+    }
 
-        wtr.flush()?;
-        return Ok(());
+    /// Returns each hero's stats averaged over every simulation, in team order
+    fn create_hero_detail_records(&self) -> Vec<HeroDetailRecord> {
+        let simulation_qty = self.results.len().max(1) as f64;
+        let mut records: Vec<HeroDetailRecord> = vec![];
+        for (i, hero_identifier) in self.team.get_team_hero_names().iter().enumerate().take(5) {
+            let mean = |metric: &dyn Fn(&SimResult) -> f64| -> f64 {
+                return self.results.iter().map(metric).sum::<f64>() / simulation_qty;
+            };
+            records.push(HeroDetailRecord {
+                hero_identifier: hero_identifier.to_string(),
+                survival_rate: mean(&|res| (res.get_team_hp_remaining()[i] > 0.0) as u8 as f64),
+                avg_hp_remaining: mean(&|res| res.get_team_hp_remaining()[i]),
+                avg_dmg_dealt: mean(&|res| res.get_team_damage_dealt()[i]),
+                avg_dmg_taken: mean(&|res| res.get_team_hp_lost()[i]),
+                avg_healing_done: mean(&|res| res.get_team_healing_done()[i]),
+                avg_crits_dealt: mean(&|res| res.get_team_crits_dealt()[i] as f64),
+                avg_crits_taken: mean(&|res| res.get_team_crits_taken()[i] as f64),
+                avg_dodges: mean(&|res| res.get_team_dodges()[i] as f64),
+                avg_attacks_missed: mean(&|res| res.get_team_attacks_missed()[i] as f64),
+                avg_rounds_survived: mean(&|res| res.get_team_rounds_survived()[i] as f64),
+                avg_procs: mean(&|res| res.get_team_procs()[i] as f64),
+            });
+        }
+        return records;
     }
 
     /// Create a trial result, performing type validation and calculating certain fields
@@ -214,19 +499,14 @@ impl Trial {
             .cloned()
             .collect();
 
-        let mut all_results_length = all_results.len();
-        let mut miniboss_results_length = miniboss_results.len();
-
-        if all_results_length == 0 {
-            all_results_length = 1
-        }
-        if miniboss_results_length == 0 {
-            miniboss_results_length = 1
-        }
+        // Averages are taken in f64 to avoid integer division truncating rates to 0 or 1
+        let all_results_length = all_results.len().max(1) as f64;
+        let miniboss_results_length = miniboss_results.len().max(1) as f64;
 
         let mut vec_hero_survival_rate: [Vec<u8>; 5] = Default::default();
         let mut vec_hero_avg_hp_remaining: [Vec<f64>; 5] = Default::default();
         let mut vec_hero_avg_dmg: [Vec<f64>; 5] = Default::default();
+        let mut vec_hero_avg_healing: [Vec<f64>; 5] = Default::default();
         let mut vec_hero_avg_dodges: [Vec<f64>; 5] = Default::default();
         let mut vec_hero_avg_atk_accuracy: [Vec<f64>; 5] = Default::default();
         let mut vec_hero_avg_crits_dealt: [Vec<f64>; 5] = Default::default();
@@ -237,6 +517,7 @@ impl Trial {
 
             let team_hp_rem = res.get_team_hp_remaining();
             let team_dmg_dealt = res.get_team_damage_dealt();
+            let team_healing_done = res.get_team_healing_done();
             let team_dodges = res.get_team_dodges();
             let team_atks_missed = res.get_team_attacks_missed();
             let team_crits_dealt = res.get_team_crits_dealt();
@@ -246,6 +527,7 @@ impl Trial {
                 vec_hero_survival_rate[i].push(survived as u8);
                 vec_hero_avg_hp_remaining[i].push(team_hp_rem[i]);
                 vec_hero_avg_dmg[i].push(team_dmg_dealt[i]);
+                vec_hero_avg_healing[i].push(team_healing_done[i]);
                 vec_hero_avg_dodges[i].push((sim_rounds - (team_dodges[i] as f64)) / sim_rounds);
                 vec_hero_avg_atk_accuracy[i]
                     .push((sim_rounds - (team_atks_missed[i] as f64)) / sim_rounds);
@@ -256,10 +538,88 @@ impl Trial {
             }
         }
 
+        let success_rate = all_results
+            .iter()
+            .map(|res| res.is_success() as u32 as f64)
+            .sum::<f64>()
+            / all_results_length;
+        let avg_entry_cost = all_results
+            .iter()
+            .map(|res| res.get_encounter().get_entry_cost())
+            .sum::<f64>()
+            / all_results_length;
+
+        let avg_polonia_loot = all_results
+            .iter()
+            .map(|res| res.get_polonia_loot() as f64)
+            .sum::<f64>()
+            / all_results_length;
+        let injury_rate = all_results
+            .iter()
+            .map(|res| res.is_any_hero_defeated() as u32 as f64)
+            .sum::<f64>()
+            / all_results_length;
+        let reward_quest = self.reward_quest.as_ref();
+        let rest_time_percent = self.team.get_rest_time_percent();
+        let xp_percent = self.team.get_xp_percent();
+        let (xp_per_hour, gold_per_hour, loot_per_hour) =
+            reward_quest.map_or((0.0, 0.0, 0.0), |quest| {
+                return quest.calculate_rewards_per_hour(
+                    success_rate,
+                    injury_rate,
+                    avg_polonia_loot,
+                    rest_time_percent,
+                    xp_percent,
+                );
+            });
+        let mut hero_xp_per_quest = [0f64; 5];
+        if let Some(quest) = reward_quest {
+            for (i, hero_xp_percent) in self.team.get_heroes_xp_percent().iter().enumerate().take(5)
+            {
+                hero_xp_per_quest[i] = quest.calculate_xp_per_quest(success_rate, *hero_xp_percent);
+            }
+        }
+
+        let metric_intervals =
+            bootstrap_intervals(&self.statistics, &self.rng, all_results.len(), |indices| {
+                let mean = |metric: fn(&SimResult) -> f64| -> f64 {
+                    return indices
+                        .iter()
+                        .map(|i| metric(&all_results[*i]))
+                        .sum::<f64>()
+                        / indices.len() as f64;
+                };
+                let success_rate = mean(|res| res.is_success() as u32 as f64);
+                let (xp_per_hour, gold_per_hour, loot_per_hour) =
+                    reward_quest.map_or((0.0, 0.0, 0.0), |quest| {
+                        return quest.calculate_rewards_per_hour(
+                            success_rate,
+                            mean(|res| res.is_any_hero_defeated() as u32 as f64),
+                            mean(|res| res.get_polonia_loot() as f64),
+                            rest_time_percent,
+                            xp_percent,
+                        );
+                    });
+                return vec![
+                    success_rate,
+                    mean(|res| res.get_rounds() as f64),
+                    xp_per_hour,
+                    gold_per_hour,
+                    loot_per_hour,
+                ];
+            })
+            .map(|intervals| MetricIntervals {
+                success_rate: intervals[0],
+                average_rounds: intervals[1],
+                xp_per_hour: intervals[2],
+                gold_per_hour: intervals[3],
+                loot_per_hour: intervals[4],
+            });
+
         let hero_names: Vec<String> = all_results[0].get_team().get_team_hero_names();
         let hero_survival_rate: [f64; 5] = vec_hero_survival_rate
             .iter()
-            .map(|sr| (sr.iter().sum::<u8>() / sr.len() as u8) as f64)
+            .map(|sr| sr.iter().map(|s| *s as f64).sum::<f64>() / sr.len() as f64)
             .collect::<Vec<f64>>()
             .try_into()
             .unwrap();
@@ -275,6 +635,12 @@ impl Trial {
             .collect::<Vec<f64>>()
             .try_into()
             .unwrap();
+        let hero_avg_healing: [f64; 5] = vec_hero_avg_healing
+            .iter()
+            .map(|healing| healing.iter().sum::<f64>() / healing.len() as f64)
+            .collect::<Vec<f64>>()
+            .try_into()
+            .unwrap();
         let hero_avg_dodge_rate: [f64; 5] = vec_hero_avg_dodges
             .iter()
             .map(|dg| dg.iter().sum::<f64>() / dg.len() as f64)
@@ -307,84 +673,421 @@ impl Trial {
             dungeon_identifier: self.dungeon._get_zone(),
             difficulty_settings: self.difficulty_settings.clone(),
             force_minibosses: self.force_minibosses,
+            max_rounds: self.max_rounds,
+            initiative: self.initiative,
+            targeting: self.targeting,
             trial_num_minibosses: miniboss_results.len(),
-            success_rate: (all_results
+            success_rate,
+            wipe_rate: all_results
                 .iter()
-                .map(|res| res.is_success() as u32)
-                .sum::<u32>()
-                / all_results_length as u32) as f64,
-            success_rate_vs_miniboss: (miniboss_results
+                .map(|res| (res.get_outcome() == SimOutcome::Wipe) as u32 as f64)
+                .sum::<f64>()
+                / all_results_length,
+            timeout_rate: all_results
                 .iter()
-                .map(|res| res.is_success() as u32)
-                .sum::<u32>()
-                / miniboss_results_length as u32) as f64,
-            average_rounds: (all_results
+                .map(|res| (res.get_outcome() == SimOutcome::Timeout) as u32 as f64)
+                .sum::<f64>()
+                / all_results_length,
+            success_rate_vs_miniboss: miniboss_results
                 .iter()
-                .map(|res| res.get_rounds() as u32)
-                .sum::<u32>()
-                / all_results_length as u32) as f64,
-            avg_rounds_vs_miniboss: (miniboss_results
+                .map(|res| res.is_success() as u32 as f64)
+                .sum::<f64>()
+                / miniboss_results_length,
+            average_rounds: all_results
                 .iter()
-                .map(|res| res.get_rounds() as u32)
-                .sum::<u32>()
-                / miniboss_results_length as u32) as f64,
-            avg_encounter_hp_remaining: (all_results
+                .map(|res| res.get_rounds() as f64)
+                .sum::<f64>()
+                / all_results_length,
+            avg_rounds_vs_miniboss: miniboss_results
                 .iter()
-                .map(|res| res.get_encounter_hp_remaining() as u32)
-                .sum::<u32>()
-                / all_results_length as u32) as f64,
-            avg_encounter_hp_remaining_vs_miniboss: (miniboss_results
+                .map(|res| res.get_rounds() as f64)
+                .sum::<f64>()
+                / miniboss_results_length,
+            avg_encounter_hp_remaining: all_results
                 .iter()
-                .map(|res| res.get_encounter_hp_remaining() as u32)
-                .sum::<u32>()
-                / miniboss_results_length as u32)
-                as f64,
+                .map(|res| res.get_encounter_hp_remaining())
+                .sum::<f64>()
+                / all_results_length,
+            avg_encounter_hp_remaining_vs_miniboss: miniboss_results
+                .iter()
+                .map(|res| res.get_encounter_hp_remaining())
+                .sum::<f64>()
+                / miniboss_results_length,
+            avg_polonia_loot,
+            polonia_loot_cap_rate: all_results
+                .iter()
+                .map(|res| res.is_polonia_loot_cap_hit() as u32 as f64)
+                .sum::<f64>()
+                / all_results_length,
+            entry_cost_resource: self.dungeon.get_entry_cost_resource().unwrap_or_default(),
+            avg_entry_cost,
+            expected_cost_per_success: if success_rate > 0.0 {
+                avg_entry_cost / success_rate
+            } else if avg_entry_cost > 0.0 {
+                f64::INFINITY
+            } else {
+                0.0
+            },
+            reward_quest: reward_quest.map_or(String::new(), |quest| quest.get_identifier()),
+            avg_cycle_minutes: reward_quest.map_or(0.0, |quest| {
+                quest.calculate_cycle_minutes(injury_rate, rest_time_percent)
+            }),
+            rest_time_percent,
+            quests_per_day: reward_quest.map_or(0.0, |quest| {
+                quest.calculate_quests_per_day(injury_rate, rest_time_percent)
+            }),
+            xp_per_quest: reward_quest.map_or(0.0, |quest| {
+                quest.calculate_xp_per_quest(success_rate, xp_percent)
+            }),
+            xp_per_hour,
+            gold_per_hour,
+            loot_per_hour,
+            metric_intervals,
 
             hero_names,
             hero_survival_rate,
             hero_avg_hp_remaining,
             hero_avg_dmg,
+            hero_avg_healing,
+            hero_xp_per_quest,
 
             hero_avg_dodge_rate,
             hero_avg_atk_hit_rate,
             hero_avg_crit_dealt_rate,
             hero_avg_crit_taken_rate,
+            team_avg_hp_lost: all_results
+                .iter()
+                .map(|res| res.get_team_hp_lost().iter().sum::<f64>())
+                .sum::<f64>()
+                / all_results_length,
+
+            build_signature: self.team.get_build_signature(),
+            build_hash: self.team.get_build_hash(),
+            data_version: stable_hash(&(
+                &self.dungeon,
+                self.team.get_hero_data_hash(),
+                &self.reward_quest,
+            )),
+            rng: self.rng.describe(),
         };
 
         return trial_result;
     }
+}
 
-    pub fn save_trial_result_to_csv(&self, string_path: String) -> Result<(), std::io::Error> {
-        let path = std::path::Path::new(&string_path);
-        let path_exists = path.exists();
+/// The names difficulties 1-8 are written as in trial results
+pub const DIFFICULTY_NAMES: [&str; 8] = [
+    "Easy",
+    "Medium",
+    "Hard",
+    "Extreme",
+    "Boss Easy",
+    "Boss Medium",
+    "Boss Hard",
+    "Boss Extreme",
+];
+
+/// The number of trials whose results are held in memory before being written
+pub const RESULT_BATCH_SIZE: usize = 25;
+
+/// Buffers the results of trials and writes them in batches, so writing files isn't interleaved with running every trial
+/// Results still buffered when the last trial is added are only written by flush, which must be called once all trials have run
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ResultWriter {
+    result_format: ResultFormat,
+    results_path: String,
+    raw_results_path: Option<String>, // When set, one row per simulation is also written to this gzip compressed csv
+    msgpack_paths: Option<MessagePackPaths>, // When set, the same records are also written as MessagePack
+    dataset_path: Option<String>, // A long-lived csv that results are appended to, skipping results already present
+    dataset_keys: Option<HashSet<Vec<String>>>, // The keys of the results in the dataset, read once when the first result is added
+    wide_records: Vec<TrialResultCSVRecord>,
+    long_records: Vec<TrialResultLongCSVRecord>,
+    #[serde(default)]
+    json_records: Vec<TrialResultJsonRecord>,
+    raw_records: Vec<TrialCSVRecord>,
+    dataset_records: Vec<TrialResultCSVRecord>,
+    buffered_trial_qty: usize,
+}
 
-        let file = std::fs::OpenOptions::new()
-            .write(true)
-            .create(true)
-            .append(true)
-            .open(path)
-            .unwrap();
+/// The files results are also written to as MessagePack, one map per record keyed by the csv's column names
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MessagePackPaths {
+    pub results_path: String,
+    pub raw_results_path: Option<String>, // Only set when raw results are saved
+}
+
+pub fn create_result_writer(
+    result_format: ResultFormat,
+    results_path: String,
+    raw_results_path: Option<String>,
+    msgpack_paths: Option<MessagePackPaths>,
+    dataset_path: Option<String>,
+) -> ResultWriter {
+    return ResultWriter {
+        result_format,
+        results_path,
+        raw_results_path,
+        msgpack_paths,
+        dataset_path,
+        dataset_keys: None,
+        wide_records: vec![],
+        long_records: vec![],
+        json_records: vec![],
+        raw_records: vec![],
+        dataset_records: vec![],
+        buffered_trial_qty: 0,
+    };
+}
+
+impl ResultWriter {
+    /// Buffer the results of a trial, writing every buffered result once a full batch has been added
+    pub fn add_trial(
+        &mut self,
+        trial: &Trial,
+        trial_result: &TrialResult,
+    ) -> Result<(), std::io::Error> {
+        match self.result_format {
+            ResultFormat::Wide => {
+                self.wide_records
+                    .push(create_trial_result_csv_record_from_trial_result(
+                        trial_result.clone(),
+                    ))
+            }
+            ResultFormat::Long => {
+                self.long_records
+                    .extend(create_trial_result_long_csv_records_from_trial_result(
+                        trial_result.clone(),
+                    ))
+            }
+            ResultFormat::Json => self.json_records.push(TrialResultJsonRecord {
+                result: trial_result.clone(),
+                success_rate_std_error: trial_result.get_success_rate_std_error(),
+                success_rate_wilson_interval: trial_result.get_success_rate_interval(),
+                heroes: trial.create_hero_detail_records(),
+            }),
+        }
+        if self.raw_results_path.is_some() {
+            self.raw_records.extend(trial.create_raw_result_records());
+        }
+        if let Some(dataset_path) = self.dataset_path.clone() {
+            let record = create_trial_result_csv_record_from_trial_result(trial_result.clone());
+            if self.dataset_keys.is_none() {
+                self.dataset_keys = Some(read_dataset_keys(&dataset_path)?);
+            }
+            if self
+                .dataset_keys
+                .as_mut()
+                .unwrap()
+                .insert(record.get_dataset_key())
+            {
+                self.dataset_records.push(record);
+            } else {
+                info!(
+                    "Result already present in dataset {}, skipped",
+                    dataset_path
+                );
+            }
+        }
 
-        let mut wtr: csv::Writer<std::fs::File>;
-        if path_exists {
-            wtr = csv::WriterBuilder::new()
-                .has_headers(false)
-                .from_writer(file);
-        } else {
-            wtr = csv::WriterBuilder::new()
-                .has_headers(true)
-                .from_writer(file);
+        self.buffered_trial_qty += 1;
+        if self.buffered_trial_qty >= RESULT_BATCH_SIZE {
+            self.flush()?;
         }
+        return Ok(());
+    }
 
-        let trial_result = self.create_trial_result();
+    /// Write every buffered result
+    pub fn flush(&mut self) -> Result<(), std::io::Error> {
+        let msgpack_paths = self.msgpack_paths.as_ref();
+        if !self.wide_records.is_empty() {
+            append_records_to_csv(&self.results_path, &self.wide_records)?;
+            if let Some(msgpack_paths) = msgpack_paths {
+                append_records_to_msgpack(&msgpack_paths.results_path, &self.wide_records)?;
+            }
+            self.wide_records.clear();
+        }
+        if !self.long_records.is_empty() {
+            append_records_to_csv(&self.results_path, &self.long_records)?;
+            if let Some(msgpack_paths) = msgpack_paths {
+                append_records_to_msgpack(&msgpack_paths.results_path, &self.long_records)?;
+            }
+            self.long_records.clear();
+        }
+        if !self.json_records.is_empty() {
+            append_records_to_json_lines(&self.results_path, &self.json_records)?;
+            if let Some(msgpack_paths) = msgpack_paths {
+                append_records_to_msgpack(&msgpack_paths.results_path, &self.json_records)?;
+            }
+            self.json_records.clear();
+        }
+        if let Some(raw_results_path) = &self.raw_results_path {
+            if !self.raw_records.is_empty() {
+                append_records_to_csv_gz(raw_results_path, &self.raw_records)?;
+                if let Some(path) = msgpack_paths.and_then(|paths| paths.raw_results_path.as_ref())
+                {
+                    append_records_to_msgpack(path, &self.raw_records)?;
+                }
+                self.raw_records.clear();
+            }
+        }
+        if let Some(dataset_path) = &self.dataset_path {
+            if !self.dataset_records.is_empty() {
+                append_records_to_csv(dataset_path, &self.dataset_records)?;
+                self.dataset_records.clear();
+            }
+        }
+        self.buffered_trial_qty = 0;
+        return Ok(());
+    }
+}
 
-        let record = create_trial_result_csv_record_from_trial_result(trial_result);
+/// Read the dataset key of every result in a dataset, returning no keys if the dataset doesn't exist yet
+fn read_dataset_keys(string_path: &str) -> Result<HashSet<Vec<String>>, std::io::Error> {
+    let mut keys: HashSet<Vec<String>> = Default::default();
+    let path = std::path::Path::new(string_path);
+    if !path.exists() {
+        return Ok(keys);
+    }
 
-        wtr.serialize(record.round_floats_for_display())?;
+    // Columns are looked up by name so datasets written before columns were added can still be checked
+    let mut rdr = csv::Reader::from_path(path)?;
+    let headers = rdr.headers()?.clone();
+    let key_indices: Vec<Option<usize>> = DATASET_KEY_COLUMNS
+        .iter()
+        .map(|column| headers.iter().position(|header| header == *column))
+        .collect();
+    for existing in rdr.records() {
+        let existing = existing?;
+        keys.insert(
+            key_indices
+                .iter()
+                .map(|index| {
+                    index
+                        .and_then(|i| existing.get(i))
+                        .unwrap_or("")
+                        .to_string()
+                })
+                .collect(),
+        );
+    }
+    return Ok(keys);
+}
 
-        wtr.flush()?;
-        return Ok(());
+/// Append records to a csv, writing the header only if the file is new
+/// Fails without writing if the file already has a different header, such as a dataset written by an older version, rather than mixing columns
+fn append_records_to_csv<T: Serialize>(
+    string_path: &str,
+    records: &[T],
+) -> Result<(), std::io::Error> {
+    let path = std::path::Path::new(string_path);
+    let path_exists = path.exists() && std::fs::metadata(path)?.len() > 0;
+    if path_exists && !records.is_empty() {
+        let existing_header = csv::Reader::from_path(path)?.headers()?.clone();
+        let header = get_csv_header(&records[0])?;
+        if existing_header != header {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                f!(
+                    "{} has columns that differ from the results being appended, move it aside or choose another path to start a new file",
+                    string_path
+                ),
+            ));
+        }
     }
+
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    let mut wtr = csv::WriterBuilder::new()
+        .has_headers(!path_exists)
+        .from_writer(file);
+
+    for record in records {
+        wtr.serialize(record)?;
+    }
+
+    wtr.flush()?;
+    return Ok(());
+}
+
+/// Returns the csv header a record is written with
+fn get_csv_header<T: Serialize>(record: &T) -> Result<csv::StringRecord, std::io::Error> {
+    let mut wtr = csv::Writer::from_writer(vec![]);
+    wtr.serialize(record)?;
+    let bytes = wtr.into_inner().map_err(|e| e.into_error())?;
+    return Ok(csv::Reader::from_reader(bytes.as_slice())
+        .headers()?
+        .clone());
+}
+
+/// Append records to a gzip compressed csv, writing the header only if the file is new
+/// Each call appends a new gzip member, which gzip tools and flate2's MultiGzDecoder read back as a single csv
+fn append_records_to_csv_gz<T: Serialize>(
+    string_path: &str,
+    records: &[T],
+) -> Result<(), std::io::Error> {
+    let path = std::path::Path::new(string_path);
+    let path_exists = path.exists();
+
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    let encoder = GzEncoder::new(file, Compression::default());
+    let mut wtr = csv::WriterBuilder::new()
+        .has_headers(!path_exists)
+        .from_writer(encoder);
+
+    for record in records {
+        wtr.serialize(record)?;
+    }
+
+    wtr.into_inner().map_err(|e| e.into_error())?.finish()?;
+    return Ok(());
+}
+
+/// Append records to a file as a stream of MessagePack maps, which can be read back one value at a time
+/// Returns where save_trace saves the MessagePack copy of a trace csv, the same path with a .msgpack extension
+pub fn get_trace_msgpack_path(path: &str) -> String {
+    return std::path::Path::new(path)
+        .with_extension("msgpack")
+        .to_string_lossy()
+        .to_string();
+}
+
+fn append_records_to_msgpack<T: Serialize>(
+    string_path: &str,
+    records: &[T],
+) -> Result<(), std::io::Error> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(string_path)?;
+    let mut wtr = std::io::BufWriter::new(file);
+    for record in records {
+        rmp_serde::encode::write_named(&mut wtr, record).map_err(std::io::Error::other)?;
+    }
+    std::io::Write::flush(&mut wtr)?;
+    return Ok(());
+}
+
+/// Append records to a file as json lines, one json object per record
+fn append_records_to_json_lines<T: Serialize>(
+    string_path: &str,
+    records: &[T],
+) -> Result<(), std::io::Error> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(string_path)?;
+    let mut wtr = std::io::BufWriter::new(file);
+    for record in records {
+        serde_json::to_writer(&mut wtr, record)?;
+        std::io::Write::write_all(&mut wtr, b"\n")?;
+    }
+    std::io::Write::flush(&mut wtr)?;
+    return Ok(());
 }
 
 /// Create a trial performing type validation and calculating certain fields
@@ -396,11 +1099,17 @@ pub fn create_trial(
     dungeon: Dungeon,
     difficulty_settings: Vec<usize>,
     force_minibosses: Option<bool>,
+    max_rounds: i16,
+    initiative: InitiativeModel,
+    targeting: TargetingModel,
     log_all: bool,
 ) -> Result<Trial, &'static str> {
     if simulation_qty < 1 {
         return Err("simulation_qty must be > 0");
     }
+    if !(1..=MAX_ROUND_LIMIT).contains(&max_rounds) {
+        return Err("max_rounds must be within range 1-1000 inclusive");
+    }
 
     let trial = Trial {
         identifier,
@@ -410,13 +1119,30 @@ pub fn create_trial(
         dungeon,
         difficulty_settings,
         force_minibosses,
+        max_rounds,
+        initiative,
+        targeting,
         results: Vec::with_capacity(simulation_qty),
         log_all,
+        trace: false,
+        reward_quest: None,
+        statistics: StatisticsConfig::default(),
+        rng: RngConfig::default(),
     };
 
     return Ok(trial);
 }
 
+/// Bootstrapped (lower, upper) confidence intervals of a trial's headline metrics
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct MetricIntervals {
+    pub success_rate: (f64, f64),
+    pub average_rounds: (f64, f64),
+    pub xp_per_hour: (f64, f64),
+    pub gold_per_hour: (f64, f64),
+    pub loot_per_hour: (f64, f64),
+}
+
 /// The result of a trial
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct TrialResult {
@@ -426,23 +1152,229 @@ pub struct TrialResult {
     dungeon_identifier: String,
     difficulty_settings: Vec<usize>,
     force_minibosses: Option<bool>,
+    max_rounds: i16,
+    initiative: InitiativeModel,
+    #[serde(default)]
+    targeting: TargetingModel,
     trial_num_minibosses: usize,
     success_rate: f64,
+    wipe_rate: f64,    // Fraction of simulations lost because no heroes remained alive
+    timeout_rate: f64, // Fraction of simulations lost because the fight lasted longer than max_rounds
     success_rate_vs_miniboss: f64,
     average_rounds: f64,
     avg_rounds_vs_miniboss: f64,
     avg_encounter_hp_remaining: f64,
     avg_encounter_hp_remaining_vs_miniboss: f64,
+    avg_polonia_loot: f64, // The extra loot Polonia is expected to steal per run, counting lost runs as no loot
+    polonia_loot_cap_rate: f64, // Fraction of simulations where Polonia stole as much loot as she can in one fight
+    entry_cost_resource: String, // What is spent to enter the dungeon, blank if entry is free
+    avg_entry_cost: f64,        // The cost of one run, averaged over the difficulties run
+    expected_cost_per_success: f64, // The cost spent on average for each successful run, infinite if no run succeeded
+    reward_quest: String, // The quest matching the trial's dungeon and difficulty that rewards are taken from, blank if none matched
+    avg_cycle_minutes: f64, // The quest's duration plus the party's expected rest time
+    #[serde(default)]
+    rest_time_percent: f64, // The fraction the party's rest time is reduced by, that of its slowest resting hero
+    #[serde(default)]
+    quests_per_day: f64, // Expected runs per day of repeatedly running the reward quest, 0 if none matched
+    #[serde(default)]
+    xp_per_quest: f64, // Expected xp each hero earns per run of the reward quest with the party's average xp bonus, counting lost runs as none
+    xp_per_hour: f64, // Expected rewards per hour of repeatedly running the reward quest, 0 if none matched
+    gold_per_hour: f64,
+    loot_per_hour: f64, // Includes any loot stolen by Polonia
+    metric_intervals: Option<MetricIntervals>, // None unless bootstrapping is configured
 
     hero_names: Vec<String>,
     hero_survival_rate: [f64; 5],
     hero_avg_hp_remaining: [f64; 5],
     hero_avg_dmg: [f64; 5],
+    hero_avg_healing: [f64; 5], // Hp restored to the team by each hero
+    #[serde(default)]
+    hero_xp_per_quest: [f64; 5], // Expected xp each hero earns per run of the reward quest with its own xp bonus
 
     hero_avg_dodge_rate: [f64; 5],
     hero_avg_atk_hit_rate: [f64; 5],
     hero_avg_crit_dealt_rate: [f64; 5],
     hero_avg_crit_taken_rate: [f64; 5],
+    #[serde(default)]
+    team_avg_hp_lost: f64, // The hp lost by the whole team per simulation, summed over its heroes
+
+    build_signature: String, // A human-readable class|gear|qualities|sockets|skills signature of each hero in the team
+    build_hash: String,      // Identifies the team's builds independent of hero identifiers
+    data_version: String, // Identifies the dungeon, hero, class, and quest data the trial was run against, so results are invalidated when that data changes
+    rng: String,          // The generator and seed, if any, the simulations drew from
+}
+
+impl TrialResult {
+    pub fn get_trial_description(&self) -> String {
+        return self.trial_description.to_string();
+    }
+    pub fn get_build_signature(&self) -> String {
+        return self.build_signature.to_string();
+    }
+    pub fn get_success_rate(&self) -> f64 {
+        return self.success_rate;
+    }
+    pub fn get_simulation_qty(&self) -> usize {
+        return self.trial_simulation_qty;
+    }
+    /// Returns the 95% Wilson score interval of the success rate
+    pub fn get_success_rate_interval(&self) -> (f64, f64) {
+        return wilson_interval(self.success_rate, self.trial_simulation_qty);
+    }
+    pub fn get_success_rate_std_error(&self) -> f64 {
+        return proportion_standard_error(self.success_rate, self.trial_simulation_qty);
+    }
+    pub fn get_wipe_rate(&self) -> f64 {
+        return self.wipe_rate;
+    }
+    pub fn get_timeout_rate(&self) -> f64 {
+        return self.timeout_rate;
+    }
+    pub fn get_average_rounds(&self) -> f64 {
+        return self.average_rounds;
+    }
+    pub fn get_avg_polonia_loot(&self) -> f64 {
+        return self.avg_polonia_loot;
+    }
+    pub fn get_expected_cost_per_success(&self) -> f64 {
+        return self.expected_cost_per_success;
+    }
+    pub fn get_loot_per_hour(&self) -> f64 {
+        return self.loot_per_hour;
+    }
+    pub fn get_hero_avg_hp_remaining(&self, hero_index: usize) -> f64 {
+        return self.hero_avg_hp_remaining[hero_index];
+    }
+    pub fn get_hero_survival_rate(&self, hero_index: usize) -> f64 {
+        return self.hero_survival_rate[hero_index];
+    }
+    pub fn get_hero_avg_dmg(&self, hero_index: usize) -> f64 {
+        return self.hero_avg_dmg[hero_index];
+    }
+    pub fn get_hero_avg_healing(&self, hero_index: usize) -> f64 {
+        return self.hero_avg_healing[hero_index];
+    }
+    pub fn get_hero_names(&self) -> Vec<String> {
+        return self.hero_names.clone();
+    }
+    pub fn get_team_avg_hp_lost(&self) -> f64 {
+        return self.team_avg_hp_lost;
+    }
+    /// Returns the number of heroes expected to survive a simulation
+    pub fn get_avg_survivors(&self) -> f64 {
+        return self.hero_survival_rate.iter().sum();
+    }
+    /// Returns a view of the headline metrics, and the survival rate and average damage of each hero
+    pub fn fmt_stats(&self) -> StatsView {
+        let mut view = create_stats_view(f!(
+            "{} vs {}",
+            self.trial_description,
+            self.describe_dungeon()
+        ))
+        .text("Simulations", self.trial_simulation_qty)
+        .float("Success Rate", self.success_rate)
+        .text("Success Rate 95% CI", {
+            let (lower, upper) = self.get_success_rate_interval();
+            f!(
+                "{} - {} (standard error {})",
+                fmt_f64(lower),
+                fmt_f64(upper),
+                fmt_f64(self.get_success_rate_std_error())
+            )
+        })
+        .float("Wipe Rate", self.wipe_rate)
+        .float("Timeout Rate", self.timeout_rate)
+        .float("Average Rounds", self.average_rounds)
+        .float("Avg Polonia Loot", self.avg_polonia_loot);
+        for (i, hero_name) in self.hero_names.iter().enumerate().take(5) {
+            view = view.text(
+                hero_name,
+                f!(
+                    "survival rate {}, avg damage {}",
+                    fmt_f64(self.hero_survival_rate[i]),
+                    fmt_f64(self.hero_avg_dmg[i])
+                ),
+            );
+        }
+        return view;
+    }
+    /// Returns the dungeon and the names of the difficulties it was run at, such as "Bleakspire Peak (Legendary)"
+    pub fn describe_dungeon(&self) -> String {
+        return f!(
+            "{} ({})",
+            self.dungeon_identifier,
+            self.difficulty_settings
+                .iter()
+                .map(|difficulty| DIFFICULTY_NAMES[difficulty - 1])
+                .collect::<Vec<&str>>()
+                .join(", ")
+        );
+    }
+}
+
+/// Defines the shape trial results are saved in
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, strum::Display, strum::EnumString,
+)]
+pub enum ResultFormat {
+    Wide, // One row per trial with a column for each summary metric
+    Long, // One row per trial per metric, with hero metrics tagged by hero identifier
+    Json, // One json object per trial with every field of the trial result, plus each hero's stats averaged over the simulations
+}
+
+/// The columns that identify a unique result in a cumulative results dataset
+const DATASET_KEY_COLUMNS: [&str; 8] = [
+    "build_hash",
+    "dungeon_identifier",
+    "difficulty_settings",
+    "force_minibosses",
+    "max_rounds",
+    "initiative",
+    "targeting",
+    "data_version",
+];
+
+/// A full trial result with the stats of each hero, used for the json result format
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct TrialResultJsonRecord {
+    #[serde(flatten)]
+    result: TrialResult,
+    success_rate_std_error: f64,
+    success_rate_wilson_interval: (f64, f64),
+    heroes: Vec<HeroDetailRecord>,
+}
+
+/// A hero's stats in a trial, each averaged over every simulation
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct HeroDetailRecord {
+    hero_identifier: String,
+    survival_rate: f64,
+    avg_hp_remaining: f64,
+    avg_dmg_dealt: f64,
+    avg_dmg_taken: f64, // The hp lost by the end of the fight, net of any healing received
+    avg_healing_done: f64,
+    avg_crits_dealt: f64,
+    avg_crits_taken: f64,
+    avg_dodges: f64,
+    avg_attacks_missed: f64,
+    avg_rounds_survived: f64, // The rounds the hero was still standing at the end of, the whole fight if they survived
+    avg_procs: f64, // Fatal blows survived, lord saves, and guaranteed crits gained from evading
+}
+
+/// A single metric of a trial result, used for the long result format
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct TrialResultLongCSVRecord {
+    trial_identifier: String,
+    trial_description: String,
+    build_signature: String,
+    build_hash: String,
+    dungeon_identifier: String,
+    data_version: String,
+    rng: String,
+    hero_identifier: String, // Blank for metrics describing the whole trial
+    metric: String,
+    #[serde(serialize_with = "serialize_rounded")]
+    value: f64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -450,142 +1382,206 @@ struct TrialResultCSVRecord {
     trial_identifier: String,
     trial_description: String,
     trial_simulation_qty: usize,
+    build_signature: String,
+    build_hash: String,
     dungeon_identifier: String,
     difficulty_settings: String,
     force_minibosses: String,
+    max_rounds: i16,
+    initiative: String,
+    targeting: String,
+    data_version: String,
+    rng: String,
     trial_num_minibosses: usize,
+    #[serde(serialize_with = "serialize_rounded")]
     success_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    success_rate_std_error: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    success_rate_wilson_lower: f64, // The 95% Wilson score interval of the success rate, always written unlike the bootstrapped intervals
+    #[serde(serialize_with = "serialize_rounded")]
+    success_rate_wilson_upper: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    wipe_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    timeout_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     success_rate_vs_miniboss: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     average_rounds: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     avg_rounds_vs_miniboss: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     avg_encounter_hp_remaining: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     avg_encounter_hp_remaining_vs_miniboss: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    avg_polonia_loot: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    polonia_loot_cap_rate: f64,
+    entry_cost_resource: String,
+    #[serde(serialize_with = "serialize_rounded")]
+    avg_entry_cost: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    expected_cost_per_success: f64,
+    reward_quest: String,
+    #[serde(serialize_with = "serialize_rounded")]
+    avg_cycle_minutes: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    rest_time_percent: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    quests_per_day: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    xp_per_quest: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    xp_per_hour: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    gold_per_hour: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    loot_per_hour: f64,
+    #[serde(serialize_with = "serialize_rounded_option")]
+    success_rate_ci_lower: Option<f64>, // Bootstrapped confidence intervals, blank unless bootstrapping is configured
+    #[serde(serialize_with = "serialize_rounded_option")]
+    success_rate_ci_upper: Option<f64>,
+    #[serde(serialize_with = "serialize_rounded_option")]
+    average_rounds_ci_lower: Option<f64>,
+    #[serde(serialize_with = "serialize_rounded_option")]
+    average_rounds_ci_upper: Option<f64>,
+    #[serde(serialize_with = "serialize_rounded_option")]
+    xp_per_hour_ci_lower: Option<f64>,
+    #[serde(serialize_with = "serialize_rounded_option")]
+    xp_per_hour_ci_upper: Option<f64>,
+    #[serde(serialize_with = "serialize_rounded_option")]
+    gold_per_hour_ci_lower: Option<f64>,
+    #[serde(serialize_with = "serialize_rounded_option")]
+    gold_per_hour_ci_upper: Option<f64>,
+    #[serde(serialize_with = "serialize_rounded_option")]
+    loot_per_hour_ci_lower: Option<f64>,
+    #[serde(serialize_with = "serialize_rounded_option")]
+    loot_per_hour_ci_upper: Option<f64>,
 
     hero_1_identifier: String,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_1_survival_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_1_avg_hp_remaining: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_1_avg_dmg: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    hero_1_avg_healing: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_1_avg_dodge_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_1_avg_atk_hit_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_1_avg_crit_dealt_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_1_avg_crit_taken_rate: f64,
 
     hero_2_identifier: String,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_2_survival_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_2_avg_hp_remaining: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_2_avg_dmg: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    hero_2_avg_healing: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_2_avg_dodge_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_2_avg_atk_hit_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_2_avg_crit_dealt_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_2_avg_crit_taken_rate: f64,
 
     hero_3_identifier: String,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_3_survival_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_3_avg_hp_remaining: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_3_avg_dmg: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    hero_3_avg_healing: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_3_avg_dodge_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_3_avg_atk_hit_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_3_avg_crit_dealt_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_3_avg_crit_taken_rate: f64,
 
     hero_4_identifier: String,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_4_survival_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_4_avg_hp_remaining: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_4_avg_dmg: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    hero_4_avg_healing: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_4_avg_dodge_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_4_avg_atk_hit_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_4_avg_crit_dealt_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_4_avg_crit_taken_rate: f64,
 
     hero_5_identifier: String,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_5_survival_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_5_avg_hp_remaining: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_5_avg_dmg: f64,
+    #[serde(serialize_with = "serialize_rounded")]
+    hero_5_avg_healing: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_5_avg_dodge_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_5_avg_atk_hit_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_5_avg_crit_dealt_rate: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     hero_5_avg_crit_taken_rate: f64,
 }
 
+/// Create a trial csv record performing type validation and calculating certain fields
 impl TrialResultCSVRecord {
-    pub fn round_floats_for_display(&self) -> TrialResultCSVRecord {
-        let mut tcr2 = self.clone();
-
-        tcr2.success_rate = round_to_2(tcr2.success_rate);
-        tcr2.success_rate_vs_miniboss = round_to_2(tcr2.success_rate_vs_miniboss);
-        tcr2.average_rounds = round_to_2(tcr2.average_rounds);
-        tcr2.avg_rounds_vs_miniboss = round_to_2(tcr2.avg_rounds_vs_miniboss);
-        tcr2.avg_encounter_hp_remaining = round_to_2(tcr2.avg_encounter_hp_remaining);
-        tcr2.avg_encounter_hp_remaining_vs_miniboss =
-            round_to_2(tcr2.avg_encounter_hp_remaining_vs_miniboss);
-
-        tcr2.hero_1_survival_rate = round_to_2(tcr2.hero_1_survival_rate);
-        tcr2.hero_1_avg_hp_remaining = round_to_2(tcr2.hero_1_avg_hp_remaining);
-        tcr2.hero_1_avg_dmg = round_to_2(tcr2.hero_1_avg_dmg);
-        tcr2.hero_1_avg_dodge_rate = round_to_2(tcr2.hero_1_avg_dodge_rate);
-        tcr2.hero_1_avg_atk_hit_rate = round_to_2(tcr2.hero_1_avg_atk_hit_rate);
-        tcr2.hero_1_avg_crit_dealt_rate = round_to_2(tcr2.hero_1_avg_crit_dealt_rate);
-        tcr2.hero_1_avg_crit_taken_rate = round_to_2(tcr2.hero_1_avg_crit_taken_rate);
-
-        tcr2.hero_2_survival_rate = round_to_2(tcr2.hero_2_survival_rate);
-        tcr2.hero_2_avg_hp_remaining = round_to_2(tcr2.hero_2_avg_hp_remaining);
-        tcr2.hero_2_avg_dmg = round_to_2(tcr2.hero_2_avg_dmg);
-        tcr2.hero_2_avg_dodge_rate = round_to_2(tcr2.hero_2_avg_dodge_rate);
-        tcr2.hero_2_avg_atk_hit_rate = round_to_2(tcr2.hero_2_avg_atk_hit_rate);
-        tcr2.hero_2_avg_crit_dealt_rate = round_to_2(tcr2.hero_2_avg_crit_dealt_rate);
-        tcr2.hero_2_avg_crit_taken_rate = round_to_2(tcr2.hero_2_avg_crit_taken_rate);
-
-        tcr2.hero_3_survival_rate = round_to_2(tcr2.hero_3_survival_rate);
-        tcr2.hero_3_avg_hp_remaining = round_to_2(tcr2.hero_3_avg_hp_remaining);
-        tcr2.hero_3_avg_dmg = round_to_2(tcr2.hero_3_avg_dmg);
-        tcr2.hero_3_avg_dodge_rate = round_to_2(tcr2.hero_3_avg_dodge_rate);
-        tcr2.hero_3_avg_atk_hit_rate = round_to_2(tcr2.hero_3_avg_atk_hit_rate);
-        tcr2.hero_3_avg_crit_dealt_rate = round_to_2(tcr2.hero_3_avg_crit_dealt_rate);
-        tcr2.hero_3_avg_crit_taken_rate = round_to_2(tcr2.hero_3_avg_crit_taken_rate);
-
-        tcr2.hero_4_survival_rate = round_to_2(tcr2.hero_4_survival_rate);
-        tcr2.hero_4_avg_hp_remaining = round_to_2(tcr2.hero_4_avg_hp_remaining);
-        tcr2.hero_4_avg_dmg = round_to_2(tcr2.hero_4_avg_dmg);
-        tcr2.hero_4_avg_dodge_rate = round_to_2(tcr2.hero_4_avg_dodge_rate);
-        tcr2.hero_4_avg_atk_hit_rate = round_to_2(tcr2.hero_4_avg_atk_hit_rate);
-        tcr2.hero_4_avg_crit_dealt_rate = round_to_2(tcr2.hero_4_avg_crit_dealt_rate);
-        tcr2.hero_4_avg_crit_taken_rate = round_to_2(tcr2.hero_4_avg_crit_taken_rate);
-
-        tcr2.hero_5_survival_rate = round_to_2(tcr2.hero_5_survival_rate);
-        tcr2.hero_5_avg_hp_remaining = round_to_2(tcr2.hero_5_avg_hp_remaining);
-        tcr2.hero_5_avg_dmg = round_to_2(tcr2.hero_5_avg_dmg);
-        tcr2.hero_5_avg_dodge_rate = round_to_2(tcr2.hero_5_avg_dodge_rate);
-        tcr2.hero_5_avg_atk_hit_rate = round_to_2(tcr2.hero_5_avg_atk_hit_rate);
-        tcr2.hero_5_avg_crit_dealt_rate = round_to_2(tcr2.hero_5_avg_crit_dealt_rate);
-        tcr2.hero_5_avg_crit_taken_rate = round_to_2(tcr2.hero_5_avg_crit_taken_rate);
-
-        return tcr2;
+    /// Returns the values of the dataset key columns, in the order of DATASET_KEY_COLUMNS
+    fn get_dataset_key(&self) -> Vec<String> {
+        return vec![
+            self.build_hash.to_string(),
+            self.dungeon_identifier.to_string(),
+            self.difficulty_settings.to_string(),
+            self.force_minibosses.to_string(),
+            self.max_rounds.to_string(),
+            self.initiative.to_string(),
+            self.targeting.to_string(),
+            self.data_version.to_string(),
+        ];
     }
 }
 
-/// Create a trial csv record performing type validation and calculating certain fields
 fn create_trial_result_csv_record_from_trial_result(result: TrialResult) -> TrialResultCSVRecord {
+    let intervals = result.metric_intervals;
+    let success_rate_std_error = result.get_success_rate_std_error();
+    let (success_rate_wilson_lower, success_rate_wilson_upper) = result.get_success_rate_interval();
     let mut new_diff_settings: Vec<&str> = Default::default();
-    let diff_map: std::collections::HashMap<usize, &str> = std::collections::HashMap::from([
-        (1 as usize, "Easy"),
-        (2 as usize, "Medium"),
-        (3 as usize, "Hard"),
-        (4 as usize, "Extreme"),
-        (5 as usize, "Boss Easy"),
-        (6 as usize, "Boss Medium"),
-        (7 as usize, "Boss Hard"),
-        (8 as usize, "Boss Extreme"),
-    ]);
-
     for diff in result.difficulty_settings {
-        new_diff_settings.push(diff_map[&diff]);
+        new_diff_settings.push(DIFFICULTY_NAMES[diff - 1]);
     }
 
     let new_force_miniboss: String;
 
     match result.force_minibosses {
         Some(setting) => {
-            if setting == true {
+            if setting {
                 new_force_miniboss = String::from("Force Only Minibosses")
             } else {
                 new_force_miniboss = String::from("No Minibosses Allowed")
@@ -598,25 +1594,61 @@ fn create_trial_result_csv_record_from_trial_result(result: TrialResult) -> Tria
         trial_identifier: result.trial_identifier,
         trial_description: result.trial_description,
         trial_simulation_qty: result.trial_simulation_qty,
+        build_signature: result.build_signature,
+        build_hash: result.build_hash,
         dungeon_identifier: result.dungeon_identifier,
         difficulty_settings: format!("{:?}", new_diff_settings),
         force_minibosses: new_force_miniboss,
+        max_rounds: result.max_rounds,
+        initiative: result.initiative.to_string(),
+        targeting: result.targeting.to_string(),
+        data_version: result.data_version,
+        rng: result.rng,
         trial_num_minibosses: result.trial_num_minibosses,
         success_rate: result.success_rate,
+        success_rate_std_error,
+        success_rate_wilson_lower,
+        success_rate_wilson_upper,
+        wipe_rate: result.wipe_rate,
+        timeout_rate: result.timeout_rate,
         success_rate_vs_miniboss: result.success_rate_vs_miniboss,
         average_rounds: result.average_rounds,
         avg_rounds_vs_miniboss: result.avg_rounds_vs_miniboss,
         avg_encounter_hp_remaining: result.avg_encounter_hp_remaining,
         avg_encounter_hp_remaining_vs_miniboss: result.avg_encounter_hp_remaining_vs_miniboss,
+        avg_polonia_loot: result.avg_polonia_loot,
+        polonia_loot_cap_rate: result.polonia_loot_cap_rate,
+        entry_cost_resource: result.entry_cost_resource,
+        avg_entry_cost: result.avg_entry_cost,
+        expected_cost_per_success: result.expected_cost_per_success,
+        reward_quest: result.reward_quest,
+        avg_cycle_minutes: result.avg_cycle_minutes,
+        rest_time_percent: result.rest_time_percent,
+        quests_per_day: result.quests_per_day,
+        xp_per_quest: result.xp_per_quest,
+        xp_per_hour: result.xp_per_hour,
+        gold_per_hour: result.gold_per_hour,
+        loot_per_hour: result.loot_per_hour,
+        success_rate_ci_lower: intervals.map(|intervals| intervals.success_rate.0),
+        success_rate_ci_upper: intervals.map(|intervals| intervals.success_rate.1),
+        average_rounds_ci_lower: intervals.map(|intervals| intervals.average_rounds.0),
+        average_rounds_ci_upper: intervals.map(|intervals| intervals.average_rounds.1),
+        xp_per_hour_ci_lower: intervals.map(|intervals| intervals.xp_per_hour.0),
+        xp_per_hour_ci_upper: intervals.map(|intervals| intervals.xp_per_hour.1),
+        gold_per_hour_ci_lower: intervals.map(|intervals| intervals.gold_per_hour.0),
+        gold_per_hour_ci_upper: intervals.map(|intervals| intervals.gold_per_hour.1),
+        loot_per_hour_ci_lower: intervals.map(|intervals| intervals.loot_per_hour.0),
+        loot_per_hour_ci_upper: intervals.map(|intervals| intervals.loot_per_hour.1),
 
         hero_1_identifier: result
             .hero_names
-            .get(0)
+            .first()
             .unwrap_or(&String::from(""))
             .to_string(),
         hero_1_survival_rate: result.hero_survival_rate[0],
         hero_1_avg_hp_remaining: result.hero_avg_hp_remaining[0],
         hero_1_avg_dmg: result.hero_avg_dmg[0],
+        hero_1_avg_healing: result.hero_avg_healing[0],
         hero_1_avg_dodge_rate: result.hero_avg_dodge_rate[0],
         hero_1_avg_atk_hit_rate: result.hero_avg_atk_hit_rate[0],
         hero_1_avg_crit_dealt_rate: result.hero_avg_crit_dealt_rate[0],
@@ -630,6 +1662,7 @@ fn create_trial_result_csv_record_from_trial_result(result: TrialResult) -> Tria
         hero_2_survival_rate: result.hero_survival_rate[1],
         hero_2_avg_hp_remaining: result.hero_avg_hp_remaining[1],
         hero_2_avg_dmg: result.hero_avg_dmg[1],
+        hero_2_avg_healing: result.hero_avg_healing[1],
         hero_2_avg_dodge_rate: result.hero_avg_dodge_rate[1],
         hero_2_avg_atk_hit_rate: result.hero_avg_atk_hit_rate[1],
         hero_2_avg_crit_dealt_rate: result.hero_avg_crit_dealt_rate[1],
@@ -643,6 +1676,7 @@ fn create_trial_result_csv_record_from_trial_result(result: TrialResult) -> Tria
         hero_3_survival_rate: result.hero_survival_rate[2],
         hero_3_avg_hp_remaining: result.hero_avg_hp_remaining[2],
         hero_3_avg_dmg: result.hero_avg_dmg[2],
+        hero_3_avg_healing: result.hero_avg_healing[2],
         hero_3_avg_dodge_rate: result.hero_avg_dodge_rate[2],
         hero_3_avg_atk_hit_rate: result.hero_avg_atk_hit_rate[2],
         hero_3_avg_crit_dealt_rate: result.hero_avg_crit_dealt_rate[2],
@@ -656,6 +1690,7 @@ fn create_trial_result_csv_record_from_trial_result(result: TrialResult) -> Tria
         hero_4_survival_rate: result.hero_survival_rate[3],
         hero_4_avg_hp_remaining: result.hero_avg_hp_remaining[3],
         hero_4_avg_dmg: result.hero_avg_dmg[3],
+        hero_4_avg_healing: result.hero_avg_healing[3],
         hero_4_avg_dodge_rate: result.hero_avg_dodge_rate[3],
         hero_4_avg_atk_hit_rate: result.hero_avg_atk_hit_rate[3],
         hero_4_avg_crit_dealt_rate: result.hero_avg_crit_dealt_rate[3],
@@ -669,6 +1704,7 @@ fn create_trial_result_csv_record_from_trial_result(result: TrialResult) -> Tria
         hero_5_survival_rate: result.hero_survival_rate[4],
         hero_5_avg_hp_remaining: result.hero_avg_hp_remaining[4],
         hero_5_avg_dmg: result.hero_avg_dmg[4],
+        hero_5_avg_healing: result.hero_avg_healing[4],
         hero_5_avg_dodge_rate: result.hero_avg_dodge_rate[4],
         hero_5_avg_atk_hit_rate: result.hero_avg_atk_hit_rate[4],
         hero_5_avg_crit_dealt_rate: result.hero_avg_crit_dealt_rate[4],
@@ -677,3 +1713,179 @@ fn create_trial_result_csv_record_from_trial_result(result: TrialResult) -> Tria
 
     return t_csv_rec;
 }
+
+/// Split a trial result into one record per trial-level metric plus one record per metric of each hero
+fn create_trial_result_long_csv_records_from_trial_result(
+    result: TrialResult,
+) -> Vec<TrialResultLongCSVRecord> {
+    let (success_rate_wilson_lower, success_rate_wilson_upper) = result.get_success_rate_interval();
+    let mut metrics: Vec<(String, &str, f64)> = vec![
+        (
+            String::new(),
+            "trial_simulation_qty",
+            result.trial_simulation_qty as f64,
+        ),
+        (String::new(), "max_rounds", result.max_rounds as f64),
+        (
+            String::new(),
+            "trial_num_minibosses",
+            result.trial_num_minibosses as f64,
+        ),
+        (String::new(), "success_rate", result.success_rate),
+        (
+            String::new(),
+            "success_rate_std_error",
+            result.get_success_rate_std_error(),
+        ),
+        (
+            String::new(),
+            "success_rate_wilson_lower",
+            success_rate_wilson_lower,
+        ),
+        (
+            String::new(),
+            "success_rate_wilson_upper",
+            success_rate_wilson_upper,
+        ),
+        (String::new(), "wipe_rate", result.wipe_rate),
+        (String::new(), "timeout_rate", result.timeout_rate),
+        (
+            String::new(),
+            "success_rate_vs_miniboss",
+            result.success_rate_vs_miniboss,
+        ),
+        (String::new(), "average_rounds", result.average_rounds),
+        (
+            String::new(),
+            "avg_rounds_vs_miniboss",
+            result.avg_rounds_vs_miniboss,
+        ),
+        (
+            String::new(),
+            "avg_encounter_hp_remaining",
+            result.avg_encounter_hp_remaining,
+        ),
+        (
+            String::new(),
+            "avg_encounter_hp_remaining_vs_miniboss",
+            result.avg_encounter_hp_remaining_vs_miniboss,
+        ),
+        (String::new(), "avg_polonia_loot", result.avg_polonia_loot),
+        (
+            String::new(),
+            "polonia_loot_cap_rate",
+            result.polonia_loot_cap_rate,
+        ),
+        (String::new(), "avg_entry_cost", result.avg_entry_cost),
+        (
+            String::new(),
+            "expected_cost_per_success",
+            result.expected_cost_per_success,
+        ),
+        (String::new(), "avg_cycle_minutes", result.avg_cycle_minutes),
+        (String::new(), "rest_time_percent", result.rest_time_percent),
+        (String::new(), "quests_per_day", result.quests_per_day),
+        (String::new(), "xp_per_quest", result.xp_per_quest),
+        (String::new(), "xp_per_hour", result.xp_per_hour),
+        (String::new(), "gold_per_hour", result.gold_per_hour),
+        (String::new(), "loot_per_hour", result.loot_per_hour),
+    ];
+    if let Some(intervals) = result.metric_intervals {
+        for (lower_metric, upper_metric, (lower, upper)) in [
+            (
+                "success_rate_ci_lower",
+                "success_rate_ci_upper",
+                intervals.success_rate,
+            ),
+            (
+                "average_rounds_ci_lower",
+                "average_rounds_ci_upper",
+                intervals.average_rounds,
+            ),
+            (
+                "xp_per_hour_ci_lower",
+                "xp_per_hour_ci_upper",
+                intervals.xp_per_hour,
+            ),
+            (
+                "gold_per_hour_ci_lower",
+                "gold_per_hour_ci_upper",
+                intervals.gold_per_hour,
+            ),
+            (
+                "loot_per_hour_ci_lower",
+                "loot_per_hour_ci_upper",
+                intervals.loot_per_hour,
+            ),
+        ] {
+            metrics.extend([
+                (String::new(), lower_metric, lower),
+                (String::new(), upper_metric, upper),
+            ]);
+        }
+    }
+
+    for (i, hero_name) in result.hero_names.iter().enumerate().take(5) {
+        metrics.extend([
+            (
+                hero_name.to_string(),
+                "survival_rate",
+                result.hero_survival_rate[i],
+            ),
+            (
+                hero_name.to_string(),
+                "avg_hp_remaining",
+                result.hero_avg_hp_remaining[i],
+            ),
+            (hero_name.to_string(), "avg_dmg", result.hero_avg_dmg[i]),
+            (
+                hero_name.to_string(),
+                "avg_healing",
+                result.hero_avg_healing[i],
+            ),
+            (
+                hero_name.to_string(),
+                "xp_per_quest",
+                result.hero_xp_per_quest[i],
+            ),
+            (
+                hero_name.to_string(),
+                "avg_dodge_rate",
+                result.hero_avg_dodge_rate[i],
+            ),
+            (
+                hero_name.to_string(),
+                "avg_atk_hit_rate",
+                result.hero_avg_atk_hit_rate[i],
+            ),
+            (
+                hero_name.to_string(),
+                "avg_crit_dealt_rate",
+                result.hero_avg_crit_dealt_rate[i],
+            ),
+            (
+                hero_name.to_string(),
+                "avg_crit_taken_rate",
+                result.hero_avg_crit_taken_rate[i],
+            ),
+        ]);
+    }
+
+    return metrics
+        .into_iter()
+        .map(
+            |(hero_identifier, metric, value)| TrialResultLongCSVRecord {
+                trial_identifier: result.trial_identifier.to_string(),
+                trial_description: result.trial_description.to_string(),
+                build_signature: result.build_signature.to_string(),
+                build_hash: result.build_hash.to_string(),
+                dungeon_identifier: result.dungeon_identifier.to_string(),
+                data_version: result.data_version.to_string(),
+                rng: result.rng.to_string(),
+                hero_identifier,
+                metric: metric.to_string(),
+                value,
+            },
+        )
+        .collect();
+}
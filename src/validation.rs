@@ -0,0 +1,805 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    str::FromStr,
+};
+
+use itertools::Itertools;
+
+use crate::{
+    champions::{Champion, ChampionData},
+    dungeons::Dungeon,
+    equipment::{
+        apply_blueprint_export, apply_enchant_eligibility, load_blueprints_from_export, Blueprint,
+        EnchantEligibility,
+    },
+    hero_builder::{
+        add_missing_class_innate_skills, get_promotion_errors, Element, Hero, HeroClass,
+        SeedValues, Spirit, PET_ITEM_TYPE,
+    },
+    inputs::{
+        create_quest_from_input, create_quest_line_from_input, load_dungeon_inputs_from_csv,
+        load_hero_classes_from_yaml, BossInput, DungeonInput, HeroInput, QuestInput,
+        QuestLineInput, SimHeroInput,
+    },
+    sheet_processing::{_get_hero_equipment_data, _get_hero_skills_data, _get_innate_skills_data},
+    skills::{apply_skill_effects, HeroSkill, PetSkill, SkillEffect},
+    studies::HeroBuilderInformation,
+};
+
+/// The paths of the data sheets the hero builder depends on
+pub struct DataSheetPaths {
+    pub hero_skills: String,
+    pub innate_skills: String,
+    pub blueprints: String,
+}
+
+/// The outcome of validating a single input file or data sheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileValidation {
+    path: String,
+    entries_checked: usize,
+    errors: Vec<String>,
+}
+
+impl FileValidation {
+    pub fn is_valid(&self) -> bool {
+        return self.errors.is_empty();
+    }
+}
+
+fn create_file_validation(path: &str) -> FileValidation {
+    return FileValidation {
+        path: path.to_string(),
+        entries_checked: 0,
+        errors: vec![],
+    };
+}
+
+/// Validate the data sheets and every hero, class, dungeon, and quest file found in the input directory
+/// Input csvs are recognized by their headers, so copies such as hero_builder_old.csv are validated too
+pub fn validate_inputs(input_dir: &str, data_sheets: &DataSheetPaths) -> Vec<FileValidation> {
+    let mut reports: Vec<FileValidation> = vec![];
+
+    let mut hero_skills_report = create_file_validation(&data_sheets.hero_skills);
+    let mut hero_skills = std::fs::File::open(&data_sheets.hero_skills)
+        .map_err(|e| e.to_string())
+        .and_then(_get_hero_skills_data);
+    match &hero_skills {
+        Ok((_, hero_skill_map)) => hero_skills_report.entries_checked = hero_skill_map.len(),
+        Err(e) => hero_skills_report.errors.push(e.to_string()),
+    }
+    reports.push(hero_skills_report);
+
+    // Skills have no combat effects by default, so skill_effects.yaml is only validated if it exists
+    let skill_effects_path = f!("{}/skill_effects.yaml", input_dir);
+    if std::path::Path::new(&skill_effects_path).exists() {
+        let (skill_effects_report, skill_effects) = validate_skill_effects(
+            &skill_effects_path,
+            hero_skills
+                .as_ref()
+                .ok()
+                .map(|(_, hero_skill_map)| hero_skill_map),
+        );
+        reports.push(skill_effects_report);
+        if let (Ok((_, hero_skill_map)), Some(skill_effects)) =
+            (hero_skills.as_mut(), skill_effects)
+        {
+            apply_skill_effects(hero_skill_map, skill_effects).unwrap();
+        }
+    }
+
+    let mut innate_skills_report = create_file_validation(&data_sheets.innate_skills);
+    let innate_skills = std::fs::File::open(&data_sheets.innate_skills)
+        .map_err(|e| e.to_string())
+        .and_then(_get_innate_skills_data);
+    match &innate_skills {
+        Ok((_, _, innate_skill_map)) => {
+            innate_skills_report.entries_checked = innate_skill_map.len()
+        }
+        Err(e) => innate_skills_report.errors.push(e.to_string()),
+    }
+    reports.push(innate_skills_report);
+
+    let mut blueprints_report = create_file_validation(&data_sheets.blueprints);
+    let mut bp_map = std::fs::File::open(&data_sheets.blueprints)
+        .map_err(|e| e.to_string())
+        .and_then(_get_hero_equipment_data);
+    match &bp_map {
+        Ok(bp_map) => blueprints_report.entries_checked = bp_map.len(),
+        Err(e) => blueprints_report.errors.push(e.to_string()),
+    }
+    reports.push(blueprints_report);
+    // A blueprint export can refresh the data sheet's blueprints, so blueprints.csv is only validated if it exists
+    let blueprints_csv_path = f!("{}/blueprints.csv", input_dir);
+    if std::path::Path::new(&blueprints_csv_path).exists() {
+        let mut blueprints_csv_report = create_file_validation(&blueprints_csv_path);
+        match std::fs::File::open(&blueprints_csv_path)
+            .map_err(|e| e.to_string())
+            .and_then(load_blueprints_from_export)
+        {
+            Ok(blueprints) => {
+                blueprints_csv_report.entries_checked = blueprints.len();
+                if let Ok(bp_map) = bp_map.as_mut() {
+                    apply_blueprint_export(bp_map, blueprints);
+                }
+            }
+            Err(e) => blueprints_csv_report.errors.push(e),
+        }
+        reports.push(blueprints_csv_report);
+    }
+
+    // Every item can hold every enchantment by default, so enchant_eligibility.yaml is only validated if it exists
+    let enchant_eligibility_path = f!("{}/enchant_eligibility.yaml", input_dir);
+    if std::path::Path::new(&enchant_eligibility_path).exists() {
+        let (enchant_eligibility_report, enchant_eligibility) =
+            validate_enchant_eligibility(&enchant_eligibility_path, bp_map.as_ref().ok());
+        reports.push(enchant_eligibility_report);
+        if let Ok(bp_map) = bp_map.as_mut() {
+            apply_enchant_eligibility(bp_map, &enchant_eligibility);
+        }
+    }
+
+    let (hero_classes_report, mut hero_classes) =
+        validate_hero_classes(&f!("{}/hero_classes.yaml", input_dir));
+    reports.push(hero_classes_report);
+
+    // Pets are optional, so pet_skills.yaml is only validated if it exists
+    let pet_skills_path = f!("{}/pet_skills.yaml", input_dir);
+    let pet_skill_map = if std::path::Path::new(&pet_skills_path).exists() {
+        let (pet_skills_report, pet_skill_map) =
+            validate_pet_skills(&pet_skills_path, bp_map.as_ref().ok());
+        reports.push(pet_skills_report);
+        pet_skill_map
+    } else {
+        Some(Default::default())
+    };
+
+    // Seeds add the game's values by default, so seeds.yaml is only validated if it exists
+    let seeds_path = f!("{}/seeds.yaml", input_dir);
+    let mut seed_values = SeedValues::default();
+    if std::path::Path::new(&seeds_path).exists() {
+        let (seeds_report, valid_seed_values) = validate_seed_values(&seeds_path);
+        reports.push(seeds_report);
+        seed_values = valid_seed_values.unwrap_or_default();
+    }
+
+    let (spirits_report, spirit_map) = validate_spirits(&f!("{}/spirits.yaml", input_dir));
+    reports.push(spirits_report);
+    let (elements_report, elements) = validate_elements(&f!("{}/elements.yaml", input_dir));
+    reports.push(elements_report);
+
+    // Champions missing from champions.yaml have no effect, so it is only validated if it exists
+    // Champions that can be built are added to the classes builder heroes are validated against
+    let champions_path = f!("{}/champions.yaml", input_dir);
+    if std::path::Path::new(&champions_path).exists() {
+        let (champions_report, champion_classes) =
+            validate_champions(&champions_path, hero_classes.as_ref());
+        reports.push(champions_report);
+        if let Some(hero_classes) = hero_classes.as_mut() {
+            hero_classes.extend(champion_classes);
+        }
+    }
+
+    // Bosses are optional, so bosses.yaml is only validated if it exists
+    let bosses_path = f!("{}/bosses.yaml", input_dir);
+    let bosses = if std::path::Path::new(&bosses_path).exists() {
+        let (bosses_report, bosses) = validate_bosses(&bosses_path);
+        reports.push(bosses_report);
+        bosses
+    } else {
+        Default::default()
+    };
+
+    let (dungeons_report, mut dungeon_zones) =
+        validate_dungeons(&f!("{}/dungeons.yaml", input_dir), &bosses);
+    reports.push(dungeons_report);
+    // Dungeons can also be given as a csv, so dungeons.csv is only validated if it exists
+    let dungeons_csv_path = f!("{}/dungeons.csv", input_dir);
+    if std::path::Path::new(&dungeons_csv_path).exists() {
+        let (dungeons_csv_report, csv_zones) =
+            validate_dungeons_csv(&dungeons_csv_path, &dungeon_zones);
+        reports.push(dungeons_csv_report);
+        dungeon_zones.extend(csv_zones);
+    }
+    let (quests_report, quest_identifiers) =
+        validate_quests(&f!("{}/quests.yaml", input_dir), &dungeon_zones);
+    reports.push(quests_report);
+    reports.push(validate_quest_lines(
+        &f!("{}/quest_lines.yaml", input_dir),
+        &quest_identifiers,
+    ));
+
+    // Builder heroes can only be validated once everything they reference has loaded
+    let hero_builder_information = match (
+        hero_skills,
+        innate_skills,
+        bp_map,
+        hero_classes,
+        pet_skill_map,
+        spirit_map,
+        elements,
+    ) {
+        (
+            Ok((hero_skill_tier_1_name_map, hero_skill_map)),
+            Ok((_, mut class_innate_skill_names_map, innate_skill_map)),
+            Ok(bp_map),
+            Some(hero_classes),
+            Some(pet_skill_map),
+            Some(spirit_map),
+            Some(elements),
+        ) => {
+            add_missing_class_innate_skills(&mut class_innate_skill_names_map, &hero_classes);
+            Some(HeroBuilderInformation {
+                bp_map,
+                hero_classes,
+                hero_skill_tier_1_name_map,
+                hero_skill_map,
+                class_innate_skill_names_map,
+                innate_skill_map,
+                pet_skill_map,
+                spirit_map,
+                elements,
+                seed_values,
+                champion_data: Default::default(),
+                reward_quests: vec![],
+                build_cache: Default::default(),
+            })
+        }
+        _ => None,
+    };
+
+    let mut csv_paths: Vec<String> = match std::fs::read_dir(input_dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "csv"))
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|name| name != "dungeons.csv" && name != "blueprints.csv")
+            })
+            .map(|path| path.to_string_lossy().to_string())
+            .collect(),
+        Err(_) => vec![],
+    };
+    csv_paths.sort();
+    for path in csv_paths {
+        reports.push(validate_heroes_csv(
+            &path,
+            hero_builder_information.as_ref(),
+        ));
+    }
+
+    return reports;
+}
+
+fn validate_hero_classes(path: &str) -> (FileValidation, Option<HashMap<String, HeroClass>>) {
+    let mut report = create_file_validation(path);
+    let hero_classes = match std::fs::File::open(path)
+        .map_err(|e| e.to_string())
+        .and_then(load_hero_classes_from_yaml)
+    {
+        Ok(hero_classes) => hero_classes,
+        Err(e) => {
+            report.errors.push(e);
+            return (report, None);
+        }
+    };
+    for (_, hero_class) in hero_classes
+        .iter()
+        .sorted_by_key(|(class_name, _)| *class_name)
+    {
+        report.errors.extend(hero_class.get_validation_errors());
+    }
+    report.errors.extend(get_promotion_errors(&hero_classes));
+    report.entries_checked = hero_classes.len();
+    if !report.is_valid() {
+        return (report, None);
+    }
+    return (report, Some(hero_classes));
+}
+
+/// Pet skills must be keyed by the name of a familiar blueprint, which can only be checked if the blueprints loaded
+/// Returns the report and the eligibility of every key that names a blueprint or an item type
+fn validate_enchant_eligibility(
+    path: &str,
+    bp_map: Option<&HashMap<String, Blueprint>>,
+) -> (FileValidation, HashMap<String, EnchantEligibility>) {
+    let mut report = create_file_validation(path);
+    let eligibility = match read_yaml::<EnchantEligibility>(path) {
+        Ok(eligibility) => eligibility,
+        Err(e) => {
+            report.errors.push(e);
+            return (report, Default::default());
+        }
+    };
+    let mut valid_eligibility: HashMap<String, EnchantEligibility> = Default::default();
+    for (key, item_eligibility) in eligibility {
+        report.entries_checked += 1;
+        let is_known = bp_map.is_none_or(|bp_map| {
+            bp_map.contains_key(&key)
+                || bp_map.values().any(|blueprint| blueprint.get_type() == key)
+        });
+        if is_known {
+            valid_eligibility.insert(key, item_eligibility);
+        } else {
+            report.errors.push(f!(
+                "{}: no blueprint or item type with this name was found",
+                key
+            ));
+        }
+    }
+    return (report, valid_eligibility);
+}
+
+fn validate_skill_effects(
+    path: &str,
+    hero_skill_map: Option<&HashMap<String, HeroSkill>>,
+) -> (FileValidation, Option<HashMap<String, Vec<SkillEffect>>>) {
+    let mut report = create_file_validation(path);
+    let skill_effects = match read_yaml::<Vec<SkillEffect>>(path) {
+        Ok(skill_effects) => skill_effects,
+        Err(e) => {
+            report.errors.push(e);
+            return (report, None);
+        }
+    };
+    for (skill, effects) in skill_effects.iter() {
+        report.entries_checked += 1;
+        for e in effects
+            .iter()
+            .flat_map(|effect| effect.get_validation_errors())
+        {
+            report.errors.push(f!("{}: {}", skill, e));
+        }
+        if hero_skill_map.is_some_and(|hero_skill_map| !hero_skill_map.contains_key(skill)) {
+            report
+                .errors
+                .push(f!("{}: no hero skill with this name was found", skill));
+        }
+    }
+    if !report.is_valid() {
+        return (report, None);
+    }
+    return (report, Some(skill_effects.into_iter().collect()));
+}
+
+fn validate_pet_skills(
+    path: &str,
+    bp_map: Option<&HashMap<String, Blueprint>>,
+) -> (FileValidation, Option<HashMap<String, PetSkill>>) {
+    let mut report = create_file_validation(path);
+    let pet_skills = match read_yaml::<PetSkill>(path) {
+        Ok(pet_skills) => pet_skills,
+        Err(e) => {
+            report.errors.push(e);
+            return (report, None);
+        }
+    };
+    for (pet, pet_skill) in pet_skills.iter() {
+        report.entries_checked += 1;
+        for e in pet_skill.get_validation_errors() {
+            report.errors.push(f!("{}: {}", pet, e));
+        }
+        match bp_map.map(|bp_map| bp_map.get(pet)) {
+            Some(None) => report
+                .errors
+                .push(f!("{}: no blueprint with this name was found", pet)),
+            Some(Some(blueprint)) if blueprint.get_type() != PET_ITEM_TYPE => {
+                report.errors.push(f!(
+                    "{}: blueprint is a {}, not a {}",
+                    pet,
+                    blueprint.get_type(),
+                    PET_ITEM_TYPE
+                ))
+            }
+            _ => (),
+        }
+    }
+    if !report.is_valid() {
+        return (report, None);
+    }
+    return (report, Some(pet_skills.into_iter().collect()));
+}
+
+fn validate_spirits(path: &str) -> (FileValidation, Option<HashMap<String, Spirit>>) {
+    let mut report = create_file_validation(path);
+    let spirits = match read_yaml::<Spirit>(path) {
+        Ok(spirits) => spirits,
+        Err(e) => {
+            report.errors.push(e);
+            return (report, None);
+        }
+    };
+    for (name, spirit) in spirits.iter() {
+        report.entries_checked += 1;
+        for e in spirit.get_validation_errors() {
+            report.errors.push(f!("{}: {}", name, e));
+        }
+    }
+    if !report.is_valid() {
+        return (report, None);
+    }
+    return (report, Some(spirits.into_iter().collect()));
+}
+
+/// Valid seed values are returned so builder heroes are checked with them
+fn validate_seed_values(path: &str) -> (FileValidation, Option<SeedValues>) {
+    let mut report = create_file_validation(path);
+    let seed_values = match std::fs::File::open(path)
+        .map_err(|e| e.to_string())
+        .and_then(|reader| {
+            serde_yaml::from_reader::<_, SeedValues>(reader).map_err(|e| e.to_string())
+        }) {
+        Ok(seed_values) => seed_values,
+        Err(e) => {
+            report.errors.push(e);
+            return (report, None);
+        }
+    };
+    report.entries_checked = 1;
+    report.errors.extend(seed_values.get_validation_errors());
+    if !report.is_valid() {
+        return (report, None);
+    }
+    return (report, Some(seed_values));
+}
+
+/// Every grade needs an element without an element type, which elements of types without their own entry fall back to
+fn validate_elements(path: &str) -> (FileValidation, Option<Vec<Element>>) {
+    let mut report = create_file_validation(path);
+    let elements = match std::fs::File::open(path)
+        .map_err(|e| e.to_string())
+        .and_then(|reader| {
+            serde_yaml::from_reader::<_, Vec<Element>>(reader).map_err(|e| e.to_string())
+        }) {
+        Ok(elements) => elements,
+        Err(e) => {
+            report.errors.push(e);
+            return (report, None);
+        }
+    };
+    let mut names: HashSet<String> = Default::default();
+    for element in elements.iter() {
+        report.entries_checked += 1;
+        for e in element.get_validation_errors() {
+            report.errors.push(f!("{}: {}", element.get_name(), e));
+        }
+        if !names.insert(element.get_name()) {
+            report
+                .errors
+                .push(f!("{}: defined more than once", element.get_name()));
+        }
+    }
+    for grade in 1..=4 {
+        if !names.contains(&f!("Any {}", grade)) {
+            report
+                .errors
+                .push(f!("no element of grade {} without an element type", grade));
+        }
+    }
+    if !report.is_valid() {
+        return (report, None);
+    }
+    return (report, Some(elements));
+}
+
+/// Returns the classes of the valid champions that can be built, keyed by champion name
+fn validate_champions(
+    path: &str,
+    hero_classes: Option<&HashMap<String, HeroClass>>,
+) -> (FileValidation, HashMap<String, HeroClass>) {
+    let mut report = create_file_validation(path);
+    let mut champion_classes: HashMap<String, HeroClass> = Default::default();
+    let champion_data = match read_yaml::<ChampionData>(path) {
+        Ok(champion_data) => champion_data,
+        Err(e) => {
+            report.errors.push(e);
+            return (report, champion_classes);
+        }
+    };
+    for (champion_key, data) in champion_data {
+        report.entries_checked += 1;
+        let errors = data.get_validation_errors();
+        for e in errors.iter() {
+            report.errors.push(f!("{}: {}", champion_key, e));
+        }
+        let champion = match Champion::from_str(&champion_key) {
+            Ok(champion) => champion,
+            Err(_) => {
+                report
+                    .errors
+                    .push(f!("{}: no champion with this name exists", champion_key));
+                continue;
+            }
+        };
+        if let Some(hero_class) = data.create_hero_class(champion) {
+            if hero_classes.is_some_and(|hero_classes| hero_classes.contains_key(&champion_key)) {
+                report.errors.push(f!(
+                    "{}: a class of the same name is in hero_classes.yaml",
+                    champion_key
+                ));
+            } else if errors.is_empty() {
+                champion_classes.insert(champion_key, hero_class);
+            }
+        }
+    }
+    return (report, champion_classes);
+}
+
+fn validate_bosses(path: &str) -> (FileValidation, HashMap<String, BossInput>) {
+    let mut report = create_file_validation(path);
+    let mut bosses: HashMap<String, BossInput> = Default::default();
+    let boss_inputs = match read_yaml::<BossInput>(path) {
+        Ok(boss_inputs) => boss_inputs,
+        Err(e) => {
+            report.errors.push(e);
+            return (report, bosses);
+        }
+    };
+    for (boss_key, boss_in) in boss_inputs {
+        report.entries_checked += 1;
+        match boss_in.validate() {
+            Ok(_) => {
+                bosses.insert(boss_key, boss_in);
+            }
+            Err(e) => report.errors.push(f!("{}: {}", boss_key, e)),
+        }
+    }
+    return (report, bosses);
+}
+
+/// Returns the report and the zones of the dungeons that loaded successfully
+fn validate_dungeons(
+    path: &str,
+    bosses: &HashMap<String, BossInput>,
+) -> (FileValidation, HashSet<String>) {
+    let mut report = create_file_validation(path);
+    let mut zones: HashSet<String> = Default::default();
+    let dungeon_inputs = match read_yaml::<DungeonInput>(path) {
+        Ok(dungeon_inputs) => dungeon_inputs,
+        Err(e) => {
+            report.errors.push(e);
+            return (report, zones);
+        }
+    };
+    for (dungeon_key, mut dungeon_in) in dungeon_inputs {
+        report.entries_checked += 1;
+        if let Err(e) = dungeon_in.apply_boss(bosses) {
+            report.errors.push(f!("{}: {}", dungeon_key, e));
+            continue;
+        }
+        match Dungeon::try_from(dungeon_in) {
+            Ok(_) => {
+                zones.insert(dungeon_key);
+            }
+            Err(e) => report.errors.push(f!("{}: {}", dungeon_key, e)),
+        }
+    }
+    return (report, zones);
+}
+
+/// Returns the report and the zones of the dungeons that loaded successfully, which can't repeat a zone of dungeons.yaml
+fn validate_dungeons_csv(
+    path: &str,
+    yaml_zones: &HashSet<String>,
+) -> (FileValidation, HashSet<String>) {
+    let mut report = create_file_validation(path);
+    let mut zones: HashSet<String> = Default::default();
+    let dungeon_inputs = match std::fs::File::open(path)
+        .map_err(|e| e.to_string())
+        .and_then(load_dungeon_inputs_from_csv)
+    {
+        Ok(dungeon_inputs) => dungeon_inputs,
+        Err(e) => {
+            report.errors.push(e);
+            return (report, zones);
+        }
+    };
+    for (zone, dungeon_in) in dungeon_inputs {
+        report.entries_checked += 1;
+        if yaml_zones.contains(&zone) {
+            report.errors.push(f!("{}: already in dungeons.yaml", zone));
+            continue;
+        }
+        match Dungeon::try_from(dungeon_in) {
+            Ok(_) => {
+                zones.insert(zone);
+            }
+            Err(e) => report.errors.push(f!("{}: {}", zone, e)),
+        }
+    }
+    return (report, zones);
+}
+
+/// Returns the report and the identifiers of the quests that loaded successfully
+fn validate_quests(
+    path: &str,
+    dungeon_zones: &HashSet<String>,
+) -> (FileValidation, HashSet<String>) {
+    let mut report = create_file_validation(path);
+    let mut identifiers: HashSet<String> = Default::default();
+    let quest_inputs = match read_yaml::<QuestInput>(path) {
+        Ok(quest_inputs) => quest_inputs,
+        Err(e) => {
+            report.errors.push(e);
+            return (report, identifiers);
+        }
+    };
+    for (quest_key, quest_in) in quest_inputs {
+        report.entries_checked += 1;
+        match create_quest_from_input(quest_key.to_string(), quest_in) {
+            Ok(quest) => {
+                if !dungeon_zones.contains(&quest.get_dungeon()) {
+                    report.errors.push(f!(
+                        "{}: dungeon {} is missing from or invalid in dungeons.yaml",
+                        quest_key,
+                        quest.get_dungeon()
+                    ));
+                } else {
+                    identifiers.insert(quest_key);
+                }
+            }
+            Err(e) => report.errors.push(f!("{}: {}", quest_key, e)),
+        }
+    }
+    return (report, identifiers);
+}
+
+fn validate_quest_lines(path: &str, quest_identifiers: &HashSet<String>) -> FileValidation {
+    let mut report = create_file_validation(path);
+    let quest_line_inputs = match read_yaml::<QuestLineInput>(path) {
+        Ok(quest_line_inputs) => quest_line_inputs,
+        Err(e) => {
+            report.errors.push(e);
+            return report;
+        }
+    };
+    for (quest_line_key, quest_line_in) in quest_line_inputs {
+        report.entries_checked += 1;
+        match create_quest_line_from_input(quest_line_key.to_string(), quest_line_in) {
+            Ok(quest_line) => {
+                for quest in quest_line.get_quests() {
+                    if !quest_identifiers.contains(&quest) {
+                        report.errors.push(f!(
+                            "{}: quest {} is missing from or invalid in quests.yaml",
+                            quest_line_key,
+                            quest
+                        ));
+                    }
+                }
+            }
+            Err(e) => report.errors.push(f!("{}: {}", quest_line_key, e)),
+        }
+    }
+    return report;
+}
+
+/// Validate a csv of either builder heroes or precomputed sim heroes, detected by its header
+fn validate_heroes_csv(
+    path: &str,
+    hero_builder_information: Option<&HeroBuilderInformation>,
+) -> FileValidation {
+    let mut report = create_file_validation(path);
+    let mut reader = match csv::Reader::from_path(path) {
+        Ok(reader) => reader,
+        Err(e) => {
+            report.errors.push(e.to_string());
+            return report;
+        }
+    };
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            report.errors.push(e.to_string());
+            return report;
+        }
+    };
+
+    let is_builder_csv = headers.iter().any(|h| h == "equipment_equipped_1");
+    if is_builder_csv && hero_builder_information.is_none() {
+        report.errors.push(String::from(
+            "builder heroes could not be checked because a data sheet, hero_classes.yaml, pet_skills.yaml, spirits.yaml, or elements.yaml failed to load",
+        ));
+        return report;
+    }
+
+    let mut identifiers: HashSet<String> = Default::default();
+    for (i, record) in reader.records().enumerate() {
+        // Row 1 is the header
+        let row = i + 2;
+        report.entries_checked += 1;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                report.errors.push(f!("row {}: {}", row, e));
+                continue;
+            }
+        };
+
+        let result: Result<String, String> = if is_builder_csv {
+            record
+                .deserialize::<HeroInput>(Some(&headers))
+                .map_err(|e| e.to_string())
+                .and_then(|hero_in| {
+                    let hbi = hero_builder_information.unwrap();
+                    let mut hero = Hero::from(hero_in);
+                    hero.validate_equipment(&hbi.bp_map, &hbi.hero_classes)
+                        .map_err(|e| e.to_string())?;
+                    hero.scale_by_class(&hbi.hero_classes)
+                        .map_err(|e| e.to_string())?;
+                    hbi.build_sim_hero(hero)
+                        .map(|sim_hero| sim_hero.get_identifier())
+                        .map_err(|e| e.to_string())
+                })
+        } else {
+            record
+                .deserialize::<SimHeroInput>(Some(&headers))
+                .map_err(|e| e.to_string())
+                .and_then(|hero_in| {
+                    hero_in
+                        .into_sim_hero(
+                            hero_builder_information
+                                .map_or(&Default::default(), |hbi| &hbi.hero_classes),
+                        )
+                        .map(|sim_hero| sim_hero.get_identifier())
+                        .map_err(String::from)
+                })
+        };
+
+        match result {
+            Ok(identifier) => {
+                if !identifiers.insert(identifier.to_string()) {
+                    report
+                        .errors
+                        .push(f!("row {}: duplicate identifier {}", row, identifier));
+                }
+            }
+            Err(e) => report.errors.push(f!("row {}: {}", row, e)),
+        }
+    }
+    return report;
+}
+
+/// Read a yaml map of inputs keyed by identifier, ordered so errors are reported consistently
+fn read_yaml<T: serde::de::DeserializeOwned>(path: &str) -> Result<BTreeMap<String, T>, String> {
+    let reader = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    return serde_yaml::from_reader::<std::fs::File, BTreeMap<String, T>>(reader)
+        .map_err(|e| e.to_string());
+}
+
+/// Format the reports as one status line per file followed by its errors, ending with a summary
+pub fn fmt_validation_report(reports: &[FileValidation]) -> String {
+    let mut res = String::new();
+    for report in reports {
+        if report.is_valid() {
+            res += &f!(
+                "OK   {} ({} checked)\n",
+                report.path,
+                report.entries_checked
+            );
+        } else {
+            res += &f!(
+                "FAIL {} ({} checked, {} errors)\n",
+                report.path,
+                report.entries_checked,
+                report.errors.len()
+            );
+            for error in report.errors.iter() {
+                res += &f!("       - {}\n", error);
+            }
+        }
+    }
+    let error_count: usize = reports.iter().map(|r| r.errors.len()).sum();
+    let failed_count = reports.iter().filter(|r| !r.is_valid()).count();
+    if error_count == 0 {
+        res += &f!("All {} files are valid\n", reports.len());
+    } else {
+        res += &f!(
+            "{} errors in {} of {} files\n",
+            error_count,
+            failed_count,
+            reports.len()
+        );
+    }
+    return res;
+}
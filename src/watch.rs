@@ -0,0 +1,128 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{
+    comparisons::{fmt_trial_result_comparison, run_hero_trial},
+    dungeons::TrialDungeon,
+    heroes::SimHero,
+    inputs::load_heroes_from_csv,
+    rng::RngConfig,
+    statistics::StatisticsConfig,
+    studies::{HeroBuilderInformation, StudyOutput},
+    trials::TrialResult,
+};
+
+/// How often the watched file is checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Load a single hero from a hero builder csv and run it through the stat pipeline, returning its build signature and SimHero
+/// Any problem with the file is returned as an error rather than panicking, since it may be mid-edit
+fn load_watched_hero(
+    path: &str,
+    identifier: &str,
+    hero_builder_information: &HeroBuilderInformation,
+) -> Result<(String, SimHero), String> {
+    let heroes = load_heroes_from_csv(
+        path.to_string(),
+        hero_builder_information.bp_map.clone(),
+        hero_builder_information.hero_classes.clone(),
+    )?;
+    let hero =
+        heroes
+            .get(identifier)
+            .cloned()
+            .ok_or(f!("No hero named {} in {}", identifier, path))?;
+    let build_signature = hero.get_build_signature();
+    let sim_hero = hero_builder_information
+        .build_sim_hero(hero)
+        .map_err(|e| f!("{} for hero {}", e, identifier))?;
+    return Ok((build_signature, sim_hero));
+}
+
+fn get_modified_time(path: &str) -> Option<SystemTime> {
+    return std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+}
+
+/// Evaluate a builder hero against a dungeon, printing the key metrics to the output
+/// When watching, the file is polled for changes and the hero is re-evaluated whenever its build changes, printing the change from the previous evaluation
+pub fn evaluate_hero(
+    path: &str,
+    identifier: &str,
+    party: Vec<SimHero>,
+    trial_dungeon: &TrialDungeon,
+    simulation_qty: usize,
+    rng: RngConfig,
+    statistics: StatisticsConfig,
+    hero_builder_information: &HeroBuilderInformation,
+    watch: bool,
+    output: StudyOutput,
+) {
+    let hero_index = party.len();
+    let mut previous: Option<(String, TrialResult)> = None;
+    let mut last_modified: Option<SystemTime> = None;
+
+    // Silence the default panic output, load errors are printed instead
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+
+    loop {
+        let modified = get_modified_time(path);
+        if previous.is_some() && modified == last_modified {
+            std::thread::sleep(POLL_INTERVAL);
+            continue;
+        }
+        last_modified = modified;
+
+        match load_watched_hero(path, identifier, hero_builder_information) {
+            Ok((signature, hero)) => {
+                if previous
+                    .as_ref()
+                    .is_some_and(|(previous_signature, _)| *previous_signature == signature)
+                {
+                    output.print(&f!("{} changed but {} did not\n\n", path, identifier));
+                } else {
+                    match run_hero_trial(
+                        hero,
+                        party.clone(),
+                        trial_dungeon,
+                        simulation_qty,
+                        rng,
+                        statistics,
+                        hero_builder_information,
+                    ) {
+                        Ok(result) => {
+                            output.print(&f!("{}\n", signature));
+                            output.print(&f!(
+                                "{}\n",
+                                fmt_trial_result_comparison(
+                                    "Previous",
+                                    "Current",
+                                    previous.as_ref().map(|(_, result)| result),
+                                    &result,
+                                    hero_index,
+                                )
+                            ));
+                            previous = Some((signature, result));
+                        }
+                        Err(e) => output.print(&f!("Could not evaluate {}: {}\n\n", identifier, e)),
+                    }
+                }
+            }
+            Err(e) => output.print(&f!("Could not load {}: {}\n\n", identifier, e)),
+        }
+
+        if !watch {
+            break;
+        }
+        if previous.is_none() {
+            // Wait for the file to be fixed rather than retrying a broken file continuously
+            std::thread::sleep(POLL_INTERVAL);
+            while get_modified_time(path) == last_modified {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+
+    std::panic::set_hook(default_hook);
+}